@@ -0,0 +1,69 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tiling::{Color, Model, Polygon, Rect, RenderOptions, Result, Shape};
+
+/// Returns the intro's 3.4.6.4 tiling repeated over a width by height canvas.
+fn model(width: i32, height: i32, scale: f64) -> Result<Model> {
+    let stroke = Color::new(242, 60, 60)?;
+    let fill_hexagon = Color::new(242, 194, 106)?;
+    let fill_square = Color::new(23, 216, 146)?;
+    let fill_triangle = Color::new(242, 209, 48)?;
+
+    let mut model = Model::new(width, height, scale);
+    model.add(Shape::new(6, fill_hexagon, stroke)?)?;
+    let squares = model.add_multi(0..1, 0..6, Shape::new(4, fill_square, stroke)?)?;
+    let _ = model.add_multi(squares.clone(), 1..2, Shape::new(3, fill_triangle, stroke)?)?;
+    let hexagons = model.add_multi(squares, 2..3, Shape::new(6, fill_hexagon, stroke)?)?;
+    model.repeat(hexagons)?;
+
+    Ok(model)
+}
+
+fn repeat(c: &mut Criterion) {
+    c.bench_function("repeat 2048x2048 at scale 8", |b| {
+        b.iter(|| model(black_box(2048), black_box(2048), 8.0).unwrap())
+    });
+}
+
+fn stats(c: &mut Criterion) {
+    let model = model(2048, 2048, 8.0).unwrap();
+    c.bench_function("stats 2048x2048 at scale 8", |b| {
+        b.iter(|| model.stats().unwrap())
+    });
+}
+
+fn dual(c: &mut Criterion) {
+    let model = model(2048, 2048, 8.0).unwrap();
+    c.bench_function("dual 2048x2048 at scale 8", |b| {
+        b.iter(|| model.freeze().unwrap())
+    });
+}
+
+fn points(c: &mut Criterion) {
+    let model = model(2048, 2048, 8.0).unwrap();
+    let shapes = model
+        .placements(Rect::new(-128.0, -128.0, 256.0, 256.0))
+        .map(|p| *p.shape())
+        .collect::<Vec<Shape>>();
+    c.bench_function("points 2048x2048 at scale 8", |b| {
+        b.iter(|| {
+            for shape in &shapes {
+                black_box(shape.points(black_box(0.1)).unwrap());
+            }
+        })
+    });
+}
+
+fn render(c: &mut Criterion) {
+    let model = model(1024, 1024, 16.0).unwrap();
+    let options = RenderOptions::new();
+    c.bench_function("render 1024x1024 at scale 16", |b| {
+        b.iter(|| model.render_with(&options).unwrap())
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = repeat, stats, dual, points, render
+}
+criterion_main!(benches);
@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use tiling::{Color, Model, Render, RenderOptions, Result, Shape};
+
+/// Returns a render of the intro's 3.4.6.4 tiling on a width by height canvas.
+fn render(width: i32, height: i32, scale: f64) -> Result<Render> {
+    let stroke = Color::new(242, 60, 60)?;
+    let fill_hexagon = Color::new(242, 194, 106)?;
+    let fill_square = Color::new(23, 216, 146)?;
+    let fill_triangle = Color::new(242, 209, 48)?;
+
+    let mut model = Model::new(width, height, scale);
+    model.add(Shape::new(6, fill_hexagon, stroke)?)?;
+    let squares = model.add_multi(0..1, 0..6, Shape::new(4, fill_square, stroke)?)?;
+    let _ = model.add_multi(squares.clone(), 1..2, Shape::new(3, fill_triangle, stroke)?)?;
+    let hexagons = model.add_multi(squares, 2..3, Shape::new(6, fill_hexagon, stroke)?)?;
+    model.repeat(hexagons)?;
+
+    model.render_with(&RenderOptions::new())
+}
+
+fn write(c: &mut Criterion) {
+    let render = render(4096, 4096, 32.0).unwrap();
+    let path = std::env::temp_dir().join("tiling-bench.png");
+    c.bench_function("write_to_png 4096x4096", |b| {
+        b.iter(|| render.write_to_png(&path).unwrap())
+    });
+    c.bench_function("write_to_png_fast 4096x4096", |b| {
+        b.iter(|| render.write_to_png_fast(&path).unwrap())
+    });
+    let _ = std::fs::remove_file(path);
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = write
+}
+criterion_main!(benches);
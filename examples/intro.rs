@@ -1,4 +1,4 @@
-use tiling::{Color, Model, Result, Shape};
+use tiling::{CairoBackend, Color, Model, RenderOptions, Result, Shape};
 
 pub fn main() -> Result<()> {
     let width = 1024;
@@ -10,14 +10,13 @@ pub fn main() -> Result<()> {
     let fill_triangle = Color::new(242, 209, 48)?;
     let background = Color::new(242, 242, 242)?;
     let margin = 0.1;
-    let show_labels = false;
     let line_width = 0.1;
 
     // create an empty model
     let mut model = Model::new(width, height, scale);
 
     // add a hexagon
-    model.add(Shape::new(6, fill_hexagon, stroke)?);
+    model.add(Shape::new(6, fill_hexagon, stroke)?)?;
 
     // attach a square to each side of the hexagon
     let squares = model.add_multi(0..1, 0..6, Shape::new(4, fill_square, stroke)?)?;
@@ -32,11 +31,15 @@ pub fn main() -> Result<()> {
     model.repeat(hexagons)?;
 
     // render the tiling
-    let render = model.render(background, margin, line_width, show_labels)?;
+    let options = RenderOptions::new()
+        .background(background)
+        .margin(margin)
+        .line_width(line_width);
+    let render = model.render(CairoBackend::new(&options))?;
     render.write_to_png("intro.png")?;
 
     // render the dual tiling
-    let render_dual = model.render_dual(background, fill_hexagon, stroke, margin, line_width)?;
+    let render_dual = model.render_dual(CairoBackend::new(&options), fill_hexagon, stroke)?;
     render_dual.write_to_png("intro-dual.png")?;
 
     Ok(())
@@ -0,0 +1,116 @@
+use std::{
+    io::{self, BufRead, Write},
+    ops::Range,
+};
+
+use tiling::{swatches, Color, Model, RenderOptions, Result, Shape};
+
+const WIDTH: i32 = 1024;
+const HEIGHT: i32 = 1024;
+const SCALE: f64 = 128.0;
+const MARGIN: f64 = 0.1;
+const LINE_WIDTH: f64 = 0.1;
+
+const HELP: &str = "commands:
+  add <sides>                      add a shape at the origin
+  attach <index> <edge> <sides>    attach a shape to one edge of one shape
+  multi <indexes> <edges> <sides>  attach a shape to a range of edges, e.g. 0..6
+  repeat <indexes>                 fill the surface with the pattern
+  render <path> [labels]           write the model to a PNG file
+  help                             print this message
+  quit                             exit";
+
+// Builds a model one command per line, e.g.
+//
+//   cargo run --example script
+//   > add 6
+//   > attach 0 0 4
+//   > render script.png labels
+pub fn main() -> Result<()> {
+    let stroke = Color::new(64, 64, 64)?;
+    let background = Color::new(242, 242, 242)?;
+    let mut model = Model::new(WIDTH, HEIGHT, SCALE);
+
+    println!("{}", HELP);
+    prompt();
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        let shape = |sides: &str| -> Option<Shape> {
+            let sides = sides.parse::<i32>().ok()?;
+            let fill = swatches::SET2.color(sides as usize);
+
+            Shape::new(sides, fill, stroke).ok()
+        };
+
+        let result = match words.as_slice() {
+            ["add", sides] => {
+                shape(sides).map(|s| model.add(s).map(|_| String::from("added shape")))
+            }
+            ["attach", index, edge, sides] => {
+                match (index.parse::<usize>(), edge.parse::<usize>(), shape(sides)) {
+                    (Ok(index), Ok(edge), Some(s)) => Some(
+                        model
+                            .attach(index, edge, s)
+                            .map(|i| format!("attached shape {}", i)),
+                    ),
+                    _ => None,
+                }
+            }
+            ["multi", indexes, edges, sides] => {
+                match (range(indexes), range(edges), shape(sides)) {
+                    (Some(indexes), Some(edges), Some(s)) => Some(
+                        model
+                            .add_multi(indexes, edges, s)
+                            .map(|r| format!("attached shapes {:?}", r)),
+                    ),
+                    _ => None,
+                }
+            }
+            ["repeat", indexes] => range(indexes).map(|indexes| {
+                model
+                    .repeat(indexes)
+                    .map(|_| String::from("repeated pattern"))
+            }),
+            ["render", path, rest @ ..] => {
+                let labels = rest == ["labels"];
+                Some(
+                    model
+                        .render_with(
+                            &RenderOptions::new()
+                                .background(background)
+                                .margin(MARGIN)
+                                .line_width(LINE_WIDTH)
+                                .labels(labels),
+                        )
+                        .and_then(|r| r.write_to_png(path))
+                        .map(|_| format!("wrote {}", path)),
+                )
+            }
+            ["help"] => Some(Ok(String::from(HELP))),
+            ["quit"] => break,
+            [] => Some(Ok(String::new())),
+            _ => None,
+        };
+
+        match result {
+            Some(Ok(message)) => println!("{}", message),
+            Some(Err(err)) => println!("error: {}", err),
+            None => println!("unrecognized command; type help for usage"),
+        }
+        prompt();
+    }
+
+    Ok(())
+}
+
+fn prompt() {
+    print!("> ");
+    let _ = io::stdout().flush();
+}
+
+fn range(s: &str) -> Option<Range<usize>> {
+    let (start, end) = s.split_once("..")?;
+
+    Some(start.parse().ok()?..end.parse().ok()?)
+}
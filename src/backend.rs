@@ -0,0 +1,271 @@
+use std::fmt::Write;
+
+use crate::{svg, Color, LineCap, LineJoin, Point, RenderOptions, Result};
+
+#[cfg(feature = "cairo")]
+use crate::{render, shape::trace, Error, Error::*, Render};
+
+/// A surface that a model can be drawn on by `Model::render`, configured by
+/// the `RenderOptions` it owns.
+/// A backend only has to fill, stroke, and frame polygons given in pixels, so models
+/// can be rendered by libraries other than cairo, such as plotters or raqote,
+/// by implementing this trait. What only cairo can draw (tiles, labels,
+/// motifs, shape hooks, image and texture fills, opacity, and blend modes)
+/// is drawn on a `CairoBackend`, and other backends refuse to render a model
+/// that needs it rather than leave it out.
+pub trait RenderBackend {
+    /// The result of rendering, such as an image or a document.
+    type Output;
+
+    /// Returns the options the backend renders with.
+    fn options(&self) -> &RenderOptions;
+
+    /// Returns the backend as a `CairoBackend`, if it is one, so models are
+    /// drawn on it in full.
+    #[cfg(feature = "cairo")]
+    fn as_cairo(&mut self) -> Option<&mut CairoBackend> {
+        None
+    }
+
+    /// Begins a render width by height pixels, painted with background if
+    /// any, whose origin is its top left corner and whose y axis points down.
+    fn begin(&mut self, width: i32, height: i32, background: Option<Color>) -> Result<()>;
+
+    /// Fills the polygon through points (in pixels), which ends where it
+    /// starts, with color.
+    fn fill(&mut self, points: &[Point], color: Color) -> Result<()>;
+
+    /// Strokes the line through points (in pixels) with color, width pixels
+    /// wide, in alternating dashes and gaps of the lengths (in pixels) in
+    /// dash, or solid if dash is empty.
+    fn stroke(&mut self, points: &[Point], color: Color, width: f64, dash: &[f64]) -> Result<()>;
+
+    /// Frames the polygons through the points (in pixels) of each of rings,
+    /// which do not overlap, with a band of color width pixels wide around
+    /// their outsides, leaving their insides uncovered.
+    fn border(&mut self, rings: &[Vec<Point>], color: Color, width: f64) -> Result<()>;
+
+    /// Finishes the render and returns it.
+    fn finish(self) -> Result<Self::Output>;
+}
+
+/// A backend that renders with cairo, as `Model::render_with` does, honoring
+/// the line caps and joins, antialiasing, tolerance, and supersampling of its
+/// options.
+#[cfg(feature = "cairo")]
+pub struct CairoBackend {
+    options: RenderOptions,
+    canvas: Option<(cairo::ImageSurface, cairo::Context)>,
+    render: Option<Render>,
+}
+
+#[cfg(feature = "cairo")]
+impl CairoBackend {
+    /// Returns a backend that renders as configured by options.
+    pub fn new(options: &RenderOptions) -> CairoBackend {
+        CairoBackend {
+            options: options.clone(),
+            canvas: None,
+            render: None,
+        }
+    }
+
+    /// Sets the render that `finish` returns, made by drawing a model in full
+    /// rather than polygon by polygon.
+    pub(crate) fn rendered(&mut self, render: Render) {
+        self.render = Some(render);
+    }
+
+    /// Returns the context to draw on, or an error if the render has not
+    /// begun.
+    fn context(&self) -> Result<&cairo::Context> {
+        self.canvas
+            .as_ref()
+            .map(|(_, context)| context)
+            .ok_or_else(not_begun)
+    }
+}
+
+#[cfg(feature = "cairo")]
+impl RenderBackend for CairoBackend {
+    type Output = Render;
+
+    fn options(&self) -> &RenderOptions {
+        &self.options
+    }
+
+    fn as_cairo(&mut self) -> Option<&mut CairoBackend> {
+        Some(self)
+    }
+
+    fn begin(&mut self, width: i32, height: i32, background: Option<Color>) -> Result<()> {
+        let options = self.options.clone().background(background);
+        let (surface, context) = render::canvas(width, height, 1.0, &options)?;
+        // The canvas is centered on the origin, and points are given from its
+        // top left corner.
+        context.translate(-width as f64 / 2.0, -height as f64 / 2.0);
+        self.canvas = Some((surface, context));
+
+        Ok(())
+    }
+
+    fn fill(&mut self, points: &[Point], color: Color) -> Result<()> {
+        let context = self.context()?;
+        let (r, g, b) = color.rgb_unit_int();
+        trace(context, points);
+        context.set_source_rgb(r, g, b);
+        context.fill()?;
+
+        Ok(())
+    }
+
+    fn stroke(&mut self, points: &[Point], color: Color, width: f64, dash: &[f64]) -> Result<()> {
+        let context = self.context()?;
+        let (r, g, b) = color.rgb_unit_int();
+        trace(context, points);
+        context.set_source_rgb(r, g, b);
+        context.set_line_width(width);
+        context.set_dash(dash, 0.0);
+        context.stroke()?;
+
+        Ok(())
+    }
+
+    fn border(&mut self, rings: &[Vec<Point>], color: Color, width: f64) -> Result<()> {
+        render::border(self.context()?, rings, color, width)
+    }
+
+    fn finish(self) -> Result<Render> {
+        if let Some(render) = self.render {
+            return Ok(render);
+        }
+        let (surface, context) = self.canvas.ok_or_else(not_begun)?;
+        drop(context);
+
+        render::finish(surface, &self.options)
+    }
+}
+
+/// Returns the error of drawing before a render has begun.
+#[cfg(feature = "cairo")]
+fn not_begun() -> Error {
+    Backend {
+        reason: String::from("render has not begun"),
+    }
+}
+
+/// A backend that writes an SVG document, without cairo.
+/// Unlike `Model::to_svg`, every polygon is written with its own colors and
+/// every styled edge as a line of its own, so the document looks like the
+/// render but is not structured for editing.
+/// It honors the line caps and joins and the miter limit of its options.
+#[derive(Default)]
+pub struct SvgBackend {
+    options: RenderOptions,
+    svg: String,
+}
+
+impl SvgBackend {
+    /// Returns a backend that writes a document as configured by options.
+    pub fn new(options: &RenderOptions) -> SvgBackend {
+        SvgBackend {
+            options: options.clone(),
+            svg: String::new(),
+        }
+    }
+}
+
+/// Returns points as the value of an SVG points attribute.
+fn coordinates(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+impl RenderBackend for SvgBackend {
+    type Output = String;
+
+    fn options(&self) -> &RenderOptions {
+        &self.options
+    }
+
+    fn begin(&mut self, width: i32, height: i32, background: Option<Color>) -> Result<()> {
+        // Writing to a String cannot fail.
+        self.svg.clear();
+        let _ = writeln!(self.svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+        let _ = writeln!(
+            self.svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height,
+        );
+        if let Some(background) = background {
+            let _ = writeln!(
+                self.svg,
+                r#"<rect width="100%" height="100%" fill="{}"/>"#,
+                background.to_hex()
+            );
+        }
+
+        Ok(())
+    }
+
+    fn fill(&mut self, points: &[Point], color: Color) -> Result<()> {
+        let _ = writeln!(
+            self.svg,
+            r#"<polygon points="{}" fill="{}"/>"#,
+            coordinates(points),
+            color.to_hex()
+        );
+
+        Ok(())
+    }
+
+    fn stroke(&mut self, points: &[Point], color: Color, width: f64, dash: &[f64]) -> Result<()> {
+        let cap = match self.options.line_cap {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        };
+        let join = match self.options.line_join {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        };
+        let _ = write!(
+            self.svg,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="{}" stroke-linejoin="{}" stroke-miterlimit="{}""#,
+            coordinates(points),
+            color.to_hex(),
+            width,
+            cap,
+            join,
+            self.options.miter_limit
+        );
+        if !dash.is_empty() {
+            let dash = dash
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+                .join(" ");
+            let _ = write!(self.svg, r#" stroke-dasharray="{}""#, dash);
+        }
+        let _ = writeln!(self.svg, "/>");
+
+        Ok(())
+    }
+
+    fn border(&mut self, rings: &[Vec<Point>], color: Color, width: f64) -> Result<()> {
+        svg::border(&mut self.svg, rings, color, width);
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<String> {
+        let _ = writeln!(self.svg, "</svg>");
+
+        Ok(self.svg)
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{Model, RenderOptions, Result};
+
+/// Renders each model of jobs as configured by its options and writes it to
+/// a PNG file at its path, spreading the jobs over a thread per core, so a
+/// catalog of tilings is written several times faster than one at a time.
+/// progress is called with the number of files written so far and the
+/// number of jobs after each file is written; calls are never concurrent
+/// and the count only grows.
+/// The first job (in the order of jobs) to fail is returned as the error,
+/// and no job after it is started once it has failed.
+pub fn batch<P, F>(jobs: &[(Model, RenderOptions, P)], progress: F) -> Result<()>
+where
+    P: AsRef<Path> + Sync,
+    F: FnMut(usize, usize) + Send,
+{
+    let threads = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(jobs.len());
+    let next = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(usize::MAX);
+    let progress = Mutex::new((0, progress));
+    let errors = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= jobs.len() || failed.load(Ordering::SeqCst) < i {
+                    return;
+                }
+
+                let (model, options, path) = &jobs[i];
+                match model
+                    .render_with(options)
+                    .and_then(|r| r.write_to_png(path))
+                {
+                    Ok(()) => {
+                        let mut progress = progress.lock().unwrap_or_else(|e| e.into_inner());
+                        progress.0 += 1;
+                        let done = progress.0;
+                        (progress.1)(done, jobs.len());
+                    }
+                    Err(err) => {
+                        failed.fetch_min(i, Ordering::SeqCst);
+                        errors
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .push((i, err));
+                    }
+                }
+            });
+        }
+    });
+
+    let errors = errors.into_inner().unwrap_or_else(|e| e.into_inner());
+    match errors.into_iter().min_by_key(|(i, _)| *i) {
+        Some((_, err)) => Err(err),
+        None => Ok(()),
+    }
+}
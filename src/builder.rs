@@ -0,0 +1,180 @@
+use std::ops::Range;
+
+use crate::{Color, Error, Error::*, Model, Palette, Result, Shape};
+
+/// Builds a model step by step, keeping track of the index ranges that
+/// `Model::add_multi` returns.
+///
+/// Each step attaches shapes to the current layer of shapes. The layer starts
+/// as the base shape and becomes the added shapes after each `attach_all`, so
+/// the intro tiling is
+///
+/// ```rust
+/// # use tiling::ModelBuilder;
+/// let model = ModelBuilder::new(1024, 1024, 128.0)
+///     .base(6)
+///     .attach_all(4)
+///     .attach(1, 3)
+///     .seed_outer(6)
+///     .repeat()?;
+/// # Ok::<(), tiling::Error>(())
+/// ```
+///
+/// The first step to fail is returned by `build` or `repeat`, and every later
+/// step is skipped.
+pub struct ModelBuilder {
+    model: Model,
+    palette: Palette,
+    stroke: Color,
+    layer: Range<usize>,
+    seeds: Option<Range<usize>>,
+    error: Option<Error>,
+}
+
+impl ModelBuilder {
+    /// Returns a builder of a width by height model with scale pixels per unit.
+    /// Shapes are filled from `swatches::SET2` by their number of sides and
+    /// stroked black.
+    pub fn new(width: i32, height: i32, scale: f64) -> ModelBuilder {
+        ModelBuilder::from_model(Model::new(width, height, scale))
+    }
+
+    /// Returns a builder that adds to model, which may be configured with the
+    /// model's `with_*` methods first.
+    pub fn from_model(model: Model) -> ModelBuilder {
+        ModelBuilder {
+            model,
+            palette: crate::swatches::SET2,
+            stroke: Color::rgb(0, 0, 0),
+            layer: 0..0,
+            seeds: None,
+            error: None,
+        }
+    }
+
+    /// Returns the builder with shapes filled from palette.
+    pub fn palette(mut self, palette: Palette) -> ModelBuilder {
+        self.palette = palette;
+
+        self
+    }
+
+    /// Returns the builder with shapes stroked with stroke.
+    pub fn stroke(mut self, stroke: Color) -> ModelBuilder {
+        self.stroke = stroke;
+
+        self
+    }
+
+    /// Adds a sides-sided shape at the origin and makes it the layer.
+    pub fn base(self, sides: i32) -> ModelBuilder {
+        self.step(|b| {
+            let start = b.model.len();
+            b.model.add(b.shape(sides)?)?;
+            b.layer = start..b.model.len();
+
+            Ok(())
+        })
+    }
+
+    /// Attaches a sides-sided shape to every edge of each shape in the layer
+    /// and makes the added shapes the layer.
+    pub fn attach_all(self, sides: i32) -> ModelBuilder {
+        self.step(|b| {
+            let start = b.model.len();
+            for i in b.layer.clone() {
+                let edges = b.layer_shape(i)?.sides() as usize;
+                b.model.add_multi(i..i + 1, 0..edges, b.shape(sides)?)?;
+            }
+            b.layer = start..b.model.len();
+
+            Ok(())
+        })
+    }
+
+    /// Attaches a sides-sided shape to the edge with index edge of each shape
+    /// in the layer, leaving the layer unchanged.
+    pub fn attach(self, edge: usize, sides: i32) -> ModelBuilder {
+        self.step(|b| {
+            b.model
+                .add_multi(b.layer.clone(), edge..edge + 1, b.shape(sides)?)?;
+
+            Ok(())
+        })
+    }
+
+    /// Attaches a sides-sided shape to the outer edge of each shape in the
+    /// layer (the edge opposite the edge the shape was attached by) and uses
+    /// the added shapes as the seeds that `repeat` translates the pattern by.
+    /// Shapes in the layer must have an even number of sides.
+    pub fn seed_outer(self, sides: i32) -> ModelBuilder {
+        self.step(|b| {
+            let start = b.model.len();
+            for i in b.layer.clone() {
+                let edges = b.layer_shape(i)?.sides();
+                if edges % 2 != 0 {
+                    return Err(InvalidShape {
+                        reason: format!("{}-sided shape {} has no outer edge", edges, i),
+                    });
+                }
+
+                let outer = edges as usize / 2;
+                b.model
+                    .attach(i, outer, b.shape(sides)?)
+                    .map_err(|err| err.context("seed_outer", Some(i), Some(outer)))?;
+            }
+            b.seeds = Some(start..b.model.len());
+
+            Ok(())
+        })
+    }
+
+    /// Returns the built model without repeating it.
+    pub fn build(self) -> Result<Model> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.model),
+        }
+    }
+
+    /// Returns the built model with its pattern repeated over the surface,
+    /// translated by the seeds of `seed_outer`.
+    pub fn repeat(self) -> Result<Model> {
+        let seeds = self.seeds.clone();
+        let mut model = self.build()?;
+        let seeds = seeds.ok_or(InvalidShape {
+            reason: String::from("no seeds to repeat; call seed_outer first"),
+        })?;
+        model.repeat(seeds)?;
+
+        Ok(model)
+    }
+
+    /// Runs f unless an earlier step failed, recording its error.
+    fn step<F>(mut self, f: F) -> ModelBuilder
+    where
+        F: FnOnce(&mut ModelBuilder) -> Result<()>,
+    {
+        if self.error.is_none() {
+            if let Err(err) = f(&mut self) {
+                self.error = Some(err);
+            }
+        }
+
+        self
+    }
+
+    /// Returns a sides-sided shape in the builder's colors.
+    fn shape(&self, sides: i32) -> Result<Shape> {
+        Shape::new(sides, self.palette.color(sides as usize), self.stroke)
+    }
+
+    /// Returns the model's shape with index index.
+    fn layer_shape(&self, index: usize) -> Result<Shape> {
+        self.model.shape(index).ok_or(OutOfBounds {
+            index,
+            length: self.model.len(),
+            name: String::from("model shapes"),
+        })
+    }
+}
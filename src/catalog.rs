@@ -0,0 +1,460 @@
+//! Named tilings ready to render.
+//!
+//! The catalog holds tilings by regular polygons, named by their vertex
+//! configurations (see `names`), each defined as a `Pattern` that is placed
+//! at the origin and repeated.
+//!
+//! It also holds the uniform colorings of the regular tilings (see
+//! `colorings`): the colorings in which every vertex is surrounded by the same
+//! colors, in the same order, and the colored tiling looks the same from
+//! every vertex. Each is named by its tiling and by the colors of the tiles
+//! around a vertex (numbered from 1 in the order they first appear), the
+//! names used by Grünbaum and Shephard; a roman numeral tells apart colorings
+//! with the same colors around their vertices.
+
+use std::{f64::consts::PI, ops::Range};
+
+use crate::{
+    lattice::{self, HexGrid},
+    Color,
+    Error::*,
+    Model, Pattern, Point, Result, Shape,
+};
+
+#[cfg(feature = "cairo")]
+use crate::{Render, RenderOptions};
+
+/// A shape with sides sides and the fill with index fill, attached to every
+/// edge in edges of each shape in indexes as `Pattern::add_multi` attaches
+/// it.
+struct Step {
+    indexes: Range<usize>,
+    edges: Range<usize>,
+    sides: i32,
+    fill: usize,
+}
+
+/// A tiling built out from a shape with sides sides and the fill with index
+/// fill, which repeats along the centers of the shapes its last step
+/// attaches.
+struct Tiling {
+    name: &'static str,
+    sides: i32,
+    fill: usize,
+    steps: &'static [Step],
+}
+
+/// The tilings of the catalog, by name.
+const TILINGS: [Tiling; 5] = [
+    Tiling {
+        name: "3.3.3.3.3.3",
+        sides: 3,
+        fill: 0,
+        steps: &[
+            Step {
+                indexes: 0..1,
+                edges: 0..3,
+                sides: 3,
+                fill: 1,
+            },
+            Step {
+                indexes: 1..4,
+                edges: 1..3,
+                sides: 3,
+                fill: 0,
+            },
+        ],
+    },
+    Tiling {
+        name: "3.3.3.3.6",
+        sides: 6,
+        fill: 0,
+        steps: &[
+            Step {
+                indexes: 0..1,
+                edges: 0..6,
+                sides: 3,
+                fill: 1,
+            },
+            Step {
+                indexes: 1..7,
+                edges: 1..2,
+                sides: 3,
+                fill: 1,
+            },
+            Step {
+                indexes: 1..7,
+                edges: 2..3,
+                sides: 3,
+                fill: 1,
+            },
+            Step {
+                indexes: 13..19,
+                edges: 1..2,
+                sides: 6,
+                fill: 0,
+            },
+        ],
+    },
+    Tiling {
+        name: "3.3.4.3.4",
+        sides: 4,
+        fill: 0,
+        steps: &[
+            Step {
+                indexes: 0..1,
+                edges: 0..4,
+                sides: 3,
+                fill: 1,
+            },
+            Step {
+                indexes: 1..5,
+                edges: 1..2,
+                sides: 4,
+                fill: 0,
+            },
+            Step {
+                indexes: 5..9,
+                edges: 2..4,
+                sides: 3,
+                fill: 1,
+            },
+            Step {
+                indexes: 9..17,
+                edges: 2..3,
+                sides: 4,
+                fill: 0,
+            },
+        ],
+    },
+    Tiling {
+        name: "3.4.6.4",
+        sides: 6,
+        fill: 0,
+        steps: &[
+            Step {
+                indexes: 0..1,
+                edges: 0..6,
+                sides: 4,
+                fill: 1,
+            },
+            Step {
+                indexes: 1..7,
+                edges: 1..2,
+                sides: 3,
+                fill: 2,
+            },
+            Step {
+                indexes: 1..7,
+                edges: 2..3,
+                sides: 6,
+                fill: 0,
+            },
+        ],
+    },
+    Tiling {
+        name: "3.6.3.6",
+        sides: 6,
+        fill: 0,
+        steps: &[
+            Step {
+                indexes: 0..1,
+                edges: 0..6,
+                sides: 3,
+                fill: 1,
+            },
+            Step {
+                indexes: 1..7,
+                edges: 1..2,
+                sides: 6,
+                fill: 0,
+            },
+        ],
+    },
+];
+
+impl Tiling {
+    /// Returns the number of fills the tiling uses.
+    fn fills(&self) -> usize {
+        self.steps
+            .iter()
+            .map(|s| s.fill)
+            .fold(self.fill, usize::max)
+            + 1
+    }
+}
+
+/// A uniform coloring of a regular tiling.
+struct Coloring {
+    name: &'static str,
+    sides: i32,
+    period: (i32, i32),
+    color: fn(i32, i32) -> usize,
+}
+
+/// The uniform colorings of the hexagonal and square tilings.
+/// Cells are addressed as by `HexGrid` and `SquareGrid`, and each coloring
+/// repeats over a block of period cells.
+const COLORINGS: [Coloring; 12] = [
+    Coloring {
+        name: "hexagonal-111",
+        sides: 6,
+        period: (1, 1),
+        color: |_, _| 0,
+    },
+    Coloring {
+        name: "hexagonal-112",
+        sides: 6,
+        period: (3, 3),
+        color: |q, r| ((q - r).rem_euclid(3) == 0) as usize,
+    },
+    Coloring {
+        name: "hexagonal-123",
+        sides: 6,
+        period: (3, 3),
+        color: |q, r| (q - r).rem_euclid(3) as usize,
+    },
+    Coloring {
+        name: "square-1111",
+        sides: 4,
+        period: (1, 1),
+        color: |_, _| 0,
+    },
+    Coloring {
+        name: "square-1112-i",
+        sides: 4,
+        period: (2, 2),
+        color: |c, r| (c % 2 == 0 && r % 2 == 0) as usize,
+    },
+    Coloring {
+        name: "square-1112-ii",
+        sides: 4,
+        period: (2, 4),
+        color: |c, r| (r % 2 == 0 && (c + r / 2) % 2 == 0) as usize,
+    },
+    Coloring {
+        name: "square-1122",
+        sides: 4,
+        period: (1, 2),
+        color: |_, r| (r % 2) as usize,
+    },
+    Coloring {
+        name: "square-1123-i",
+        sides: 4,
+        period: (2, 2),
+        color: |c, r| match r % 2 {
+            0 => 0,
+            _ => 1 + (c % 2) as usize,
+        },
+    },
+    Coloring {
+        name: "square-1123-ii",
+        sides: 4,
+        period: (2, 4),
+        color: |c, r| match r % 2 {
+            0 => 0,
+            _ => 1 + ((c + r / 2) % 2) as usize,
+        },
+    },
+    Coloring {
+        name: "square-1212",
+        sides: 4,
+        period: (2, 2),
+        color: |c, r| ((c + r) % 2) as usize,
+    },
+    Coloring {
+        name: "square-1213",
+        sides: 4,
+        period: (2, 2),
+        color: |c, r| match (c + r) % 2 {
+            0 => 0,
+            _ => 1 + (r % 2) as usize,
+        },
+    },
+    Coloring {
+        name: "square-1234",
+        sides: 4,
+        period: (2, 2),
+        color: |c, r| (c % 2 + 2 * (r % 2)) as usize,
+    },
+];
+
+impl Coloring {
+    /// Returns the number of colors the coloring uses.
+    fn colors(&self) -> usize {
+        self.cells().map(|(_, color)| color + 1).max().unwrap_or(1)
+    }
+
+    /// Returns the coordinates and color of each cell of the coloring's
+    /// period.
+    fn cells(&self) -> impl Iterator<Item = ((i32, i32), usize)> + '_ {
+        let (columns, rows) = self.period;
+
+        (0..rows).flat_map(move |r| (0..columns).map(move |c| ((c, r), (self.color)(c, r))))
+    }
+
+    /// Returns the center of the cell with coordinates (c, r).
+    fn center(&self, c: i32, r: i32) -> Point {
+        match self.sides {
+            6 => HexGrid::center(c, r),
+            _ => Point {
+                x: c as f64,
+                y: r as f64,
+            },
+        }
+    }
+}
+
+/// Returns the names of the tilings in the catalog.
+pub fn names() -> Vec<&'static str> {
+    TILINGS.iter().map(|t| t.name).collect()
+}
+
+/// Returns the number of fills the tiling with the name uses.
+pub fn fills(name: &str) -> Result<usize> {
+    Ok(find_tiling(name)?.fills())
+}
+
+/// Returns the pattern of the tiling with the name, whose shapes are filled
+/// with fills and stroked with stroke, and the range of the pattern's shapes
+/// whose centers it repeats along (see `Model::repeat`) once it is placed
+/// with `Model::place`.
+/// Fails if the catalog has no tiling with the name or fills has fewer
+/// colors than it uses.
+pub fn pattern(name: &str, fills: &[Color], stroke: Color) -> Result<(Pattern, Range<usize>)> {
+    let tiling = find_tiling(name)?;
+    check_colors(name, tiling.fills(), fills)?;
+
+    let mut pattern = Pattern::new(Shape::new(tiling.sides, fills[tiling.fill], stroke)?);
+    let mut seeds = 0..1;
+    for step in tiling.steps {
+        let start = pattern.len();
+        pattern = pattern.add_multi(
+            step.indexes.clone(),
+            step.edges.clone(),
+            Shape::new(step.sides, fills[step.fill], stroke)?,
+        )?;
+        seeds = start..pattern.len();
+    }
+
+    Ok((pattern, seeds))
+}
+
+/// Returns a model of a width by height canvas with scale pixels per edge,
+/// tiled with the tiling with the name: its pattern (see `pattern`), placed
+/// at the origin with `Model::place` and repeated.
+/// Fails if the catalog has no tiling with the name or fills has fewer
+/// colors than it uses.
+pub fn model(
+    name: &str,
+    width: i32,
+    height: i32,
+    scale: f64,
+    fills: &[Color],
+    stroke: Color,
+) -> Result<Model> {
+    let (pattern, seeds) = pattern(name, fills, stroke)?;
+
+    let mut model = Model::new(width, height, scale);
+    let placed = model.place(&pattern)?;
+    model.repeat(placed.start + seeds.start..placed.start + seeds.end)?;
+
+    Ok(model)
+}
+
+/// Renders the tiling with the name (see `model`) as configured by options.
+#[cfg(feature = "cairo")]
+pub fn render(
+    name: &str,
+    width: i32,
+    height: i32,
+    scale: f64,
+    fills: &[Color],
+    stroke: Color,
+    options: &RenderOptions,
+) -> Result<Render> {
+    model(name, width, height, scale, fills, stroke)?.render_with(options)
+}
+
+/// Returns the names of the uniform colorings in the catalog.
+pub fn colorings() -> Vec<&'static str> {
+    COLORINGS.iter().map(|c| c.name).collect()
+}
+
+/// Returns the number of colors the uniform coloring with the name uses.
+pub fn coloring_colors(name: &str) -> Result<usize> {
+    Ok(find_coloring(name)?.colors())
+}
+
+/// Returns a model of a width by height canvas with scale pixels per edge,
+/// tiled with the uniform coloring with the name, whose tiles are filled with
+/// colors in the order of the name's digits and stroked with stroke.
+/// The coloring's period is colored before it is repeated, so each copy (and
+/// each shape placed by repeating the model again) keeps its colors.
+/// Fails if the catalog has no coloring with the name or colors has fewer
+/// colors than it uses.
+pub fn coloring(
+    name: &str,
+    width: i32,
+    height: i32,
+    scale: f64,
+    colors: &[Color],
+    stroke: Color,
+) -> Result<Model> {
+    let coloring = find_coloring(name)?;
+    check_colors(name, coloring.colors(), colors)?;
+
+    let rotation = match coloring.sides {
+        4 => PI / 4.0,
+        _ => 0.0,
+    };
+    let shapes = coloring
+        .cells()
+        .map(|((c, r), color)| {
+            Ok(Shape::new(coloring.sides, colors[color], stroke)?
+                .rotated(rotation, Point::origin())
+                .translated(coloring.center(c, r)))
+        })
+        .collect::<Result<Vec<Shape>>>()?;
+    let (columns, rows) = coloring.period;
+
+    lattice::fill_model(
+        width,
+        height,
+        scale,
+        &shapes,
+        &[coloring.center(columns, 0), coloring.center(0, rows)],
+    )
+}
+
+/// Returns the tiling with the name.
+fn find_tiling(name: &str) -> Result<&'static Tiling> {
+    TILINGS
+        .iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| UnknownCatalogEntry {
+            name: String::from(name),
+        })
+}
+
+/// Returns the uniform coloring with the name.
+fn find_coloring(name: &str) -> Result<&'static Coloring> {
+    COLORINGS
+        .iter()
+        .find(|c| c.name == name)
+        .ok_or_else(|| UnknownCatalogEntry {
+            name: String::from(name),
+        })
+}
+
+/// Returns an error if colors has fewer than the needed colors of the
+/// catalog entry with the name.
+fn check_colors(name: &str, needed: usize, colors: &[Color]) -> Result<()> {
+    match colors.len() < needed {
+        true => Err(InfeasibleColoring {
+            colors: colors.len(),
+            reason: format!("{} uses {} colors", name, needed),
+        }),
+        false => Ok(()),
+    }
+}
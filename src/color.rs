@@ -1,4 +1,4 @@
-use std::ops::RangeInclusive;
+use std::{convert::TryFrom, ops::RangeInclusive};
 
 use crate::{Error::*, Result};
 
@@ -6,7 +6,7 @@ use crate::{Error::*, Result};
 const RGB_RANGE: RangeInclusive<i32> = 0..=255;
 
 /// A color with red, green, and blue components.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Color {
     red: i32,
     green: i32,
@@ -14,6 +14,26 @@ pub struct Color {
 }
 
 impl Color {
+    /// Black.
+    pub const BLACK: Color = Color {
+        red: 0,
+        green: 0,
+        blue: 0,
+    };
+
+    /// White.
+    pub const WHITE: Color = Color {
+        red: 255,
+        green: 255,
+        blue: 255,
+    };
+
+    /// Returns a new color from components that are known to be in the range
+    /// [0, 255].
+    pub(crate) const fn rgb(red: i32, green: i32, blue: i32) -> Color {
+        Color { red, green, blue }
+    }
+
     /// Returns a new color, validating each component is in the range [0, 255].
     pub fn new(red: i32, green: i32, blue: i32) -> Result<Color> {
         if !(RGB_RANGE.contains(&red) && RGB_RANGE.contains(&green) && RGB_RANGE.contains(&blue)) {
@@ -23,6 +43,44 @@ impl Color {
         Ok(Color { red, green, blue })
     }
 
+    /// Returns a new color from 8-bit components, which are always in range.
+    pub const fn from_rgb8(red: u8, green: u8, blue: u8) -> Color {
+        Color::rgb(red as i32, green as i32, blue as i32)
+    }
+
+    /// Returns a new color from a packed 0xRRGGBB value, ensuring the top
+    /// byte is zero.
+    pub fn from_u32(rgb: u32) -> Result<Color> {
+        if rgb > 0xff_ff_ff {
+            return Err(InvalidColor);
+        }
+
+        Ok(Color::from_rgb8(
+            (rgb >> 16) as u8,
+            (rgb >> 8) as u8,
+            rgb as u8,
+        ))
+    }
+
+    /// Returns the color written as a hex triplet, e.g. "#ff8000", as
+    /// returned by `to_hex`.
+    pub fn from_hex(hex: &str) -> Result<Color> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        // `from_str_radix` also takes a leading sign, as in "+f".
+        if digits.len() != 6 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(InvalidColor);
+        }
+
+        let component =
+            |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| InvalidColor);
+
+        Ok(Color::from_rgb8(
+            component(0)?,
+            component(2)?,
+            component(4)?,
+        ))
+    }
+
     /// Returns the red component.
     pub fn red(&self) -> i32 {
         self.red
@@ -38,6 +96,16 @@ impl Color {
         self.blue
     }
 
+    /// Returns the red, green, and blue components as bytes.
+    pub fn to_rgb8(&self) -> [u8; 3] {
+        [self.red as u8, self.green as u8, self.blue as u8]
+    }
+
+    /// Returns the color packed as a 0xRRGGBB value.
+    pub fn to_u32(&self) -> u32 {
+        (self.red as u32) << 16 | (self.green as u32) << 8 | self.blue as u32
+    }
+
     /// Returns the red, green, and blue comonents as a tuple where each component
     /// has been translated into the unit interval (0 to 1 inclusive).
     pub fn rgb_unit_int(&self) -> (f64, f64, f64) {
@@ -51,4 +119,211 @@ impl Color {
             unit_int(self.blue),
         )
     }
+
+    /// Returns the color's relative luminance, from 0 for black to 1 for
+    /// white, as defined by WCAG: the weighted sum of its linear red, green,
+    /// and blue components.
+    pub fn luminance(&self) -> f64 {
+        let linear = |c: f64| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        let (r, g, b) = self.rgb_unit_int();
+
+        0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+    }
+
+    /// Returns the color t of the way from the color to other, with t clamped
+    /// between 0 and 1, for blending colors into gradients.
+    pub fn mix(&self, other: Color, t: f64) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let mix = |a: i32, b: i32| (a as f64 + (b - a) as f64 * t).round() as i32;
+
+        Color::rgb(
+            mix(self.red, other.red),
+            mix(self.green, other.green),
+            mix(self.blue, other.blue),
+        )
+    }
+
+    /// Returns the color with its hue turned by hue degrees and its lightness
+    /// (between 0 and 1) raised by lightness, clamped to the valid range.
+    pub(crate) fn shifted(&self, hue: f64, lightness: f64) -> Color {
+        let (r, g, b) = self.rgb_unit_int();
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+        let l = (max + min) / 2.0;
+        let d = max - min;
+        let s = if d == 0.0 {
+            0.0
+        } else {
+            d / (1.0 - (2.0 * l - 1.0).abs())
+        };
+        let h = if d == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / d).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / d + 2.0)
+        } else {
+            60.0 * ((r - g) / d + 4.0)
+        };
+
+        let (h, l) = ((h + hue).rem_euclid(360.0), (l + lightness).clamp(0.0, 1.0));
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match (h / 60.0) as i32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = l - c / 2.0;
+        let component = |v: f64| (((v + m) * 255.0).round() as i32).clamp(0, 255);
+
+        Color::rgb(component(r), component(g), component(b))
+    }
+
+    /// Returns the color as a hex triplet, e.g. "#ff8000".
+    pub fn to_hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+impl From<[u8; 3]> for Color {
+    /// Converts red, green, and blue bytes.
+    fn from([red, green, blue]: [u8; 3]) -> Color {
+        Color::from_rgb8(red, green, blue)
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    /// Converts red, green, and blue bytes.
+    fn from((red, green, blue): (u8, u8, u8)) -> Color {
+        Color::from_rgb8(red, green, blue)
+    }
+}
+
+impl From<Color> for [u8; 3] {
+    /// Converts a color to its red, green, and blue bytes.
+    fn from(color: Color) -> [u8; 3] {
+        color.to_rgb8()
+    }
+}
+
+impl From<Color> for u32 {
+    /// Converts a color to a packed 0xRRGGBB value.
+    fn from(color: Color) -> u32 {
+        color.to_u32()
+    }
+}
+
+impl TryFrom<u32> for Color {
+    type Error = crate::Error;
+
+    /// Converts a packed 0xRRGGBB value, ensuring the top byte is zero.
+    fn try_from(rgb: u32) -> Result<Color> {
+        Color::from_u32(rgb)
+    }
+}
+
+impl TryFrom<[i32; 3]> for Color {
+    type Error = crate::Error;
+
+    /// Converts red, green, and blue components, ensuring each is in the
+    /// range [0, 255].
+    fn try_from([red, green, blue]: [i32; 3]) -> Result<Color> {
+        Color::new(red, green, blue)
+    }
+}
+
+impl TryFrom<(i32, i32, i32)> for Color {
+    type Error = crate::Error;
+
+    /// Converts red, green, and blue components, ensuring each is in the
+    /// range [0, 255].
+    fn try_from((red, green, blue): (i32, i32, i32)) -> Result<Color> {
+        Color::new(red, green, blue)
+    }
+}
+
+impl TryFrom<&str> for Color {
+    type Error = crate::Error;
+
+    /// Converts a hex triplet, e.g. "#ff8000".
+    fn try_from(hex: &str) -> Result<Color> {
+        Color::from_hex(hex)
+    }
+}
+
+#[cfg(feature = "palette")]
+impl From<Color> for palette::Srgb<u8> {
+    fn from(color: Color) -> palette::Srgb<u8> {
+        let [red, green, blue] = color.to_rgb8();
+
+        palette::Srgb::new(red, green, blue)
+    }
+}
+
+#[cfg(feature = "palette")]
+impl From<palette::Srgb<u8>> for Color {
+    fn from(color: palette::Srgb<u8>) -> Color {
+        Color::from_rgb8(color.red, color.green, color.blue)
+    }
+}
+
+#[cfg(feature = "palette")]
+impl From<Color> for palette::Srgb {
+    fn from(color: Color) -> palette::Srgb {
+        palette::Srgb::<u8>::from(color).into_format()
+    }
+}
+
+#[cfg(feature = "palette")]
+impl From<palette::Srgb> for Color {
+    fn from(color: palette::Srgb) -> Color {
+        Color::from(color.into_format::<u8>())
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<Color> for image::Rgba<u8> {
+    fn from(color: Color) -> image::Rgba<u8> {
+        let [red, green, blue] = color.to_rgb8();
+
+        image::Rgba([red, green, blue, 255])
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::Rgba<u8>> for Color {
+    /// Converts an RGBA pixel, discarding the alpha component.
+    fn from(pixel: image::Rgba<u8>) -> Color {
+        let [red, green, blue, _] = pixel.0;
+
+        Color::from_rgb8(red, green, blue)
+    }
+}
+
+#[cfg(feature = "csscolorparser")]
+impl From<Color> for csscolorparser::Color {
+    fn from(color: Color) -> csscolorparser::Color {
+        let [red, green, blue] = color.to_rgb8();
+
+        csscolorparser::Color::from_rgba8(red, green, blue, 255)
+    }
+}
+
+#[cfg(feature = "csscolorparser")]
+impl From<csscolorparser::Color> for Color {
+    /// Converts a parsed CSS color, discarding the alpha component.
+    fn from(color: csscolorparser::Color) -> Color {
+        let [red, green, blue, _] = color.to_rgba8();
+
+        Color::from_rgb8(red, green, blue)
+    }
 }
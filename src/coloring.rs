@@ -0,0 +1,364 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{Error::*, Result};
+
+/// The most times the search for a coloring backs up before giving up.
+const MAX_BACKTRACKS: usize = 1_000_000;
+
+/// A vertex colored during the search, with the colors left to try and the
+/// depths of the earlier choices whose colors rule out its others.
+struct Choice {
+    vertex: usize,
+    untried: Vec<usize>,
+    conflicts: BTreeSet<usize>,
+}
+
+/// Returns a color below k for each vertex of the graph with the adjacency
+/// lists neighbors, such that no two neighbors share a color.
+/// A single pass of DSATUR is tried first: it colors the vertex with the
+/// most differently colored neighbors next, breaking ties by the most
+/// uncolored neighbors and then at random, with one of its free colors at
+/// random, so seed picks one of the many colorings. When that pass gets
+/// stuck, a graph with no odd cycle is colored with two of the colors, and
+/// any other graph is searched by DSATUR with conflict-directed
+/// backjumping.
+/// Fails with `InvalidArgument` if k is 0, with `InfeasibleColoring` if
+/// there is no coloring, and with `UndecidedColoring` if the search gives up
+/// before finding one or proving there is none.
+pub(crate) fn color(neighbors: &[Vec<usize>], k: usize, seed: u64) -> Result<Vec<usize>> {
+    if k == 0 {
+        return Err(InvalidArgument {
+            name: String::from("k"),
+            reason: String::from("a coloring needs at least one color"),
+        });
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let ranks = (0..neighbors.len())
+        .map(|_| rng.gen::<u64>())
+        .collect::<Vec<u64>>();
+
+    if let Some(colors) = Search::new(neighbors, k, &ranks).greedy(&mut rng) {
+        return Ok(colors);
+    }
+    match bipartition(neighbors) {
+        Some(sides) if k >= 2 => {
+            let mut pair = (0..k).collect::<Vec<usize>>();
+            pair.shuffle(&mut rng);
+            return Ok(sides.into_iter().map(|s| pair[s]).collect());
+        }
+        None if k <= 2 => {
+            return Err(InfeasibleColoring {
+                colors: k,
+                reason: String::from("adjacent shapes form an odd cycle"),
+            })
+        }
+        _ => {}
+    }
+
+    Search::new(neighbors, k, &ranks).backjumping(&mut rng)
+}
+
+/// The state of a DSATUR search: each vertex's color, and for each vertex
+/// the number of its neighbors of each color, its saturation (the number of
+/// distinct colors among its neighbors), and its number of uncolored
+/// neighbors. Uncolored vertices wait in a bucket queue indexed by
+/// saturation, each bucket ordered by uncolored neighbors and rank, so the
+/// next vertex to color is found without scanning the graph.
+struct Search<'a> {
+    neighbors: &'a [Vec<usize>],
+    k: usize,
+    ranks: &'a [u64],
+    colors: Vec<Option<usize>>,
+    depths: Vec<usize>,
+    counts: Vec<u32>,
+    saturation: Vec<usize>,
+    uncolored: Vec<usize>,
+    buckets: Vec<BTreeSet<(usize, u64, usize)>>,
+}
+
+impl<'a> Search<'a> {
+    /// Returns a search with every vertex of the graph uncolored, ranked by
+    /// ranks when ties are broken.
+    fn new(neighbors: &'a [Vec<usize>], k: usize, ranks: &'a [u64]) -> Search<'a> {
+        let n = neighbors.len();
+        let uncolored = neighbors.iter().map(|ns| ns.len()).collect::<Vec<usize>>();
+        let mut buckets = vec![BTreeSet::new(); k + 1];
+        buckets[0] = (0..n).map(|v| (uncolored[v], ranks[v], v)).collect();
+
+        Search {
+            neighbors,
+            k,
+            ranks,
+            colors: vec![None; n],
+            depths: vec![0; n],
+            counts: vec![0; n * k],
+            saturation: vec![0; n],
+            uncolored,
+            buckets,
+        }
+    }
+
+    /// Colors every vertex in DSATUR order without backing up, returning
+    /// the colors, or None if a vertex is left with no free color.
+    fn greedy(mut self, rng: &mut ChaCha8Rng) -> Option<Vec<usize>> {
+        while let Some(vertex) = self.next() {
+            let c = *self.free(vertex).choose(rng)?;
+            self.assign(vertex, c, 0);
+        }
+
+        Some(self.finish())
+    }
+
+    /// Colors every vertex in DSATUR order, backing up when a vertex is left
+    /// with no color to try. A vertex out of colors jumps back to the latest
+    /// choice among its conflicts, skipping the choices in between that had
+    /// no part in the failure, and hands that choice its other conflicts;
+    /// running out of colors with no conflicts proves there is no coloring.
+    fn backjumping(mut self, rng: &mut ChaCha8Rng) -> Result<Vec<usize>> {
+        let mut choices: Vec<Choice> = Vec::new();
+        let mut backtracks = 0;
+
+        while let Some(vertex) = self.next() {
+            let mut untried = self.free(vertex);
+            untried.shuffle(rng);
+            // A vertex with no colored neighbors starts a new component,
+            // whose colorings are the same up to renaming colors, so only
+            // one color need be tried.
+            if self.saturation[vertex] == 0 {
+                untried.truncate(1);
+            }
+            let conflicts = self.conflicts(vertex);
+            choices.push(Choice {
+                vertex,
+                untried,
+                conflicts,
+            });
+
+            // Color the latest vertex with a color left to try, jumping back
+            // to earlier vertices while none are left.
+            loop {
+                let depth = choices.len() - 1;
+                let choice = &mut choices[depth];
+                if let Some(c) = choice.untried.pop() {
+                    self.assign(choice.vertex, c, depth);
+                    break;
+                }
+
+                let conflicts = std::mem::take(&mut choice.conflicts);
+                let target = match conflicts.iter().next_back() {
+                    Some(d) => *d,
+                    None => {
+                        return Err(InfeasibleColoring {
+                            colors: self.k,
+                            reason: String::from("adjacent shapes would share a fill"),
+                        })
+                    }
+                };
+                backtracks += 1;
+                if backtracks > MAX_BACKTRACKS {
+                    return Err(UndecidedColoring {
+                        colors: self.k,
+                        backtracks: MAX_BACKTRACKS,
+                    });
+                }
+
+                choices.pop();
+                while choices.len() > target + 1 {
+                    if let Some(skipped) = choices.pop() {
+                        self.unassign(skipped.vertex);
+                    }
+                }
+                let choice = &mut choices[target];
+                self.unassign(choice.vertex);
+                choice
+                    .conflicts
+                    .extend(conflicts.into_iter().filter(|d| *d != target));
+            }
+        }
+
+        Ok(self.finish())
+    }
+
+    /// Returns the uncolored vertex to color next: the one with the highest
+    /// saturation, most uncolored neighbors, and highest rank.
+    fn next(&self) -> Option<usize> {
+        self.buckets
+            .iter()
+            .rev()
+            .find_map(|b| b.iter().next_back())
+            .map(|(_, _, v)| *v)
+    }
+
+    /// Returns the colors none of vertex's neighbors have.
+    fn free(&self, vertex: usize) -> Vec<usize> {
+        (0..self.k)
+            .filter(|c| self.counts[vertex * self.k + c] == 0)
+            .collect()
+    }
+
+    /// Returns the depths of the choices that rule out each of vertex's
+    /// colors: for each color its neighbors have, the earliest of them to be
+    /// given it.
+    fn conflicts(&self, vertex: usize) -> BTreeSet<usize> {
+        let mut earliest: Vec<Option<usize>> = vec![None; self.k];
+        for n in self.neighbors[vertex].iter() {
+            if let Some(c) = self.colors[*n] {
+                let depth = self.depths[*n];
+                earliest[c] = Some(earliest[c].map_or(depth, |d| d.min(depth)));
+            }
+        }
+
+        earliest.into_iter().flatten().collect()
+    }
+
+    /// Gives vertex the color c at the choice with index depth.
+    fn assign(&mut self, vertex: usize, c: usize, depth: usize) {
+        self.dequeue(vertex);
+        self.colors[vertex] = Some(c);
+        self.depths[vertex] = depth;
+        for i in 0..self.neighbors[vertex].len() {
+            let n = self.neighbors[vertex][i];
+            let queued = self.colors[n].is_none();
+            if queued {
+                self.dequeue(n);
+            }
+            self.counts[n * self.k + c] += 1;
+            if self.counts[n * self.k + c] == 1 {
+                self.saturation[n] += 1;
+            }
+            self.uncolored[n] -= 1;
+            if queued {
+                self.enqueue(n);
+            }
+        }
+    }
+
+    /// Takes vertex's color away, undoing `assign`.
+    fn unassign(&mut self, vertex: usize) {
+        let c = match self.colors[vertex].take() {
+            Some(c) => c,
+            None => return,
+        };
+        for i in 0..self.neighbors[vertex].len() {
+            let n = self.neighbors[vertex][i];
+            let queued = self.colors[n].is_none();
+            if queued {
+                self.dequeue(n);
+            }
+            self.counts[n * self.k + c] -= 1;
+            if self.counts[n * self.k + c] == 0 {
+                self.saturation[n] -= 1;
+            }
+            self.uncolored[n] += 1;
+            if queued {
+                self.enqueue(n);
+            }
+        }
+        self.enqueue(vertex);
+    }
+
+    /// Puts the uncolored vertex in the bucket of its saturation.
+    fn enqueue(&mut self, vertex: usize) {
+        let key = (self.uncolored[vertex], self.ranks[vertex], vertex);
+        self.buckets[self.saturation[vertex]].insert(key);
+    }
+
+    /// Takes the uncolored vertex out of its bucket.
+    fn dequeue(&mut self, vertex: usize) {
+        let key = (self.uncolored[vertex], self.ranks[vertex], vertex);
+        self.buckets[self.saturation[vertex]].remove(&key);
+    }
+
+    /// Returns the colors of the fully colored graph.
+    fn finish(self) -> Vec<usize> {
+        self.colors
+            .into_iter()
+            .map(|c| c.unwrap_or_default())
+            .collect()
+    }
+}
+
+/// Returns the side (0 or 1) of each vertex of the graph with the adjacency
+/// lists neighbors, such that no two neighbors are on the same side, or
+/// None if the graph has an odd cycle.
+fn bipartition(neighbors: &[Vec<usize>]) -> Option<Vec<usize>> {
+    let mut sides: Vec<Option<usize>> = vec![None; neighbors.len()];
+    let mut queue = VecDeque::new();
+    for start in 0..neighbors.len() {
+        if sides[start].is_some() {
+            continue;
+        }
+        sides[start] = Some(0);
+        queue.push_back(start);
+        while let Some(v) = queue.pop_front() {
+            let side = sides[v]?;
+            for n in neighbors[v].iter() {
+                match sides[*n] {
+                    Some(s) if s == side => return None,
+                    Some(_) => {}
+                    None => {
+                        sides[*n] = Some(1 - side);
+                        queue.push_back(*n);
+                    }
+                }
+            }
+        }
+    }
+
+    sides.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the adjacency lists of a rows by columns grid of squares
+    /// with both diagonals of every square also joined, whose every 2 by 2
+    /// block is a 4-clique.
+    fn kings(rows: usize, columns: usize) -> Vec<Vec<usize>> {
+        let mut neighbors = vec![Vec::new(); rows * columns];
+        for r in 0..rows {
+            for c in 0..columns {
+                for (dr, dc) in [(0, 1), (1, -1), (1, 0), (1, 1)] {
+                    let (r1, c1) = (r as i64 + dr, c as i64 + dc);
+                    if r1 < rows as i64 && c1 >= 0 && c1 < columns as i64 {
+                        let (a, b) = (r * columns + c, r1 as usize * columns + c1 as usize);
+                        neighbors[a].push(b);
+                        neighbors[b].push(a);
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    #[test]
+    fn colorings_are_proper_or_proved_infeasible() -> Result<()> {
+        let graph = kings(60, 60);
+        let colors = color(&graph, 4, 3)?;
+        for (v, ns) in graph.iter().enumerate() {
+            assert!(ns.iter().all(|n| colors[*n] != colors[v]));
+        }
+
+        // A 4-clique needs 4 colors; the search proves 3 are too few
+        // without giving up.
+        assert!(matches!(
+            color(&graph, 3, 3),
+            Err(InfeasibleColoring { colors: 3, .. })
+        ));
+        assert!(matches!(color(&graph, 0, 3), Err(InvalidArgument { .. })));
+        // An odd cycle needs 3 colors.
+        let triangle = vec![vec![1, 2], vec![0, 2], vec![0, 1]];
+        assert!(matches!(
+            color(&triangle, 2, 3),
+            Err(InfeasibleColoring { colors: 2, .. })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use crate::Color;
+
+/// A named sequential colormap that maps values between 0 and 1 to colors by
+/// interpolating between evenly spaced stops.
+#[derive(Clone, Copy, Debug)]
+pub struct Colormap {
+    name: &'static str,
+    stops: &'static [Color],
+}
+
+/// The viridis colormap of matplotlib, from dark purple through teal to
+/// yellow, which is perceptually uniform and readable with color blindness.
+pub const VIRIDIS: Colormap = Colormap {
+    name: "viridis",
+    stops: &[
+        Color::rgb(68, 1, 84),
+        Color::rgb(71, 45, 123),
+        Color::rgb(59, 82, 139),
+        Color::rgb(44, 114, 142),
+        Color::rgb(33, 145, 140),
+        Color::rgb(40, 174, 128),
+        Color::rgb(94, 201, 98),
+        Color::rgb(173, 220, 48),
+        Color::rgb(253, 231, 37),
+    ],
+};
+
+/// The magma colormap of matplotlib, from black through purple and orange to
+/// pale yellow.
+pub const MAGMA: Colormap = Colormap {
+    name: "magma",
+    stops: &[
+        Color::rgb(0, 0, 4),
+        Color::rgb(28, 16, 68),
+        Color::rgb(79, 18, 123),
+        Color::rgb(129, 37, 129),
+        Color::rgb(181, 54, 122),
+        Color::rgb(229, 80, 100),
+        Color::rgb(251, 135, 97),
+        Color::rgb(254, 194, 135),
+        Color::rgb(252, 253, 191),
+    ],
+};
+
+/// A colormap from white to black.
+pub const GREYS: Colormap = Colormap {
+    name: "greys",
+    stops: &[Color::rgb(255, 255, 255), Color::rgb(0, 0, 0)],
+};
+
+/// Every built-in colormap.
+const COLORMAPS: [Colormap; 3] = [VIRIDIS, MAGMA, GREYS];
+
+impl Colormap {
+    /// Returns the built-in colormap named name (ignoring case), e.g.
+    /// "viridis".
+    pub fn by_name(name: &str) -> Option<Colormap> {
+        COLORMAPS
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Returns every built-in colormap.
+    pub fn all() -> &'static [Colormap] {
+        &COLORMAPS
+    }
+
+    /// Returns the colormap's name.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the color of t, which is clamped between 0 and 1.
+    pub fn color(&self, t: f64) -> Color {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let position = t * (self.stops.len() - 1) as f64;
+        let i = (position.floor() as usize).min(self.stops.len() - 2);
+
+        self.stops[i].mix(self.stops[i + 1], position - i as f64)
+    }
+}
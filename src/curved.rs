@@ -0,0 +1,357 @@
+use std::f64::consts::PI;
+
+use crate::{irregular, Axis, Color, Error::*, PaintMode, Point, Polygon, Result, Tile};
+
+#[cfg(feature = "cairo")]
+use crate::shape;
+
+/// The largest angle (in radians) spanned by one segment of a flattened arc.
+const ARC_STEP: f64 = PI / 32.0;
+
+/// A shape whose edges may be circular arcs rather than straight lines, such
+/// as the petals of a flower of life (two vertices joined by two arcs) or a
+/// Reuleaux triangle.
+/// Each edge has a bulge, the tangent of a quarter of the angle its arc spans:
+/// 0 is straight, 1 is a semicircle, and positive bulges bow out of the shape
+/// while negative bulges bow into it.
+/// Curved shapes are added to a model with `Model::add_tile`; they are traced
+/// with true arcs when rendered, and flattened into segments by `points`.
+#[derive(Clone, Debug)]
+pub struct CurvedShape {
+    vertices: Vec<Point>,
+    bulges: Vec<f64>,
+    fill: Color,
+    stroke: Color,
+    paint: PaintMode,
+}
+
+/// An edge's circular arc.
+struct Arc {
+    center: Point,
+    radius: f64,
+    start: f64,
+    sweep: f64,
+}
+
+impl CurvedShape {
+    /// Returns a new curved shape with vertices in edge order and straight
+    /// edges, ensuring it has at least 2 vertices.
+    pub fn new(vertices: &[Point], fill: Color, stroke: Color) -> Result<CurvedShape> {
+        if vertices.len() < 2 {
+            return Err(InvalidShape {
+                reason: format!("curved shape has {} vertices", vertices.len()),
+            });
+        }
+
+        Ok(CurvedShape {
+            vertices: vertices.to_vec(),
+            bulges: vec![0.0; vertices.len()],
+            fill,
+            stroke,
+            paint: PaintMode::default(),
+        })
+    }
+
+    /// Returns a curved shape with the vertices of polygon, which may be a
+    /// regular `Shape`, and every edge bulging by bulge.
+    pub fn from_polygon<P: Polygon>(
+        polygon: &P,
+        bulge: f64,
+        fill: Color,
+        stroke: Color,
+    ) -> Result<CurvedShape> {
+        let mut points = polygon.points(0.0)?;
+        points.pop();
+
+        CurvedShape::new(&points, fill, stroke)?.with_bulges(bulge)
+    }
+
+    /// Returns the curved shape with the edge with index edge bulging by
+    /// bulge, ensuring the edge exists and bulge is finite.
+    pub fn with_bulge(mut self, edge: usize, bulge: f64) -> Result<CurvedShape> {
+        if !bulge.is_finite() {
+            return Err(InvalidShape {
+                reason: format!("edge bulge {} is not finite", bulge),
+            });
+        }
+        let length = self.bulges.len();
+        let b = self.bulges.get_mut(edge).ok_or(OutOfBounds {
+            index: edge,
+            length,
+            name: String::from("curved shape edges"),
+        })?;
+        *b = bulge;
+
+        Ok(self)
+    }
+
+    /// Returns the curved shape with every edge bulging by bulge, ensuring
+    /// bulge is finite.
+    pub fn with_bulges(mut self, bulge: f64) -> Result<CurvedShape> {
+        for edge in 0..self.bulges.len() {
+            self = self.with_bulge(edge, bulge)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the curved shape with the parts painted by paint mode.
+    pub fn with_paint(mut self, paint: PaintMode) -> CurvedShape {
+        self.paint = paint;
+
+        self
+    }
+
+    /// Returns the curved shape's vertices in edge order.
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Returns the bulge of each edge, in edge order.
+    pub fn bulges(&self) -> &[f64] {
+        &self.bulges
+    }
+
+    /// Returns the curved shape's center, the mean of its vertices.
+    pub fn point(&self) -> Point {
+        let n = self.vertices.len() as f64;
+        let (x, y) = self
+            .vertices
+            .iter()
+            .fold((0.0, 0.0), |(x, y), p| (x + p.x, y + p.y));
+
+        Point { x: x / n, y: y / n }
+    }
+
+    /// Returns the curved shape's fill.
+    pub fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the curved shape's stroke.
+    pub fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns a copy of the curved shape centered at point.
+    pub fn clone_at(&self, point: Point) -> CurvedShape {
+        let center = self.point();
+        let mut c = self.clone();
+        c.vertices = self
+            .vertices
+            .iter()
+            .map(|p| Point {
+                x: p.x + point.x - center.x,
+                y: p.y + point.y - center.y,
+            })
+            .collect();
+
+        c
+    }
+
+    /// Returns the vertices inset by margin, which scales them about the
+    /// curved shape's center until its outline is margin closer to the
+    /// center (as insetting a rhombus does).
+    fn inset(&self, margin: f64) -> Vec<Point> {
+        let center = self.point();
+        let outline = self.flatten(&self.vertices);
+        let inradius = outline
+            .windows(2)
+            .map(|w| distance_to_segment(center, w[0], w[1]))
+            .fold(f64::INFINITY, f64::min);
+        let scale = match inradius > 0.0 {
+            true => (1.0 - margin / inradius).max(0.0),
+            false => 1.0,
+        };
+
+        self.vertices
+            .iter()
+            .map(|p| Point {
+                x: center.x + (p.x - center.x) * scale,
+                y: center.y + (p.y - center.y) * scale,
+            })
+            .collect()
+    }
+
+    /// Returns the arc of each edge between vertices, or none for straight
+    /// edges.
+    fn arcs(&self, vertices: &[Point]) -> Vec<Option<Arc>> {
+        // The outward side of each edge depends on the vertices' winding,
+        // which is clockwise on the canvas when the signed area is positive.
+        let side = match irregular::area(vertices) >= 0.0 {
+            true => 1.0,
+            false => -1.0,
+        };
+
+        (0..vertices.len())
+            .map(|i| {
+                let (p0, p1) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+                arc(p0, p1, self.bulges[i], side)
+            })
+            .collect()
+    }
+
+    /// Returns the closed outline through vertices, with arcs flattened into
+    /// segments.
+    fn flatten(&self, vertices: &[Point]) -> Vec<Point> {
+        let mut points = vec![vertices[0]];
+        for (i, arc) in self.arcs(vertices).into_iter().enumerate() {
+            if let Some(a) = arc {
+                let steps = (a.sweep.abs() / ARC_STEP).ceil().max(1.0) as usize;
+                points.extend((1..steps).map(|k| {
+                    let angle = a.start + a.sweep * k as f64 / steps as f64;
+                    Point {
+                        x: a.center.x + a.radius * angle.cos(),
+                        y: a.center.y + a.radius * angle.sin(),
+                    }
+                }));
+            }
+            points.push(vertices[(i + 1) % vertices.len()]);
+        }
+
+        points
+    }
+}
+
+/// Returns the arc from p0 to p1 bulging by bulge toward the side of the
+/// segment (1 for its left on the canvas, -1 for its right), or none if the
+/// edge is straight.
+fn arc(p0: Point, p1: Point, bulge: f64, side: f64) -> Option<Arc> {
+    let (dx, dy) = (p1.x - p0.x, p1.y - p0.y);
+    let chord = dx.hypot(dy);
+    if bulge == 0.0 || chord == 0.0 {
+        return None;
+    }
+
+    // The sagitta is the distance from the chord's midpoint to the arc.
+    let sagitta = bulge * chord / 2.0;
+    let radius = (chord * chord / 4.0 + sagitta * sagitta) / (2.0 * sagitta.abs());
+    let (nx, ny) = (side * dy / chord, -side * dx / chord);
+    let offset = sagitta - sagitta.signum() * radius;
+    let center = Point {
+        x: (p0.x + p1.x) / 2.0 + nx * offset,
+        y: (p0.y + p1.y) / 2.0 + ny * offset,
+    };
+    let start = (p0.y - center.y).atan2(p0.x - center.x);
+    let end = (p1.y - center.y).atan2(p1.x - center.x);
+    // Arcs bulging to the left of the segment sweep clockwise on the canvas,
+    // as angles increase.
+    let clockwise = (end - start).rem_euclid(2.0 * PI);
+    let sweep = match bulge * side > 0.0 {
+        true => clockwise,
+        false => clockwise - 2.0 * PI,
+    };
+
+    Some(Arc {
+        center,
+        radius,
+        start,
+        sweep,
+    })
+}
+
+/// Returns the distance from point to the segment from p0 to p1.
+pub(crate) fn distance_to_segment(point: Point, p0: Point, p1: Point) -> f64 {
+    let (dx, dy) = (p1.x - p0.x, p1.y - p0.y);
+    let length = dx * dx + dy * dy;
+    let t = match length > 0.0 {
+        true => (((point.x - p0.x) * dx + (point.y - p0.y) * dy) / length).clamp(0.0, 1.0),
+        false => 0.0,
+    };
+
+    (point.x - p0.x - t * dx).hypot(point.y - p0.y - t * dy)
+}
+
+impl Polygon for CurvedShape {
+    /// Returns the polygon's points, with arcs flattened into segments no
+    /// more than `ARC_STEP` of a circle apart.
+    fn points(&self, margin: f64) -> Result<Vec<Point>> {
+        Ok(self.flatten(&self.inset(margin)))
+    }
+
+    /// Renders the polygon, tracing its arcs as circular arcs, which
+    /// `render_snapped` does not snap.
+    #[cfg(feature = "cairo")]
+    fn render(&self, context: &cairo::Context, margin: f64) -> Result<()> {
+        let vertices = self.inset(margin);
+        context.move_to(vertices[0].x, vertices[0].y);
+        for (i, arc) in self.arcs(&vertices).into_iter().enumerate() {
+            match arc {
+                Some(a) if a.sweep > 0.0 => {
+                    context.arc(a.center.x, a.center.y, a.radius, a.start, a.start + a.sweep)
+                }
+                Some(a) => context.arc_negative(
+                    a.center.x,
+                    a.center.y,
+                    a.radius,
+                    a.start,
+                    a.start + a.sweep,
+                ),
+                None => {
+                    let p = vertices[(i + 1) % vertices.len()];
+                    context.line_to(p.x, p.y);
+                }
+            }
+        }
+        context.close_path();
+
+        shape::paint(context, self.fill, self.stroke, self.paint)
+    }
+}
+
+impl Tile for CurvedShape {
+    /// Returns the tile's center.
+    fn point(&self) -> Point {
+        CurvedShape::point(self)
+    }
+
+    /// Returns a copy of the tile moved by vector.
+    fn translated(&self, vector: Point) -> Box<dyn Tile> {
+        Box::new(self.clone_at(CurvedShape::point(self) + vector))
+    }
+
+    /// Returns a copy of the tile rotated by angle (in radians) about center.
+    fn rotated(&self, angle: f64, center: Point) -> Box<dyn Tile> {
+        let mut c = self.clone();
+        c.vertices = self
+            .vertices
+            .iter()
+            .map(|p| p.rotate(angle, center))
+            .collect();
+
+        Box::new(c)
+    }
+
+    /// Returns a copy of the tile mirrored across axis.
+    fn reflected(&self, axis: &Axis) -> Box<dyn Tile> {
+        // Mirroring reverses the vertices' winding, and bulges are measured
+        // against the winding, so each edge keeps its bulge.
+        let mut c = self.clone();
+        c.vertices = self.vertices.iter().map(|p| axis.reflect(*p)).collect();
+
+        Box::new(c)
+    }
+
+    /// Returns a copy of the tile filled with fill.
+    fn filled(&self, fill: Color) -> Box<dyn Tile> {
+        let mut c = self.clone();
+        c.fill = fill;
+
+        Box::new(c)
+    }
+
+    /// Returns the color the tile is filled with.
+    fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the color the tile is stroked with.
+    fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns whether the tile is filled, stroked, or both.
+    fn paint(&self) -> PaintMode {
+        self.paint
+    }
+}
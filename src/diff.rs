@@ -0,0 +1,112 @@
+use std::{cmp::Ordering::Less, collections::HashMap};
+
+use crate::{shape::Key, Shape};
+
+/// The farthest (in edge lengths) a shape may move and still be reported as
+/// moved rather than removed and added.
+const MOVE_DISTANCE: f64 = 1.0;
+
+/// The differences between the shapes of two tilings.
+#[derive(Clone, Debug, Default)]
+pub struct TilingDiff {
+    added: Vec<Shape>,
+    removed: Vec<Shape>,
+    moved: Vec<(Shape, Shape)>,
+    unchanged: usize,
+}
+
+impl TilingDiff {
+    /// Returns the differences from the shapes before to the shapes after.
+    /// Shapes match when they coincide with centers compared to precision
+    /// decimal places.
+    /// Each unmatched shape before is then paired with the nearest unmatched
+    /// shape after that has as many sides and lies within one edge length, if
+    /// any, and reported as moved.
+    pub(crate) fn new<'a, B, A>(before: B, after: A, precision: i32) -> TilingDiff
+    where
+        B: IntoIterator<Item = &'a Shape>,
+        A: IntoIterator<Item = &'a Shape>,
+    {
+        let mut diff = TilingDiff::default();
+        let mut after_by_key: HashMap<Key, Vec<Shape>> = HashMap::new();
+        for s in after {
+            after_by_key
+                .entry(s.point().key(precision))
+                .or_default()
+                .push(*s);
+        }
+
+        for s in before {
+            let candidates = after_by_key.entry(s.point().key(precision)).or_default();
+            match candidates
+                .iter()
+                .position(|c| c.coincides_within(s, precision))
+            {
+                Some(i) => {
+                    candidates.swap_remove(i);
+                    diff.unchanged += 1;
+                }
+                None => diff.removed.push(*s),
+            }
+        }
+        diff.added = after_by_key.into_values().flatten().collect();
+        sort(&mut diff.added);
+        sort(&mut diff.removed);
+
+        let mut removed = Vec::new();
+        for r in diff.removed.drain(..) {
+            let distance = |s: &Shape| {
+                let (dx, dy) = (s.point().x - r.point().x, s.point().y - r.point().y);
+                (dx * dx + dy * dy).sqrt()
+            };
+            let nearest = diff
+                .added
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| a.sides() == r.sides() && distance(a) <= MOVE_DISTANCE)
+                .min_by(|(_, a), (_, b)| distance(a).partial_cmp(&distance(b)).unwrap_or(Less))
+                .map(|(i, _)| i);
+            match nearest {
+                Some(i) => diff.moved.push((r, diff.added.remove(i))),
+                None => removed.push(r),
+            }
+        }
+        diff.removed = removed;
+
+        diff
+    }
+
+    /// Returns the shapes that only the second tiling contains.
+    pub fn added(&self) -> &[Shape] {
+        &self.added
+    }
+
+    /// Returns the shapes that only the first tiling contains.
+    pub fn removed(&self) -> &[Shape] {
+        &self.removed
+    }
+
+    /// Returns each shape of the first tiling that was moved or rotated, paired
+    /// with its counterpart in the second tiling.
+    pub fn moved(&self) -> &[(Shape, Shape)] {
+        &self.moved
+    }
+
+    /// Returns the number of shapes that both tilings contain.
+    pub fn unchanged(&self) -> usize {
+        self.unchanged
+    }
+
+    /// Returns true if the tilings contain the same shapes.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty()
+    }
+}
+
+/// Sorts shapes by their centers (top to bottom, then left to right).
+fn sort(shapes: &mut [Shape]) {
+    shapes.sort_by(|a, b| {
+        let (a, b) = (a.point(), b.point());
+        (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less)
+    });
+}
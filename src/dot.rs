@@ -0,0 +1,34 @@
+use std::fmt::Write;
+
+use crate::{Point, Shape};
+
+/// Returns a Graphviz DOT graph with a node for each of shapes and an edge
+/// between each pair of shapes in adjacent (indexes into shapes).
+/// Each node is labeled with its index and carries the shape's number of
+/// sides and colors, and is pinned at the shape's center (with the y axis
+/// pointing up) so that `neato` draws the graph over the tiling.
+pub(crate) fn graph(shapes: &[Shape], adjacent: &[(usize, usize)]) -> String {
+    // Writing to a String cannot fail.
+    let mut dot = String::from("graph adjacency {\n");
+    let _ = writeln!(dot, "  node [shape=circle, style=filled];");
+    for (i, s) in shapes.iter().enumerate() {
+        let Point { x, y } = s.point();
+        let _ = writeln!(
+            dot,
+            r#"  {} [label="{}", sides={}, fillcolor="{}", color="{}", pos="{},{}!"];"#,
+            i,
+            i,
+            s.sides(),
+            s.fill().to_hex(),
+            s.stroke().to_hex(),
+            x,
+            0.0 - y,
+        );
+    }
+    for (a, b) in adjacent {
+        let _ = writeln!(dot, "  {} -- {};", a, b);
+    }
+    dot.push_str("}\n");
+
+    dot
+}
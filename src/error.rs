@@ -1,7 +1,9 @@
-use std::{io, result};
+use std::{io, path::PathBuf, result};
 
 use thiserror::Error;
 
+use crate::VertexFigure;
+
 /// A type that represents a success or failure.
 pub type Result<T> = result::Result<T, Error>;
 
@@ -17,22 +19,251 @@ pub enum Error {
     },
 
     /// An error occurred while rendering a shape.
+    #[cfg(feature = "cairo")]
     #[error("render error: {0}")]
     Render(#[from] cairo::Error),
 
+    /// A render backend other than cairo failed, or was used out of order.
+    #[error("render backend error: {reason}")]
+    Backend { reason: String },
+
     /// An error occurred while cairo was doing I/O.
+    #[cfg(feature = "cairo")]
     #[error("cairo I/O error")]
     CairoIO(#[from] cairo::IoError),
 
+    /// The pixels of a rendered image could not be accessed.
+    #[cfg(feature = "cairo")]
+    #[error("cairo borrow error")]
+    CairoBorrow(#[from] cairo::BorrowError),
+
     /// An I/O error occurred.
     #[error("file I/O error")]
     FileIO(#[from] io::Error),
 
+    /// An image could not be encoded as a PNG file.
+    #[error("PNG encoding error")]
+    PngEncoding(#[from] png::EncodingError),
+
     /// User-provided shape parameters were invalid.
-    #[error("invalid shape parameters")]
-    InvalidShape,
+    #[error("invalid shape parameters: {reason}")]
+    InvalidShape { reason: String },
+
+    /// A shape coincides with the previously added shape at index.
+    #[error("shape coincides with existing shape {index}")]
+    CoincidentShape { index: usize },
+
+    /// In strict mode, a shape attached at vertex (x, y) would overlap the
+    /// model's shapes with indexes in shapes, since their interior angles
+    /// and its own sum to angle degrees, more than a full turn.
+    #[error("angles at vertex ({x:.3}, {y:.3}) with shapes {shapes:?} sum to {angle:.1}°, more than 360°")]
+    AngleOverflow {
+        x: f64,
+        y: f64,
+        angle: f64,
+        shapes: Vec<usize>,
+    },
+
+    /// A pattern repeated in wrap mode has no rectangular period.
+    #[error("pattern has no rectangular period within {depth} repeats")]
+    NotPeriodic { depth: usize },
+
+    /// The centers of the seed shapes with indexes, which translate the
+    /// pattern when it is repeated, do not surround the origin, so the
+    /// pattern could never fill the surface.
+    #[error("seed shapes {indexes:?} cannot repeat the pattern: {reason}")]
+    InvalidRepeatSeed { indexes: Vec<usize>, reason: String },
+
+    /// The vectors a pattern is repeated along cannot translate it.
+    #[error("invalid repeat vectors: {reason}")]
+    InvalidRepeatVectors { reason: String },
+
+    /// The edges bounding a union of shapes do not close into a loop at
+    /// vertex (x, y), as where a shape's corner touches another's side.
+    #[error("boundary of the shapes is open at ({x:.3}, {y:.3})")]
+    OpenBoundary { x: f64, y: f64 },
+
+    /// An argument named name is out of the range the operation accepts.
+    #[error("invalid {name}: {reason}")]
+    InvalidArgument { name: String, reason: String },
+
+    /// A viewport to render has no area.
+    #[error("viewport {width} by {height} has no area")]
+    EmptyViewport { width: f64, height: f64 },
+
+    /// The margin named field is negative or not finite, or would inset a
+    /// shape by its inradius or more, turning it inside out.
+    #[error("invalid {field} {margin}: {reason}")]
+    InvalidMargin {
+        field: String,
+        margin: f64,
+        reason: String,
+    },
+
+    /// A canvas (supersampled, if it is) is wider or taller than cairo can
+    /// draw on.
+    #[error(
+        "canvas {width} by {height} exceeds the largest surface cairo can draw on; \
+         write it with Model::write_large_png"
+    )]
+    CanvasTooLarge { width: i64, height: i64 },
+
+    /// A tile pyramid has more levels than its tiles can be numbered in.
+    #[error("tile pyramid of {levels} levels exceeds the largest of {max} levels")]
+    TooManyLevels { levels: u32, max: u32 },
+
+    /// No shape is centered at a point of the tiling.
+    #[error("no shape is centered at ({x}, {y})")]
+    EmptyCell { x: f64, y: f64 },
 
     /// User-provided color parameters were invalid.
     #[error("invalid color parameters")]
     InvalidColor,
+
+    /// A tiling file could not be parsed.
+    #[error("invalid tiling file at line {line}: {reason}")]
+    InvalidTilingFile { line: usize, reason: String },
+
+    /// A pattern file could not be parsed.
+    #[error("invalid pattern file: {reason}")]
+    InvalidPatternFile { reason: String },
+
+    /// No shape, or no edge of the shapes selected, was given the name.
+    #[error("unknown anchor {name:?}")]
+    UnknownAnchor { name: String },
+
+    /// A model has no style set with the name.
+    #[error("unknown style set {name:?}")]
+    UnknownStyleSet { name: String },
+
+    /// The catalog has no entry with the name.
+    #[error("unknown catalog entry {name:?}")]
+    UnknownCatalogEntry { name: String },
+
+    /// A tiling could not be colored with colors colors without adjacent
+    /// shapes sharing a fill.
+    #[error("cannot color the tiling with {colors} colors: {reason}")]
+    InfeasibleColoring { colors: usize, reason: String },
+
+    /// The search for a coloring of a tiling with colors colors gave up
+    /// after backing up backtracks times, neither finding a coloring nor
+    /// proving there is none.
+    #[error("gave up coloring the tiling with {colors} colors after {backtracks} backtracks")]
+    UndecidedColoring { colors: usize, backtracks: usize },
+
+    /// An edge name names different edges of the shapes selected.
+    #[error("anchor {name:?} names different edges of the shapes")]
+    AmbiguousAnchor { name: String },
+
+    /// Edge ids were given to select shapes, or shape ids to select edges.
+    #[error("{found} ids given where {expected} are expected")]
+    MismatchedAnchor { expected: String, found: String },
+
+    /// Actual geometry or pixels did not match what was expected.
+    #[error("mismatch: {reason}")]
+    Mismatch { reason: String },
+
+    /// A fixture file could not be parsed.
+    #[error("invalid fixture at line {line}")]
+    InvalidFixture { line: usize },
+
+    /// Repeating a model would place more than max shapes, which usually
+    /// means its scale is too small for its canvas.
+    #[error("repeating would place more than {max} shapes; is the scale {scale} too small?")]
+    TooManyShapes { max: usize, scale: f64 },
+
+    /// A snap tolerance is not finite, is negative, or reaches past the
+    /// spatial index's cells, beyond which near shapes would be missed.
+    #[error("snap tolerance {tolerance} is not between 0 and {max} edge lengths")]
+    InvalidSnapTolerance { tolerance: f64, max: f64 },
+
+    /// The tile with index tile and the tile with index neighbor share an
+    /// edge whose matching-rule markings differ.
+    #[error("tile {tile}'s edge {edge} does not match tile {neighbor}'s edge {neighbor_edge}")]
+    UnmatchedEdge {
+        tile: usize,
+        edge: usize,
+        neighbor: usize,
+        neighbor_edge: usize,
+    },
+
+    /// Vertices of a tiling expected to be uniform have figures other than
+    /// configuration.
+    #[error("{} vertices are not {configuration}", .vertices.len())]
+    NotUniform {
+        configuration: String,
+        vertices: Vec<VertexFigure>,
+    },
+
+    /// The font file at path could not be loaded.
+    #[error("cannot load font {path:?}")]
+    InvalidFont { path: PathBuf },
+
+    /// The PNG image at path could not be read.
+    #[error("cannot load image {path:?}: {reason}")]
+    InvalidImage { path: PathBuf, reason: String },
+
+    /// No model of a tiling could be fitted to an image.
+    #[error("cannot fit a tiling to the image: {reason}")]
+    Unfit { reason: String },
+
+    /// A model operation failed while working with the shape with index shape
+    /// (and its edge with index edge), if known.
+    #[error("model operation error in {operation}{}", location(*.shape, *.edge))]
+    Operation {
+        operation: &'static str,
+        shape: Option<usize>,
+        edge: Option<usize>,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Returns the error wrapped with the model operation during which it
+    /// occurred and the shape and edge that operation was working with.
+    pub(crate) fn context(
+        self,
+        operation: &'static str,
+        shape: Option<usize>,
+        edge: Option<usize>,
+    ) -> Error {
+        Error::Operation {
+            operation,
+            shape,
+            edge,
+            source: Box::new(self),
+        }
+    }
+
+    /// Returns the error of margin, the margin named field, reaching the
+    /// inradius of a shape with sides sides.
+    pub(crate) fn margin_reaches(field: &str, margin: f64, inradius: f64, sides: i32) -> Error {
+        Error::InvalidMargin {
+            field: field.to_string(),
+            margin,
+            reason: format!(
+                "reaches the inradius {} of a {}-sided shape",
+                inradius, sides
+            ),
+        }
+    }
+
+    /// Returns the innermost error, skipping any operation context.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Operation { source, .. } => source.root(),
+            e => e,
+        }
+    }
+}
+
+/// Formats the location of an operation error.
+fn location(shape: Option<usize>, edge: Option<usize>) -> String {
+    match (shape, edge) {
+        (Some(s), Some(e)) => format!(" at edge {} of shape {}", e, s),
+        (Some(s), None) => format!(" at shape {}", s),
+        (None, Some(e)) => format!(" at edge {}", e),
+        (None, None) => String::new(),
+    }
 }
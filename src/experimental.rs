@@ -0,0 +1,19 @@
+//! Experimental APIs that are not yet covered by semantic versioning.
+//!
+//! This module is only available when the `unstable` feature is enabled.
+//! Anything within it may change or be removed in a minor release.
+//!
+//! New subsystems are implemented in private modules of the crate, declared
+//! with `cfg_unstable!`, and exposed only by the re-exports here. Once an API
+//! is stable, its module is made public at the crate root and its items are
+//! re-exported there, while its re-export here is kept, so code written
+//! against the experimental path keeps compiling until the next major
+//! release.
+
+/// Fitting models to images of tilings: `fit::fit` reverse-engineers a
+/// raster image of a periodic tiling by regular polygons into an editable
+/// `Model` of one period of the tiling.
+#[cfg(feature = "cairo")]
+pub mod fit {
+    pub use crate::fit::{fit, Fit, FitOptions};
+}
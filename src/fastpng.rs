@@ -0,0 +1,204 @@
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+use deflate::{write::DeflateEncoder, Compression};
+use rayon::prelude::*;
+
+use crate::Result;
+
+/// The number of rows compressed together by each thread.
+/// Each group of rows is compressed independently, so larger groups compress
+/// slightly better while smaller groups spread over more threads.
+const ROWS_PER_GROUP: usize = 64;
+
+/// Writes a width by height image of pixels, row by row from the top with
+/// channels bytes per pixel (3 for RGB or 4 for RGBA), as a PNG to writer,
+/// recording its resolution of dpi dots per inch if any.
+/// Rows are filtered and compressed in parallel: the rows are split into
+/// groups whose deflate streams are sync flushed and concatenated into the
+/// image's single zlib stream, as pigz does, trading a little file size for
+/// speed on large images.
+pub(crate) fn write<W: Write>(
+    writer: W,
+    width: usize,
+    height: usize,
+    channels: usize,
+    pixels: &[u8],
+    dpi: Option<f64>,
+) -> Result<()> {
+    let row = width * channels;
+    let filtered = (0..height)
+        .into_par_iter()
+        .map(|y| {
+            let previous = match y {
+                0 => None,
+                _ => Some(&pixels[(y - 1) * row..y * row]),
+            };
+            filter(&pixels[y * row..(y + 1) * row], previous, channels)
+        })
+        .collect::<Vec<Vec<u8>>>();
+
+    let mut groups = filtered.chunks(ROWS_PER_GROUP).collect::<Vec<&[Vec<u8>]>>();
+    if groups.is_empty() {
+        groups.push(&[]);
+    }
+    let last = groups.len() - 1;
+    let blocks = groups
+        .into_par_iter()
+        .enumerate()
+        .map(|(i, rows)| compress(rows, i == last))
+        .collect::<std::io::Result<Vec<Vec<u8>>>>()?;
+
+    // A zlib stream is a header, deflate blocks, and the Adler-32 checksum of
+    // the uncompressed data.
+    let mut data = vec![0x78, 0x01];
+    for b in blocks.iter() {
+        data.extend_from_slice(b);
+    }
+    let checksum = filtered.iter().fold((1, 0), |s, r| adler32(s, r));
+    data.extend_from_slice(&(checksum.1 << 16 | checksum.0).to_be_bytes());
+
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(match channels {
+        3 => png::ColorType::RGB,
+        _ => png::ColorType::RGBA,
+    });
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    crate::render::write_dpi(&mut writer, dpi)?;
+    writer.write_chunk(png::chunk::IDAT, &data)?;
+
+    Ok(())
+}
+
+/// Returns row, whose pixels have channels bytes, filtered against the row
+/// above it (if any) with whichever PNG filter leaves the smallest sum of
+/// absolute differences, prefixed by the filter's type.
+fn filter(row: &[u8], previous: Option<&[u8]>, channels: usize) -> Vec<u8> {
+    let zeros = vec![0; row.len()];
+    let up = previous.unwrap_or(&zeros);
+    let c = channels.min(row.len());
+    let (mut best, mut best_sum) = (Vec::new(), u64::MAX);
+    let mut filtered = vec![0; row.len() + 1];
+    for kind in 0..5 {
+        filtered[0] = kind;
+        let out = &mut filtered[1..];
+        // The first pixel has no left neighbors, which count as 0.
+        for i in 0..c {
+            out[i] = row[i].wrapping_sub(match kind {
+                0 | 1 => 0,
+                2 | 4 => up[i],
+                _ => up[i] / 2,
+            });
+        }
+        let rest = out[c..]
+            .iter_mut()
+            .zip(row[c..].iter().zip(up[c..].iter()))
+            .zip(row.iter().zip(up.iter()));
+        match kind {
+            0 => out[c..].copy_from_slice(&row[c..]),
+            1 => rest.for_each(|((o, (x, _)), (a, _))| *o = x.wrapping_sub(*a)),
+            2 => rest.for_each(|((o, (x, b)), _)| *o = x.wrapping_sub(*b)),
+            3 => rest.for_each(|((o, (x, b)), (a, _))| {
+                *o = x.wrapping_sub(((*a as u16 + *b as u16) / 2) as u8)
+            }),
+            _ => rest.for_each(|((o, (x, b)), (a, c))| *o = x.wrapping_sub(paeth(*a, *b, *c))),
+        }
+
+        let sum = out.iter().map(|f| (*f as i8).unsigned_abs() as u64).sum();
+        if sum < best_sum {
+            best_sum = sum;
+            std::mem::swap(&mut best, &mut filtered);
+            filtered.resize(row.len() + 1, 0);
+        }
+    }
+
+    best
+}
+
+/// Returns the PNG Paeth predictor of a byte whose left, upper, and upper left
+/// neighbors are a, b, and c.
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Returns rows compressed as deflate blocks, ending in a sync flush so that
+/// the next group's blocks can follow them, or in the stream's final block if
+/// last.
+fn compress(rows: &[Vec<u8>], last: bool) -> std::io::Result<Vec<u8>> {
+    let output = Shared::default();
+    let mut encoder = DeflateEncoder::new(output.clone(), Compression::Fast);
+    for r in rows {
+        encoder.write_all(r)?;
+    }
+    if last {
+        encoder.finish()?;
+        return Ok(output.take());
+    }
+
+    encoder.flush()?;
+    // Finishing the encoder would end the stream, so only the output up to
+    // the sync flush is kept.
+    let length = output.len();
+    encoder.finish()?;
+    let mut blocks = output.take();
+    blocks.truncate(length);
+
+    Ok(blocks)
+}
+
+/// Returns the Adler-32 sums (a, b) updated with bytes.
+fn adler32((mut a, mut b): (u32, u32), bytes: &[u8]) -> (u32, u32) {
+    const MODULUS: u32 = 65521;
+    // Sums of up to 5552 bytes cannot overflow before they are reduced.
+    for chunk in bytes.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+
+    (a, b)
+}
+
+/// A buffer shared between an encoder and its caller, which can read how much
+/// the encoder has written while it still owns the buffer.
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+
+impl Shared {
+    /// Returns the number of bytes written.
+    fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    /// Returns the bytes written, leaving the buffer empty.
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut self.0.borrow_mut())
+    }
+}
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,1115 @@
+use std::{fmt::Write, path::PathBuf};
+
+use crate::{
+    irregular, shape, Affine, Axis, Blend, Color, Corner, EdgeStyle, Error, Error::*, Fill, Fit,
+    Outline, PaintMode, Point, Polygon, Result, Shape, Style, StyleSet, Texture, Tile, Winding,
+};
+
+/// The name of the struct a tiling file holds.
+pub(crate) const MAGIC: &str = "Tiling";
+
+/// The version of the tiling file format, which changes only when the meaning
+/// of an existing field changes; fields a reader does not know are skipped.
+pub(crate) const VERSION: u32 = 1;
+
+/// The blend modes a shape may be composited with, by their variant names.
+const BLENDS: [(Blend, &str); 6] = [
+    (Blend::Normal, "Normal"),
+    (Blend::Multiply, "Multiply"),
+    (Blend::Screen, "Screen"),
+    (Blend::Overlay, "Overlay"),
+    (Blend::Darken, "Darken"),
+    (Blend::Lighten, "Lighten"),
+];
+
+/// A value of a tiling file, which is written in RON (Rusty Object
+/// Notation).
+/// Numbers keep the text they were written as, so each is parsed as the type
+/// it is read as without losing precision.
+#[derive(Clone, Debug)]
+pub(crate) enum Value {
+    Bool(bool),
+    Number(String),
+    Text(String),
+    /// A unit variant, such as `None` or `Miter`.
+    Unit(String),
+    /// A tuple variant, such as `Some(1.5)` or `Round(0.5)`.
+    Variant(String, Vec<Value>),
+    Tuple(Vec<Value>),
+    List(Vec<Value>),
+    Struct(Fields),
+}
+
+impl Value {
+    /// Writes the value to file as RON, with the items of lists of structs
+    /// and the fields of structs holding such lists on lines of their own,
+    /// indented by indent levels.
+    pub(crate) fn write(&self, file: &mut String, indent: usize) {
+        let pad = |file: &mut String, indent: usize| file.push_str(&"    ".repeat(indent));
+        // Writing to a String cannot fail.
+        match self {
+            Value::Bool(b) => {
+                let _ = write!(file, "{}", b);
+            }
+            Value::Number(n) => file.push_str(n),
+            Value::Text(text) => quote(file, text),
+            Value::Unit(name) => file.push_str(name),
+            Value::Variant(name, items) => {
+                file.push_str(name);
+                write_items(file, "(", items, ")", indent);
+            }
+            Value::Tuple(items) => write_items(file, "(", items, ")", indent),
+            Value::List(items) if items.iter().any(|v| matches!(v, Value::Struct(_))) => {
+                file.push_str("[\n");
+                for item in items {
+                    pad(file, indent + 1);
+                    item.write(file, indent + 1);
+                    file.push_str(",\n");
+                }
+                pad(file, indent);
+                file.push(']');
+            }
+            Value::List(items) => write_items(file, "[", items, "]", indent),
+            Value::Struct(fields) => {
+                file.push_str(fields.name.as_deref().unwrap_or_default());
+                let nested = fields.fields.iter().any(|(_, v)| match v {
+                    Value::List(items) => items.iter().any(|v| matches!(v, Value::Struct(_))),
+                    _ => false,
+                });
+                if !nested {
+                    file.push('(');
+                    for (i, (name, value)) in fields.fields.iter().enumerate() {
+                        if i > 0 {
+                            file.push_str(", ");
+                        }
+                        let _ = write!(file, "{}: ", name);
+                        value.write(file, indent);
+                    }
+                    file.push(')');
+                    return;
+                }
+
+                file.push_str("(\n");
+                for (name, value) in fields.fields.iter() {
+                    pad(file, indent + 1);
+                    let _ = write!(file, "{}: ", name);
+                    value.write(file, indent + 1);
+                    file.push_str(",\n");
+                }
+                pad(file, indent);
+                file.push(')');
+            }
+        }
+    }
+}
+
+/// Writes items to file on one line, separated by commas and enclosed by
+/// open and close.
+fn write_items(file: &mut String, open: &str, items: &[Value], close: &str, indent: usize) {
+    file.push_str(open);
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            file.push_str(", ");
+        }
+        item.write(file, indent);
+    }
+    file.push_str(close);
+}
+
+/// Writes text to file as a quoted string, escaping quotes, backslashes, and
+/// control characters.
+fn quote(file: &mut String, text: &str) {
+    file.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => file.push_str("\\\""),
+            '\\' => file.push_str("\\\\"),
+            '\n' => file.push_str("\\n"),
+            '\t' => file.push_str("\\t"),
+            '\r' => file.push_str("\\r"),
+            c if c.is_control() => {
+                // Writing to a String cannot fail.
+                let _ = write!(file, "\\u{{{:x}}}", c as u32);
+            }
+            c => file.push(c),
+        }
+    }
+    file.push('"');
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Value {
+        Value::Text(text.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Value {
+        Value::Text(text)
+    }
+}
+
+impl From<Color> for Value {
+    fn from(color: Color) -> Value {
+        Value::Text(color.to_hex())
+    }
+}
+
+impl From<Point> for Value {
+    fn from(point: Point) -> Value {
+        Value::Tuple(vec![point.x.into(), point.y.into()])
+    }
+}
+
+impl From<Fields> for Value {
+    fn from(fields: Fields) -> Value {
+        Value::Struct(fields)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Value {
+        Value::List(items.into_iter().map(|v| v.into()).collect())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Value {
+        match value {
+            Some(v) => Value::Variant(String::from("Some"), vec![v.into()]),
+            None => Value::Unit(String::from("None")),
+        }
+    }
+}
+
+/// Implements conversions between values and numbers of each type.
+macro_rules! numbers {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(n: $t) -> Value {
+                    Value::Number(n.to_string())
+                }
+            }
+
+            impl FromValue for $t {
+                fn from_value(value: &Value) -> Option<$t> {
+                    match value {
+                        // Numbers that are not finite are written as words.
+                        Value::Number(n) | Value::Unit(n) => n.parse().ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+numbers!(f64, i32, u32, u64, usize);
+
+/// A type that can be read from a value of a tiling file.
+pub(crate) trait FromValue: Sized {
+    /// Returns the value read as the type, or none if it holds another.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Option<Value> {
+        Some(value.clone())
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Option<bool> {
+        match value {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Option<String> {
+        match value {
+            Value::Text(text) => Some(text.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Color {
+    fn from_value(value: &Value) -> Option<Color> {
+        Color::from_hex(&String::from_value(value)?).ok()
+    }
+}
+
+impl FromValue for Point {
+    fn from_value(value: &Value) -> Option<Point> {
+        match value {
+            Value::Tuple(items) if items.len() == 2 => Some(Point {
+                x: f64::from_value(&items[0])?,
+                y: f64::from_value(&items[1])?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Fields {
+    fn from_value(value: &Value) -> Option<Fields> {
+        match value {
+            Value::Struct(fields) => Some(fields.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Option<Vec<T>> {
+        match value {
+            Value::List(items) => items.iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Option<Option<T>> {
+        match value {
+            Value::Unit(name) if name == "None" => Some(None),
+            Value::Variant(name, items) if name == "Some" && items.len() == 1 => {
+                T::from_value(&items[0]).map(Some)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The fields of a struct of a tiling file, with its name, if it has one,
+/// and the line it starts on.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Fields {
+    line: usize,
+    name: Option<String>,
+    fields: Vec<(String, Value)>,
+}
+
+impl Fields {
+    /// Returns a struct with the name and no fields.
+    pub(crate) fn new(name: &str) -> Fields {
+        Fields {
+            name: Some(name.to_string()),
+            ..Fields::default()
+        }
+    }
+
+    /// Returns a struct without a name and with no fields.
+    pub(crate) fn anonymous() -> Fields {
+        Fields::default()
+    }
+
+    /// Returns the struct with the field name set to value.
+    pub(crate) fn with<V: Into<Value>>(mut self, name: &str, value: V) -> Fields {
+        self.fields.push((name.to_string(), value.into()));
+
+        self
+    }
+
+    /// Returns the struct with the field name set to value if it is some, or
+    /// as it is if it is none.
+    pub(crate) fn with_some<V: Into<Value>>(self, name: &str, value: Option<V>) -> Fields {
+        match value {
+            Some(v) => self.with(name, v),
+            None => self,
+        }
+    }
+
+    /// Returns the struct's name, if it has one.
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the value of the field name, if the struct has it.
+    pub(crate) fn value(&self, name: &str) -> Option<&Value> {
+        self.fields.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    /// Returns the field name read as a T.
+    pub(crate) fn get<T: FromValue>(&self, name: &str) -> Result<T> {
+        let value = self
+            .value(name)
+            .ok_or_else(|| self.error(format!("missing field {}", name)))?;
+
+        T::from_value(value).ok_or_else(|| self.error(format!("invalid {} {:?}", name, value)))
+    }
+
+    /// Returns the field name read as a T, or none if the struct does not
+    /// have it.
+    pub(crate) fn optional<T: FromValue>(&self, name: &str) -> Result<Option<T>> {
+        match self.value(name) {
+            Some(_) => self.get(name).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the field name read as a T, or default if the struct does not
+    /// have it.
+    pub(crate) fn get_or<T: FromValue>(&self, name: &str, default: T) -> Result<T> {
+        match self.value(name) {
+            Some(_) => self.get(name),
+            None => Ok(default),
+        }
+    }
+
+    /// Returns the name of the variant in the field name and the values it
+    /// holds, or none if the struct does not have it.
+    pub(crate) fn variant(&self, name: &str) -> Result<Option<(&str, &[Value])>> {
+        match self.value(name) {
+            Some(Value::Unit(v)) => Ok(Some((v.as_str(), &[]))),
+            Some(Value::Variant(v, items)) => Ok(Some((v.as_str(), items.as_slice()))),
+            Some(value) => Err(self.error(format!("invalid {} {:?}", name, value))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns an error describing why the struct is invalid.
+    pub(crate) fn error(&self, reason: String) -> Error {
+        InvalidTilingFile {
+            line: self.line,
+            reason,
+        }
+    }
+}
+
+/// Returns the value written in text as RON.
+pub(crate) fn parse(text: &str) -> Result<Value> {
+    let mut parser = Parser {
+        text: text.as_bytes(),
+        position: 0,
+        line: 1,
+    };
+    let value = parser.value()?;
+    parser.skip();
+    if parser.position < parser.text.len() {
+        return Err(parser.error("unexpected text after the value"));
+    }
+
+    Ok(value)
+}
+
+/// A parser of RON text, at a byte position on a line of it.
+struct Parser<'a> {
+    text: &'a [u8],
+    position: usize,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Returns the byte at the position, if there is one.
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).copied()
+    }
+
+    /// Moves past the byte at the position, counting lines.
+    fn bump(&mut self) {
+        if self.peek() == Some(b'\n') {
+            self.line += 1;
+        }
+        self.position += 1;
+    }
+
+    /// Moves past whitespace and comments.
+    fn skip(&mut self) {
+        loop {
+            match (self.peek(), self.text.get(self.position + 1)) {
+                (Some(b), _) if b.is_ascii_whitespace() => self.bump(),
+                (Some(b'/'), Some(b'/')) => {
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.bump();
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    self.position += 2;
+                    while self.peek().is_some() && !self.text[self.position..].starts_with(b"*/") {
+                        self.bump();
+                    }
+                    self.position += 2;
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Moves past the byte expected, or fails if another is next.
+    fn expect(&mut self, expected: u8) -> Result<()> {
+        self.skip();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected {:?}", expected as char)));
+        }
+        self.bump();
+
+        Ok(())
+    }
+
+    /// Returns the text of the bytes from start to the position.
+    fn since(&self, start: usize) -> String {
+        String::from_utf8_lossy(&self.text[start..self.position]).into_owned()
+    }
+
+    /// Returns the next value.
+    fn value(&mut self) -> Result<Value> {
+        self.skip();
+        match self.peek() {
+            Some(b'"') => self.text().map(Value::Text),
+            Some(b'[') => {
+                self.bump();
+                self.items(b']').map(Value::List)
+            }
+            Some(b'(') => self.body(None),
+            Some(b) if b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.') => {
+                let start = self.position;
+                while matches!(self.peek(), Some(b) if b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'+' | b'_'))
+                {
+                    self.bump();
+                }
+                Ok(Value::Number(self.since(start)))
+            }
+            Some(b) if b.is_ascii_alphabetic() || b == b'_' => {
+                let name = self.identifier();
+                match name.as_str() {
+                    "true" => return Ok(Value::Bool(true)),
+                    "false" => return Ok(Value::Bool(false)),
+                    _ => {}
+                }
+                self.skip();
+                match self.peek() {
+                    Some(b'(') => self.body(Some(name)),
+                    _ => Ok(Value::Unit(name)),
+                }
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    /// Returns the identifier at the position.
+    fn identifier(&mut self) -> String {
+        let start = self.position;
+        while matches!(self.peek(), Some(b) if b.is_ascii_alphanumeric() || b == b'_') {
+            self.bump();
+        }
+
+        self.since(start)
+    }
+
+    /// Returns the values up to the byte close, separated by commas.
+    fn items(&mut self, close: u8) -> Result<Vec<Value>> {
+        let mut items = Vec::new();
+        loop {
+            self.skip();
+            if self.peek() == Some(close) {
+                self.bump();
+                return Ok(items);
+            }
+            items.push(self.value()?);
+            self.skip();
+            match self.peek() {
+                Some(b',') => self.bump(),
+                Some(b) if b == close => {}
+                _ => return Err(self.error(&format!("expected ',' or {:?}", close as char))),
+            }
+        }
+    }
+
+    /// Returns the struct, tuple, or tuple variant named name whose
+    /// parentheses start at the position.
+    fn body(&mut self, name: Option<String>) -> Result<Value> {
+        let line = self.line;
+        self.expect(b'(')?;
+        self.skip();
+        // A struct's first field is an identifier followed by a colon.
+        let start = (self.position, self.line);
+        let field = self.identifier();
+        self.skip();
+        let is_struct = !field.is_empty() && self.peek() == Some(b':');
+        self.position = start.0;
+        self.line = start.1;
+        if !is_struct {
+            let items = self.items(b')')?;
+            return Ok(match name {
+                Some(name) => Value::Variant(name, items),
+                None => Value::Tuple(items),
+            });
+        }
+
+        let mut fields = Vec::new();
+        loop {
+            self.skip();
+            if self.peek() == Some(b')') {
+                self.bump();
+                break;
+            }
+            let field = self.identifier();
+            if field.is_empty() {
+                return Err(self.error("expected a field name"));
+            }
+            self.expect(b':')?;
+            fields.push((field, self.value()?));
+            self.skip();
+            match self.peek() {
+                Some(b',') => self.bump(),
+                Some(b')') => {}
+                _ => return Err(self.error("expected ',' or ')'")),
+            }
+        }
+
+        Ok(Value::Struct(Fields { line, name, fields }))
+    }
+
+    /// Returns the text of the quoted string at the position.
+    fn text(&mut self) -> Result<String> {
+        self.bump();
+        let mut bytes = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.bump();
+                    break;
+                }
+                Some(b'\\') => {
+                    self.bump();
+                    let escaped = match self.peek() {
+                        Some(b'n') => '\n',
+                        Some(b't') => '\t',
+                        Some(b'r') => '\r',
+                        Some(b'0') => '\0',
+                        Some(b'u') => {
+                            self.bump();
+                            self.expect(b'{')?;
+                            let start = self.position;
+                            while matches!(self.peek(), Some(b) if b.is_ascii_hexdigit()) {
+                                self.bump();
+                            }
+                            let code = u32::from_str_radix(&self.since(start), 16).ok();
+                            if self.peek() != Some(b'}') {
+                                return Err(self.error("invalid unicode escape"));
+                            }
+                            code.and_then(char::from_u32)
+                                .ok_or_else(|| self.error("invalid unicode escape"))?
+                        }
+                        Some(b) if matches!(b, b'"' | b'\\' | b'/') => b as char,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.bump();
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut buffer).as_bytes());
+                }
+                Some(b) => {
+                    bytes.push(b);
+                    self.bump();
+                }
+            }
+        }
+
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8 in string"))
+    }
+
+    /// Returns an error describing why the text is invalid at the position.
+    fn error(&self, reason: &str) -> Error {
+        InvalidTilingFile {
+            line: self.line,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Returns the struct recording shape: its number of sides, center, and
+/// rotation, followed by its style as `write_style` records it.
+pub(crate) fn write_shape(shape: &Shape) -> Fields {
+    let fields = Fields::new("Shape")
+        .with("sides", shape.sides())
+        .with("x", shape.point().x)
+        .with("y", shape.point().y)
+        .with("rotation", shape.rotation());
+
+    write_styling(fields, shape)
+}
+
+/// Returns the struct recording the style of shape, one of the styles a
+/// model interns: its fill and stroke, followed by its rendering overrides.
+pub(crate) fn write_style(shape: &Shape) -> Fields {
+    write_styling(Fields::new("Style"), shape)
+}
+
+/// Returns fields with the fill, stroke, and rendering overrides of shape
+/// added.
+fn write_styling(fields: Fields, shape: &Shape) -> Fields {
+    let corner = shape.corner().map(|corner| match corner {
+        Corner::Miter => Value::Unit(String::from("Miter")),
+        Corner::Round(radius) => Value::Variant(String::from("Round"), vec![radius.into()]),
+        Corner::Bevel(distance) => Value::Variant(String::from("Bevel"), vec![distance.into()]),
+    });
+    let outline = shape.outline().map(|outline| {
+        Fields::anonymous()
+            .with("width", outline.width())
+            .with("color", outline.color())
+    });
+    let transform = shape.transform().map(|transform| {
+        let (xx, xy, yx, yy) = transform.components();
+        Value::Tuple(vec![xx.into(), xy.into(), yx.into(), yy.into()])
+    });
+    let blend = shape
+        .blend()
+        .and_then(|blend| BLENDS.iter().find(|(b, _)| *b == blend))
+        .map(|(_, name)| Value::Unit(name.to_string()));
+    let winding = match shape.winding() {
+        Winding::Clockwise => None,
+        Winding::CounterClockwise => Some(Value::Unit(String::from("CounterClockwise"))),
+    };
+
+    fields
+        .with("fill", shape.fill())
+        .with("stroke", shape.stroke())
+        .with_some("margin", shape.margin())
+        .with_some("corner", corner)
+        .with_some("paint", shape.paint().map(paint))
+        .with_some("outline", outline)
+        .with_some("transform", transform)
+        .with_some("opacity", shape.opacity())
+        .with_some("blend", blend)
+        .with_some("winding", winding)
+}
+
+/// Returns the variant paint is written as.
+fn paint(paint: PaintMode) -> Value {
+    Value::Unit(String::from(match paint {
+        PaintMode::FillAndStroke => "FillAndStroke",
+        PaintMode::Fill => "Fill",
+        PaintMode::Stroke => "Stroke",
+    }))
+}
+
+/// Returns the paint mode written in the field name of fields, if it has it.
+fn read_paint(fields: &Fields, name: &str) -> Result<Option<PaintMode>> {
+    Ok(match fields.variant(name)? {
+        Some(("FillAndStroke", [])) => Some(PaintMode::FillAndStroke),
+        Some(("Fill", [])) => Some(PaintMode::Fill),
+        Some(("Stroke", [])) => Some(PaintMode::Stroke),
+        Some((paint, _)) => return Err(fields.error(format!("invalid paint {:?}", paint))),
+        None => None,
+    })
+}
+
+/// Returns the shape recorded in fields by `write_shape`.
+pub(crate) fn read_shape(fields: &Fields) -> Result<Shape> {
+    let center = Point {
+        x: fields.get("x")?,
+        y: fields.get("y")?,
+    };
+    let (fill, stroke) = (fields.get("fill")?, fields.get("stroke")?);
+    let shape = Shape::new(fields.get("sides")?, fill, stroke)
+        .map_err(|err| fields.error(err.to_string()))?
+        .placed_at(center, fields.get("rotation")?);
+
+    read_styling(fields, shape)
+}
+
+/// Returns a triangle styled with the style recorded in fields by
+/// `write_style`.
+pub(crate) fn read_style(fields: &Fields) -> Result<Shape> {
+    let shape = Shape::new(shape::MIN_SIDES, fields.get("fill")?, fields.get("stroke")?)?;
+
+    read_styling(fields, shape)
+}
+
+/// Returns shape with the rendering overrides recorded in fields.
+fn read_styling(fields: &Fields, mut shape: Shape) -> Result<Shape> {
+    if let Some(margin) = fields.optional("margin")? {
+        shape = shape.with_margin(margin);
+    }
+    if let Some((corner, sizes)) = fields.variant("corner")? {
+        let invalid = || fields.error(format!("invalid corner {:?}", corner));
+        let size = || match sizes {
+            [size] => f64::from_value(size).ok_or_else(invalid),
+            _ => Err(invalid()),
+        };
+        shape = shape.with_corner(match corner {
+            "Miter" if sizes.is_empty() => Corner::Miter,
+            "Round" => Corner::Round(size()?),
+            "Bevel" => Corner::Bevel(size()?),
+            _ => return Err(invalid()),
+        });
+    }
+    if let Some(paint) = read_paint(fields, "paint")? {
+        shape = shape.with_paint(paint);
+    }
+    if let Some(outline) = fields.optional::<Fields>("outline")? {
+        shape = shape.with_outline(Outline::new(outline.get("width")?, outline.get("color")?));
+    }
+    if let Some(m) = fields.optional::<Value>("transform")? {
+        let m = match m {
+            Value::Tuple(m) if m.len() == 4 => {
+                m.iter().map(f64::from_value).collect::<Option<Vec<f64>>>()
+            }
+            _ => None,
+        }
+        .ok_or_else(|| fields.error(String::from("transform needs 4 components")))?;
+        shape = shape.with_transform(Affine::new(m[0], m[1], m[2], m[3]));
+    }
+    if let Some(opacity) = fields.optional("opacity")? {
+        shape = shape.with_opacity(opacity);
+    }
+    if let Some((blend, _)) = fields.variant("blend")? {
+        shape = shape.with_blend(
+            BLENDS
+                .iter()
+                .find(|(_, name)| *name == blend)
+                .map(|(b, _)| *b)
+                .ok_or_else(|| fields.error(format!("invalid blend {:?}", blend)))?,
+        );
+    }
+    match fields.variant("winding")? {
+        Some(("Clockwise", [])) | None => {}
+        Some(("CounterClockwise", [])) => shape = shape.with_winding(Winding::CounterClockwise),
+        Some((winding, _)) => return Err(fields.error(format!("invalid winding {:?}", winding))),
+    }
+
+    Ok(shape)
+}
+
+/// Returns the struct recording style, the style of the edge with index edge
+/// of the model shape with index index.
+pub(crate) fn write_edge(index: usize, edge: usize, style: &EdgeStyle) -> Fields {
+    let dashes = style
+        .dashes()
+        .map(|(on, off)| Value::Tuple(vec![on.into(), off.into()]));
+
+    Fields::new("Edge")
+        .with("shape", index)
+        .with("edge", edge)
+        .with("hidden", style.is_hidden())
+        .with_some("color", style.stroke())
+        .with_some("dashes", dashes)
+}
+
+/// Returns the shape index, edge index, and style recorded in fields by
+/// `write_edge`.
+pub(crate) fn read_edge(fields: &Fields) -> Result<(usize, usize, EdgeStyle)> {
+    let mut style = match fields.optional::<Point>("dashes")? {
+        Some(d) => EdgeStyle::dashed(d.x, d.y),
+        None if fields.get_or("hidden", false)? => EdgeStyle::hidden(),
+        None => EdgeStyle::solid(),
+    };
+    if let Some(color) = fields.optional("color")? {
+        style = style.color(color);
+    }
+
+    Ok((fields.get("shape")?, fields.get("edge")?, style))
+}
+
+/// Returns the struct recording set, the model's style set named name, with
+/// each style it gives by the index or number of sides of the shapes it
+/// gives it to, and its fallback.
+pub(crate) fn write_style_set(name: &str, set: &StyleSet) -> Fields {
+    let style = |fields: Fields, style: &Style| {
+        fields
+            .with("fill", style.fill())
+            .with("stroke", style.stroke())
+    };
+    let mut shapes = set.shape_styles().iter().collect::<Vec<(&usize, &Style)>>();
+    shapes.sort_by_key(|(index, _)| **index);
+    let shapes = shapes
+        .into_iter()
+        .map(|(index, s)| style(Fields::anonymous().with("shape", *index), s))
+        .collect::<Vec<Fields>>();
+    let sides = set
+        .side_styles()
+        .iter()
+        .map(|(sides, s)| style(Fields::anonymous().with("sides", *sides), s))
+        .collect::<Vec<Fields>>();
+
+    Fields::new("StyleSet")
+        .with("name", name)
+        .with("shapes", shapes)
+        .with("sides", sides)
+        .with_some(
+            "fallback",
+            set.fallback_style().map(|s| style(Fields::anonymous(), &s)),
+        )
+}
+
+/// Returns the name of the style set recorded in fields by `write_style_set`
+/// and the set.
+pub(crate) fn read_style_set(fields: &Fields) -> Result<(String, StyleSet)> {
+    let style = |f: &Fields| Ok::<Style, Error>(Style::new(f.get("fill")?, f.get("stroke")?));
+    let mut set = StyleSet::new();
+    for s in fields.get_or::<Vec<Fields>>("shapes", Vec::new())? {
+        set = set.shape(s.get("shape")?, style(&s)?);
+    }
+    for s in fields.get_or::<Vec<Fields>>("sides", Vec::new())? {
+        set = set.sides(s.get("sides")?, style(&s)?);
+    }
+    if let Some(s) = fields.optional::<Fields>("fallback")? {
+        set = set.fallback(style(&s)?);
+    }
+
+    Ok((fields.get("name")?, set))
+}
+
+/// Returns the struct recording fill, the image or texture overlaid on the
+/// model shape with index index.
+pub(crate) fn write_overlay(index: usize, fill: &Fill) -> Fields {
+    let fill = match fill {
+        Fill::Color(color) => Value::Variant(String::from("Color"), vec![(*color).into()]),
+        Fill::Image(path, fit) => {
+            let fit = match fit {
+                Fit::Cover => "Cover",
+                Fit::Contain => "Contain",
+                Fit::Canvas => "Canvas",
+            };
+            Fields::new("Image")
+                .with("path", path.to_string_lossy().into_owned())
+                .with("fit", Value::Unit(fit.to_string()))
+                .into()
+        }
+        Fill::Texture(texture, ink) => match *texture {
+            Texture::Hatch { angle, spacing } => Fields::new("Hatch")
+                .with("angle", angle)
+                .with("spacing", spacing),
+            Texture::CrossHatch { angle, spacing } => Fields::new("CrossHatch")
+                .with("angle", angle)
+                .with("spacing", spacing),
+            Texture::Dots { spacing, radius } => Fields::new("Dots")
+                .with("spacing", spacing)
+                .with("radius", radius),
+        }
+        .with("ink", *ink)
+        .into(),
+    };
+
+    Fields::new("Overlay")
+        .with("shape", index)
+        .with("fill", fill)
+}
+
+/// Returns the shape index and fill recorded in fields by `write_overlay`.
+pub(crate) fn read_overlay(fields: &Fields) -> Result<(usize, Fill)> {
+    let fill = match fields.value("fill") {
+        Some(Value::Variant(name, color)) if name == "Color" && color.len() == 1 => Fill::Color(
+            Color::from_value(&color[0])
+                .ok_or_else(|| fields.error(String::from("invalid overlay color")))?,
+        ),
+        Some(Value::Struct(f)) => match f.name() {
+            Some("Image") => {
+                let fit = match f.variant("fit")? {
+                    Some(("Cover", [])) => Fit::Cover,
+                    Some(("Contain", [])) => Fit::Contain,
+                    Some(("Canvas", [])) => Fit::Canvas,
+                    fit => return Err(f.error(format!("invalid fit {:?}", fit))),
+                };
+                Fill::Image(PathBuf::from(f.get::<String>("path")?), fit)
+            }
+            Some("Hatch") => Fill::Texture(
+                Texture::Hatch {
+                    angle: f.get("angle")?,
+                    spacing: f.get("spacing")?,
+                },
+                f.get("ink")?,
+            ),
+            Some("CrossHatch") => Fill::Texture(
+                Texture::CrossHatch {
+                    angle: f.get("angle")?,
+                    spacing: f.get("spacing")?,
+                },
+                f.get("ink")?,
+            ),
+            Some("Dots") => Fill::Texture(
+                Texture::Dots {
+                    spacing: f.get("spacing")?,
+                    radius: f.get("radius")?,
+                },
+                f.get("ink")?,
+            ),
+            name => return Err(f.error(format!("invalid overlay {:?}", name))),
+        },
+        fill => return Err(fields.error(format!("invalid overlay {:?}", fill))),
+    };
+
+    Ok((fields.get("shape")?, fill))
+}
+
+/// A tile read from a tiling file: the rings outlining a tile that was saved,
+/// which may be of any type, and the colors it was painted with.
+#[derive(Clone, Debug)]
+pub(crate) struct SavedTile {
+    center: Point,
+    rings: Vec<Vec<Point>>,
+    fill: Color,
+    stroke: Color,
+    paint: PaintMode,
+}
+
+impl SavedTile {
+    /// Returns a copy of the tile with f applied to its center and each point
+    /// of its rings.
+    fn mapped<F: Fn(Point) -> Point>(&self, f: F) -> Box<dyn Tile> {
+        let mut t = self.clone();
+        t.center = f(self.center);
+        t.rings = self
+            .rings
+            .iter()
+            .map(|r| r.iter().map(|p| f(*p)).collect())
+            .collect();
+
+        Box::new(t)
+    }
+}
+
+impl Polygon for SavedTile {
+    /// Returns the polygon's points.
+    fn points(&self, margin: f64) -> Result<Vec<Point>> {
+        irregular::inset(&self.rings[0], margin)
+    }
+
+    /// Renders the polygon, filling the space between its rings so that holes
+    /// are left empty.
+    #[cfg(feature = "cairo")]
+    fn render(&self, context: &cairo::Context, margin: f64) -> Result<()> {
+        self.render_snapped(context, margin, None)
+    }
+
+    /// Renders the polygon as `render` does, with its stroke snapped to the
+    /// centers of pixels pixel device units wide if pixel is some.
+    #[cfg(feature = "cairo")]
+    fn render_snapped(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        let rings = self.rings(margin)?;
+        context.save()?;
+        context.set_fill_rule(cairo::FillRule::EvenOdd);
+        let painted = shape::paint_path(
+            context,
+            self.fill,
+            self.stroke,
+            self.paint,
+            pixel,
+            |pixel| {
+                for ring in rings.iter() {
+                    shape::trace_snapped(context, ring, pixel);
+                    context.close_path();
+                }
+            },
+        );
+        context.restore()?;
+
+        painted
+    }
+}
+
+impl Tile for SavedTile {
+    /// Returns the tile's center, as it was saved.
+    fn point(&self) -> Point {
+        self.center
+    }
+
+    /// Returns a copy of the tile moved by vector.
+    fn translated(&self, vector: Point) -> Box<dyn Tile> {
+        self.mapped(|p| p + vector)
+    }
+
+    /// Returns a copy of the tile rotated by angle (in radians) about center.
+    fn rotated(&self, angle: f64, center: Point) -> Box<dyn Tile> {
+        self.mapped(|p| p.rotate(angle, center))
+    }
+
+    /// Returns a copy of the tile mirrored across axis, with its rings
+    /// reversed so they keep winding the same way.
+    fn reflected(&self, axis: &Axis) -> Box<dyn Tile> {
+        let mut t = self.clone();
+        t.center = axis.reflect(self.center);
+        t.rings = self
+            .rings
+            .iter()
+            .map(|r| r.iter().rev().map(|p| axis.reflect(*p)).collect())
+            .collect();
+
+        Box::new(t)
+    }
+
+    /// Returns a copy of the tile filled with fill.
+    fn filled(&self, fill: Color) -> Box<dyn Tile> {
+        let mut t = self.clone();
+        t.fill = fill;
+
+        Box::new(t)
+    }
+
+    /// Returns the color the tile is filled with.
+    fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the color the tile is stroked with.
+    fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns whether the tile is filled, stroked, or both.
+    fn paint(&self) -> PaintMode {
+        self.paint
+    }
+
+    /// Returns the closed rings outlining the tile inset by margin: its
+    /// outline and then its holes.
+    fn rings(&self, margin: f64) -> Result<Vec<Vec<Point>>> {
+        self.rings
+            .iter()
+            .map(|r| irregular::inset(r, margin))
+            .collect()
+    }
+}
+
+/// Returns the struct recording tile: its center, fill, stroke, and paint
+/// mode, and each of its rings as a list of points.
+pub(crate) fn write_tile(tile: &dyn Tile) -> Result<Fields> {
+    let paint = match tile.paint() {
+        PaintMode::FillAndStroke => None,
+        mode => Some(paint(mode)),
+    };
+
+    Ok(Fields::new("Tile")
+        .with("x", tile.point().x)
+        .with("y", tile.point().y)
+        .with("fill", tile.fill())
+        .with("stroke", tile.stroke())
+        .with_some("paint", paint)
+        .with("rings", tile.rings(0.0)?))
+}
+
+/// Returns the tile recorded in fields by `write_tile`.
+pub(crate) fn read_tile(fields: &Fields) -> Result<SavedTile> {
+    let rings = fields.get::<Vec<Vec<Point>>>("rings")?;
+    if rings.is_empty() || rings.iter().any(|r| r.len() < 4) {
+        return Err(fields.error(String::from("tile needs rings of at least 4 points")));
+    }
+
+    Ok(SavedTile {
+        center: Point {
+            x: fields.get("x")?,
+            y: fields.get("y")?,
+        },
+        rings,
+        fill: fields.get("fill")?,
+        stroke: fields.get("stroke")?,
+        paint: read_paint(fields, "paint")?.unwrap_or(PaintMode::FillAndStroke),
+    })
+}
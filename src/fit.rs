@@ -0,0 +1,560 @@
+//! Fitting models to images of tilings.
+//!
+//! `fit` reverse-engineers a raster image of a periodic tiling by regular
+//! polygons: it segments the image into regions of one color, recognizes the
+//! regular polygon each region is, stitches the polygons together edge to
+//! edge, and finds the lattice the tiling repeats along. The result is an
+//! editable `Model` of one period of the tiling, repeated over a canvas the
+//! size of the image.
+//!
+//! Tiles must be separated by strokes (or margins) or differ in color from
+//! their neighbors, since two touching tiles of one color read as a single
+//! region. Rounded corners, textures, and gradients are not recognized.
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    f64::consts::PI,
+};
+
+use crate::{quantize, Color, Error::*, Model, Point, Polygon, Render, Result, Shape};
+
+/// The sides of the polygons that appear in tilings by regular polygons,
+/// which are the only polygons that are recognized.
+const SIDES: [i32; 5] = [3, 4, 6, 8, 12];
+
+/// How far (in edge lengths) a region's center may be from where a polygon
+/// across an edge would be centered for the region to be taken as that
+/// polygon.
+const TOLERANCE: f64 = 0.2;
+
+/// The angle (in radians) that rotations close to a multiple of are snapped
+/// to the multiple.
+const SNAP: f64 = PI / 24.0;
+
+/// How close (in radians) a rotation must be to a multiple of `SNAP` to be
+/// snapped.
+const SNAP_TOLERANCE: f64 = 0.01;
+
+/// The magnitude of the harmonic of each polygon in `SIDES`, with as many
+/// turns as it has sides, of the angles of its points weighted by their
+/// squared distance from its center, relative to that weight.
+const HARMONICS: [f64; 5] = [0.5496, 0.2876, 0.1187, 0.0649, 0.0283];
+
+/// Configures how `fit` reads an image.
+#[derive(Clone, Copy, Debug)]
+pub struct FitOptions {
+    colors: usize,
+    min_area: usize,
+}
+
+impl FitOptions {
+    /// Returns the default options: the image is reduced to 8 colors and
+    /// regions smaller than 64 pixels are ignored.
+    pub fn new() -> FitOptions {
+        FitOptions {
+            colors: 8,
+            min_area: 64,
+        }
+    }
+
+    /// Sets the number of colors (between 2 and 256) the image is reduced to
+    /// before it is segmented, which should be at least the number of fills
+    /// plus the stroke and background.
+    pub fn colors(mut self, colors: usize) -> FitOptions {
+        self.colors = colors.clamp(2, 256);
+
+        self
+    }
+
+    /// Sets the area (in pixels) below which regions are ignored, such as the
+    /// antialiased fringes of strokes.
+    pub fn min_area(mut self, min_area: usize) -> FitOptions {
+        self.min_area = min_area.max(1);
+
+        self
+    }
+}
+
+impl Default for FitOptions {
+    fn default() -> FitOptions {
+        FitOptions::new()
+    }
+}
+
+/// A model fitted to an image of a tiling by `fit`.
+#[derive(Debug)]
+pub struct Fit {
+    model: Model,
+    lattice: (Point, Point),
+    fills: Vec<Color>,
+    stroke: Color,
+    configuration: Option<String>,
+}
+
+impl Fit {
+    /// Returns the fitted model: one period of the tiling, positioned as in
+    /// the image, repeated along the lattice over a canvas the size of the
+    /// image.
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Returns the fitted model, consuming the fit.
+    pub fn into_model(self) -> Model {
+        self.model
+    }
+
+    /// Returns the vectors (in edge lengths) the tiling repeats along.
+    pub fn lattice(&self) -> (Point, Point) {
+        self.lattice
+    }
+
+    /// Returns the fills of the tiling's period, in the order of its shapes.
+    pub fn fills(&self) -> &[Color] {
+        &self.fills
+    }
+
+    /// Returns the color of the strokes between tiles.
+    pub fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns the tiling's notation (see `Model::notation`), or none if the
+    /// fitted tiling has gaps or overlaps.
+    pub fn configuration(&self) -> Option<&str> {
+        self.configuration.as_deref()
+    }
+}
+
+/// A connected region of pixels of one palette color.
+struct Region {
+    color: usize,
+    area: usize,
+    sum: (f64, f64),
+    border: bool,
+    moment: f64,
+    harmonics: [(f64, f64); SIDES.len()],
+}
+
+impl Region {
+    /// Returns the region's centroid (in pixels).
+    fn center(&self) -> Point {
+        Point {
+            x: self.sum.0 / self.area as f64,
+            y: self.sum.1 / self.area as f64,
+        }
+    }
+
+    /// Returns the index in `SIDES` of the polygon the region is, or none if
+    /// it is none of them.
+    /// A polygon with n sides is symmetric under turns by 1/n, so only the
+    /// harmonics of its pixels' angles with a multiple of n turns survive:
+    /// the region is the polygon with the fewest sides whose harmonic is
+    /// strong enough.
+    fn kind(&self) -> Option<usize> {
+        (0..SIDES.len()).find(|k| {
+            let (re, im) = self.harmonics[*k];
+            (re * re + im * im).sqrt() > HARMONICS[*k] / 2.0 * self.moment
+        })
+    }
+
+    /// Returns the rotation of the region as a polygon of kind k.
+    /// Pixels far from the center gather in the polygon's corners, so the
+    /// angle of the polygon's harmonic points at a corner. Rotations close to
+    /// a multiple of `SNAP` are snapped to it, since tilings are usually drawn
+    /// square to the page.
+    fn rotation(&self, k: usize) -> f64 {
+        let (re, im) = self.harmonics[k];
+        let corner = im.atan2(re) / SIDES[k] as f64;
+        let rotation = (corner + PI / 2.0).rem_euclid(2.0 * PI);
+        let snapped = (rotation / SNAP).round() * SNAP;
+
+        match (rotation - snapped).abs() < SNAP_TOLERANCE {
+            true => snapped.rem_euclid(2.0 * PI),
+            false => rotation,
+        }
+    }
+}
+
+/// A region recognized as a polygon.
+struct Tile {
+    region: usize,
+    sides: i32,
+    rotation: f64,
+    center: Point,
+    fill: Color,
+}
+
+/// Returns the model of the periodic tiling by regular polygons in render,
+/// which is often read with `Render::from_png`.
+/// Fails if no region of the image is recognized as a polygon or the
+/// recognized polygons do not cover a full period of the tiling.
+pub fn fit(render: &Render, options: &FitOptions) -> Result<Fit> {
+    let (width, height) = (render.width() as usize, render.height() as usize);
+    let pixels = render
+        .as_rgba8()?
+        .chunks_exact(4)
+        .map(|p| [p[0], p[1], p[2], p[3]])
+        .collect::<Vec<[u8; 4]>>();
+    let mut counts: HashMap<[u8; 4], usize> = HashMap::new();
+    for p in pixels.iter() {
+        *counts.entry(*p).or_insert(0) += 1;
+    }
+    let palette = quantize::palette(&counts, options.colors);
+    let nearest = counts
+        .keys()
+        .map(|c| (*c, quantize::nearest(&palette, *c)))
+        .collect::<HashMap<[u8; 4], usize>>();
+    let colors = pixels.iter().map(|p| nearest[p]).collect::<Vec<usize>>();
+
+    let (labels, mut regions) = segment(&colors, width, height);
+    measure(&mut regions, &labels, width);
+    let kinds = regions
+        .iter()
+        .enumerate()
+        .filter(|(_, r)| r.area >= options.min_area && !r.border)
+        .filter_map(|(i, r)| Some((i, r.kind()?)))
+        .collect::<Vec<(usize, usize)>>();
+    let tiled = kinds.iter().map(|(i, _)| *i).collect::<HashSet<usize>>();
+    let strokes = strokes(&labels, &tiled, width, height);
+    let mut wanted = tiled.clone();
+    wanted.extend(strokes);
+    let modes = modes(&pixels, &labels, &wanted);
+    let color = |region: usize| {
+        let [r, g, b, _] = modes[&region];
+        Color::from_rgb8(r, g, b)
+    };
+    let stroke = strokes.map_or(Color::BLACK, color);
+    let tiles = kinds
+        .iter()
+        .map(|(i, k)| Tile {
+            region: *i,
+            sides: SIDES[*k],
+            rotation: regions[*i].rotation(*k),
+            center: regions[*i].center(),
+            fill: color(*i),
+        })
+        .collect::<Vec<Tile>>();
+    let scale = scale(&tiles).ok_or_else(|| Unfit {
+        reason: String::from("no region of the image is a regular polygon"),
+    })?;
+
+    let model = Model::new(width as i32, height as i32, scale);
+    let found = stitch(model, &tiles, stroke)?;
+    let lattice = lattice(&found).ok_or_else(|| Unfit {
+        reason: String::from("the tiling does not repeat within the image"),
+    })?;
+    let period = period(&found, lattice);
+    let area = period
+        .iter()
+        .map(|s| s.sides() as f64 / (4.0 * (PI / s.sides() as f64).tan()))
+        .sum::<f64>();
+    let cell = cross(lattice.0, lattice.1).abs();
+    if (area - cell).abs() > cell * 0.01 {
+        return Err(Unfit {
+            reason: format!(
+                "the polygons found cover {:.3} of the period's area of {:.3}",
+                area, cell
+            ),
+        });
+    }
+
+    let mut model = Model::new(width as i32, height as i32, scale);
+    for shape in period.iter() {
+        model.add(shape)?;
+    }
+    // Enough steps along the lattice reach the canvas's corners from its
+    // center.
+    let reach = ((width * width + height * height) as f64).sqrt() / 2.0 / scale;
+    let spacing = (cell / lattice.0.distance(Point::origin()))
+        .min(cell / lattice.1.distance(Point::origin()));
+    model.repeat_by(
+        &[lattice.0, lattice.1],
+        (reach / spacing).ceil() as usize + 1,
+    )?;
+    let configuration = model.notation()?;
+
+    Ok(Fit {
+        model,
+        lattice,
+        fills: period.iter().map(|s| s.fill()).collect(),
+        stroke,
+        configuration,
+    })
+}
+
+/// Returns the label of the region of each pixel and the regions of pixels
+/// of one color connected through their sides.
+fn segment(colors: &[usize], width: usize, height: usize) -> (Vec<usize>, Vec<Region>) {
+    let mut labels = vec![usize::MAX; colors.len()];
+    let mut regions = Vec::new();
+    for start in 0..colors.len() {
+        if labels[start] != usize::MAX {
+            continue;
+        }
+
+        let label = regions.len();
+        let mut region = Region {
+            color: colors[start],
+            area: 0,
+            sum: (0.0, 0.0),
+            border: false,
+            moment: 0.0,
+            harmonics: [(0.0, 0.0); SIDES.len()],
+        };
+        labels[start] = label;
+        let mut stack = vec![start];
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % width, i / width);
+            region.area += 1;
+            region.sum.0 += x as f64 + 0.5;
+            region.sum.1 += y as f64 + 0.5;
+            region.border |= x == 0 || y == 0 || x + 1 == width || y + 1 == height;
+            for j in neighbors(i, width, height).iter().flatten() {
+                if labels[*j] == usize::MAX && colors[*j] == region.color {
+                    labels[*j] = label;
+                    stack.push(*j);
+                }
+            }
+        }
+        regions.push(region);
+    }
+
+    (labels, regions)
+}
+
+/// Returns the indexes of the pixels beside pixel i, where they exist.
+fn neighbors(i: usize, width: usize, height: usize) -> [Option<usize>; 4] {
+    let (x, y) = (i % width, i / width);
+
+    [
+        (x > 0).then(|| i - 1),
+        (x + 1 < width).then(|| i + 1),
+        (y > 0).then(|| i - width),
+        (y + 1 < height).then(|| i + width),
+    ]
+}
+
+/// Measures each region's polar moment and, for each polygon in `SIDES`, the
+/// harmonic of its pixels' angles about its center with as many turns as the
+/// polygon has sides, each weighted by its squared distance from the center.
+fn measure(regions: &mut [Region], labels: &[usize], width: usize) {
+    let centers = regions.iter().map(|r| r.center()).collect::<Vec<Point>>();
+    for (i, label) in labels.iter().enumerate() {
+        let region = &mut regions[*label];
+        let (x, y) = ((i % width) as f64 + 0.5, (i / width) as f64 + 0.5);
+        let (dx, dy) = (x - centers[*label].x, y - centers[*label].y);
+        let r2 = dx * dx + dy * dy;
+        region.moment += r2;
+        let angle = dy.atan2(dx);
+        for (h, n) in region.harmonics.iter_mut().zip(SIDES.iter()) {
+            let (sin, cos) = (*n as f64 * angle).sin_cos();
+            h.0 += r2 * cos;
+            h.1 += r2 * sin;
+        }
+    }
+}
+
+/// Returns the apothem of a polygon with sides sides and edges of length 1.
+fn apothem(sides: i32) -> f64 {
+    0.5 / (PI / sides as f64).tan()
+}
+
+/// Returns the number of pixels per edge length, the median over tiles of the
+/// distance to the nearest tile over the distance between the centers of
+/// two such polygons sharing an edge, or none if there are fewer than two
+/// tiles.
+fn scale(tiles: &[Tile]) -> Option<f64> {
+    let mut scales = tiles
+        .iter()
+        .filter_map(|a| {
+            tiles
+                .iter()
+                .filter(|b| b.region != a.region)
+                .map(|b| {
+                    let d = a.center.distance(b.center);
+                    (d, d / (apothem(a.sides) + apothem(b.sides)))
+                })
+                .min_by(|p, q| p.0.total_cmp(&q.0))
+                .map(|(_, scale)| scale)
+        })
+        .collect::<Vec<f64>>();
+    if scales.is_empty() {
+        return None;
+    }
+    scales.sort_by(|a, b| a.total_cmp(b));
+
+    Some(scales[scales.len() / 2])
+}
+
+/// Returns the region of the strokes: the region that is not a tile with the
+/// most pixels beside tiles, or none if the tiles only touch each other.
+fn strokes(labels: &[usize], tiled: &HashSet<usize>, width: usize, height: usize) -> Option<usize> {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for (i, label) in labels.iter().enumerate() {
+        if tiled.contains(label) {
+            continue;
+        }
+        if neighbors(i, width, height)
+            .iter()
+            .flatten()
+            .any(|j| tiled.contains(&labels[*j]))
+        {
+            *counts.entry(*label).or_insert(0) += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .max_by_key(|(label, n)| (*n, usize::MAX - label))
+        .map(|(label, _)| label)
+}
+
+/// Returns the most common color of the pixels of each region in wanted,
+/// which for a region painted one color is that color, however its edges
+/// were antialiased.
+fn modes(pixels: &[[u8; 4]], labels: &[usize], wanted: &HashSet<usize>) -> HashMap<usize, [u8; 4]> {
+    let mut counts: HashMap<(usize, [u8; 4]), usize> = HashMap::new();
+    for (p, label) in pixels.iter().zip(labels.iter()) {
+        if wanted.contains(label) {
+            *counts.entry((*label, *p)).or_insert(0) += 1;
+        }
+    }
+
+    let mut modes: HashMap<usize, ([u8; 4], usize)> = HashMap::new();
+    for ((label, p), n) in counts {
+        let mode = modes.entry(label).or_insert((p, 0));
+        if n > mode.1 || (n == mode.1 && p < mode.0) {
+            *mode = (p, n);
+        }
+    }
+
+    modes
+        .into_iter()
+        .map(|(label, (p, _))| (label, p))
+        .collect()
+}
+
+/// Places the tile nearest the center of model's canvas and then, one edge at
+/// a time, attaches every tile centered across an edge of a placed shape, so
+/// that the shapes meet exactly.
+fn stitch(mut model: Model, tiles: &[Tile], stroke: Color) -> Result<Model> {
+    let centers = tiles
+        .iter()
+        .map(|t| model.canvas_to_model(t.center.x, t.center.y))
+        .collect::<Vec<Point>>();
+    let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, c) in centers.iter().enumerate() {
+        grid.entry((c.x.floor() as i64, c.y.floor() as i64))
+            .or_default()
+            .push(i);
+    }
+
+    let mut shapes = vec![None; tiles.len()];
+    let first = (0..tiles.len())
+        .min_by(|a, b| {
+            let (a, b) = (centers[*a], centers[*b]);
+            a.distance(Point::origin())
+                .total_cmp(&b.distance(Point::origin()))
+        })
+        .ok_or_else(|| Unfit {
+            reason: String::from("no region of the image is a regular polygon"),
+        })?;
+    let tile = &tiles[first];
+    model.add(
+        Shape::new(tile.sides, tile.fill, stroke)?
+            .rotated(tile.rotation, Point::origin())
+            .translated(centers[first]),
+    )?;
+    shapes[first] = Some(model.len() - 1);
+
+    let mut queue = VecDeque::from(vec![first]);
+    while let Some(i) = queue.pop_front() {
+        let index = shapes[i].unwrap_or_default();
+        let shape = match model.shape(index) {
+            Some(shape) => shape,
+            None => continue,
+        };
+        let points = shape.points(0.0)?;
+        for (edge, pair) in points.windows(2).enumerate() {
+            let middle = (pair[0] + pair[1]) * 0.5;
+            let out = middle - shape.point();
+            let out = out * (1.0 / out.distance(Point::origin()));
+            let (x, y) = (middle.x.floor() as i64, middle.y.floor() as i64);
+            let near = (x - 2..=x + 2)
+                .flat_map(|x| (y - 2..=y + 2).map(move |y| (x, y)))
+                .filter_map(|key| grid.get(&key))
+                .flatten()
+                .copied()
+                .find(|j| {
+                    shapes[*j].is_none()
+                        && centers[*j].distance(middle + out * apothem(tiles[*j].sides)) < TOLERANCE
+                });
+            if let Some(j) = near {
+                let index = model.attach(
+                    index,
+                    edge,
+                    Shape::new(tiles[j].sides, tiles[j].fill, stroke)?,
+                )?;
+                shapes[j] = Some(index);
+                queue.push_back(j);
+            }
+        }
+    }
+
+    Ok(model)
+}
+
+/// Returns the z component of the cross product of a and b.
+fn cross(a: Point, b: Point) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Returns a reduced basis of the lattice of translations between the first
+/// stitched shape and its copies: the shapes of the same polygon, fill, and
+/// rotation. Returns none if there are no copies in two directions.
+fn lattice(model: &Model) -> Option<(Point, Point)> {
+    let first = model.shape(0)?;
+    let turn = 2.0 * PI / first.sides() as f64;
+    let mut translations = (0..model.len())
+        .filter_map(|i| model.shape(i))
+        .filter(|s| s.sides() == first.sides() && s.fill() == first.fill())
+        .filter(|s| {
+            let d = (s.rotation() - first.rotation()).rem_euclid(turn);
+            d.min(turn - d) < 1e-6
+        })
+        .map(|s| s.point() - first.point())
+        .filter(|v| v.distance(Point::origin()) > 1e-6)
+        .collect::<Vec<Point>>();
+    translations.sort_by(|a, b| {
+        a.distance(Point::origin())
+            .total_cmp(&b.distance(Point::origin()))
+    });
+
+    let a = *translations.first()?;
+    let b = translations.iter().copied().find(|b| {
+        cross(a, *b).abs() > 1e-6 * a.distance(Point::origin()) * b.distance(Point::origin())
+    })?;
+
+    Some((a, b))
+}
+
+/// Returns one period of the stitched shapes: those whose centers, relative
+/// to the first shape's, have coordinates in [-1/2, 1/2) along the lattice.
+fn period(model: &Model, lattice: (Point, Point)) -> Vec<Shape> {
+    let origin = match model.shape(0) {
+        Some(shape) => shape.point(),
+        None => return Vec::new(),
+    };
+    let (a, b) = lattice;
+    let det = cross(a, b);
+    let within = |t: f64| (0.0..1.0).contains(&(t + 0.5 + 1e-6));
+
+    (0..model.len())
+        .filter_map(|i| model.shape(i))
+        .filter(|s| {
+            let d = s.point() - origin;
+            within(cross(d, b) / det) && within(cross(a, d) / det)
+        })
+        .collect()
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{Color, Model, Result, Shape};
+
+/// The default canvas width used by generated models.
+const WIDTH: i32 = 1024;
+
+/// The default canvas height used by generated models.
+const HEIGHT: i32 = 1024;
+
+/// The default scale used by generated models.
+const SCALE: f64 = 128.0;
+
+/// The amount of randomness used when choosing the next edge to grow from.
+/// Lower values produce rounder patches, higher values produce stringier ones.
+const JITTER: f64 = 1.5;
+
+/// Stochastically grows an edge-to-edge tiling patch from a set of allowed
+/// regular polygons.
+/// The same seed and configuration always produce the same patch.
+#[derive(Clone, Debug)]
+pub struct Generator {
+    seed: u64,
+    allowed: Vec<i32>,
+    fills: HashMap<i32, Color>,
+    fill: Color,
+    stroke: Color,
+    width: i32,
+    height: i32,
+    scale: f64,
+}
+
+impl Generator {
+    /// Returns a generator seeded with seed that allows no shapes.
+    pub fn new(seed: u64) -> Generator {
+        Generator {
+            seed,
+            allowed: Vec::new(),
+            fills: HashMap::new(),
+            fill: Color::WHITE,
+            stroke: Color::BLACK,
+            width: WIDTH,
+            height: HEIGHT,
+            scale: SCALE,
+        }
+    }
+
+    /// Allows shapes with sides sides to be placed.
+    pub fn allow(mut self, sides: i32) -> Generator {
+        if !self.allowed.contains(&sides) {
+            self.allowed.push(sides);
+        }
+
+        self
+    }
+
+    /// Sets the fill of shapes with sides sides.
+    pub fn fill(mut self, sides: i32, fill: Color) -> Generator {
+        self.fills.insert(sides, fill);
+
+        self
+    }
+
+    /// Sets the fill of shapes without a fill of their own and the stroke of all
+    /// shapes.
+    pub fn style(mut self, fill: Color, stroke: Color) -> Generator {
+        self.fill = fill;
+        self.stroke = stroke;
+
+        self
+    }
+
+    /// Sets the width, height, and scale of generated models.
+    pub fn canvas(mut self, width: i32, height: i32, scale: f64) -> Generator {
+        self.width = width;
+        self.height = height;
+        self.scale = scale;
+
+        self
+    }
+
+    /// Returns a model containing up to count shapes.
+    /// Growth stops early if no further shape can be placed without overlapping
+    /// the patch or breaking its edge-to-edge property.
+    pub fn grow(&self, count: usize) -> Result<Model> {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let mut model = Model::new(self.width, self.height, self.scale);
+        let prototypes = self
+            .allowed
+            .iter()
+            .map(|&sides| {
+                let fill = *self.fills.get(&sides).unwrap_or(&self.fill);
+                Shape::new(sides, fill, self.stroke)
+            })
+            .collect::<Result<Vec<Shape>>>()?;
+
+        let first = match prototypes.choose(&mut rng) {
+            Some(s) if count > 0 => *s,
+            _ => return Ok(model),
+        };
+
+        let mut placed = vec![first];
+        let mut frontier = (0..first.sides() as usize)
+            .map(|e| (0, e))
+            .collect::<Vec<(usize, usize)>>();
+        model.add(first)?;
+
+        while placed.len() < count && !frontier.is_empty() {
+            let next = frontier
+                .iter()
+                .map(|&(i, _)| {
+                    let p = placed[i].point();
+                    (p.x * p.x + p.y * p.y).sqrt() + rng.gen::<f64>() * JITTER
+                })
+                .enumerate()
+                .fold(
+                    (0, f64::MAX),
+                    |min, (i, d)| if d < min.1 { (i, d) } else { min },
+                )
+                .0;
+            let (index, edge) = frontier.swap_remove(next);
+            let parent = placed[index];
+
+            let mut candidates = prototypes.clone();
+            candidates.shuffle(&mut rng);
+            for c in candidates {
+                let shape = parent.adjacent(c.sides(), edge, c.fill(), c.stroke())?;
+                if !self.fits(&shape, &placed)? {
+                    continue;
+                }
+
+                let index = placed.len();
+                frontier.extend((1..shape.sides() as usize).map(|e| (index, e)));
+                placed.push(shape);
+                model.add(shape)?;
+                break;
+            }
+        }
+
+        Ok(model)
+    }
+
+    /// Returns true if shape can join placed without overlapping or partially
+    /// meeting any placed shape.
+    fn fits(&self, shape: &Shape, placed: &[Shape]) -> Result<bool> {
+        for p in placed {
+            if shape.overlaps(p)? || shape.meets_partially(p)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt::Write;
+
+use crate::{Color, Point};
+
+/// A polygon to be written as a GeoJSON feature, with its fill and stroke
+/// colors if it has any.
+pub(crate) struct Feature<'a> {
+    pub(crate) kind: &'a str,
+    pub(crate) index: usize,
+    pub(crate) sides: usize,
+    pub(crate) colors: Option<(Color, Color)>,
+    pub(crate) points: Vec<Point>,
+}
+
+/// Returns features as a GeoJSON feature collection.
+/// The y axis is flipped so that tilings appear upright in tools that draw y
+/// upward, and every ring is wound counterclockwise as RFC 7946 recommends.
+pub(crate) fn feature_collection(features: &[Feature]) -> String {
+    let mut json = String::from(r#"{"type":"FeatureCollection","features":["#);
+    for (i, f) in features.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write_feature(&mut json, f);
+    }
+    json.push_str("]}");
+
+    json
+}
+
+/// Appends feature to json.
+fn write_feature(json: &mut String, feature: &Feature) {
+    let mut ring = feature
+        .points
+        .iter()
+        .map(|p| (p.x, -p.y))
+        .collect::<Vec<(f64, f64)>>();
+    let signed_area: f64 = ring
+        .windows(2)
+        .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+        .sum();
+    if signed_area < 0.0 {
+        ring.reverse();
+    }
+
+    let coordinates = ring
+        .iter()
+        .map(|(x, y)| format!("[{},{}]", x, y))
+        .collect::<Vec<String>>()
+        .join(",");
+
+    let colors = match feature.colors {
+        Some((fill, stroke)) => format!(
+            r#","fill":"{}","stroke":"{}""#,
+            fill.to_hex(),
+            stroke.to_hex()
+        ),
+        None => String::new(),
+    };
+
+    // Writing to a String cannot fail.
+    let _ = write!(
+        json,
+        r#"{{"type":"Feature","geometry":{{"type":"Polygon","coordinates":[[{}]]}},"properties":{{"kind":"{}","index":{},"sides":{}{}}}}}"#,
+        coordinates, feature.kind, feature.index, feature.sides, colors,
+    );
+}
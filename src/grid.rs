@@ -0,0 +1,157 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::BuildHasherDefault,
+};
+
+use crate::{hash::Fnv, shape::Key, Point, Shape};
+
+/// The side length of a grid cell (in model units).
+pub(crate) const CELL_SIZE: f64 = 1.0;
+
+/// The offsets of a cell and its neighbors, starting with the cell itself.
+const NEIGHBORHOOD: [(i64, i64); 9] = [
+    (0, 0),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A spatial index of shapes that buckets each shape's key by the grid cells
+/// overlapped by the shape's bounding circle.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Grid {
+    cells: HashMap<(i64, i64), Vec<Key>>,
+}
+
+impl Grid {
+    /// Indexes shape by key.
+    pub(crate) fn insert(&mut self, shape: &Shape, key: Key) {
+        let r = shape.circumradius();
+        let (x0, y0) = cell(shape.point().x - r, shape.point().y - r);
+        let (x1, y1) = cell(shape.point().x + r, shape.point().y + r);
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                let keys = self.cells.entry((x, y)).or_default();
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+    }
+
+    /// Removes shape, indexed by key.
+    pub(crate) fn remove(&mut self, shape: &Shape, key: Key) {
+        let r = shape.circumradius();
+        let (x0, y0) = cell(shape.point().x - r, shape.point().y - r);
+        let (x1, y1) = cell(shape.point().x + r, shape.point().y + r);
+        for x in x0..=x1 {
+            for y in y0..=y1 {
+                if let Some(keys) = self.cells.get_mut(&(x, y)) {
+                    keys.retain(|k| *k != key);
+                }
+            }
+        }
+    }
+
+    /// Removes every indexed shape.
+    pub(crate) fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Returns the keys of the shapes whose bounding circles may contain point.
+    pub(crate) fn candidates(&self, point: Point) -> &[Key] {
+        self.cells
+            .get(&cell(point.x, point.y))
+            .map_or(&[], |keys| keys.as_slice())
+    }
+
+    /// Returns the keys of the shapes whose bounding circles may lie within a
+    /// cell of point, so that every shape within `CELL_SIZE` of point is
+    /// included.
+    pub(crate) fn nearby(&self, point: Point) -> HashSet<Key> {
+        let (x, y) = cell(point.x, point.y);
+        let mut keys = HashSet::new();
+        for cx in x.saturating_sub(1)..=x.saturating_add(1) {
+            for cy in y.saturating_sub(1)..=y.saturating_add(1) {
+                if let Some(cell) = self.cells.get(&(cx, cy)) {
+                    keys.extend(cell.iter().copied());
+                }
+            }
+        }
+
+        keys
+    }
+}
+
+/// A spatial index that merges points lying within a tolerance of each other
+/// into clusters, so points that differ only by rounding error are found
+/// together however they round.
+#[derive(Clone, Debug)]
+pub(crate) struct Clusters {
+    tolerance: f64,
+    cells: HashMap<(i64, i64), Vec<usize>, BuildHasherDefault<Fnv>>,
+    points: Vec<Point>,
+}
+
+impl Clusters {
+    /// Returns an empty index that merges points within tolerance.
+    pub(crate) fn new(tolerance: f64) -> Clusters {
+        Clusters {
+            tolerance,
+            cells: HashMap::default(),
+            points: Vec::new(),
+        }
+    }
+
+    /// Returns the index of the cluster point belongs to: the first cluster
+    /// whose first point lies within the tolerance of it, or a new cluster
+    /// at point if there is none.
+    pub(crate) fn insert(&mut self, point: Point) -> usize {
+        if let Some(i) = self.find(point) {
+            return i;
+        }
+
+        let index = self.points.len();
+        self.points.push(point);
+        self.cells.entry(self.cell(point)).or_default().push(index);
+
+        index
+    }
+
+    /// Returns the index of the first cluster whose first point lies within
+    /// the tolerance of point, or none.
+    pub(crate) fn find(&self, point: Point) -> Option<usize> {
+        // Cells are as wide as the tolerance, so a matching point lies in the
+        // point's cell or one next to it; the point's own cell is searched
+        // first, since that is where repeated points almost always fall.
+        let (x, y) = self.cell(point);
+        NEIGHBORHOOD.iter().find_map(|(dx, dy)| {
+            self.cells
+                .get(&(x + dx, y + dy))?
+                .iter()
+                .copied()
+                .find(|i| self.points[*i].distance(point) <= self.tolerance)
+        })
+    }
+
+    /// Returns the cell of the index containing point.
+    fn cell(&self, point: Point) -> (i64, i64) {
+        (
+            (point.x / self.tolerance).floor() as i64,
+            (point.y / self.tolerance).floor() as i64,
+        )
+    }
+}
+
+/// Returns the grid cell containing the point (x, y).
+fn cell(x: f64, y: f64) -> (i64, i64) {
+    (
+        (x / CELL_SIZE).floor() as i64,
+        (y / CELL_SIZE).floor() as i64,
+    )
+}
@@ -0,0 +1,71 @@
+use std::hash::Hasher;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    shape::{self, Key},
+    Point,
+};
+
+/// A 64-bit FNV-1a hash.
+/// Unlike the standard library's hashers its output is fixed, the same on
+/// every platform and with every version of Rust, so hashes can be stored and
+/// compared across runs.
+pub(crate) struct Fnv(u64);
+
+impl Fnv {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    /// Returns a hash of no bytes.
+    pub(crate) fn new() -> Fnv {
+        Fnv(Fnv::OFFSET)
+    }
+
+    /// Adds bytes to the hash.
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ *b as u64).wrapping_mul(Fnv::PRIME);
+        }
+    }
+
+    /// Adds n to the hash as little-endian bytes.
+    pub(crate) fn write_i64(&mut self, n: i64) {
+        self.write(&n.to_le_bytes());
+    }
+
+    /// Returns the hash of the bytes added so far.
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Returns a random number generator seeded by seed and point, so each point
+/// of a tiling (such as a shape's center) draws its own numbers, the same ones
+/// every time for the same seed.
+pub(crate) fn rng_at(seed: u64, point: Point) -> ChaCha8Rng {
+    let Key(x, y) = point.key(shape::PRECISION);
+    let mut hash = Fnv::new();
+    hash.write_i64(seed as i64);
+    hash.write_i64(x);
+    hash.write_i64(y);
+
+    ChaCha8Rng::seed_from_u64(hash.finish())
+}
+
+impl Default for Fnv {
+    fn default() -> Fnv {
+        Fnv::new()
+    }
+}
+
+impl Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        Fnv::write(self, bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        Fnv::finish(self)
+    }
+}
@@ -0,0 +1,327 @@
+use std::{fmt, ops::Range};
+
+use crate::{model::Checkpoint, Axis, Color, Pattern, Point, Shape, Side, Vector};
+
+/// An operation that built a model, as recorded in its history.
+/// Each operation stores the arguments it was called with, so it can be
+/// replayed by `Model::redo`.
+#[derive(Clone, Debug)]
+pub enum Operation {
+    /// `Model::add`.
+    Add { shape: Shape },
+    /// `Model::add_multi`.
+    AddMulti {
+        indexes: Range<usize>,
+        edges: Range<usize>,
+        shape: Shape,
+    },
+    /// `Model::add_multi_lossy`.
+    AddMultiLossy {
+        indexes: Range<usize>,
+        edges: Range<usize>,
+        shape: Shape,
+    },
+    /// `Model::add_pattern`.
+    AddPattern {
+        indexes: Range<usize>,
+        edges: Range<usize>,
+        pattern: Pattern,
+    },
+    /// `Model::place`.
+    Place { pattern: Pattern },
+    /// `Model::add_ring`.
+    AddRing {
+        indexes: Range<usize>,
+        shapes: Vec<Option<Shape>>,
+    },
+    /// `Model::add_multi_mirrored`.
+    AddMultiMirrored {
+        indexes: Range<usize>,
+        edges: Range<usize>,
+        pattern: Pattern,
+    },
+    /// `Model::attach` (with an offset of 0 on the outward side),
+    /// `Model::attach_at`, or `Model::attach_on`.
+    Attach {
+        index: usize,
+        edge: usize,
+        t: f64,
+        side: Side,
+        shape: Shape,
+    },
+    /// `Model::repeat`.
+    Repeat { indexes: Range<usize> },
+    /// `Model::repeat_around` or `Model::repeat_from`.
+    RepeatAround {
+        indexes: Range<usize>,
+        center: Point,
+    },
+    /// `Model::repeat_groups` (with a center at the origin) or
+    /// `Model::repeat_groups_around`.
+    RepeatGroups {
+        groups: Vec<Range<usize>>,
+        center: Point,
+    },
+    /// `Model::repeat_with_coloring`.
+    RepeatWithColoring {
+        indexes: Range<usize>,
+        colorings: Vec<Vec<Color>>,
+    },
+    /// `Model::repeat_by`.
+    RepeatBy { vectors: Vec<Point>, extent: usize },
+    /// `Model::repeat_n` (with a center at the origin) or
+    /// `Model::repeat_n_around`.
+    RepeatN {
+        indexes: Range<usize>,
+        copies: usize,
+        center: Point,
+    },
+    /// `Model::repeat_radius` (with a center at the origin) or
+    /// `Model::repeat_radius_around`.
+    RepeatRadius {
+        indexes: Range<usize>,
+        radius: f64,
+        center: Point,
+    },
+    /// `Model::repeat_rotational`.
+    RepeatRotational { indexes: Range<usize>, n: usize },
+    /// `Model::repeat_strip`.
+    RepeatStrip {
+        indexes: Range<usize>,
+        direction: Vector,
+        glide: bool,
+    },
+    /// `Model::translate`.
+    Translate { vector: Vector },
+    /// `Model::rotate`.
+    Rotate { angle: f64, center: Point },
+    /// `Model::reflect`.
+    Reflect { axis: Axis },
+}
+
+impl Operation {
+    /// Returns true if the operation repeats the model's pattern.
+    pub(crate) fn is_repeat(&self) -> bool {
+        matches!(
+            self,
+            Operation::Repeat { .. }
+                | Operation::RepeatAround { .. }
+                | Operation::RepeatGroups { .. }
+                | Operation::RepeatWithColoring { .. }
+                | Operation::RepeatBy { .. }
+                | Operation::RepeatN { .. }
+                | Operation::RepeatRadius { .. }
+                | Operation::RepeatRotational { .. }
+                | Operation::RepeatStrip { .. }
+        )
+    }
+}
+
+impl fmt::Display for Operation {
+    /// Formats the operation as the call that applied it, with each shape
+    /// written as its number of sides and center, e.g. "add(6, (0, 0))" or
+    /// "repeat(0..7)".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let shape = |s: &Shape| format!("{}, ({}, {})", s.sides(), s.point().x, s.point().y);
+        let point = |p: &Point| format!("({}, {})", p.x, p.y);
+        match self {
+            Operation::Add { shape: s } => write!(f, "add({})", shape(s)),
+            Operation::AddMulti {
+                indexes,
+                edges,
+                shape: s,
+            } => write!(f, "add_multi({:?}, {:?}, {})", indexes, edges, s.sides()),
+            Operation::AddMultiLossy {
+                indexes,
+                edges,
+                shape: s,
+            } => write!(
+                f,
+                "add_multi_lossy({:?}, {:?}, {})",
+                indexes,
+                edges,
+                s.sides()
+            ),
+            Operation::AddPattern {
+                indexes,
+                edges,
+                pattern,
+            } => write!(
+                f,
+                "add_pattern({:?}, {:?}, {} shapes)",
+                indexes,
+                edges,
+                pattern.len()
+            ),
+            Operation::Place { pattern } => write!(f, "place({} shapes)", pattern.len()),
+            Operation::AddRing { indexes, shapes } => {
+                let sides = shapes
+                    .iter()
+                    .map(|s| s.map_or(String::from("_"), |s| s.sides().to_string()))
+                    .collect::<Vec<String>>();
+                write!(f, "add_ring({:?}, [{}])", indexes, sides.join(", "))
+            }
+            Operation::AddMultiMirrored {
+                indexes,
+                edges,
+                pattern,
+            } => write!(
+                f,
+                "add_multi_mirrored({:?}, {:?}, {} shapes)",
+                indexes,
+                edges,
+                pattern.len()
+            ),
+            Operation::Attach {
+                index,
+                edge,
+                t,
+                side,
+                shape: s,
+            } => write!(
+                f,
+                "attach_on({}, {}, {}, {:?}, {})",
+                index,
+                edge,
+                t,
+                side,
+                s.sides()
+            ),
+            Operation::Repeat { indexes } => write!(f, "repeat({:?})", indexes),
+            Operation::RepeatAround { indexes, center } => {
+                write!(f, "repeat_around({:?}, {})", indexes, point(center))
+            }
+            Operation::RepeatGroups { groups, center } if *center == Point::origin() => {
+                write!(f, "repeat_groups({:?})", groups)
+            }
+            Operation::RepeatGroups { groups, center } => {
+                write!(f, "repeat_groups_around({:?}, {})", groups, point(center))
+            }
+            Operation::RepeatWithColoring { indexes, colorings } => write!(
+                f,
+                "repeat_with_coloring({:?}, {} colorings)",
+                indexes,
+                colorings.len()
+            ),
+            Operation::RepeatBy { vectors, extent } => {
+                let vectors = vectors.iter().map(point).collect::<Vec<String>>();
+                write!(f, "repeat_by([{}], {})", vectors.join(", "), extent)
+            }
+            Operation::RepeatN {
+                indexes,
+                copies,
+                center,
+            } if *center == Point::origin() => write!(f, "repeat_n({:?}, {})", indexes, copies),
+            Operation::RepeatN {
+                indexes,
+                copies,
+                center,
+            } => write!(
+                f,
+                "repeat_n_around({:?}, {}, {})",
+                indexes,
+                copies,
+                point(center)
+            ),
+            Operation::RepeatRadius {
+                indexes,
+                radius,
+                center,
+            } if *center == Point::origin() => {
+                write!(f, "repeat_radius({:?}, {})", indexes, radius)
+            }
+            Operation::RepeatRadius {
+                indexes,
+                radius,
+                center,
+            } => write!(
+                f,
+                "repeat_radius_around({:?}, {}, {})",
+                indexes,
+                radius,
+                point(center)
+            ),
+            Operation::RepeatRotational { indexes, n } => {
+                write!(f, "repeat_rotational({:?}, {})", indexes, n)
+            }
+            Operation::RepeatStrip {
+                indexes,
+                direction,
+                glide,
+            } => write!(
+                f,
+                "repeat_strip({:?}, {}, {})",
+                indexes,
+                point(direction),
+                glide
+            ),
+            Operation::Translate { vector } => write!(f, "translate({})", point(vector)),
+            Operation::Rotate { angle, center } => {
+                write!(f, "rotate({}, {})", angle, point(center))
+            }
+            Operation::Reflect { axis } => {
+                write!(f, "reflect({}, {})", point(&axis.point()), axis.angle())
+            }
+        }
+    }
+}
+
+/// The operations applied to a model, each with a checkpoint of what it
+/// changed, and the operations that were undone since.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct History {
+    operations: Vec<Operation>,
+    checkpoints: Vec<Checkpoint>,
+    undone: Vec<Operation>,
+    journal: Option<Checkpoint>,
+}
+
+impl History {
+    /// Returns the operations applied, oldest first.
+    pub(crate) fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Returns true while an operation is being recorded, so the operations
+    /// it calls are not recorded too.
+    pub(crate) fn is_recording(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Begins recording an operation, noting its changes in checkpoint.
+    pub(crate) fn begin(&mut self, checkpoint: Checkpoint) {
+        self.journal = Some(checkpoint);
+    }
+
+    /// Returns the checkpoint of the operation being recorded, if any.
+    pub(crate) fn journal(&mut self) -> Option<&mut Checkpoint> {
+        self.journal.as_mut()
+    }
+
+    /// Ends recording an operation and returns its checkpoint.
+    pub(crate) fn end(&mut self) -> Option<Checkpoint> {
+        self.journal.take()
+    }
+
+    /// Records operation along with the checkpoint of what it changed,
+    /// discarding the operations that were undone.
+    pub(crate) fn push(&mut self, operation: Operation, checkpoint: Checkpoint) {
+        self.operations.push(operation);
+        self.checkpoints.push(checkpoint);
+        self.undone.clear();
+    }
+
+    /// Removes the last operation applied and returns it along with the
+    /// checkpoint of what it changed.
+    pub(crate) fn pop(&mut self) -> Option<(Operation, Checkpoint)> {
+        let operation = self.operations.pop()?;
+        let checkpoint = self.checkpoints.pop()?;
+
+        Some((operation, checkpoint))
+    }
+
+    /// Returns the operations that were undone, most recently undone last.
+    pub(crate) fn undone_mut(&mut self) -> &mut Vec<Operation> {
+        &mut self.undone
+    }
+}
@@ -0,0 +1,496 @@
+use std::{cmp::Ordering::Less, collections::HashMap};
+
+use crate::{
+    shape::{corners, Key, PRECISION},
+    Axis, Color, Corner, Dual,
+    Error::*,
+    PaintMode, Point, Polygon, Result, Tile,
+};
+
+#[cfg(feature = "cairo")]
+use crate::shape;
+
+/// The smallest area of a piece that is kept, below which pieces are slivers
+/// left over from clipping along a shared edge.
+const AREA_EPSILON: f64 = 1e-9;
+
+/// The largest distance from an edge at which a vertex is considered to lie on
+/// the edge.
+const EDGE_EPSILON: f64 = 1e-7;
+
+/// A polygon that need not be regular or convex, made by combining polygons
+/// with `union`, `intersection`, and `difference`, such as a hexagon with a
+/// triangular notch.
+/// The polygon is stored as convex pieces that do not overlap, from which its
+/// outline is traced; the outline may have holes or several parts.
+#[derive(Clone, Debug)]
+pub struct IrregularShape {
+    pieces: Vec<Vec<Point>>,
+    rings: Vec<Vec<Point>>,
+    fill: Color,
+    stroke: Color,
+    corner: Corner,
+    paint: PaintMode,
+}
+
+impl IrregularShape {
+    /// Returns an irregular shape covering polygon, which must be convex (as
+    /// regular polygons, rhombi, and dual cells are).
+    pub fn new<P: Polygon>(polygon: &P, fill: Color, stroke: Color) -> Result<IrregularShape> {
+        let mut points = polygon.points(0.0)?;
+        points.pop();
+
+        IrregularShape::from_pieces(vec![points], fill, stroke)
+    }
+
+    /// Returns the irregular shape made of pieces, ensuring it has some area.
+    fn from_pieces(pieces: Vec<Vec<Point>>, fill: Color, stroke: Color) -> Result<IrregularShape> {
+        let pieces = pieces
+            .into_iter()
+            .filter(|p| area(p).abs() > AREA_EPSILON)
+            .map(|mut p| {
+                if area(&p) < 0.0 {
+                    p.reverse();
+                }
+
+                p
+            })
+            .collect::<Vec<Vec<Point>>>();
+        if pieces.is_empty() {
+            return Err(InvalidShape {
+                reason: String::from("irregular shape has no area"),
+            });
+        }
+
+        Ok(IrregularShape {
+            rings: outline(&pieces),
+            pieces,
+            fill,
+            stroke,
+            corner: Corner::default(),
+            paint: PaintMode::default(),
+        })
+    }
+
+    /// Returns the shape with its corners rendered in the corner style.
+    pub fn with_corner(mut self, corner: Corner) -> IrregularShape {
+        self.corner = corner;
+
+        self
+    }
+
+    /// Returns the shape with the parts painted by paint mode.
+    pub fn with_paint(mut self, paint: PaintMode) -> IrregularShape {
+        self.paint = paint;
+
+        self
+    }
+
+    /// Returns the shape's fill.
+    pub fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the shape's stroke.
+    pub fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns the convex pieces that make up the shape.
+    pub fn pieces(&self) -> &[Vec<Point>] {
+        &self.pieces
+    }
+
+    /// Returns the shape's outline as closed rings of points, largest first.
+    /// Rings wound the same way as a `Shape`'s points bound the shape's parts,
+    /// and rings wound the other way bound its holes.
+    pub fn rings(&self) -> &[Vec<Point>] {
+        &self.rings
+    }
+
+    /// Returns the shape's area.
+    pub fn area(&self) -> f64 {
+        self.pieces.iter().map(|p| area(p)).sum()
+    }
+
+    /// Returns the shape's centroid.
+    pub fn point(&self) -> Point {
+        let (mut x, mut y) = (0.0, 0.0);
+        for p in self.pieces.iter() {
+            let (a, c) = (area(p), centroid(p));
+            x += c.x * a;
+            y += c.y * a;
+        }
+        let a = self.area();
+
+        Point { x: x / a, y: y / a }
+    }
+
+    /// Returns a copy of the shape centered at point.
+    pub fn clone_at(&self, point: Point) -> IrregularShape {
+        let center = self.point();
+        let (dx, dy) = (point.x - center.x, point.y - center.y);
+        let translate = |ps: &Vec<Point>| {
+            ps.iter()
+                .map(|p| Point {
+                    x: p.x + dx,
+                    y: p.y + dy,
+                })
+                .collect()
+        };
+
+        let mut s = self.clone();
+        s.pieces = self.pieces.iter().map(translate).collect();
+        s.rings = self.rings.iter().map(translate).collect();
+
+        s
+    }
+
+    /// Returns the region covered by the shape or other, with the shape's
+    /// colors.
+    pub fn union(&self, other: &IrregularShape) -> Result<IrregularShape> {
+        let mut pieces = self.pieces.clone();
+        pieces.extend(subtract_all(&other.pieces, &self.pieces));
+
+        IrregularShape::from_pieces(pieces, self.fill, self.stroke)
+    }
+
+    /// Returns the region covered by both the shape and other, with the
+    /// shape's colors, ensuring they overlap.
+    pub fn intersection(&self, other: &IrregularShape) -> Result<IrregularShape> {
+        let mut pieces = Vec::new();
+        for a in self.pieces.iter() {
+            for b in other.pieces.iter() {
+                pieces.push(intersect(a, b));
+            }
+        }
+
+        IrregularShape::from_pieces(pieces, self.fill, self.stroke)
+    }
+
+    /// Returns the region covered by the shape but not other, with the shape's
+    /// colors, ensuring other does not cover the whole shape.
+    pub fn difference(&self, other: &IrregularShape) -> Result<IrregularShape> {
+        IrregularShape::from_pieces(
+            subtract_all(&self.pieces, &other.pieces),
+            self.fill,
+            self.stroke,
+        )
+    }
+}
+
+impl Polygon for IrregularShape {
+    /// Returns the polygon's points.
+    /// Only the largest ring of the outline is returned; `rings` returns the
+    /// rest.
+    fn points(&self, margin: f64) -> Result<Vec<Point>> {
+        inset(&self.rings[0], margin)
+    }
+
+    /// Returns the points of the largest ring of the outline inset by margin
+    /// with its corners cut in the shape's corner style.
+    fn outline_points(&self, margin: f64) -> Result<Vec<Point>> {
+        Ok(corners(&self.points(margin)?, self.corner))
+    }
+
+    /// Renders the polygon, filling the space between its rings so that holes
+    /// are left empty.
+    #[cfg(feature = "cairo")]
+    fn render(&self, context: &cairo::Context, margin: f64) -> Result<()> {
+        self.render_snapped(context, margin, None)
+    }
+
+    /// Renders the polygon as `render` does, with its stroke snapped to the
+    /// centers of pixels pixel device units wide if pixel is some.
+    #[cfg(feature = "cairo")]
+    fn render_snapped(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        let rings = self
+            .rings
+            .iter()
+            .map(|ring| Ok(corners(&inset(ring, margin)?, self.corner)))
+            .collect::<Result<Vec<_>>>()?;
+        context.save()?;
+        context.set_fill_rule(cairo::FillRule::EvenOdd);
+        let painted = shape::paint_path(
+            context,
+            self.fill,
+            self.stroke,
+            self.paint,
+            pixel,
+            |pixel| {
+                for ring in rings.iter() {
+                    shape::trace_snapped(context, ring, pixel);
+                    context.close_path();
+                }
+            },
+        );
+        context.restore()?;
+
+        painted
+    }
+}
+
+impl Tile for IrregularShape {
+    /// Returns the tile's centroid.
+    fn point(&self) -> Point {
+        IrregularShape::point(self)
+    }
+
+    /// Returns a copy of the tile moved by vector.
+    fn translated(&self, vector: Point) -> Box<dyn Tile> {
+        Box::new(self.clone_at(IrregularShape::point(self) + vector))
+    }
+
+    /// Returns a copy of the tile rotated by angle (in radians) about center.
+    fn rotated(&self, angle: f64, center: Point) -> Box<dyn Tile> {
+        let rotate = |ps: &Vec<Point>| ps.iter().map(|p| p.rotate(angle, center)).collect();
+
+        let mut s = self.clone();
+        s.pieces = self.pieces.iter().map(rotate).collect();
+        s.rings = self.rings.iter().map(rotate).collect();
+
+        Box::new(s)
+    }
+
+    /// Returns a copy of the tile mirrored across axis, with its pieces and
+    /// rings reversed so they keep winding the same way.
+    fn reflected(&self, axis: &Axis) -> Box<dyn Tile> {
+        let reflect = |ps: &Vec<Point>| ps.iter().rev().map(|p| axis.reflect(*p)).collect();
+
+        let mut s = self.clone();
+        s.pieces = self.pieces.iter().map(reflect).collect();
+        s.rings = self.rings.iter().map(reflect).collect();
+
+        Box::new(s)
+    }
+
+    /// Returns a copy of the tile filled with fill.
+    fn filled(&self, fill: Color) -> Box<dyn Tile> {
+        let mut s = self.clone();
+        s.fill = fill;
+
+        Box::new(s)
+    }
+
+    /// Returns the color the tile is filled with.
+    fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the color the tile is stroked with.
+    fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns whether the tile is filled, stroked, or both.
+    fn paint(&self) -> PaintMode {
+        self.paint
+    }
+
+    /// Returns the closed rings outlining the tile inset by margin: its
+    /// outline and then its holes.
+    fn rings(&self, margin: f64) -> Result<Vec<Vec<Point>>> {
+        self.rings.iter().map(|r| inset(r, margin)).collect()
+    }
+}
+
+/// Returns the closed ring inset by margin, leaving it as is without a
+/// margin.
+pub(crate) fn inset(ring: &[Point], margin: f64) -> Result<Vec<Point>> {
+    if margin == 0.0 {
+        return Ok(ring.to_vec());
+    }
+
+    Dual::inset_polygon(ring.to_vec(), margin)
+}
+
+/// Returns the signed area of the polygon with vertices points, which is
+/// positive when they are wound the same way as a `Shape`'s points.
+pub(crate) fn area(points: &[Point]) -> f64 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (p, q) = (points[i], points[(i + 1) % n]);
+            p.x * q.y - q.x * p.y
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Returns the centroid of the region bounded by the polygon with vertices
+/// points.
+pub(crate) fn centroid(points: &[Point]) -> Point {
+    let n = points.len();
+    let (mut x, mut y) = (0.0, 0.0);
+    for i in 0..n {
+        let (p, q) = (points[i], points[(i + 1) % n]);
+        let cross = p.x * q.y - q.x * p.y;
+        x += (p.x + q.x) * cross;
+        y += (p.y + q.y) * cross;
+    }
+    let a = 6.0 * area(points);
+
+    Point { x: x / a, y: y / a }
+}
+
+/// Returns how far p lies to the inner side of the line through a and b.
+fn side(a: Point, b: Point, p: Point) -> f64 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Returns the part of the convex polygon points on the inner side of the line
+/// through a and b.
+fn clip(points: &[Point], a: Point, b: Point) -> Vec<Point> {
+    let mut clipped = Vec::new();
+    for (i, p) in points.iter().enumerate() {
+        let q = points[(i + 1) % points.len()];
+        let (dp, dq) = (side(a, b, *p), side(a, b, q));
+        if dp >= 0.0 {
+            clipped.push(*p);
+        }
+        if (dp >= 0.0) != (dq >= 0.0) {
+            let t = dp / (dp - dq);
+            clipped.push(Point {
+                x: p.x + (q.x - p.x) * t,
+                y: p.y + (q.y - p.y) * t,
+            });
+        }
+    }
+
+    clipped
+}
+
+/// Returns the intersection of the convex polygons a and b.
+pub(crate) fn intersect(a: &[Point], b: &[Point]) -> Vec<Point> {
+    let mut points = a.to_vec();
+    for (i, p) in b.iter().enumerate() {
+        if points.is_empty() {
+            break;
+        }
+        points = clip(&points, *p, b[(i + 1) % b.len()]);
+    }
+
+    points
+}
+
+/// Returns the convex pieces of a that lie outside of the convex polygon b.
+fn subtract(a: &[Point], b: &[Point]) -> Vec<Vec<Point>> {
+    let mut pieces = Vec::new();
+    let mut rest = a.to_vec();
+    for (i, p) in b.iter().enumerate() {
+        let q = b[(i + 1) % b.len()];
+        let outside = clip(&rest, q, *p);
+        if area(&outside).abs() > AREA_EPSILON {
+            pieces.push(outside);
+        }
+        rest = clip(&rest, *p, q);
+        if area(&rest).abs() <= AREA_EPSILON {
+            break;
+        }
+    }
+
+    pieces
+}
+
+/// Returns the convex pieces of pieces that lie outside of every polygon of
+/// others.
+fn subtract_all(pieces: &[Vec<Point>], others: &[Vec<Point>]) -> Vec<Vec<Point>> {
+    let mut pieces = pieces.to_vec();
+    for b in others.iter() {
+        pieces = pieces.iter().flat_map(|a| subtract(a, b)).collect();
+    }
+
+    pieces
+}
+
+/// Returns the closed rings that bound pieces, largest first, found by
+/// cancelling the edges that pieces share.
+fn outline(pieces: &[Vec<Point>]) -> Vec<Vec<Point>> {
+    let mut points: HashMap<Key, Point> = HashMap::new();
+    for p in pieces.iter().flatten() {
+        points.entry(p.key(PRECISION)).or_insert(*p);
+    }
+
+    // Split each edge at the vertices of other pieces that lie on it, so that
+    // edges shared by pieces match exactly.
+    let mut edges: HashMap<(Key, Key), i32> = HashMap::new();
+    for piece in pieces.iter() {
+        for (i, p) in piece.iter().enumerate() {
+            let q = piece[(i + 1) % piece.len()];
+            let length = ((q.x - p.x).powi(2) + (q.y - p.y).powi(2)).sqrt();
+            let mut stops = points
+                .values()
+                .filter_map(|v| {
+                    let t = ((v.x - p.x) * (q.x - p.x) + (v.y - p.y) * (q.y - p.y)) / length;
+                    let on = (side(*p, q, *v) / length).abs() < EDGE_EPSILON;
+                    Some((t, v.key(PRECISION)))
+                        .filter(|_| on && t > EDGE_EPSILON && t < length - EDGE_EPSILON)
+                })
+                .collect::<Vec<(f64, Key)>>();
+            stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Less));
+
+            let mut keys = vec![p.key(PRECISION)];
+            keys.extend(stops.into_iter().map(|(_, k)| k));
+            keys.push(q.key(PRECISION));
+            for k in keys.windows(2) {
+                match edges.get_mut(&(k[1], k[0])) {
+                    Some(n) if *n > 0 => *n -= 1,
+                    _ => *edges.entry((k[0], k[1])).or_insert(0) += 1,
+                }
+            }
+        }
+    }
+
+    let mut next: HashMap<Key, Vec<Key>> = HashMap::new();
+    for ((a, b), n) in edges.into_iter() {
+        for _ in 0..n {
+            next.entry(a).or_default().push(b);
+        }
+    }
+
+    let mut rings = Vec::new();
+    let mut starts = next.keys().copied().collect::<Vec<Key>>();
+    starts.sort_by_key(|k| (k.1, k.0));
+    for start in starts {
+        while let Some(mut key) = next.get_mut(&start).and_then(|ks| ks.pop()) {
+            let mut ring = vec![points[&start]];
+            while key != start {
+                ring.push(points[&key]);
+                match next.get_mut(&key).and_then(|ks| ks.pop()) {
+                    Some(k) => key = k,
+                    None => break,
+                }
+            }
+            rings.push(simplify(ring));
+        }
+    }
+    rings.retain(|r| r.len() >= 3);
+    rings.sort_by(|a, b| area(b).abs().partial_cmp(&area(a).abs()).unwrap_or(Less));
+
+    rings
+        .into_iter()
+        .map(|mut r| {
+            r.push(r[0]);
+            r
+        })
+        .collect()
+}
+
+/// Returns ring without the vertices that lie on the line between their
+/// neighbors.
+fn simplify(ring: Vec<Point>) -> Vec<Point> {
+    let n = ring.len();
+    (0..n)
+        .filter(|i| {
+            let (p, v, q) = (ring[(i + n - 1) % n], ring[*i], ring[(i + 1) % n]);
+            let length = ((q.x - p.x).powi(2) + (q.y - p.y).powi(2)).sqrt();
+            (side(p, q, v) / length).abs() >= EDGE_EPSILON
+        })
+        .map(|i| ring[i])
+        .collect()
+}
@@ -0,0 +1,454 @@
+use std::f64::consts::PI;
+
+use crate::{
+    curved::distance_to_segment,
+    irregular::centroid,
+    math,
+    shape::{self, frame},
+    Axis, Color,
+    Error::*,
+    Model, PaintMode, Point, Polygon, Result, Tile,
+};
+
+/// The isohedral tiling types of Grünbaum and Shephard's classification that
+/// are supported, each a family of tilings by copies of one tile whose
+/// corners are set by the type's parameters.
+/// Only translational types are supported so far, in which every edge of the
+/// tile is matched by the opposite edge of a neighbor, so an edge may take
+/// any shape.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IsohedralType {
+    /// A hexagon whose opposite edges are translates of each other, with the
+    /// directions of its second and third edges (relative to the first) as
+    /// parameters: x and y of each.
+    Ih1,
+    /// A parallelogram whose opposite edges are translates of each other,
+    /// with the direction of its second edge (relative to the first) as
+    /// parameters: x and y.
+    Ih41,
+}
+
+impl IsohedralType {
+    /// Returns the type's number in the classification (1 to 93).
+    pub fn number(&self) -> usize {
+        match self {
+            IsohedralType::Ih1 => 1,
+            IsohedralType::Ih41 => 41,
+        }
+    }
+
+    /// Returns the number of corners of the type's tiles.
+    pub fn corners(&self) -> usize {
+        match self {
+            IsohedralType::Ih1 => 6,
+            IsohedralType::Ih41 => 4,
+        }
+    }
+
+    /// Returns the number of edge shapes of the type's tiles, each shared by
+    /// a pair of opposite edges.
+    pub fn edge_shapes(&self) -> usize {
+        self.corners() / 2
+    }
+
+    /// Returns the parameters of the type's most regular tile: a regular
+    /// hexagon or a square.
+    pub fn default_parameters(&self) -> Vec<f64> {
+        let (c, s) = (math::cos(PI / 3.0), math::sin(PI / 3.0));
+        match self {
+            IsohedralType::Ih1 => vec![c, s, -c, s],
+            IsohedralType::Ih41 => vec![0.0, 1.0],
+        }
+    }
+}
+
+/// An isohedral tiling: copies of one tile, of an `IsohedralType`, whose
+/// edges may be deformed Escher-style while the copies still tile the plane.
+/// Each edge shape is a polyline drawn in the frame of an edge running from
+/// (0, 0) to (1, 0), with y pointing to the right of the edge on the canvas,
+/// and is shared by a pair of opposite edges, so a bump out of one edge is a
+/// dent in the other.
+#[derive(Clone, Debug)]
+pub struct IsohedralTiling {
+    kind: IsohedralType,
+    parameters: Vec<f64>,
+    edges: Vec<Vec<Point>>,
+    fills: Vec<Color>,
+    stroke: Color,
+}
+
+impl IsohedralTiling {
+    /// Returns a tiling of kind with its default parameters and straight
+    /// edges, filled with fill.
+    pub fn new(kind: IsohedralType, fill: Color, stroke: Color) -> IsohedralTiling {
+        IsohedralTiling {
+            kind,
+            parameters: kind.default_parameters(),
+            edges: vec![Vec::new(); kind.edge_shapes()],
+            fills: vec![fill],
+            stroke,
+        }
+    }
+
+    /// Returns the tiling with parameters in place of its type's parameters,
+    /// ensuring there are as many as the type's defaults and that the tile
+    /// has area.
+    pub fn with_parameters(mut self, parameters: &[f64]) -> Result<IsohedralTiling> {
+        let expected = self.kind.default_parameters().len();
+        if parameters.len() != expected {
+            return Err(InvalidShape {
+                reason: format!(
+                    "isohedral type {} has {} parameters, not {}",
+                    self.kind.number(),
+                    expected,
+                    parameters.len()
+                ),
+            });
+        }
+        self.parameters = parameters.to_vec();
+        let (t1, t2) = self.translations();
+        if (t1.x * t2.y - t1.y * t2.x).abs() < shape::EPSILON {
+            return Err(InvalidShape {
+                reason: format!(
+                    "isohedral type {} tile with parameters {:?} has no area",
+                    self.kind.number(),
+                    parameters
+                ),
+            });
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the tiling with the edge shape with index edge drawn through
+    /// points (in the edge's frame, between its ends), ensuring the edge
+    /// shape exists.
+    pub fn with_edge(mut self, edge: usize, points: &[Point]) -> Result<IsohedralTiling> {
+        let length = self.edges.len();
+        let e = self.edges.get_mut(edge).ok_or(OutOfBounds {
+            index: edge,
+            length,
+            name: String::from("isohedral edge shapes"),
+        })?;
+        *e = points.to_vec();
+
+        Ok(self)
+    }
+
+    /// Returns the tiling with its tiles filled from fills, ensuring there is
+    /// at least one.
+    /// With three fills (or two, for parallelograms), no two tiles that share
+    /// an edge are filled alike.
+    pub fn with_fills(mut self, fills: &[Color]) -> Result<IsohedralTiling> {
+        if fills.is_empty() {
+            return Err(InvalidColor);
+        }
+        self.fills = fills.to_vec();
+
+        Ok(self)
+    }
+
+    /// Returns the tiling's type.
+    pub fn kind(&self) -> IsohedralType {
+        self.kind
+    }
+
+    /// Returns the tiling's parameters.
+    pub fn parameters(&self) -> &[f64] {
+        &self.parameters
+    }
+
+    /// Returns the points of each edge shape, in the edge's frame.
+    pub fn edges(&self) -> &[Vec<Point>] {
+        &self.edges
+    }
+
+    /// Returns the corners of the tile at the origin, in edge order, whose
+    /// first edge has length 1.
+    pub fn corners(&self) -> Vec<Point> {
+        let p = &self.parameters;
+        let steps = match self.kind {
+            IsohedralType::Ih1 => {
+                let (e0, e1, e2) = (
+                    Point { x: 1.0, y: 0.0 },
+                    Point { x: p[0], y: p[1] },
+                    Point { x: p[2], y: p[3] },
+                );
+                vec![e0, e1, e2, e0 * -1.0, e1 * -1.0]
+            }
+            IsohedralType::Ih41 => {
+                let (e0, e1) = (Point { x: 1.0, y: 0.0 }, Point { x: p[0], y: p[1] });
+                vec![e0, e1, e0 * -1.0]
+            }
+        };
+
+        let mut corners = vec![Point::origin()];
+        for s in steps {
+            corners.push(corners[corners.len() - 1] + s);
+        }
+
+        corners
+    }
+
+    /// Returns the outline of the tile at the origin through the points of
+    /// its edge shapes, ending where it starts.
+    pub fn outline(&self) -> Vec<Point> {
+        let corners = self.corners();
+        let n = corners.len();
+        let mut outline = vec![corners[0]];
+        for k in 0..n {
+            let (p0, p1) = (corners[k], corners[(k + 1) % n]);
+            let edge = &self.edges[k % self.edges.len()];
+            // The second edge of each pair is the first moved onto it, so it
+            // is traced in the first's frame and walked backward.
+            let points = match k < self.edges.len() {
+                true => edge.iter().map(|q| frame(p0, p1, *q)).collect(),
+                false => {
+                    let mut points = edge
+                        .iter()
+                        .map(|q| frame(p1, p0, *q))
+                        .collect::<Vec<Point>>();
+                    points.reverse();
+                    points
+                }
+            };
+            outline.extend(points);
+            outline.push(p1);
+        }
+
+        outline
+    }
+
+    /// Returns the two vectors that the tile is translated along to tile the
+    /// plane.
+    pub fn translations(&self) -> (Point, Point) {
+        let c = self.corners();
+        match self.kind {
+            // Neighbors across the fourth and fifth edges of a hexagon.
+            IsohedralType::Ih1 => (c[4] - c[0], c[5] - c[1]),
+            IsohedralType::Ih41 => (c[1] - c[0], c[3] - c[0]),
+        }
+    }
+
+    /// Returns the tiles covering a canvas of width by height pixels with
+    /// scale pixels per edge, centered on the origin.
+    /// Returns an error if scale is not a positive number.
+    pub fn tiles(&self, width: i32, height: i32, scale: f64) -> Result<Vec<IsohedralTile>> {
+        if !(scale > 0.0 && scale.is_finite()) {
+            return Err(InvalidShape {
+                reason: format!("scale {} is not positive", scale),
+            });
+        }
+
+        let outline = self.outline();
+        let (t1, t2) = self.translations();
+        let center = centroid(&outline);
+        let radius = outline
+            .iter()
+            .map(|p| math::hypot(p.x - center.x, p.y - center.y))
+            .fold(0.0, f64::max);
+        let (w, h) = (
+            width as f64 / 2.0 / scale + radius,
+            height as f64 / 2.0 / scale + radius,
+        );
+
+        // The lattice coordinates of the canvas' corners bound the copies
+        // that may reach it.
+        let det = t1.x * t2.y - t1.y * t2.x;
+        let coordinates = |p: Point| {
+            let (x, y) = (p.x - center.x, p.y - center.y);
+            ((x * t2.y - y * t2.x) / det, (t1.x * y - t1.y * x) / det)
+        };
+        let (mut i0, mut i1, mut j0, mut j1) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+        for (x, y) in [(-w, -h), (w, -h), (-w, h), (w, h)] {
+            let (i, j) = coordinates(Point { x, y });
+            i0 = i0.min(i);
+            i1 = i1.max(i);
+            j0 = j0.min(j);
+            j1 = j1.max(j);
+        }
+
+        let mut tiles = Vec::new();
+        for i in i0.floor() as i64..=i1.ceil() as i64 {
+            for j in j0.floor() as i64..=j1.ceil() as i64 {
+                let offset = t1 * i as f64 + t2 * j as f64;
+                let c = center + offset;
+                if c.x.abs() > w || c.y.abs() > h {
+                    continue;
+                }
+
+                // Neighbors are offset by t1, t2, and, for hexagons,
+                // t1 - t2, which these sums tell apart.
+                let n = self.fills.len() as i64;
+                let k = match n {
+                    2 => i + j,
+                    _ => i + 2 * j,
+                };
+                let fill = self.fills[k.rem_euclid(n) as usize];
+                tiles.push(IsohedralTile {
+                    points: outline.iter().map(|p| *p + offset).collect(),
+                    fill,
+                    stroke: self.stroke,
+                    paint: PaintMode::default(),
+                });
+            }
+        }
+
+        Ok(tiles)
+    }
+
+    /// Returns a model of width by height pixels with scale pixels per edge
+    /// whose canvas is covered by the tiling's tiles.
+    pub fn model(&self, width: i32, height: i32, scale: f64) -> Result<Model> {
+        let mut model = Model::new(width, height, scale);
+        for tile in self.tiles(width, height, scale)? {
+            model.add_tile(tile);
+        }
+
+        Ok(model)
+    }
+}
+
+/// One tile of an `IsohedralTiling`, which need not be convex.
+#[derive(Clone, Debug)]
+pub struct IsohedralTile {
+    points: Vec<Point>,
+    fill: Color,
+    stroke: Color,
+    paint: PaintMode,
+}
+
+impl IsohedralTile {
+    /// Returns the tile with the parts painted by paint mode.
+    pub fn with_paint(mut self, paint: PaintMode) -> IsohedralTile {
+        self.paint = paint;
+
+        self
+    }
+
+    /// Returns the tile's center, the centroid of its outline.
+    pub fn point(&self) -> Point {
+        centroid(&self.points)
+    }
+
+    /// Returns the tile's fill.
+    pub fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the tile's stroke.
+    pub fn stroke(&self) -> Color {
+        self.stroke
+    }
+}
+
+impl Polygon for IsohedralTile {
+    /// Returns the polygon's points.
+    /// The tile need not have an inscribed circle, so insetting it by margin
+    /// scales it about its center until its outline is margin closer to the
+    /// center.
+    fn points(&self, margin: f64) -> Result<Vec<Point>> {
+        let center = self.point();
+        let inradius = self
+            .points
+            .windows(2)
+            .map(|w| distance_to_segment(center, w[0], w[1]))
+            .fold(f64::INFINITY, f64::min);
+        let scale = match inradius > 0.0 {
+            true => (1.0 - margin / inradius).max(0.0),
+            false => 1.0,
+        };
+
+        Ok(self
+            .points
+            .iter()
+            .map(|p| Point {
+                x: center.x + (p.x - center.x) * scale,
+                y: center.y + (p.y - center.y) * scale,
+            })
+            .collect())
+    }
+
+    /// Renders the polygon.
+    #[cfg(feature = "cairo")]
+    fn render(&self, context: &cairo::Context, margin: f64) -> Result<()> {
+        self.render_snapped(context, margin, None)
+    }
+
+    /// Renders the polygon as `render` does, with its stroke snapped to the
+    /// centers of pixels pixel device units wide if pixel is some.
+    #[cfg(feature = "cairo")]
+    fn render_snapped(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        shape::render_snapped(
+            context,
+            &self.points(margin)?,
+            self.fill,
+            self.stroke,
+            self.paint,
+            pixel,
+        )
+    }
+}
+
+impl Tile for IsohedralTile {
+    /// Returns the tile's center.
+    fn point(&self) -> Point {
+        IsohedralTile::point(self)
+    }
+
+    /// Returns a copy of the tile moved by vector.
+    fn translated(&self, vector: Point) -> Box<dyn Tile> {
+        let mut t = self.clone();
+        t.points = self.points.iter().map(|p| *p + vector).collect();
+
+        Box::new(t)
+    }
+
+    /// Returns a copy of the tile rotated by angle (in radians) about center.
+    fn rotated(&self, angle: f64, center: Point) -> Box<dyn Tile> {
+        let mut t = self.clone();
+        t.points = self
+            .points
+            .iter()
+            .map(|p| p.rotate(angle, center))
+            .collect();
+
+        Box::new(t)
+    }
+
+    /// Returns a copy of the tile mirrored across axis.
+    fn reflected(&self, axis: &Axis) -> Box<dyn Tile> {
+        let mut t = self.clone();
+        t.points = self.points.iter().map(|p| axis.reflect(*p)).collect();
+
+        Box::new(t)
+    }
+
+    /// Returns a copy of the tile filled with fill.
+    fn filled(&self, fill: Color) -> Box<dyn Tile> {
+        let mut t = self.clone();
+        t.fill = fill;
+
+        Box::new(t)
+    }
+
+    /// Returns the color the tile is filled with.
+    fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the color the tile is stroked with.
+    fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns whether the tile is filled, stroked, or both.
+    fn paint(&self) -> PaintMode {
+        self.paint
+    }
+}
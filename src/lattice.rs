@@ -0,0 +1,280 @@
+use std::{cmp::Ordering::Less, f64::consts::PI};
+
+use crate::{math, CellId, Color, Model, Point, Result, Shape};
+
+/// The height of a row of triangles with unit edges.
+const ROW_HEIGHT: f64 = 0.866_025_403_784_438_6;
+
+/// A tiling of hexagons addressed by axial coordinates (q, r), layered on top
+/// of a `Model`.
+/// The hexagons point up, with cell (0, 0) at the origin; q counts cells to
+/// the right along a row and r counts rows down, each row shifted half a cell
+/// to the right of the one above it.
+#[derive(Clone, Debug)]
+pub struct HexGrid {
+    model: Model,
+}
+
+impl HexGrid {
+    /// Returns a grid of hexagons covering a width by height canvas with scale
+    /// pixels per edge.
+    pub fn new(width: i32, height: i32, scale: f64, fill: Color, stroke: Color) -> Result<HexGrid> {
+        let model = fill_model(
+            width,
+            height,
+            scale,
+            &[Shape::new(6, fill, stroke)?],
+            &[HexGrid::center(1, 0), HexGrid::center(0, 1)],
+        )?;
+
+        Ok(HexGrid { model })
+    }
+
+    /// Returns the center of cell (q, r).
+    pub fn at(&self, q: i32, r: i32) -> Point {
+        HexGrid::center(q, r)
+    }
+
+    /// Returns the coordinates of the cell containing point.
+    pub fn coordinates(&self, point: Point) -> (i32, i32) {
+        let q = point.x / 3f64.sqrt() - point.y / 3.0;
+        let r = point.y * 2.0 / 3.0;
+
+        // Round in cube coordinates, fixing up the coordinate that rounded
+        // the most so the three still sum to zero.
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+
+        (rq as i32, rr as i32)
+    }
+
+    /// Fills cell (q, r) with fill, ensuring the cell is on the canvas.
+    pub fn fill(&mut self, q: i32, r: i32, fill: Color) -> Result<()> {
+        self.model.fill_at(self.at(q, r), fill)
+    }
+
+    /// Returns the identifier of cell (q, r) for `Model::paint_cells`, or none
+    /// if the cell is not on the canvas.
+    pub fn cell(&self, q: i32, r: i32) -> Option<CellId> {
+        self.model.cell_at(self.at(q, r))
+    }
+
+    /// Returns the grid's model.
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Returns the grid's model, which may be rendered or further styled.
+    pub fn model_mut(&mut self) -> &mut Model {
+        &mut self.model
+    }
+
+    /// Returns the center of cell (q, r).
+    pub(crate) fn center(q: i32, r: i32) -> Point {
+        Point {
+            x: 3f64.sqrt() * (q as f64 + r as f64 / 2.0),
+            y: 1.5 * r as f64,
+        }
+    }
+}
+
+/// A tiling of squares addressed by column and row, layered on top of a
+/// `Model`.
+/// Cell (0, 0) is at the origin; columns count to the right and rows down.
+#[derive(Clone, Debug)]
+pub struct SquareGrid {
+    model: Model,
+}
+
+impl SquareGrid {
+    /// Returns a grid of squares covering a width by height canvas with scale
+    /// pixels per edge.
+    pub fn new(
+        width: i32,
+        height: i32,
+        scale: f64,
+        fill: Color,
+        stroke: Color,
+    ) -> Result<SquareGrid> {
+        let square = Shape::new(4, fill, stroke)?.rotated(PI / 4.0, Point::origin());
+        let model = fill_model(
+            width,
+            height,
+            scale,
+            &[square],
+            &[Point { x: 1.0, y: 0.0 }, Point { x: 0.0, y: 1.0 }],
+        )?;
+
+        Ok(SquareGrid { model })
+    }
+
+    /// Returns the center of the cell in column and row.
+    pub fn at(&self, column: i32, row: i32) -> Point {
+        Point {
+            x: column as f64,
+            y: row as f64,
+        }
+    }
+
+    /// Returns the column and row of the cell containing point.
+    pub fn coordinates(&self, point: Point) -> (i32, i32) {
+        (point.x.round() as i32, point.y.round() as i32)
+    }
+
+    /// Fills the cell in column and row with fill, ensuring the cell is on the
+    /// canvas.
+    pub fn fill(&mut self, column: i32, row: i32, fill: Color) -> Result<()> {
+        self.model.fill_at(self.at(column, row), fill)
+    }
+
+    /// Returns the identifier of the cell in column and row for
+    /// `Model::paint_cells`, or none if the cell is not on the canvas.
+    pub fn cell(&self, column: i32, row: i32) -> Option<CellId> {
+        self.model.cell_at(self.at(column, row))
+    }
+
+    /// Returns the grid's model.
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Returns the grid's model, which may be rendered or further styled.
+    pub fn model_mut(&mut self) -> &mut Model {
+        &mut self.model
+    }
+}
+
+/// A tiling of triangles addressed by column and row, layered on top of a
+/// `Model`.
+/// Each row alternates between triangles pointing up and down, with columns
+/// half an edge apart: cell (column, row) points up when column + row is even.
+/// Cell (0, 0) points up and is centered at the origin; columns count to the
+/// right and rows down.
+#[derive(Clone, Debug)]
+pub struct TriangleGrid {
+    model: Model,
+}
+
+impl TriangleGrid {
+    /// Returns a grid of triangles covering a width by height canvas with
+    /// scale pixels per edge.
+    pub fn new(
+        width: i32,
+        height: i32,
+        scale: f64,
+        fill: Color,
+        stroke: Color,
+    ) -> Result<TriangleGrid> {
+        let up = Shape::new(3, fill, stroke)?;
+        let down = up
+            .rotated(PI, Point::origin())
+            .clone_at(TriangleGrid::center(1, 0));
+        let model = fill_model(
+            width,
+            height,
+            scale,
+            &[up, down],
+            &[
+                Point { x: 1.0, y: 0.0 },
+                Point {
+                    x: 0.5,
+                    y: ROW_HEIGHT,
+                },
+            ],
+        )?;
+
+        Ok(TriangleGrid { model })
+    }
+
+    /// Returns the center of the cell in column and row.
+    pub fn at(&self, column: i32, row: i32) -> Point {
+        TriangleGrid::center(column, row)
+    }
+
+    /// Returns whether the cell in column and row points up.
+    pub fn points_up(&self, column: i32, row: i32) -> bool {
+        (column + row).rem_euclid(2) == 0
+    }
+
+    /// Returns the column and row of the cell containing point.
+    pub fn coordinates(&self, point: Point) -> (i32, i32) {
+        // Rows span from the apexes of the triangles pointing up to their
+        // bases.
+        let row = ((point.y + ROW_HEIGHT * 2.0 / 3.0) / ROW_HEIGHT).floor() as i32;
+        let column = (point.x * 2.0).round() as i32;
+        let distance = |c: i32| {
+            let center = TriangleGrid::center(c, row);
+            (point.x - center.x).powi(2) + (point.y - center.y).powi(2)
+        };
+        // Neighboring triangles mirror each other across their shared edge, so
+        // the triangle containing point is the one whose center is nearest.
+        let column = (column - 1..=column + 1)
+            .min_by(|a, b| distance(*a).partial_cmp(&distance(*b)).unwrap_or(Less))
+            .unwrap_or(column);
+
+        (column, row)
+    }
+
+    /// Fills the cell in column and row with fill, ensuring the cell is on the
+    /// canvas.
+    pub fn fill(&mut self, column: i32, row: i32, fill: Color) -> Result<()> {
+        self.model.fill_at(self.at(column, row), fill)
+    }
+
+    /// Returns the identifier of the cell in column and row for
+    /// `Model::paint_cells`, or none if the cell is not on the canvas.
+    pub fn cell(&self, column: i32, row: i32) -> Option<CellId> {
+        self.model.cell_at(self.at(column, row))
+    }
+
+    /// Returns the grid's model.
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Returns the grid's model, which may be rendered or further styled.
+    pub fn model_mut(&mut self) -> &mut Model {
+        &mut self.model
+    }
+
+    /// Returns the center of the cell in column and row.
+    fn center(column: i32, row: i32) -> Point {
+        let up = (column + row).rem_euclid(2) == 0;
+
+        Point {
+            x: column as f64 / 2.0,
+            y: row as f64 * ROW_HEIGHT - if up { 0.0 } else { ROW_HEIGHT / 3.0 },
+        }
+    }
+}
+
+/// Returns a model of a width by height canvas with scale pixels per edge,
+/// filled with shapes repeated along vectors.
+pub(crate) fn fill_model(
+    width: i32,
+    height: i32,
+    scale: f64,
+    shapes: &[Shape],
+    vectors: &[Point],
+) -> Result<Model> {
+    let mut model = Model::new(width, height, scale);
+    for s in shapes {
+        model.add(*s)?;
+    }
+
+    // Enough steps along the shortest vector to reach every corner.
+    let diagonal = math::hypot(width as f64, height as f64) / scale;
+    let step = vectors
+        .iter()
+        .map(|v| math::hypot(v.x, v.y))
+        .fold(f64::INFINITY, f64::min);
+    model.repeat_by(vectors, (diagonal / step).ceil() as usize + 1)?;
+
+    Ok(model)
+}
@@ -10,8 +10,11 @@
 //!
 //! # Requirements
 //!
-//! *tiling* uses [cairo-rs](https://crates.io/crates/cairo-rs) for rendering and
-//! requires [cairo](https://www.cairographics.org/download/) to be installed.
+//! *tiling* uses [cairo-rs](https://crates.io/crates/cairo-rs) for rendering and,
+//! with its default `cairo` feature, requires
+//! [cairo](https://www.cairographics.org/download/) to be installed.
+//! Without it, models are still built, saved, and written as SVG, and the
+//! `skia` feature renders them with `Model::render_skia`.
 //!
 //! # Example
 //!
@@ -30,7 +33,7 @@
 //! let stroke = Color::new(242, 60, 60)?;
 //! let fill_hexagon = Color::new(242, 194, 106)?;
 //!
-//! model.add(Shape::new(6, fill_hexagon, stroke)?);
+//! model.add(Shape::new(6, fill_hexagon, stroke)?)?;
 //! ```
 //!
 //! At this point we can render the model.
@@ -38,10 +41,13 @@
 //! ```rust
 //! let background = Color::new(242, 242, 242)?;
 //! let margin = 0.1;
-//! let show_labels = false;
 //! let line_width = 0.1;
 //!
-//! let render = model.render(background, margin, line_width, show_labels)?;
+//! let options = RenderOptions::new()
+//!     .background(background)
+//!     .margin(margin)
+//!     .line_width(line_width);
+//! let render = model.render(CairoBackend::new(&options))?;
 //! render.write_to_png("output.png")?;
 //! ```
 //!
@@ -57,13 +63,16 @@
 //! (by their index).
 //! In this example, the square is attached to the hexagon (index `0`).
 //!
-//! > When `show_labels` is `true`, each shape is labeled with its index.
+//! > When labels are on (`RenderOptions::labels`, drawn by `Model::render_with`),
+//! > each shape is labeled with its index.
+//! > Shapes placed by `repeat` are labeled with the index of the shape they
+//! > repeat in parentheses.
 //!
 //! The second paramter `0..6` is a range that indicates the edge(s) to attach to
 //! (by their index).
 //! In this example, the square is attached to all six edges of the hexagon.
 //!
-//! > When `show_labels` is `true`, each edge is labeled with its index.
+//! > When labels are on, each edge is labeled with its index.
 //!
 //! The final paramter defines the shape to add (a square).
 //!
@@ -99,12 +108,141 @@
 //!
 //! Dual tilings may be created using the `render_dual` method.
 //! A tiling's dual is formed by drawing edges between the centers of adjacent polygons.
+//!
+//! # Stability
+//!
+//! Everything exported from the crate root follows semantic versioning.
+//! APIs that are still taking shape live in the `experimental` module, which
+//! requires the `unstable` feature and may change in any release.
+//! When one of them is stabilized it is also exported from the crate root,
+//! and its experimental path keeps working until the next major release.
+//!
+//! # Profiling
+//!
+//! With the `tracing` feature, repeating, building a dual, and rendering
+//! each run in a `tracing` span at the debug level that records the number
+//! of shapes involved, so a subscriber that times spans shows where a slow
+//! pattern spends its time.
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+#[macro_use]
+mod macros;
+
+#[cfg(feature = "cairo")]
+pub use backend::CairoBackend;
+pub use backend::{RenderBackend, SvgBackend};
+#[cfg(feature = "cairo")]
+pub use batch::batch;
+pub use builder::ModelBuilder;
 pub use color::Color;
+pub use colormap::Colormap;
+pub use curved::CurvedShape;
+pub use diff::TilingDiff;
 pub use error::{Error, Result};
-pub use model::Model;
-pub use shape::{Dual, Point, Polygon, Shape};
+pub use generator::Generator;
+pub use history::Operation;
+pub use irregular::IrregularShape;
+pub use isohedral::{IsohedralTile, IsohedralTiling, IsohedralType};
+pub use lattice::{HexGrid, SquareGrid, TriangleGrid};
+pub use maze::{Maze, MazeAlgorithm};
+pub use mesh::Mesh;
+pub use mesh3d::Mesh3D;
+pub use model::{
+    Anchor, CellId, DualBoundary, DuplicatePolicy, EdgeAnchor, EdgeId, EdgeNumbering, IntoShape,
+    Model, Origin, Placements, ShapeAnchor, ShapeId, ShapeRef, ShapeSet, ShapeSpec, StyleId,
+    Vertex,
+};
+pub use observer::{Change, ChangeFn};
+pub use pattern::Pattern;
+pub use penrose::{Arrow, PenroseKind, PenroseRhombus};
+#[cfg(feature = "pdf")]
+pub use print::PrintOptions;
+#[cfg(feature = "cairo")]
+pub use render::RenderStyle;
+pub use render::{Annotation, Metadata, RenderOptions, VertexLabel, Warning};
+#[cfg(feature = "cairo")]
+pub use render::{Font, Render};
+pub use rhombus::Rhombus;
+pub use scene::{Node, Scene};
+pub use sdf::DistanceField;
+pub use shape::{
+    Affine, Axis, Dual, Point, Polygon, PolygonKind, Rect, Shape, Side, Tile, Vector, Winding,
+};
+#[cfg(feature = "skia")]
+pub use skia::{SkiaBackend, SkiaRender};
+pub use snapshot::Tiling;
+pub use spherical::{Polyhedron, Projection};
+pub use stats::{Stats, VertexFigure};
+pub use style::{
+    Antialias, Blend, Corner, EdgeStyle, Fill, Fit, LineCap, LineJoin, Outline, PaintMode, Style,
+    StyleSet, Texture,
+};
+#[cfg(feature = "cairo")]
+pub use style::{Motif, MotifFn};
+pub use swatches::Palette;
+pub use theme::Theme;
+pub use truchet::{Truchet, TruchetLattice, TruchetStyle};
 
+pub mod backend;
+#[cfg(feature = "cairo")]
+pub mod batch;
+pub mod builder;
+pub mod catalog;
 pub mod color;
+mod coloring;
+pub mod colormap;
+pub mod curved;
+pub mod diff;
+mod dot;
 pub mod error;
+#[cfg(feature = "fast-png")]
+mod fastpng;
+mod file;
+pub mod generator;
+mod geojson;
+mod grid;
+mod hash;
+pub mod history;
+pub mod irregular;
+pub mod isohedral;
+pub mod lattice;
+mod math;
+pub mod maze;
+pub mod mesh;
+pub mod mesh3d;
 pub mod model;
+pub mod observer;
+pub mod pattern;
+#[cfg(feature = "toml")]
+pub mod patterns;
+pub mod penrose;
+mod plot;
+#[cfg(feature = "pdf")]
+pub mod print;
+#[cfg(feature = "cairo")]
+mod quantize;
+pub mod render;
+pub mod rhombus;
+pub mod scene;
+pub mod sdf;
 pub mod shape;
+#[cfg(feature = "skia")]
+pub mod skia;
+pub mod snapshot;
+pub mod spherical;
+pub mod stats;
+pub mod style;
+mod svg;
+pub mod swatches;
+mod symmetry;
+mod table;
+pub mod testing;
+pub mod theme;
+pub mod truchet;
+mod voronoi;
+
+cfg_unstable! {
+    pub mod experimental;
+    #[cfg(feature = "cairo")]
+    mod fit;
+}
@@ -0,0 +1,11 @@
+/// Declares items that are only compiled with the `unstable` feature, and
+/// marks them in the documentation as requiring it.
+macro_rules! cfg_unstable {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "unstable")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "unstable")))]
+            $item
+        )*
+    };
+}
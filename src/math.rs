@@ -0,0 +1,114 @@
+//! The floating-point functions used to place shapes and compare their points.
+//!
+//! Basic arithmetic and square roots are correctly rounded on every platform,
+//! but the standard library's transcendental functions call the platform's
+//! math library, whose results may differ in the last bit. Those differences
+//! can move a point across a rounding boundary when it is snapped to the
+//! model's precision, changing which shapes are deduplicated and so the
+//! model's `geometry_hash`.
+//! With the `deterministic` feature these functions are computed by the
+//! pure-Rust `libm` crate instead, so a model built on any platform has
+//! bit-identical geometry.
+
+/// Returns the sine of x (in radians).
+#[cfg(feature = "deterministic")]
+pub(crate) fn sin(x: f64) -> f64 {
+    libm::sin(x)
+}
+
+/// Returns the sine of x (in radians).
+#[cfg(not(feature = "deterministic"))]
+pub(crate) fn sin(x: f64) -> f64 {
+    x.sin()
+}
+
+/// Returns the cosine of x (in radians).
+#[cfg(feature = "deterministic")]
+pub(crate) fn cos(x: f64) -> f64 {
+    libm::cos(x)
+}
+
+/// Returns the cosine of x (in radians).
+#[cfg(not(feature = "deterministic"))]
+pub(crate) fn cos(x: f64) -> f64 {
+    x.cos()
+}
+
+/// Returns the sine and cosine of x (in radians).
+#[cfg(feature = "deterministic")]
+pub(crate) fn sin_cos(x: f64) -> (f64, f64) {
+    libm::sincos(x)
+}
+
+/// Returns the sine and cosine of x (in radians).
+#[cfg(not(feature = "deterministic"))]
+pub(crate) fn sin_cos(x: f64) -> (f64, f64) {
+    x.sin_cos()
+}
+
+/// Returns the tangent of x (in radians).
+#[cfg(feature = "deterministic")]
+pub(crate) fn tan(x: f64) -> f64 {
+    libm::tan(x)
+}
+
+/// Returns the tangent of x (in radians).
+#[cfg(not(feature = "deterministic"))]
+pub(crate) fn tan(x: f64) -> f64 {
+    x.tan()
+}
+
+/// Returns the arccosine of x, in radians.
+#[cfg(feature = "deterministic")]
+pub(crate) fn acos(x: f64) -> f64 {
+    libm::acos(x)
+}
+
+/// Returns the arccosine of x, in radians.
+#[cfg(not(feature = "deterministic"))]
+pub(crate) fn acos(x: f64) -> f64 {
+    x.acos()
+}
+
+/// Returns the angle (in radians) of the vector from the origin to (x, y).
+#[cfg(feature = "deterministic")]
+pub(crate) fn atan2(y: f64, x: f64) -> f64 {
+    libm::atan2(y, x)
+}
+
+/// Returns the angle (in radians) of the vector from the origin to (x, y).
+#[cfg(not(feature = "deterministic"))]
+pub(crate) fn atan2(y: f64, x: f64) -> f64 {
+    y.atan2(x)
+}
+
+/// Returns the length of the vector from the origin to (x, y).
+#[cfg(feature = "deterministic")]
+pub(crate) fn hypot(x: f64, y: f64) -> f64 {
+    libm::hypot(x, y)
+}
+
+/// Returns the length of the vector from the origin to (x, y).
+#[cfg(not(feature = "deterministic"))]
+pub(crate) fn hypot(x: f64, y: f64) -> f64 {
+    x.hypot(y)
+}
+
+/// Returns x raised to the power n.
+/// `f64::powi` may be computed differently by different targets, so with the
+/// `deterministic` feature the power is taken by repeated multiplication,
+/// which is exact for the powers of ten used as precisions.
+#[cfg(feature = "deterministic")]
+pub(crate) fn powi(x: f64, n: i32) -> f64 {
+    let p = (0..n.unsigned_abs()).fold(1.0, |p, _| p * x);
+    match n < 0 {
+        true => 1.0 / p,
+        false => p,
+    }
+}
+
+/// Returns x raised to the power n.
+#[cfg(not(feature = "deterministic"))]
+pub(crate) fn powi(x: f64, n: i32) -> f64 {
+    x.powi(n)
+}
@@ -0,0 +1,174 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{CellId, Point};
+
+/// An algorithm that carves a maze's passages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MazeAlgorithm {
+    /// A randomized depth-first search that carves a passage to an unvisited
+    /// neighbor while there is one and backs up when there is not (the
+    /// default), making long, winding corridors.
+    #[default]
+    Backtracker,
+    /// Randomized Kruskal's algorithm, which opens walls in a random order
+    /// unless the cells on either side are already connected, making many
+    /// short dead ends.
+    Kruskal,
+}
+
+/// A perfect maze carved through a tiling: exactly one path joins any two of
+/// its cells (within one connected part of the tiling).
+/// The cells are the shapes of the tiling, the passages are the edges opened
+/// between neighboring cells, and the walls are every other edge, which are
+/// all that `Model::render_maze` strokes.
+#[derive(Clone, Debug)]
+pub struct Maze {
+    cells: Vec<CellId>,
+    passages: Vec<(CellId, CellId)>,
+    walls: Vec<(Point, Point)>,
+}
+
+impl Maze {
+    /// Returns the maze carved by algorithm through cells, whose shapes are
+    /// outlined by outlines and whose neighbors' indexes are neighbors, with
+    /// points compared to precision decimal places.
+    pub(crate) fn generate(
+        cells: Vec<CellId>,
+        outlines: &[Vec<Point>],
+        neighbors: &[Vec<usize>],
+        algorithm: MazeAlgorithm,
+        seed: u64,
+        precision: i32,
+    ) -> Maze {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let passages = match algorithm {
+            MazeAlgorithm::Backtracker => backtrack(neighbors, &mut rng),
+            MazeAlgorithm::Kruskal => kruskal(neighbors, &mut rng),
+        };
+
+        // An edge is a wall unless it is shared by the two cells of a
+        // passage.
+        let open = passages
+            .iter()
+            .copied()
+            .collect::<HashSet<(usize, usize)>>();
+        let mut edges = HashMap::new();
+        for (i, points) in outlines.iter().enumerate() {
+            for w in points.windows(2) {
+                let (k0, k1) = (w[0].key(precision), w[1].key(precision));
+                edges
+                    .entry((k0.min(k1), k0.max(k1)))
+                    .or_insert_with(|| ((w[0], w[1]), Vec::new()))
+                    .1
+                    .push(i);
+            }
+        }
+        let mut walls = edges
+            .into_iter()
+            .filter(|(_, (_, sides))| match sides[..] {
+                [a, b] => !open.contains(&(a.min(b), a.max(b))),
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+        walls.sort_by_key(|(key, _)| *key);
+
+        Maze {
+            passages: passages
+                .into_iter()
+                .map(|(a, b)| (cells[a], cells[b]))
+                .collect(),
+            walls: walls.into_iter().map(|(_, (edge, _))| edge).collect(),
+            cells,
+        }
+    }
+
+    /// Returns the maze's cells, ordered as `Model::cells` orders them.
+    pub fn cells(&self) -> &[CellId] {
+        &self.cells
+    }
+
+    /// Returns the pairs of neighboring cells joined by a passage.
+    pub fn passages(&self) -> &[(CellId, CellId)] {
+        &self.passages
+    }
+
+    /// Returns the maze's walls: every edge of its cells' shapes that is not
+    /// opened by a passage, including the edges on its boundary.
+    pub fn walls(&self) -> &[(Point, Point)] {
+        &self.walls
+    }
+}
+
+/// Returns the passages (as pairs of indexes, the smaller first) carved by
+/// a randomized depth-first search through every connected part of the
+/// graph with the adjacency lists neighbors.
+fn backtrack(neighbors: &[Vec<usize>], rng: &mut ChaCha8Rng) -> Vec<(usize, usize)> {
+    let mut visited = vec![false; neighbors.len()];
+    let mut passages = Vec::new();
+    for root in 0..neighbors.len() {
+        if visited[root] {
+            continue;
+        }
+
+        visited[root] = true;
+        let mut stack = vec![root];
+        while let Some(&cell) = stack.last() {
+            let unvisited = neighbors[cell]
+                .iter()
+                .copied()
+                .filter(|n| !visited[*n])
+                .collect::<Vec<usize>>();
+            match unvisited.choose(rng) {
+                Some(&next) => {
+                    visited[next] = true;
+                    passages.push((cell.min(next), cell.max(next)));
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    passages
+}
+
+/// Returns the passages (as pairs of indexes, the smaller first) opened by
+/// randomized Kruskal's algorithm over the graph with the adjacency lists
+/// neighbors.
+fn kruskal(neighbors: &[Vec<usize>], rng: &mut ChaCha8Rng) -> Vec<(usize, usize)> {
+    let mut edges = neighbors
+        .iter()
+        .enumerate()
+        .flat_map(|(a, ns)| ns.iter().filter(move |b| a < **b).map(move |b| (a, *b)))
+        .collect::<Vec<(usize, usize)>>();
+    edges.shuffle(rng);
+
+    // Each cell's parent in a forest of the connected sets of cells.
+    let mut parents = (0..neighbors.len()).collect::<Vec<usize>>();
+    let mut passages = Vec::new();
+    for (a, b) in edges {
+        let (ra, rb) = (root(&mut parents, a), root(&mut parents, b));
+        if ra != rb {
+            parents[ra] = rb;
+            passages.push((a, b));
+        }
+    }
+
+    passages
+}
+
+/// Returns the root of the set containing i in the forest parents, halving
+/// the path to it along the way.
+fn root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+
+    i
+}
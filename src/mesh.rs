@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use crate::{shape::Key, Point, Polygon, Result, Shape};
+
+/// A tiling's geometry as shared vertices, edges, and faces.
+/// Edges and faces refer to vertices by their index, so vertices that are
+/// shared by several shapes appear only once.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    vertices: Vec<Point>,
+    edges: Vec<(usize, usize)>,
+    faces: Vec<Vec<usize>>,
+}
+
+impl Mesh {
+    /// Returns the mesh of shapes, merging vertices that are equal to precision
+    /// decimal places.
+    pub(crate) fn new<'a, I>(shapes: I, precision: i32) -> Result<Mesh>
+    where
+        I: IntoIterator<Item = &'a Shape>,
+    {
+        let mut mesh = Mesh::default();
+        let mut vertices: HashMap<Key, usize> = HashMap::new();
+        let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+
+        for s in shapes {
+            let points = s.points(0.0)?;
+            let face = points[0..points.len() - 1]
+                .iter()
+                .map(|p| {
+                    *vertices.entry(p.key(precision)).or_insert_with(|| {
+                        mesh.vertices.push(*p);
+                        mesh.vertices.len() - 1
+                    })
+                })
+                .collect::<Vec<usize>>();
+
+            for (i, v0) in face.iter().enumerate() {
+                let v1 = face[(i + 1) % face.len()];
+                let key = (*v0.min(&v1), *v0.max(&v1));
+                edges.entry(key).or_insert_with(|| {
+                    mesh.edges.push(key);
+                    mesh.edges.len() - 1
+                });
+            }
+
+            mesh.faces.push(face);
+        }
+
+        Ok(mesh)
+    }
+
+    /// Returns the mesh's vertices.
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Returns the mesh's edges as pairs of vertex indexes.
+    /// Each edge appears once, with its smaller vertex index first.
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    /// Returns the mesh's faces (one per shape) as lists of vertex indexes in
+    /// the order they appear around the shape.
+    pub fn faces(&self) -> &[Vec<usize>] {
+        &self.faces
+    }
+}
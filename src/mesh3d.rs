@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use crate::{render, Error, Error::*, Point, Polygon, Result, Shape};
+
+/// A solid made of triangles, such as a tiling whose shapes are extruded into
+/// prisms by `Model::extrude` so it can be 3D printed as a tray, trivet, or
+/// stamp.
+/// Coordinates are in model units (a shape's side has length 1), with the y
+/// axis pointing up and the z axis pointing out of the tiling, and every
+/// triangle is wound counterclockwise as seen from outside of the solid.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh3D {
+    vertices: Vec<[f64; 3]>,
+    triangles: Vec<[usize; 3]>,
+    solids: Vec<(usize, usize)>,
+}
+
+impl Mesh3D {
+    /// Returns the mesh of shapes, each inset by margin and extruded into a
+    /// prism height high, ensuring height is positive and margin is at least
+    /// 0 and less than every shape's inradius, so no shape is inset to
+    /// nothing.
+    /// Each solid keeps the index of its shape among shapes.
+    pub(crate) fn extrude<'a, I>(shapes: I, height: f64, margin: f64) -> Result<Mesh3D>
+    where
+        I: IntoIterator<Item = &'a Shape>,
+    {
+        if !(height > 0.0 && height.is_finite()) {
+            return Err(InvalidShape {
+                reason: format!("extrusion height {} is not positive", height),
+            });
+        }
+        render::check_margin("margin", margin)?;
+
+        let shapes = shapes.into_iter().collect::<Vec<&Shape>>();
+        if let Some(s) = shapes.iter().find(|s| margin >= s.inradius()) {
+            return Err(Error::margin_reaches(
+                "margin",
+                margin,
+                s.inradius(),
+                s.sides(),
+            ));
+        }
+
+        let mut mesh = Mesh3D::default();
+        for (i, s) in shapes.into_iter().enumerate() {
+            let mut points = s.points(margin)?;
+            points.pop();
+            mesh.prism(i, &points, height);
+        }
+
+        Ok(mesh)
+    }
+
+    /// Adds the prism height high over the polygon with vertices points, the
+    /// shape with index index.
+    fn prism(&mut self, index: usize, points: &[Point], height: f64) {
+        // Flipping the y axis makes the polygon's winding counterclockwise
+        // when its signed area is positive.
+        let mut ring = points
+            .iter()
+            .map(|p| (p.x, -p.y))
+            .collect::<Vec<(f64, f64)>>();
+        let signed_area: f64 = (0..ring.len())
+            .map(|i| {
+                let (p, q) = (ring[i], ring[(i + 1) % ring.len()]);
+                p.0 * q.1 - q.0 * p.1
+            })
+            .sum();
+        if signed_area < 0.0 {
+            ring.reverse();
+        }
+
+        let n = ring.len();
+        let base = self.vertices.len();
+        self.vertices
+            .extend(ring.iter().map(|(x, y)| [*x, *y, 0.0]));
+        self.vertices
+            .extend(ring.iter().map(|(x, y)| [*x, *y, height]));
+        let (bottom, top) = (|i: usize| base + i % n, |i: usize| base + n + i % n);
+
+        // Shapes are convex, so their faces are fans of triangles.
+        for i in 1..n - 1 {
+            self.triangles.push([top(0), top(i), top(i + 1)]);
+            self.triangles.push([bottom(0), bottom(i + 1), bottom(i)]);
+        }
+        for i in 0..n {
+            self.triangles.push([bottom(i), bottom(i + 1), top(i + 1)]);
+            self.triangles.push([bottom(i), top(i + 1), top(i)]);
+        }
+        self.solids.push((index, self.triangles.len()));
+    }
+
+    /// Returns the mesh's vertices as x, y, and z coordinates.
+    pub fn vertices(&self) -> &[[f64; 3]] {
+        &self.vertices
+    }
+
+    /// Returns the mesh's triangles as triples of vertex indexes.
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    /// Returns the number of solids (one per extruded shape) in the mesh.
+    pub fn len(&self) -> usize {
+        self.solids.len()
+    }
+
+    /// Returns true if the mesh has no solids.
+    pub fn is_empty(&self) -> bool {
+        self.solids.is_empty()
+    }
+
+    /// Returns the mesh as a Wavefront OBJ document, with each solid in a
+    /// group of its own named "shape-{index}", where index is the index of
+    /// its shape (the face of `Model::to_mesh` it was extruded from).
+    pub fn to_obj(&self) -> String {
+        // Writing to a String cannot fail.
+        let mut obj = String::new();
+        for [x, y, z] in self.vertices.iter() {
+            let _ = writeln!(obj, "v {} {} {}", x, y, z);
+        }
+        let mut start = 0;
+        for (index, end) in self.solids.iter() {
+            let _ = writeln!(obj, "g shape-{}", index);
+            for [a, b, c] in self.triangles[start..*end].iter() {
+                // OBJ numbers vertices from 1.
+                let _ = writeln!(obj, "f {} {} {}", a + 1, b + 1, c + 1);
+            }
+            start = *end;
+        }
+
+        obj
+    }
+
+    /// Returns the mesh as an ASCII STL document named name, with a normal
+    /// for each triangle.
+    pub fn to_stl(&self, name: &str) -> String {
+        // Writing to a String cannot fail.
+        let mut stl = String::new();
+        let _ = writeln!(stl, "solid {}", name);
+        for t in self.triangles.iter() {
+            let [a, b, c] = [
+                self.vertices[t[0]],
+                self.vertices[t[1]],
+                self.vertices[t[2]],
+            ];
+            let [nx, ny, nz] = normal(a, b, c);
+            let _ = writeln!(stl, "  facet normal {} {} {}\n    outer loop", nx, ny, nz);
+            for [x, y, z] in [a, b, c].iter() {
+                let _ = writeln!(stl, "      vertex {} {} {}", x, y, z);
+            }
+            let _ = writeln!(stl, "    endloop\n  endfacet");
+        }
+        let _ = writeln!(stl, "endsolid {}", name);
+
+        stl
+    }
+}
+
+/// Returns the unit normal of the triangle a, b, c, which points toward the
+/// side it is wound counterclockwise as seen from.
+fn normal(a: [f64; 3], b: [f64; 3], c: [f64; 3]) -> [f64; 3] {
+    let (u, v) = (
+        [b[0] - a[0], b[1] - a[1], b[2] - a[2]],
+        [c[0] - a[0], c[1] - a[1], c[2] - a[2]],
+    );
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if length == 0.0 {
+        return [0.0; 3];
+    }
+
+    [n[0] / length, n[1] / length, n[2] / length]
+}
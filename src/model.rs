@@ -1,260 +1,8712 @@
-use std::{cmp::Ordering::Less, collections::HashMap, fs::File, ops::Range, path::Path};
+use std::{
+    cmp::Ordering::{Equal, Less},
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    f64::consts::PI,
+    fs,
+    marker::PhantomData,
+    ops::Range,
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
-use crate::{Color, Dual, Error::*, Point, Polygon, Result, Shape};
+#[cfg(feature = "cairo")]
+use std::fmt::Write;
+
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+#[cfg(feature = "cairo")]
+pub use crate::render::Render;
+#[cfg(feature = "cairo")]
+pub use crate::render::RenderStyle;
+pub use crate::render::{Metadata, RenderOptions, Warning};
+use crate::{
+    coloring,
+    curved::distance_to_segment,
+    dot, file,
+    geojson::{self, Feature},
+    grid::{Clusters, Grid, CELL_SIZE},
+    hash::Fnv,
+    history::History,
+    irregular, math,
+    observer::Observers,
+    plot, render,
+    shape::{self, Key},
+    stats, svg, symmetry,
+    table::ShapeTable,
+    voronoi, Axis, Change, Color, Colormap, Corner, DistanceField, Dual, EdgeStyle, Error,
+    Error::*,
+    Fill, Maze, MazeAlgorithm, Mesh, Mesh3D, Operation, Palette, Pattern, Point, Polygon, Rect,
+    RenderBackend, Result, Scene, Shape, Side, Stats, StyleSet, Theme, Tile, Tiling, TilingDiff,
+    Vector, VertexFigure,
+};
+
+#[cfg(feature = "pdf")]
+use crate::{render::POINTS_PER_INCH, PrintOptions};
+#[cfg(feature = "cairo")]
+use crate::{Fit, PaintMode};
+#[cfg(feature = "skia")]
+use crate::{SkiaBackend, SkiaRender};
+
+/// The maximum number of repeats searched for a rectangular period in wrap mode.
+const MAX_WRAP_DEPTH: usize = 64;
+
+/// The maximum number of translations searched for a basis of the lattice a
+/// model was repeated along.
+const MAX_LATTICE_POINTS: usize = 4096;
+
+/// The default maximum number of shapes a model places, counting repeats.
+const DEFAULT_MAX_SHAPES: usize = 1_000_000;
+
+/// The width (in pixels) of the chunks `write_large_png` draws.
+#[cfg(feature = "cairo")]
+const CHUNK_WIDTH: i32 = 4096;
+
+/// The height (in pixels) of the bands of chunks `write_large_png` stitches
+/// together and writes at a time.
+#[cfg(feature = "cairo")]
+const CHUNK_HEIGHT: i32 = 512;
+
+/// The most levels of a tile pyramid, whose tiles are numbered with 32-bit
+/// integers.
+#[cfg(feature = "cairo")]
+const MAX_PYRAMID_LEVELS: u32 = 31;
 
 /// Represents a tiling composed of an arbitrary number of regular polygons.
 /// A model is used to imperatively construct a tiling by building small patterns
 /// of shapes that are then repeated to fill a two-dimensional space.
 /// Use `render` to render the tiling.
 /// Use `render_dual` to render the dual tiling.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Model {
     width: i32,
     height: i32,
     scale: f64,
-    shapes: Vec<Shape>,
-    lookup: HashMap<Point, Shape>,
+    dpi: Option<f64>,
+    shapes: ShapeTable,
+    lookup: BTreeMap<Key, Placement>,
+    precision: i32,
+    snap: Option<f64>,
+    max_shapes: Option<usize>,
+    grid: Grid,
+    duplicates: DuplicatePolicy,
+    numbering: EdgeNumbering,
+    dual_boundary: DualBoundary,
+    origin: Origin,
+    strict: bool,
+    edge_styles: HashMap<usize, HashMap<usize, EdgeStyle>>,
+    edge_paths: HashMap<usize, HashMap<usize, Vec<Point>>>,
+    shape_names: BTreeMap<String, usize>,
+    edge_names: HashMap<usize, BTreeMap<String, usize>>,
+    hidden: HashSet<usize>,
+    fills: HashMap<Key, Color>,
+    overlays: HashMap<usize, Fill>,
+    image_offsets: HashMap<Key, Vector>,
+    style_sets: BTreeMap<String, StyleSet>,
+    border: Option<(f64, Color)>,
+    coverage: Option<Rect>,
+    tiles: Vec<Arc<dyn Tile>>,
+    translations: HashMap<Key, Point>,
+    wrap: bool,
+    lazy: bool,
+    period: Option<Point>,
+    generators: Vec<Point>,
+    seed: Option<u64>,
+    history: History,
+    saved_steps: Vec<String>,
+    observers: Observers,
+    graph: GraphCache,
 }
 
-impl Model {
-    /// Returns an empty model.
-    pub fn new(width: i32, height: i32, scale: f64) -> Model {
-        Model {
-            width,
-            height,
-            scale,
-            shapes: Vec::new(),
-            lookup: HashMap::new(),
+/// A shape placed in the tiling, stored as the index of the model shape it
+/// was placed from (either the shape itself or the shape it repeats) and
+/// where that shape was moved to.
+/// Repeats share everything but their position with their model shape, so
+/// storing the position alone keeps large tilings compact.
+#[derive(Clone, Copy, Debug)]
+struct Placement {
+    index: usize,
+    point: Point,
+    rotation: f64,
+}
+
+impl Placement {
+    /// Returns the placement of a copy of shape, the model shape with index
+    /// index, at point.
+    fn new(index: usize, shape: &Shape, point: Point) -> Placement {
+        Placement {
+            index,
+            point,
+            rotation: shape.rotation(),
         }
     }
+}
 
-    /// Adds shape to the model.
-    pub fn add(&mut self, shape: Shape) {
-        self.shapes.push(shape);
-        self.lookup.insert(shape.point(), shape);
-    }
+/// What an operation recorded in a model's history changed, so it can be
+/// undone (or rolled back when it fails) without a copy of the whole model.
+/// Operations only append shapes (or replace them, by the duplicate policy),
+/// insert placements, translations, generators, and fills, and set the
+/// period, except that a repeat in wrap mode places the period afresh, so
+/// the model's lengths before the operation and the entries it replaced are
+/// enough to restore it. Geometry transforms move everything, so they keep
+/// the geometry they replace.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Checkpoint {
+    shapes: usize,
+    generators: usize,
+    period: Option<Point>,
+    replaced: Vec<(usize, Shape)>,
+    placed: Vec<(Key, Option<Placement>)>,
+    translations: Vec<Key>,
+    fills: Vec<(Key, Option<Color>)>,
+    layout: Option<Box<Layout>>,
+    geometry: Option<Box<Geometry>>,
+}
 
-    /// Attaches shape to every edge in edges of each shape in indexes.
-    pub fn add_multi(
-        &mut self,
-        indexes: Range<usize>,
-        edges: Range<usize>,
-        shape: Shape,
-    ) -> Result<Range<usize>> {
-        let start = self.shapes.len();
-        for i in indexes {
-            for e in edges.clone() {
-                self.attach(i, e, shape)?;
+/// The placements of a model's (repeated) tiling, kept by a checkpoint when
+/// an operation places them afresh.
+#[derive(Clone, Debug)]
+struct Layout {
+    lookup: BTreeMap<Key, Placement>,
+    grid: Grid,
+    translations: HashMap<Key, Point>,
+}
+
+/// The shapes and placements of a model, with everything keyed by them,
+/// kept by a checkpoint when a geometry transform moves them all.
+#[derive(Clone, Debug)]
+struct Geometry {
+    shapes: ShapeTable,
+    tiles: Vec<Arc<dyn Tile>>,
+    generators: Vec<Point>,
+    period: Option<Point>,
+    lookup: BTreeMap<Key, Placement>,
+    grid: Grid,
+    translations: HashMap<Key, Point>,
+    fills: HashMap<Key, Color>,
+    image_offsets: HashMap<Key, Vector>,
+    edge_styles: HashMap<usize, HashMap<usize, EdgeStyle>>,
+    edge_paths: HashMap<usize, HashMap<usize, Vec<Point>>>,
+    edge_names: HashMap<usize, BTreeMap<String, usize>>,
+}
+
+/// A placed shape along with the index of the model shape it was placed from.
+#[derive(Clone, Copy, Debug)]
+struct Placed {
+    index: usize,
+    shape: Shape,
+}
+
+/// An edge of a model shape, as the shape's index and the edge's index.
+type EdgeIndex = (usize, usize);
+
+/// An edge that failed to take a shape in `add_multi_lossy`, as the shape's
+/// index, the edge's index, and the error.
+type EdgeFailure = (usize, usize, Error);
+
+/// A model's cells, ordered as `cells` orders them, with the indexes of each
+/// cell's neighbors in that order.
+#[derive(Debug)]
+struct CellGraph {
+    cells: Vec<CellId>,
+    indexes: HashMap<CellId, usize>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl CellGraph {
+    /// Returns the index of cell in the graph, or an error naming the point
+    /// the cell would be centered at (at precision) if it is not there.
+    fn index(&self, cell: CellId, precision: i32) -> Result<usize> {
+        self.indexes.get(&cell).copied().ok_or_else(|| {
+            let unit = math::powi(10.0, precision);
+            EmptyCell {
+                x: (cell.0).0 as f64 / unit,
+                y: (cell.0).1 as f64 / unit,
             }
+        })
+    }
+}
+
+/// The cell graph of a model, built the first time it is queried and kept
+/// until the model's shapes are placed again.
+#[derive(Debug, Default)]
+struct GraphCache(Mutex<Option<Arc<CellGraph>>>);
+
+impl GraphCache {
+    /// Returns the cached graph, building it with build if there is none.
+    fn get_or_build<F>(&self, build: F) -> Result<Arc<CellGraph>>
+    where
+        F: FnOnce() -> Result<CellGraph>,
+    {
+        let mut graph = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(g) = graph.as_ref() {
+            return Ok(g.clone());
         }
-        let end = self.shapes.len();
 
-        Ok(start..end)
+        let g = Arc::new(build()?);
+        *graph = Some(g.clone());
+
+        Ok(g)
     }
 
-    /// Attaches shape to the edge with index edge of the shape with index index.
-    fn attach(&mut self, index: usize, edge: usize, shape: Shape) -> Result<()> {
-        let parent = self.shapes.get(index).ok_or(OutOfBounds {
-            index: index,
-            length: self.shapes.len(),
-            name: String::from("model shapes"),
+    /// Discards the cached graph.
+    fn clear(&mut self) {
+        *self.0.get_mut().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl Clone for GraphCache {
+    fn clone(&self) -> GraphCache {
+        let graph = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        GraphCache(Mutex::new(graph.clone()))
+    }
+}
+
+/// A vertex's point, the shapes incident to it, and each shape's corners
+/// before and after the vertex.
+type Incidence = (Point, Vec<Shape>, Vec<(Point, Point)>);
+
+/// A shape meeting at a vertex, as the shape's index and its corners before
+/// and after the vertex.
+type Incident = (usize, Point, Point);
+
+/// The stroke color and dash pattern of a pen.
+#[cfg(feature = "cairo")]
+type Pen = (Color, Option<(f64, f64)>);
+
+/// Identifies a cell of a model's (repeated) tiling: one placed shape or
+/// tile.
+/// Identifiers remain valid until the model is transformed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CellId(Key);
+
+/// Identifies a shape of a model by its index, so it cannot be mistaken for
+/// an edge: `Model::attach`, `Model::shape`, and the other methods that take
+/// a shape take anything that converts into a `ShapeId`, and `add_multi`
+/// takes a `ShapeAnchor`, neither of which an `EdgeId` is, so passing an
+/// edge where a shape is expected does not compile.
+/// Ids convert to and from indexes, and into anchors selecting shapes only.
+/// An id is not tied to the model whose shape it identifies, though: an id
+/// of one model given to another is only checked against the other's
+/// shapes when it is used, like an index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShapeId(usize);
+
+impl ShapeId {
+    /// Returns the id of the shape with index.
+    pub fn new(index: usize) -> ShapeId {
+        ShapeId(index)
+    }
+
+    /// Returns the shape's index.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+
+    /// Returns the ids of the shapes with index in indexes, such as the range
+    /// `Model::add_multi` returns.
+    pub fn range(indexes: Range<usize>) -> Range<ShapeId> {
+        ShapeId(indexes.start)..ShapeId(indexes.end)
+    }
+}
+
+impl From<usize> for ShapeId {
+    fn from(index: usize) -> ShapeId {
+        ShapeId(index)
+    }
+}
+
+impl From<ShapeId> for usize {
+    fn from(id: ShapeId) -> usize {
+        id.0
+    }
+}
+
+/// Identifies an edge of a shape by its index, so it cannot be mistaken for a
+/// shape: the methods that take an edge take anything that converts into an
+/// `EdgeId`, and `add_multi` takes an `EdgeAnchor`, neither of which a
+/// `ShapeId` is.
+/// Ids convert to and from indexes, and into anchors selecting edges only.
+/// Like a `ShapeId`, an id is checked against the shape it is an edge of
+/// only when it is used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeId(usize);
+
+impl EdgeId {
+    /// Returns the id of the edge with index.
+    pub fn new(index: usize) -> EdgeId {
+        EdgeId(index)
+    }
+
+    /// Returns the edge's index.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+
+    /// Returns the ids of the edges with index in indexes.
+    pub fn range(indexes: Range<usize>) -> Range<EdgeId> {
+        EdgeId(indexes.start)..EdgeId(indexes.end)
+    }
+}
+
+impl From<usize> for EdgeId {
+    fn from(index: usize) -> EdgeId {
+        EdgeId(index)
+    }
+}
+
+impl From<EdgeId> for usize {
+    fn from(id: EdgeId) -> usize {
+        id.0
+    }
+}
+
+/// Identifies a style interned by `Model::intern_style`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StyleId(usize);
+
+/// A shape to attach described by its number of sides and a style interned
+/// in the model it is attached to.
+/// A spec is a few bytes however the shape is styled, so constructions that
+/// attach thousands of shapes of a handful of styles pass indexes rather
+/// than whole shapes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ShapeSpec {
+    sides: i32,
+    style: StyleId,
+}
+
+impl ShapeSpec {
+    /// Returns the spec of a sides-sided shape styled with style.
+    pub fn new(sides: i32, style: StyleId) -> ShapeSpec {
+        ShapeSpec { sides, style }
+    }
+
+    /// Returns the number of sides of the shape.
+    pub fn sides(&self) -> i32 {
+        self.sides
+    }
+
+    /// Returns the shape's style.
+    pub fn style(&self) -> StyleId {
+        self.style
+    }
+}
+
+/// A shape that can be added to a model: a `Shape`, a reference to one, or a
+/// `ShapeSpec` of a style interned in the model.
+pub trait IntoShape {
+    /// Returns the shape to add to model.
+    fn into_shape(self, model: &Model) -> Result<Shape>;
+}
+
+impl IntoShape for Shape {
+    fn into_shape(self, _: &Model) -> Result<Shape> {
+        Ok(self)
+    }
+}
+
+impl IntoShape for &Shape {
+    fn into_shape(self, _: &Model) -> Result<Shape> {
+        Ok(*self)
+    }
+}
+
+impl IntoShape for ShapeSpec {
+    fn into_shape(self, model: &Model) -> Result<Shape> {
+        let StyleId(index) = self.style;
+        let styling = model.shapes.styling(index).ok_or(OutOfBounds {
+            index,
+            length: model.shapes.stylings().len(),
+            name: String::from("model styles"),
         })?;
-        let shape = parent.adjacent(shape.sides(), edge, shape.fill(), shape.stroke())?;
-        self.add(shape);
+        shape::check_sides(self.sides)?;
 
-        Ok(())
+        Ok(Shape::styled(self.sides, Point::origin(), 0.0, styling))
     }
+}
 
-    /// Fills the rest of the surface with the pattern contained by the shapes
-    /// with index in indexes.
-    pub fn repeat(&mut self, indexes: Range<usize>) -> Result<()> {
-        let mut memo: HashMap<Point, i32> = HashMap::new();
-        let mut depth = 0;
+/// A set of a model's shapes picked by `Model::select`, held as the indexes
+/// of the shapes in ascending order.
+/// Sets are used with `add_multi_set`, `hide_set`, and `recolor_set`, and
+/// with any method that takes a range of indexes through `ranges`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShapeSet {
+    indexes: Vec<usize>,
+}
 
-        loop {
-            self.repeat_r(indexes.clone(), Point::origin(), depth, &mut memo)?;
-            let w = self.width as f64 / 2.0 / self.scale;
-            let h = self.height as f64 / 2.0 / self.scale;
-            let tl = memo.keys().any(|p| p.x < -w && p.y < -h);
-            let tr = memo.keys().any(|p| p.x > w && p.y < -h);
-            let bl = memo.keys().any(|p| p.x < -w && p.y > h);
-            let br = memo.keys().any(|p| p.x > w && p.y > h);
-            if tl && tr && bl && br {
-                break;
+impl ShapeSet {
+    /// Returns the indexes of the set's shapes in ascending order.
+    pub fn indexes(&self) -> &[usize] {
+        &self.indexes
+    }
+
+    /// Returns true if the shape with index index is in the set.
+    pub fn contains(&self, index: usize) -> bool {
+        self.indexes.binary_search(&index).is_ok()
+    }
+
+    /// Returns the number of shapes in the set.
+    pub fn len(&self) -> usize {
+        self.indexes.len()
+    }
+
+    /// Returns true if the set has no shapes.
+    pub fn is_empty(&self) -> bool {
+        self.indexes.is_empty()
+    }
+
+    /// Returns the set's indexes as the fewest ranges of consecutive indexes,
+    /// in ascending order.
+    pub fn ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for i in self.indexes.iter() {
+            match ranges.last_mut() {
+                Some(r) if r.end == *i => r.end += 1,
+                _ => ranges.push(*i..*i + 1),
             }
-            depth += 1;
         }
 
-        Ok(())
+        ranges
     }
+}
 
-    /// Recurisvely fills the surface by repeating a pattern of shapes.
-    fn repeat_r(
-        &mut self,
-        indexes: Range<usize>,
-        point: Point,
-        depth: i32,
-        memo: &mut HashMap<Point, i32>,
-    ) -> Result<()> {
-        if depth < 0 {
-            return Ok(());
-        }
+/// A vertex of a model's (repeated) tiling, where the corners of shapes meet,
+/// returned by `Model::vertices`.
+#[derive(Clone, Debug)]
+pub struct Vertex {
+    point: Point,
+    shapes: Vec<Shape>,
+    cells: Vec<CellId>,
+    edges: Vec<(Point, Point)>,
+    crossing: usize,
+}
 
-        let prev_depth = *memo.get(&point).unwrap_or(&-1);
-        if prev_depth >= depth {
-            return Ok(());
-        }
+impl Vertex {
+    /// Returns the vertex's position.
+    pub fn point(&self) -> Point {
+        self.point
+    }
 
-        memo.insert(point, depth);
+    /// Returns the shapes with a corner at the vertex, sorted around it.
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
 
-        if prev_depth == -1 {
-            self.add_repeats(point);
-        }
+    /// Returns the cells of the shapes with a corner at the vertex, ordered as
+    /// `shapes` orders them.
+    pub fn cells(&self) -> &[CellId] {
+        &self.cells
+    }
 
-        let mut shapes = Vec::new();
-        for i in indexes.clone() {
-            let s = self.shapes.get(i).ok_or(OutOfBounds {
-                index: i,
-                length: self.shapes.len(),
-                name: String::from("model shapes"),
-            })?;
+    /// Returns the edges of the tiling that end at the vertex, each once and
+    /// sorted around it, as pairs of the vertex and the edge's other end.
+    pub fn edges(&self) -> &[(Point, Point)] {
+        &self.edges
+    }
 
-            shapes.push(*s);
-        }
+    /// Returns the number of edges that end at the vertex.
+    pub fn degree(&self) -> usize {
+        self.edges.len()
+    }
 
-        for s in shapes.iter() {
-            self.repeat_r(indexes.clone(), point + s.point(), depth - 1, memo)?;
-        }
+    /// Returns the vertex's figure: the configuration of the shapes around it.
+    pub fn figure(&self) -> VertexFigure {
+        VertexFigure::new(self.point, &self.shapes, self.crossing)
+    }
+}
 
-        Ok(())
+/// A reference to a shape placed in a model's (repeated) tiling.
+#[derive(Clone, Copy, Debug)]
+pub struct ShapeRef<'a> {
+    index: usize,
+    cell: CellId,
+    shape: Shape,
+    model: PhantomData<&'a Model>,
+}
+
+impl<'a> ShapeRef<'a> {
+    /// Returns the index of the model shape that the shape was placed from
+    /// (either the shape itself or the shape it repeats).
+    pub fn index(&self) -> usize {
+        self.index
     }
 
-    /// Adds a shape to be repeated at point.
-    fn add_repeats(&mut self, point: Point) {
-        for s in self.shapes.iter() {
-            let p = point + s.point();
-            if self.lookup.contains_key(&p) {
-                continue;
-            }
+    /// Returns the cell the shape is placed in.
+    pub fn cell(&self) -> CellId {
+        self.cell
+    }
 
-            self.lookup.insert(p, s.clone_at(p));
-        }
+    /// Returns the placed shape.
+    pub fn shape(&self) -> &Shape {
+        &self.shape
     }
+}
 
-    /// Returns the model's dual tiling.
-    fn dual(&self, fill: Color, stroke: Color) -> Result<Vec<Dual>> {
-        let mut vertexes: HashMap<Point, Vec<Shape>> = HashMap::new();
-        for s in self.lookup.values() {
-            let points = s.points(0.0)?;
-            for p in &points[0..points.len() - 1] {
-                if let Some(shapes) = vertexes.get_mut(p) {
-                    shapes.push(*s);
-                } else {
-                    vertexes.insert(*p, vec![*s]);
-                }
+/// A lazy iterator over the shapes of a model's tiling, repeated without end,
+/// that may overlap a rectangle; returned by `Model::placements`.
+/// Lattice points are visited row by row, and each yields the shapes of the
+/// repeated pattern translated there.
+pub struct Placements<'a> {
+    model: &'a Model,
+    rect: Rect,
+    basis: (Point, Point),
+    pattern: Vec<Placement>,
+    radius: f64,
+    columns: (i64, i64),
+    rows: (i64, i64),
+    position: (i64, i64, usize),
+}
+
+impl<'a> Iterator for Placements<'a> {
+    type Item = ShapeRef<'a>;
+
+    fn next(&mut self) -> Option<ShapeRef<'a>> {
+        loop {
+            let (i, j, k) = self.position;
+            if j > self.rows.1 {
+                return None;
             }
-        }
+            self.position = match k + 1 < self.pattern.len() {
+                true => (i, j, k + 1),
+                false if i < self.columns.1 => (i + 1, j, 0),
+                false => (self.columns.0, j + 1, 0),
+            };
+            let p = match self.pattern.get(k) {
+                Some(p) => p,
+                None => continue,
+            };
 
-        let mut duals: Vec<Dual> = Vec::new();
-        for (p, shapes) in vertexes.iter_mut() {
-            if shapes.len() < 3 {
+            let (b1, b2) = self.basis;
+            let point = p.point + b1 * i as f64 + b2 * j as f64;
+            let (x, y) = (self.rect.origin().x, self.rect.origin().y);
+            if point.x + self.radius < x
+                || point.x - self.radius > x + self.rect.width()
+                || point.y + self.radius < y
+                || point.y - self.radius > y + self.rect.height()
+            {
                 continue;
             }
 
-            let angle = |s: &Shape| (s.point().y - p.y).atan2(s.point().x - p.x);
+            // Shapes on the model's canvas are the shapes the model placed.
+            let key = self.model.key(point);
+            let placement = self.model.lookup.get(&key).copied().unwrap_or(Placement {
+                index: p.index,
+                point,
+                rotation: p.rotation,
+            });
+            return Some(ShapeRef {
+                index: placement.index,
+                cell: CellId(key),
+                shape: self.model.resolve(&placement),
+                model: PhantomData,
+            });
+        }
+    }
+}
+
+/// Determines how a model treats a shape that coincides with an existing shape.
+/// Two shapes coincide when they share a center, a number of sides, and a
+/// rotation (modulo the shape's rotational symmetry).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Add the shape anyway (the default).
+    #[default]
+    Allow,
+    /// Fail with `Error::CoincidentShape`.
+    Reject,
+    /// Ignore the shape, leaving the existing shape in place.
+    Skip,
+    /// Replace the existing shape with the shape.
+    Replace,
+}
+
+/// Determines how the edges of shapes added to a model are numbered.
+/// Edges are always numbered clockwise (on the canvas); the numbering only
+/// chooses which edge has index 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EdgeNumbering {
+    /// Number each attached shape's edges starting from the edge shared with
+    /// the shape it was attached to, as `Shape::adjacent` does (the default).
+    /// Shapes added directly keep the numbering of `Shape::new`.
+    /// Edge indexes are relative to the parent, so the same pattern of
+    /// attachments produces the same shapes in any orientation.
+    #[default]
+    Parent,
+    /// Number every shape's edges starting from the edge facing north, or the
+    /// first edge clockwise from north if none faces it exactly.
+    /// Edge indexes are absolute, so an edge's index depends only on its
+    /// direction.
+    North,
+}
 
-            shapes.sort_by(|a, b| angle(b).partial_cmp(&angle(a)).unwrap_or(Less));
+/// Determines how a model's dual tiling treats the vertices on the boundary
+/// of its (repeated) tiling, which are not surrounded by shapes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DualBoundary {
+    /// Form a cell from each vertex with at least 3 incident shapes and
+    /// discard the rest (the default), so the dual of a finite patch has a
+    /// ragged boundary.
+    #[default]
+    Discard,
+    /// Also form a cell, clipped to the tiling, from each boundary vertex: the
+    /// vertex, the midpoints of its two boundary edges, and the centers of
+    /// its incident shapes.
+    Clip,
+    /// Extend the tiling by a belt of this many more repeats of its pattern
+    /// beyond the canvas before forming cells, keeping those that overlap the
+    /// canvas, so the dual of a repeated model fully covers it.
+    Extend(usize),
+}
 
-            let mut points = shapes.iter().map(|s| s.point()).collect::<Vec<Point>>();
-            points.push(*points.first().ok_or(OutOfBounds {
-                index: 0,
-                length: shapes.len(),
-                name: String::from("dual shapes"),
-            })?);
+/// Where a model's origin lies on its canvas.
+/// Shapes are placed around the origin, so anchoring it at a corner or edge
+/// lines the tiling up with that side of the canvas, which helps compose
+/// renders into larger layouts.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Origin {
+    /// The center of the canvas (the default).
+    #[default]
+    Center,
+    /// The top left corner.
+    TopLeft,
+    /// The midpoint of the top edge.
+    Top,
+    /// The top right corner.
+    TopRight,
+    /// The midpoint of the left edge.
+    Left,
+    /// The midpoint of the right edge.
+    Right,
+    /// The bottom left corner.
+    BottomLeft,
+    /// The midpoint of the bottom edge.
+    Bottom,
+    /// The bottom right corner.
+    BottomRight,
+    /// These canvas coordinates (in pixels, from the top left corner).
+    At(f64, f64),
+}
 
-            duals.push(Dual::new(points, fill, stroke));
+impl Origin {
+    /// Returns the canvas coordinates (in pixels, from the top left corner)
+    /// of the origin on a width by height canvas.
+    fn position(&self, width: f64, height: f64) -> (f64, f64) {
+        match *self {
+            Origin::Center => (width / 2.0, height / 2.0),
+            Origin::TopLeft => (0.0, 0.0),
+            Origin::Top => (width / 2.0, 0.0),
+            Origin::TopRight => (width, 0.0),
+            Origin::Left => (0.0, height / 2.0),
+            Origin::Right => (width, height / 2.0),
+            Origin::BottomLeft => (0.0, height),
+            Origin::Bottom => (width / 2.0, height),
+            Origin::BottomRight => (width, height),
+            Origin::At(x, y) => (x, y),
         }
+    }
+}
 
-        Ok(duals)
+/// How far `Model::repeat_bounded` repeats a pattern.
+#[derive(Clone, Copy, Debug)]
+enum Bound {
+    /// Place this many copies of the pattern.
+    Copies(usize),
+    /// Place the shapes centered within this distance of the origin.
+    Radius(f64),
+}
+
+/// Selects the shapes, or the edges of each shape, that `Model::add_multi`
+/// attaches to: a range of indexes, or a name given with `Model::name_shape`
+/// or `Model::name_edge`.
+/// Ranges, single indexes, and names all convert into anchors, so patterns
+/// can refer to shapes by name rather than by indexes that shift as the
+/// pattern changes.
+/// `ShapeId`s and `EdgeId`s (and ranges of them) convert into anchors that
+/// select only shapes or only edges. `add_multi` takes them as a
+/// `ShapeAnchor` and an `EdgeAnchor`, so passing an edge id where shapes are
+/// expected does not compile; an `Anchor` built at run time that selects
+/// the wrong kind fails with `Error::MismatchedAnchor` instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    /// The shapes or edges with index in the range.
+    Indexes(Range<usize>),
+    /// The shape or edge with the name.
+    Name(String),
+    /// The shapes with index in the range, given by `ShapeId`s.
+    Shapes(Range<usize>),
+    /// The edges with index in the range, given by `EdgeId`s.
+    Edges(Range<usize>),
+}
+
+impl From<Range<usize>> for Anchor {
+    fn from(indexes: Range<usize>) -> Anchor {
+        Anchor::Indexes(indexes)
     }
+}
 
-    /// Renders the model.
-    pub fn render(
-        &self,
-        background: Color,
-        margin: f64,
-        line_width: f64,
-        show_labels: bool,
-    ) -> Result<Render> {
-        let (surface, context) = self.render_init(background, line_width)?;
-        let shapes = self.lookup.values();
+impl From<usize> for Anchor {
+    fn from(index: usize) -> Anchor {
+        Anchor::Indexes(index..index + 1)
+    }
+}
 
-        if show_labels {
-            for s in shapes.clone() {
-                s.render_edge_labels(&context, margin - 0.25)?;
-            }
-        }
-        for s in shapes.clone() {
-            s.render(&context, margin)?;
+impl From<ShapeId> for Anchor {
+    fn from(id: ShapeId) -> Anchor {
+        Anchor::Shapes(id.0..id.0 + 1)
+    }
+}
+
+impl From<Range<ShapeId>> for Anchor {
+    fn from(ids: Range<ShapeId>) -> Anchor {
+        Anchor::Shapes(ids.start.0..ids.end.0)
+    }
+}
+
+impl From<EdgeId> for Anchor {
+    fn from(id: EdgeId) -> Anchor {
+        Anchor::Edges(id.0..id.0 + 1)
+    }
+}
+
+impl From<Range<EdgeId>> for Anchor {
+    fn from(ids: Range<EdgeId>) -> Anchor {
+        Anchor::Edges(ids.start.0..ids.end.0)
+    }
+}
+
+impl From<&str> for Anchor {
+    fn from(name: &str) -> Anchor {
+        Anchor::Name(String::from(name))
+    }
+}
+
+impl From<String> for Anchor {
+    fn from(name: String) -> Anchor {
+        Anchor::Name(name)
+    }
+}
+
+/// Returns the error of an anchor selecting found where expected are
+/// expected.
+fn mismatched(expected: &str, found: &str) -> Error {
+    MismatchedAnchor {
+        expected: String::from(expected),
+        found: String::from(found),
+    }
+}
+
+/// A type that can select the shapes `Model::add_multi` attaches to: indexes,
+/// names, `ShapeId`s (and ranges of them), or an `Anchor`.
+/// `EdgeId`s do not implement it, so they cannot be passed for shapes.
+pub trait ShapeAnchor: Into<Anchor> {}
+
+impl ShapeAnchor for Anchor {}
+impl ShapeAnchor for usize {}
+impl ShapeAnchor for Range<usize> {}
+impl ShapeAnchor for ShapeId {}
+impl ShapeAnchor for Range<ShapeId> {}
+impl ShapeAnchor for &str {}
+impl ShapeAnchor for String {}
+
+/// A type that can select the edges `Model::add_multi` attaches to: indexes,
+/// names, `EdgeId`s (and ranges of them), or an `Anchor`.
+/// `ShapeId`s do not implement it, so they cannot be passed for edges.
+pub trait EdgeAnchor: Into<Anchor> {}
+
+impl EdgeAnchor for Anchor {}
+impl EdgeAnchor for usize {}
+impl EdgeAnchor for Range<usize> {}
+impl EdgeAnchor for EdgeId {}
+impl EdgeAnchor for Range<EdgeId> {}
+impl EdgeAnchor for &str {}
+impl EdgeAnchor for String {}
+
+impl Model {
+    /// Returns an empty model.
+    pub fn new(width: i32, height: i32, scale: f64) -> Model {
+        Model {
+            width,
+            height,
+            scale,
+            dpi: None,
+            shapes: ShapeTable::default(),
+            lookup: BTreeMap::new(),
+            precision: shape::PRECISION,
+            snap: None,
+            max_shapes: Some(DEFAULT_MAX_SHAPES),
+            grid: Grid::default(),
+            duplicates: DuplicatePolicy::default(),
+            numbering: EdgeNumbering::default(),
+            dual_boundary: DualBoundary::default(),
+            origin: Origin::default(),
+            strict: false,
+            edge_styles: HashMap::new(),
+            edge_paths: HashMap::new(),
+            shape_names: BTreeMap::new(),
+            edge_names: HashMap::new(),
+            hidden: HashSet::new(),
+            fills: HashMap::new(),
+            overlays: HashMap::new(),
+            image_offsets: HashMap::new(),
+            style_sets: BTreeMap::new(),
+            border: None,
+            coverage: None,
+            tiles: Vec::new(),
+            translations: HashMap::new(),
+            wrap: false,
+            lazy: false,
+            period: None,
+            generators: Vec::new(),
+            seed: None,
+            history: History::default(),
+            saved_steps: Vec::new(),
+            observers: Observers::default(),
+            graph: GraphCache::default(),
         }
-        if show_labels {
-            for (i, s) in shapes.clone().enumerate() {
-                s.render_label(&context, &i.to_string())?;
+    }
+
+    /// Returns an empty model whose canvas is width_mm by height_mm
+    /// millimeters at dpi dots per inch, with edges scale_mm millimeters
+    /// long.
+    /// Its renders record the resolution, so printed tilings come out at
+    /// their real-world size.
+    pub fn new_physical(width_mm: f64, height_mm: f64, dpi: f64, scale_mm: f64) -> Model {
+        let pixels = |mm: f64| mm / render::MM_PER_INCH * dpi;
+
+        Model::new(
+            pixels(width_mm).round() as i32,
+            pixels(height_mm).round() as i32,
+            pixels(scale_mm),
+        )
+        .with_dpi(dpi)
+    }
+
+    /// Returns the model with its resolution set to dpi dots per inch, or
+    /// left unspecified if it is `None`.
+    /// Renders record the resolution in their PNG files, and PDF pages are
+    /// sized by it.
+    pub fn with_dpi(mut self, dpi: impl Into<Option<f64>>) -> Model {
+        self.dpi = dpi.into();
+
+        self
+    }
+
+    /// Returns the model with its duplicate policy set to policy.
+    pub fn with_duplicates(mut self, policy: DuplicatePolicy) -> Model {
+        self.duplicates = policy;
+
+        self
+    }
+
+    /// Returns the model with points compared to digits decimal places, rather
+    /// than `shape::PRECISION`.
+    /// Points closer than this are merged when placing, repeating, and forming
+    /// the dual of shapes.
+    /// Use fewer digits for very large coordinates, whose rounding error may
+    /// otherwise split points that should be shared, and more for very small
+    /// shapes, which may otherwise be merged.
+    pub fn with_precision(mut self, digits: i32) -> Model {
+        self.precision = digits;
+        self.graph.clear();
+
+        self
+    }
+
+    /// Returns the model with shapes snapped to within tolerance (in edge
+    /// lengths) of a shape already placed, or not snapped if it is `None`.
+    /// Long chains of attachments accumulate floating point error, so a shape
+    /// may miss the position of a coincident shape by more than the model's
+    /// precision; snapping moves a shape added or repeated within tolerance
+    /// of a placed shape onto that shape's center, so the two are merged (or
+    /// matched by the duplicate policy) instead of drawn twice.
+    /// Shapes are found within a cell of the model's spatial index, so a
+    /// tolerance that is negative, not finite, or longer than an edge is an
+    /// error.
+    pub fn with_snap_tolerance(mut self, tolerance: impl Into<Option<f64>>) -> Result<Model> {
+        let tolerance = tolerance.into();
+        if let Some(t) = tolerance {
+            if !(0.0..=CELL_SIZE).contains(&t) {
+                return Err(InvalidSnapTolerance {
+                    tolerance: t,
+                    max: CELL_SIZE,
+                });
             }
         }
+        self.snap = tolerance;
 
-        Ok(Render(surface))
+        Ok(self)
     }
 
-    /// Renders the model's dual tiling.
-    pub fn render_dual(
-        &self,
-        background: Color,
-        fill: Color,
-        stroke: Color,
-        margin: f64,
-        line_width: f64,
-    ) -> Result<Render> {
-        let (surface, context) = self.render_init(background, line_width)?;
-        let shapes = self.dual(fill, stroke)?;
+    /// Returns the model with at most max shapes placed, counting repeats, or
+    /// no limit if it is `None`.
+    /// Repeating past the limit fails with `Error::TooManyShapes`, so a
+    /// tiling repeated at a mistakenly small scale fails fast instead of
+    /// placing millions of shapes. The failed repeat is rolled back, leaving
+    /// the model as it was before it.
+    /// The limit defaults to a million shapes.
+    pub fn with_max_shapes(mut self, max: impl Into<Option<usize>>) -> Model {
+        self.max_shapes = max.into();
 
-        for s in shapes.clone() {
-            s.render(&context, margin)?;
-        }
+        self
+    }
 
-        Ok(Render(surface))
+    /// Returns the model with its edge numbering set to numbering.
+    /// The numbering applies to shapes added after it is set.
+    pub fn with_edge_numbering(mut self, numbering: EdgeNumbering) -> Model {
+        self.numbering = numbering;
+
+        self
     }
 
-    /// Prepares a cairo surface and context for rendering.
-    fn render_init(
-        &self,
-        background: Color,
-        line_width: f64,
-    ) -> Result<(cairo::ImageSurface, cairo::Context)> {
-        let surface = cairo::ImageSurface::create(cairo::Format::Rgb24, self.width, self.height)?;
-        let context = cairo::Context::new(&surface)?;
-        let (red, green, blue) = background.rgb_unit_int();
-        context.set_line_cap(cairo::LineCap::Round);
-        context.set_line_join(cairo::LineJoin::Round);
-        context.set_line_width(line_width);
-        context.set_font_size(18.0 / self.scale);
-        context.translate(self.width as f64 / 2.0, self.height as f64 / 2.0);
-        context.scale(self.scale, self.scale);
-        context.set_source_rgb(red, green, blue);
-        context.paint()?;
+    /// Returns the model with the boundary of its dual tiling handled as
+    /// boundary determines.
+    pub fn with_dual_boundary(mut self, boundary: DualBoundary) -> Model {
+        self.dual_boundary = boundary;
 
-        Ok((surface, context))
+        self
     }
-}
 
-/// Represents a rendered model.
-pub struct Render(cairo::ImageSurface);
+    /// Returns the model with its origin placed on the canvas at origin
+    /// rather than its center.
+    /// Set the origin before repeating, since repeats fill the canvas around
+    /// it.
+    pub fn with_origin(mut self, origin: Origin) -> Model {
+        self.origin = origin;
+
+        self
+    }
+
+    /// Returns the model with strict mode set to strict.
+    /// In strict mode `attach` (and so `add_multi` and `add_pattern`) fails
+    /// with `Error::AngleOverflow` instead of attaching a shape whose interior
+    /// angle, added to those of the shapes already at one of its vertices,
+    /// would exceed 360°, so overlapping shapes are caught where they are
+    /// attached.
+    pub fn with_strict(mut self, strict: bool) -> Model {
+        self.strict = strict;
+
+        self
+    }
+
+    /// Returns the model with wrap mode set to wrap.
+    /// In wrap mode `repeat` fills exactly one rectangular period of the
+    /// pattern, centered on the origin, instead of the whole canvas.
+    /// Renders are then sized to that period (ignoring the model's width and
+    /// height) and draw shapes that cross its sides on both sides, so the
+    /// resulting image tiles seamlessly.
+    /// Reflecting, rotating, or translating the model discards its period.
+    pub fn with_wrap(mut self, wrap: bool) -> Model {
+        self.wrap = wrap;
+
+        self
+    }
+
+    /// Returns the model with the region (in model units) that `repeat` and
+    /// `repeat_groups` fill with copies of the pattern, or the canvas if it is
+    /// `None` (the default).
+    /// A region smaller than the canvas repeats the pattern only as far as a
+    /// layout needs it; a larger one fills beyond the canvas for models that
+    /// are later translated or resized.
+    pub fn with_coverage(mut self, region: impl Into<Option<Rect>>) -> Model {
+        self.coverage = region.into();
+
+        self
+    }
+
+    /// Returns the model with lazy repeats set to lazy.
+    /// With lazy repeats `repeat`, `repeat_groups`, and `repeat_by` record
+    /// only the lattice the pattern is repeated along rather than placing
+    /// every repeat, and the shapes overlapping the canvas (or a viewport) are
+    /// generated from the lattice whenever they are needed, as `placements`
+    /// generates them. Huge canvases then cost no memory for their repeats,
+    /// and a model resized with `with_canvas` is filled without repeating it
+    /// again.
+    /// Only the model's own shapes are cells of its tiling, so `cells`,
+    /// `repeated_len`, and the methods that fill or look up cells ignore the
+    /// repeats. Wrap mode and the bounded repeats always place their repeats.
+    pub fn with_lazy_repeat(mut self, lazy: bool) -> Model {
+        self.lazy = lazy;
+
+        self
+    }
+
+    /// Returns the model with a canvas width by height pixels, with edges
+    /// scale pixels long.
+    /// Repeats already placed are kept as they are, so a model should be
+    /// resized before it is repeated, unless its repeats are lazy.
+    pub fn with_canvas(mut self, width: i32, height: i32, scale: f64) -> Model {
+        self.width = width;
+        self.height = height;
+        self.scale = scale;
+
+        self
+    }
+
+    /// Returns the width of the model's surface in pixels.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Returns the height of the model's surface in pixels.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns the model's scale, the length of an edge in pixels.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Returns the model's resolution in dots per inch, or none if it is
+    /// unspecified.
+    pub fn dpi(&self) -> Option<f64> {
+        self.dpi
+    }
+
+    /// Returns where the model's origin lies on its canvas.
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// Returns the width and height of the rectangular period filled by
+    /// `repeat` in wrap mode, or none if the model has not been repeated in
+    /// wrap mode.
+    pub fn period(&self) -> Option<(f64, f64)> {
+        self.period.map(|p| (p.x, p.y))
+    }
+
+    /// Returns the identifier of shape's style: its fill, its stroke, and
+    /// the options its `with_*` methods set, but not its sides or position.
+    /// A style is interned once, so shapes styled alike share an identifier,
+    /// which `ShapeSpec`s of any number of sides can refer to.
+    /// The model stores each of its shapes as its sides, its position, and
+    /// the identifier of its style, so shapes added from a `ShapeSpec` (or
+    /// styled like one) share the interned style rather than copying it.
+    pub fn intern_style(&mut self, shape: &Shape) -> StyleId {
+        StyleId(self.shapes.intern(shape.styling()))
+    }
+
+    /// Adds shape to the model, applying the model's duplicate policy if shape
+    /// coincides with a shape that was previously added.
+    pub fn add(&mut self, shape: impl IntoShape) -> Result<()> {
+        let shape = shape.into_shape(self)?;
+        self.record(Operation::Add { shape }, |m| m.add_shape(shape).map(|_| ()))
+    }
+
+    /// Adds shape to the model without recording the operation, returning
+    /// its index, or the index of the shape it coincides with if the
+    /// duplicate policy skips or replaces it.
+    fn add_shape(&mut self, shape: Shape) -> Result<usize> {
+        let shape = match self.numbering {
+            EdgeNumbering::Parent => shape,
+            EdgeNumbering::North => shape.renumbered(-PI / 2.0),
+        };
+        let shape = shape.clone_at(self.snap(shape.point()));
+
+        if self.duplicates != DuplicatePolicy::Allow {
+            let precision = self.precision;
+            let coincident = self
+                .shapes
+                .iter()
+                .position(|s| s.coincides_within(&shape, precision));
+            if let Some(index) = coincident {
+                match self.duplicates {
+                    DuplicatePolicy::Reject => return Err(CoincidentShape { index }),
+                    DuplicatePolicy::Skip => return Ok(index),
+                    DuplicatePolicy::Replace => {
+                        let replaced = self.shapes.set(index, &shape);
+                        if let Some(journal) = self.history.journal() {
+                            journal.replaced.push((index, replaced));
+                        }
+                        self.place_copy(index, &shape);
+                        self.observers.notify(Change::Replaced { index });
+                        return Ok(index);
+                    }
+                    DuplicatePolicy::Allow => {}
+                }
+            }
+        }
+
+        let index = self.shapes.len();
+        self.shapes.push(&shape);
+        self.place_copy(index, &shape);
+
+        Ok(index)
+    }
+
+    /// Adds tile, a polygon other than a regular shape, to the model and
+    /// returns its index among the model's tiles.
+    /// Tiles are repeated, moved, rendered, and saved along with the model's
+    /// shapes, and are cells of its tiling with neighbors of their own; they
+    /// count toward `repeated_len`, `boundary`, `to_svg`, and `to_polylines`.
+    /// They are numbered apart from shapes, so `len` and `shape` (and the
+    /// indexes attaching and repeating take) cover shapes only, and tiles are
+    /// not attached to or matched against shapes by queries such as
+    /// `hit_test`, `stats`, and `dual`.
+    pub fn add_tile<T: Tile + 'static>(&mut self, tile: T) -> usize {
+        self.tiles.push(Arc::new(tile));
+
+        self.tiles.len() - 1
+    }
+
+    /// Returns the tiles added to the model, not counting repeats.
+    pub fn tiles(&self) -> Vec<&dyn Tile> {
+        self.tiles.iter().map(|t| t.as_ref()).collect()
+    }
+
+    /// Returns the tile with index index, as `add_tile` numbered it, if there
+    /// is one.
+    pub fn tile(&self, index: usize) -> Option<&dyn Tile> {
+        self.tiles.get(index).map(|t| t.as_ref())
+    }
+
+    /// Returns the number of shapes added to the model, not counting repeats
+    /// or tiles, which `tiles` returns.
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Returns the number of shapes and tiles placed in the model's tiling,
+    /// counting repeats.
+    pub fn repeated_len(&self) -> usize {
+        self.lookup.len() + self.placed_tiles().len()
+    }
+
+    /// Returns true if no shapes or tiles have been added to the model.
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty() && self.tiles.is_empty()
+    }
+
+    /// Returns the shape with id (or index) id, if there is one.
+    pub fn shape(&self, id: impl Into<ShapeId>) -> Option<Shape> {
+        self.shapes.get(id.into().0)
+    }
+
+    /// Returns the set of the model's shapes (not counting repeats) for which
+    /// predicate returns true, such as `|s| s.sides() == 6 && s.point().y > 0.0`.
+    pub fn select<F>(&self, predicate: F) -> ShapeSet
+    where
+        F: Fn(&Shape) -> bool,
+    {
+        let indexes = (0..self.shapes.len())
+            .filter(|i| predicate(&self.shapes.at(*i)))
+            .collect();
+
+        ShapeSet { indexes }
+    }
+
+    /// Returns the cells of the model's (repeated) tiling whose shapes
+    /// predicate returns true for, ordered as `cells` orders them, so single
+    /// cells of a repeated tiling can be picked out by position and filled
+    /// with `fill_cells`. The cells of tiles are never selected.
+    pub fn select_cells<F>(&self, predicate: F) -> Vec<CellId>
+    where
+        F: Fn(&Shape) -> bool,
+    {
+        self.cells()
+            .into_iter()
+            .filter(|c| {
+                self.lookup
+                    .get(&c.0)
+                    .map_or(false, |p| predicate(&self.resolve(p)))
+            })
+            .collect()
+    }
+
+    /// Sets the style of the edge with index edge of the shape with index index.
+    /// The style also applies to the edge of every repeat of the shape.
+    /// Note that an edge shared by two shapes is drawn by both, so hiding a
+    /// shared edge requires hiding it on both shapes.
+    pub fn style_edge(&mut self, index: usize, edge: usize, style: EdgeStyle) -> Result<()> {
+        let shape = self.shapes.get(index).ok_or(OutOfBounds {
+            index,
+            length: self.shapes.len(),
+            name: String::from("model shapes"),
+        })?;
+        if edge >= shape.sides() as usize {
+            let err = OutOfBounds {
+                index: edge,
+                length: shape.sides() as usize,
+                name: String::from("shape edges"),
+            };
+
+            return Err(err.context("style_edge", Some(index), Some(edge)));
+        }
+
+        self.edge_styles
+            .entry(index)
+            .or_default()
+            .insert(edge, style);
+
+        Ok(())
+    }
+
+    /// Replaces the straight edge with index edge of the shape with index
+    /// index with the path through points, drawn in the frame of the edge,
+    /// which runs from (0, 0) to (1, 0) with y pointing out of the shape, so
+    /// a path with positive y bulges out of the shape.
+    /// Like edge styles, the path applies to every repeat of the shape, and
+    /// it is also given (reversed, so it bulges into that shape) to every
+    /// edge it meets in the (repeated) tiling, and so on, so the shapes keep
+    /// fitting together: deforming the edges of a tiling's seed shapes makes
+    /// Escher-style tiles that still tile the plane. An empty path makes the
+    /// edges straight again.
+    /// Returns an error, changing nothing, if the edge meets a repeat of
+    /// itself and the path is not symmetric about the edge's midpoint.
+    /// Paths are drawn by renders with cairo and ignore the shapes' corner
+    /// style.
+    pub fn deform_edge(&mut self, index: usize, edge: usize, points: &[Point]) -> Result<()> {
+        self.deform(index, edge, points)
+            .map_err(|err| err.context("deform_edge", Some(index), Some(edge)))
+    }
+
+    /// Deforms the edge as `deform_edge` does, without adding context to
+    /// errors.
+    fn deform(&mut self, index: usize, edge: usize, points: &[Point]) -> Result<()> {
+        let shape = self.shapes.get(index).ok_or(OutOfBounds {
+            index,
+            length: self.shapes.len(),
+            name: String::from("model shapes"),
+        })?;
+        if edge >= shape.sides() as usize {
+            return Err(OutOfBounds {
+                index: edge,
+                length: shape.sides() as usize,
+                name: String::from("shape edges"),
+            });
+        }
+        if let Some(p) = points
+            .iter()
+            .find(|p| !(p.x.is_finite() && p.y.is_finite()))
+        {
+            return Err(InvalidShape {
+                reason: format!("edge path point ({}, {}) is not finite", p.x, p.y),
+            });
+        }
+
+        // A matching edge runs the other way, so its path is reversed and
+        // mirrored in its frame.
+        let mirrored = points
+            .iter()
+            .rev()
+            .map(|p| Point {
+                x: 1.0 - p.x,
+                y: -p.y,
+            })
+            .collect::<Vec<Point>>();
+        let symmetric = points
+            .iter()
+            .zip(mirrored.iter())
+            .all(|(p, q)| self.key(*p) == self.key(*q));
+
+        // Every edge that meets a repeat of the edge takes the mirrored path,
+        // every edge that meets one of those takes the path, and so on, so
+        // that the whole (repeated) tiling still fits together.
+        let neighbors = self.matching_edges()?;
+        let mut sides: HashMap<(usize, usize), bool> = HashMap::new();
+        let mut queue = VecDeque::from(vec![((index, edge), false)]);
+        while let Some((e, flipped)) = queue.pop_front() {
+            match sides.get(&e) {
+                Some(f) if *f != flipped && !symmetric => {
+                    return Err(InvalidShape {
+                        reason: String::from(
+                            "edge meets a repeat of itself, so its path must be symmetric about its midpoint",
+                        ),
+                    });
+                }
+                Some(_) => continue,
+                None => {}
+            }
+
+            sides.insert(e, flipped);
+            for n in neighbors.get(&e).into_iter().flatten() {
+                queue.push_back((*n, !flipped));
+            }
+        }
+
+        for ((i, e), flipped) in sides {
+            let path = match flipped {
+                true => mirrored.clone(),
+                false => points.to_vec(),
+            };
+            let paths = self.edge_paths.entry(i).or_default();
+            match path.is_empty() {
+                true => paths.remove(&e),
+                false => paths.insert(e, path),
+            };
+        }
+        self.edge_paths.retain(|_, paths| !paths.is_empty());
+
+        Ok(())
+    }
+
+    /// Returns the path that replaces the edge with index edge of the shape
+    /// with index index, in the frame of the edge, if it is deformed.
+    pub fn edge_path(&self, index: usize, edge: usize) -> Option<&[Point]> {
+        self.edge_paths
+            .get(&index)
+            .and_then(|paths| paths.get(&edge))
+            .map(|path| path.as_slice())
+    }
+
+    /// Returns the edges (as shape and edge indexes) that each edge meets
+    /// somewhere in the model's (repeated) tiling.
+    fn matching_edges(&self) -> Result<HashMap<EdgeIndex, HashSet<EdgeIndex>>> {
+        let mut edges = HashMap::new();
+        for p in self.placed() {
+            for (e, w) in p.shape.vertices(0.0)?.windows(2).enumerate() {
+                edges.insert((self.key(w[0]), self.key(w[1])), (p.index, e));
+            }
+        }
+
+        let mut matching: HashMap<(usize, usize), HashSet<(usize, usize)>> = HashMap::new();
+        for ((k0, k1), e) in edges.iter() {
+            if let Some(m) = edges.get(&(*k1, *k0)) {
+                matching.entry(*e).or_default().insert(*m);
+            }
+        }
+
+        Ok(matching)
+    }
+
+    /// Hides the shapes indexes selects (and their repeats) from renders.
+    /// Shapes are selected as `add_multi` selects them, by ranges of indexes,
+    /// single indexes, ids, or names.
+    /// Hidden shapes remain part of the model's geometry, so shapes may still
+    /// be attached to them and they are still repeated, which suits scaffolding
+    /// used only to position other shapes. Hidden shapes are still labeled when
+    /// labels are shown.
+    pub fn hide(&mut self, indexes: impl ShapeAnchor) -> Result<()> {
+        let indexes = self
+            .shape_anchor(&indexes.into())
+            .map_err(|err| err.context("hide", None, None))?;
+        if indexes.end > self.shapes.len() {
+            let err = OutOfBounds {
+                index: indexes.end - 1,
+                length: self.shapes.len(),
+                name: String::from("model shapes"),
+            };
+
+            return Err(err.context("hide", Some(indexes.end - 1), None));
+        }
+
+        self.hidden.extend(indexes);
+
+        Ok(())
+    }
+
+    /// Hides the shapes in set (and their repeats) from renders, as `hide`
+    /// does.
+    pub fn hide_set(&mut self, set: &ShapeSet) -> Result<()> {
+        for indexes in set.ranges() {
+            self.hide(indexes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Shows the shapes indexes selects, as `hide` selects them, if they
+    /// were hidden.
+    pub fn show(&mut self, indexes: impl ShapeAnchor) -> Result<()> {
+        let indexes = self
+            .shape_anchor(&indexes.into())
+            .map_err(|err| err.context("show", None, None))?;
+        for i in indexes {
+            self.hidden.remove(&i);
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the shape with id (or index) id is hidden from
+    /// renders.
+    pub fn is_hidden(&self, id: impl Into<ShapeId>) -> bool {
+        self.hidden.contains(&id.into().0)
+    }
+
+    /// Names the shape with index index, so `add_multi` can select it by
+    /// name.
+    /// Naming another shape with the same name moves the name to it.
+    pub fn name_shape(&mut self, index: usize, name: &str) -> Result<()> {
+        if index >= self.shapes.len() {
+            let err = OutOfBounds {
+                index,
+                length: self.shapes.len(),
+                name: String::from("model shapes"),
+            };
+
+            return Err(err.context("name_shape", Some(index), None));
+        }
+
+        self.shape_names.insert(String::from(name), index);
+
+        Ok(())
+    }
+
+    /// Names the edge with index edge of the shape with index index, so
+    /// `add_multi` can select it by name.
+    /// Edge names belong to their shape, so several shapes may each name one
+    /// of their edges the same.
+    pub fn name_edge(&mut self, index: usize, edge: usize, name: &str) -> Result<()> {
+        let shape = self.shapes.get(index).ok_or(OutOfBounds {
+            index,
+            length: self.shapes.len(),
+            name: String::from("model shapes"),
+        })?;
+        if edge >= shape.sides() as usize {
+            let err = OutOfBounds {
+                index: edge,
+                length: shape.sides() as usize,
+                name: String::from("shape edges"),
+            };
+
+            return Err(err.context("name_edge", Some(index), Some(edge)));
+        }
+
+        self.edge_names
+            .entry(index)
+            .or_default()
+            .insert(String::from(name), edge);
+
+        Ok(())
+    }
+
+    /// Returns the index of the shape named name, or none if no shape has
+    /// the name.
+    pub fn named_shape(&self, name: &str) -> Option<usize> {
+        self.shape_names.get(name).copied()
+    }
+
+    /// Returns the index of the edge named name of the shape with index
+    /// index, or none if none of its edges has the name.
+    pub fn named_edge(&self, index: usize, name: &str) -> Option<usize> {
+        self.edge_names.get(&index)?.get(name).copied()
+    }
+
+    /// Returns the name of the shape with index index, or none if it has no
+    /// name.
+    pub fn shape_name(&self, index: usize) -> Option<&str> {
+        self.shape_names
+            .iter()
+            .find(|(_, i)| **i == index)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the name of the edge with index edge of the shape with index
+    /// index, or none if it has no name.
+    pub fn edge_name(&self, index: usize, edge: usize) -> Option<&str> {
+        self.edge_names
+            .get(&index)?
+            .iter()
+            .find(|(_, e)| **e == edge)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the range of shape indexes that indexes selects.
+    fn shape_anchor(&self, indexes: &Anchor) -> Result<Range<usize>> {
+        match indexes {
+            Anchor::Indexes(indexes) | Anchor::Shapes(indexes) => Ok(indexes.clone()),
+            Anchor::Name(name) => {
+                let index = self
+                    .named_shape(name)
+                    .ok_or_else(|| UnknownAnchor { name: name.clone() })?;
+
+                Ok(index..index + 1)
+            }
+            Anchor::Edges(_) => Err(mismatched("shapes", "edge")),
+        }
+    }
+
+    /// Returns the ranges of shape and edge indexes that indexes and edges
+    /// select.
+    /// An edge name selects the edge with that name of every shape indexes
+    /// selects, which must be the same edge of each.
+    pub(crate) fn anchors(
+        &self,
+        indexes: &Anchor,
+        edges: &Anchor,
+    ) -> Result<(Range<usize>, Range<usize>)> {
+        let unknown = |name: &String| UnknownAnchor { name: name.clone() };
+        let indexes = self.shape_anchor(indexes)?;
+        let edges = match edges {
+            Anchor::Indexes(edges) | Anchor::Edges(edges) => edges.clone(),
+            Anchor::Shapes(_) => return Err(mismatched("edges", "shape")),
+            Anchor::Name(name) => {
+                let mut edge = None;
+                for i in indexes.clone() {
+                    let e = self.named_edge(i, name).ok_or_else(|| unknown(name))?;
+                    if edge.map_or(false, |edge| edge != e) {
+                        return Err(AmbiguousAnchor { name: name.clone() });
+                    }
+                    edge = Some(e);
+                }
+                let edge = edge.ok_or_else(|| unknown(name))?;
+                edge..edge + 1
+            }
+        };
+
+        Ok((indexes, edges))
+    }
+
+    /// Attaches shape to every edge in edges of each shape in indexes.
+    /// Shapes and edges are selected by ranges of indexes, single indexes,
+    /// ids (see `ShapeId` and `EdgeId`), or names given with `name_shape` and
+    /// `name_edge`.
+    pub fn add_multi(
+        &mut self,
+        indexes: impl ShapeAnchor,
+        edges: impl EdgeAnchor,
+        shape: impl IntoShape,
+    ) -> Result<Range<usize>> {
+        let shape = shape
+            .into_shape(self)
+            .map_err(|err| err.context("add_multi", None, None))?;
+        let (indexes, edges) = self
+            .anchors(&indexes.into(), &edges.into())
+            .map_err(|err| err.context("add_multi", None, None))?;
+        let operation = Operation::AddMulti {
+            indexes: indexes.clone(),
+            edges: edges.clone(),
+            shape,
+        };
+
+        self.record(operation, |m| {
+            let start = m.shapes.len();
+            for i in indexes {
+                for e in edges.clone() {
+                    m.attach(i, e, shape)
+                        .map_err(|err| err.context("add_multi", Some(i), Some(e)))?;
+                }
+            }
+            let end = m.shapes.len();
+
+            Ok(start..end)
+        })
+    }
+
+    /// Attaches shape to every edge in edges of each shape in indexes, as
+    /// `add_multi` does, but carries on past edges that fail.
+    /// Returns the index of the model's shape at each position that was
+    /// attached, and the index, edge, and error of each that was not, so
+    /// batch constructions can proceed past known-bad edges.
+    pub fn add_multi_lossy(
+        &mut self,
+        indexes: impl ShapeAnchor,
+        edges: impl EdgeAnchor,
+        shape: impl IntoShape,
+    ) -> Result<(Vec<usize>, Vec<EdgeFailure>)> {
+        let shape = shape
+            .into_shape(self)
+            .map_err(|err| err.context("add_multi_lossy", None, None))?;
+        let (indexes, edges) = self
+            .anchors(&indexes.into(), &edges.into())
+            .map_err(|err| err.context("add_multi_lossy", None, None))?;
+        let operation = Operation::AddMultiLossy {
+            indexes: indexes.clone(),
+            edges: edges.clone(),
+            shape,
+        };
+
+        self.record(operation, |m| {
+            let mut added = Vec::new();
+            let mut failed = Vec::new();
+            for i in indexes {
+                for e in edges.clone() {
+                    match m.attach(i, e, shape) {
+                        Ok(index) => added.push(index),
+                        Err(err) => failed.push((i, e, err)),
+                    }
+                }
+            }
+
+            Ok((added, failed))
+        })
+    }
+
+    /// Attaches shape to every edge in edges of each shape in set, as
+    /// `add_multi` does for each range of consecutive indexes in the set, and
+    /// returns the indexes of the shapes added.
+    pub fn add_multi_set(
+        &mut self,
+        set: &ShapeSet,
+        edges: impl EdgeAnchor,
+        shape: impl IntoShape,
+    ) -> Result<Vec<usize>> {
+        let shape = shape.into_shape(self)?;
+        let edges = edges.into();
+        let mut added = Vec::new();
+        for indexes in set.ranges() {
+            added.extend(self.add_multi(indexes, edges.clone(), shape)?);
+        }
+
+        Ok(added)
+    }
+
+    /// Attaches ring[e], if it is some shape, to the edge with index e of each
+    /// shape in indexes, such as alternating squares and triangles around a
+    /// hexagon.
+    /// Edges past the end of ring are left bare. Returns the range of the
+    /// model's shapes that were added, in the order of their parents and
+    /// then their edges.
+    pub fn add_ring<S: IntoShape + Clone>(
+        &mut self,
+        indexes: Range<usize>,
+        ring: &[Option<S>],
+    ) -> Result<Range<usize>> {
+        let shapes = ring
+            .iter()
+            .map(|s| s.clone().map(|s| s.into_shape(self)).transpose())
+            .collect::<Result<Vec<Option<Shape>>>>()
+            .map_err(|err| err.context("add_ring", None, None))?;
+        let operation = Operation::AddRing {
+            indexes: indexes.clone(),
+            shapes: shapes.clone(),
+        };
+
+        self.record(operation, |m| {
+            let start = m.shapes.len();
+            for i in indexes {
+                for (e, shape) in shapes.iter().enumerate() {
+                    if let Some(shape) = shape {
+                        m.attach(i, e, *shape)
+                            .map_err(|err| err.context("add_ring", Some(i), Some(e)))?;
+                    }
+                }
+            }
+            let end = m.shapes.len();
+
+            Ok(start..end)
+        })
+    }
+
+    /// Adds pattern to the model, placing its first shape where that shape
+    /// lies (the origin, unless it was moved) and attaching each of its other
+    /// shapes as the pattern attaches them, as `add` and `attach` do.
+    /// Returns the range of the model's shapes that were added, which appear
+    /// in the same order as in the pattern, so a pattern's indexes (such as
+    /// the seeds `catalog::pattern` returns) are offset by the range's start.
+    pub fn place(&mut self, pattern: &Pattern) -> Result<Range<usize>> {
+        let operation = Operation::Place {
+            pattern: pattern.clone(),
+        };
+
+        self.record(operation, |m| {
+            let start = m.shapes.len();
+            let mut indexes = vec![m.add_shape(pattern.root())?];
+            for step in pattern.steps() {
+                let parent = indexes[step.index];
+                let index = m
+                    .attach(parent, step.edge, step.shape)
+                    .map_err(|err| err.context("place", Some(parent), Some(step.edge)))?;
+                indexes.push(index);
+            }
+            let end = m.shapes.len();
+
+            Ok(start..end)
+        })
+    }
+
+    /// Attaches a copy of pattern to every edge in edges of each shape in
+    /// indexes.
+    /// Returns the range of the model's shapes that were added, in which each
+    /// copy's shapes appear in the same order as in the pattern.
+    pub fn add_pattern(
+        &mut self,
+        indexes: Range<usize>,
+        edges: Range<usize>,
+        pattern: &Pattern,
+    ) -> Result<Range<usize>> {
+        let operation = Operation::AddPattern {
+            indexes: indexes.clone(),
+            edges: edges.clone(),
+            pattern: pattern.clone(),
+        };
+
+        self.record(operation, |m| {
+            let start = m.shapes.len();
+            for i in indexes {
+                for e in edges.clone() {
+                    m.attach_pattern(i, e, pattern)
+                        .map_err(|err| err.context("add_pattern", Some(i), Some(e)))?;
+                }
+            }
+            let end = m.shapes.len();
+
+            Ok(start..end)
+        })
+    }
+
+    /// Attaches a mirrored copy of pattern (a single shape or a `Pattern`) to
+    /// every edge in edges of each shape in indexes, as `add_pattern` does.
+    /// Each copy is the reflection, across its shared edge, of the copy
+    /// `add_pattern` would attach on the other side of the edge, so its edges
+    /// are numbered in the opposite direction and its shapes are laid out in
+    /// mirror image. Tilings whose fundamental domain includes reflections,
+    /// or that pair a pattern with its mirror image, are built this way.
+    /// Returns the range of the model's shapes that were added, in which each
+    /// copy's shapes appear in the same order as in the pattern.
+    pub fn add_multi_mirrored(
+        &mut self,
+        indexes: impl ShapeAnchor,
+        edges: impl EdgeAnchor,
+        pattern: impl Into<Pattern>,
+    ) -> Result<Range<usize>> {
+        let pattern = pattern.into();
+        let (indexes, edges) = self
+            .anchors(&indexes.into(), &edges.into())
+            .map_err(|err| err.context("add_multi_mirrored", None, None))?;
+        let operation = Operation::AddMultiMirrored {
+            indexes: indexes.clone(),
+            edges: edges.clone(),
+            pattern: pattern.clone(),
+        };
+
+        self.record(operation, |m| {
+            let start = m.shapes.len();
+            for i in indexes {
+                for e in edges.clone() {
+                    m.attach_mirrored(i, e, &pattern)
+                        .map_err(|err| err.context("add_multi_mirrored", Some(i), Some(e)))?;
+                }
+            }
+            let end = m.shapes.len();
+
+            Ok(start..end)
+        })
+    }
+
+    /// Attaches a mirrored copy of pattern to the edge with index edge of the
+    /// shape with index index.
+    /// The copy is laid out as `attach_pattern` would, then mirrored across
+    /// the edge's perpendicular bisector, which maps its first shape onto
+    /// itself.
+    fn attach_mirrored(&mut self, index: usize, edge: usize, pattern: &Pattern) -> Result<()> {
+        let parent = self.shapes.get(index).ok_or(OutOfBounds {
+            index,
+            length: self.shapes.len(),
+            name: String::from("model shapes"),
+        })?;
+        let (p0, p1) = parent.edge(edge, 0.0)?;
+        let axis = Axis::new(
+            p0.midpoint(p1),
+            math::atan2(p1.y - p0.y, p1.x - p0.x) + PI / 2.0,
+        );
+
+        let root = pattern.root();
+        let mut shapes = vec![parent
+            .adjacent(root.sides(), edge, root.fill(), root.stroke())?
+            .inherit(&root)];
+        for step in pattern.steps() {
+            let s = step.shape;
+            let attached = shapes[step.index]
+                .adjacent(s.sides(), step.edge, s.fill(), s.stroke())?
+                .inherit(&s);
+            shapes.push(attached);
+        }
+        for shape in shapes {
+            let mirrored = shape.reflected(&axis);
+            if self.strict {
+                self.check_angles(&mirrored)?;
+            }
+            self.add_shape(mirrored)?;
+        }
+
+        Ok(())
+    }
+
+    /// Attaches a copy of pattern to the edge with index edge of the shape with
+    /// index index.
+    fn attach_pattern(&mut self, index: usize, edge: usize, pattern: &Pattern) -> Result<()> {
+        let mut indexes = vec![self.attach(index, edge, pattern.root())?];
+        for step in pattern.steps() {
+            let parent = indexes[step.index];
+            indexes.push(self.attach(parent, step.edge, step.shape)?);
+        }
+
+        Ok(())
+    }
+
+    /// Attaches shape to the edge with id (or index) edge of the shape with id
+    /// (or index) index and returns the index of the model's shape at that
+    /// position.
+    /// The index is of an existing shape if the duplicate policy skipped or
+    /// replaced the attached shape.
+    pub fn attach(
+        &mut self,
+        index: impl Into<ShapeId>,
+        edge: impl Into<EdgeId>,
+        shape: impl IntoShape,
+    ) -> Result<usize> {
+        self.attach_at(index, edge, 0.0, shape)
+    }
+
+    /// Attaches shape to the edge with index edge of the shape with index
+    /// index, slid t edge lengths along the edge, and returns the index of the
+    /// model's shape at that position, as `attach` does.
+    /// Shapes attached at an offset share only part of the edge (see
+    /// `Shape::adjacent_at`), which builds tilings that are not edge-to-edge,
+    /// such as bricks offset by half a brick.
+    pub fn attach_at(
+        &mut self,
+        index: impl Into<ShapeId>,
+        edge: impl Into<EdgeId>,
+        t: f64,
+        shape: impl IntoShape,
+    ) -> Result<usize> {
+        self.attach_on(index, edge, t, Side::Outward, shape)
+    }
+
+    /// Attaches shape on side of the edge with index edge of the shape with
+    /// index index, slid t edge lengths along the edge, and returns the index
+    /// of the model's shape at that position, as `attach_at` does.
+    /// Attaching inward places shapes into notches and the holes inside rings
+    /// of shapes (see `Shape::adjacent_on`).
+    pub fn attach_on(
+        &mut self,
+        index: impl Into<ShapeId>,
+        edge: impl Into<EdgeId>,
+        t: f64,
+        side: Side,
+        shape: impl IntoShape,
+    ) -> Result<usize> {
+        let (index, edge) = (index.into().0, edge.into().0);
+        let shape = shape.into_shape(self)?;
+        let operation = Operation::Attach {
+            index,
+            edge,
+            t,
+            side,
+            shape,
+        };
+
+        self.record(operation, |m| m.attach_shape(index, edge, t, side, shape))
+    }
+
+    /// Attaches shape as `attach_on` does without recording the operation.
+    fn attach_shape(
+        &mut self,
+        index: usize,
+        edge: usize,
+        t: f64,
+        side: Side,
+        shape: Shape,
+    ) -> Result<usize> {
+        let parent = self.shapes.get(index).ok_or(OutOfBounds {
+            index,
+            length: self.shapes.len(),
+            name: String::from("model shapes"),
+        })?;
+        let attached = parent
+            .adjacent_on(shape.sides(), edge, t, side, shape.fill(), shape.stroke())?
+            .inherit(&shape);
+        if self.strict {
+            self.check_angles(&attached)?;
+        }
+
+        self.add_shape(attached)
+    }
+
+    /// Fills the rest of the surface with the pattern contained by the shapes
+    /// with index in indexes.
+    pub fn repeat(&mut self, indexes: Range<usize>) -> Result<()> {
+        let operation = Operation::Repeat {
+            indexes: indexes.clone(),
+        };
+
+        self.record(operation, |m| {
+            m.repeat_seeds(std::slice::from_ref(&indexes), Point::origin())
+                .map_err(|err| err.context("repeat", None, None))
+        })
+    }
+
+    /// Fills the rest of the surface with the pattern contained by the shapes
+    /// with index in indexes, as `repeat` does, but translating the pattern by
+    /// the offsets of the seed shapes' centers from center rather than from
+    /// the origin, so a pattern built around any point repeats along its own
+    /// lattice.
+    pub fn repeat_around(&mut self, indexes: Range<usize>, center: Point) -> Result<()> {
+        let operation = Operation::RepeatAround {
+            indexes: indexes.clone(),
+            center,
+        };
+
+        self.record(operation, |m| {
+            m.repeat_seeds(std::slice::from_ref(&indexes), center)
+                .map_err(|err| err.context("repeat_around", None, None))
+        })
+    }
+
+    /// Fills the rest of the surface with the pattern contained by the shapes
+    /// with index in indexes, as `repeat_around` does, around the center of
+    /// the shape with index root (usually the first shape of the pattern).
+    pub fn repeat_from(&mut self, indexes: Range<usize>, root: usize) -> Result<()> {
+        let center = self
+            .shapes
+            .get(root)
+            .ok_or(OutOfBounds {
+                index: root,
+                length: self.shapes.len(),
+                name: String::from("model shapes"),
+            })?
+            .point();
+
+        self.repeat_around(indexes, center)
+    }
+
+    /// Fills the rest of the surface with the pattern once for each group of
+    /// seed shapes in groups.
+    /// Each group translates the pattern by the centers of its own shapes, so
+    /// tilings with several translation orbits (such as 2-uniform tilings) can
+    /// be repeated in a single call.
+    pub fn repeat_groups(&mut self, groups: &[Range<usize>]) -> Result<()> {
+        self.repeat_groups_around(groups, Point::origin())
+    }
+
+    /// Fills the rest of the surface with the pattern once for each group of
+    /// seed shapes in groups, as `repeat_groups` does, but translating the
+    /// pattern by the offsets of the seed shapes' centers from center rather
+    /// than from the origin, as `repeat_around` does.
+    pub fn repeat_groups_around(&mut self, groups: &[Range<usize>], center: Point) -> Result<()> {
+        let operation = Operation::RepeatGroups {
+            groups: groups.to_vec(),
+            center,
+        };
+
+        self.record(operation, |m| {
+            m.repeat_seeds(groups, center)
+                .map_err(|err| err.context("repeat_groups", None, None))
+        })
+    }
+
+    /// Fills the rest of the surface with the pattern contained by the shapes
+    /// with index in indexes, as `repeat` does, cycling the copies of the
+    /// pattern through colorings across the lattice they are repeated along.
+    /// Each coloring lists a fill for each of the model's shapes by index;
+    /// shapes past the end of a coloring keep their fills. A placed shape
+    /// takes its fill for the model shape it is a translation of with the
+    /// lowest index, from the coloring of the copy that translation belongs
+    /// to.
+    /// Neighboring copies take different colorings: two colorings alternate
+    /// checkerboard-fashion on a square lattice (and in stripes on a hexagonal
+    /// one), and three color a hexagonal lattice.
+    /// Like `fill_at` the colorings fill cells, so they apply to the shapes
+    /// placed by this repeat (none in lazy mode) and not to those placed
+    /// afterwards.
+    pub fn repeat_with_coloring(
+        &mut self,
+        indexes: Range<usize>,
+        colorings: &[Vec<Color>],
+    ) -> Result<()> {
+        let operation = Operation::RepeatWithColoring {
+            indexes: indexes.clone(),
+            colorings: colorings.to_vec(),
+        };
+
+        self.record(operation, |m| {
+            m.repeat_seeds(std::slice::from_ref(&indexes), Point::origin())
+                .and_then(|_| m.color_copies(&indexes, colorings))
+                .map_err(|err| err.context("repeat_with_coloring", None, None))
+        })
+    }
+
+    /// Repeats the pattern contained by the shapes with index in indexes, as
+    /// `repeat` does, but places only the copies copies of the pattern
+    /// nearest the origin (including the pattern itself) rather than filling
+    /// the surface, so the tiling's size does not depend on the canvas, as
+    /// suits exporting its geometry.
+    /// In wrap mode the period is filled, as `repeat` does.
+    pub fn repeat_n(&mut self, indexes: Range<usize>, copies: usize) -> Result<()> {
+        self.repeat_n_around(indexes, copies, Point::origin())
+    }
+
+    /// Repeats the pattern contained by the shapes with index in indexes, as
+    /// `repeat_n` does, but translating the pattern by the offsets of the
+    /// seed shapes' centers from center rather than from the origin, as
+    /// `repeat_around` does, so the copies placed are those nearest center.
+    pub fn repeat_n_around(
+        &mut self,
+        indexes: Range<usize>,
+        copies: usize,
+        center: Point,
+    ) -> Result<()> {
+        let operation = Operation::RepeatN {
+            indexes: indexes.clone(),
+            copies,
+            center,
+        };
+
+        self.record(operation, |m| {
+            m.repeat_bounded(indexes, Bound::Copies(copies), center)
+                .map_err(|err| err.context("repeat_n", None, None))
+        })
+    }
+
+    /// Repeats the pattern contained by the shapes with index in indexes, as
+    /// `repeat` does, but places only the shapes centered within radius (in
+    /// model units) of the origin rather than filling the surface, so the
+    /// tiling is a disc whose size does not depend on the canvas.
+    /// In wrap mode the period is filled, as `repeat` does.
+    /// A radius that is negative or not finite is an error.
+    pub fn repeat_radius(&mut self, indexes: Range<usize>, radius: f64) -> Result<()> {
+        self.repeat_radius_around(indexes, radius, Point::origin())
+    }
+
+    /// Repeats the pattern contained by the shapes with index in indexes, as
+    /// `repeat_radius` does, but translating the pattern by the offsets of
+    /// the seed shapes' centers from center rather than from the origin, as
+    /// `repeat_around` does, and placing the shapes centered within radius
+    /// of center.
+    pub fn repeat_radius_around(
+        &mut self,
+        indexes: Range<usize>,
+        radius: f64,
+        center: Point,
+    ) -> Result<()> {
+        let operation = Operation::RepeatRadius {
+            indexes: indexes.clone(),
+            radius,
+            center,
+        };
+
+        self.record(operation, |m| {
+            m.repeat_bounded(indexes, Bound::Radius(radius), center)
+                .map_err(|err| err.context("repeat_radius", None, None))
+        })
+    }
+
+    /// Copies the pattern contained by the shapes with index in indexes by
+    /// rotating it n times about the origin, a turn of 1/n each time,
+    /// rather than translating it, so rosettes and other radial designs can
+    /// be built from the same shapes as periodic tilings.
+    /// Copies that coincide with a placed shape are skipped, so a pattern
+    /// with a rotational symmetry of its own is not placed twice.
+    pub fn repeat_rotational(&mut self, indexes: Range<usize>, n: usize) -> Result<()> {
+        let operation = Operation::RepeatRotational {
+            indexes: indexes.clone(),
+            n,
+        };
+
+        self.record(operation, |m| {
+            m.repeat_rotated(indexes, n)
+                .map_err(|err| err.context("repeat_rotational", None, None))
+        })
+    }
+
+    /// Rotates the pattern as `repeat_rotational` does without recording the
+    /// operation.
+    fn repeat_rotated(&mut self, indexes: Range<usize>, n: usize) -> Result<()> {
+        if n == 0 {
+            return Err(InvalidRepeatSeed {
+                indexes: indexes.collect(),
+                reason: String::from("the pattern cannot be rotated zero times"),
+            });
+        }
+        if indexes.end > self.shapes.len() {
+            return Err(OutOfBounds {
+                index: indexes.end - 1,
+                length: self.shapes.len(),
+                name: String::from("model shapes"),
+            });
+        }
+
+        for k in 1..n {
+            let angle = 2.0 * PI * k as f64 / n as f64;
+            for index in indexes.clone() {
+                let s = self.shapes.at(index).rotated(angle, Point::origin());
+                let key = self.key(s.point());
+                if self.lookup.contains_key(&key) {
+                    continue;
+                }
+
+                self.check_max_shapes()?;
+                self.place_copy(index, &s);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Repeats the pattern contained by the shapes with index in indexes along
+    /// direction alone, forward and backward across the canvas, making a
+    /// strip such as the border or trim of a frieze.
+    /// With glide, every other copy is also reflected across the strip's
+    /// axis (the line through the origin along direction), so the copies
+    /// alternate like footprints. Reflected copies are placed from mirrored
+    /// shapes added to the model after its others, with the styles of the
+    /// edges they mirror.
+    pub fn repeat_strip(
+        &mut self,
+        indexes: Range<usize>,
+        direction: Vector,
+        glide: bool,
+    ) -> Result<()> {
+        let operation = Operation::RepeatStrip {
+            indexes: indexes.clone(),
+            direction,
+            glide,
+        };
+
+        self.record(operation, |m| {
+            m.repeat_along(indexes, direction, glide)
+                .map_err(|err| err.context("repeat_strip", None, None))
+        })
+    }
+
+    /// Repeats the pattern as `repeat_strip` does without recording the
+    /// operation.
+    fn repeat_along(
+        &mut self,
+        indexes: Range<usize>,
+        direction: Vector,
+        glide: bool,
+    ) -> Result<()> {
+        let length = direction.distance(Point::origin());
+        if shape::snap(length, self.precision) == 0 {
+            return Err(InvalidRepeatSeed {
+                indexes: indexes.collect(),
+                reason: String::from("the strip's direction has no length"),
+            });
+        }
+        if indexes.end > self.shapes.len() {
+            return Err(OutOfBounds {
+                index: indexes.end - 1,
+                length: self.shapes.len(),
+                name: String::from("model shapes"),
+            });
+        }
+
+        // Copies are placed until the pattern is beyond the canvas at both
+        // ends of the strip.
+        let canvas = self.canvas_rect();
+        let (x0, y0) = (canvas.origin().x, canvas.origin().y);
+        let reach = math::hypot(
+            x0.abs().max((x0 + canvas.width()).abs()),
+            y0.abs().max((y0 + canvas.height()).abs()),
+        );
+        let extent = indexes
+            .clone()
+            .map(|i| self.shapes.at(i))
+            .map(|s| s.point().distance(Point::origin()) + s.circumradius())
+            .fold(0.0, f64::max);
+        let copies = ((reach + extent) / length).ceil() as i64;
+        let axis = Axis::new(Point::origin(), math::atan2(direction.y, direction.x));
+        // Reflected copies run their edges the other way, so they are placed
+        // from mirrored model shapes of their own rather than the originals.
+        let mut mirrors = HashMap::new();
+        for k in -copies..=copies {
+            if k == 0 {
+                continue;
+            }
+
+            let reflected = glide && k % 2 != 0;
+            for index in indexes.clone() {
+                let s = match reflected {
+                    true => self.shapes.at(index).reflected(&axis),
+                    false => self.shapes.at(index),
+                };
+                let vector = direction * k as f64;
+                let s = s.clone_at(s.point() + vector);
+                let key = self.key(s.point());
+                if self.lookup.contains_key(&key) {
+                    continue;
+                }
+
+                self.check_max_shapes()?;
+                match (reflected, mirrors.get(&index)) {
+                    (false, _) => self.place_copy(index, &s),
+                    (true, Some(mirror)) => {
+                        let m = self.shapes.at(*mirror);
+                        self.place_copy(*mirror, &m.placed_at(s.point(), m.rotation()));
+                    }
+                    (true, None) => {
+                        let mirror = self.add_mirror(index, s, |p| axis.reflect(p) + vector)?;
+                        mirrors.insert(index, mirror);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds shape, the image of the shape with index index under the
+    /// reflection image, to the model and returns its index.
+    /// Mirroring reverses the order of the shape's edges, so the styles and
+    /// paths of the original's edges are carried over to the edges they are
+    /// mirrored onto, as are its overlay and whether it is hidden.
+    fn add_mirror<F>(&mut self, index: usize, shape: Shape, image: F) -> Result<usize>
+    where
+        F: Fn(Point) -> Point,
+    {
+        let mirror = self.add_shape(shape)?;
+        let (original, copy) = (self.shapes.at(index), self.shapes.at(mirror));
+        let midpoints = |s: &Shape| -> Result<Vec<Point>> {
+            Ok(s.points(0.0)?
+                .windows(2)
+                .map(|w| w[0].midpoint(w[1]))
+                .collect())
+        };
+        let copied = midpoints(&copy)?
+            .into_iter()
+            .map(|p| self.key(p))
+            .collect::<Vec<Key>>();
+        let edges = midpoints(&original)?
+            .into_iter()
+            .enumerate()
+            .filter_map(|(e, p)| {
+                let key = self.key(image(p));
+                copied.iter().position(|k| *k == key).map(|j| (e, j))
+            })
+            .collect::<Vec<(usize, usize)>>();
+
+        for (e, j) in edges {
+            if let Some(style) = self.edge_styles.get(&index).and_then(|s| s.get(&e)) {
+                let style = *style;
+                self.edge_styles.entry(mirror).or_default().insert(j, style);
+            }
+            // The path runs the other way along the mirrored edge, which
+            // leaves it on the same side of the edge.
+            if let Some(path) = self.edge_paths.get(&index).and_then(|p| p.get(&e)) {
+                let path = path
+                    .iter()
+                    .rev()
+                    .map(|p| Point {
+                        x: 1.0 - p.x,
+                        y: p.y,
+                    })
+                    .collect();
+                self.edge_paths.entry(mirror).or_default().insert(j, path);
+            }
+        }
+        if let Some(overlay) = self.overlays.get(&index).cloned() {
+            self.overlays.insert(mirror, overlay);
+        }
+        if self.hidden.contains(&index) {
+            self.hidden.insert(mirror);
+        }
+
+        Ok(mirror)
+    }
+
+    /// Fills the surface with the pattern of every shape in the model,
+    /// translated by each combination of vectors taking up to extent steps
+    /// (forward or backward) along each vector.
+    /// Unlike `repeat`, the lattice is given rather than inferred from the
+    /// centers of seed shapes, so patterns whose repeating shapes are not
+    /// centered on lattice points can be repeated.
+    /// Like `repeat`, only the copies of the pattern that overlap the canvas
+    /// (or the region set by `with_coverage`) are placed. Vectors that are not
+    /// finite or are zero are an error, as are vectors no two of which are
+    /// linearly independent, which cannot fill the plane, and an extent whose
+    /// (2 * extent + 1)^n combinations of n vectors exceed the model's limit
+    /// on placed shapes.
+    /// In wrap mode extent is ignored and one period of the lattice is filled.
+    pub fn repeat_by(&mut self, vectors: &[Point], extent: usize) -> Result<()> {
+        let operation = Operation::RepeatBy {
+            vectors: vectors.to_vec(),
+            extent,
+        };
+
+        self.record(operation, |m| m.repeat_vectors(vectors, extent))
+    }
+
+    /// Repeats the pattern as `repeat_by` does without recording the
+    /// operation.
+    fn repeat_vectors(&mut self, vectors: &[Point], extent: usize) -> Result<()> {
+        if let Some(v) = vectors.iter().find(|v| {
+            !v.x.is_finite() || !v.y.is_finite() || self.key(**v) == self.key(Point::origin())
+        }) {
+            return Err(InvalidRepeatVectors {
+                reason: format!("vector ({}, {}) is zero or not finite", v.x, v.y),
+            }
+            .context("repeat_by", None, None));
+        }
+        // Two vectors are independent if the sine of the angle between them,
+        // their cross product over their lengths, is not lost to rounding.
+        let tolerance = math::powi(10.0, -self.precision);
+        let independent = vectors.iter().enumerate().any(|(i, a)| {
+            vectors[i + 1..].iter().any(|b| {
+                let cross = a.x * b.y - a.y * b.x;
+                cross.abs() > tolerance * a.distance(Point::origin()) * b.distance(Point::origin())
+            })
+        });
+        if !independent {
+            return Err(InvalidRepeatVectors {
+                reason: format!(
+                    "no two of the {} vectors are linearly independent",
+                    vectors.len()
+                ),
+            }
+            .context("repeat_by", None, None));
+        }
+        if self.wrap {
+            let mut translations = vectors.to_vec();
+            translations.extend(vectors.iter().map(|v| Point { x: -v.x, y: -v.y }));
+
+            return self
+                .repeat_wrapped(translations)
+                .map_err(|err| err.context("repeat_by", None, None));
+        }
+
+        let max = self.max_shapes.unwrap_or(usize::MAX);
+        let copies = vectors.iter().try_fold(1usize, |copies, _| {
+            copies.checked_mul(extent.checked_mul(2)?.checked_add(1)?)
+        });
+        if !self.lazy && copies.map_or(true, |copies| copies > max) {
+            return Err(TooManyShapes {
+                max,
+                scale: self.scale,
+            }
+            .context("repeat_by", None, None));
+        }
+
+        self.generators.extend_from_slice(vectors);
+        if self.lazy {
+            return Ok(());
+        }
+
+        // A copy of the pattern is placed only if the bounding box of its
+        // shapes overlaps the region.
+        let region = self.coverage.unwrap_or_else(|| self.canvas_rect());
+        let (mut lo, mut hi) = (Point::origin(), Point::origin());
+        for (i, s) in self.shapes.iter().enumerate() {
+            let r = s.circumradius();
+            let (a, b) = (
+                Point {
+                    x: s.point().x - r,
+                    y: s.point().y - r,
+                },
+                Point {
+                    x: s.point().x + r,
+                    y: s.point().y + r,
+                },
+            );
+            (lo, hi) = match i {
+                0 => (a, b),
+                _ => (
+                    Point {
+                        x: lo.x.min(a.x),
+                        y: lo.y.min(a.y),
+                    },
+                    Point {
+                        x: hi.x.max(b.x),
+                        y: hi.y.max(b.y),
+                    },
+                ),
+            };
+        }
+        let bounds = Rect::new(lo.x, lo.y, hi.x - lo.x, hi.y - lo.y);
+
+        let steps = extent as i64;
+        let mut coefficients = vec![-steps; vectors.len()];
+        loop {
+            let point =
+                vectors
+                    .iter()
+                    .zip(coefficients.iter())
+                    .fold(Point::origin(), |p, (v, c)| {
+                        p + Point {
+                            x: v.x * *c as f64,
+                            y: v.y * *c as f64,
+                        }
+                    });
+            let copy = Rect::new(
+                bounds.origin().x + point.x,
+                bounds.origin().y + point.y,
+                bounds.width(),
+                bounds.height(),
+            );
+            if copy.intersects(&region) {
+                self.add_repeats(point)
+                    .map_err(|err| err.context("repeat_by", None, None))?;
+            }
+
+            // Advance the coefficients like an odometer.
+            match coefficients.iter().position(|c| *c < steps) {
+                Some(i) => {
+                    coefficients[i] += 1;
+                    for c in coefficients[..i].iter_mut() {
+                        *c = -steps;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the operations that built the model (`add`, `add_multi`,
+    /// `add_pattern`, `attach`, the repeats, and the transforms `translate`,
+    /// `rotate`, and `reflect`), oldest first, excluding those that were
+    /// undone.
+    pub fn history(&self) -> &[Operation] {
+        self.history.operations()
+    }
+
+    /// Undoes the last operation in the model's history, restoring the model
+    /// as it was before the operation, and returns true, or returns false if
+    /// the history is empty.
+    /// Only the operation's own changes are undone: changes made since by
+    /// methods that are not recorded (such as styling or filling the model)
+    /// are kept, except for those of the shapes the operation added, which
+    /// are removed with them.
+    pub fn undo(&mut self) -> bool {
+        let (operation, checkpoint) = match self.history.pop() {
+            Some(undone) => undone,
+            None => return false,
+        };
+
+        let shapes = self.shapes.len();
+        self.restore(checkpoint);
+        if self.shapes.len() < shapes {
+            self.observers.notify(Change::Removed {
+                indexes: self.shapes.len()..shapes,
+            });
+        }
+        if operation.is_repeat() {
+            self.observers.notify(Change::Repeated);
+        }
+        self.history.undone_mut().push(operation);
+
+        true
+    }
+
+    /// Redoes the last operation undone by `undo`, replaying it on the model,
+    /// and returns true, or returns false if no operation was undone since
+    /// the last operation was recorded.
+    pub fn redo(&mut self) -> Result<bool> {
+        let operation = match self.history.undone_mut().pop() {
+            Some(operation) => operation,
+            None => return Ok(false),
+        };
+
+        // Recording the replayed operation would discard the rest of the
+        // operations that were undone.
+        let undone = std::mem::take(self.history.undone_mut());
+        let replayed = self.replay(&operation);
+        *self.history.undone_mut() = undone;
+        if let Err(err) = replayed {
+            self.history.undone_mut().push(operation);
+
+            return Err(err);
+        }
+
+        Ok(true)
+    }
+
+    /// Registers callback to be called with each change to the model's shapes
+    /// (see `Change`), so that a view of the model can be kept up to date
+    /// without rescanning it.
+    /// Each operation is reported once it completes, so `add_multi` reports
+    /// all of the shapes it added as a single `Change::Added`.
+    /// Changes made by methods that only restyle the model, such as `hide` or
+    /// `fill_at`, are not reported.
+    pub fn on_change<F>(&mut self, callback: F)
+    where
+        F: Fn(&Change) + Send + Sync + 'static,
+    {
+        self.observers.push(Arc::new(callback));
+    }
+
+    /// Applies operation to the model, recording it in the model's history.
+    fn replay(&mut self, operation: &Operation) -> Result<()> {
+        match operation {
+            Operation::Add { shape } => self.add(*shape),
+            Operation::AddMulti {
+                indexes,
+                edges,
+                shape,
+            } => self
+                .add_multi(indexes.clone(), edges.clone(), *shape)
+                .map(|_| ()),
+            Operation::AddMultiLossy {
+                indexes,
+                edges,
+                shape,
+            } => self
+                .add_multi_lossy(indexes.clone(), edges.clone(), *shape)
+                .map(|_| ()),
+            Operation::AddPattern {
+                indexes,
+                edges,
+                pattern,
+            } => self
+                .add_pattern(indexes.clone(), edges.clone(), pattern)
+                .map(|_| ()),
+            Operation::AddRing { indexes, shapes } => {
+                self.add_ring(indexes.clone(), shapes).map(|_| ())
+            }
+            Operation::Place { pattern } => self.place(pattern).map(|_| ()),
+            Operation::AddMultiMirrored {
+                indexes,
+                edges,
+                pattern,
+            } => self
+                .add_multi_mirrored(indexes.clone(), edges.clone(), pattern.clone())
+                .map(|_| ()),
+            Operation::Attach {
+                index,
+                edge,
+                t,
+                side,
+                shape,
+            } => self.attach_on(*index, *edge, *t, *side, *shape).map(|_| ()),
+            Operation::Repeat { indexes } => self.repeat(indexes.clone()),
+            Operation::RepeatAround { indexes, center } => {
+                self.repeat_around(indexes.clone(), *center)
+            }
+            Operation::RepeatGroups { groups, center } => {
+                self.repeat_groups_around(groups, *center)
+            }
+            Operation::RepeatWithColoring { indexes, colorings } => {
+                self.repeat_with_coloring(indexes.clone(), colorings)
+            }
+            Operation::RepeatBy { vectors, extent } => self.repeat_by(vectors, *extent),
+            Operation::RepeatN {
+                indexes,
+                copies,
+                center,
+            } => self.repeat_n_around(indexes.clone(), *copies, *center),
+            Operation::RepeatRadius {
+                indexes,
+                radius,
+                center,
+            } => self.repeat_radius_around(indexes.clone(), *radius, *center),
+            Operation::RepeatRotational { indexes, n } => {
+                self.repeat_rotational(indexes.clone(), *n)
+            }
+            Operation::RepeatStrip {
+                indexes,
+                direction,
+                glide,
+            } => self.repeat_strip(indexes.clone(), *direction, *glide),
+            Operation::Translate { vector } => {
+                self.translate(*vector);
+                Ok(())
+            }
+            Operation::Rotate { angle, center } => {
+                self.rotate(*angle, *center);
+                Ok(())
+            }
+            Operation::Reflect { axis } => {
+                self.reflect(*axis);
+                Ok(())
+            }
+        }
+    }
+
+    /// Applies f to the model and, if it succeeds, records operation in the
+    /// model's history along with a checkpoint of what it changed, or if it
+    /// fails, restores the model as it was before.
+    /// Operations applied by f are part of operation, so they are not
+    /// recorded.
+    fn record<T, F>(&mut self, operation: Operation, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Model) -> Result<T>,
+    {
+        if self.history.is_recording() {
+            return f(self);
+        }
+
+        let len = self.shapes.len();
+        self.history.begin(Checkpoint {
+            shapes: len,
+            generators: self.generators.len(),
+            period: self.period,
+            ..Checkpoint::default()
+        });
+        #[cfg(feature = "tracing")]
+        let span = operation.is_repeat().then(|| {
+            tracing::debug_span!("repeat", shapes = len, placed = tracing::field::Empty).entered()
+        });
+        let result = f(self);
+        let checkpoint = self.history.end().unwrap_or_default();
+        #[cfg(feature = "tracing")]
+        if let Some(span) = span {
+            span.record("placed", self.repeated_len());
+        }
+        if result.is_err() {
+            self.restore(checkpoint);
+
+            return result;
+        }
+
+        if self.shapes.len() > len {
+            self.observers.notify(Change::Added {
+                indexes: len..self.shapes.len(),
+            });
+        }
+        if operation.is_repeat() {
+            self.observers.notify(Change::Repeated);
+        }
+        self.history.push(operation, checkpoint);
+
+        result
+    }
+
+    /// Restores the model as it was before the operation whose changes
+    /// checkpoint holds, removing the shapes it added (with their styles,
+    /// paths, overlays, and names) and their placements.
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.graph.clear();
+        if let Some(geometry) = checkpoint.geometry {
+            let geometry = *geometry;
+            self.shapes = geometry.shapes;
+            self.tiles = geometry.tiles;
+            self.generators = geometry.generators;
+            self.period = geometry.period;
+            self.lookup = geometry.lookup;
+            self.grid = geometry.grid;
+            self.translations = geometry.translations;
+            self.fills = geometry.fills;
+            self.image_offsets = geometry.image_offsets;
+            self.edge_styles = geometry.edge_styles;
+            self.edge_paths = geometry.edge_paths;
+            self.edge_names = geometry.edge_names;
+            self.observers.notify(Change::Transformed);
+
+            return;
+        }
+        match checkpoint.layout {
+            Some(layout) => {
+                self.lookup = layout.lookup;
+                self.grid = layout.grid;
+                self.translations = layout.translations;
+            }
+            None => {
+                for (key, previous) in checkpoint.placed.into_iter().rev() {
+                    let placement = match previous {
+                        Some(p) => self.lookup.insert(key, p),
+                        None => self.lookup.remove(&key),
+                    };
+                    if let (None, Some(p)) = (previous, placement) {
+                        let shape = self.resolve(&p);
+                        self.grid.remove(&shape, key);
+                    }
+                }
+                for key in checkpoint.translations {
+                    self.translations.remove(&key);
+                }
+            }
+        }
+        for (key, previous) in checkpoint.fills.into_iter().rev() {
+            match previous {
+                Some(fill) => self.fills.insert(key, fill),
+                None => self.fills.remove(&key),
+            };
+        }
+        for (index, shape) in checkpoint.replaced.into_iter().rev() {
+            self.shapes.set(index, &shape);
+            self.observers.notify(Change::Replaced { index });
+        }
+        self.generators.truncate(checkpoint.generators);
+        self.period = checkpoint.period;
+
+        let len = checkpoint.shapes;
+        if self.shapes.len() > len {
+            self.shapes.truncate(len);
+            self.edge_styles.retain(|i, _| *i < len);
+            self.edge_paths.retain(|i, _| *i < len);
+            self.edge_names.retain(|i, _| *i < len);
+            self.shape_names.retain(|_, i| *i < len);
+            self.hidden.retain(|i| *i < len);
+            self.overlays.retain(|i, _| *i < len);
+            // Placements moved since the operation (as by `mirror`) are not
+            // where it put them, so any left of the removed shapes go now.
+            let placed = self.lookup.len();
+            self.lookup.retain(|_, p| p.index < len);
+            if self.lookup.len() < placed {
+                self.grid.clear();
+                for (key, p) in self.lookup.iter() {
+                    let shape = self.resolve(p);
+                    self.grid.insert(&shape, *key);
+                }
+            }
+        }
+    }
+
+    /// Repeats the pattern for every group of seed shapes in groups, each
+    /// translating it by the offset of its center from center.
+    fn repeat_seeds(&mut self, groups: &[Range<usize>], center: Point) -> Result<()> {
+        let mut translations = Vec::new();
+        for indexes in groups {
+            self.check_seeds(indexes.clone(), center)?;
+            translations.push(
+                indexes
+                    .clone()
+                    .map(|i| self.shapes.point(i) - center)
+                    .collect::<Vec<Point>>(),
+            );
+        }
+
+        if self.wrap {
+            let translations = translations
+                .iter()
+                .flatten()
+                .flat_map(|t| [*t, Point { x: -t.x, y: -t.y }])
+                .collect();
+
+            return self.repeat_wrapped(translations);
+        }
+
+        self.generators
+            .extend(translations.iter().flatten().copied());
+        if self.lazy {
+            return Ok(());
+        }
+
+        // Each translation places a copy of the pattern about the center of
+        // its bounding box, so the pattern has covered the region once
+        // copies lie beyond each of the region's corners.
+        let region = self.coverage.unwrap_or_else(|| self.canvas_rect());
+        let center = self.pattern_center();
+        let (x0, y0) = (region.origin().x - center.x, region.origin().y - center.y);
+        let (x1, y1) = (x0 + region.width(), y0 + region.height());
+        let middle = Point {
+            x: (x0 + x1) / 2.0,
+            y: (y0 + y1) / 2.0,
+        };
+        let mut memos: Vec<HashMap<Key, (Point, i32)>> = vec![HashMap::new(); groups.len()];
+        for (translations, memo) in translations.iter().zip(memos.iter_mut()) {
+            // The copies spread out evenly from the copy they start from, so
+            // starting from the copy nearest the middle of the region, rather
+            // than from the pattern wherever it was built, covers the region
+            // in the fewest steps.
+            let mut start = Point::origin();
+            while let Some(next) = translations
+                .iter()
+                .map(|t| start + *t)
+                .filter(|p| p.distance(middle) < start.distance(middle) - shape::EPSILON)
+                .min_by(|a, b| a.distance(middle).total_cmp(&b.distance(middle)))
+            {
+                start = next;
+            }
+            let mut depth = 0;
+            loop {
+                self.repeat_r(translations, start, depth, memo)?;
+                let tl = memo.values().any(|(p, _)| p.x < x0 && p.y < y0);
+                let tr = memo.values().any(|(p, _)| p.x > x1 && p.y < y0);
+                let bl = memo.values().any(|(p, _)| p.x < x0 && p.y > y1);
+                let br = memo.values().any(|(p, _)| p.x > x1 && p.y > y1);
+                if tl && tr && bl && br {
+                    break;
+                }
+                depth += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fills each placed shape with its fill in the coloring of the copy of
+    /// the pattern it belongs to, as `repeat_with_coloring` describes.
+    fn color_copies(&mut self, indexes: &Range<usize>, colorings: &[Vec<Color>]) -> Result<()> {
+        if colorings.is_empty() {
+            return Ok(());
+        }
+
+        let (a, b) = self.lattice_basis().ok_or_else(|| InvalidRepeatSeed {
+            indexes: indexes.clone().collect(),
+            reason: String::from("the copies of the pattern do not form a lattice"),
+        })?;
+        // With an obtuse basis every copy's nearest neighbors differ from it
+        // by a, b, or a + b, so cycling the colorings by the sum of a copy's
+        // coordinates gives neighbors different colorings.
+        let b = match a.x * b.x + a.y * b.y > shape::EPSILON {
+            true => b - a,
+            false => b,
+        };
+        let det = a.x * b.y - a.y * b.x;
+        let n = colorings.len() as i64;
+        let mut fills = Vec::new();
+        for (key, placement) in self.lookup.iter() {
+            let placed = &self.shapes.at(placement.index);
+            let turn = 2.0 * PI / placed.sides() as f64;
+            let copy = self.shapes.iter().enumerate().find_map(|(k, s)| {
+                let d = (s.rotation() - placed.rotation()).rem_euclid(turn);
+                if s.sides() != placed.sides() || d.min(turn - d) > shape::EPSILON {
+                    return None;
+                }
+
+                let t = placement.point - s.point();
+                let (u, v) = ((t.x * b.y - t.y * b.x) / det, (a.x * t.y - a.y * t.x) / det);
+                let integral = |c: f64| (c - c.round()).abs() < shape::EPSILON;
+                (integral(u) && integral(v)).then(|| (k, u.round() as i64 + v.round() as i64))
+            });
+            if let Some((k, c)) = copy {
+                if let Some(fill) = colorings[c.rem_euclid(n) as usize].get(k) {
+                    fills.push((*key, *fill));
+                }
+            }
+        }
+        for (key, fill) in fills {
+            let previous = self.fills.insert(key, fill);
+            if let Some(journal) = self.history.journal() {
+                journal.fills.push((key, previous));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the center of the bounding box of the centers of the model's
+    /// shapes, the pattern that repeats copy, or the origin if there are none.
+    fn pattern_center(&self) -> Point {
+        let (mut lo, mut hi) = (
+            Point {
+                x: f64::INFINITY,
+                y: f64::INFINITY,
+            },
+            Point {
+                x: f64::NEG_INFINITY,
+                y: f64::NEG_INFINITY,
+            },
+        );
+        for s in self.shapes.iter() {
+            let p = s.point();
+            lo = Point {
+                x: lo.x.min(p.x),
+                y: lo.y.min(p.y),
+            };
+            hi = Point {
+                x: hi.x.max(p.x),
+                y: hi.y.max(p.y),
+            };
+        }
+
+        match self.shapes.is_empty() {
+            true => Point::origin(),
+            false => lo.midpoint(hi),
+        }
+    }
+
+    /// Repeats the pattern contained by the shapes with index in indexes,
+    /// translated by the offsets of their centers from center, until bound is
+    /// reached, as `repeat_n_around` and `repeat_radius_around` do.
+    fn repeat_bounded(&mut self, indexes: Range<usize>, bound: Bound, center: Point) -> Result<()> {
+        if let Bound::Radius(radius) = bound {
+            if !radius.is_finite() || radius < 0.0 {
+                return Err(InvalidRepeatSeed {
+                    indexes: indexes.collect(),
+                    reason: format!("radius {} is not a finite, non-negative length", radius),
+                });
+            }
+        }
+        if self.wrap {
+            return self.repeat_seeds(std::slice::from_ref(&indexes), center);
+        }
+
+        self.check_seeds(indexes.clone(), center)?;
+        // Each copy places every shape of the pattern, so copies past the
+        // model's limit on placed shapes are refused before they are sought.
+        let max = self.max_shapes.unwrap_or(usize::MAX);
+        let too_many = TooManyShapes {
+            max,
+            scale: self.scale,
+        };
+        let pattern = self.shapes.len().max(1);
+        if let Bound::Copies(copies) = bound {
+            if copies.saturating_mul(pattern) > max {
+                return Err(too_many);
+            }
+        }
+        let seeds = indexes
+            .clone()
+            .map(|i| self.shapes.point(i) - center)
+            .collect::<Vec<Point>>();
+        self.generators.extend_from_slice(&seeds);
+        // Shapes centered within radius belong to copies translated by at
+        // most radius plus the farthest center of a shape of the pattern.
+        let extent = self
+            .shapes
+            .iter()
+            .map(|s| s.point().distance(center))
+            .fold(0.0, f64::max);
+
+        // Translations are found step by step along the seeds; once every
+        // translation of the latest step is farther than a distance, the
+        // nearer translations are taken to be complete.
+        let origin = Point::origin();
+        let mut seen = HashSet::from([self.key(origin)]);
+        let mut found = vec![origin];
+        let mut frontier = vec![origin];
+        let mut inside = 0usize;
+        loop {
+            let mut next = Vec::new();
+            for p in frontier.iter() {
+                for s in seeds.iter() {
+                    let q = *p + *s;
+                    if seen.insert(self.key(q)) {
+                        next.push(q);
+                    }
+                }
+            }
+            // Copies translated by at most radius less the pattern's extent
+            // lie wholly within radius, so all of their shapes are placed.
+            if let Bound::Radius(radius) = bound {
+                inside += next
+                    .iter()
+                    .filter(|p| p.distance(origin) + extent <= radius)
+                    .count();
+                if inside.saturating_mul(pattern) > max {
+                    return Err(too_many);
+                }
+            }
+            let reach = next
+                .iter()
+                .map(|p| p.distance(origin))
+                .fold(f64::INFINITY, f64::min);
+            let complete = match bound {
+                Bound::Copies(copies) => {
+                    found.iter().filter(|p| p.distance(origin) < reach).count() >= copies
+                }
+                Bound::Radius(radius) => reach > radius + extent,
+            };
+            if complete || next.is_empty() {
+                break;
+            }
+
+            found.extend_from_slice(&next);
+            frontier = next;
+        }
+
+        // Nearer translations are placed first, in a fixed order.
+        found.sort_by(|a, b| {
+            let (da, db) = (a.distance(origin), b.distance(origin));
+            (shape::snap(da, self.precision), self.key(*a))
+                .partial_cmp(&(shape::snap(db, self.precision), self.key(*b)))
+                .unwrap_or(Less)
+        });
+        match bound {
+            Bound::Copies(copies) => {
+                for p in found.into_iter().take(copies) {
+                    self.add_repeats(p)?;
+                }
+            }
+            Bound::Radius(radius) => {
+                for p in found {
+                    self.add_repeats_within(p, center, radius)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recurisvely fills the surface by repeating a pattern of shapes along
+    /// translations.
+    fn repeat_r(
+        &mut self,
+        translations: &[Point],
+        point: Point,
+        depth: i32,
+        memo: &mut HashMap<Key, (Point, i32)>,
+    ) -> Result<()> {
+        if depth < 0 {
+            return Ok(());
+        }
+
+        let key = self.key(point);
+        let prev_depth = memo.get(&key).map_or(-1, |(_, d)| *d);
+        if prev_depth >= depth {
+            return Ok(());
+        }
+
+        memo.insert(key, (point, depth));
+
+        if prev_depth == -1 {
+            self.add_repeats(point)?;
+        }
+
+        for t in translations.iter() {
+            self.repeat_r(translations, point + *t, depth - 1, memo)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if the interior angles of shape and of the model's
+    /// shapes that share one of its vertices sum to more than 360° at that
+    /// vertex, so shape would overlap them.
+    /// Shapes that coincide with shape are left out, since the duplicate
+    /// policy decides what becomes of shape then.
+    fn check_angles(&self, shape: &Shape) -> Result<()> {
+        let points = shape.points(0.0)?;
+        for p in &points[..points.len() - 1] {
+            let key = self.key(*p);
+            let mut angle = shape.vertex_angle();
+            let mut shapes = Vec::new();
+            for (i, s) in self.shapes.iter().enumerate() {
+                if s.coincides_within(shape, self.precision) {
+                    continue;
+                }
+
+                if s.points(0.0)?.iter().any(|q| self.key(*q) == key) {
+                    angle += s.vertex_angle();
+                    shapes.push(i);
+                }
+            }
+
+            if angle > 2.0 * PI + shape::EPSILON {
+                return Err(AngleOverflow {
+                    x: p.x,
+                    y: p.y,
+                    angle: angle.to_degrees(),
+                    shapes,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if repeating the seed shapes with indexes around
+    /// center would never reach the surface's corners: when every seed is
+    /// centered at center, along a single line through it, or (outside of
+    /// wrap mode) on one side of a line through it.
+    fn check_seeds(&self, indexes: Range<usize>, center: Point) -> Result<()> {
+        let mut translations = Vec::new();
+        for i in indexes.clone() {
+            let s = self.shapes.get(i).ok_or(OutOfBounds {
+                index: i,
+                length: self.shapes.len(),
+                name: String::from("model shapes"),
+            })?;
+            translations.push((i, s.point() - center));
+        }
+
+        let origin = self.key(Point::origin());
+        let (zero, nonzero): (Vec<_>, Vec<_>) = translations
+            .into_iter()
+            .partition(|(_, p)| self.key(*p) == origin);
+        if nonzero.is_empty() {
+            let reason = if zero.is_empty() {
+                "there are no seeds"
+            } else {
+                "every seed is centered at the origin"
+            };
+            return Err(InvalidRepeatSeed {
+                indexes: zero.iter().map(|(i, _)| *i).collect(),
+                reason: String::from(reason),
+            });
+        }
+
+        let (_, a) = nonzero[0];
+        let spans = nonzero
+            .iter()
+            .any(|(_, b)| shape::snap(a.x * b.y - a.y * b.x, self.precision) != 0);
+        if !spans {
+            return Err(InvalidRepeatSeed {
+                indexes: indexes.collect(),
+                reason: String::from("every seed is centered along a single line"),
+            });
+        }
+
+        // Outside of wrap mode the pattern only moves forward along each
+        // seed's translation, so the translations must surround the origin:
+        // no two consecutive directions may be half a turn or more apart.
+        let mut angles = nonzero
+            .iter()
+            .map(|(_, p)| math::atan2(p.y, p.x))
+            .collect::<Vec<f64>>();
+        angles.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Less));
+        let gap = angles
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .fold(angles[0] + 2.0 * PI - angles[angles.len() - 1], f64::max);
+        if !self.wrap && gap >= PI - shape::EPSILON {
+            return Err(InvalidRepeatSeed {
+                indexes: indexes.collect(),
+                reason: String::from("every seed is centered on one side of the origin"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Fills one rectangular period of the pattern of every shape in the
+    /// model.
+    /// The pattern is translated by every combination of translations, so the
+    /// period is the shortest horizontal and vertical translation.
+    fn repeat_wrapped(&mut self, translations: Vec<Point>) -> Result<()> {
+        let mut seen = HashSet::new();
+        let mut frontier = vec![Point::origin()];
+        let mut width: Option<f64> = None;
+        let mut height: Option<f64> = None;
+        seen.insert(self.key(Point::origin()));
+        for _ in 0..MAX_WRAP_DEPTH {
+            let mut next = Vec::new();
+            for p in frontier.iter() {
+                for t in translations.iter() {
+                    let q = *p + *t;
+                    let key = self.key(q);
+                    if !seen.insert(key) {
+                        continue;
+                    }
+
+                    if key.1 == 0 && key.0 > 0 {
+                        width = Some(width.map_or(q.x, |w| w.min(q.x)));
+                    }
+                    if key.0 == 0 && key.1 > 0 {
+                        height = Some(height.map_or(q.y, |h| h.min(q.y)));
+                    }
+                    next.push(q);
+                }
+            }
+            frontier = next;
+
+            if width.is_some() && height.is_some() {
+                break;
+            }
+        }
+
+        let period = match (width, height) {
+            (Some(x), Some(y)) => Point { x, y },
+            _ => {
+                return Err(NotPeriodic {
+                    depth: MAX_WRAP_DEPTH,
+                })
+            }
+        };
+
+        // Search the translations modulo the period, which are finitely many.
+        let mut seen = HashSet::new();
+        let mut frontier = vec![Point::origin()];
+        let mut lattice = vec![Point::origin()];
+        seen.insert(self.key(Point::origin()));
+        while let Some(p) = frontier.pop() {
+            for t in translations.iter() {
+                let q = wrap(p + *t, period);
+                if seen.insert(self.key(q)) {
+                    frontier.push(q);
+                    lattice.push(q);
+                }
+            }
+        }
+
+        if let Some(journal) = self.history.journal() {
+            if journal.layout.is_none() {
+                journal.layout = Some(Box::new(Layout {
+                    lookup: self.lookup.clone(),
+                    grid: self.grid.clone(),
+                    translations: self.translations.clone(),
+                }));
+            }
+        }
+        self.lookup.clear();
+        self.grid.clear();
+        self.graph.clear();
+        self.translations.clear();
+        for p in lattice {
+            self.translations.insert(self.key(p), p);
+            for (index, s) in self.shapes.iter().enumerate() {
+                let q = wrap(p + s.point(), period);
+                let key = q.key(self.precision);
+                if self.lookup.contains_key(&key) {
+                    continue;
+                }
+
+                self.check_max_shapes()?;
+                self.graph.clear();
+                self.grid.insert(&s.clone_at(q), key);
+                self.lookup.insert(key, Placement::new(index, &s, q));
+            }
+        }
+        self.period = Some(period);
+
+        Ok(())
+    }
+
+    /// Adds a shape to be repeated at point, ensuring the model's limit on
+    /// placed shapes is not exceeded.
+    fn add_repeats(&mut self, point: Point) -> Result<()> {
+        self.add_repeats_within(point, Point::origin(), f64::INFINITY)
+    }
+
+    /// Adds a repeat of each of the model's shapes translated by point, as
+    /// `add_repeats` does, that is centered within radius of center.
+    fn add_repeats_within(&mut self, point: Point, center: Point, radius: f64) -> Result<()> {
+        let translation = self.key(point);
+        if let Entry::Vacant(e) = self.translations.entry(translation) {
+            e.insert(point);
+            if let Some(journal) = self.history.journal() {
+                journal.translations.push(translation);
+            }
+        }
+        for (index, s) in self.shapes.iter().enumerate() {
+            let p = self.snap(point + s.point());
+            let key = p.key(self.precision);
+            if self.lookup.contains_key(&key) || p.distance(center) > radius {
+                continue;
+            }
+
+            self.check_max_shapes()?;
+            self.graph.clear();
+            self.grid.insert(&s.clone_at(p), key);
+            self.lookup.insert(key, Placement::new(index, &s, p));
+            if let Some(journal) = self.history.journal() {
+                journal.placed.push((key, None));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error if placing another shape would exceed the model's
+    /// limit on placed shapes.
+    fn check_max_shapes(&self) -> Result<()> {
+        match self.max_shapes {
+            Some(max) if self.lookup.len() >= max => Err(TooManyShapes {
+                max,
+                scale: self.scale,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the center of the placed shape nearest point if it is within
+    /// the model's snap tolerance, or point if there is none.
+    fn snap(&self, point: Point) -> Point {
+        let tolerance = match self.snap {
+            Some(tolerance) => tolerance,
+            None => return point,
+        };
+
+        self.grid
+            .nearby(point)
+            .iter()
+            .filter_map(|key| self.lookup.get(key))
+            .map(|p| {
+                (
+                    p.point,
+                    math::hypot(p.point.x - point.x, p.point.y - point.y),
+                )
+            })
+            .filter(|(_, distance)| *distance <= tolerance)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Less))
+            .map_or(point, |(q, _)| q)
+    }
+
+    /// Places shape, a copy of the model shape with index index, in the
+    /// model's (repeated) tiling.
+    fn place_copy(&mut self, index: usize, shape: &Shape) {
+        self.graph.clear();
+        let key = self.key(shape.point());
+        self.grid.insert(shape, key);
+        let previous = self
+            .lookup
+            .insert(key, Placement::new(index, shape, shape.point()));
+        if let Some(journal) = self.history.journal() {
+            journal.placed.push((key, previous));
+        }
+    }
+
+    /// Returns the shape that placement places.
+    fn resolve(&self, placement: &Placement) -> Shape {
+        let s = self
+            .shapes
+            .placed_at(placement.index, placement.point, placement.rotation);
+        match self.fills.get(&self.key(placement.point)) {
+            Some(fill) => s.with_colors(*fill, s.stroke()),
+            None => s,
+        }
+    }
+
+    /// Returns the key that identifies point at the model's precision.
+    fn key(&self, point: Point) -> Key {
+        point.key(self.precision)
+    }
+
+    /// Returns a copy of the model whose shapes are colored with the fill and
+    /// stroke that mapping returns for each of them.
+    /// Repeats share their colors with the shape they repeat, so mapping is
+    /// called once per model shape and the copy's tiling is not repeated
+    /// again.
+    pub fn recolor<F>(&self, mapping: F) -> Model
+    where
+        F: Fn(&Shape) -> (Color, Color),
+    {
+        let mut model = self.clone();
+        model.recolor_mut(mapping);
+
+        model
+    }
+
+    /// Colors the model's shapes (and their repeats) with the fill and stroke
+    /// that mapping returns for each of them.
+    pub fn recolor_mut<F>(&mut self, mapping: F)
+    where
+        F: Fn(&Shape) -> (Color, Color),
+    {
+        self.shapes.update(|s| {
+            let (fill, stroke) = mapping(&s);
+            s.with_colors(fill, stroke)
+        });
+    }
+
+    /// Colors the shapes in set (and their repeats) with fill and stroke.
+    pub fn recolor_set(&mut self, set: &ShapeSet, fill: Color, stroke: Color) {
+        for i in set.indexes() {
+            if let Some(s) = self.shapes.get(*i) {
+                self.shapes.set(*i, &s.with_colors(fill, stroke));
+            }
+        }
+    }
+
+    /// Adds set, a look the model can be rendered in, under name, replacing
+    /// any set of that name.
+    /// Sets only take effect when chosen with `RenderOptions::style_set`, so
+    /// the model keeps its own colors for renders that choose none.
+    pub fn add_style_set(&mut self, name: &str, set: StyleSet) {
+        self.style_sets.insert(String::from(name), set);
+    }
+
+    /// Returns the style set added under name, if any.
+    pub fn style_set(&self, name: &str) -> Option<&StyleSet> {
+        self.style_sets.get(name)
+    }
+
+    /// Returns the names of the model's style sets, in order.
+    pub fn style_sets(&self) -> impl Iterator<Item = &str> {
+        self.style_sets.keys().map(String::as_str)
+    }
+
+    /// Returns an error naming the margin if the margin of options, or a
+    /// shape's own margin, is negative or not finite or would inset a shape
+    /// by its inradius or more, or if a tile cannot be inset by the margin of
+    /// options.
+    /// Shapes stretched by a transform are left to render as they are.
+    fn check_margin(&self, options: &RenderOptions) -> Result<()> {
+        render::check_margin("RenderOptions::margin", options.margin)?;
+        for s in self.shapes.iter() {
+            let (field, margin) = match s.margin() {
+                Some(margin) => ("Shape::margin", margin),
+                None => ("RenderOptions::margin", options.margin),
+            };
+            render::check_margin(field, margin)?;
+            if s.transform().is_none() && margin >= s.inradius() {
+                return Err(Error::margin_reaches(
+                    field,
+                    margin,
+                    s.inradius(),
+                    s.sides(),
+                ));
+            }
+        }
+        // A tile's inset outline turns inside out (or vanishes) once the
+        // margin reaches its inradius, reported as the distance from its
+        // center to its nearest edge.
+        for t in self.tiles.iter() {
+            let outline = t.points(0.0)?;
+            let inset = t.points(options.margin)?;
+            let area = irregular::area(&outline);
+            if irregular::area(&inset) * area <= 0.0 {
+                let inradius = outline
+                    .windows(2)
+                    .map(|w| distance_to_segment(t.point(), w[0], w[1]))
+                    .fold(f64::INFINITY, f64::min);
+
+                return Err(Error::margin_reaches(
+                    "RenderOptions::margin",
+                    options.margin,
+                    inradius,
+                    outline.len() as i32 - 1,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draws the shapes of placed in the style set options choose, if any.
+    /// Cells filled individually keep their fills.
+    fn apply_style_set(&self, placed: &mut [Placed], options: &RenderOptions) -> Result<()> {
+        let name = match &options.style_set {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let set = self
+            .style_sets
+            .get(name)
+            .ok_or_else(|| UnknownStyleSet { name: name.clone() })?;
+
+        for p in placed.iter_mut() {
+            if let Some(style) = set.get(p.index, &p.shape) {
+                let fill = match self.fills.get(&self.key(p.shape.point())) {
+                    Some(fill) => *fill,
+                    None => style.fill(),
+                };
+                p.shape = p.shape.with_colors(fill, style.stroke());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fills the shapes with index in indexes (and their repeats) with fill:
+    /// a color, which replaces their fills, or an image or texture, which is
+    /// painted over their fills, clipped to each shape.
+    /// Images are read when the model is rendered, so a missing or invalid
+    /// image fails the render.
+    pub fn set_fill(&mut self, indexes: Range<usize>, fill: Fill) -> Result<()> {
+        if indexes.end > self.shapes.len() {
+            let err = OutOfBounds {
+                index: indexes.end - 1,
+                length: self.shapes.len(),
+                name: String::from("model shapes"),
+            };
+
+            return Err(err.context("set_fill", Some(indexes.end - 1), None));
+        }
+
+        for i in indexes {
+            match &fill {
+                Fill::Color(color) => {
+                    let s = self.shapes.at(i);
+                    self.shapes.set(i, &s.with_colors(*color, s.stroke()));
+                    self.overlays.remove(&i);
+                }
+                Fill::Image(..) | Fill::Texture(..) => {
+                    self.overlays.insert(i, fill.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Sets the corner style of the shapes with index in indexes (and their
+    /// repeats) to corner, overriding the corner style they are rendered with.
+    /// The style is stored on the shapes, so `shape` (and the shape's
+    /// `outline_points`), `to_svg`, and `save` all see it.
+    pub fn set_corner(&mut self, indexes: Range<usize>, corner: Corner) -> Result<()> {
+        if indexes.end > self.shapes.len() {
+            let err = OutOfBounds {
+                index: indexes.end - 1,
+                length: self.shapes.len(),
+                name: String::from("model shapes"),
+            };
+
+            return Err(err.context("set_corner", Some(indexes.end - 1), None));
+        }
+
+        for i in indexes {
+            self.shapes.set(i, &self.shapes.at(i).with_corner(corner));
+        }
+
+        Ok(())
+    }
+
+    /// Moves the image filling the shape of cell by offset (in model units),
+    /// so each tile of an image fill can frame a different part of it,
+    /// ensuring cell is in the model.
+    pub fn offset_image(&mut self, cell: CellId, offset: Vector) -> Result<()> {
+        if !self.lookup.contains_key(&cell.0) {
+            let unit = math::powi(10.0, self.precision);
+            return Err(EmptyCell {
+                x: cell.0 .0 as f64 / unit,
+                y: cell.0 .1 as f64 / unit,
+            });
+        }
+
+        self.image_offsets.insert(cell.0, offset);
+
+        Ok(())
+    }
+
+    /// Colors the model's shapes (and their repeats) by theme, as
+    /// `Shape::themed` does, and drops the fills set by `fill_at` and
+    /// `fill_with`.
+    /// The theme's background is applied by rendering with
+    /// `RenderOptions::theme`.
+    pub fn apply_theme(&mut self, theme: &Theme) {
+        self.fills.clear();
+        self.seed = None;
+        self.recolor_mut(|s| (theme.fill(s.sides()), theme.stroke()));
+    }
+
+    /// Mirrors every shape in the model (including repeats) across axis.
+    /// Mirroring reverses the order of each shape's edges; styled, named, and
+    /// deformed edges are renumbered to match.
+    pub fn reflect(&mut self, axis: Axis) {
+        let operation = Operation::Reflect { axis };
+        self.record_transform(operation, |m| m.mirror_all(axis));
+    }
+
+    /// Mirrors the model as `reflect` does without recording the operation.
+    fn mirror_all(&mut self, axis: Axis) {
+        for (index, styles) in self.edge_styles.iter_mut() {
+            let sides = self.shapes.sides(*index) as usize;
+            *styles = styles
+                .drain()
+                .map(|(edge, style)| (sides - 1 - edge, style))
+                .collect();
+        }
+        // The paths of renumbered edges run the other way and are mirrored,
+        // which leaves them on the same side of their edges.
+        for (index, paths) in self.edge_paths.iter_mut() {
+            let sides = self.shapes.sides(*index) as usize;
+            *paths = paths
+                .drain()
+                .map(|(edge, path)| {
+                    let path = path
+                        .into_iter()
+                        .rev()
+                        .map(|p| Point {
+                            x: 1.0 - p.x,
+                            y: p.y,
+                        })
+                        .collect();
+                    (sides - 1 - edge, path)
+                })
+                .collect();
+        }
+        for (index, names) in self.edge_names.iter_mut() {
+            let sides = self.shapes.sides(*index) as usize;
+            for edge in names.values_mut() {
+                *edge = sides - 1 - *edge;
+            }
+        }
+
+        self.transform(|s| s.reflected(&axis), |t| t.reflected(&axis));
+    }
+
+    /// Rotates every shape in the model (including repeats) by angle (in
+    /// radians) about center.
+    pub fn rotate(&mut self, angle: f64, center: Point) {
+        self.record_transform(Operation::Rotate { angle, center }, |m| {
+            m.transform(|s| s.rotated(angle, center), |t| t.rotated(angle, center))
+        });
+    }
+
+    /// Moves every shape in the model (including repeats) by vector.
+    pub fn translate(&mut self, vector: Point) {
+        self.record_transform(Operation::Translate { vector }, |m| {
+            m.transform(|s| s.translated(vector), |t| t.translated(vector))
+        });
+    }
+
+    /// Applies the geometry transform f to the model, recording operation in
+    /// the model's history along with the geometry f replaces, so undoing
+    /// it restores the model exactly.
+    fn record_transform<F>(&mut self, operation: Operation, f: F)
+    where
+        F: FnOnce(&mut Model),
+    {
+        let geometry = Geometry {
+            shapes: self.shapes.clone(),
+            tiles: self.tiles.clone(),
+            generators: self.generators.clone(),
+            period: self.period,
+            lookup: self.lookup.clone(),
+            grid: self.grid.clone(),
+            translations: self.translations.clone(),
+            fills: self.fills.clone(),
+            image_offsets: self.image_offsets.clone(),
+            edge_styles: self.edge_styles.clone(),
+            edge_paths: self.edge_paths.clone(),
+            edge_names: self.edge_names.clone(),
+        };
+        // Transforms cannot fail.
+        let _ = self.record(operation, |m| {
+            if let Some(journal) = m.history.journal() {
+                if journal.geometry.is_none() {
+                    journal.geometry = Some(Box::new(geometry));
+                }
+            }
+            f(m);
+
+            Ok(())
+        });
+    }
+
+    /// Replaces every shape in the model (including repeats) with its image
+    /// under f, and every tile (including repeats) with its image under g.
+    fn transform<F, G>(&mut self, f: F, g: G)
+    where
+        F: Fn(&Shape) -> Shape,
+        G: Fn(&dyn Tile) -> Box<dyn Tile>,
+    {
+        // Repeats of tiles are only stored as translations, so place them
+        // before the translations are lost.
+        self.tiles = self
+            .placed_tiles()
+            .iter()
+            .map(|t| Arc::from(g(t.as_ref())))
+            .collect();
+        self.translations.clear();
+
+        // The pattern is repeated along the images of its generators.
+        if let Some(s) = self.shapes.first() {
+            let origin = f(&s).point();
+            self.generators = self
+                .generators
+                .iter()
+                .map(|g| f(&s.clone_at(s.point() + *g)).point() - origin)
+                .collect();
+        }
+
+        let placed = self.placed().collect::<Vec<Placed>>();
+        self.shapes.update(|s| f(&s));
+
+        self.lookup.clear();
+        self.grid.clear();
+        self.graph.clear();
+        self.period = None;
+        let mut fills = HashMap::new();
+        let mut image_offsets = HashMap::new();
+        for p in placed {
+            let shape = f(&p.shape);
+            let (from, to) = (self.key(p.shape.point()), self.key(shape.point()));
+            if let Some(fill) = self.fills.get(&from) {
+                fills.insert(to, *fill);
+            }
+            if let Some(offset) = self.image_offsets.get(&from) {
+                image_offsets.insert(to, *offset);
+            }
+            self.place_copy(p.index, &shape);
+        }
+        self.fills = fills;
+        self.image_offsets = image_offsets;
+        self.observers.notify(Change::Transformed);
+    }
+
+    /// Fills the placed shape (or tile) centered at point with fill instead
+    /// of the fill it shares with the shape it was placed from, so single
+    /// cells of a repeated tiling can be colored, ensuring a cell is centered
+    /// there.
+    pub fn fill_at(&mut self, point: Point, fill: Color) -> Result<()> {
+        let point = match self.period {
+            Some(period) => wrap(point, period),
+            None => point,
+        };
+        let key = self.key(point);
+        self.check_cells(&[CellId(key)])?;
+
+        self.fills.insert(key, fill);
+
+        Ok(())
+    }
+
+    /// Fills the shapes of cells with fill, as `fill_at` fills a single
+    /// shape, ensuring every cell is in the model first.
+    pub fn fill_cells(&mut self, cells: &[CellId], fill: Color) -> Result<()> {
+        self.check_cells(cells)?;
+
+        for CellId(key) in cells.iter() {
+            self.fills.insert(*key, fill);
+        }
+
+        Ok(())
+    }
+
+    /// Ensures each of cells is the cell of a placed shape or tile, as `cells`
+    /// returns them, failing with `EmptyCell` at the center of the first that
+    /// is not.
+    fn check_cells<'a, I>(&self, cells: I) -> Result<()>
+    where
+        I: IntoIterator<Item = &'a CellId>,
+    {
+        // Tiles are placed only when a cell is not a shape's.
+        let mut tiles = None;
+        for CellId(key) in cells {
+            if self.lookup.contains_key(key) {
+                continue;
+            }
+            if !tiles
+                .get_or_insert_with(|| self.tile_cells())
+                .contains_key(key)
+            {
+                let unit = math::powi(10.0, self.precision);
+                return Err(EmptyCell {
+                    x: key.0 as f64 / unit,
+                    y: key.1 as f64 / unit,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fills every placed shape with the color that fill returns for the
+    /// shape's center (in model units), so colors can wash across the tiling,
+    /// such as in a gradient from `Color::mix`.
+    /// Like `fill_at` the fills apply to single cells rather than the shapes
+    /// they were placed from, so call it after the model is repeated; shapes
+    /// placed afterwards keep their own fills.
+    pub fn fill_with<F>(&mut self, fill: F)
+    where
+        F: Fn(Point) -> Color,
+    {
+        for (key, placement) in self.lookup.iter() {
+            self.fills.insert(*key, fill(placement.point));
+        }
+    }
+
+    /// Fills every placed shape with one of the first k colors of
+    /// `swatches::SET2` so that no two shapes sharing an edge have the same
+    /// fill, as `color_with_palette` does.
+    pub fn color_with(&mut self, k: usize, seed: u64) -> Result<()> {
+        self.color_with_palette(k, seed, &crate::swatches::SET2)
+    }
+
+    /// Fills every placed shape with one of the first k colors of palette so
+    /// that no two shapes sharing an edge have the same fill, or returns an
+    /// error if k is 0 or palette has fewer than k colors
+    /// (`InvalidArgument`), if the tiling cannot be colored with k colors
+    /// (`InfeasibleColoring`), or if the search gives up before finding a
+    /// coloring or proving there is none (`UndecidedColoring`).
+    /// Each seed picks a different (but always the same) coloring among the
+    /// many that usually exist. Like `fill_with` the fills apply to single
+    /// cells, so call it after the model is repeated.
+    pub fn color_with_palette(&mut self, k: usize, seed: u64, palette: &Palette) -> Result<()> {
+        if k == 0 || k > palette.colors().len() {
+            return Err(InvalidArgument {
+                name: String::from("k"),
+                reason: format!(
+                    "{} colors asked for, but the palette has {}",
+                    k,
+                    palette.colors().len()
+                ),
+            });
+        }
+
+        let placed = self.placed().collect::<Vec<Placed>>();
+        let neighbors = self.adjacency(placed.iter().map(|p| &p.shape))?;
+        let colors = coloring::color(&neighbors, k, seed)?;
+        for (p, c) in placed.iter().zip(colors) {
+            self.fills
+                .insert(self.key(p.shape.point()), palette.color(c));
+        }
+        self.seed = Some(seed);
+
+        Ok(())
+    }
+
+    /// Shuffles the fills of the placed shapes among the shapes with the
+    /// same number of sides, so a tiling keeps its colors, in the same
+    /// proportions for each kind of shape, but scatters them differently.
+    /// Each seed picks a different (but always the same) shuffle, so many
+    /// variants of one tiling can be made and remade. Like `fill_with` the
+    /// fills apply to single cells, so call it after the model is repeated.
+    pub fn shuffle_fills(&mut self, seed: u64) {
+        let mut cells: BTreeMap<i32, Vec<(Key, Color)>> = BTreeMap::new();
+        for p in self.placed() {
+            let key = self.key(p.shape.point());
+            cells
+                .entry(p.shape.sides())
+                .or_default()
+                .push((key, p.shape.fill()));
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        for cells in cells.values() {
+            let mut fills = cells.iter().map(|(_, fill)| *fill).collect::<Vec<Color>>();
+            fills.shuffle(&mut rng);
+            for ((key, _), fill) in cells.iter().zip(fills) {
+                self.fills.insert(*key, fill);
+            }
+        }
+        self.seed = Some(seed);
+    }
+
+    /// Returns the indexes of the shapes that share an edge with each of
+    /// shapes, sorted.
+    fn adjacency<'a, I>(&self, shapes: I) -> Result<Vec<Vec<usize>>>
+    where
+        I: IntoIterator<Item = &'a Shape>,
+    {
+        let outlines = shapes
+            .into_iter()
+            .map(|s| s.points(0.0))
+            .collect::<Result<Vec<Vec<Point>>>>()?;
+
+        Ok(self.outline_adjacency(&outlines))
+    }
+
+    /// Returns the indexes of the outlines sharing an edge with each of
+    /// outlines, as `adjacency` does for shapes.
+    fn outline_adjacency(&self, outlines: &[Vec<Point>]) -> Vec<Vec<usize>> {
+        let mut edges: HashMap<(Key, Key), Vec<usize>> = HashMap::new();
+        for (i, points) in outlines.iter().enumerate() {
+            for w in points.windows(2) {
+                let (k0, k1) = (self.key(w[0]), self.key(w[1]));
+                edges.entry((k0.min(k1), k0.max(k1))).or_default().push(i);
+            }
+        }
+        let mut neighbors = vec![Vec::new(); outlines.len()];
+        for shapes in edges.values() {
+            for a in shapes.iter() {
+                neighbors[*a].extend(shapes.iter().filter(|b| *b != a));
+            }
+        }
+        for n in neighbors.iter_mut() {
+            n.sort_unstable();
+            n.dedup();
+        }
+
+        neighbors
+    }
+
+    /// Restores the fill of every shape filled by `fill_at`, `fill_with`,
+    /// `color_with`, or `paint_cells`.
+    pub fn clear_fills(&mut self) {
+        self.fills.clear();
+        self.seed = None;
+    }
+
+    /// Returns the placed shape containing the canvas coordinates (x, y) of a
+    /// render, or none if the coordinates fall between shapes.
+    /// Where shapes meet, the shape returned is unspecified.
+    pub fn hit_test(&self, x: f64, y: f64) -> Option<ShapeRef<'_>> {
+        let point = self.canvas_to_model(x, y);
+        let point = match self.period {
+            Some(period) => wrap(point, period),
+            None => point,
+        };
+
+        self.shape_at(point)
+    }
+
+    /// Returns the canvas coordinates (in pixels, from the top left corner of
+    /// a render) of point (in model units), as the renderer places it: the
+    /// model's origin is where `with_origin` anchors it (the center of the
+    /// canvas by default), and each edge length
+    /// spans the model's scale in pixels (stretched slightly in wrap mode, so
+    /// the period spans the canvas).
+    /// Overlays drawn over a render line up with the tiling when placed with
+    /// it.
+    pub fn model_to_canvas(&self, point: Point) -> (f64, f64) {
+        let (width, height, (sx, sy)) = self.pixels();
+        let (x, y) = self.origin.position(width as f64, height as f64);
+
+        (point.x * sx + x, point.y * sy + y)
+    }
+
+    /// Returns the point (in model units) at the canvas coordinates (x, y) of
+    /// a render, undoing `model_to_canvas`.
+    pub fn canvas_to_model(&self, x: f64, y: f64) -> Point {
+        let (width, height, (sx, sy)) = self.pixels();
+        let origin = self.origin.position(width as f64, height as f64);
+
+        Point {
+            x: (x - origin.0) / sx,
+            y: (y - origin.1) / sy,
+        }
+    }
+
+    /// Returns the point (in model units) at the center of the canvas, which
+    /// is the origin unless `with_origin` moves it.
+    fn center(&self) -> Point {
+        let (width, height) = self.size();
+
+        self.canvas_to_model(width as f64 / 2.0, height as f64 / 2.0)
+    }
+
+    /// Returns the cell whose shape contains point (in model units), or none
+    /// if point falls between shapes.
+    pub fn cell_at(&self, point: Point) -> Option<CellId> {
+        let point = match self.period {
+            Some(period) => wrap(point, period),
+            None => point,
+        };
+
+        self.shape_at(point).map(|s| s.cell)
+    }
+
+    /// Returns every cell of the model's (repeated) tiling ordered by the
+    /// centers of their shapes (top to bottom, then left to right).
+    /// The cells of tiles follow the same order, among the cells of shapes.
+    pub fn cells(&self) -> Vec<CellId> {
+        self.centers().into_iter().map(|(k, _)| CellId(k)).collect()
+    }
+
+    /// Returns the key and center of every cell of the model's (repeated)
+    /// tiling, ordered as `cells` orders them.
+    fn centers(&self) -> Vec<(Key, Point)> {
+        let mut centers = self
+            .lookup
+            .iter()
+            .map(|(k, p)| (*k, p.point))
+            .collect::<Vec<(Key, Point)>>();
+        centers.extend(self.tile_cells().into_iter().map(|(k, t)| (k, t.point())));
+        centers.sort_by(|(_, a), (_, b)| (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less));
+
+        centers
+    }
+
+    /// Returns every tile placed in the model's (repeated) tiling keyed by its
+    /// center, leaving out any centered where a shape or another tile is.
+    fn tile_cells(&self) -> HashMap<Key, Box<dyn Tile>> {
+        let mut cells = HashMap::new();
+        for t in self.placed_tiles() {
+            let key = self.key(t.point());
+            if !self.lookup.contains_key(&key) {
+                cells.entry(key).or_insert(t);
+            }
+        }
+
+        cells
+    }
+
+    /// Returns the cells whose shapes share an edge with the shape of cell,
+    /// ordered as `cells` orders them, ensuring cell is in the model.
+    pub fn neighbors(&self, cell: CellId) -> Result<Vec<CellId>> {
+        let graph = self.cell_graph()?;
+        let i = graph.index(cell, self.precision)?;
+
+        Ok(graph.neighbors[i].iter().map(|n| graph.cells[*n]).collect())
+    }
+
+    /// Returns every cell that can be reached from start by crossing the
+    /// edges shapes share, in breadth-first order (nearest first, starting
+    /// with start itself), ensuring start is in the model.
+    pub fn bfs(&self, start: CellId) -> Result<Vec<CellId>> {
+        let graph = self.cell_graph()?;
+        let (cells, neighbors) = (&graph.cells, &graph.neighbors);
+        let start = graph.index(start, self.precision)?;
+
+        let mut visited = vec![false; cells.len()];
+        let mut queue = VecDeque::from([start]);
+        let mut order = Vec::new();
+        visited[start] = true;
+        while let Some(i) = queue.pop_front() {
+            order.push(cells[i]);
+            for n in neighbors[i].iter() {
+                if !visited[*n] {
+                    visited[*n] = true;
+                    queue.push_back(*n);
+                }
+            }
+        }
+
+        Ok(order)
+    }
+
+    /// Returns every cell that can be reached from start by crossing the
+    /// edges shapes share, in depth-first order (each cell before the cells
+    /// reached through it, starting with start itself), ensuring start is in
+    /// the model.
+    /// Neighbors are visited in the order `neighbors` returns them.
+    pub fn dfs(&self, start: CellId) -> Result<Vec<CellId>> {
+        let graph = self.cell_graph()?;
+        let (cells, neighbors) = (&graph.cells, &graph.neighbors);
+        let start = graph.index(start, self.precision)?;
+
+        let mut visited = vec![false; cells.len()];
+        let mut stack = vec![start];
+        let mut order = Vec::new();
+        while let Some(i) = stack.pop() {
+            if visited[i] {
+                continue;
+            }
+
+            visited[i] = true;
+            order.push(cells[i]);
+            stack.extend(neighbors[i].iter().rev().filter(|n| !visited[**n]));
+        }
+
+        Ok(order)
+    }
+
+    /// Returns a shortest path of cells from a to b (both included), each
+    /// sharing an edge with the next, or none if b cannot be reached from a,
+    /// ensuring both cells are in the model.
+    /// Mazes and puzzles can be carved through a tiling along such paths.
+    pub fn path_between(&self, a: CellId, b: CellId) -> Result<Option<Vec<CellId>>> {
+        let graph = self.cell_graph()?;
+        let (cells, neighbors) = (&graph.cells, &graph.neighbors);
+        let (a, b) = (
+            graph.index(a, self.precision)?,
+            graph.index(b, self.precision)?,
+        );
+
+        // Search outward from a, remembering where each cell was reached
+        // from, then walk back from b.
+        let mut previous: Vec<Option<usize>> = vec![None; cells.len()];
+        let mut queue = VecDeque::from([a]);
+        previous[a] = Some(a);
+        while let Some(i) = queue.pop_front() {
+            if i == b {
+                break;
+            }
+            for n in neighbors[i].iter() {
+                if previous[*n].is_none() {
+                    previous[*n] = Some(i);
+                    queue.push_back(*n);
+                }
+            }
+        }
+        if previous[b].is_none() {
+            return Ok(None);
+        }
+
+        let mut path = vec![cells[b]];
+        let mut i = b;
+        while i != a {
+            i = previous[i].unwrap_or(a);
+            path.push(cells[i]);
+        }
+        path.reverse();
+
+        Ok(Some(path))
+    }
+
+    /// Returns a perfect maze carved through the model's (repeated) tiling
+    /// by algorithm, whose cells are the tiling's shapes and whose passages
+    /// cross the edges they share.
+    /// Each seed picks a different (but always the same) maze. Render it with
+    /// `render_maze`.
+    pub fn maze(&self, algorithm: MazeAlgorithm, seed: u64) -> Result<Maze> {
+        let graph = self.cell_graph()?;
+        let outlines = self.cell_outlines(&graph.cells)?;
+
+        Ok(Maze::generate(
+            graph.cells.clone(),
+            &outlines,
+            &graph.neighbors,
+            algorithm,
+            seed,
+            self.precision,
+        ))
+    }
+
+    /// Returns the model's cell graph, building it only if the model's shapes
+    /// were placed since it was last built.
+    fn cell_graph(&self) -> Result<Arc<CellGraph>> {
+        self.graph.get_or_build(|| {
+            let cells = self.cells();
+            let neighbors = self.outline_adjacency(&self.cell_outlines(&cells)?);
+            let indexes = cells.iter().enumerate().map(|(i, c)| (*c, i)).collect();
+
+            Ok(CellGraph {
+                cells,
+                indexes,
+                neighbors,
+            })
+        })
+    }
+
+    /// Returns the outlines of the shapes and tiles of cells.
+    fn cell_outlines(&self, cells: &[CellId]) -> Result<Vec<Vec<Point>>> {
+        let tiles = self.tile_cells();
+        cells
+            .iter()
+            .map(|c| match self.lookup.get(&c.0) {
+                Some(p) => self.resolve(p).points(0.0),
+                None => tiles[&c.0].points(0.0),
+            })
+            .collect()
+    }
+
+    /// Returns a lazy iterator over the shapes of the model's tiling, repeated
+    /// without end, that may overlap rect (in model units), whether or not
+    /// they are on the model's canvas, so map renderers can stream the tiles
+    /// of any region without changing the model.
+    /// The tiling is repeated along the lattice of its `repeat` (or
+    /// `repeat_by`), or by its period in wrap mode; a model that was never
+    /// repeated yields only its placed shapes. Shapes on the canvas are the
+    /// shapes the model placed, with their fills; hidden shapes are included.
+    pub fn placements(&self, rect: Rect) -> Placements<'_> {
+        let placed = self
+            .stored()
+            .map(|p| Placement::new(p.index, &p.shape, p.shape.point()));
+        let (basis, pattern) = match (self.period, self.lattice_basis()) {
+            (Some(period), _) => (
+                (
+                    Point {
+                        x: period.x,
+                        y: 0.0,
+                    },
+                    Point {
+                        x: 0.0,
+                        y: period.y,
+                    },
+                ),
+                placed.collect::<Vec<Placement>>(),
+            ),
+            (None, Some((b1, b2))) => {
+                // Keep one shape of each orbit of the pattern under the
+                // lattice, identified by its center reduced to the lattice's
+                // fundamental parallelogram.
+                let mut orbits = HashSet::new();
+                let pattern = self
+                    .shapes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, s)| {
+                        let (u, v) = coordinates(s.point(), (b1, b2), self.precision);
+                        let reduced = b1 * (u - u.floor()) + b2 * (v - v.floor());
+                        orbits.insert((self.key(reduced), s.sides()))
+                    })
+                    .map(|(i, s)| Placement::new(i, &s, s.point()))
+                    .collect();
+                ((b1, b2), pattern)
+            }
+            (None, None) => (
+                (Point::origin(), Point::origin()),
+                placed.collect::<Vec<Placement>>(),
+            ),
+        };
+
+        let radius = pattern
+            .iter()
+            .map(|p| self.shapes.at(p.index).circumradius())
+            .fold(0.0, f64::max);
+        let (columns, rows) = if basis.0.x == 0.0 && basis.0.y == 0.0 {
+            ((0, 0), (0, 0))
+        } else {
+            // The lattice points whose translations of the pattern may reach
+            // rect: rect grown by the pattern's extent, in lattice
+            // coordinates.
+            let (x, y) = (rect.origin().x, rect.origin().y);
+            let (mut min, mut max) = (
+                Point { x, y },
+                Point {
+                    x: x + rect.width(),
+                    y: y + rect.height(),
+                },
+            );
+            for p in pattern.iter() {
+                min.x = min.x.min(x - p.point.x - radius);
+                min.y = min.y.min(y - p.point.y - radius);
+                max.x = max.x.max(x + rect.width() - p.point.x + radius);
+                max.y = max.y.max(y + rect.height() - p.point.y + radius);
+            }
+            let corners = [
+                min,
+                Point { x: max.x, y: min.y },
+                Point { x: min.x, y: max.y },
+                max,
+            ]
+            .map(|c| coordinates(c, basis, self.precision));
+            let range = |f: fn(&(f64, f64)) -> f64| {
+                let values = corners.iter().map(f);
+                (
+                    values.clone().fold(f64::INFINITY, f64::min).floor() as i64,
+                    values.fold(f64::NEG_INFINITY, f64::max).ceil() as i64,
+                )
+            };
+            (range(|c| c.0), range(|c| c.1))
+        };
+
+        Placements {
+            model: self,
+            rect,
+            basis,
+            pattern,
+            radius,
+            columns,
+            rows,
+            position: (columns.0, rows.0, 0),
+        }
+    }
+
+    /// Returns a basis of the lattice of translations that the model's
+    /// pattern was repeated along, or none if the model was not repeated (or
+    /// its translations do not form a lattice).
+    /// The lattice's two shortest independent vectors are a basis, so they
+    /// are found among the translations near the origin.
+    fn lattice_basis(&self) -> Option<(Point, Point)> {
+        let reach = 4.0
+            * self
+                .generators
+                .iter()
+                .map(|g| math::hypot(g.x, g.y))
+                .fold(0.0, f64::max);
+        let origin = Point::origin();
+        let mut seen = HashSet::from([self.key(origin)]);
+        let (mut queue, mut points) = (vec![origin], Vec::new());
+        while let Some(p) = queue.pop() {
+            for g in self.generators.iter() {
+                for q in [p + *g, p - *g] {
+                    if math::hypot(q.x, q.y) <= reach
+                        && points.len() < MAX_LATTICE_POINTS
+                        && seen.insert(self.key(q))
+                    {
+                        points.push(q);
+                        queue.push(q);
+                    }
+                }
+            }
+        }
+
+        let length = |p: &Point| math::hypot(p.x, p.y);
+        let shortest = |points: &mut dyn Iterator<Item = &Point>| {
+            points
+                .min_by(|a, b| length(a).partial_cmp(&length(b)).unwrap_or(Less))
+                .copied()
+        };
+        let b1 = shortest(&mut points.iter())?;
+        let b2 = shortest(&mut points.iter().filter(|p| {
+            (b1.x * p.y - b1.y * p.x).abs() > shape::EPSILON * length(&b1) * length(p)
+        }))?;
+        // Translations closer than the shapes themselves are not a lattice of
+        // the tiling, and would be too dense to visit.
+        if length(&b1) < shape::EPSILON.sqrt() {
+            return None;
+        }
+
+        Some((b1, b2))
+    }
+
+    /// Fills each cell in values with the color of colormap for its value,
+    /// scaling the values so the smallest takes the colormap's first color
+    /// and the largest its last, ensuring every cell is in the model first.
+    /// Cells without a value keep their fill, as do cells whose values are not
+    /// finite.
+    pub fn paint_cells(
+        &mut self,
+        values: &HashMap<CellId, f64>,
+        colormap: &Colormap,
+    ) -> Result<()> {
+        let finite = values.values().filter(|v| v.is_finite());
+        let min = finite.clone().fold(f64::INFINITY, |a, b| a.min(*b));
+        let max = finite.fold(f64::NEG_INFINITY, |a, b| a.max(*b));
+
+        self.check_cells(values.keys())?;
+
+        for (CellId(key), value) in values.iter().filter(|(_, v)| v.is_finite()) {
+            let t = if max > min {
+                (value - min) / (max - min)
+            } else {
+                0.5
+            };
+            self.fills.insert(*key, colormap.color(t));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the parameters of the model that its renders record: its
+    /// canvas, scale, and resolution, the number of shapes it places, its
+    /// notation, the seed it was last colored with, its geometry hash, and
+    /// the operations that built it.
+    pub fn metadata(&self) -> Result<Metadata> {
+        Ok(Metadata {
+            width: self.width,
+            height: self.height,
+            scale: self.scale,
+            dpi: self.dpi,
+            shapes: self.repeated_len(),
+            notation: self.notation()?,
+            seed: self.seed,
+            hash: self.geometry_hash()?,
+            steps: self.steps(),
+        })
+    }
+
+    /// Returns the operations that built the model, as their calls: those
+    /// loaded from the file it was saved to, if it was loaded, and then its
+    /// history.
+    fn steps(&self) -> Vec<String> {
+        self.saved_steps
+            .iter()
+            .cloned()
+            .chain(self.history().iter().map(|o| o.to_string()))
+            .collect()
+    }
+
+    /// Returns a hash of the geometry of the model's (repeated) tiling: the
+    /// number of sides, center, and vertices of every placed shape, snapped to
+    /// the model's precision.
+    /// Unlike a render's `content_hash` it ignores colors and is independent
+    /// of cairo, and it is the same with every version of Rust, so snapshot
+    /// tests can compare it to a stored value to detect unintended changes to
+    /// a tiling's geometry.
+    /// Platforms' math libraries may round trigonometry differently, so build
+    /// with the `deterministic` feature to get the same hash on every
+    /// platform.
+    pub fn geometry_hash(&self) -> Result<u64> {
+        let mut shapes = Vec::with_capacity(self.lookup.len());
+        for p in self.placed() {
+            let points = p.shape.points(0.0)?;
+            let mut vertices = points[0..points.len() - 1]
+                .iter()
+                .map(|v| self.key(*v))
+                .collect::<Vec<Key>>();
+            vertices.sort();
+            shapes.push((self.key(p.shape.point()), p.shape.sides(), vertices));
+        }
+        // Placements are unordered, so sort them to hash them in a fixed order.
+        shapes.sort();
+
+        let mut hash = Fnv::new();
+        hash.write_i64(self.precision as i64);
+        for (center, sides, vertices) in shapes {
+            hash.write_i64(sides as i64);
+            for Key(x, y) in std::iter::once(center).chain(vertices) {
+                hash.write_i64(x);
+                hash.write_i64(y);
+            }
+        }
+
+        Ok(hash.finish())
+    }
+
+    /// Saves the model to a tiling file at path: a versioned RON (Rusty
+    /// Object Notation) file that captures the model's parameters, interned
+    /// styles, shapes, names, edge styles and deformed edges, fills and
+    /// overlays, style sets, border, and the placement of every shape in its
+    /// (repeated) tiling, so `load` restores the tiling without repeating it
+    /// again, renders it as it was, and `StyleId`s interned before saving
+    /// refer to the same styles after loading. Its metadata is kept too, with
+    /// the steps that built the model, though not its undo history or
+    /// observers.
+    /// Unlike serializing the model's fields, the format is stable: fields
+    /// added by later versions of the crate are skipped by earlier ones, and
+    /// fields a file leaves out keep their defaults, so files load across
+    /// crate versions.
+    /// Tiles are saved as the rings outlining every placed tile and their
+    /// colors, so they load as polygons that trace them, without the
+    /// corner styles, curves, or matching rules their own types add.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let unit = |name: &str| file::Value::Unit(name.to_string());
+        let variant =
+            |name: &str, items: Vec<file::Value>| file::Value::Variant(name.to_string(), items);
+        let canvas = file::Fields::anonymous()
+            .with("width", self.width)
+            .with("height", self.height)
+            .with("scale", self.scale);
+        let duplicates = unit(match self.duplicates {
+            DuplicatePolicy::Allow => "Allow",
+            DuplicatePolicy::Reject => "Reject",
+            DuplicatePolicy::Skip => "Skip",
+            DuplicatePolicy::Replace => "Replace",
+        });
+        let numbering = unit(match self.numbering {
+            EdgeNumbering::Parent => "Parent",
+            EdgeNumbering::North => "North",
+        });
+        let dual_boundary = match self.dual_boundary {
+            DualBoundary::Discard => unit("Discard"),
+            DualBoundary::Clip => unit("Clip"),
+            DualBoundary::Extend(belt) => variant("Extend", vec![belt.into()]),
+        };
+        let origin = match self.origin {
+            Origin::Center => unit("Center"),
+            Origin::TopLeft => unit("TopLeft"),
+            Origin::Top => unit("Top"),
+            Origin::TopRight => unit("TopRight"),
+            Origin::Left => unit("Left"),
+            Origin::Right => unit("Right"),
+            Origin::BottomLeft => unit("BottomLeft"),
+            Origin::Bottom => unit("Bottom"),
+            Origin::BottomRight => unit("BottomRight"),
+            Origin::At(x, y) => variant("At", vec![x.into(), y.into()]),
+        };
+        let coverage = self.coverage.map(|c| {
+            file::Fields::anonymous()
+                .with("x", c.origin().x)
+                .with("y", c.origin().y)
+                .with("width", c.width())
+                .with("height", c.height())
+        });
+        let border = self.border.map(|(thickness, color)| {
+            file::Fields::anonymous()
+                .with("thickness", thickness)
+                .with("color", color)
+        });
+
+        // Styles are written in the order they were interned, ahead of the
+        // shapes, so loading interns them again under the same `StyleId`s.
+        let styles = self
+            .shapes
+            .stylings()
+            .iter()
+            .map(|styling| {
+                let style = Shape::styled(shape::MIN_SIDES, Point::origin(), 0.0, styling);
+                file::write_style(&style)
+            })
+            .collect::<Vec<file::Fields>>();
+        let shapes = self
+            .shapes
+            .iter()
+            .map(|s| file::write_shape(&s))
+            .collect::<Vec<file::Fields>>();
+        let mut hidden = self.hidden.iter().copied().collect::<Vec<usize>>();
+        hidden.sort_unstable();
+        let mut edges = self
+            .edge_styles
+            .iter()
+            .flat_map(|(index, edges)| {
+                edges
+                    .iter()
+                    .map(move |(edge, style)| (*index, *edge, style))
+            })
+            .collect::<Vec<(usize, usize, &EdgeStyle)>>();
+        edges.sort_by_key(|(index, edge, _)| (*index, *edge));
+        let edges = edges
+            .into_iter()
+            .map(|(index, edge, style)| file::write_edge(index, edge, style))
+            .collect::<Vec<file::Fields>>();
+        let tiles = self
+            .placed_tiles()
+            .iter()
+            .map(|t| file::write_tile(t.as_ref()))
+            .collect::<Result<Vec<file::Fields>>>()?;
+        let style_sets = self
+            .style_sets
+            .iter()
+            .map(|(name, set)| file::write_style_set(name, set))
+            .collect::<Vec<file::Fields>>();
+        let shape_names = self
+            .shape_names
+            .iter()
+            .map(|(name, index)| {
+                file::Fields::anonymous()
+                    .with("shape", *index)
+                    .with("name", name.as_str())
+            })
+            .collect::<Vec<file::Fields>>();
+        let mut edge_names = self
+            .edge_names
+            .iter()
+            .flat_map(|(index, names)| names.iter().map(move |(name, edge)| (*index, *edge, name)))
+            .collect::<Vec<(usize, usize, &String)>>();
+        edge_names.sort();
+        let edge_names = edge_names
+            .into_iter()
+            .map(|(index, edge, name)| {
+                file::Fields::anonymous()
+                    .with("shape", index)
+                    .with("edge", edge)
+                    .with("name", name.as_str())
+            })
+            .collect::<Vec<file::Fields>>();
+        let mut edge_paths = self
+            .edge_paths
+            .iter()
+            .flat_map(|(index, paths)| paths.iter().map(move |(edge, path)| (*index, *edge, path)))
+            .collect::<Vec<(usize, usize, &Vec<Point>)>>();
+        edge_paths.sort_by_key(|(index, edge, _)| (*index, *edge));
+        let edge_paths = edge_paths
+            .into_iter()
+            .map(|(index, edge, path)| {
+                file::Fields::anonymous()
+                    .with("shape", index)
+                    .with("edge", edge)
+                    .with("path", path.clone())
+            })
+            .collect::<Vec<file::Fields>>();
+        let mut overlays = self.overlays.iter().collect::<Vec<(&usize, &Fill)>>();
+        overlays.sort_by_key(|(index, _)| **index);
+        let overlays = overlays
+            .into_iter()
+            .map(|(index, fill)| file::write_overlay(*index, fill))
+            .collect::<Vec<file::Fields>>();
+        let placements = self
+            .lookup
+            .iter()
+            .map(|(key, p)| {
+                file::Fields::anonymous()
+                    .with("shape", p.index)
+                    .with("x", p.point.x)
+                    .with("y", p.point.y)
+                    .with("rotation", p.rotation)
+                    .with_some("fill", self.fills.get(key).copied())
+                    .with_some("image_offset", self.image_offsets.get(key).copied())
+            })
+            .collect::<Vec<file::Fields>>();
+
+        let tiling = file::Fields::new(file::MAGIC)
+            .with("version", file::VERSION)
+            .with("canvas", canvas)
+            .with("precision", self.precision)
+            .with("duplicates", duplicates)
+            .with("numbering", numbering)
+            .with("wrap", self.wrap)
+            .with("lazy", self.lazy)
+            .with_some("period", self.period)
+            .with("generators", self.generators.clone())
+            .with_some("dpi", self.dpi)
+            .with_some("snap", self.snap)
+            .with("max_shapes", self.max_shapes)
+            .with("strict", self.strict)
+            .with("dual_boundary", dual_boundary)
+            .with("origin", origin)
+            .with_some("coverage", coverage)
+            .with_some("border", border)
+            .with_some("seed", self.seed)
+            .with("steps", self.steps())
+            .with("styles", styles)
+            .with("shapes", shapes)
+            .with("hidden", hidden)
+            .with("edges", edges)
+            .with("tiles", tiles)
+            .with("style_sets", style_sets)
+            .with("shape_names", shape_names)
+            .with("edge_names", edge_names)
+            .with("edge_paths", edge_paths)
+            .with("overlays", overlays)
+            .with("placements", placements);
+        let mut out = String::new();
+        file::Value::from(tiling).write(&mut out, 0);
+        out.push('\n');
+        fs::write(path, out)?;
+
+        Ok(())
+    }
+
+    /// Loads a model from a tiling file at path written by `save`.
+    /// Fields the crate does not know are skipped, but a file written in a
+    /// later version of the format fails with `Error::InvalidTilingFile`. If
+    /// the file places no shapes, each shape is placed where it is centered,
+    /// as if it had been added.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Model> {
+        let text = fs::read_to_string(path)?;
+        let tiling = match file::parse(&text)? {
+            file::Value::Struct(tiling) if tiling.name() == Some(file::MAGIC) => tiling,
+            _ => {
+                return Err(InvalidTilingFile {
+                    line: 1,
+                    reason: String::from("the file is not a tiling file"),
+                })
+            }
+        };
+        let version = tiling.get::<u32>("version")?;
+        if version > file::VERSION {
+            return Err(tiling.error(format!(
+                "version {} is newer than the supported version {}",
+                version,
+                file::VERSION
+            )));
+        }
+
+        let canvas = tiling.get::<file::Fields>("canvas")?;
+        let mut model = Model::new(
+            canvas.get("width")?,
+            canvas.get("height")?,
+            canvas.get("scale")?,
+        );
+        model.precision = tiling.get_or("precision", model.precision)?;
+        model.duplicates = match tiling.variant("duplicates")? {
+            Some(("Allow", [])) | None => DuplicatePolicy::Allow,
+            Some(("Reject", [])) => DuplicatePolicy::Reject,
+            Some(("Skip", [])) => DuplicatePolicy::Skip,
+            Some(("Replace", [])) => DuplicatePolicy::Replace,
+            Some((d, _)) => return Err(tiling.error(format!("invalid duplicate policy {:?}", d))),
+        };
+        model.numbering = match tiling.variant("numbering")? {
+            Some(("Parent", [])) | None => EdgeNumbering::Parent,
+            Some(("North", [])) => EdgeNumbering::North,
+            Some((n, _)) => return Err(tiling.error(format!("invalid edge numbering {:?}", n))),
+        };
+        model.wrap = tiling.get_or("wrap", false)?;
+        model.lazy = tiling.get_or("lazy", false)?;
+        model.period = tiling.optional("period")?;
+        model.generators = tiling.get_or("generators", Vec::new())?;
+        model.dpi = tiling.optional("dpi")?;
+        model.snap = tiling.optional("snap")?;
+        model.max_shapes = tiling.get_or("max_shapes", model.max_shapes)?;
+        model.strict = tiling.get_or("strict", false)?;
+        let invalid = |field: &str, items: &[file::Value]| {
+            tiling.error(format!("invalid {} {:?}", field, items))
+        };
+        model.dual_boundary = match tiling.variant("dual_boundary")? {
+            Some(("Discard", [])) | None => DualBoundary::Discard,
+            Some(("Clip", [])) => DualBoundary::Clip,
+            Some(("Extend", [belt])) => DualBoundary::Extend(
+                file::FromValue::from_value(belt)
+                    .ok_or_else(|| invalid("dual_boundary", std::slice::from_ref(belt)))?,
+            ),
+            Some((b, _)) => return Err(tiling.error(format!("invalid dual boundary {:?}", b))),
+        };
+        model.origin = match tiling.variant("origin")? {
+            Some(("Center", [])) | None => Origin::Center,
+            Some(("TopLeft", [])) => Origin::TopLeft,
+            Some(("Top", [])) => Origin::Top,
+            Some(("TopRight", [])) => Origin::TopRight,
+            Some(("Left", [])) => Origin::Left,
+            Some(("Right", [])) => Origin::Right,
+            Some(("BottomLeft", [])) => Origin::BottomLeft,
+            Some(("Bottom", [])) => Origin::Bottom,
+            Some(("BottomRight", [])) => Origin::BottomRight,
+            Some(("At", at)) => {
+                let at: Point = file::FromValue::from_value(&file::Value::Tuple(at.to_vec()))
+                    .ok_or_else(|| invalid("origin", at))?;
+                Origin::At(at.x, at.y)
+            }
+            Some((o, _)) => return Err(tiling.error(format!("invalid origin {:?}", o))),
+        };
+        if let Some(c) = tiling.optional::<file::Fields>("coverage")? {
+            let (x, y) = (c.get("x")?, c.get("y")?);
+            model.coverage = Some(Rect::new(x, y, c.get("width")?, c.get("height")?));
+        }
+        if let Some(border) = tiling.optional::<file::Fields>("border")? {
+            model.border(border.get("thickness")?, border.get("color")?);
+        }
+        model.seed = tiling.optional("seed")?;
+        model.saved_steps = tiling.get_or("steps", Vec::new())?;
+
+        let structs = |field: &str| tiling.get_or::<Vec<file::Fields>>(field, Vec::new());
+        for s in structs("styles")? {
+            model.intern_style(&file::read_style(&s)?);
+        }
+        for s in structs("shapes")? {
+            model.shapes.push(&file::read_shape(&s)?);
+        }
+        for index in tiling.get_or::<Vec<usize>>("hidden", Vec::new())? {
+            model
+                .hide(index..index + 1)
+                .map_err(|err| tiling.error(err.to_string()))?;
+        }
+        for e in structs("edges")? {
+            let (index, edge, style) = file::read_edge(&e)?;
+            model
+                .style_edge(index, edge, style)
+                .map_err(|err| e.error(err.to_string()))?;
+        }
+        for t in structs("tiles")? {
+            model.tiles.push(Arc::new(file::read_tile(&t)?));
+        }
+        for s in structs("style_sets")? {
+            let (name, set) = file::read_style_set(&s)?;
+            model.style_sets.insert(name, set);
+        }
+        for n in structs("shape_names")? {
+            model
+                .name_shape(n.get("shape")?, &n.get::<String>("name")?)
+                .map_err(|err| n.error(err.to_string()))?;
+        }
+        for n in structs("edge_names")? {
+            model
+                .name_edge(n.get("shape")?, n.get("edge")?, &n.get::<String>("name")?)
+                .map_err(|err| n.error(err.to_string()))?;
+        }
+        let defined = |fields: &file::Fields, index: usize| {
+            if index < model.shapes.len() {
+                Ok(index)
+            } else {
+                Err(fields.error(format!("shape {} has not been defined", index)))
+            }
+        };
+        let mut edge_paths = Vec::new();
+        for p in structs("edge_paths")? {
+            let path = p.get::<Vec<Point>>("path")?;
+            if path.is_empty() {
+                return Err(p.error(String::from("edge path has no points")));
+            }
+            edge_paths.push((defined(&p, p.get("shape")?)?, p.get("edge")?, path));
+        }
+        let mut overlays = Vec::new();
+        for o in structs("overlays")? {
+            let (index, fill) = file::read_overlay(&o)?;
+            overlays.push((defined(&o, index)?, fill));
+        }
+        let mut places = Vec::new();
+        for p in structs("placements")? {
+            let point = Point {
+                x: p.get("x")?,
+                y: p.get("y")?,
+            };
+            let fill = p.optional::<Color>("fill")?;
+            let offset = p.optional::<Point>("image_offset")?;
+            places.push((
+                defined(&p, p.get("shape")?)?,
+                point,
+                p.get("rotation")?,
+                fill,
+                offset,
+            ));
+        }
+        for (index, edge, path) in edge_paths {
+            model
+                .edge_paths
+                .entry(index)
+                .or_default()
+                .insert(edge, path);
+        }
+        model.overlays.extend(overlays);
+
+        if places.is_empty() {
+            places = model
+                .shapes
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (i, s.point(), s.rotation(), None, None))
+                .collect();
+        }
+        for (index, point, rotation, fill, offset) in places {
+            let shape = model.shapes.at(index).placed_at(point, rotation);
+            model.place_copy(index, &shape);
+            let key = model.key(point);
+            if !model.lookup.contains_key(&key) {
+                continue;
+            }
+            if let Some(fill) = fill {
+                model.fills.insert(key, fill);
+            }
+            if let Some(offset) = offset {
+                model.image_offsets.insert(key, offset);
+            }
+        }
+
+        Ok(model)
+    }
+
+    /// Returns the placed shape containing point (in model units), which lies
+    /// within the period in wrap mode.
+    fn shape_at(&self, point: Point) -> Option<ShapeRef<'_>> {
+        // In wrap mode the point may lie in a copy of a shape that was placed
+        // in a neighboring period.
+        self.offsets().into_iter().find_map(|offset| {
+            let point = point + offset;
+            self.grid
+                .candidates(point)
+                .iter()
+                .filter_map(|c| self.lookup.get(c).map(|p| (*c, p)))
+                .map(|(c, p)| (c, p.index, self.resolve(p)))
+                .find(|(_, _, s)| s.contains(point))
+                .map(|(cell, index, shape)| ShapeRef {
+                    index,
+                    cell: CellId(cell),
+                    shape,
+                    model: PhantomData,
+                })
+        })
+    }
+
+    /// Returns every shape placed in the model's (repeated) tiling, in a fixed
+    /// order, generated from its lattice over the canvas if its repeats are
+    /// lazy.
+    fn placed(&self) -> impl Iterator<Item = Placed> + '_ {
+        let generated = match self.is_lazy() {
+            true => Some(self.generated(&[self.canvas_rect()])),
+            false => None,
+        };
+        let stored = match generated {
+            Some(_) => None,
+            None => Some(self.stored()),
+        };
+
+        stored
+            .into_iter()
+            .flatten()
+            .chain(generated.into_iter().flatten())
+    }
+
+    /// Returns every shape stored in the model's (repeated) tiling, in a fixed
+    /// order.
+    /// Placements are kept ordered by key, so shapes are always drawn in the
+    /// same order, and renders of the same model are identical.
+    fn stored(&self) -> impl Iterator<Item = Placed> + '_ {
+        self.lookup.values().map(move |p| Placed {
+            index: p.index,
+            shape: self.resolve(p),
+        })
+    }
+
+    /// Returns whether the model's repeats are generated from its lattice
+    /// rather than placed.
+    fn is_lazy(&self) -> bool {
+        self.lazy && self.period.is_none() && !self.generators.is_empty()
+    }
+
+    /// Returns the shapes of the model's tiling generated from its lattice
+    /// that may overlap a rectangle of rects, each once, in a fixed order.
+    fn generated(&self, rects: &[Rect]) -> Vec<Placed> {
+        let mut seen = HashSet::new();
+        rects
+            .iter()
+            .flat_map(|rect| self.placements(*rect))
+            .filter(|s| seen.insert(s.cell))
+            .map(|s| Placed {
+                index: s.index,
+                shape: s.shape,
+            })
+            .collect()
+    }
+
+    /// Returns every tile placed in the model's (repeated) tiling, in a fixed
+    /// order, filled as its cell is if it was filled on its own.
+    /// Repeats of tiles share the translations of repeats of shapes, which
+    /// in wrap mode are wrapped into the period tile by tile.
+    fn placed_tiles(&self) -> Vec<Box<dyn Tile>> {
+        if self.tiles.is_empty() {
+            return Vec::new();
+        }
+
+        let origin = Point::origin();
+        let mut translations = self.translations.clone();
+        translations.entry(self.key(origin)).or_insert(origin);
+        let mut translations = translations.into_iter().collect::<Vec<(Key, Point)>>();
+        translations.sort_by_key(|(k, _)| *k);
+
+        let mut tiles = Vec::new();
+        for (_, t) in translations {
+            for tile in self.tiles.iter() {
+                let vector = match self.period {
+                    Some(period) => {
+                        let p = wrap(t + tile.point(), period);
+                        Point {
+                            x: p.x - tile.point().x,
+                            y: p.y - tile.point().y,
+                        }
+                    }
+                    None => t,
+                };
+                let tile = tile.translated(vector);
+                // A fill keyed where a shape is centered is the shape's.
+                let key = self.key(tile.point());
+                match self.fills.get(&key) {
+                    Some(fill) if !self.lookup.contains_key(&key) => tiles.push(tile.filled(*fill)),
+                    _ => tiles.push(tile),
+                }
+            }
+        }
+
+        tiles
+    }
+
+    /// Returns every shape placed in the model's (repeated) tiling, along with
+    /// its copies in each neighboring period in wrap mode.
+    fn tiled(&self) -> Vec<Placed> {
+        let placed = self.placed().collect::<Vec<Placed>>();
+        let mut placements = Vec::new();
+        for offset in self.offsets() {
+            for p in placed.iter() {
+                placements.push(Placed {
+                    index: p.index,
+                    shape: p.shape.clone_at(p.shape.point() + offset),
+                });
+            }
+        }
+
+        placements
+    }
+
+    /// Returns whether placed is the model shape it was placed from rather
+    /// than a repeat of it.
+    #[cfg(feature = "cairo")]
+    fn is_original(&self, placed: &Placed) -> bool {
+        self.shapes.point(placed.index).key(self.precision)
+            == placed.shape.point().key(self.precision)
+    }
+
+    /// Returns the shapes of `tiled` that are not hidden.
+    fn visible(&self) -> Vec<Placed> {
+        let mut placements = self.tiled();
+        placements.retain(|p| !self.hidden.contains(&p.index));
+
+        placements
+    }
+
+    /// Returns the offset of each period that is drawn when rendering: the
+    /// period itself and, in wrap mode, each of its neighbors.
+    fn offsets(&self) -> Vec<Point> {
+        let period = match self.period {
+            Some(period) => period,
+            None => return vec![Point::origin()],
+        };
+
+        let mut offsets = Vec::new();
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                offsets.push(Point {
+                    x: dx as f64 * period.x,
+                    y: dy as f64 * period.y,
+                });
+            }
+        }
+
+        offsets
+    }
+
+    /// Returns a blank canvas for rendering the model.
+    /// In wrap mode the canvas is sized to the model's period, stretched by
+    /// less than a pixel so that the period spans a whole number of pixels.
+    #[cfg(feature = "cairo")]
+    fn canvas(&self, options: &RenderOptions) -> Result<(cairo::ImageSurface, cairo::Context)> {
+        let (width, height) = self.size();
+        let (surface, context) = render::canvas(width, height, self.scale, options)?;
+        self.fit_period(&context);
+
+        Ok((surface, context))
+    }
+
+    /// Stretches context, prepared for a canvas of the model's size, so the
+    /// model's period fills it in wrap mode, and moves the origin from the
+    /// canvas' center to where the model anchors it.
+    #[cfg(feature = "cairo")]
+    fn fit_period(&self, context: &cairo::Context) {
+        let (width, height) = self.size();
+        if let Some(period) = self.period {
+            context.scale(
+                width as f64 / (period.x * self.scale),
+                height as f64 / (period.y * self.scale),
+            );
+        }
+        let center = self.center();
+        context.translate(-center.x, -center.y);
+    }
+
+    /// Returns a context for drawing over render, a render of the model's
+    /// whole canvas such as `render_with` makes, in model units: the origin
+    /// and scale are those the model's shapes were drawn with, so extra
+    /// geometry lines up with the tiling.
+    #[cfg(feature = "cairo")]
+    pub fn context_for(&self, render: &Render) -> Result<cairo::Context> {
+        let context = render.context()?;
+        let (width, height) = self.size();
+        context.translate(width as f64 / 2.0, height as f64 / 2.0);
+        context.scale(self.scale, self.scale);
+        self.fit_period(&context);
+
+        Ok(context)
+    }
+
+    /// Returns the render of surface, prepared by `canvas`, as `render::finish`
+    /// does, with the model's resolution and, if options ask for it, its
+    /// metadata.
+    #[cfg(feature = "cairo")]
+    fn finish(&self, surface: cairo::ImageSurface, options: &RenderOptions) -> Result<Render> {
+        let render = render::finish(surface, options)?.with_dpi(self.dpi);
+
+        match options.metadata {
+            true => Ok(render.with_metadata(self.metadata()?)),
+            false => Ok(render),
+        }
+    }
+
+    /// Returns the bounding box (in model units) of the shape with index as it
+    /// is drawn as configured by options: moved by its transform and along
+    /// its deformed edges, and grown by its widest stroke or outline.
+    #[cfg(feature = "cairo")]
+    fn drawn_bounds(&self, index: usize, shape: &Shape, options: &RenderOptions) -> Result<Rect> {
+        let outline = shape
+            .outline()
+            .or(options.outline)
+            .map_or(0.0, |o| o.width());
+        let pad = options.line_width.max(outline);
+        let paths = self.edge_paths.get(&index);
+        if shape.transform().is_none() && paths.is_none() {
+            let (c, r) = (shape.point(), shape.circumradius() + pad);
+            return Ok(Rect::new(c.x - r, c.y - r, 2.0 * r, 2.0 * r));
+        }
+
+        let no_paths = HashMap::new();
+        let points = shape
+            .traced_edges(0.0, paths.unwrap_or(&no_paths))?
+            .concat();
+        let (mut x0, mut y0) = (f64::INFINITY, f64::INFINITY);
+        let (mut x1, mut y1) = (f64::NEG_INFINITY, f64::NEG_INFINITY);
+        for p in points.iter() {
+            x0 = x0.min(p.x);
+            y0 = y0.min(p.y);
+            x1 = x1.max(p.x);
+            y1 = y1.max(p.y);
+        }
+
+        Ok(Rect::new(
+            x0 - pad,
+            y0 - pad,
+            x1 - x0 + 2.0 * pad,
+            y1 - y0 + 2.0 * pad,
+        ))
+    }
+
+    /// Returns the width and height (in pixels) of the model's renders and
+    /// the number of pixels per edge length along each axis.
+    fn pixels(&self) -> (i32, i32, (f64, f64)) {
+        let (width, height) = self.size();
+        let scale = match self.period {
+            Some(period) => (width as f64 / period.x, height as f64 / period.y),
+            None => (self.scale, self.scale),
+        };
+
+        (width, height, scale)
+    }
+
+    /// Returns the width and height (in pixels) of the model's renders.
+    /// In wrap mode the size is that of the model's period, rounded to whole
+    /// pixels.
+    fn size(&self) -> (i32, i32) {
+        match self.period {
+            Some(period) => (
+                (period.x * self.scale).round().max(1.0) as i32,
+                (period.y * self.scale).round().max(1.0) as i32,
+            ),
+            None => (self.width, self.height),
+        }
+    }
+
+    /// Returns the model's (repeated) tiling as an SVG document that can be
+    /// restyled in a vector editor.
+    /// Shapes with the same number of sides are grouped into a layer with the
+    /// id "layer-{sides}" and a class naming the shapes (e.g. "hexagons").
+    /// Each shape is a polygon with the id "shape-{index}", numbered by
+    /// position (top to bottom, then left to right), and the classes "shape"
+    /// and the shape's name (e.g. "hexagon").
+    /// The background, margin, stroke style, corner style, and paint mode of
+    /// options are applied; edge styles, labels, and hooks are not. The
+    /// model's tiles are polygons (or, with holes, paths) in a layer with the
+    /// id "tiles", each with the id "tile-{index}" numbered by position and
+    /// the class "tile", in their own colors. The model's border, if it has
+    /// one, is a group with the id "border".
+    pub fn to_svg(&self, options: &RenderOptions) -> Result<String> {
+        let shapes = self.svg_shapes(options, None)?;
+        let tiles = self.svg_tiles(options)?;
+
+        self.svg_document(&shapes, &tiles, &[], options)
+    }
+
+    /// Returns the model's dual tiling as an SVG document whose cells are
+    /// polygons in a layer with the id "dual".
+    /// Each cell has the id "dual-{vertex}" and a "data-vertex" attribute
+    /// naming the vertex of the tiling it was made from, with vertices
+    /// numbered by position (top to bottom, then left to right).
+    /// Cells too small for margin are left out.
+    pub fn dual_to_svg(
+        &self,
+        fill: Color,
+        stroke: Color,
+        options: &RenderOptions,
+    ) -> Result<String> {
+        let vertexes = self.numbered_vertexes()?;
+        let dual = self.svg_dual(&vertexes, fill, stroke, options)?;
+
+        self.svg_document(&[], &[], &dual, options)
+    }
+
+    /// Returns the model's (repeated) tiling and its dual tiling as one SVG
+    /// document, with the dual's layer above the tiling's layers.
+    /// Shapes are written as by `to_svg` and cells as by `dual_to_svg`; each
+    /// shape also has a "data-vertices" attribute listing the vertices of the
+    /// tiling (and so the cells of the dual) it touches.
+    pub fn to_svg_with_dual(
+        &self,
+        fill: Color,
+        stroke: Color,
+        options: &RenderOptions,
+    ) -> Result<String> {
+        let vertexes = self.numbered_vertexes()?;
+        let numbers = vertexes
+            .iter()
+            .enumerate()
+            .map(|(i, (key, _))| (*key, i))
+            .collect::<HashMap<Key, usize>>();
+        let shapes = self.svg_shapes(options, Some(&numbers))?;
+        let tiles = self.svg_tiles(options)?;
+        let dual = self.svg_dual(&vertexes, fill, stroke, options)?;
+
+        self.svg_document(&shapes, &tiles, &dual, options)
+    }
+
+    /// Returns the visible shapes of the model's (repeated) tiling as SVG
+    /// elements numbered by position, linking each to the numbers of its
+    /// vertices if vertexes is given.
+    fn svg_shapes(
+        &self,
+        options: &RenderOptions,
+        vertexes: Option<&HashMap<Key, usize>>,
+    ) -> Result<Vec<svg::Element>> {
+        let mut placements = self.visible();
+        self.check_margin(options)?;
+        self.apply_style_set(&mut placements, options)?;
+        placements.sort_by(|a, b| {
+            let (a, b) = (a.shape.point(), b.shape.point());
+            (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less)
+        });
+
+        let mut elements = Vec::new();
+        for (id, p) in placements.into_iter().enumerate() {
+            let s = p.shape;
+            let corner = s.corner().unwrap_or(options.corner);
+            let paint = s.paint().unwrap_or(options.paint);
+            let points = s.points(s.margin().unwrap_or(options.margin))?;
+            let link = match vertexes {
+                Some(vertexes) => {
+                    let outline = s.points(0.0)?;
+                    let numbers = outline[0..outline.len() - 1]
+                        .iter()
+                        .filter_map(|p| vertexes.get(&p.key(self.precision)))
+                        .map(|i| i.to_string())
+                        .collect::<Vec<String>>();
+
+                    Some(("data-vertices", numbers.join(" ")))
+                }
+                None => None,
+            };
+            let points = shape::corners(&points, corner);
+            elements.push(svg::Element {
+                id,
+                sides: s.sides(),
+                snapped: self.snapped(options, &[&points]),
+                points,
+                holes: Vec::new(),
+                fill: Some(s.fill()).filter(|_| paint.fills()),
+                stroke: Some(s.stroke()).filter(|_| paint.strokes()),
+                opacity: s.opacity(),
+                blend: s.blend(),
+                link,
+            });
+        }
+
+        Ok(elements)
+    }
+
+    /// Returns the tiles placed in the model's (repeated) tiling as SVG
+    /// elements numbered by position, inset by the margin of options.
+    fn svg_tiles(&self, options: &RenderOptions) -> Result<Vec<svg::Element>> {
+        let mut tiles = self.placed_tiles();
+        tiles.sort_by(|a, b| {
+            let (a, b) = (a.point(), b.point());
+            (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less)
+        });
+
+        let mut elements = Vec::new();
+        for (id, t) in tiles.iter().enumerate() {
+            let rings = t.rings(options.margin)?;
+            let sides = rings.first().map_or(0, |r| r.len() as i32 - 1);
+            let points = t.outline_points(options.margin)?;
+            let holes = rings.into_iter().skip(1).collect::<Vec<_>>();
+            let outline = std::iter::once(&points)
+                .chain(holes.iter())
+                .map(|ring| ring.as_slice())
+                .collect::<Vec<_>>();
+            elements.push(svg::Element {
+                id,
+                sides,
+                snapped: self.snapped(options, &outline),
+                points,
+                holes,
+                fill: Some(t.fill()).filter(|_| t.paint().fills()),
+                stroke: Some(t.stroke()).filter(|_| t.paint().strokes()),
+                opacity: None,
+                blend: None,
+                link: None,
+            });
+        }
+
+        Ok(elements)
+    }
+
+    /// Returns the cells of the model's dual tiling as SVG elements, each
+    /// numbered by the vertex of vertexes it was made from.
+    fn svg_dual(
+        &self,
+        vertexes: &[(Key, Incidence)],
+        fill: Color,
+        stroke: Color,
+        options: &RenderOptions,
+    ) -> Result<Vec<svg::Element>> {
+        let mut elements = Vec::new();
+        for (id, (_, (point, shapes, ends))) in vertexes.iter().enumerate() {
+            let points = match self.dual_cell(*point, shapes, ends) {
+                Some(points) => points,
+                None => continue,
+            };
+
+            let sides = points.len() as i32 - 1;
+            let dual = Dual::new(points, fill, stroke);
+            if dual.collapses(options.cell_margin()?)? {
+                continue;
+            }
+
+            let points = shape::corners(&dual.points(options.cell_margin()?)?, options.corner);
+            elements.push(svg::Element {
+                id,
+                sides,
+                snapped: self.snapped(options, &[&points]),
+                points,
+                holes: Vec::new(),
+                fill: Some(fill).filter(|_| options.paint.fills()),
+                stroke: Some(stroke).filter(|_| options.paint.strokes()),
+                opacity: None,
+                blend: None,
+                link: Some(("data-vertex", id.to_string())),
+            });
+        }
+
+        Ok(elements)
+    }
+
+    /// Returns an SVG document of shapes, tiles, and dual sized to the model.
+    fn svg_document(
+        &self,
+        shapes: &[svg::Element],
+        tiles: &[svg::Element],
+        dual: &[svg::Element],
+        options: &RenderOptions,
+    ) -> Result<String> {
+        let (width, height, scale) = self.pixels();
+        let border = match self.border {
+            Some((thickness, color)) if !shapes.is_empty() || !tiles.is_empty() => {
+                Some((self.frame()?, thickness, color))
+            }
+            _ => None,
+        };
+        let canvas = svg::Canvas {
+            width,
+            height,
+            origin: self.origin.position(width as f64, height as f64),
+            scale,
+            background: options.background,
+            line_width: options.line_width,
+            line_cap: options.line_cap,
+            line_join: options.line_join,
+            dash: options.dash.clone(),
+            dash_offset: options.dash_offset,
+            miter_limit: options.miter_limit,
+            border,
+        };
+
+        Ok(svg::document(&canvas, shapes, tiles, dual))
+    }
+
+    /// Returns rings (in model units) with their points moved to the centers
+    /// of the pixels of the model's renders they fall in, as the rings an
+    /// SVG element is stroked through, or none if options do not snap
+    /// strokes to pixels.
+    fn snapped(&self, options: &RenderOptions, rings: &[&[Point]]) -> Option<Vec<Vec<Point>>> {
+        if !options.pixel_snap {
+            return None;
+        }
+        let snap = |p: &Point| {
+            let (x, y) = self.model_to_canvas(*p);
+            self.canvas_to_model(shape::pixel_center(x, 1.0), shape::pixel_center(y, 1.0))
+        };
+
+        Some(
+            rings
+                .iter()
+                .map(|ring| ring.iter().map(snap).collect())
+                .collect(),
+        )
+    }
+
+    /// Returns each vertex of the model's (repeated) tiling with its incident
+    /// shapes, ordered by position (top to bottom, then left to right).
+    fn numbered_vertexes(&self) -> Result<Vec<(Key, Incidence)>> {
+        let mut vertexes = vertexes(&self.dual_shapes(), self.precision)?;
+        vertexes.sort_by(|(_, (a, _, _)), (_, (b, _, _))| {
+            (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less)
+        });
+
+        Ok(vertexes)
+    }
+
+    /// Returns an immutable snapshot of the model's (repeated) tiling that may be
+    /// shared between threads and rendered many times.
+    /// Hidden shapes are left out of the snapshot's shapes but not its dual.
+    /// In wrap mode the snapshot holds one period, with each cell of the dual
+    /// once rather than once per neighboring period.
+    pub fn freeze(&self) -> Result<Tiling> {
+        let shapes = self
+            .placed()
+            .filter(|p| !self.hidden.contains(&p.index))
+            .map(|p| p.shape)
+            .collect();
+
+        Ok(Tiling::new(shapes, self.unique_dual_points()?))
+    }
+
+    /// Returns the model's (repeated) tiling as a scene graph in canvas
+    /// coordinates, with one node per visible shape numbered by position (top
+    /// to bottom, then left to right).
+    pub fn scene(&self) -> Result<Scene> {
+        let mut placements = self.visible();
+        placements.sort_by(|a, b| {
+            let (a, b) = (a.shape.point(), b.shape.point());
+            (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less)
+        });
+
+        let (width, height) = self.size();
+        let transform = |p: Point| {
+            let (x, y) = self.model_to_canvas(p);
+            Point { x, y }
+        };
+
+        let mut scene = Scene::new(width, height);
+        for p in placements {
+            scene.push(p.index, &p.shape, transform)?;
+        }
+
+        Ok(scene)
+    }
+
+    /// Returns the model's (repeated) tiling as a mesh of shared vertices and
+    /// edges.
+    /// Shapes are ordered by their centers (top to bottom, then left to right),
+    /// so the same model always produces the same mesh.
+    pub fn to_mesh(&self) -> Result<Mesh> {
+        Mesh::new(&self.ordered(), self.precision)
+    }
+
+    /// Returns the model's (repeated) tiling with each shape extruded into a
+    /// prism height high (in model units, where a shape's side has length 1),
+    /// for export to STL or OBJ and 3D printing.
+    /// Shapes are ordered as the faces of `to_mesh` are.
+    pub fn extrude(&self, height: f64) -> Result<Mesh3D> {
+        self.extrude_with_margin(height, 0.0)
+    }
+
+    /// Returns the model's (repeated) tiling extruded as by `extrude`, with
+    /// each shape inset by margin first, so the prisms are separated by gaps
+    /// twice margin wide rather than sharing faces.
+    /// A margin that is negative, not finite, or reaches the inradius of a
+    /// shape, insetting it to nothing, is an error.
+    pub fn extrude_with_margin(&self, height: f64, margin: f64) -> Result<Mesh3D> {
+        Mesh3D::extrude(&self.ordered(), height, margin)
+            .map_err(|err| err.context("extrude", None, None))
+    }
+
+    /// Returns the graph of which shapes of the model's (repeated) tiling share
+    /// an edge as a Graphviz DOT document.
+    /// Nodes are numbered as the faces of `to_mesh` are and carry their
+    /// shape's number of sides and colors; each is pinned at its shape's
+    /// center, so `neato` lays the graph out over the tiling.
+    pub fn adjacency_to_dot(&self) -> Result<String> {
+        let shapes = self.ordered();
+        let mesh = Mesh::new(&shapes, self.precision)?;
+
+        let mut faces: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (i, face) in mesh.faces().iter().enumerate() {
+            for (j, v0) in face.iter().enumerate() {
+                let v1 = face[(j + 1) % face.len()];
+                faces
+                    .entry((*v0.min(&v1), *v0.max(&v1)))
+                    .or_default()
+                    .push(i);
+            }
+        }
+        let mut adjacent = faces
+            .values()
+            .filter(|fs| fs.len() == 2)
+            .map(|fs| (fs[0].min(fs[1]), fs[0].max(fs[1])))
+            .collect::<Vec<(usize, usize)>>();
+        adjacent.sort_unstable();
+        adjacent.dedup();
+
+        Ok(dot::graph(&shapes, &adjacent))
+    }
+
+    /// Returns the model's (repeated) tiling as a GeoJSON feature collection
+    /// with one polygon feature per shape, followed by one per polygon of the
+    /// dual tiling if dual is true (in wrap mode, each polygon of the period's
+    /// dual once).
+    /// Each feature's properties hold its kind ("shape" or "dual"), index,
+    /// number of sides, and, for shapes, fill and stroke colors.
+    /// Model coordinates are used as is, except that the y axis points up.
+    pub fn to_geojson(&self, dual: bool) -> Result<String> {
+        let mut features = Vec::new();
+        for (i, s) in self.ordered().into_iter().enumerate() {
+            features.push(Feature {
+                kind: "shape",
+                index: i,
+                sides: s.sides() as usize,
+                colors: Some((s.fill(), s.stroke())),
+                points: s.points(0.0)?,
+            });
+        }
+
+        if dual {
+            let mut duals = self.unique_dual_points()?;
+            duals.sort_by(|a, b| {
+                let (a, b) = (centroid(a), centroid(b));
+                (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less)
+            });
+            for (i, points) in duals.into_iter().enumerate() {
+                features.push(Feature {
+                    kind: "dual",
+                    index: i,
+                    sides: points.len() - 1,
+                    colors: None,
+                    points,
+                });
+            }
+        }
+
+        Ok(geojson::feature_collection(&features))
+    }
+
+    /// Returns the edges of the model's visible (repeated) tiling, clipped to
+    /// its canvas (or, in wrap mode, its period), joined into polylines for
+    /// pen plotters.
+    /// An edge shared by two shapes is drawn once, and edges are joined
+    /// end to end into as few lines as possible, so no line is drawn twice
+    /// and the pen is lifted as little as possible.
+    /// Edges are traced as they are rendered: moved by the shapes'
+    /// transforms and along their deformed paths, and left out where hidden
+    /// (unless the shape on the edge's other side strokes it). The edges of
+    /// the model's tiles, holes included, are traced along with them.
+    /// Points are in model units, with the model's origin wherever
+    /// `with_origin` anchors it.
+    pub fn to_polylines(&self) -> Result<Vec<Vec<Point>>> {
+        let (extent, center) = (self.extent(), self.center());
+        let (no_styles, no_paths) = (HashMap::new(), HashMap::new());
+        let mut lines = Vec::new();
+        for p in self.visible() {
+            let styles = self.edge_styles.get(&p.index).unwrap_or(&no_styles);
+            let paths = self.edge_paths.get(&p.index).unwrap_or(&no_paths);
+            for (i, line) in p.shape.traced_edges(0.0, paths)?.into_iter().enumerate() {
+                if !styles.get(&i).map_or(false, |s| s.is_hidden()) {
+                    lines.push(line);
+                }
+            }
+        }
+        for t in self.placed_tiles() {
+            lines.extend(t.rings(0.0)?);
+        }
+
+        let segments = lines
+            .iter()
+            .flat_map(|line| line.windows(2))
+            .filter_map(|w| plot::clip(w[0] - center, w[1] - center, extent))
+            .map(|(p0, p1)| (p0 + center, p1 + center))
+            .collect::<Vec<(Point, Point)>>();
+
+        Ok(plot::chain(&segments, self.precision))
+    }
+
+    /// Returns the lines of `to_polylines` as HPGL for a pen plotter, with
+    /// edges edge_mm millimeters long and the bottom left corner of the
+    /// canvas at the plotter's origin.
+    /// Fails with `InvalidArgument` unless edge_mm is finite and positive.
+    pub fn to_hpgl(&self, edge_mm: f64) -> Result<String> {
+        Ok(plot::hpgl(&self.plotted(edge_mm)?, self.extent(), edge_mm))
+    }
+
+    /// Returns the lines of `to_polylines` as G-code for a pen plotter or CNC
+    /// machine, as `to_hpgl` places them, lowering the pen to Z0 to draw each
+    /// line, ensuring edge_mm is finite and positive as `to_hpgl` does.
+    pub fn to_gcode(&self, edge_mm: f64) -> Result<String> {
+        Ok(plot::gcode(&self.plotted(edge_mm)?, self.extent(), edge_mm))
+    }
+
+    /// Returns the lines of `to_polylines` moved so the center of the canvas
+    /// is at the origin, as the plotters' formats place them, ensuring edges
+    /// are plotted edge_mm millimeters long, a finite, positive length.
+    fn plotted(&self, edge_mm: f64) -> Result<Vec<Vec<Point>>> {
+        if !edge_mm.is_finite() || edge_mm <= 0.0 {
+            return Err(InvalidArgument {
+                name: String::from("edge_mm"),
+                reason: format!("edges cannot be plotted {} millimeters long", edge_mm),
+            });
+        }
+
+        let center = self.center();
+        let lines = self
+            .to_polylines()?
+            .into_iter()
+            .map(|line| line.into_iter().map(|p| p - center).collect())
+            .collect();
+
+        Ok(lines)
+    }
+
+    /// Returns every shape placed in the model's (repeated) tiling ordered by
+    /// their centers (top to bottom, then left to right).
+    fn ordered(&self) -> Vec<Shape> {
+        let mut shapes = self.placed().map(|p| p.shape).collect::<Vec<Shape>>();
+        shapes.sort_by(|a, b| {
+            let (a, b) = (a.point(), b.point());
+            (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less)
+        });
+
+        shapes
+    }
+
+    /// Returns the differences between the shapes of the model's (repeated)
+    /// tiling and other's, comparing centers to the model's precision.
+    pub fn diff(&self, other: &Model) -> TilingDiff {
+        let (before, after) = (
+            self.placed().collect::<Vec<Placed>>(),
+            other.placed().collect::<Vec<Placed>>(),
+        );
+
+        TilingDiff::new(
+            before.iter().map(|p| &p.shape),
+            after.iter().map(|p| &p.shape),
+            self.precision,
+        )
+    }
+
+    /// Returns the area of the model's canvas (or, in wrap mode, its period)
+    /// covered by the visible shapes of its (repeated) tiling, in square model
+    /// units, where a shape's side has length 1.
+    /// Shapes that overlap are counted once for each shape, so a covered area
+    /// larger than the canvas reveals overlaps.
+    pub fn covered_area(&self) -> Result<f64> {
+        let ((w, h), c) = (self.extent(), self.center());
+        let mut canvas = vec![
+            Point { x: -w, y: -h } + c,
+            Point { x: w, y: -h } + c,
+            Point { x: w, y: h } + c,
+            Point { x: -w, y: h } + c,
+        ];
+        // Clipping keeps the side of each edge that a shape's edges keep.
+        if irregular::area(&canvas) < 0.0 {
+            canvas.reverse();
+        }
+
+        let mut covered = 0.0;
+        for p in self.visible() {
+            let mut points = p.shape.points(0.0)?;
+            points.pop();
+            covered += irregular::area(&irregular::intersect(&points, &canvas)).abs();
+        }
+
+        Ok(covered)
+    }
+
+    /// Returns the fraction of the model's canvas (or, in wrap mode, its
+    /// period) covered by the visible shapes of its (repeated) tiling.
+    /// A ratio below 1 reveals gaps in the tiling, or a tiling that was not
+    /// repeated to the canvas' edges, and a ratio above 1 reveals overlaps.
+    pub fn coverage_ratio(&self) -> Result<f64> {
+        let (w, h) = self.extent();
+
+        Ok(self.covered_area()? / (4.0 * w * h))
+    }
+
+    /// Returns the outer boundary of the patch formed by the model's shapes
+    /// and tiles (not counting repeats), wound as a shape's points are, or no
+    /// points if there are none.
+    /// Edges shared by two shapes lie inside the union of the shapes and the
+    /// others form loops, of which the one enclosing the most area is the
+    /// outer boundary, so holes are left out. Where pieces of the patch meet
+    /// only at a vertex, each is a loop of its own, and the boundary is that
+    /// of the largest. The boundary outlines the shapes for clipping,
+    /// framing, and contour cuts.
+    pub fn boundary(&self) -> Result<Vec<Point>> {
+        Ok(outer(self.outlines(self.patch_rings()?)?))
+    }
+
+    /// Returns the outer boundary of the model's (repeated) tiling's visible
+    /// shapes and its tiles, as `boundary` does for the patch.
+    pub fn repeated_boundary(&self) -> Result<Vec<Point>> {
+        let mut rings = Vec::new();
+        for p in self.visible() {
+            rings.extend(oriented(vec![p.shape.points(0.0)?]));
+        }
+        for t in self.placed_tiles() {
+            rings.extend(oriented(t.rings(0.0)?));
+        }
+
+        Ok(outer(self.outlines(rings)?))
+    }
+
+    /// Returns the rings outlining the model's shapes and tiles (not counting
+    /// repeats), as `outlines` takes them.
+    fn patch_rings(&self) -> Result<Vec<Vec<Point>>> {
+        let mut rings = Vec::new();
+        for s in self.shapes.iter() {
+            rings.extend(oriented(vec![s.points(0.0)?]));
+        }
+        for t in self.tiles.iter() {
+            rings.extend(oriented(t.rings(0.0)?));
+        }
+
+        Ok(rings)
+    }
+
+    /// Returns every loop of edges that bound the union of the polygons
+    /// outlined by rings, each wound as a shape's points are if it bounds a
+    /// piece of the union, or the other way if it bounds a hole, or an error
+    /// if a loop does not close. Rings are wound as `oriented` winds them, so
+    /// a ring wound the other way cuts a hole in the polygon before it.
+    /// Each ring's edges are directed with its interior on the same side, and
+    /// split where other rings' corners lie along them, so a loop that
+    /// reaches a vertex where pieces meet turns as sharply toward that side
+    /// as it can, which keeps to its own piece.
+    fn outlines(&self, rings: Vec<Vec<Point>>) -> Result<Vec<Vec<Point>>> {
+        let mut outlines = Vec::new();
+        let mut points = HashMap::new();
+        for outline in rings {
+            for p in outline.iter() {
+                points.insert(self.key(*p), *p);
+            }
+            outlines.push(outline);
+        }
+
+        // Vertices are bucketed by unit cell, so each edge is split only at
+        // the vertices near it that lie between its ends, as the corners of
+        // tilings that are not edge-to-edge lie along their neighbors' edges.
+        let tolerance = math::powi(10.0, -self.precision);
+        let cell = |x: f64| x.floor() as i64;
+        let mut buckets: HashMap<(i64, i64), Vec<Point>> = HashMap::new();
+        for p in points.values() {
+            buckets.entry((cell(p.x), cell(p.y))).or_default().push(*p);
+        }
+        let mut counts: HashMap<(Key, Key), usize> = HashMap::new();
+        let mut directed = HashMap::new();
+        for outline in outlines {
+            for w in outline.windows(2) {
+                let (a, b) = (w[0], w[1]);
+                let mut through = vec![a, b];
+                for x in cell(a.x.min(b.x) - tolerance)..=cell(a.x.max(b.x) + tolerance) {
+                    for y in cell(a.y.min(b.y) - tolerance)..=cell(a.y.max(b.y) + tolerance) {
+                        through.extend(buckets.get(&(x, y)).into_iter().flatten().filter(|p| {
+                            p.distance(a) > tolerance
+                                && p.distance(b) > tolerance
+                                && distance_to_segment(**p, a, b) <= tolerance
+                        }));
+                    }
+                }
+                through
+                    .sort_by(|p, q| a.distance(*p).partial_cmp(&a.distance(*q)).unwrap_or(Equal));
+                for w in through.windows(2) {
+                    let (k0, k1) = (self.key(w[0]), self.key(w[1]));
+                    directed.insert((k0.min(k1), k0.max(k1)), (k0, k1));
+                    *counts.entry((k0.min(k1), k0.max(k1))).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut edges = counts
+            .into_iter()
+            .filter(|(_, count)| *count == 1)
+            .map(|(edge, _)| directed[&edge])
+            .collect::<Vec<(Key, Key)>>();
+        edges.sort_unstable();
+        let mut outgoing: HashMap<Key, Vec<Key>> = HashMap::new();
+        for (k0, k1) in edges.iter() {
+            outgoing.entry(*k0).or_default().push(*k1);
+        }
+
+        // Walk each loop from its first unused edge until it closes.
+        let turn = |a: Point, b: Point, c: Point| {
+            let (ux, uy, vx, vy) = (b.x - a.x, b.y - a.y, c.x - b.x, c.y - b.y);
+            (ux * vy - uy * vx).atan2(ux * vx + uy * vy)
+        };
+        let mut unused = edges.iter().copied().collect::<HashSet<(Key, Key)>>();
+        let mut loops = Vec::new();
+        for (start, next) in edges {
+            if !unused.remove(&(start, next)) {
+                continue;
+            }
+            let mut ring = vec![points[&start]];
+            let (mut previous, mut current) = (start, next);
+            while current != start {
+                let (a, b) = (points[&previous], points[&current]);
+                ring.push(b);
+                let step = outgoing
+                    .get(&current)
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .filter(|n| unused.contains(&(current, *n)))
+                    .max_by(|m, n| {
+                        let (m, n) = (turn(a, b, points[m]), turn(a, b, points[n]));
+                        m.partial_cmp(&n).unwrap_or(Equal)
+                    })
+                    .ok_or(OpenBoundary { x: b.x, y: b.y })?;
+                unused.remove(&(current, step));
+                previous = current;
+                current = step;
+            }
+            loops.push(ring);
+        }
+
+        Ok(loops)
+    }
+
+    /// Returns the loops that bound the pieces of the model's patch, which its
+    /// border frames: the patch's `boundary`, and the loops of any pieces that
+    /// meet it only at a vertex or lie apart from it.
+    fn frame(&self) -> Result<Vec<Vec<Point>>> {
+        let loops = self.outlines(self.patch_rings()?)?;
+
+        Ok(loops
+            .into_iter()
+            .filter(|l| irregular::area(l) > 0.0)
+            .collect())
+    }
+
+    /// Frames the model's patch (its `boundary` before repeating) with a band
+    /// thickness wide (in model units, where a shape's side has length 1) of
+    /// color, drawn around the outside of the boundary over the tiling, so
+    /// coasters and puzzles can be cut along an outline that hugs the
+    /// pattern. The frame is drawn by `render`, `render_with`, and `to_svg`.
+    /// A thickness of 0 removes the frame.
+    pub fn border(&mut self, thickness: f64, color: Color) {
+        self.border = match thickness > 0.0 {
+            true => Some((thickness, color)),
+            false => None,
+        };
+    }
+
+    /// Returns half the width and height (in model units) of the region the
+    /// model renders around its `center`: its canvas, or its period in wrap
+    /// mode.
+    fn extent(&self) -> (f64, f64) {
+        match self.period {
+            Some(period) => (period.x / 2.0, period.y / 2.0),
+            None => (
+                self.width as f64 / 2.0 / self.scale,
+                self.height as f64 / 2.0 / self.scale,
+            ),
+        }
+    }
+
+    /// Returns summary statistics of the model's (repeated) tiling.
+    pub fn stats(&self) -> Result<Stats> {
+        let shapes = self.ordered();
+        let mut counts = BTreeMap::new();
+        let mut area = 0.0;
+        for s in shapes.iter() {
+            *counts.entry(s.sides()).or_insert(0) += 1;
+            area += s.area();
+        }
+
+        let mut vertex_configurations = BTreeMap::new();
+        for (_, (_, shapes, _)) in vertexes(&shapes, self.precision)? {
+            if let Some(c) = stats::vertex_configuration(&shapes) {
+                *vertex_configurations.entry(c).or_insert(0) += 1;
+            }
+        }
+
+        Ok(Stats::new(counts, vertex_configurations, area))
+    }
+
+    /// Returns the figure of each vertex within the model's canvas (or, in wrap
+    /// mode, its period) ordered by position (top to bottom, then left to
+    /// right).
+    /// Vertices whose figures do not close to 360° reveal gaps or overlaps in
+    /// the construction; vertices outside the canvas are left out since the
+    /// repeated tiling may end there.
+    pub fn vertex_configurations(&self) -> Result<Vec<VertexFigure>> {
+        let ((w, h), c) = (self.extent(), self.center());
+        let tiled = self.tiled();
+
+        let vertexes = vertexes(tiled.iter().map(|p| &p.shape), self.precision)?
+            .into_iter()
+            .filter(|(_, (p, _, _))| (p.x - c.x).abs() <= w && (p.y - c.y).abs() <= h)
+            .collect::<Vec<(Key, Incidence)>>();
+        let points = vertexes
+            .iter()
+            .map(|(_, (p, _, _))| *p)
+            .collect::<Vec<Point>>();
+        let tolerance = math::powi(10.0, -self.precision);
+        let crossings = crossings(tiled.iter().map(|p| &p.shape), &points, tolerance)?;
+        let mut figures = vertexes
+            .into_iter()
+            .zip(crossings)
+            .map(|((_, (p, shapes, _)), crossing)| VertexFigure::new(p, &shapes, crossing))
+            .collect::<Vec<VertexFigure>>();
+        figures.sort_by(|a, b| {
+            let (a, b) = (a.point(), b.point());
+            (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less)
+        });
+
+        Ok(figures)
+    }
+
+    /// Returns the notation of the model's (repeated) tiling, for captions:
+    /// the vertex configuration of a uniform tiling (e.g. `"3.4.6.4"`), or the
+    /// distinct configurations of a tiling with several kinds of vertex in
+    /// brackets (e.g. `"[3.3.3.3.3.3; 3.3.4.3.4]"`), followed by the orbifold
+    /// notation of its symmetry group (e.g. `"3.4.6.4 (*632)"`).
+    /// The symmetry group is found from the isometries that map the tiling
+    /// onto itself, and is left out unless the model has been repeated and
+    /// its canvas (or, in wrap mode, its period and the copies around it)
+    /// covers a cell of the lattice it was repeated along.
+    /// The vertices are those `vertex_configurations` returns. Returns `None`
+    /// if there are none or any of them does not close, since the tiling then
+    /// has gaps or overlaps.
+    pub fn notation(&self) -> Result<Option<String>> {
+        let figures = self.vertex_configurations()?;
+        if figures.is_empty() || figures.iter().any(|f| !f.closes()) {
+            return Ok(None);
+        }
+
+        let configurations = figures
+            .iter()
+            .map(|f| f.configuration())
+            .collect::<BTreeSet<&str>>()
+            .into_iter()
+            .collect::<Vec<&str>>();
+        let notation = match configurations[..] {
+            [configuration] => configuration.to_string(),
+            _ => format!("[{}]", configurations.join("; ")),
+        };
+
+        // In wrap mode the period is drawn with a copy on every side.
+        let ((w, h), c) = (self.extent(), self.center());
+        let (bounds, mut generators) = match self.period {
+            Some(period) => (
+                (3.0 * w, 3.0 * h),
+                vec![
+                    Point {
+                        x: period.x,
+                        y: 0.0,
+                    },
+                    Point {
+                        x: 0.0,
+                        y: period.y,
+                    },
+                ],
+            ),
+            None => ((w, h), Vec::new()),
+        };
+        generators.extend_from_slice(&self.generators);
+        let shapes = self
+            .tiled()
+            .into_iter()
+            .map(|p| p.shape)
+            .collect::<Vec<Shape>>();
+        let tolerance = math::powi(10.0, -self.precision);
+        let orbifold = symmetry::orbifold(&shapes, &generators, c, bounds, tolerance)?;
+
+        Ok(Some(match orbifold {
+            Some(orbifold) => format!("{} ({})", notation, orbifold),
+            None => notation,
+        }))
+    }
+
+    /// Checks that the model's (repeated) tiling is uniform with the vertex
+    /// configuration configuration (e.g. `"3.4.6.4"` or `"3^2.4.3.4"`, in any
+    /// rotation or reflection): that the figure of every vertex that
+    /// `vertex_configurations` returns has it.
+    /// The figures that differ, including those that do not close, are
+    /// returned in a `NotUniform` error.
+    pub fn assert_uniform(&self, configuration: &str) -> Result<()> {
+        let expected = stats::parse_configuration(configuration).ok_or_else(|| InvalidShape {
+            reason: format!("{:?} is not a vertex configuration", configuration),
+        })?;
+        let vertices = self
+            .vertex_configurations()?
+            .into_iter()
+            .filter(|f| !f.closes() || f.configuration() != expected)
+            .collect::<Vec<VertexFigure>>();
+        if !vertices.is_empty() {
+            return Err(NotUniform {
+                configuration: expected,
+                vertices,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns every vertex of the model's (repeated) tiling, merging corners
+    /// within 10^-precision of each other (see `with_precision`), with the
+    /// shapes and edges that meet there, ordered by position (top to bottom, then left to
+    /// right).
+    /// Vertices on the edge of the repeated tiling are included, so their
+    /// shapes may not close around them.
+    pub fn vertices(&self) -> Result<Vec<Vertex>> {
+        let placed = self.placed().collect::<Vec<Placed>>();
+        let mut vertices = Vec::new();
+        let tolerance = math::powi(10.0, -self.precision);
+        let vertexes = vertexes(placed.iter().map(|p| &p.shape), self.precision)?;
+        let points = vertexes
+            .iter()
+            .map(|(_, (p, _, _))| *p)
+            .collect::<Vec<Point>>();
+        let crossings = crossings(placed.iter().map(|p| &p.shape), &points, tolerance)?;
+        for ((_, (point, shapes, ends)), crossing) in vertexes.into_iter().zip(crossings) {
+            let ends = ends.iter().flat_map(|(a, b)| [*a, *b]);
+            let angle = |p: &Point| math::atan2(p.y - point.y, p.x - point.x);
+            let mut ends = merge(ends, tolerance)
+                .into_iter()
+                .map(|(end, _)| end)
+                .collect::<Vec<Point>>();
+            ends.sort_by(|a, b| angle(b).partial_cmp(&angle(a)).unwrap_or(Less));
+
+            vertices.push(Vertex {
+                point,
+                cells: shapes.iter().map(|s| CellId(self.key(s.point()))).collect(),
+                edges: ends.into_iter().map(|end| (point, end)).collect(),
+                shapes,
+                crossing,
+            });
+        }
+        vertices.sort_by(|a, b| {
+            (a.point.y, a.point.x)
+                .partial_cmp(&(b.point.y, b.point.x))
+                .unwrap_or(Less)
+        });
+
+        Ok(vertices)
+    }
+
+    /// Returns the Voronoi diagram of the centers of the shapes of the
+    /// model's (repeated) tiling, clipped to the canvas: one cell for each
+    /// shape, ordered as `cells` orders them, holding the points nearer to
+    /// its shape's center than to any other.
+    /// For a tiling of a single regular polygon each cell is its shape;
+    /// elsewhere the cells part from the shapes (the octagons and squares of
+    /// 4.8.8 get cells of one size), as `render_voronoi` shows.
+    pub fn voronoi_of_centers(&self, fill: Color, stroke: Color) -> Vec<Dual> {
+        let centers = self
+            .centers()
+            .into_iter()
+            .map(|(_, p)| p)
+            .collect::<Vec<Point>>();
+
+        voronoi::cells(&centers, self.canvas_rect())
+            .into_iter()
+            .map(|points| Dual::new(points, fill, stroke))
+            .collect()
+    }
+
+    /// Returns the model's dual tiling.
+    fn dual(&self, fill: Color, stroke: Color) -> Result<Vec<Dual>> {
+        let duals = self
+            .dual_points()?
+            .into_iter()
+            .map(|points| Dual::new(points, fill, stroke))
+            .collect();
+
+        Ok(duals)
+    }
+
+    /// Returns the points of each polygon in the model's dual tiling.
+    /// In wrap mode the polygons of each neighboring period are included, as
+    /// they are drawn when rendering.
+    fn dual_points(&self) -> Result<Vec<Vec<Point>>> {
+        self.dual_points_where(|_| true)
+    }
+
+    /// Returns the points of each polygon in the model's dual tiling once: in
+    /// wrap mode, only the polygons formed from the vertices of the period
+    /// itself, rather than the copies of them in its neighbors.
+    fn unique_dual_points(&self) -> Result<Vec<Vec<Point>>> {
+        match self.period {
+            Some(period) => self.dual_points_where(|p| self.key(wrap(p, period)) == self.key(p)),
+            None => self.dual_points(),
+        }
+    }
+
+    /// Returns the points of each polygon in the model's dual tiling formed
+    /// from a vertex whose point keep accepts.
+    fn dual_points_where<F>(&self, keep: F) -> Result<Vec<Vec<Point>>>
+    where
+        F: Fn(Point) -> bool,
+    {
+        let shapes = self.dual_shapes();
+        #[cfg(feature = "tracing")]
+        let span =
+            tracing::debug_span!("dual", shapes = shapes.len(), cells = tracing::field::Empty)
+                .entered();
+        let mut duals: Vec<Vec<Point>> = Vec::new();
+        for (_, (point, shapes, ends)) in vertexes(&shapes, self.precision)? {
+            if !keep(point) {
+                continue;
+            }
+            if let Some(points) = self.dual_cell(point, &shapes, &ends) {
+                duals.push(points);
+            }
+        }
+        #[cfg(feature = "tracing")]
+        span.record("cells", duals.len());
+
+        Ok(duals)
+    }
+
+    /// Returns the geometry part of the way, t from 0 to 1, between the
+    /// model's (repeated) tiling and its dual, for animations that explain
+    /// duality.
+    /// Every corner where a shape meets a vertex of the tiling moves from the
+    /// vertex toward the shape's center: the shapes, in their own colors,
+    /// shrink into their centers while the dual's cells, filled with fill
+    /// and stroked with stroke, grow out of the vertices, with gaps along the
+    /// tiling's edges between them. At 0 only the shapes remain and at 1
+    /// only the cells of the dual (as the dual boundary of the model keeps
+    /// them).
+    pub fn morph_to_dual(&self, t: f64, fill: Color, stroke: Color) -> Result<Vec<Dual>> {
+        let (mut morphed, cells) = self.morphed(t, fill, stroke)?;
+        morphed.extend(cells);
+
+        Ok(morphed)
+    }
+
+    /// Returns the shrinking shapes and the growing cells of the dual of
+    /// `morph_to_dual` apart.
+    fn morphed(&self, t: f64, fill: Color, stroke: Color) -> Result<(Vec<Dual>, Vec<Dual>)> {
+        let t = t.clamp(0.0, 1.0);
+        let toward = |from: Point, to: Point| Point {
+            x: from.x + (to.x - from.x) * t,
+            y: from.y + (to.y - from.y) * t,
+        };
+
+        let shapes = self.dual_shapes();
+        let (mut morphed, mut cells) = (Vec::new(), Vec::new());
+        if t < 1.0 {
+            for s in shapes.iter() {
+                let points = s
+                    .points(0.0)?
+                    .into_iter()
+                    .map(|p| toward(p, s.point()))
+                    .collect();
+                morphed.push(Dual::new(points, s.fill(), s.stroke()));
+            }
+        }
+        if t > 0.0 {
+            for (_, (point, shapes, ends)) in vertexes(&shapes, self.precision)? {
+                if let Some(points) = self.dual_cell(point, &shapes, &ends) {
+                    let points = points.into_iter().map(|p| toward(point, p)).collect();
+                    cells.push(Dual::new(points, fill, stroke));
+                }
+            }
+        }
+
+        Ok((morphed, cells))
+    }
+
+    /// Returns the shapes whose vertices form the model's dual tiling: the
+    /// shapes of its (repeated) tiling, extended beyond the canvas under
+    /// `DualBoundary::Extend`.
+    fn dual_shapes(&self) -> Vec<Shape> {
+        let belt = match self.dual_boundary {
+            DualBoundary::Extend(belt) => belt as f64,
+            _ => return self.tiled().into_iter().map(|p| p.shape).collect(),
+        };
+
+        let step = match (self.period, self.lattice_basis()) {
+            (Some(period), _) => period.x.max(period.y),
+            (None, Some((b1, b2))) => math::hypot(b1.x, b1.y).max(math::hypot(b2.x, b2.y)),
+            (None, None) => 0.0,
+        };
+        let canvas = self.canvas_rect();
+        let rect = Rect::new(
+            canvas.origin().x - belt * step,
+            canvas.origin().y - belt * step,
+            canvas.width() + 2.0 * belt * step,
+            canvas.height() + 2.0 * belt * step,
+        );
+
+        self.placements(rect).map(|p| *p.shape()).collect()
+    }
+
+    /// Returns the canvas in model units.
+    fn canvas_rect(&self) -> Rect {
+        let (width, height) = (
+            self.width as f64 / self.scale,
+            self.height as f64 / self.scale,
+        );
+        let center = self.center();
+
+        Rect::new(
+            center.x - width / 2.0,
+            center.y - height / 2.0,
+            width,
+            height,
+        )
+    }
+
+    /// Returns the closed polygon of the dual tiling's cell formed from the
+    /// vertex point, its incident shapes (ordered around it), and their
+    /// corners on either side of it, or none if the model's dual boundary
+    /// discards the cell.
+    fn dual_cell(
+        &self,
+        point: Point,
+        shapes: &[Shape],
+        ends: &[(Point, Point)],
+    ) -> Option<Vec<Point>> {
+        // An edge from point that only one shape has lies on the boundary.
+        let ends = merge(
+            ends.iter().flat_map(|(a, b)| [*a, *b]),
+            math::powi(10.0, -self.precision),
+        );
+        let boundary = ends
+            .iter()
+            .filter(|(_, count)| *count == 1)
+            .map(|(q, _)| Point {
+                x: (point.x + q.x) / 2.0,
+                y: (point.y + q.y) / 2.0,
+            })
+            .collect::<Vec<Point>>();
+
+        let mut points = shapes.iter().map(|s| s.point()).collect::<Vec<Point>>();
+        match (self.dual_boundary, boundary.len()) {
+            (_, 0) => {}
+            (DualBoundary::Clip, 2) => {
+                // No shape lies between the boundary edges, so their
+                // midpoints are neighbors in order around the vertex, and the
+                // vertex goes between them.
+                let angle = |p: &Point| math::atan2(p.y - point.y, p.x - point.x);
+                points.extend_from_slice(&boundary);
+                points.sort_by(|a, b| angle(b).partial_cmp(&angle(a)).unwrap_or(Less));
+                let is_boundary = |p: &Point| boundary.iter().any(|b| b == p);
+                let n = points.len();
+                let start = match (0..n)
+                    .find(|i| is_boundary(&points[*i]) && is_boundary(&points[(i + 1) % n]))
+                {
+                    Some(i) => (i + 1) % n,
+                    None => return None,
+                };
+                points.rotate_left(start);
+                points.insert(0, point);
+            }
+            (DualBoundary::Clip, _) => return None,
+            _ if shapes.len() < 3 => return None,
+            _ => {}
+        }
+        if let DualBoundary::Extend(_) = self.dual_boundary {
+            let canvas = self.canvas_rect();
+            let (origin, (width, height)) = (canvas.origin(), (canvas.width(), canvas.height()));
+            let outside = points.iter().all(|p| p.x < origin.x)
+                || points.iter().all(|p| p.x > origin.x + width)
+                || points.iter().all(|p| p.y < origin.y)
+                || points.iter().all(|p| p.y > origin.y + height);
+            if outside {
+                return None;
+            }
+        }
+        points.push(points[0]);
+
+        Some(points)
+    }
+
+    /// Renders the model as configured by options with cairo, as `render`
+    /// renders it on a `CairoBackend`.
+    #[cfg(feature = "cairo")]
+    pub fn render_with(&self, options: &RenderOptions) -> Result<Render> {
+        let (surface, context) = self.canvas(options)?;
+        self.draw(&context, options, &mut |_, _| Ok(()))?;
+
+        self.finish(surface, options)
+    }
+
+    /// Redraws the parts of prev, a render of the model made with options,
+    /// that the shapes of changed cover, so after a few shapes are restyled
+    /// or recolored the render is brought up to date without drawing the
+    /// whole tiling again.
+    /// The bounding box of each changed shape as it is drawn, with its
+    /// transform and deformed edges (grown by its stroke or outline width and
+    /// a pixel), is cleared to the background and every shape that may overlap
+    /// it is drawn again, clipped to the boxes. Pixels outside the boxes are
+    /// kept, so a shape that moved needs a changed shape covering where it
+    /// was, too. The redrawn regions are not supersampled.
+    #[cfg(feature = "cairo")]
+    pub fn render_incremental(
+        &self,
+        prev: &mut Render,
+        changed: &[ShapeRef],
+        options: &RenderOptions,
+    ) -> Result<()> {
+        let (width, height) = self.size();
+        if (prev.width(), prev.height()) != (width, height) {
+            return Err(Mismatch {
+                reason: format!(
+                    "render is {} by {} pixels but the model renders at {} by {}",
+                    prev.width(),
+                    prev.height(),
+                    width,
+                    height
+                ),
+            });
+        }
+
+        let mut dirty = Vec::new();
+        for r in changed {
+            let bounds = self.drawn_bounds(r.index(), r.shape(), options)?;
+            let (origin, pad) = (bounds.origin(), 1.0 / self.scale);
+            let (x0, y0) = (origin.x, origin.y);
+            let (x1, y1) = (x0 + bounds.width(), y0 + bounds.height());
+            // In wrap mode the shape is also drawn beside each edge of the
+            // period. Boxes are grown to whole pixels, so no pixel is blended
+            // from its old and new colors.
+            for offset in self.offsets() {
+                let (cx0, cy0) = self.model_to_canvas(Point {
+                    x: x0 - pad + offset.x,
+                    y: y0 - pad + offset.y,
+                });
+                let (cx1, cy1) = self.model_to_canvas(Point {
+                    x: x1 + pad + offset.x,
+                    y: y1 + pad + offset.y,
+                });
+                let p0 = self.canvas_to_model(cx0.floor(), cy0.floor());
+                let p1 = self.canvas_to_model(cx1.ceil(), cy1.ceil());
+                dirty.push(Rect::new(p0.x, p0.y, p1.x - p0.x, p1.y - p0.y));
+            }
+        }
+        if dirty.is_empty() {
+            return Ok(());
+        }
+
+        let context = cairo::Context::new(&prev.surface)?;
+        let mut transparent = options.clone();
+        transparent.background = None;
+        render::prepare(&context, width, height, self.scale, 1, &transparent)?;
+        self.fit_period(&context);
+        for rect in dirty.iter() {
+            let origin = rect.origin();
+            context.rectangle(origin.x, origin.y, rect.width(), rect.height());
+        }
+        context.clip();
+        match options.background {
+            Some(background) => {
+                let (red, green, blue) = background.rgb_unit_int();
+                context.set_source_rgb(red, green, blue);
+                context.paint()?;
+            }
+            None => {
+                context.set_operator(cairo::Operator::Clear);
+                context.paint()?;
+                context.set_operator(cairo::Operator::Over);
+            }
+        }
+
+        self.draw_in(&context, options, Some(&dirty), &mut |_, _| Ok(()))
+    }
+
+    /// Returns a preview of the model columns characters wide for a terminal
+    /// that supports true color, as `Render::to_ansi` draws it, so a tiling
+    /// can be checked over SSH without transferring an image.
+    /// Rows are chosen to keep the render's proportions.
+    #[cfg(feature = "cairo")]
+    pub fn preview_terminal(&self, columns: usize) -> Result<String> {
+        let render = self.render_with(&RenderOptions::new())?;
+        // Each character is about twice as tall as it is wide and shows two
+        // pixels of the preview.
+        let rows = (columns as f64 * render.height() as f64 / render.width().max(1) as f64 / 2.0)
+            .round()
+            .max(1.0) as usize;
+
+        render.to_ansi(columns, rows)
+    }
+
+    /// Writes the model to a PDF file at path as configured by options, on a
+    /// page sized by the model's resolution (or a point per pixel if it has
+    /// none), so tilings print at their real-world size.
+    #[cfg(feature = "pdf")]
+    pub fn write_pdf<P: AsRef<Path>>(&self, path: P, options: &RenderOptions) -> Result<()> {
+        self.write_pdf_sheet(path, options, 0.0, 0.0, |_, _| Ok(()))
+    }
+
+    /// Writes the model to a print-ready PDF file at path as configured by
+    /// options and print: the page is sized as `write_pdf` sizes it, trimmed
+    /// from a larger sheet that the tiling bleeds onto and that carries crop
+    /// marks.
+    /// The fills and strokes of the shapes, the fills of cells, and the
+    /// background are adjusted for the press as print configures.
+    #[cfg(feature = "pdf")]
+    pub fn write_print_pdf<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &RenderOptions,
+        print: &PrintOptions,
+    ) -> Result<()> {
+        let mut model = self.recolor(|s| (print.adjust(s.fill()), print.adjust(s.stroke())));
+        for fill in model.fills.values_mut() {
+            *fill = print.adjust(*fill);
+        }
+        let mut options = options.clone();
+        options.background = options.background.map(|b| print.adjust(b));
+
+        model.write_pdf_sheet(
+            path,
+            &options,
+            print.border(),
+            print.bleed_points(),
+            |context, trim| print.mark(context, trim),
+        )
+    }
+
+    /// Writes the model to a PDF file at path as configured by options, on
+    /// the page `write_pdf` draws grown by border (in points) on every side,
+    /// with the tiling drawn bleed (in points) past the page, then calls
+    /// finish with the context and the page's rectangle on the sheet to draw
+    /// around it.
+    #[cfg(feature = "cairo")]
+    #[cfg(feature = "pdf")]
+    fn write_pdf_sheet<P, F>(
+        &self,
+        path: P,
+        options: &RenderOptions,
+        border: f64,
+        bleed: f64,
+        finish: F,
+    ) -> Result<()>
+    where
+        P: AsRef<Path>,
+        F: FnOnce(&cairo::Context, Rect) -> Result<()>,
+    {
+        let (width, height) = self.size();
+        let points = POINTS_PER_INCH / self.dpi.unwrap_or(POINTS_PER_INCH);
+        let trim = Rect::new(
+            border,
+            border,
+            width as f64 * points,
+            height as f64 * points,
+        );
+        let surface = cairo::PdfSurface::new(
+            trim.width() + 2.0 * border,
+            trim.height() + 2.0 * border,
+            path,
+        )?;
+        let context = cairo::Context::new(&surface)?;
+
+        // The tiling is clipped to the bleed, which culls the shapes beyond
+        // it, and drawn with its canvas on the page.
+        context.save()?;
+        context.rectangle(
+            border - bleed,
+            border - bleed,
+            trim.width() + 2.0 * bleed,
+            trim.height() + 2.0 * bleed,
+        );
+        context.clip();
+        context.translate(border, border);
+        context.scale(points, points);
+        render::prepare(&context, width, height, self.scale, 1, options)?;
+        self.fit_period(&context);
+        self.draw(&context, options, &mut |_, _| Ok(()))?;
+        context.restore()?;
+        finish(&context, trim)?;
+        drop(context);
+        surface.finish();
+
+        Ok(())
+    }
+
+    /// Renders the model on backend, such as a `CairoBackend`, an
+    /// `SvgBackend`, or a `SkiaBackend`, as configured by the backend's
+    /// options, and returns the backend's output.
+    /// A `CairoBackend` draws everything `render_with` draws. Other backends
+    /// only fill, stroke, and frame polygons, so the model's shapes are drawn
+    /// with their margins, corners, paint modes, outlines, edge styles,
+    /// deformed edges, color jitter, and pixel snapping, and framed by its
+    /// border; a model or options with anything more, such as tiles or
+    /// labels, fails with `Backend` rather than render without it.
+    pub fn render<B: RenderBackend>(&self, mut backend: B) -> Result<B::Output> {
+        #[cfg(feature = "cairo")]
+        if let Some(cairo) = backend.as_cairo() {
+            let options = cairo.options().clone();
+            cairo.rendered(self.render_with(&options)?);
+
+            return backend.finish();
+        }
+
+        let options = backend.options().clone();
+        let (width, height, (sx, _)) = self.pixels();
+        let pixels = |points: &[Point]| {
+            points
+                .iter()
+                .map(|p| {
+                    let (x, y) = self.model_to_canvas(*p);
+                    Point { x, y }
+                })
+                .collect::<Vec<Point>>()
+        };
+        // Strokes, but not fills, are snapped to the centers of pixels.
+        let snap = |points: Vec<Point>| match options.pixel_snap {
+            true => points
+                .iter()
+                .map(|p| Point {
+                    x: shape::pixel_center(p.x, 1.0),
+                    y: shape::pixel_center(p.y, 1.0),
+                })
+                .collect(),
+            false => points,
+        };
+        let line_width = options.line_width * sx;
+        let dash = options.dash.iter().map(|d| d * sx).collect::<Vec<f64>>();
+        let (no_styles, no_paths) = (HashMap::new(), HashMap::new());
+
+        let mut visible = match self.is_lazy() {
+            true => self.generated(&[self.canvas_rect()]),
+            false => self.visible(),
+        };
+        visible.retain(|p| !self.hidden.contains(&p.index));
+        self.check_margin(&options)?;
+        self.apply_style_set(&mut visible, &options)?;
+        if let Some(what) = self.cairo_only(&visible, &options) {
+            return Err(Backend {
+                reason: format!("{} can only be drawn on a CairoBackend", what),
+            });
+        }
+        let mut outlines = Vec::new();
+        let mut strokes = Vec::new();
+        backend.begin(width, height, options.background)?;
+        for p in visible.iter() {
+            let s = &p.shape;
+            let margin = s.margin().unwrap_or(options.margin);
+            let paint = s.paint().unwrap_or(options.paint);
+            let corner = s.corner().unwrap_or(options.corner);
+            let fill = match options.jitter {
+                Some((amplitude, seed)) => render::jitter(s.fill(), s.point(), amplitude, seed),
+                None => s.fill(),
+            };
+            // Corners are sized in edge lengths, so they are cut before the
+            // points are scaled to pixels. Deformed edges are followed
+            // instead of the corners, as cairo follows them.
+            let paths = self.edge_paths.get(&p.index).unwrap_or(&no_paths);
+            let edges = s.traced_edges(margin, paths)?;
+            let path = match paths.is_empty() {
+                true => pixels(&shape::corners(&s.rendered_points(margin)?, corner)),
+                false => pixels(&edges.concat()),
+            };
+            let edges = edges.iter().map(|e| pixels(e)).collect::<Vec<_>>();
+            let styles = self.edge_styles.get(&p.index).unwrap_or(&no_styles);
+            if paint.fills() {
+                backend.fill(&path, fill)?;
+            }
+            if !paint.strokes() {
+                continue;
+            }
+            let (path, edges) = (snap(path), edges.into_iter().map(snap).collect::<Vec<_>>());
+            if let Some(outline) = s.outline().or(options.outline) {
+                outlines.push((path.clone(), edges.clone(), styles, outline));
+            }
+            strokes.push((path, edges, styles, s.stroke()));
+        }
+        // Outlines would be covered by the fills of the shapes drawn after
+        // them, so they are drawn once every shape is filled, and strokes
+        // once every shape is outlined.
+        for (path, edges, styles, outline) in outlines {
+            let pen = |_: &EdgeStyle| (outline.color(), dash.clone());
+            let width = outline.width() * sx;
+            stroke_edges(&mut backend, &path, &edges, styles, &pen, width)?;
+        }
+        for (path, edges, styles, stroke) in strokes {
+            let pen = |style: &EdgeStyle| {
+                let color = style.stroke().unwrap_or(stroke);
+                match style.dashes() {
+                    Some((on, off)) => (color, vec![on * sx, off * sx]),
+                    None => (color, dash.clone()),
+                }
+            };
+            stroke_edges(&mut backend, &path, &edges, styles, &pen, line_width)?;
+        }
+        if let Some((thickness, color)) = self.border {
+            let rings = self.frame()?.iter().map(|r| pixels(r)).collect::<Vec<_>>();
+            backend.border(&rings, color, thickness * sx)?;
+        }
+
+        backend.finish()
+    }
+
+    /// Returns what a render of the shapes of visible as configured by
+    /// options would draw that only cairo can draw, if anything.
+    fn cairo_only(&self, visible: &[Placed], options: &RenderOptions) -> Option<&'static str> {
+        #[cfg(feature = "cairo")]
+        if !options.shape_hooks.is_empty() {
+            return Some("shape hooks");
+        }
+        #[cfg(feature = "cairo")]
+        if !options.motifs.is_empty() {
+            return Some("motifs");
+        }
+        let textured = visible.iter().any(|p| {
+            matches!(
+                self.overlays.get(&p.index),
+                Some(Fill::Image(..)) | Some(Fill::Texture(..))
+            )
+        });
+        let composited = visible
+            .iter()
+            .any(|p| p.shape.opacity().is_some() || p.shape.blend().is_some());
+
+        match () {
+            _ if !self.tiles.is_empty() => Some("tiles"),
+            _ if options.show_labels || options.vertex_labels.is_some() => Some("labels"),
+            _ if options.debug_edges => Some("debug edges"),
+            _ if !options.annotations.is_empty() => Some("annotations"),
+            _ if textured => Some("image and texture fills"),
+            _ if composited => Some("opacity and blend modes"),
+            _ => None,
+        }
+    }
+
+    /// Renders the model as configured by options with tiny-skia instead of
+    /// cairo, drawing what `render` draws.
+    #[cfg(feature = "skia")]
+    pub fn render_skia(&self, options: &RenderOptions) -> Result<SkiaRender> {
+        self.render(SkiaBackend::new(options))
+    }
+
+    /// Renders the model as configured by options with a legend strip
+    /// appended below the tiling.
+    /// The legend has a row for each kind of shape in the (repeated) tiling,
+    /// a number of sides and a fill, showing a swatch of the shape, its name,
+    /// and how many times it appears (e.g. "12 hexagons"), ordered by number
+    /// of sides and then by fill. Hidden shapes are left out.
+    /// The render carries the warnings of the tiling's render.
+    #[cfg(feature = "cairo")]
+    pub fn render_with_legend(&self, options: &RenderOptions) -> Result<Render> {
+        let mut entries: BTreeMap<(i32, String), (Shape, usize)> = BTreeMap::new();
+        for p in self.placed() {
+            if self.hidden.contains(&p.index) {
+                continue;
+            }
+
+            entries
+                .entry((p.shape.sides(), p.shape.fill().to_hex()))
+                .or_insert((p.shape, 0))
+                .1 += 1;
+        }
+        let entries = entries.into_values().collect::<Vec<(Shape, usize)>>();
+
+        let (width, _) = self.size();
+        let scale = match self.period {
+            Some(period) => width as f64 / period.x,
+            None => self.scale,
+        };
+
+        let render = self.render_with(options)?;
+        let legend = render::legend(&render, &entries, options.line_width * scale, options)?;
+
+        // The legend is a new render, so it takes over the tiling's warnings.
+        let legend = legend
+            .with_dpi(self.dpi)
+            .with_warnings(render.warnings().to_vec());
+
+        match options.metadata {
+            true => Ok(legend.with_metadata(self.metadata()?)),
+            false => Ok(legend),
+        }
+    }
+
+    /// Renders the model as configured by options, calling progress with the
+    /// render so far and the percentage of shapes drawn each time another
+    /// whole percent is drawn, and once more with 100 when the render is done.
+    /// Front-ends can show the partial renders as a live preview of very
+    /// large tilings.
+    #[cfg(feature = "cairo")]
+    pub fn render_progressive<F>(&self, options: &RenderOptions, mut progress: F) -> Result<Render>
+    where
+        F: FnMut(&Render, f64) -> Result<()>,
+    {
+        let (surface, context) = self.canvas(options)?;
+        let mut reported = 0;
+        self.draw(&context, options, &mut |drawn, total| {
+            let percent = drawn * 100 / total;
+            if percent > reported && percent < 100 {
+                reported = percent;
+                surface.flush();
+                progress(&self.finish(surface.clone(), options)?, percent as f64)?;
+            }
+
+            Ok(())
+        })?;
+
+        let render = self.finish(surface, options)?;
+        progress(&render, 100.0)?;
+
+        Ok(render)
+    }
+
+    /// Returns a signed distance field of the edges of the model's (repeated)
+    /// tiling, sampled resolution times per model unit over the region the
+    /// model renders (its canvas, or its period in wrap mode).
+    /// Distances outside of the tiling are clamped to one model unit.
+    /// Fails with `InvalidArgument` unless resolution is finite and positive.
+    pub fn render_sdf(&self, resolution: f64) -> Result<DistanceField> {
+        if !resolution.is_finite() || resolution <= 0.0 {
+            return Err(InvalidArgument {
+                name: String::from("resolution"),
+                reason: format!("{} is not a positive number of samples", resolution),
+            });
+        }
+        let (w, h) = match self.period {
+            Some(period) => (period.x, period.y),
+            None => (
+                self.width as f64 / self.scale,
+                self.height as f64 / self.scale,
+            ),
+        };
+        let width = (w * resolution).round().max(1.0) as usize;
+        let height = (h * resolution).round().max(1.0) as usize;
+        let c = self.center();
+
+        let mut values = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let point = Point {
+                    x: c.x + (x as f64 + 0.5) / resolution - w / 2.0,
+                    y: c.y + (y as f64 + 0.5) / resolution - h / 2.0,
+                };
+                values.push(self.signed_distance(point)? as f32);
+            }
+        }
+
+        Ok(DistanceField::new(width, height, resolution, values))
+    }
+
+    /// Returns the distance from point to the nearest edge of the shape
+    /// containing it, or the negated distance to the nearest shape (up to one
+    /// model unit) if no shape contains it.
+    fn signed_distance(&self, point: Point) -> Result<f64> {
+        let mut distance = -CELL_SIZE;
+        for offset in self.offsets() {
+            let point = point + offset;
+            for key in self.grid.nearby(point) {
+                if let Some(p) = self.lookup.get(&key) {
+                    let d = self.resolve(p).signed_distance(point)?;
+                    if d >= 0.0 {
+                        return Ok(d);
+                    }
+                    distance = distance.max(d);
+                }
+            }
+        }
+
+        Ok(distance)
+    }
+
+    /// Renders the region of the model within rect onto a width by height
+    /// canvas as configured by options, stretching the region if the canvas'
+    /// aspect ratio differs from rect's.
+    /// Only the region of the repeated tiling is drawn, so zoomed in renders
+    /// and the tiles of an image pyramid may all be made from one model.
+    /// Regions beyond the model's width and height are left empty unless the
+    /// model wraps.
+    #[cfg(feature = "cairo")]
+    pub fn render_viewport(
+        &self,
+        rect: Rect,
+        width: i32,
+        height: i32,
+        options: &RenderOptions,
+    ) -> Result<Render> {
+        if !(rect.width() > 0.0 && rect.height() > 0.0) {
+            return Err(EmptyViewport {
+                width: rect.width(),
+                height: rect.height(),
+            });
+        }
+
+        let (sx, sy) = (width as f64 / rect.width(), height as f64 / rect.height());
+        let (surface, context) = render::canvas(width, height, sx, options)?;
+        let center = rect.center();
+        context.scale(1.0, sy / sx);
+        context.translate(-center.x, -center.y);
+        self.draw(&context, options, &mut |_, _| Ok(()))?;
+
+        self.finish(surface, options)
+    }
+
+    /// Writes the model to a PNG file at path as configured by options, as
+    /// `render_with` and `Render::write_to_png` would, on a canvas of any
+    /// size.
+    /// Cairo cannot draw on a surface wider or taller than
+    /// `render::MAX_SURFACE_SIZE` pixels, so the canvas is drawn in chunks
+    /// that are stitched into bands of rows and written a band at a time,
+    /// and only one band is ever held in memory. Annotations, which are laid
+    /// out on the whole canvas, are left out.
+    #[cfg(feature = "cairo")]
+    pub fn write_large_png<P: AsRef<Path>>(&self, path: P, options: &RenderOptions) -> Result<()> {
+        let (width, height) = self.size();
+        let mut options = options.clone();
+        options.annotations.clear();
+        let channels = match options.background {
+            Some(_) => 3,
+            None => 4,
+        };
+        let chunk = CHUNK_WIDTH.min(render::MAX_SURFACE_SIZE / options.supersample.max(1) as i32);
+        let rows_per_band = CHUNK_HEIGHT.min(chunk);
+
+        let row = width as usize * channels;
+        render::write_png_bands(
+            path,
+            (width, height),
+            channels,
+            self.dpi,
+            rows_per_band,
+            |y, rows| {
+                let mut band = vec![0; row * rows as usize];
+                for x in (0..width).step_by(chunk as usize) {
+                    let columns = chunk.min(width - x);
+                    let p0 = self.canvas_to_model(x as f64, y as f64);
+                    let p1 = self.canvas_to_model((x + columns) as f64, (y + rows) as f64);
+                    let rect = Rect::new(p0.x, p0.y, p1.x - p0.x, p1.y - p0.y);
+                    let render = self.render_viewport(rect, columns, rows, &options)?;
+                    let pixels = match channels {
+                        3 => render.rgb()?.concat(),
+                        _ => render.as_rgba8()?,
+                    };
+
+                    let span = columns as usize * channels;
+                    for (i, line) in pixels.chunks_exact(span).enumerate() {
+                        let start = i * row + x as usize * channels;
+                        band[start..start + span].copy_from_slice(line);
+                    }
+                }
+
+                Ok(band)
+            },
+        )
+    }
+
+    /// Writes a slippy map style pyramid of tiles of the model to dir, as
+    /// `export_tile_pyramid_with` does with the default options.
+    #[cfg(feature = "cairo")]
+    pub fn export_tile_pyramid<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        levels: u32,
+        tile_px: i32,
+    ) -> Result<()> {
+        self.export_tile_pyramid_with(dir, levels, tile_px, &RenderOptions::new())
+    }
+
+    /// Writes a slippy map style pyramid of tiles of the model, rendered as
+    /// configured by options, to dir as `{z}/{x}/{y}.png`, so very large
+    /// tilings can be explored in viewers such as Leaflet or OpenSeadragon.
+    /// The pyramid covers the square around the model's canvas (or, in wrap
+    /// mode, its period): level z, for each z below levels, splits the square
+    /// into 2^z by 2^z tiles, each tile_px pixels square, numbered from the
+    /// top left.
+    /// Tiles are numbered with 32-bit integers, so more than 31 levels is an
+    /// error, as are no levels, tiles with no pixels, and a model whose square
+    /// has no area.
+    #[cfg(feature = "cairo")]
+    pub fn export_tile_pyramid_with<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        levels: u32,
+        tile_px: i32,
+        options: &RenderOptions,
+    ) -> Result<()> {
+        if levels > MAX_PYRAMID_LEVELS {
+            return Err(TooManyLevels {
+                levels,
+                max: MAX_PYRAMID_LEVELS,
+            });
+        }
+        if levels == 0 {
+            return Err(InvalidArgument {
+                name: String::from("levels"),
+                reason: String::from("a pyramid needs at least one level"),
+            });
+        }
+        if tile_px <= 0 {
+            return Err(InvalidArgument {
+                name: String::from("tile_px"),
+                reason: format!("tiles {} pixels square have no pixels", tile_px),
+            });
+        }
+
+        let ((w, h), c) = (self.extent(), self.center());
+        let side = 2.0 * w.max(h);
+        if !side.is_finite() || side <= 0.0 {
+            return Err(EmptyViewport {
+                width: side,
+                height: side,
+            });
+        }
+        for z in 0..levels {
+            let count: i32 = 1 << z;
+            let size = side / count as f64;
+            for x in 0..count {
+                let column = dir.as_ref().join(z.to_string()).join(x.to_string());
+                fs::create_dir_all(&column)?;
+                for y in 0..count {
+                    let rect = Rect::new(
+                        c.x - side / 2.0 + x as f64 * size,
+                        c.y - side / 2.0 + y as f64 * size,
+                        size,
+                        size,
+                    );
+                    self.render_viewport(rect, tile_px, tile_px, options)?
+                        .write_to_png(column.join(format!("{}.png", y)))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes a guide to assembling the model's tiling from physical tiles: a
+    /// render at path (a PNG file) with each tile on the canvas labeled with
+    /// its number, and a CSV file beside it (path with the extension "csv")
+    /// listing each tile's number, type, number of sides, fill, position, and
+    /// rotation.
+    /// Tiles are numbered by position (top to bottom, then left to right) as
+    /// the shapes of `to_svg` are, so a tiling always numbers its tiles the
+    /// same way. Positions are the tiles' centers in model units (where a
+    /// side has length 1) with the y axis pointing down, and rotations are in
+    /// degrees clockwise from 0 up to 360 / sides, the turn after which a
+    /// regular tile looks the same.
+    #[cfg(feature = "cairo")]
+    pub fn assembly_guide<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let canvas = self.canvas_rect();
+        let mut tiles = self
+            .visible()
+            .into_iter()
+            .map(|p| p.shape)
+            .filter(|s| {
+                let (c, r) = (s.point(), s.circumradius());
+                canvas.intersects(&Rect::new(c.x - r, c.y - r, 2.0 * r, 2.0 * r))
+            })
+            .collect::<Vec<Shape>>();
+        tiles.sort_by(|a, b| {
+            let (a, b) = (a.point(), b.point());
+            (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap_or(Less)
+        });
+
+        let precision = self.precision;
+        let ids = tiles
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.point().key(precision), i))
+            .collect::<HashMap<Key, usize>>();
+        let options = RenderOptions::new().on_shape(move |context, shape, _| {
+            match ids.get(&shape.point().key(precision)) {
+                Some(id) => {
+                    let text = id.to_string();
+                    render::label(context, &text, shape.point(), None, None, shape.fill())
+                }
+                None => Ok(()),
+            }
+        });
+        self.render_with(&options)?.write_to_png(path.as_ref())?;
+
+        let mut csv = String::from("id,type,sides,fill,x,y,rotation\n");
+        for (i, s) in tiles.iter().enumerate() {
+            // Round first, so a turn a hair short of a full one reads as 0,
+            // and drop the sign of -0.
+            let rotation = (s.rotation().to_degrees() * 100.0).round() / 100.0;
+            let rotation = rotation.rem_euclid(360.0 / s.sides() as f64).abs();
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{:.4},{:.4},{:.2}",
+                i,
+                svg::name(s.sides()).0,
+                s.sides(),
+                s.fill().to_hex(),
+                s.point().x,
+                s.point().y,
+                rotation
+            );
+        }
+        fs::write(path.as_ref().with_extension("csv"), csv)?;
+
+        Ok(())
+    }
+
+    /// Draws the model's (repeated) tiling onto context as configured by
+    /// options, calling progress with the number of shapes drawn so far and
+    /// the number to draw after each shape.
+    #[cfg(feature = "cairo")]
+    fn draw(
+        &self,
+        context: &cairo::Context,
+        options: &RenderOptions,
+        progress: &mut dyn FnMut(usize, usize) -> Result<()>,
+    ) -> Result<()> {
+        self.draw_in(context, options, None, progress)
+    }
+
+    /// Draws the model's (repeated) tiling onto context as `draw` does, but
+    /// only the shapes that may overlap a rectangle of region (in model
+    /// units), if given, or else context's clip.
+    /// Repeating leaves a belt of shapes just beyond the canvas, so culling
+    /// them to the clip saves painting shapes that would never be seen.
+    #[cfg(feature = "cairo")]
+    fn draw_in(
+        &self,
+        context: &cairo::Context,
+        options: &RenderOptions,
+        region: Option<&[Rect]>,
+        progress: &mut dyn FnMut(usize, usize) -> Result<()>,
+    ) -> Result<()> {
+        let (x1, y1, x2, y2) = context.clip_extents()?;
+        let clip = [Rect::new(x1, y1, x2 - x1, y2 - y1)];
+        let rects = region.unwrap_or(&clip);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("render", shapes = self.repeated_len()).entered();
+        let no_styles = HashMap::new();
+
+        let mut visible = Vec::new();
+        let placed = match self.is_lazy() {
+            true => self.generated(rects),
+            false => self.visible(),
+        };
+        for p in placed {
+            if self.hidden.contains(&p.index) {
+                continue;
+            }
+            let bounds = self.drawn_bounds(p.index, &p.shape, options)?;
+            if rects.iter().any(|rect| rect.intersects(&bounds)) {
+                visible.push(p);
+            }
+        }
+        self.check_margin(options)?;
+        self.apply_style_set(&mut visible, options)?;
+        let no_paths = HashMap::new();
+        let paint_shape =
+            |index: usize, shape: &Shape, paint: PaintMode, styles: &HashMap<usize, EdgeStyle>| {
+                let paths = self.edge_paths.get(&index).unwrap_or(&no_paths);
+                let fill = match options.jitter {
+                    Some((amplitude, seed)) => {
+                        render::jitter(shape.fill(), shape.point(), amplitude, seed)
+                    }
+                    None => shape.fill(),
+                };
+                shape
+                    .with_colors(fill, shape.stroke())
+                    .with_corner(shape.corner().unwrap_or(options.corner))
+                    .with_paint(paint)
+                    .draw(
+                        context,
+                        shape.margin().unwrap_or(options.margin),
+                        styles,
+                        paths,
+                        options.snap_pixel(),
+                    )
+            };
+        // Each image is read once per render.
+        let mut images = HashMap::new();
+        for overlay in self.overlays.values() {
+            if let Fill::Image(path, _) = overlay {
+                if !images.contains_key(path) {
+                    let invalid = |reason: String| InvalidImage {
+                        path: path.clone(),
+                        reason,
+                    };
+                    let mut file = fs::File::open(path).map_err(|err| invalid(err.to_string()))?;
+                    let image = cairo::ImageSurface::create_from_png(&mut file)
+                        .map_err(|err| invalid(err.to_string()))?;
+                    images.insert(path.clone(), image);
+                }
+            }
+        }
+        let canvas = self.canvas_rect();
+        let paint_overlay = |p: &Placed| -> Result<()> {
+            let overlay = match self.overlays.get(&p.index) {
+                Some(overlay) => overlay,
+                None => return Ok(()),
+            };
+            let points = p
+                .shape
+                .rendered_points(p.shape.margin().unwrap_or(options.margin))?;
+
+            context.save()?;
+            shape::trace(
+                context,
+                &shape::corners(&points, p.shape.corner().unwrap_or(options.corner)),
+            );
+            context.clip();
+            match overlay {
+                Fill::Color(_) => {}
+                Fill::Image(path, fit) => {
+                    let bounds = match fit {
+                        Fit::Canvas => canvas,
+                        Fit::Cover | Fit::Contain => {
+                            let (c, r) = (p.shape.point(), p.shape.circumradius());
+                            Rect::new(c.x - r, c.y - r, 2.0 * r, 2.0 * r)
+                        }
+                    };
+                    let offset = self
+                        .image_offsets
+                        .get(&self.key(p.shape.point()))
+                        .copied()
+                        .unwrap_or_else(Point::origin);
+                    fit.paint(context, &images[path], bounds, offset)?;
+                }
+                Fill::Texture(texture, ink) => texture.paint(context, *ink)?,
+            }
+            context.restore()?;
+
+            Ok(())
+        };
+        let outlined = visible
+            .iter()
+            .any(|p| p.shape.outline().or(options.outline).is_some());
+        if outlined {
+            // Outlines would be covered by the fills of the shapes drawn after
+            // them, so every shape is filled and outlined before any shape is
+            // stroked.
+            for p in visible.iter() {
+                if p.shape.paint().unwrap_or(options.paint).fills() {
+                    paint_shape(p.index, &p.shape, PaintMode::Fill, &no_styles)?;
+                    paint_overlay(p)?;
+                }
+            }
+            for p in visible.iter() {
+                let outline = match p.shape.outline().or(options.outline) {
+                    Some(o) if p.shape.paint().unwrap_or(options.paint).strokes() => o,
+                    _ => continue,
+                };
+                // Hidden edges are not outlined either.
+                let hidden = self
+                    .edge_styles
+                    .get(&p.index)
+                    .unwrap_or(&no_styles)
+                    .iter()
+                    .filter(|(_, s)| s.is_hidden())
+                    .map(|(e, s)| (*e, *s))
+                    .collect::<HashMap<usize, EdgeStyle>>();
+                context.save()?;
+                context.set_line_width(outline.width());
+                paint_shape(
+                    p.index,
+                    &p.shape.with_colors(p.shape.fill(), outline.color()),
+                    PaintMode::Stroke,
+                    &hidden,
+                )?;
+                context.restore()?;
+            }
+        }
+        for (i, p) in visible.iter().enumerate() {
+            let styles = self.edge_styles.get(&p.index).unwrap_or(&no_styles);
+            let paint = p.shape.paint().unwrap_or(options.paint);
+            let overlaid = paint.fills() && self.overlays.contains_key(&p.index);
+            if options.dedup_edges {
+                if !outlined && paint.fills() {
+                    paint_shape(p.index, &p.shape, PaintMode::Fill, &no_styles)?;
+                    paint_overlay(p)?;
+                }
+            } else if !outlined && overlaid {
+                // The overlay goes between the shape's fill and its stroke.
+                paint_shape(p.index, &p.shape, PaintMode::Fill, &no_styles)?;
+                paint_overlay(p)?;
+                if paint.strokes() {
+                    paint_shape(p.index, &p.shape, PaintMode::Stroke, styles)?;
+                }
+            } else if !outlined {
+                paint_shape(p.index, &p.shape, paint, styles)?;
+            } else if paint.strokes() {
+                paint_shape(p.index, &p.shape, PaintMode::Stroke, styles)?;
+            }
+            progress(i + 1, visible.len())?;
+        }
+        if options.dedup_edges {
+            self.stroke_distinct_edges(context, options, &visible)?;
+        }
+        for motif in options.motifs.iter() {
+            for p in visible.iter() {
+                motif.render(
+                    context,
+                    &p.shape,
+                    p.shape.margin().unwrap_or(options.margin),
+                )?;
+            }
+        }
+        let tiles = self.placed_tiles();
+        for offset in self.offsets() {
+            for t in tiles.iter() {
+                t.translated(offset).render_snapped(
+                    context,
+                    options.margin,
+                    options.snap_pixel(),
+                )?;
+            }
+        }
+        if let Some((thickness, color)) = self.border {
+            render::border(context, &self.frame()?, color, thickness)?;
+        }
+        for hook in options.shape_hooks.iter() {
+            for p in visible.iter() {
+                context.save()?;
+                hook(context, &p.shape, p.index)?;
+                context.restore()?;
+            }
+        }
+        if options.debug_edges {
+            for p in visible.iter() {
+                let margin = p.shape.margin().unwrap_or(options.margin);
+                p.shape.debug_edges(context, margin)?;
+            }
+        }
+        let (background, color) = (options.label_background, options.label_color);
+        if options.show_labels {
+            for p in visible.iter() {
+                let margin = p.shape.margin().unwrap_or(options.margin);
+                p.shape.label_edges(context, margin, background, color)?;
+                let label = if self.is_original(p) {
+                    p.index.to_string()
+                } else {
+                    format!("({})", p.index)
+                };
+                let (point, fill) = (p.shape.point(), p.shape.fill());
+                render::label(context, &label, point, background, color, fill)?;
+            }
+        }
+        if let Some(label) = options.vertex_labels {
+            for p in visible.iter() {
+                let margin = p.shape.margin().unwrap_or(options.margin);
+                p.shape
+                    .label_vertices(context, margin, label, background, color)?;
+            }
+        }
+        render::annotate(context, options)?;
+
+        Ok(())
+    }
+
+    /// Strokes every distinct edge of the shapes of placed that are stroked
+    /// once, as `RenderOptions::dedup_edges` configures, joining the edges of
+    /// each stroke and dash pattern into as few lines as possible.
+    #[cfg(feature = "cairo")]
+    fn stroke_distinct_edges(
+        &self,
+        context: &cairo::Context,
+        options: &RenderOptions,
+        placed: &[Placed],
+    ) -> Result<()> {
+        let no_styles = HashMap::new();
+        let mut seen = HashSet::new();
+        let mut pens: Vec<(Pen, Vec<(Point, Point)>)> = Vec::new();
+        for p in placed {
+            if !p.shape.paint().unwrap_or(options.paint).strokes() {
+                continue;
+            }
+
+            let styles = self.edge_styles.get(&p.index).unwrap_or(&no_styles);
+            let margin = p.shape.margin().unwrap_or(options.margin);
+            for (i, e) in p.shape.rendered_points(margin)?.windows(2).enumerate() {
+                let style = styles.get(&i).copied().unwrap_or_default();
+                let (k0, k1) = (self.key(e[0]), self.key(e[1]));
+                // An edge hidden by one shape is still stroked by the other.
+                if style.is_hidden() || !seen.insert((k0.min(k1), k0.max(k1))) {
+                    continue;
+                }
+
+                let (stroke, dashes) = (style.stroke().unwrap_or(p.shape.stroke()), style.dashes());
+                let same = |(c, d): &Pen| {
+                    (c.red(), c.green(), c.blue()) == (stroke.red(), stroke.green(), stroke.blue())
+                        && *d == dashes
+                };
+                match pens.iter_mut().find(|(pen, _)| same(pen)) {
+                    Some((_, edges)) => edges.push((e[0], e[1])),
+                    None => pens.push(((stroke, dashes), vec![(e[0], e[1])])),
+                }
+            }
+        }
+
+        for ((stroke, dashes), edges) in pens {
+            context.save()?;
+            if let Some((on, off)) = dashes {
+                context.set_dash(&[on, off], 0.0);
+            }
+            for line in plot::chain(&edges, self.precision) {
+                shape::trace_snapped(context, &line, options.snap_pixel());
+            }
+            let (r, g, b) = stroke.rgb_unit_int();
+            context.set_source_rgb(r, g, b);
+            context.stroke()?;
+            context.restore()?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the model once for each style in styles.
+    /// Each shape's geometry is computed once and shared by every render, which
+    /// is much faster than calling `render` once per color scheme.
+    /// A style's fills and stroke replace the shapes' colors, but the shapes
+    /// keep their own margins, corners, paint modes, outlines, transforms,
+    /// opacity, blend modes, edge styles, and deformed edges.
+    #[cfg(feature = "cairo")]
+    pub fn render_variants(&self, margin: f64, styles: &[RenderStyle]) -> Result<Vec<Render>> {
+        let (no_styles, no_paths) = (HashMap::new(), HashMap::new());
+        let traced = self
+            .visible()
+            .into_iter()
+            .map(|p| {
+                let s = p.shape;
+                let paths = self.edge_paths.get(&p.index).unwrap_or(&no_paths);
+                let (outline, edges) = s.traced(s.margin().unwrap_or(margin), paths)?;
+                let styles = self.edge_styles.get(&p.index).unwrap_or(&no_styles);
+
+                Ok((s, outline, edges, styles))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let outlined = traced.iter().any(|(s, ..)| s.outline().is_some());
+
+        styles
+            .iter()
+            .map(|style| {
+                let options = RenderOptions::new()
+                    .background(style.background)
+                    .line_width(style.line_width);
+                let (surface, context) = self.canvas(&options)?;
+                let restyled = traced.iter().map(|(s, outline, edges, styles)| {
+                    let fill = *style.fills.get(&s.sides()).unwrap_or(&s.fill());
+                    let stroke = style.stroke.unwrap_or_else(|| s.stroke());
+                    let paint = s.paint().unwrap_or(style.paint);
+
+                    (s.with_colors(fill, stroke), paint, outline, edges, *styles)
+                });
+                if outlined {
+                    // As in `render`, every shape is filled and outlined
+                    // before any shape is stroked.
+                    for (s, paint, outline, edges, _) in restyled.clone() {
+                        if paint.fills() {
+                            let s = s.with_paint(PaintMode::Fill);
+                            s.render_traced(&context, outline, edges, &no_styles)?;
+                        }
+                    }
+                    for (s, paint, outline, edges, styles) in restyled.clone() {
+                        let o = match s.outline() {
+                            Some(o) if paint.strokes() => o,
+                            _ => continue,
+                        };
+                        let hidden = styles
+                            .iter()
+                            .filter(|(_, s)| s.is_hidden())
+                            .map(|(e, s)| (*e, *s))
+                            .collect::<HashMap<usize, EdgeStyle>>();
+                        let s = s
+                            .with_colors(s.fill(), o.color())
+                            .with_paint(PaintMode::Stroke);
+                        context.save()?;
+                        context.set_line_width(o.width());
+                        s.render_traced(&context, outline, edges, &hidden)?;
+                        context.restore()?;
+                    }
+                }
+                for (s, paint, outline, edges, styles) in restyled {
+                    let paint = match outlined {
+                        true if paint.strokes() => PaintMode::Stroke,
+                        true => continue,
+                        false => paint,
+                    };
+                    s.with_paint(paint)
+                        .render_traced(&context, outline, edges, styles)?;
+                }
+
+                Ok(Render::new(surface).with_dpi(self.dpi))
+            })
+            .collect()
+    }
+
+    /// Renders the model's dual tiling as configured by options with cairo,
+    /// as `render_dual` renders it on a `CairoBackend`.
+    /// Cells too small for the dual margin are skipped and reported by
+    /// `Render::warnings`.
+    #[cfg(feature = "cairo")]
+    pub fn render_dual_with(
+        &self,
+        fill: Color,
+        stroke: Color,
+        options: &RenderOptions,
+    ) -> Result<Render> {
+        let (surface, context) = self.canvas(options)?;
+        let shapes = self.dual(fill, stroke)?;
+
+        let mut warnings = Vec::new();
+        for (index, s) in shapes.into_iter().enumerate() {
+            if s.collapses(options.cell_margin()?)? {
+                warnings.push(Warning::CollapsedCell {
+                    index,
+                    margin: options.cell_margin()?,
+                });
+                continue;
+            }
+
+            s.with_corner(options.corner)
+                .with_paint(options.paint)
+                .render_snapped(&context, options.cell_margin()?, options.snap_pixel())?;
+        }
+
+        Ok(self.finish(surface, options)?.with_warnings(warnings))
+    }
+
+    /// Renders the geometry t of the way from the model's (repeated) tiling
+    /// to its dual (see `morph_to_dual`) as configured by options.
+    /// The shapes are inset by the margin of options, which is checked as
+    /// `render_with` checks it, and the cells of the dual by its dual margin,
+    /// as `render_dual_with` insets them. Polygons too small for their margin
+    /// are skipped, with a warning for each cell of the dual.
+    #[cfg(feature = "cairo")]
+    pub fn render_morph(
+        &self,
+        t: f64,
+        fill: Color,
+        stroke: Color,
+        options: &RenderOptions,
+    ) -> Result<Render> {
+        self.check_margin(options)?;
+        let (surface, context) = self.canvas(options)?;
+        let (shapes, cells) = self.morphed(t, fill, stroke)?;
+
+        for s in shapes {
+            if s.collapses(options.margin)? {
+                continue;
+            }
+
+            s.with_corner(options.corner)
+                .with_paint(options.paint)
+                .render_snapped(&context, options.margin, options.snap_pixel())?;
+        }
+        let mut warnings = Vec::new();
+        for (index, s) in cells.into_iter().enumerate() {
+            if s.collapses(options.cell_margin()?)? {
+                warnings.push(Warning::CollapsedCell {
+                    index,
+                    margin: options.cell_margin()?,
+                });
+                continue;
+            }
+
+            s.with_corner(options.corner)
+                .with_paint(options.paint)
+                .render_snapped(&context, options.cell_margin()?, options.snap_pixel())?;
+        }
+
+        Ok(self.finish(surface, options)?.with_warnings(warnings))
+    }
+
+    /// Renders frames evenly spaced frames of the morph from the model's
+    /// (repeated) tiling to its dual, as `render_morph` renders each, from
+    /// the tiling itself to the dual itself.
+    #[cfg(feature = "cairo")]
+    pub fn render_morph_frames(
+        &self,
+        frames: usize,
+        fill: Color,
+        stroke: Color,
+        options: &RenderOptions,
+    ) -> Result<Vec<Render>> {
+        (0..frames)
+            .map(|i| {
+                let t = match frames {
+                    1 => 0.0,
+                    _ => i as f64 / (frames - 1) as f64,
+                };
+                self.render_morph(t, fill, stroke, options)
+            })
+            .collect()
+    }
+
+    /// Renders the model's dual tiling as configured by options, stroking each
+    /// of its edges in the color that color returns for the two shapes of the
+    /// (repeated) tiling whose centers the edge joins, so edges can be styled
+    /// by the polygons they join (such as hexagon to triangle).
+    /// Each edge is stroked once, between the centers, with the shapes passed
+    /// in a fixed order; edges that do not join two centers (where the dual is
+    /// clipped to the canvas) are stroked in stroke.
+    #[cfg(feature = "cairo")]
+    pub fn render_dual_edges<F>(
+        &self,
+        fill: Color,
+        stroke: Color,
+        options: &RenderOptions,
+        color: F,
+    ) -> Result<Render>
+    where
+        F: Fn(&Shape, &Shape) -> Color,
+    {
+        let (surface, context) = self.canvas(options)?;
+        let shapes = self
+            .dual_shapes()
+            .into_iter()
+            .map(|s| (self.key(s.point()), s))
+            .collect::<HashMap<Key, Shape>>();
+
+        let mut seen = HashSet::new();
+        let mut pens: Vec<(Color, Vec<(Point, Point)>)> = Vec::new();
+        for cell in self.dual(fill, stroke)? {
+            if options.paint.fills() {
+                cell.clone()
+                    .with_corner(options.corner)
+                    .with_paint(PaintMode::Fill)
+                    .render(&context, options.cell_margin()?)?;
+            }
+            if !options.paint.strokes() {
+                continue;
+            }
+
+            for e in cell.points(0.0)?.windows(2) {
+                let (k0, k1) = (self.key(e[0]), self.key(e[1]));
+                if !seen.insert((k0.min(k1), k0.max(k1))) {
+                    continue;
+                }
+
+                let pen = match (shapes.get(&k0.min(k1)), shapes.get(&k0.max(k1))) {
+                    (Some(a), Some(b)) => color(a, b),
+                    _ => stroke,
+                };
+                let same = |c: &Color| {
+                    (c.red(), c.green(), c.blue()) == (pen.red(), pen.green(), pen.blue())
+                };
+                match pens.iter_mut().find(|(c, _)| same(c)) {
+                    Some((_, edges)) => edges.push((e[0], e[1])),
+                    None => pens.push((pen, vec![(e[0], e[1])])),
+                }
+            }
+        }
+
+        for (pen, edges) in pens {
+            for line in plot::chain(&edges, self.precision) {
+                shape::trace(&context, &line);
+            }
+            let (r, g, b) = pen.rgb_unit_int();
+            context.set_source_rgb(r, g, b);
+            context.stroke()?;
+        }
+
+        self.finish(surface, options)
+    }
+
+    /// Renders the model as configured by options, then strokes the cells of
+    /// the Voronoi diagram of its shapes' centers, as returned by
+    /// `voronoi_of_centers`, over it in stroke, so the two can be compared.
+    #[cfg(feature = "cairo")]
+    pub fn render_voronoi(&self, stroke: Color, options: &RenderOptions) -> Result<Render> {
+        let (surface, context) = self.canvas(options)?;
+        self.draw(&context, options, &mut |_, _| Ok(()))?;
+
+        for cell in self.voronoi_of_centers(stroke, stroke) {
+            cell.with_corner(options.corner)
+                .with_paint(PaintMode::Stroke)
+                .render(&context, 0.0)?;
+        }
+
+        self.finish(surface, options)
+    }
+
+    /// Renders maze, carved through the model by `maze`, as configured by
+    /// options: each cell is filled with its shape's fill and the maze's
+    /// walls are stroked in wall.
+    #[cfg(feature = "cairo")]
+    pub fn render_maze(&self, maze: &Maze, wall: Color, options: &RenderOptions) -> Result<Render> {
+        let (surface, context) = self.canvas(options)?;
+        let tiles = self.tile_cells();
+        for cell in maze.cells() {
+            if let Some(p) = self.lookup.get(&cell.0) {
+                self.resolve(p)
+                    .with_paint(PaintMode::Fill)
+                    .render(&context, options.margin)?;
+            } else if let Some(t) = tiles.get(&cell.0) {
+                t.render(&context, options.margin)?;
+            }
+        }
+
+        let (r, g, b) = wall.rgb_unit_int();
+        context.set_source_rgb(r, g, b);
+        for (p0, p1) in maze.walls() {
+            context.move_to(p0.x, p0.y);
+            context.line_to(p1.x, p1.y);
+        }
+        context.stroke()?;
+
+        self.finish(surface, options)
+    }
+
+    /// Renders the model's dual tiling on backend as configured by the
+    /// backend's options, filling its cells with fill and stroking them with
+    /// stroke, as `render` renders the model, and returns the backend's
+    /// output.
+    /// Cells that collapse under the dual margin are left out.
+    pub fn render_dual<B: RenderBackend>(
+        &self,
+        mut backend: B,
+        fill: Color,
+        stroke: Color,
+    ) -> Result<B::Output> {
+        #[cfg(feature = "cairo")]
+        if let Some(cairo) = backend.as_cairo() {
+            let options = cairo.options().clone();
+            cairo.rendered(self.render_dual_with(fill, stroke, &options)?);
+
+            return backend.finish();
+        }
+
+        let options = backend.options().clone();
+        let (width, height, (sx, _)) = self.pixels();
+        let line_width = options.line_width * sx;
+        let dash = options.dash.iter().map(|d| d * sx).collect::<Vec<f64>>();
+        let pixels = |points: &[Point]| {
+            points
+                .iter()
+                .map(|p| {
+                    let (x, y) = self.model_to_canvas(*p);
+                    Point { x, y }
+                })
+                .collect::<Vec<Point>>()
+        };
+
+        backend.begin(width, height, options.background)?;
+        for cell in self.dual(fill, stroke)? {
+            if cell.collapses(options.cell_margin()?)? {
+                continue;
+            }
+
+            let corners = shape::corners(&cell.points(options.cell_margin()?)?, options.corner);
+            let points = pixels(&corners);
+            if options.paint.fills() {
+                backend.fill(&points, fill)?;
+            }
+            if options.paint.strokes() {
+                // Strokes, but not fills, are snapped to the centers of
+                // pixels.
+                let points = match options.pixel_snap {
+                    true => points
+                        .iter()
+                        .map(|p| Point {
+                            x: shape::pixel_center(p.x, 1.0),
+                            y: shape::pixel_center(p.y, 1.0),
+                        })
+                        .collect(),
+                    false => points,
+                };
+                backend.stroke(&points, stroke, line_width, &dash)?;
+            }
+        }
+
+        backend.finish()
+    }
+
+    /// Renders the model's dual tiling as configured by options with
+    /// tiny-skia instead of cairo, drawing what `render_dual` draws.
+    #[cfg(feature = "skia")]
+    pub fn render_dual_skia(
+        &self,
+        fill: Color,
+        stroke: Color,
+        options: &RenderOptions,
+    ) -> Result<SkiaRender> {
+        self.render_dual(SkiaBackend::new(options), fill, stroke)
+    }
+}
+
+/// Returns each vertex of shapes, keyed at precision, merging vertices that
+/// lie within 10^-precision of each other, with its incident shapes sorted
+/// clockwise around the vertex.
+fn vertexes<'a, I>(shapes: I, precision: i32) -> Result<Vec<(Key, Incidence)>>
+where
+    I: IntoIterator<Item = &'a Shape>,
+{
+    // Each corner is recorded as its shape and its neighboring corners, and
+    // shapes are copied into their vertexes only once the corners are
+    // grouped.
+    let shapes = shapes.into_iter().collect::<Vec<&Shape>>();
+    let mut clusters = Clusters::new(math::powi(10.0, -precision));
+    let mut vertexes: Vec<(Point, Vec<Incident>)> = Vec::new();
+    for (index, s) in shapes.iter().enumerate() {
+        let points = s.points(0.0)?;
+        let corners = &points[0..points.len() - 1];
+        let n = corners.len();
+        for (i, p) in corners.iter().enumerate() {
+            let cluster = clusters.insert(*p);
+            if cluster == vertexes.len() {
+                vertexes.push((*p, Vec::new()));
+            }
+            let ends = (corners[(i + n - 1) % n], corners[(i + 1) % n]);
+            vertexes[cluster].1.push((index, ends.0, ends.1));
+        }
+    }
+
+    let vertexes = vertexes
+        .into_iter()
+        .map(|(p, mut incident)| {
+            let angle = |i: usize| {
+                let c = shapes[i].point();
+                math::atan2(c.y - p.y, c.x - p.x)
+            };
+            incident.sort_by(|a, b| angle(b.0).partial_cmp(&angle(a.0)).unwrap_or(Less));
+            let incidence = (
+                p,
+                incident.iter().map(|(i, _, _)| *shapes[*i]).collect(),
+                incident.iter().map(|(_, a, b)| (*a, *b)).collect(),
+            );
+
+            (p.key(precision), incidence)
+        })
+        .collect();
+
+    Ok(vertexes)
+}
+
+/// Returns, for each of points, the number of shapes with an edge that passes
+/// through the point between its ends (within tolerance), as edges do at the
+/// vertices of tilings that are not edge-to-edge.
+fn crossings<'a, I>(shapes: I, points: &[Point], tolerance: f64) -> Result<Vec<usize>>
+where
+    I: IntoIterator<Item = &'a Shape>,
+{
+    // Edges are bucketed by the unit cells their bounding boxes overlap, so
+    // each point is only tested against the edges near it.
+    let cell = |x: f64| x.floor() as i64;
+    let mut buckets: HashMap<(i64, i64), Vec<(Point, Point)>> = HashMap::new();
+    for s in shapes {
+        for w in s.points(0.0)?.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            for x in cell(a.x.min(b.x) - tolerance)..=cell(a.x.max(b.x) + tolerance) {
+                for y in cell(a.y.min(b.y) - tolerance)..=cell(a.y.max(b.y) + tolerance) {
+                    buckets.entry((x, y)).or_default().push((a, b));
+                }
+            }
+        }
+    }
+
+    let crossings = points
+        .iter()
+        .map(|p| {
+            buckets.get(&(cell(p.x), cell(p.y))).map_or(0, |edges| {
+                edges
+                    .iter()
+                    .filter(|(a, b)| {
+                        p.distance(*a) > tolerance
+                            && p.distance(*b) > tolerance
+                            && distance_to_segment(*p, *a, *b) <= tolerance
+                    })
+                    .count()
+            })
+        })
+        .collect();
+
+    Ok(crossings)
+}
+
+/// Returns the distinct points among points, merging those within tolerance
+/// of each other, each with the number of points merged into it.
+fn merge<I>(points: I, tolerance: f64) -> Vec<(Point, usize)>
+where
+    I: IntoIterator<Item = Point>,
+{
+    let mut merged: Vec<(Point, usize)> = Vec::new();
+    for p in points {
+        match merged.iter_mut().find(|(q, _)| q.distance(p) <= tolerance) {
+            Some((_, count)) => *count += 1,
+            None => merged.push((p, 1)),
+        }
+    }
+
+    merged
+}
+
+/// Returns the loop of loops that encloses the most area, wound as a shape's
+/// points are, or no points if there are no loops.
+fn outer(loops: Vec<Vec<Point>>) -> Vec<Point> {
+    let mut outer = loops
+        .into_iter()
+        .max_by(|a, b| {
+            let (a, b) = (irregular::area(a).abs(), irregular::area(b).abs());
+            a.partial_cmp(&b).unwrap_or(Equal)
+        })
+        .unwrap_or_default();
+    if irregular::area(&outer) < 0.0 {
+        outer.reverse();
+    }
+
+    outer
+}
+
+/// Returns rings, the outline of a polygon followed by its holes, wound as a
+/// shape's points are, with its holes wound the other way.
+fn oriented(mut rings: Vec<Vec<Point>>) -> Vec<Vec<Point>> {
+    for (i, ring) in rings.iter_mut().enumerate() {
+        if (irregular::area(ring) < 0.0) == (i == 0) {
+            ring.reverse();
+        }
+    }
+
+    rings
+}
+
+/// Returns the mean of the closed polygon points' vertices.
+fn centroid(points: &[Point]) -> Point {
+    let vertices = &points[0..points.len() - 1];
+    let n = vertices.len() as f64;
+
+    Point {
+        x: vertices.iter().map(|p| p.x).sum::<f64>() / n,
+        y: vertices.iter().map(|p| p.y).sum::<f64>() / n,
+    }
+}
+
+/// Returns point translated by a whole number of periods into the period
+/// centered on the origin.
+fn wrap(point: Point, period: Point) -> Point {
+    let wrap = |n: f64, size: f64| n - size * (n / size + 0.5 + shape::EPSILON).floor();
+
+    Point {
+        x: wrap(point.x, period.x),
+        y: wrap(point.y, period.y),
+    }
+}
+
+/// Returns the coordinates of point in basis, rounded to precision decimal
+/// digits so that points on the lattice have whole coordinates.
+fn coordinates(point: Point, basis: (Point, Point), precision: i32) -> (f64, f64) {
+    let (b1, b2) = basis;
+    let det = b1.x * b2.y - b1.y * b2.x;
+    let round = |n: f64| (n * math::powi(10.0, precision)).round() / math::powi(10.0, precision);
+
+    (
+        round((point.x * b2.y - point.y * b2.x) / det),
+        round((b1.x * point.y - b1.y * point.x) / det),
+    )
+}
+
+/// Strokes path, the outline through edges with its corners cut, on backend,
+/// width pixels wide, in the color and dashes that pen returns for each
+/// edge's style, or if any of its edges has a style in styles, strokes each
+/// of edges on its own, leaving out its hidden edges.
+fn stroke_edges<B: RenderBackend>(
+    backend: &mut B,
+    path: &[Point],
+    edges: &[Vec<Point>],
+    styles: &HashMap<usize, EdgeStyle>,
+    pen: &dyn Fn(&EdgeStyle) -> (Color, Vec<f64>),
+    width: f64,
+) -> Result<()> {
+    if styles.is_empty() {
+        let (color, dash) = pen(&EdgeStyle::default());
+
+        return backend.stroke(path, color, width, &dash);
+    }
+
+    for (i, e) in edges.iter().enumerate() {
+        let style = styles.get(&i).copied().unwrap_or_default();
+        if style.is_hidden() {
+            continue;
+        }
+
+        let (color, dash) = pen(&style);
+        backend.stroke(e, color, width, &dash)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IsohedralTiling, IsohedralType, PenroseKind, PenroseRhombus, Style};
+
+    /// Returns a 3.4.6.4 model, built as the README's introduction builds it,
+    /// and the range of its seed hexagons.
+    fn intro() -> Result<(Model, Range<usize>)> {
+        let (fill, stroke) = (Color::new(242, 194, 106)?, Color::new(242, 60, 60)?);
+        let mut model = Model::new(512, 512, 64.0);
+        model.add(Shape::new(6, fill, stroke)?)?;
+        let squares = model.add_multi(0..1, 0..6, Shape::new(4, fill, stroke)?)?;
+        model.add_multi(squares.clone(), 1..2, Shape::new(3, fill, stroke)?)?;
+        let hexagons = model.add_multi(squares, 2..3, Shape::new(6, fill, stroke)?)?;
+
+        Ok((model, hexagons))
+    }
+
+    #[test]
+    fn surrounded_vertex_closes() -> Result<()> {
+        let (model, _) = intro()?;
+        // Each corner of the first hexagon is surrounded by the hexagon, two
+        // squares, and a triangle, even before the pattern is repeated.
+        let corners = model.shapes.at(0).points(0.0)?;
+        let figures = model.vertex_configurations()?;
+        for corner in corners.iter() {
+            let figure = figures
+                .iter()
+                .find(|f| f.point().distance(*corner) < 1e-6)
+                .expect("corner is a vertex");
+            assert!(figure.closes());
+            assert_eq!(figure.configuration(), "3.4.6.4");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_vertices_close() -> Result<()> {
+        let (mut model, hexagons) = intro()?;
+        model.repeat(hexagons)?;
+
+        let figures = model.vertex_configurations()?;
+        assert!(!figures.is_empty());
+        assert!(figures.iter().all(|f| f.closes()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn vertex_on_edge_closes() -> Result<()> {
+        // Squares laid like bricks, each row offset by half a square, meet
+        // where a corner touches the middle of another square's edge.
+        let color = Color::new(0, 0, 0)?;
+        let square = Shape::new(4, color, color)?;
+        let mut model = Model::new(256, 256, 32.0);
+        model.add(square)?;
+        model.attach(0, 0, square)?;
+        model.attach_at(0, 1, 0.5, square)?;
+        model.attach(0, 2, square)?;
+        model.attach_at(0, 3, 0.5, square)?;
+        model.repeat(1..5)?;
+
+        let figures = model.vertex_configurations()?;
+        assert!(!figures.is_empty());
+        assert!(figures.iter().all(|f| f.closes()));
+        assert!(figures.iter().all(|f| f.configuration() == "4.4"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn boundary_keeps_pinched_pieces_apart() -> Result<()> {
+        // Squares on adjacent edges of a square meet only at its corner.
+        let color = Color::new(0, 0, 0)?;
+        let square = Shape::new(4, color, color)?;
+        let mut model = Model::new(256, 256, 32.0);
+        model.add(square)?;
+        model.attach(0, 0, square)?;
+        model.attach(0, 1, square)?;
+
+        let mut rings = Vec::new();
+        for s in model.shapes.iter().skip(1) {
+            rings.extend(oriented(vec![s.points(0.0)?]));
+        }
+        let loops = model.outlines(rings)?;
+        assert_eq!(loops.len(), 2);
+        assert!(loops.iter().all(|l| l.len() == 4));
+        assert!(loops.iter().all(|l| irregular::area(l) > 0.0));
+        assert_eq!(model.boundary()?.len(), 8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn boundary_splits_edges_at_corners() -> Result<()> {
+        // A square attached halfway along an edge has a corner in the middle
+        // of the first square's side, so the two outline one hexagon.
+        let color = Color::new(0, 0, 0)?;
+        let square = Shape::new(4, color, color)?;
+        let mut model = Model::new(256, 256, 32.0);
+        model.add(square)?;
+        model.attach_at(0, 0, 0.5, square)?;
+
+        let boundary = model.boundary()?;
+        assert_eq!(boundary.len(), 8);
+        assert!((irregular::area(&boundary) - 2.0).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn unclosed_boundary_is_an_error() -> Result<()> {
+        // A shape counted twice cancels its own edges, leaving its neighbor's
+        // other edges open at the ends of the edge they share.
+        let color = Color::new(0, 0, 0)?;
+        let square = Shape::new(4, color, color)?;
+        let mut model = Model::new(256, 256, 32.0);
+        model.add(square)?;
+        model.attach(0, 0, square)?;
+
+        let rings = [model.shapes.at(0), model.shapes.at(0), model.shapes.at(1)]
+            .iter()
+            .map(|s| s.points(0.0))
+            .collect::<Result<Vec<Vec<Point>>>>()?;
+        match model.outlines(rings) {
+            Err(OpenBoundary { .. }) => Ok(()),
+            r => panic!("expected an open boundary, got {:?}", r),
+        }
+    }
+
+    #[test]
+    fn placed_pattern_matches_intro() -> Result<()> {
+        let (intro, hexagons) = intro()?;
+        let (fill, stroke) = (Color::new(242, 194, 106)?, Color::new(242, 60, 60)?);
+        let (pattern, seeds) = crate::catalog::pattern("3.4.6.4", &[fill; 3], stroke)?;
+        let mut model = Model::new(512, 512, 64.0);
+        let placed = model.place(&pattern)?;
+
+        assert_eq!(placed, 0..intro.shapes.len());
+        assert_eq!(seeds, hexagons);
+        for (a, b) in model.shapes.iter().zip(intro.shapes.iter()) {
+            assert_eq!(a.sides(), b.sides());
+            assert!(a.point().distance(b.point()) < 1e-9);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn undo_restores_placements() -> Result<()> {
+        let (mut model, hexagons) = intro()?;
+        let (len, placed) = (model.len(), model.repeated_len());
+        let keys = model.lookup.keys().copied().collect::<Vec<Key>>();
+        model.repeat(hexagons)?;
+        assert!(model.repeated_len() > placed);
+
+        assert!(model.undo());
+        assert_eq!(model.repeated_len(), placed);
+        assert_eq!(model.lookup.keys().copied().collect::<Vec<Key>>(), keys);
+        assert!(model.translations.is_empty());
+
+        assert!(model.undo());
+        assert_eq!(model.len(), len - 6);
+        assert_eq!(model.repeated_len(), len - 6);
+        assert!(model.shape_at(model.shapes.point(0)).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn undo_and_redo_after_a_transform() -> Result<()> {
+        let (mut model, hexagons) = intro()?;
+        let (len, placed) = (model.len(), model.repeated_len());
+        model.repeat(hexagons)?;
+        let repeated = model.repeated_len();
+        let center = model.shapes.point(0);
+        model.translate(Point { x: 0.5, y: 0.25 });
+        model.reflect(Axis::vertical());
+
+        assert!(model.undo());
+        assert!(model.undo());
+        assert_eq!(model.repeated_len(), repeated);
+        assert_eq!(model.shapes.point(0), center);
+        assert!(model.undo());
+        assert_eq!(model.repeated_len(), placed);
+
+        assert!(model.redo()?);
+        assert!(model.redo()?);
+        assert_eq!(model.len(), len);
+        assert_eq!(model.repeated_len(), repeated);
+        assert!(
+            model
+                .shapes
+                .point(0)
+                .distance(center + Point { x: 0.5, y: 0.25 })
+                < 1e-9
+        );
+        assert_eq!(model.history().len(), 6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn failed_operation_rolls_back() -> Result<()> {
+        let color = Color::new(0, 0, 0)?;
+        let square = Shape::new(4, color, color)?;
+        let mut model = Model::new(256, 256, 32.0);
+        model.add(square)?;
+        model.attach(0, 0, square)?;
+        let added = Arc::new(Mutex::new(0));
+        let counter = added.clone();
+        model.on_change(move |c| {
+            if let Change::Added { .. } = c {
+                *counter.lock().unwrap() += 1;
+            }
+        });
+
+        // Squares are attached to edges 1 to 3 before edge 4 is found
+        // missing.
+        assert!(model.add_multi(0..1, 1..5, square).is_err());
+        assert_eq!(model.len(), 2);
+        assert_eq!(model.repeated_len(), 2);
+        assert_eq!(model.history().len(), 2);
+        assert_eq!(*added.lock().unwrap(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn too_many_shapes_rolls_back_repeat() -> Result<()> {
+        let (model, hexagons) = intro()?;
+        let mut model = model.with_max_shapes(40);
+        let placed: Vec<Key> = model.lookup.keys().copied().collect();
+
+        let err = model.repeat(hexagons).unwrap_err();
+        assert!(matches!(err.root(), TooManyShapes { max: 40, .. }));
+        assert_eq!(model.repeated_len(), placed.len());
+        assert_eq!(model.lookup.keys().copied().collect::<Vec<_>>(), placed);
+        assert!(model.translations.is_empty());
+        assert_eq!(model.history().len(), 4);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bounded_repeats_stop_at_the_shape_limit() -> Result<()> {
+        let (mut model, hexagons) = intro()?;
+        let err = model.repeat_radius(hexagons.clone(), 1e9).unwrap_err();
+        assert!(matches!(err.root(), TooManyShapes { .. }));
+        let err = model.repeat_n(hexagons.clone(), usize::MAX).unwrap_err();
+        assert!(matches!(err.root(), TooManyShapes { .. }));
+        assert!(model.generators.is_empty());
+
+        model.repeat_radius(hexagons, 4.0)?;
+        assert!(model.repeated_len() > model.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn repeat_vectors_must_span_the_plane() -> Result<()> {
+        let (mut model, _) = intro()?;
+        let (a, b) = (Point { x: 2.0, y: 0.0 }, Point { x: 1.0, y: 1.5 });
+        let invalid = [
+            vec![],
+            vec![a],
+            vec![a, Point { x: -4.0, y: 0.0 }],
+            vec![a, Point::origin()],
+            vec![
+                a,
+                Point {
+                    x: f64::NAN,
+                    y: 1.0,
+                },
+            ],
+        ];
+        for vectors in invalid.iter() {
+            let err = model.repeat_by(vectors, 2).unwrap_err();
+            assert!(matches!(err.root(), InvalidRepeatVectors { .. }));
+        }
+        assert!(model.generators.is_empty());
+
+        model.repeat_by(&[a, b], 2)?;
+        assert!(model.repeated_len() > model.len());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn tile_pyramids_are_validated_before_rendering() -> Result<()> {
+        let (model, _) = intro()?;
+        let dir = std::env::temp_dir().join("tile_pyramids_are_validated");
+        for (levels, tile_px) in [(0, 256), (2, 0), (2, -256)] {
+            assert!(matches!(
+                model.export_tile_pyramid(&dir, levels, tile_px),
+                Err(InvalidArgument { .. })
+            ));
+        }
+        assert!(!dir.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn distance_fields_need_a_positive_resolution() -> Result<()> {
+        let (model, _) = intro()?;
+        for resolution in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                model.render_sdf(resolution),
+                Err(InvalidArgument { .. })
+            ));
+        }
+        let field = model.render_sdf(0.5)?;
+        assert!(field.width() > 1 && field.height() > 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn tiles_are_cells_of_the_tiling() -> Result<()> {
+        let color = Color::new(0, 0, 0)?;
+        let square = Shape::new(4, color, color)?;
+        let mut pair = Model::new(256, 256, 32.0);
+        pair.add(square)?;
+        pair.attach(0, 0, square)?;
+        let tile = crate::IrregularShape::new(&pair.shapes.at(1), color, color)?;
+
+        let mut model = Model::new(256, 256, 32.0);
+        model.add_tile(tile);
+        assert!(!model.is_empty());
+        assert_eq!((model.len(), model.repeated_len()), (0, 1));
+        model.add(square)?;
+
+        let cells = model.cells();
+        assert_eq!(cells.len(), 2);
+        let shape = model.cell_at(square.point()).expect("square is a cell");
+        let tile = cells.iter().find(|c| **c != shape).copied();
+        assert_eq!(
+            model.neighbors(shape)?,
+            tile.into_iter().collect::<Vec<_>>()
+        );
+        assert!(model
+            .to_svg(&RenderOptions::default())?
+            .contains(r#"id="tile-0""#));
+        // The tile's edges are drawn along with the square's, the edge they
+        // share once.
+        let length = model
+            .to_polylines()?
+            .iter()
+            .flat_map(|l| l.windows(2))
+            .map(|w| w[0].distance(w[1]))
+            .sum::<f64>();
+        assert!((length - 7.0).abs() < 1e-6);
+        let area = irregular::area(&model.boundary()?);
+        assert!((area - 2.0).abs() < 1e-6);
+
+        let path = std::env::temp_dir().join("tiles_are_cells_of_the_tiling.tiling");
+        model.save(&path)?;
+        let loaded = Model::load(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(loaded.tiles().len(), 1);
+        assert_eq!(loaded.cells(), cells);
+
+        Ok(())
+    }
+
+    #[test]
+    fn tile_cells_are_painted() -> Result<()> {
+        let color = Color::new(0, 0, 0)?;
+        let square = Shape::new(4, color, color)?;
+        let mut model = Model::new(256, 256, 32.0);
+        model.add_tile(square.clone_at(Point { x: 1.0, y: 0.0 }));
+        model.add(square)?;
+
+        let values = model
+            .cells()
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| (c, i as f64))
+            .collect::<HashMap<CellId, f64>>();
+        model.paint_cells(&values, &crate::colormap::VIRIDIS)?;
+        let fills = model
+            .placed_tiles()
+            .iter()
+            .map(|t| t.fill().to_hex())
+            .collect::<Vec<String>>();
+        assert_eq!(fills, vec![crate::colormap::VIRIDIS.color(1.0).to_hex()]);
+
+        model.fill_at(Point { x: 1.0, y: 0.0 }, Color::WHITE)?;
+        assert_eq!(model.placed_tiles()[0].fill().to_hex(), "#ffffff");
+        assert!(matches!(
+            model.fill_at(Point { x: 5.0, y: 5.0 }, color),
+            Err(EmptyCell { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn coloring_with_no_colors_is_invalid() -> Result<()> {
+        let (mut model, hexagons) = intro()?;
+        model.repeat(hexagons)?;
+        for k in [0, 9] {
+            assert!(matches!(
+                model.color_with(k, 1),
+                Err(InvalidArgument { .. })
+            ));
+        }
+        assert!(matches!(
+            model.color_with(1, 1),
+            Err(InfeasibleColoring { colors: 1, .. })
+        ));
+        model.color_with(3, 1)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn colorings_give_neighbors_different_fills() -> Result<()> {
+        let (mut model, hexagons) = intro()?;
+        model.repeat(hexagons)?;
+        model.color_with(3, 7)?;
+        let fills = model.fills.clone();
+
+        let cells = model.cells();
+        assert_eq!(fills.len(), cells.len());
+        for cell in cells.iter() {
+            for n in model.neighbors(*cell)? {
+                assert_ne!(fills[&cell.0].to_hex(), fills[&n.0].to_hex());
+            }
+        }
+        // The same seed picks the same coloring.
+        model.clear_fills();
+        model.color_with(3, 7)?;
+        assert!(cells
+            .iter()
+            .all(|c| model.fills[&c.0].to_hex() == fills[&c.0].to_hex()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn boolean_operations_combine_regions() -> Result<()> {
+        let square = Shape::new(4, Color::WHITE, Color::BLACK)?;
+        let points = square.points(0.0)?;
+        let edge = points[1] - points[0];
+        let half = square.clone_at(square.point() + edge * 0.5);
+        let apart = square.clone_at(square.point() + edge * 2.0);
+
+        assert!((square.union(&half)?.area() - 1.5).abs() < 1e-9);
+        assert!((square.intersection(&half)?.area() - 0.5).abs() < 1e-9);
+        assert!((square.difference(&half)?.area() - 0.5).abs() < 1e-9);
+        assert!((square.union(&apart)?.area() - 2.0).abs() < 1e-9);
+        assert!(square.intersection(&apart).is_err());
+        assert!(square.difference(&square).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn hit_tests_find_the_shape_under_a_point() -> Result<()> {
+        let (model, _) = intro()?;
+        for r in model.placed() {
+            let (x, y) = model.model_to_canvas(r.shape.point());
+            let hit = model.hit_test(x, y).map(|s| s.index());
+            assert_eq!(hit, Some(r.index));
+        }
+        // The unrepeated pattern leaves the canvas' corners uncovered.
+        assert!(model.hit_test(1.0, 1.0).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn mazes_are_perfect() -> Result<()> {
+        let (mut model, hexagons) = intro()?;
+        model.repeat(hexagons)?;
+        let cells = model.cells().len();
+
+        for algorithm in [MazeAlgorithm::Backtracker, MazeAlgorithm::Kruskal] {
+            let maze = model.maze(algorithm, 3)?;
+            assert_eq!(maze.cells().len(), cells);
+            // A spanning tree of the connected tiling: one passage fewer
+            // than cells, joining every cell.
+            assert_eq!(maze.passages().len(), cells - 1);
+            let mut joined = HashSet::new();
+            for (a, b) in maze.passages() {
+                joined.insert(*a);
+                joined.insert(*b);
+            }
+            assert_eq!(joined.len(), cells);
+            assert_eq!(model.maze(algorithm, 3)?.walls(), maze.walls());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn isohedral_tiles_fill_their_lattice_cell() -> Result<()> {
+        let (white, black) = (Color::WHITE, Color::BLACK);
+        let bump = [Point { x: 0.5, y: 0.3 }];
+        for kind in [IsohedralType::Ih1, IsohedralType::Ih41] {
+            let tiling = IsohedralTiling::new(kind, white, black).with_edge(0, &bump)?;
+            // Deforming an edge moves area from the tile's other side, so a
+            // tile still covers exactly one cell of its lattice.
+            let (t1, t2) = tiling.translations();
+            let cell = (t1.x * t2.y - t1.y * t2.x).abs();
+            let area = irregular::area(&tiling.outline()).abs();
+            assert!((area - cell).abs() < 1e-9);
+            assert!(!tiling.tiles(256, 256, 32.0)?.is_empty());
+        }
+        assert!(matches!(
+            IsohedralTiling::new(IsohedralType::Ih41, white, black).with_parameters(&[1.0, 0.0]),
+            Err(InvalidShape { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn penrose_patches_obey_the_matching_rules() -> Result<()> {
+        let (white, black) = (Color::WHITE, Color::BLACK);
+        let thick = PenroseRhombus::new(PenroseKind::Thick, white, black);
+        // The double arrow on a thick rhombus' first edge points to its
+        // start, as the one on a neighbor's second edge does once the edge is
+        // shared.
+        let matched = thick.adjacent(0, PenroseKind::Thick, 1, white, black)?;
+        crate::penrose::validate(&[thick, matched])?;
+        let unmatched = thick.adjacent(0, PenroseKind::Thick, 0, white, black)?;
+        assert!(matches!(
+            crate::penrose::validate(&[thick, unmatched]),
+            Err(UnmatchedEdge {
+                tile: 1,
+                edge: 0,
+                neighbor: 0,
+                neighbor_edge: 0,
+            })
+        ));
+        assert!(matches!(
+            thick.adjacent(0, PenroseKind::Thin, 4, white, black),
+            Err(OutOfBounds { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn hex_colors_are_only_hex_digits() -> Result<()> {
+        assert_eq!(Color::from_hex("#ff8000")?.to_hex(), "#ff8000");
+        assert_eq!(Color::from_hex("FF8000")?.to_hex(), "#ff8000");
+        for hex in [
+            "#+f+f+f",
+            "#-1-1-1",
+            "#ff80 0",
+            "#ff800",
+            "#ff8000ff",
+            "#ff800g",
+        ] {
+            assert!(matches!(Color::from_hex(hex), Err(InvalidColor)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn extrusion_margins_stay_within_the_shapes() -> Result<()> {
+        let (model, _) = intro()?;
+        // The triangles' inradius of about 0.29 is the smallest.
+        for margin in [-0.1, 0.5, 2.0, f64::NAN, f64::INFINITY] {
+            let err = model.extrude_with_margin(1.0, margin).unwrap_err();
+            assert!(matches!(err.root(), InvalidMargin { .. }));
+        }
+        model.extrude_with_margin(1.0, 0.1)?;
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "cairo")]
+    fn margins_must_be_finite_and_not_negative() -> Result<()> {
+        let (model, _) = intro()?;
+        let color = Color::new(0, 0, 0)?;
+        let names = |result: Result<Render>, name: &str| match result {
+            Err(e) => matches!(e.root(), InvalidMargin { field, .. } if field == name),
+            Ok(_) => false,
+        };
+        for margin in [-0.1, f64::NAN, f64::INFINITY] {
+            let options = RenderOptions::default().margin(margin);
+            assert!(names(model.render_with(&options), "RenderOptions::margin"));
+            let options = RenderOptions::default().dual_margin(margin);
+            let dual = model.render_dual_with(color, color, &options);
+            assert!(names(dual, "RenderOptions::dual_margin"));
+        }
+        // The triangles' inradius of about 0.29 is the smallest.
+        let options = RenderOptions::default().margin(0.5);
+        assert!(names(model.render_with(&options), "RenderOptions::margin"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn notations_name_the_symmetry_group() -> Result<()> {
+        let c = Color::new(0, 0, 0)?;
+        for (name, notation) in [
+            ("3.3.3.3.6", "3.3.3.3.6 (632)"),
+            ("3.3.4.3.4", "3.3.4.3.4 (4*2)"),
+            ("3.4.6.4", "3.4.6.4 (*632)"),
+        ] {
+            let model = crate::catalog::model(name, 384, 384, 32.0, &[c; 3], c)?;
+            assert_eq!(model.notation()?.as_deref(), Some(notation));
+        }
+
+        // A 2-uniform tiling: hexagons ringed by triangles, with triangles
+        // between them.
+        let mut model = Model::new(384, 384, 32.0);
+        model.add(Shape::new(6, c, c)?)?;
+        let triangles = model.add_multi(0..1, 0..6, Shape::new(3, c, c)?)?;
+        model.add_multi(triangles, 1..2, Shape::new(3, c, c)?)?;
+        let (x, y) = (1.5 * 3f64.sqrt(), 1.5);
+        model.repeat_by(&[Point { x, y }, Point { x: 0.0, y: 3.0 }], 8)?;
+        let notation = model.notation()?;
+        assert_eq!(notation.as_deref(), Some("[3.3.3.3.3.3; 3.3.3.3.6] (*632)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn plotted_edges_have_a_positive_length() -> Result<()> {
+        let (model, _) = intro()?;
+        for edge_mm in [0.0, -10.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                model.to_hpgl(edge_mm),
+                Err(InvalidArgument { .. })
+            ));
+            assert!(matches!(
+                model.to_gcode(edge_mm),
+                Err(InvalidArgument { .. })
+            ));
+        }
+        assert!(model.to_hpgl(10.0)?.starts_with("IN;"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn corner_styles_are_stored_on_shapes() -> Result<()> {
+        let color = Color::new(0, 0, 0)?;
+        let mut model = Model::new(256, 256, 32.0);
+        model.add(Shape::new(4, color, color)?)?;
+        model.set_corner(0..1, Corner::Bevel(0.1))?;
+
+        let shape = model.shape(0).expect("shape was added");
+        assert_eq!(shape.corner(), Some(Corner::Bevel(0.1)));
+        // Each of the square's 4 corners is cut into 2.
+        assert_eq!(shape.outline_points(0.0)?.len(), 9);
+        let svg = model.to_svg(&RenderOptions::default())?;
+        let points = svg
+            .split("points=\"")
+            .nth(1)
+            .and_then(|p| p.split('"').next())
+            .expect("svg has a polygon");
+        assert_eq!(points.split(' ').count(), 8);
+
+        let path = std::env::temp_dir().join("corner_styles_are_stored_on_shapes.tiling");
+        model.save(&path)?;
+        let loaded = Model::load(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(
+            loaded.shape(0).and_then(|s| s.corner()),
+            Some(Corner::Bevel(0.1))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn styles_are_interned_once_and_saved() -> Result<()> {
+        let (red, black) = (Color::new(255, 0, 0)?, Color::new(0, 0, 0)?);
+        let mut model = Model::new(256, 256, 32.0);
+        let plain = model.intern_style(&Shape::new(4, black, black)?);
+        let bevel = Shape::new(6, red, black)?.with_corner(Corner::Bevel(0.1));
+        let beveled = model.intern_style(&bevel);
+        assert_eq!(
+            model.intern_style(&bevel.clone_at(Point { x: 1.0, y: 2.0 })),
+            beveled
+        );
+        assert_ne!(plain, beveled);
+
+        model.add(ShapeSpec::new(6, beveled))?;
+        model.attach(0, 0, ShapeSpec::new(4, plain))?;
+        assert_eq!(
+            model.shape(0).and_then(|s| s.corner()),
+            Some(Corner::Bevel(0.1))
+        );
+        assert_eq!(model.shape(1).map(|s| s.fill()), Some(black));
+
+        let path = std::env::temp_dir().join("styles_are_interned_once_and_saved.tiling");
+        model.save(&path)?;
+        let mut loaded = Model::load(&path)?;
+        std::fs::remove_file(&path)?;
+        loaded.attach(0, 1, ShapeSpec::new(3, beveled))?;
+        let triangle = loaded.shape(2).expect("triangle was attached");
+        assert_eq!(triangle.fill(), red);
+        assert_eq!(triangle.corner(), Some(Corner::Bevel(0.1)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn tiling_files_skip_unknown_fields_and_reject_newer_versions() -> Result<()> {
+        let path = std::env::temp_dir().join("tiling_files_skip_unknown_fields.tiling");
+        let file = |version: u32| {
+            format!(
+                "// Written by hand.\n\
+                 Tiling(\n\
+                     version: {},\n\
+                     canvas: (width: 256, height: 256, scale: 32.0),\n\
+                     shapes: [\n\
+                         Shape(sides: 6, x: 0, y: 0, rotation: 0, fill: \"#ffffff\", stroke: \"#000000\", glow: Some(2)),\n\
+                         Shape(sides: 4, x: 1.5, y: 0, rotation: 0, fill: \"#ffffff\", stroke: \"#000000\"),\n\
+                     ],\n\
+                     shape_names: [(shape: 1, name: \"a \\\"square\\\"\")],\n\
+                     layers: [/* not known yet */],\n\
+                 )\n",
+                version
+            )
+        };
+
+        fs::write(&path, file(file::VERSION))?;
+        let loaded = Model::load(&path);
+        fs::write(&path, file(file::VERSION + 1))?;
+        let newer = Model::load(&path);
+        std::fs::remove_file(&path)?;
+
+        // Unplaced shapes are placed where they are centered.
+        let loaded = loaded?;
+        assert_eq!(loaded.placed().count(), 2);
+        assert_eq!(loaded.named_shape("a \"square\""), Some(1));
+        assert!(matches!(newer, Err(InvalidTilingFile { line: 2, .. })));
+
+        Ok(())
+    }
+
+    #[test]
+    fn style_sets_are_saved() -> Result<()> {
+        let (red, white, black) = (
+            Color::new(255, 0, 0)?,
+            Color::new(255, 255, 255)?,
+            Color::new(0, 0, 0)?,
+        );
+        let mut model = Model::new(256, 256, 32.0);
+        model.add(Shape::new(6, white, black)?)?;
+        model.attach(0, 0, Shape::new(4, white, black)?)?;
+        model.add_style_set(
+            "dark mode",
+            StyleSet::new()
+                .shape(1, Style::new(red, white))
+                .sides(6, Style::new(black, white)),
+        );
+        model.add_style_set("plain", StyleSet::new());
+
+        let path = std::env::temp_dir().join("style_sets_are_saved.tiling");
+        model.save(&path)?;
+        let loaded = Model::load(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(
+            loaded.style_sets().collect::<Vec<&str>>(),
+            vec!["dark mode", "plain"]
+        );
+        let dark = loaded.style_set("dark mode").expect("set was saved");
+        let square = loaded.shape(1).expect("square was saved");
+        assert_eq!(dark.get(1, &square), Some(Style::new(red, white)));
+        let hexagon = loaded.shape(0).expect("hexagon was saved");
+        assert_eq!(dark.get(0, &hexagon), Some(Style::new(black, white)));
+        assert_eq!(hexagon.style(), Style::new(white, black));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn saved_models_render_the_same() -> Result<()> {
+        use crate::{Blend, Texture};
+
+        let (black, white) = (Color::new(0, 0, 0)?, Color::new(255, 255, 255)?);
+        let (model, hexagons) = intro()?;
+        let mut model = model
+            .with_origin(Origin::At(200.0, 240.0))
+            .with_coverage(Rect::new(-4.0, -4.0, 8.0, 8.0))
+            .with_dpi(150.0);
+        let glass = Shape::new(4, white, black)?
+            .with_opacity(0.5)
+            .with_blend(Blend::Multiply)
+            .clone_at(Point { x: 5.0, y: 5.0 });
+        model.add(glass)?;
+        model.deform_edge(
+            0,
+            0,
+            &[Point { x: 0.25, y: 0.1 }, Point { x: 0.75, y: -0.1 }],
+        )?;
+        model.style_edge(1, 0, EdgeStyle::hidden())?;
+        model.hide(2..3)?;
+        model.name_shape(0, "center hexagon")?;
+        model.set_fill(
+            3..4,
+            Fill::Texture(
+                Texture::Hatch {
+                    angle: 0.5,
+                    spacing: 0.2,
+                },
+                black,
+            ),
+        )?;
+        model.repeat(hexagons)?;
+        model.border(0.2, black);
+        model.add_style_set("print", StyleSet::new().sides(4, Style::new(black, white)));
+
+        let path = std::env::temp_dir().join("saved_models_render_the_same.tiling");
+        model.save(&path)?;
+        let loaded = Model::load(&path)?;
+        std::fs::remove_file(&path)?;
+
+        let options = RenderOptions::default().style_set("print");
+        assert_eq!(
+            loaded.render_with(&options)?.content_hash()?,
+            model.render_with(&options)?.content_hash()?
+        );
+        assert_eq!(loaded.named_shape("center hexagon"), Some(0));
+        assert_eq!(loaded.metadata()?.steps(), model.metadata()?.steps());
+        assert_eq!(loaded.metadata()?.dpi(), Some(150.0));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn pixel_snapping_moves_strokes_only() -> Result<()> {
+        let (model, _) = intro()?;
+        let snapped = RenderOptions::default().pixel_snap(true);
+        let fills = |svg: &str| {
+            svg.split(" points=\"")
+                .skip(1)
+                .map(|s| s.split('"').next().unwrap_or_default().to_string())
+                .collect::<Vec<String>>()
+        };
+
+        let (plain, snapped_svg) = (
+            model.to_svg(&RenderOptions::default())?,
+            model.to_svg(&snapped)?,
+        );
+        assert_eq!(fills(&plain), fills(&snapped_svg));
+        assert!(!plain.contains("-stroke\""));
+        assert!(snapped_svg.contains("-stroke\""));
+
+        let filled = RenderOptions::default().paint(PaintMode::Fill);
+        assert_eq!(
+            model.render_with(&filled)?.content_hash()?,
+            model
+                .render_with(&filled.clone().pixel_snap(true))?
+                .content_hash()?
+        );
+        assert_ne!(
+            model
+                .render_with(&RenderOptions::default())?
+                .content_hash()?,
+            model.render_with(&snapped)?.content_hash()?
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn culling_keeps_outlines_that_reach_the_canvas() -> Result<()> {
+        let (white, black) = (Color::new(255, 255, 255)?, Color::new(0, 0, 0)?);
+        // The square lies just beyond the canvas' right edge, but its
+        // outline reaches onto it.
+        let square = Shape::new(4, white, black)?
+            .with_outline(crate::Outline::new(2.0, black))
+            .clone_at(Point { x: 6.0, y: 0.0 });
+        let mut model = Model::new(100, 100, 10.0);
+        model.add(square)?;
+
+        let options = RenderOptions::default();
+        assert_ne!(
+            model.render_with(&options)?.content_hash()?,
+            Model::new(100, 100, 10.0)
+                .render_with(&options)?
+                .content_hash()?
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn backends_draw_everything_or_refuse() -> Result<()> {
+        use crate::{CairoBackend, SvgBackend};
+
+        let (mut model, hexagons) = intro()?;
+        model.repeat(hexagons)?;
+        let labeled = RenderOptions::default().labels(true).pixel_snap(true);
+        assert_eq!(
+            model.render(CairoBackend::new(&labeled))?.content_hash()?,
+            model.render_with(&labeled)?.content_hash()?
+        );
+        let (fill, stroke) = (Color::new(0, 0, 0)?, Color::new(255, 255, 255)?);
+        assert_eq!(
+            model
+                .render_dual(CairoBackend::new(&labeled), fill, stroke)?
+                .content_hash()?,
+            model
+                .render_dual_with(fill, stroke, &labeled)?
+                .content_hash()?
+        );
+
+        assert!(matches!(
+            model.render(SvgBackend::new(&labeled)),
+            Err(Backend { .. })
+        ));
+        let svg = model.render(SvgBackend::new(&RenderOptions::default()))?;
+        assert!(svg.contains("<polygon"));
+        assert!(model
+            .render_dual(SvgBackend::new(&labeled), fill, stroke)?
+            .contains("<polyline"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn ids_select_shapes_and_edges() -> Result<()> {
+        let (mut model, _) = intro()?;
+        let color = Color::new(0, 0, 0)?;
+        let index = model.attach(
+            ShapeId::new(1),
+            EdgeId::new(2),
+            Shape::new(4, color, color)?,
+        )?;
+        let point = model.shape(index).map(|s| s.point());
+        assert_eq!(model.shape(ShapeId::new(index)).map(|s| s.point()), point);
+
+        model.name_shape(index, "porch")?;
+        model.hide("porch")?;
+        assert!(model.is_hidden(ShapeId::new(index)));
+        model.show(ShapeId::range(index..index + 1))?;
+        assert!(!model.is_hidden(index));
 
-impl Render {
-    /// Writes a rendered model to a PNG file at path.
-    pub fn write_to_png<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let mut file = File::create(path)?;
-        self.0.write_to_png(&mut file)?;
+        // Anchors built at run time are still checked when they are used.
+        let err = model
+            .add_multi(Anchor::Edges(0..1), 0..1, Shape::new(4, color, color)?)
+            .unwrap_err();
+        assert!(matches!(err.root(), MismatchedAnchor { .. }));
 
         Ok(())
     }
@@ -0,0 +1,56 @@
+use std::{fmt, ops::Range, sync::Arc};
+
+/// A change to a model, passed to the callbacks registered with
+/// `Model::on_change`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// The shapes with index in indexes were added, by `add`, `add_multi`,
+    /// `add_pattern`, `attach`, or `attach_at`.
+    Added { indexes: Range<usize> },
+    /// The shape with index index was replaced by a coincident shape under
+    /// `DuplicatePolicy::Replace`.
+    Replaced { index: usize },
+    /// The shapes with index in indexes were removed by `undo`.
+    Removed { indexes: Range<usize> },
+    /// The model's repeats changed, having been filled by `repeat`,
+    /// `repeat_groups`, or `repeat_by`, or discarded by `undo`.
+    Repeated,
+    /// Every shape (including repeats) was moved by `reflect`, `rotate`, or
+    /// `translate`.
+    Transformed,
+}
+
+/// A function called with each change to the model it was registered with.
+pub type ChangeFn = Arc<dyn Fn(&Change) + Send + Sync>;
+
+/// The callbacks registered with a model.
+/// Cloning a model does not clone its callbacks, since the clone's changes
+/// are not the changes of the model the callbacks were registered with.
+#[derive(Default)]
+pub(crate) struct Observers(Vec<ChangeFn>);
+
+impl Observers {
+    /// Registers callback.
+    pub(crate) fn push(&mut self, callback: ChangeFn) {
+        self.0.push(callback);
+    }
+
+    /// Calls every callback with change, in the order they were registered.
+    pub(crate) fn notify(&self, change: Change) {
+        for callback in self.0.iter() {
+            callback(&change);
+        }
+    }
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Observers {
+        Observers::default()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
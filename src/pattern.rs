@@ -0,0 +1,86 @@
+use std::ops::Range;
+
+use crate::{Error::*, Result, Shape};
+
+/// A motif of shapes that is defined once and attached as a whole to the edges
+/// of a model's shapes with `Model::add_pattern`.
+/// The pattern's first shape (with index 0) is attached to the model's edge;
+/// every other shape is attached to an edge of a shape earlier in the pattern.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    root: Shape,
+    steps: Vec<Step>,
+}
+
+/// A shape attached to the edge with index edge of the pattern's shape with
+/// index index.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Step {
+    pub(crate) index: usize,
+    pub(crate) edge: usize,
+    pub(crate) shape: Shape,
+}
+
+impl Pattern {
+    /// Returns a pattern of the single shape root.
+    pub fn new(root: Shape) -> Pattern {
+        Pattern {
+            root,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Returns the pattern with shape attached to every edge in edges of each
+    /// of its shapes with index in indexes.
+    /// As in `Model::add_multi`, the attached shapes are numbered in order
+    /// after the pattern's existing shapes.
+    pub fn add_multi(
+        mut self,
+        indexes: Range<usize>,
+        edges: Range<usize>,
+        shape: Shape,
+    ) -> Result<Pattern> {
+        let len = self.len();
+        for index in indexes {
+            if index >= len {
+                return Err(OutOfBounds {
+                    index,
+                    length: len,
+                    name: String::from("pattern shapes"),
+                });
+            }
+
+            for edge in edges.clone() {
+                self.steps.push(Step { index, edge, shape });
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the number of shapes in the pattern.
+    pub fn len(&self) -> usize {
+        self.steps.len() + 1
+    }
+
+    /// Returns false, since a pattern always contains at least its first shape.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the pattern's first shape.
+    pub(crate) fn root(&self) -> Shape {
+        self.root
+    }
+
+    /// Returns each shape attached after the pattern's first shape.
+    pub(crate) fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+impl From<Shape> for Pattern {
+    fn from(shape: Shape) -> Pattern {
+        Pattern::new(shape)
+    }
+}
@@ -0,0 +1,966 @@
+//! Shareable pattern definitions in TOML.
+//!
+//! A pattern file names a tiling and lists the operations that construct it,
+//! in the order `Model::history` records them, with the colors they use named
+//! in a palette:
+//!
+//! ```toml
+//! name = "3.4.6.4"
+//! width = 1024
+//! height = 1024
+//! scale = 64.0
+//!
+//! [palette]
+//! hexagon = "#fc8d62"
+//! square = "#66c2a5"
+//! triangle = "#8da0cb"
+//! stroke = "#000000"
+//!
+//! [[operation]]
+//! kind = "add"
+//! sides = 6
+//! fill = "hexagon"
+//! stroke = "stroke"
+//!
+//! [[operation]]
+//! kind = "add_multi"
+//! indexes = [0, 1]
+//! edges = [0, 6]
+//! sides = 4
+//! fill = "square"
+//! stroke = "stroke"
+//!
+//! [[operation]]
+//! kind = "add_multi"
+//! indexes = [1, 7]
+//! edges = [1, 2]
+//! sides = 3
+//! fill = "triangle"
+//! stroke = "stroke"
+//!
+//! [[operation]]
+//! kind = "add_multi"
+//! indexes = [1, 7]
+//! edges = [2, 3]
+//! sides = 6
+//! fill = "hexagon"
+//! stroke = "stroke"
+//!
+//! [[operation]]
+//! kind = "repeat"
+//! indexes = [13, 19]
+//! ```
+//!
+//! Ranges are written as `[start, end]` with end excluded. Colors are either
+//! names from the palette or hex triplets. Besides `add`, `add_multi`, and
+//! `repeat`, operations may be:
+//! - `add_multi_lossy`, with the same fields as `add_multi`;
+//! - `attach`, with `index`, `edge`, an optional offset `t`, an optional
+//!   `side` (`"outward"`, the default, or `"inward"`), and a shape;
+//! - `add_pattern`, with `indexes`, `edges`, a `root` shape, and `steps`, each
+//!   a shape with the `index` and `edge` of the pattern shape it attaches to;
+//! - `add_multi_mirrored`, with the same fields as `add_pattern`;
+//! - `place`, with a `root` shape and `steps`, as `add_pattern` has;
+//! - `add_ring`, with `indexes` and `shapes`, a shape (or an empty table,
+//!   leaving the edge bare) for each edge;
+//! - `repeat_around`, with `indexes` and a `center` `[x, y]` pair;
+//! - `repeat_groups`, with `groups`, a list of ranges;
+//! - `repeat_by`, with `vectors`, a list of `[x, y]` pairs, and `extent`;
+//! - `repeat_n`, with `indexes` and `copies`;
+//! - `repeat_radius`, with `indexes` and `radius`;
+//! - `repeat_rotational`, with `indexes` and `n`;
+//! - `repeat_strip`, with `indexes`, a `direction` `[x, y]` pair, and an
+//!   optional `glide` (`false` by default);
+//! - `translate`, with a `vector` `[x, y]` pair;
+//! - `rotate`, with an `angle` (in radians) and an optional `center`;
+//! - `reflect`, with an `angle` (in radians) and an optional `center`, a
+//!   point on the axis.
+//!
+//! Shapes are written as their `sides`, `fill`, and `stroke`; their rendering
+//! overrides (margins, corners, and so on) are not part of the format.
+//!
+//! Shapes and edges may be named, as by `Model::name_shape` and
+//! `Model::name_edge`, with an `anchor` table for each giving the shape's
+//! `index` (and the `edge`, for an edge name); the name is given as soon as
+//! the shape is added. The `indexes` and `edges` of `add_multi` (and
+//! `add_multi_lossy`) may then be names instead of ranges:
+//!
+//! ```toml
+//! [[anchor]]
+//! name = "core"
+//! index = 0
+//!
+//! [[operation]]
+//! kind = "add_multi"
+//! indexes = "core"
+//! edges = [0, 6]
+//! sides = 4
+//! fill = "square"
+//! stroke = "stroke"
+//! ```
+
+use std::{collections::BTreeMap, convert::TryFrom, fmt::Write, fs, ops::Range, path::Path};
+
+use toml::{Table, Value};
+
+use crate::{
+    Anchor, Axis, Color, Error, Error::*, Model, Operation, Pattern, Point, Result, Shape, Side,
+};
+
+/// The canvas of a pattern file that does not give one: width and height in
+/// pixels and scale in pixels per edge.
+const CANVAS: (i32, i32, f64) = (1024, 1024, 64.0);
+
+/// Returns the model constructed by the pattern file at path.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Model> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/// Returns the model constructed by the pattern definition text.
+pub fn parse(text: &str) -> Result<Model> {
+    let table = text
+        .parse::<Table>()
+        .map_err(|err| invalid(err.to_string()))?;
+    let integer = |key: &str, default: i32| match table.get(key) {
+        Some(v) => v
+            .as_integer()
+            .map(|i| i as i32)
+            .ok_or_else(|| invalid(format!("{} is not an integer", key))),
+        None => Ok(default),
+    };
+    let scale = match table.get("scale") {
+        Some(v) => number(v).ok_or_else(|| invalid(String::from("scale is not a number")))?,
+        None => CANVAS.2,
+    };
+    let mut model = Model::new(
+        integer("width", CANVAS.0)?,
+        integer("height", CANVAS.1)?,
+        scale,
+    );
+
+    let mut palette = BTreeMap::new();
+    if let Some(p) = table.get("palette") {
+        let p = p
+            .as_table()
+            .ok_or_else(|| invalid(String::from("palette is not a table")))?;
+        for (name, hex) in p {
+            let color = hex
+                .as_str()
+                .and_then(|hex| Color::from_hex(hex).ok())
+                .ok_or_else(|| invalid(format!("palette color {} is not a hex triplet", name)))?;
+            palette.insert(name.clone(), color);
+        }
+    }
+
+    let mut names = Vec::new();
+    match table.get("anchor") {
+        Some(Value::Array(tables)) => {
+            for (i, name) in tables.iter().enumerate() {
+                let fields = Fields {
+                    table: name
+                        .as_table()
+                        .ok_or_else(|| invalid(format!("anchor {} is not a table", i)))?,
+                    palette: &palette,
+                    context: format!("anchor {}", i),
+                };
+                names.push(fields.name()?);
+            }
+        }
+        Some(_) => return Err(invalid(String::from("anchor is not an array of tables"))),
+        None => {}
+    }
+
+    let operations = match table.get("operation") {
+        Some(Value::Array(operations)) => operations.as_slice(),
+        Some(_) => return Err(invalid(String::from("operation is not an array of tables"))),
+        None => &[],
+    };
+    for (i, operation) in operations.iter().enumerate() {
+        apply_names(&mut model, &mut names)?;
+        let operation = Fields {
+            table: operation
+                .as_table()
+                .ok_or_else(|| invalid(format!("operation {} is not a table", i)))?,
+            palette: &palette,
+            context: format!("operation {}", i),
+        }
+        .operation(&model)?;
+        replay(&mut model, operation)?;
+    }
+    apply_names(&mut model, &mut names)?;
+    if let Some(name) = names.first() {
+        return Err(invalid(format!(
+            "anchor {:?} is given to shape {}, which is never added",
+            name.name, name.index
+        )));
+    }
+
+    Ok(model)
+}
+
+/// A name for a shape, or for an edge of a shape, in a pattern file.
+struct Name {
+    name: String,
+    index: usize,
+    edge: Option<usize>,
+}
+
+/// Gives model the names whose shapes it has, removing them from names.
+fn apply_names(model: &mut Model, names: &mut Vec<Name>) -> Result<()> {
+    let len = model.len();
+    for name in names.iter().filter(|n| n.index < len) {
+        match name.edge {
+            Some(edge) => model.name_edge(name.index, edge, &name.name),
+            None => model.name_shape(name.index, &name.name),
+        }
+        .map_err(|err| invalid(format!("anchor {:?}: {}", name.name, err.root())))?;
+    }
+    names.retain(|n| n.index >= len);
+
+    Ok(())
+}
+
+/// Returns a pattern file named name that constructs model from the
+/// operations in its history, naming each color it uses in its palette.
+pub fn to_toml(model: &Model, name: &str) -> String {
+    let mut colors: Vec<Color> = Vec::new();
+    let mut shapes = Vec::new();
+    for operation in model.history() {
+        match operation {
+            Operation::Add { shape }
+            | Operation::AddMulti { shape, .. }
+            | Operation::AddMultiLossy { shape, .. }
+            | Operation::Attach { shape, .. } => shapes.push(*shape),
+            Operation::AddRing { shapes: ring, .. } => shapes.extend(ring.iter().flatten()),
+            Operation::AddPattern { pattern, .. }
+            | Operation::AddMultiMirrored { pattern, .. }
+            | Operation::Place { pattern } => {
+                shapes.push(pattern.root());
+                shapes.extend(pattern.steps().iter().map(|s| s.shape));
+            }
+            _ => {}
+        }
+    }
+    let fills = model
+        .history()
+        .iter()
+        .flat_map(|operation| match operation {
+            Operation::RepeatWithColoring { colorings, .. } => colorings.concat(),
+            _ => Vec::new(),
+        });
+    for c in shapes
+        .iter()
+        .flat_map(|s| [s.fill(), s.stroke()])
+        .chain(fills)
+    {
+        if !colors.iter().any(|p| p.to_hex() == c.to_hex()) {
+            colors.push(c);
+        }
+    }
+    let color = |c: Color| {
+        let i = colors.iter().position(|p| p.to_hex() == c.to_hex());
+        format!("color{}", i.unwrap_or_default())
+    };
+    let shape = |s: &Shape| {
+        format!(
+            "sides = {}\nfill = \"{}\"\nstroke = \"{}\"\n",
+            s.sides(),
+            color(s.fill()),
+            color(s.stroke())
+        )
+    };
+    let inline = |s: &Shape| {
+        format!(
+            "sides = {}, fill = \"{}\", stroke = \"{}\"",
+            s.sides(),
+            color(s.fill()),
+            color(s.stroke())
+        )
+    };
+    let range = |r: &Range<usize>| format!("[{}, {}]", r.start, r.end);
+    // Repeats around the origin are written without a center.
+    let around = |p: &Point| match *p == Point::origin() {
+        true => String::new(),
+        false => format!("\ncenter = [{:?}, {:?}]", p.x, p.y),
+    };
+    // Single shapes and edges are written by name when they have one that
+    // selects them.
+    let indexes_anchor = |r: &Range<usize>| match model.shape_name(r.start) {
+        Some(name) if r.len() == 1 => Value::from(name).to_string(),
+        _ => range(r),
+    };
+    let edges_anchor = |indexes: &Range<usize>, r: &Range<usize>| {
+        let name = model.edge_name(indexes.start, r.start);
+        match name {
+            Some(name)
+                if r.len() == 1
+                    && indexes
+                        .clone()
+                        .all(|i| model.named_edge(i, name) == Some(r.start)) =>
+            {
+                Value::from(name).to_string()
+            }
+            _ => range(r),
+        }
+    };
+
+    let steps = |pattern: &Pattern| {
+        let steps = pattern
+            .steps()
+            .iter()
+            .map(|s| {
+                format!(
+                    "  {{ index = {}, edge = {}, {} }},\n",
+                    s.index,
+                    s.edge,
+                    inline(&s.shape)
+                )
+            })
+            .collect::<String>();
+        format!(
+            "root = {{ {} }}\nsteps = [\n{}]\n",
+            inline(&pattern.root()),
+            steps
+        )
+    };
+
+    // Writing to a String cannot fail.
+    let mut toml = String::new();
+    let _ = writeln!(toml, "name = {}", Value::from(name));
+    let _ = writeln!(toml, "width = {}", model.width());
+    let _ = writeln!(toml, "height = {}", model.height());
+    let _ = writeln!(toml, "scale = {:?}", model.scale());
+    let _ = writeln!(toml, "\n[palette]");
+    for (i, c) in colors.iter().enumerate() {
+        let _ = writeln!(toml, "color{} = \"{}\"", i, c.to_hex());
+    }
+    for index in 0..model.len() {
+        if let Some(name) = model.shape_name(index) {
+            let _ = writeln!(
+                toml,
+                "\n[[anchor]]\nname = {}\nindex = {}",
+                Value::from(name),
+                index
+            );
+        }
+        for edge in 0..model.shape(index).map_or(0, |s| s.sides() as usize) {
+            if let Some(name) = model.edge_name(index, edge) {
+                let _ = writeln!(
+                    toml,
+                    "\n[[anchor]]\nname = {}\nindex = {}\nedge = {}",
+                    Value::from(name),
+                    index,
+                    edge
+                );
+            }
+        }
+    }
+    for operation in model.history() {
+        let _ = writeln!(toml, "\n[[operation]]");
+        let _ = match operation {
+            Operation::Add { shape: s } => write!(toml, "kind = \"add\"\n{}", shape(s)),
+            Operation::AddMulti {
+                indexes,
+                edges,
+                shape: s,
+            } => write!(
+                toml,
+                "kind = \"add_multi\"\nindexes = {}\nedges = {}\n{}",
+                indexes_anchor(indexes),
+                edges_anchor(indexes, edges),
+                shape(s)
+            ),
+            Operation::AddMultiLossy {
+                indexes,
+                edges,
+                shape: s,
+            } => write!(
+                toml,
+                "kind = \"add_multi_lossy\"\nindexes = {}\nedges = {}\n{}",
+                indexes_anchor(indexes),
+                edges_anchor(indexes, edges),
+                shape(s)
+            ),
+            Operation::AddRing { indexes, shapes } => {
+                let shapes = shapes
+                    .iter()
+                    .map(|s| match s {
+                        Some(s) => format!("  {{ {} }},\n", inline(s)),
+                        None => String::from("  {},\n"),
+                    })
+                    .collect::<String>();
+                write!(
+                    toml,
+                    "kind = \"add_ring\"\nindexes = {}\nshapes = [\n{}]\n",
+                    range(indexes),
+                    shapes
+                )
+            }
+            Operation::AddPattern {
+                indexes,
+                edges,
+                pattern,
+            }
+            | Operation::AddMultiMirrored {
+                indexes,
+                edges,
+                pattern,
+            } => {
+                let kind = match operation {
+                    Operation::AddPattern { .. } => "add_pattern",
+                    _ => "add_multi_mirrored",
+                };
+                write!(
+                    toml,
+                    "kind = \"{}\"\nindexes = {}\nedges = {}\n{}",
+                    kind,
+                    range(indexes),
+                    range(edges),
+                    steps(pattern),
+                )
+            }
+            Operation::Place { pattern } => {
+                write!(toml, "kind = \"place\"\n{}", steps(pattern))
+            }
+            Operation::Attach {
+                index,
+                edge,
+                t,
+                side,
+                shape: s,
+            } => write!(
+                toml,
+                "kind = \"attach\"\nindex = {}\nedge = {}\nt = {:?}\n{}{}",
+                index,
+                edge,
+                t,
+                match side {
+                    Side::Outward => "",
+                    Side::Inward => "side = \"inward\"\n",
+                },
+                shape(s)
+            ),
+            Operation::Repeat { indexes } => {
+                writeln!(toml, "kind = \"repeat\"\nindexes = {}", range(indexes))
+            }
+            Operation::RepeatAround { indexes, center } => writeln!(
+                toml,
+                "kind = \"repeat_around\"\nindexes = {}\ncenter = [{:?}, {:?}]",
+                range(indexes),
+                center.x,
+                center.y
+            ),
+            Operation::RepeatGroups { groups, center } => writeln!(
+                toml,
+                "kind = \"repeat_groups\"\ngroups = [{}]{}",
+                groups.iter().map(range).collect::<Vec<String>>().join(", "),
+                around(center)
+            ),
+            Operation::RepeatWithColoring { indexes, colorings } => writeln!(
+                toml,
+                "kind = \"repeat_with_coloring\"\nindexes = {}\ncolorings = [{}]",
+                range(indexes),
+                colorings
+                    .iter()
+                    .map(|fills| format!(
+                        "[{}]",
+                        fills
+                            .iter()
+                            .map(|c| format!("\"{}\"", color(*c)))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Operation::RepeatBy { vectors, extent } => writeln!(
+                toml,
+                "kind = \"repeat_by\"\nvectors = [{}]\nextent = {}",
+                vectors
+                    .iter()
+                    .map(|v| format!("[{:?}, {:?}]", v.x, v.y))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                extent
+            ),
+            Operation::RepeatN {
+                indexes,
+                copies,
+                center,
+            } => writeln!(
+                toml,
+                "kind = \"repeat_n\"\nindexes = {}\ncopies = {}{}",
+                range(indexes),
+                copies,
+                around(center)
+            ),
+            Operation::RepeatRadius {
+                indexes,
+                radius,
+                center,
+            } => writeln!(
+                toml,
+                "kind = \"repeat_radius\"\nindexes = {}\nradius = {:?}{}",
+                range(indexes),
+                radius,
+                around(center)
+            ),
+            Operation::RepeatRotational { indexes, n } => writeln!(
+                toml,
+                "kind = \"repeat_rotational\"\nindexes = {}\nn = {}",
+                range(indexes),
+                n
+            ),
+            Operation::RepeatStrip {
+                indexes,
+                direction,
+                glide,
+            } => writeln!(
+                toml,
+                "kind = \"repeat_strip\"\nindexes = {}\ndirection = [{:?}, {:?}]\nglide = {}",
+                range(indexes),
+                direction.x,
+                direction.y,
+                glide
+            ),
+            Operation::Translate { vector } => writeln!(
+                toml,
+                "kind = \"translate\"\nvector = [{:?}, {:?}]",
+                vector.x, vector.y
+            ),
+            Operation::Rotate { angle, center } => writeln!(
+                toml,
+                "kind = \"rotate\"\nangle = {:?}{}",
+                angle,
+                around(center)
+            ),
+            Operation::Reflect { axis } => writeln!(
+                toml,
+                "kind = \"reflect\"\nangle = {:?}{}",
+                axis.angle(),
+                around(&axis.point())
+            ),
+        };
+    }
+
+    toml
+}
+
+/// Writes a pattern file named name that constructs model to path, as
+/// returned by `to_toml`.
+pub fn save<P: AsRef<Path>>(model: &Model, name: &str, path: P) -> Result<()> {
+    fs::write(path, to_toml(model, name))?;
+
+    Ok(())
+}
+
+/// Applies operation to model.
+fn replay(model: &mut Model, operation: Operation) -> Result<()> {
+    match operation {
+        Operation::Add { shape } => model.add(shape),
+        Operation::AddMulti {
+            indexes,
+            edges,
+            shape,
+        } => model.add_multi(indexes, edges, shape).map(|_| ()),
+        Operation::AddMultiLossy {
+            indexes,
+            edges,
+            shape,
+        } => model.add_multi_lossy(indexes, edges, shape).map(|_| ()),
+        Operation::AddPattern {
+            indexes,
+            edges,
+            pattern,
+        } => model.add_pattern(indexes, edges, &pattern).map(|_| ()),
+        Operation::Place { pattern } => model.place(&pattern).map(|_| ()),
+        Operation::AddRing { indexes, shapes } => model.add_ring(indexes, &shapes).map(|_| ()),
+        Operation::AddMultiMirrored {
+            indexes,
+            edges,
+            pattern,
+        } => model
+            .add_multi_mirrored(indexes, edges, pattern)
+            .map(|_| ()),
+        Operation::Attach {
+            index,
+            edge,
+            t,
+            side,
+            shape,
+        } => model.attach_on(index, edge, t, side, shape).map(|_| ()),
+        Operation::Repeat { indexes } => model.repeat(indexes),
+        Operation::RepeatAround { indexes, center } => model.repeat_around(indexes, center),
+        Operation::RepeatGroups { groups, center } => model.repeat_groups_around(&groups, center),
+        Operation::RepeatWithColoring { indexes, colorings } => {
+            model.repeat_with_coloring(indexes, &colorings)
+        }
+        Operation::RepeatBy { vectors, extent } => model.repeat_by(&vectors, extent),
+        Operation::RepeatN {
+            indexes,
+            copies,
+            center,
+        } => model.repeat_n_around(indexes, copies, center),
+        Operation::RepeatRadius {
+            indexes,
+            radius,
+            center,
+        } => model.repeat_radius_around(indexes, radius, center),
+        Operation::RepeatRotational { indexes, n } => model.repeat_rotational(indexes, n),
+        Operation::RepeatStrip {
+            indexes,
+            direction,
+            glide,
+        } => model.repeat_strip(indexes, direction, glide),
+        Operation::Translate { vector } => {
+            model.translate(vector);
+            Ok(())
+        }
+        Operation::Rotate { angle, center } => {
+            model.rotate(angle, center);
+            Ok(())
+        }
+        Operation::Reflect { axis } => {
+            model.reflect(axis);
+            Ok(())
+        }
+    }
+}
+
+/// The fields of a table in a pattern file, whose colors are named in
+/// palette, described by context in errors.
+struct Fields<'a> {
+    table: &'a Table,
+    palette: &'a BTreeMap<String, Color>,
+    context: String,
+}
+
+impl Fields<'_> {
+    /// Returns the operation the fields describe, selecting shapes and edges
+    /// by their names in model.
+    fn operation(&self, model: &Model) -> Result<Operation> {
+        let kind = self.get("kind")?;
+        let kind = kind
+            .as_str()
+            .ok_or_else(|| self.error("kind is not a string"))?;
+
+        Ok(match kind {
+            "add" => Operation::Add {
+                shape: self.shape()?,
+            },
+            "add_multi" | "add_multi_lossy" => {
+                let (indexes, edges) = model
+                    .anchors(
+                        &self.anchor(self.get("indexes")?)?,
+                        &self.anchor(self.get("edges")?)?,
+                    )
+                    .map_err(|err| self.error(&err.to_string()))?;
+                let shape = self.shape()?;
+
+                match kind {
+                    "add_multi" => Operation::AddMulti {
+                        indexes,
+                        edges,
+                        shape,
+                    },
+                    _ => Operation::AddMultiLossy {
+                        indexes,
+                        edges,
+                        shape,
+                    },
+                }
+            }
+            "add_ring" => Operation::AddRing {
+                indexes: self.range(self.get("indexes")?)?,
+                shapes: self
+                    .get("shapes")?
+                    .as_array()
+                    .ok_or_else(|| self.error("shapes is not an array"))?
+                    .iter()
+                    .map(|shape| {
+                        let shape = self.nested(shape)?;
+                        match shape.table.is_empty() {
+                            true => Ok(None),
+                            false => shape.shape().map(Some),
+                        }
+                    })
+                    .collect::<Result<Vec<Option<Shape>>>>()?,
+            },
+            "place" => Operation::Place {
+                pattern: self.pattern()?,
+            },
+            "add_pattern" | "add_multi_mirrored" => {
+                let pattern = self.pattern()?;
+                let (indexes, edges) = (
+                    self.range(self.get("indexes")?)?,
+                    self.range(self.get("edges")?)?,
+                );
+
+                match kind {
+                    "add_pattern" => Operation::AddPattern {
+                        indexes,
+                        edges,
+                        pattern,
+                    },
+                    _ => Operation::AddMultiMirrored {
+                        indexes,
+                        edges,
+                        pattern,
+                    },
+                }
+            }
+            "attach" => Operation::Attach {
+                index: self.index("index")?,
+                edge: self.index("edge")?,
+                t: match self.table.get("t") {
+                    Some(t) => number(t).ok_or_else(|| self.error("t is not a number"))?,
+                    None => 0.0,
+                },
+                side: match self.table.get("side").map(|s| s.as_str()) {
+                    Some(Some("outward")) | None => Side::Outward,
+                    Some(Some("inward")) => Side::Inward,
+                    Some(_) => return Err(self.error("side is not \"outward\" or \"inward\"")),
+                },
+                shape: self.shape()?,
+            },
+            "repeat" => Operation::Repeat {
+                indexes: self.range(self.get("indexes")?)?,
+            },
+            "repeat_around" => Operation::RepeatAround {
+                indexes: self.range(self.get("indexes")?)?,
+                center: self.point(self.get("center")?)?,
+            },
+            "repeat_groups" => Operation::RepeatGroups {
+                groups: self
+                    .get("groups")?
+                    .as_array()
+                    .ok_or_else(|| self.error("groups is not an array"))?
+                    .iter()
+                    .map(|g| self.range(g))
+                    .collect::<Result<Vec<Range<usize>>>>()?,
+                center: self.center()?,
+            },
+            "repeat_with_coloring" => Operation::RepeatWithColoring {
+                indexes: self.range(self.get("indexes")?)?,
+                colorings: self
+                    .get("colorings")?
+                    .as_array()
+                    .ok_or_else(|| self.error("colorings is not an array"))?
+                    .iter()
+                    .map(|fills| {
+                        fills
+                            .as_array()
+                            .ok_or_else(|| self.error("coloring is not an array"))?
+                            .iter()
+                            .map(|c| self.color_value("coloring", c))
+                            .collect::<Result<Vec<Color>>>()
+                    })
+                    .collect::<Result<Vec<Vec<Color>>>>()?,
+            },
+            "repeat_by" => Operation::RepeatBy {
+                vectors: self
+                    .get("vectors")?
+                    .as_array()
+                    .ok_or_else(|| self.error("vectors is not an array"))?
+                    .iter()
+                    .map(|v| self.point(v))
+                    .collect::<Result<Vec<Point>>>()?,
+                extent: self.index("extent")?,
+            },
+            "repeat_n" => Operation::RepeatN {
+                indexes: self.range(self.get("indexes")?)?,
+                copies: self.index("copies")?,
+                center: self.center()?,
+            },
+            "repeat_radius" => Operation::RepeatRadius {
+                indexes: self.range(self.get("indexes")?)?,
+                radius: self.number("radius")?,
+                center: self.center()?,
+            },
+            "repeat_rotational" => Operation::RepeatRotational {
+                indexes: self.range(self.get("indexes")?)?,
+                n: self.index("n")?,
+            },
+            "repeat_strip" => Operation::RepeatStrip {
+                indexes: self.range(self.get("indexes")?)?,
+                direction: self.point(self.get("direction")?)?,
+                glide: match self.table.get("glide") {
+                    Some(g) => g
+                        .as_bool()
+                        .ok_or_else(|| self.error("glide is not a boolean"))?,
+                    None => false,
+                },
+            },
+            "translate" => Operation::Translate {
+                vector: self.point(self.get("vector")?)?,
+            },
+            "rotate" => Operation::Rotate {
+                angle: self.number("angle")?,
+                center: self.center()?,
+            },
+            "reflect" => Operation::Reflect {
+                axis: Axis::new(self.center()?, self.number("angle")?),
+            },
+            _ => return Err(self.error(&format!("unknown kind {:?}", kind))),
+        })
+    }
+
+    /// Returns the fields of the inline table value, described by the same
+    /// context.
+    fn nested<'b>(&'b self, value: &'b Value) -> Result<Fields<'b>> {
+        Ok(Fields {
+            table: value
+                .as_table()
+                .ok_or_else(|| self.error("shape is not a table"))?,
+            palette: self.palette,
+            context: self.context.clone(),
+        })
+    }
+
+    /// Returns the value of the field named key.
+    fn get(&self, key: &str) -> Result<&Value> {
+        self.table
+            .get(key)
+            .ok_or_else(|| self.error(&format!("{} is missing", key)))
+    }
+
+    /// Returns the field named key as a number.
+    fn number(&self, key: &str) -> Result<f64> {
+        number(self.get(key)?).ok_or_else(|| self.error(&format!("{} is not a number", key)))
+    }
+
+    /// Returns the field named key as an index.
+    fn index(&self, key: &str) -> Result<usize> {
+        self.get(key)?
+            .as_integer()
+            .and_then(|i| usize::try_from(i).ok())
+            .ok_or_else(|| self.error(&format!("{} is not an index", key)))
+    }
+
+    /// Returns value, written as `[start, end]`, as a range.
+    fn range(&self, value: &Value) -> Result<Range<usize>> {
+        let bounds = value.as_array().filter(|a| a.len() == 2).and_then(|a| {
+            let start = usize::try_from(a[0].as_integer()?).ok()?;
+            let end = usize::try_from(a[1].as_integer()?).ok()?;
+
+            Some(start..end)
+        });
+
+        bounds.ok_or_else(|| self.error(&format!("{} is not a range [start, end]", value)))
+    }
+
+    /// Returns value, written as a name or as a range `[start, end]`, as an
+    /// anchor.
+    fn anchor(&self, value: &Value) -> Result<Anchor> {
+        match value.as_str() {
+            Some(name) => Ok(Anchor::from(name)),
+            None => Ok(Anchor::Indexes(self.range(value)?)),
+        }
+    }
+
+    /// Returns the name the fields give a shape or edge.
+    fn name(&self) -> Result<Name> {
+        let name = self
+            .get("name")?
+            .as_str()
+            .ok_or_else(|| self.error("name is not a string"))?;
+
+        Ok(Name {
+            name: String::from(name),
+            index: self.index("index")?,
+            edge: match self.table.get("edge") {
+                Some(_) => Some(self.index("edge")?),
+                None => None,
+            },
+        })
+    }
+
+    /// Returns value, written as `[x, y]`, as a point.
+    fn point(&self, value: &Value) -> Result<Point> {
+        let point = value.as_array().filter(|a| a.len() == 2).and_then(|a| {
+            Some(Point {
+                x: number(&a[0])?,
+                y: number(&a[1])?,
+            })
+        });
+
+        point.ok_or_else(|| self.error(&format!("{} is not a vector [x, y]", value)))
+    }
+
+    /// Returns the point given by the optional field center, or the origin.
+    fn center(&self) -> Result<Point> {
+        match self.table.get("center") {
+            Some(center) => self.point(center),
+            None => Ok(Point::origin()),
+        }
+    }
+
+    /// Returns the pattern given by the fields root, a shape, and steps, each
+    /// a shape with the index and edge of the pattern shape it attaches to.
+    fn pattern(&self) -> Result<Pattern> {
+        let mut pattern = Pattern::new(self.nested(self.get("root")?)?.shape()?);
+        let steps = self
+            .get("steps")?
+            .as_array()
+            .ok_or_else(|| self.error("steps is not an array"))?;
+        for step in steps {
+            let step = self.nested(step)?;
+            let index = step.index("index")?;
+            let edge = step.index("edge")?;
+            pattern = pattern
+                .add_multi(index..index + 1, edge..edge + 1, step.shape()?)
+                .map_err(|err| self.error(&err.to_string()))?;
+        }
+
+        Ok(pattern)
+    }
+
+    /// Returns the shape given by the fields sides, fill, and stroke.
+    fn shape(&self) -> Result<Shape> {
+        let sides = self
+            .get("sides")?
+            .as_integer()
+            .ok_or_else(|| self.error("sides is not an integer"))?;
+
+        Shape::new(sides as i32, self.color("fill")?, self.color("stroke")?)
+            .map_err(|err| self.error(&err.to_string()))
+    }
+
+    /// Returns the field named key as a color, named in the palette or
+    /// written as a hex triplet.
+    fn color(&self, key: &str) -> Result<Color> {
+        self.color_value(key, self.get(key)?)
+    }
+
+    /// Returns value, a color of the field named key, named in the palette
+    /// or written as a hex triplet.
+    fn color_value(&self, key: &str, value: &Value) -> Result<Color> {
+        let name = value
+            .as_str()
+            .ok_or_else(|| self.error(&format!("{} is not a string", key)))?;
+
+        match self.palette.get(name) {
+            Some(color) => Ok(*color),
+            None => Color::from_hex(name)
+                .map_err(|_| self.error(&format!("{} {:?} is not in the palette", key, name))),
+        }
+    }
+
+    /// Returns an error in the fields described by reason.
+    fn error(&self, reason: &str) -> Error {
+        invalid(format!("{}: {}", self.context, reason))
+    }
+}
+
+/// Returns value as a number, whether it is written as a float or an
+/// integer.
+fn number(value: &Value) -> Option<f64> {
+    value
+        .as_float()
+        .or_else(|| value.as_integer().map(|i| i as f64))
+}
+
+/// Returns the error of an invalid pattern file described by reason.
+fn invalid(reason: String) -> Error {
+    InvalidPatternFile { reason }
+}
@@ -0,0 +1,427 @@
+use std::{collections::HashMap, f64::consts::PI};
+
+use crate::{
+    math, shape::corners, Axis, Color, Corner, Error, Error::*, PaintMode, Point, Polygon, Result,
+    Tile,
+};
+
+#[cfg(feature = "cairo")]
+use crate::shape;
+
+/// The length of one arm of a marking's arrowhead, in model units.
+#[cfg(feature = "cairo")]
+const ARROW_SIZE: f64 = 0.2;
+
+/// The angle (in radians) between an arrowhead's arms and its shaft.
+#[cfg(feature = "cairo")]
+const ARROW_ANGLE: f64 = PI / 5.0;
+
+/// The two rhombi of Penrose's P3 tiling, whose sides have length 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PenroseKind {
+    /// The thick rhombus, with interior angles of 72° at its vertices with
+    /// index 1 and 3 and 108° at the others.
+    Thick,
+    /// The thin rhombus, with interior angles of 36° at its vertices with
+    /// index 1 and 3 and 144° at the others.
+    Thin,
+}
+
+/// The arrow marking an edge of a Penrose rhombus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arrow {
+    Single,
+    Double,
+}
+
+impl PenroseKind {
+    /// Returns the interior angle (in radians) at the vertex with index
+    /// vertex.
+    pub fn angle(&self, vertex: usize) -> f64 {
+        let acute = match self {
+            PenroseKind::Thick => 2.0 * PI / 5.0,
+            PenroseKind::Thin => PI / 5.0,
+        };
+        match vertex % 2 {
+            1 => acute,
+            _ => PI - acute,
+        }
+    }
+
+    /// Returns the arrow marking the edge with index edge (from the vertex
+    /// with index edge to the next), and whether it points toward the edge's
+    /// end rather than its start.
+    /// On a thick rhombus, double arrows point away from vertex 1 and single
+    /// arrows toward vertex 3; on a thin rhombus, double arrows point toward
+    /// vertex 0 and single arrows toward vertex 2.
+    pub fn marking(&self, edge: usize) -> (Arrow, bool) {
+        match (self, edge % 4) {
+            (PenroseKind::Thick, 0) => (Arrow::Double, false),
+            (PenroseKind::Thick, 1) => (Arrow::Double, true),
+            (PenroseKind::Thick, 2) => (Arrow::Single, true),
+            (PenroseKind::Thick, _) => (Arrow::Single, false),
+            (PenroseKind::Thin, 0) => (Arrow::Double, false),
+            (PenroseKind::Thin, 1) => (Arrow::Single, true),
+            (PenroseKind::Thin, 2) => (Arrow::Single, false),
+            (PenroseKind::Thin, _) => (Arrow::Double, true),
+        }
+    }
+}
+
+/// A rhombus of Penrose's P3 tiling, with arrows on its edges that enforce
+/// the tiling's matching rules: two rhombi may only share an edge if its
+/// arrows are alike and point the same way.
+/// Patches are built by hand edge to edge with `adjacent`, checked with
+/// `validate`, and added to a model with `Model::add_tile`.
+#[derive(Clone, Copy, Debug)]
+pub struct PenroseRhombus {
+    kind: PenroseKind,
+    vertices: [Point; 4],
+    fill: Color,
+    stroke: Color,
+    corner: Corner,
+    paint: PaintMode,
+    markings: bool,
+}
+
+impl PenroseRhombus {
+    /// Returns a new rhombus of kind centered at the origin, with its edge
+    /// with index 0 horizontal.
+    pub fn new(kind: PenroseKind, fill: Color, stroke: Color) -> PenroseRhombus {
+        let rhombus = PenroseRhombus {
+            kind,
+            vertices: trace(kind, 0, Point::origin(), Point { x: 1.0, y: 0.0 }),
+            fill,
+            stroke,
+            corner: Corner::default(),
+            paint: PaintMode::default(),
+            markings: true,
+        };
+
+        rhombus.clone_at(Point::origin())
+    }
+
+    /// Returns the rhombus of kind adjacent to the edge with index edge,
+    /// sharing it as its edge with index shared.
+    pub fn adjacent(
+        &self,
+        edge: usize,
+        kind: PenroseKind,
+        shared: usize,
+        fill: Color,
+        stroke: Color,
+    ) -> Result<PenroseRhombus> {
+        PenroseRhombus::on_edge(self, edge, kind, shared, fill, stroke)
+    }
+
+    /// Returns the rhombus of kind adjacent to the edge with index edge of
+    /// polygon, which may be a regular `Shape`, sharing it as its edge with
+    /// index shared.
+    pub fn on_edge<P: Polygon>(
+        polygon: &P,
+        edge: usize,
+        kind: PenroseKind,
+        shared: usize,
+        fill: Color,
+        stroke: Color,
+    ) -> Result<PenroseRhombus> {
+        let points = polygon.points(0.0)?;
+        let (p0, p1) = points
+            .get(edge)
+            .zip(points.get(edge + 1))
+            .ok_or(OutOfBounds {
+                index: edge,
+                length: points.len().saturating_sub(1),
+                name: String::from("polygon edges"),
+            })?;
+        if shared >= 4 {
+            return Err(OutOfBounds {
+                index: shared,
+                length: 4,
+                name: String::from("rhombus edges"),
+            });
+        }
+
+        let mut rhombus = PenroseRhombus::new(kind, fill, stroke);
+        rhombus.vertices = trace(kind, shared, *p1, *p0);
+
+        Ok(rhombus)
+    }
+
+    /// Returns the rhombus with its corners rendered in the corner style.
+    pub fn with_corner(mut self, corner: Corner) -> PenroseRhombus {
+        self.corner = corner;
+
+        self
+    }
+
+    /// Returns the rhombus with the parts painted by paint mode.
+    pub fn with_paint(mut self, paint: PaintMode) -> PenroseRhombus {
+        self.paint = paint;
+
+        self
+    }
+
+    /// Returns the rhombus with its edges' arrows rendered (the default) or
+    /// not.
+    pub fn with_markings(mut self, markings: bool) -> PenroseRhombus {
+        self.markings = markings;
+
+        self
+    }
+
+    /// Returns the rhombus' kind.
+    pub fn kind(&self) -> PenroseKind {
+        self.kind
+    }
+
+    /// Returns the rhombus' four vertices in edge order.
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Returns the rhombus' center.
+    pub fn point(&self) -> Point {
+        self.vertices[0].midpoint(self.vertices[2])
+    }
+
+    /// Returns the rhombus' fill.
+    pub fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the rhombus' stroke.
+    pub fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns true if the rhombus' edges' arrows are rendered.
+    pub fn markings(&self) -> bool {
+        self.markings
+    }
+
+    /// Returns a copy of the rhombus centered at point.
+    pub fn clone_at(&self, point: Point) -> PenroseRhombus {
+        let vector = point - self.point();
+        let mut r = *self;
+        for v in r.vertices.iter_mut() {
+            *v = *v + vector;
+        }
+
+        r
+    }
+
+    /// Renders the arrows on the edges of the rhombus with vertices points.
+    #[cfg(feature = "cairo")]
+    fn render_markings(&self, context: &cairo::Context, points: &[Point]) -> Result<()> {
+        context.save()?;
+        let (r, g, b) = self.stroke.rgb_unit_int();
+        context.set_source_rgb(r, g, b);
+        context.set_line_width(context.line_width() / 2.0);
+        for edge in 0..4 {
+            let (arrow, forward) = self.kind.marking(edge);
+            let (tail, head) = match forward {
+                true => (points[edge], points[edge + 1]),
+                false => (points[edge + 1], points[edge]),
+            };
+            let length = tail.distance(head);
+            if length == 0.0 {
+                continue;
+            }
+
+            // Double arrows are two arrowheads, one behind the other, about
+            // the edge's midpoint.
+            let direction = (head - tail) * (1.0 / length);
+            let heads = match arrow {
+                Arrow::Single => vec![0.0],
+                Arrow::Double => vec![-0.3, 0.3],
+            };
+            for offset in heads {
+                let tip = tail.midpoint(head) + direction * ((offset + 0.5) * ARROW_SIZE);
+                let arm = direction * -ARROW_SIZE;
+                let (left, right) = (
+                    tip + arm.rotate(ARROW_ANGLE, Point::origin()),
+                    tip + arm.rotate(-ARROW_ANGLE, Point::origin()),
+                );
+                context.move_to(left.x, left.y);
+                context.line_to(tip.x, tip.y);
+                context.line_to(right.x, right.y);
+            }
+        }
+        context.stroke()?;
+        context.restore()?;
+
+        Ok(())
+    }
+}
+
+/// Returns the vertices of the rhombus of kind whose vertex with index start
+/// is at p0 and the next vertex is at p1.
+fn trace(kind: PenroseKind, start: usize, p0: Point, p1: Point) -> [Point; 4] {
+    let mut vertices = [p0; 4];
+    vertices[(start + 1) % 4] = p1;
+    let mut heading = math::atan2(p1.y - p0.y, p1.x - p0.x);
+    for i in 1..3 {
+        let v = (start + i) % 4;
+        heading += PI - kind.angle(v);
+        vertices[(v + 1) % 4] = Point {
+            x: vertices[v].x + math::cos(heading),
+            y: vertices[v].y + math::sin(heading),
+        };
+    }
+
+    vertices
+}
+
+/// Checks that a hand-constructed patch of rhombi obeys Penrose's matching
+/// rules: wherever two rhombi share an edge, its arrows on either side must be
+/// alike and point the same way.
+/// Rhombi are expected to meet edge to edge.
+pub fn validate(rhombi: &[PenroseRhombus]) -> Result<()> {
+    // Each edge seen so far, keyed by the tail and head of its arrow.
+    let mut seen: HashMap<(Point, Point), (usize, usize, Arrow)> = HashMap::new();
+    for (i, r) in rhombi.iter().enumerate() {
+        for edge in 0..4 {
+            let (arrow, forward) = r.kind.marking(edge);
+            let (p0, p1) = (r.vertices[edge], r.vertices[(edge + 1) % 4]);
+            let (tail, head) = match forward {
+                true => (p0, p1),
+                false => (p1, p0),
+            };
+
+            let neighbor = match (seen.get(&(tail, head)), seen.get(&(head, tail))) {
+                (Some((j, e, a)), _) if *a != arrow => Some((*j, *e)),
+                (_, Some((j, e, _))) => Some((*j, *e)),
+                _ => None,
+            };
+            if let Some((j, e)) = neighbor {
+                return Err(UnmatchedEdge {
+                    tile: i,
+                    edge,
+                    neighbor: j,
+                    neighbor_edge: e,
+                });
+            }
+            seen.insert((tail, head), (i, edge, arrow));
+        }
+    }
+
+    Ok(())
+}
+
+impl Polygon for PenroseRhombus {
+    /// Returns the polygon's points.
+    /// A rhombus has an inscribed circle, so insetting it by margin scales it
+    /// about its center; a margin that reaches the inradius is an error.
+    fn points(&self, margin: f64) -> Result<Vec<Point>> {
+        let center = self.point();
+        let inradius = math::sin(self.kind.angle(1)) / 2.0;
+        if margin.is_nan() || margin >= inradius {
+            return Err(Error::margin_reaches("margin", margin, inradius, 4));
+        }
+        let scale = 1.0 - margin / inradius;
+
+        let v = self.vertices;
+        let points = [v[0], v[1], v[2], v[3], v[0]]
+            .iter()
+            .map(|p| p.scale(scale, center))
+            .collect();
+
+        Ok(points)
+    }
+
+    /// Returns the rhombus' points inset by margin with its corners cut in
+    /// its corner style.
+    fn outline_points(&self, margin: f64) -> Result<Vec<Point>> {
+        Ok(corners(&self.points(margin)?, self.corner))
+    }
+
+    /// Renders the polygon, with its edges' arrows if markings are enabled.
+    #[cfg(feature = "cairo")]
+    fn render(&self, context: &cairo::Context, margin: f64) -> Result<()> {
+        self.render_snapped(context, margin, None)
+    }
+
+    /// Renders the polygon as `render` does, with its stroke snapped to the
+    /// centers of pixels pixel device units wide if pixel is some. Its
+    /// arrows are not snapped.
+    #[cfg(feature = "cairo")]
+    fn render_snapped(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        let points = self.points(margin)?;
+        shape::render_snapped(
+            context,
+            &self.outline_points(margin)?,
+            self.fill,
+            self.stroke,
+            self.paint,
+            pixel,
+        )?;
+        if self.markings {
+            self.render_markings(context, &points)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Tile for PenroseRhombus {
+    /// Returns the tile's center.
+    fn point(&self) -> Point {
+        PenroseRhombus::point(self)
+    }
+
+    /// Returns a copy of the tile moved by vector.
+    fn translated(&self, vector: Point) -> Box<dyn Tile> {
+        Box::new(self.clone_at(PenroseRhombus::point(self) + vector))
+    }
+
+    /// Returns a copy of the tile rotated by angle (in radians) about center.
+    fn rotated(&self, angle: f64, center: Point) -> Box<dyn Tile> {
+        let mut r = *self;
+        for v in r.vertices.iter_mut() {
+            *v = v.rotate(angle, center);
+        }
+
+        Box::new(r)
+    }
+
+    /// Returns a copy of the tile mirrored across axis.
+    fn reflected(&self, axis: &Axis) -> Box<dyn Tile> {
+        // Each vertex keeps its index, so the arrows are mirrored with the
+        // edges they mark.
+        let mut r = *self;
+        for v in r.vertices.iter_mut() {
+            *v = axis.reflect(*v);
+        }
+
+        Box::new(r)
+    }
+
+    /// Returns a copy of the tile filled with fill.
+    fn filled(&self, fill: Color) -> Box<dyn Tile> {
+        let mut r = *self;
+        r.fill = fill;
+
+        Box::new(r)
+    }
+
+    /// Returns the color the tile is filled with.
+    fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the color the tile is stroked with.
+    fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns whether the tile is filled, stroked, or both.
+    fn paint(&self) -> PaintMode {
+        self.paint
+    }
+}
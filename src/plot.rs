@@ -0,0 +1,183 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write,
+};
+
+use crate::{shape::Key, Point};
+
+/// The number of HPGL plotter units in a millimeter.
+const HPGL_UNITS_PER_MM: f64 = 40.0;
+
+/// The height (in millimeters) a G-code pen is raised to between lines.
+const PEN_UP_Z: f64 = 5.0;
+
+/// The speed (in millimeters per minute) a G-code pen draws at.
+const FEED_RATE: f64 = 1000.0;
+
+/// Returns the part of the segment from p0 to p1 within extent, half the width
+/// and height of a rectangle centered on the origin, or none if it lies
+/// outside of the rectangle.
+pub(crate) fn clip(p0: Point, p1: Point, extent: (f64, f64)) -> Option<(Point, Point)> {
+    let (w, h) = extent;
+    let (dx, dy) = (p1.x - p0.x, p1.y - p0.y);
+    let (mut t0, mut t1) = (0.0, 1.0);
+    // Each side bounds the segment's parameter from one end.
+    for (p, q) in [
+        (-dx, p0.x + w),
+        (dx, w - p0.x),
+        (-dy, p0.y + h),
+        (dy, h - p0.y),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+            continue;
+        }
+
+        let t = q / p;
+        if p < 0.0 {
+            t0 = f64::max(t0, t);
+        } else {
+            t1 = f64::min(t1, t);
+        }
+    }
+    if t0 >= t1 {
+        return None;
+    }
+
+    let at = |t: f64| Point {
+        x: p0.x + dx * t,
+        y: p0.y + dy * t,
+    };
+
+    Some((at(t0), at(t1)))
+}
+
+/// Returns segments joined into as few polylines as a greedy walk finds,
+/// drawing each distinct segment (with ends equal to precision decimal
+/// places) once.
+/// Walks start at vertices with an odd number of segments, where some line
+/// must end, before the rest, top to bottom and then left to right, so the
+/// same segments always produce the same polylines.
+pub(crate) fn chain(segments: &[(Point, Point)], precision: i32) -> Vec<Vec<Point>> {
+    let mut edges = Vec::new();
+    let mut seen = HashSet::new();
+    for (p0, p1) in segments {
+        let (k0, k1) = (p0.key(precision), p1.key(precision));
+        if k0 != k1 && seen.insert((k0.min(k1), k0.max(k1))) {
+            edges.push(((k0, *p0), (k1, *p1)));
+        }
+    }
+
+    // Vertices are ordered by their keys' y and then x.
+    let order = |k: &Key| (k.1, k.0);
+    let mut incident: BTreeMap<(i64, i64), Vec<usize>> = BTreeMap::new();
+    for (i, ((k0, _), (k1, _))) in edges.iter().enumerate() {
+        incident.entry(order(k0)).or_default().push(i);
+        incident.entry(order(k1)).or_default().push(i);
+    }
+    let mut starts = incident
+        .iter()
+        .filter(|(_, es)| es.len() % 2 == 1)
+        .map(|(k, _)| *k)
+        .collect::<Vec<(i64, i64)>>();
+    starts.extend(
+        incident
+            .iter()
+            .filter(|(_, es)| es.len() % 2 == 0)
+            .map(|(k, _)| *k),
+    );
+
+    let mut used = vec![false; edges.len()];
+    let mut polylines = Vec::new();
+    for start in starts {
+        loop {
+            let mut vertex = start;
+            let mut line: Vec<Point> = Vec::new();
+            while let Some(i) = incident[&vertex].iter().copied().find(|i| !used[*i]) {
+                used[i] = true;
+                let ((k0, p0), (k1, p1)) = edges[i];
+                let (from, to, next) = match order(&k0) == vertex {
+                    true => (p0, p1, order(&k1)),
+                    false => (p1, p0, order(&k0)),
+                };
+                if line.is_empty() {
+                    line.push(from);
+                }
+                line.push(to);
+                vertex = next;
+            }
+            if line.is_empty() {
+                break;
+            }
+
+            polylines.push(line);
+        }
+    }
+
+    polylines
+}
+
+/// Returns polylines (in model units, centered on the origin with the y axis
+/// pointing down) as HPGL for a pen plotter, with edges mm millimeters long
+/// and the bottom left corner of the region extent (half its width and
+/// height) at the plotter's origin.
+pub(crate) fn hpgl(polylines: &[Vec<Point>], extent: (f64, f64), mm: f64) -> String {
+    let unit = |p: &Point| {
+        let (x, y) = physical(p, extent, mm);
+        (
+            (x * HPGL_UNITS_PER_MM).round() as i64,
+            (y * HPGL_UNITS_PER_MM).round() as i64,
+        )
+    };
+
+    // Writing to a String cannot fail.
+    let mut hpgl = String::from("IN;SP1;\n");
+    for line in polylines {
+        let (x, y) = unit(&line[0]);
+        let _ = writeln!(hpgl, "PU{},{};", x, y);
+        let points = line[1..]
+            .iter()
+            .map(|p| {
+                let (x, y) = unit(p);
+                format!("{},{}", x, y)
+            })
+            .collect::<Vec<String>>()
+            .join(",");
+        let _ = writeln!(hpgl, "PD{};", points);
+    }
+    hpgl.push_str("PU;SP0;\n");
+
+    hpgl
+}
+
+/// Returns polylines (as for `hpgl`) as G-code in millimeters for a pen
+/// plotter or CNC machine that lowers its pen to Z0 to draw and raises it to
+/// `PEN_UP_Z` to move.
+pub(crate) fn gcode(polylines: &[Vec<Point>], extent: (f64, f64), mm: f64) -> String {
+    // Writing to a String cannot fail.
+    let mut gcode = String::from("G21\nG90\n");
+    let _ = writeln!(gcode, "G0 Z{}", PEN_UP_Z);
+    for line in polylines {
+        let (x, y) = physical(&line[0], extent, mm);
+        let _ = writeln!(gcode, "G0 X{:.3} Y{:.3}", x, y);
+        let _ = writeln!(gcode, "G1 Z0 F{}", FEED_RATE);
+        for p in line[1..].iter() {
+            let (x, y) = physical(p, extent, mm);
+            let _ = writeln!(gcode, "G1 X{:.3} Y{:.3}", x, y);
+        }
+        let _ = writeln!(gcode, "G0 Z{}", PEN_UP_Z);
+    }
+    gcode.push_str("G0 X0 Y0\n");
+
+    gcode
+}
+
+/// Returns point (in model units) in millimeters from the bottom left corner
+/// of the region extent, with the y axis pointing up.
+fn physical(point: &Point, extent: (f64, f64), mm: f64) -> (f64, f64) {
+    let (w, h) = extent;
+
+    ((point.x + w) * mm, (h - point.y) * mm)
+}
@@ -0,0 +1,151 @@
+use crate::{
+    render::{MM_PER_INCH, POINTS_PER_INCH},
+    Color, Rect, Result,
+};
+
+/// The default bleed (in millimeters), the most print shops ask for.
+const BLEED: f64 = 3.0;
+
+/// The length (in millimeters) of each crop mark.
+const CROP_MARK_LENGTH: f64 = 5.0;
+
+/// The gap (in millimeters) between the bleed and the crop marks, so the
+/// marks never print on the trimmed page.
+const CROP_MARK_OFFSET: f64 = 1.0;
+
+/// The width (in points) of crop marks' hairlines.
+const CROP_MARK_WIDTH: f64 = 0.25;
+
+/// The largest dot gain that can be compensated: beyond it, the gain model
+/// would print a solid lighter than a darker tint.
+const MAX_DOT_GAIN: f64 = 0.25;
+
+/// Configures print-ready output made with `Model::write_print_pdf`.
+/// The tiling is drawn past the trimmed page by a bleed, so a slightly
+/// misaligned cut leaves no white sliver, and crop marks outside the bleed
+/// show the print shop where to cut. Models repeat a little past their
+/// canvas; a bleed wider than that needs `Model::with_coverage` to fill it.
+#[derive(Clone, Copy, Debug)]
+pub struct PrintOptions {
+    bleed: f64,
+    crop_marks: bool,
+    dot_gain: f64,
+}
+
+impl PrintOptions {
+    /// Returns the default options: a 3 mm bleed, crop marks, and colors
+    /// left as they are.
+    pub fn new() -> PrintOptions {
+        PrintOptions {
+            bleed: BLEED,
+            crop_marks: true,
+            dot_gain: 0.0,
+        }
+    }
+
+    /// Sets the width (in millimeters) of the bleed.
+    pub fn bleed(mut self, bleed: f64) -> PrintOptions {
+        self.bleed = bleed.max(0.0);
+
+        self
+    }
+
+    /// Sets whether crop marks are drawn at the corners of the trimmed page.
+    pub fn crop_marks(mut self, crop_marks: bool) -> PrintOptions {
+        self.crop_marks = crop_marks;
+
+        self
+    }
+
+    /// Sets the dot gain to compensate for: how much darker (as a fraction
+    /// of full ink) a 50% tint prints as ink spreads into the paper, from 0
+    /// (the default) to 0.25. Coated stock gains about 0.15 and uncoated
+    /// stock about 0.2.
+    /// Colors are lightened so that, once printed, their tints match the
+    /// screen.
+    pub fn dot_gain(mut self, dot_gain: f64) -> PrintOptions {
+        self.dot_gain = dot_gain.clamp(0.0, MAX_DOT_GAIN);
+
+        self
+    }
+
+    /// Returns the width (in points) of the border around the trimmed page
+    /// holding the bleed and any crop marks.
+    pub(crate) fn border(&self) -> f64 {
+        let border = match self.crop_marks {
+            true => self.bleed + CROP_MARK_OFFSET + CROP_MARK_LENGTH,
+            false => self.bleed,
+        };
+
+        points(border)
+    }
+
+    /// Returns the width (in points) of the bleed.
+    pub(crate) fn bleed_points(&self) -> f64 {
+        points(self.bleed)
+    }
+
+    /// Returns color lightened to compensate for the dot gain.
+    /// The press is modeled as printing a tint covering t of the paper as
+    /// t + 4gt(1 - t) for a dot gain g, which this inverts channel by
+    /// channel.
+    pub(crate) fn adjust(&self, color: Color) -> Color {
+        let g = self.dot_gain;
+        if g <= 0.0 {
+            return color;
+        }
+
+        let channel = |v: i32| {
+            let t = 1.0 - v as f64 / 255.0;
+            let b = 1.0 + 4.0 * g;
+            let t = (b - (b * b - 16.0 * g * t).max(0.0).sqrt()) / (8.0 * g);
+            ((1.0 - t) * 255.0).round() as i32
+        };
+
+        Color::rgb(
+            channel(color.red()),
+            channel(color.green()),
+            channel(color.blue()),
+        )
+    }
+
+    /// Draws crop marks in black at the corners of trim (in the context's
+    /// units, points), outside the bleed, if crop marks are enabled.
+    pub(crate) fn mark(&self, context: &cairo::Context, trim: Rect) -> Result<()> {
+        if !self.crop_marks {
+            return Ok(());
+        }
+
+        let (x0, y0) = (trim.origin().x, trim.origin().y);
+        let (x1, y1) = (x0 + trim.width(), y0 + trim.height());
+        let start = self.bleed_points() + points(CROP_MARK_OFFSET);
+        let end = start + points(CROP_MARK_LENGTH);
+        for (x, dx) in [(x0, -1.0), (x1, 1.0)] {
+            for (y, dy) in [(y0, -1.0), (y1, 1.0)] {
+                // Each corner has a mark along each of the trim's edges
+                // through it.
+                context.move_to(x + dx * start, y);
+                context.line_to(x + dx * end, y);
+                context.move_to(x, y + dy * start);
+                context.line_to(x, y + dy * end);
+            }
+        }
+        context.set_source_rgb(0.0, 0.0, 0.0);
+        context.set_line_width(CROP_MARK_WIDTH);
+        context.set_dash(&[], 0.0);
+        context.stroke()?;
+
+        Ok(())
+    }
+}
+
+impl Default for PrintOptions {
+    fn default() -> PrintOptions {
+        PrintOptions::new()
+    }
+}
+
+/// Returns mm millimeters in points.
+fn points(mm: f64) -> f64 {
+    mm * POINTS_PER_INCH / MM_PER_INCH
+}
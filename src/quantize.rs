@@ -0,0 +1,95 @@
+use std::{cmp::Ordering::Less, collections::HashMap};
+
+/// An RGBA color and the number of pixels with that color.
+type Entry = ([u8; 4], usize);
+
+/// Returns a palette of at most max_colors colors (at least one) for an image
+/// with the pixel counts of counts, found by median cut: the colors are
+/// repeatedly split along the channel with the widest range until there are
+/// enough boxes, and each box contributes its pixel-weighted mean color.
+/// Images with few enough colors keep their colors exactly.
+pub(crate) fn palette(counts: &HashMap<[u8; 4], usize>, max_colors: usize) -> Vec<[u8; 4]> {
+    let max_colors = max_colors.max(1);
+    let mut colors = counts.iter().map(|(c, n)| (*c, *n)).collect::<Vec<Entry>>();
+    colors.sort_unstable();
+    if colors.len() <= max_colors {
+        return colors.into_iter().map(|(c, _)| c).collect();
+    }
+
+    let mut boxes = vec![colors];
+    while boxes.len() < max_colors {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| (i, widest_channel(b)))
+            .max_by_key(|(_, (_, range))| *range);
+        let (i, (channel, _)) = match widest {
+            Some(widest) => widest,
+            None => break,
+        };
+
+        let mut b = boxes.swap_remove(i);
+        b.sort_by_key(|(c, _)| c[channel]);
+        let half = b.iter().map(|(_, n)| n).sum::<usize>() / 2;
+        let mut seen = 0;
+        let split = b
+            .iter()
+            .position(|(_, n)| {
+                seen += n;
+                seen > half
+            })
+            .unwrap_or(0)
+            .clamp(1, b.len() - 1);
+        let rest = b.split_off(split);
+        boxes.push(b);
+        boxes.push(rest);
+    }
+
+    boxes.iter().map(|b| mean(b)).collect()
+}
+
+/// Returns the index of the color in palette nearest to color.
+pub(crate) fn nearest(palette: &[[u8; 4]], color: [u8; 4]) -> usize {
+    let distance = |p: &[u8; 4]| {
+        p.iter()
+            .zip(color.iter())
+            .map(|(a, b)| (*a as i32 - *b as i32).pow(2))
+            .sum::<i32>()
+    };
+
+    palette
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(a).partial_cmp(&distance(b)).unwrap_or(Less))
+        .map_or(0, |(i, _)| i)
+}
+
+/// Returns the channel along which the colors of entries spread the most, and
+/// how far they spread.
+fn widest_channel(entries: &[Entry]) -> (usize, u8) {
+    (0..4)
+        .map(|channel| {
+            let min = entries.iter().map(|(c, _)| c[channel]).min().unwrap_or(0);
+            let max = entries.iter().map(|(c, _)| c[channel]).max().unwrap_or(0);
+
+            (channel, max - min)
+        })
+        .max_by_key(|(_, range)| *range)
+        .unwrap_or((0, 0))
+}
+
+/// Returns the pixel-weighted mean color of entries.
+fn mean(entries: &[Entry]) -> [u8; 4] {
+    let total = entries.iter().map(|(_, n)| n).sum::<usize>().max(1);
+    let mut mean = [0; 4];
+    for (channel, m) in mean.iter_mut().enumerate() {
+        let sum = entries
+            .iter()
+            .map(|(c, n)| c[channel] as usize * n)
+            .sum::<usize>();
+        *m = ((sum + total / 2) / total) as u8;
+    }
+
+    mean
+}
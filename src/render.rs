@@ -0,0 +1,1651 @@
+use std::fmt;
+
+#[cfg(feature = "cairo")]
+use std::{
+    collections::HashMap,
+    f64::consts::PI,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+
+#[cfg(feature = "freetype")]
+use std::path::PathBuf;
+
+use rand::Rng;
+
+use crate::{
+    hash, Antialias, Color, Corner, Error::InvalidMargin, LineCap, LineJoin, Outline, PaintMode,
+    Point, Result, Theme,
+};
+
+#[cfg(feature = "cairo")]
+use crate::{hash::Fnv, quantize, svg, Error::CanvasTooLarge, Motif, Palette, Polygon, Shape};
+
+/// The number of millimeters in an inch.
+pub(crate) const MM_PER_INCH: f64 = 25.4;
+
+/// The number of PDF points in an inch.
+#[cfg(feature = "pdf")]
+pub(crate) const POINTS_PER_INCH: f64 = 72.0;
+
+/// The largest width or height (in pixels) of a surface cairo can draw on.
+pub const MAX_SURFACE_SIZE: i32 = 32767;
+
+/// The type of PNG chunks holding text.
+#[cfg(feature = "cairo")]
+const TEXT: png::chunk::ChunkType = *b"tEXt";
+
+/// The keyword of the PNG text chunk holding a render's metadata as JSON.
+#[cfg(feature = "cairo")]
+const METADATA_KEYWORD: &str = "tiling";
+
+/// The height (in pixels) of each row of a legend.
+#[cfg(feature = "cairo")]
+const LEGEND_ROW: f64 = 24.0;
+
+/// The padding (in pixels) around the rows of a legend.
+#[cfg(feature = "cairo")]
+const LEGEND_PADDING: f64 = 8.0;
+
+/// The default height (in pixels) of label text.
+const LABEL_SIZE: f64 = 18.0;
+
+/// The largest height (in edge lengths) of label text, so labels stay within
+/// their shapes at small scales.
+#[cfg(feature = "cairo")]
+const MAX_LABEL_SIZE: f64 = 0.25;
+
+/// The padding around label text inside its background box, as a fraction of
+/// the text's height.
+#[cfg(feature = "cairo")]
+const LABEL_PADDING: f64 = 0.2;
+
+/// The luminance below which labels are drawn in white rather than black,
+/// where black and white text contrast equally with the color beneath.
+#[cfg(feature = "cairo")]
+pub(crate) const LABEL_LUMINANCE: f64 = 0.179;
+
+/// The largest turn (in degrees) of a hue jittered by an amplitude of 1.
+const JITTER_HUE: f64 = 180.0;
+
+/// The height (in pixels) of an annotation's title text.
+#[cfg(feature = "cairo")]
+const TITLE_SIZE: f64 = 24.0;
+
+/// The height (in pixels) of annotation text other than titles.
+#[cfg(feature = "cairo")]
+const CAPTION_SIZE: f64 = 16.0;
+
+/// The padding (in pixels) between annotations and the sides of a render.
+#[cfg(feature = "cairo")]
+const ANNOTATION_PADDING: f64 = 12.0;
+
+/// The width (in pixels) of the lines of scale bars and axes.
+#[cfg(feature = "cairo")]
+const ANNOTATION_LINE: f64 = 2.0;
+
+/// The length (in pixels) of the ticks of scale bars and axes.
+#[cfg(feature = "cairo")]
+const TICK_SIZE: f64 = 6.0;
+
+/// The characters of an ASCII preview, from the lightest to the darkest.
+#[cfg(feature = "cairo")]
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// What each vertex of a shape is labeled with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VertexLabel {
+    /// The vertex's index, which is also the index of the edge starting at it.
+    Index,
+    /// The vertex's coordinates (in edge lengths from the canvas' center).
+    Coordinates,
+}
+
+/// The font of a render's text, set with `RenderOptions::label_font`.
+#[cfg(feature = "cairo")]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Font {
+    /// A font family (such as `"serif"` or `"DejaVu Sans"`) and weight,
+    /// looked up by cairo's toy font API among the fonts installed on the
+    /// system.
+    Family(String, cairo::FontWeight),
+    /// The TrueType or OpenType font file at a path, loaded through FreeType.
+    #[cfg(feature = "freetype")]
+    File(PathBuf),
+}
+
+/// A figure annotation drawn over a render, added with
+/// `RenderOptions::annotate`.
+/// Annotations are sized in pixels, so they look the same at any scale.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Annotation {
+    /// A title centered along the top of the render.
+    Title(String),
+    /// A caption centered along the bottom of the render.
+    Caption(String),
+    /// A bar length edge lengths long in the bottom left corner of the
+    /// render, labeled with label (such as "1 cm").
+    ScaleBar { length: f64, label: String },
+    /// The x and y axes through the model's origin, with a tick at every
+    /// edge length.
+    Axes,
+}
+
+/// A function called with the render context, a shape, and the shape's index
+/// once for every shape in a render.
+#[cfg(feature = "cairo")]
+pub type ShapeHook = Arc<dyn Fn(&cairo::Context, &Shape, usize) -> Result<()> + Send + Sync>;
+
+/// Configures how a model is rendered.
+#[derive(Clone)]
+pub struct RenderOptions {
+    pub(crate) background: Option<Color>,
+    pub(crate) margin: f64,
+    pub(crate) dual_margin: Option<f64>,
+    pub(crate) line_width: f64,
+    pub(crate) line_cap: LineCap,
+    pub(crate) line_join: LineJoin,
+    pub(crate) dash: Vec<f64>,
+    pub(crate) dash_offset: f64,
+    pub(crate) miter_limit: f64,
+    pub(crate) show_labels: bool,
+    pub(crate) vertex_labels: Option<VertexLabel>,
+    pub(crate) label_size: f64,
+    pub(crate) label_background: Option<Color>,
+    #[cfg(feature = "cairo")]
+    pub(crate) label_font: Option<Font>,
+    pub(crate) label_color: Option<Color>,
+    pub(crate) corner: Corner,
+    pub(crate) paint: PaintMode,
+    pub(crate) outline: Option<Outline>,
+    pub(crate) antialias: Antialias,
+    pub(crate) tolerance: Option<f64>,
+    pub(crate) supersample: u32,
+    pub(crate) pixel_snap: bool,
+    #[cfg(feature = "cairo")]
+    pub(crate) shape_hooks: Vec<ShapeHook>,
+    #[cfg(feature = "cairo")]
+    pub(crate) motifs: Vec<Motif>,
+    pub(crate) jitter: Option<(f64, u64)>,
+    pub(crate) dedup_edges: bool,
+    pub(crate) annotations: Vec<Annotation>,
+    pub(crate) metadata: bool,
+    pub(crate) debug_edges: bool,
+    pub(crate) style_set: Option<String>,
+}
+
+impl RenderOptions {
+    /// Returns the default options: a white background, no margin, solid
+    /// strokes 0.1 wide with round caps and joins and no outlines, sharp
+    /// corners, filled and stroked polygons, no labels, and cairo's default
+    /// antialiasing without supersampling.
+    pub fn new() -> RenderOptions {
+        RenderOptions {
+            background: Some(Color::WHITE),
+            margin: 0.0,
+            dual_margin: None,
+            line_width: 0.1,
+            line_cap: LineCap::Round,
+            line_join: LineJoin::Round,
+            dash: Vec::new(),
+            dash_offset: 0.0,
+            miter_limit: 10.0,
+            show_labels: false,
+            vertex_labels: None,
+            label_size: LABEL_SIZE,
+            label_background: None,
+            #[cfg(feature = "cairo")]
+            label_font: None,
+            label_color: None,
+            corner: Corner::default(),
+            paint: PaintMode::default(),
+            outline: None,
+            antialias: Antialias::Default,
+            tolerance: None,
+            supersample: 1,
+            pixel_snap: false,
+            #[cfg(feature = "cairo")]
+            shape_hooks: Vec::new(),
+            #[cfg(feature = "cairo")]
+            motifs: Vec::new(),
+            jitter: None,
+            dedup_edges: false,
+            annotations: Vec::new(),
+            metadata: false,
+            debug_edges: false,
+            style_set: None,
+        }
+    }
+
+    /// Sets the background color, or leaves the background transparent if it
+    /// is `None` so the render can be composited onto other artwork.
+    pub fn background(mut self, background: impl Into<Option<Color>>) -> RenderOptions {
+        self.background = background.into();
+
+        self
+    }
+
+    /// Sets the background color to theme's background.
+    pub fn theme(self, theme: &Theme) -> RenderOptions {
+        self.background(theme.background())
+    }
+
+    /// Sets the margin (in model units, where a polygon's side has length 1)
+    /// each polygon is inset by.
+    /// A margin that is negative or not finite, or as large as a shape's
+    /// inradius, which would turn the shape inside out, fails rendering with
+    /// `Error::InvalidMargin` instead. Dual
+    /// tilings are inset by this margin too unless `dual_margin` is set.
+    pub fn margin(mut self, margin: f64) -> RenderOptions {
+        self.margin = margin;
+
+        self
+    }
+
+    /// Sets the margin (in model units) each cell of a dual tiling is inset
+    /// by, in place of `margin`, so a tiling and its dual can be rendered with
+    /// the same options but inset differently.
+    /// Cells that a margin would turn inside out are skipped with a
+    /// `Warning::CollapsedCell`; a margin that is negative or not finite
+    /// fails rendering with `Error::InvalidMargin`.
+    pub fn dual_margin(mut self, margin: f64) -> RenderOptions {
+        self.dual_margin = Some(margin);
+
+        self
+    }
+
+    /// Returns the margin each cell of a dual tiling is inset by, ensuring it
+    /// is finite and not negative.
+    pub(crate) fn cell_margin(&self) -> Result<f64> {
+        match self.dual_margin {
+            Some(margin) => check_margin("RenderOptions::dual_margin", margin),
+            None => check_margin("RenderOptions::margin", self.margin),
+        }
+    }
+
+    /// Sets the width of polygon strokes.
+    pub fn line_width(mut self, line_width: f64) -> RenderOptions {
+        self.line_width = line_width;
+
+        self
+    }
+
+    /// Sets how the ends of strokes are drawn.
+    pub fn line_cap(mut self, line_cap: impl Into<LineCap>) -> RenderOptions {
+        self.line_cap = line_cap.into();
+
+        self
+    }
+
+    /// Sets how strokes are drawn where they meet at a polygon's vertices.
+    pub fn line_join(mut self, line_join: impl Into<LineJoin>) -> RenderOptions {
+        self.line_join = line_join.into();
+
+        self
+    }
+
+    /// Sets the lengths of the alternating dashes and gaps of strokes, starting
+    /// offset into the pattern, in the same units as the line width.
+    /// An empty pattern draws solid strokes. Edges with their own dashes keep
+    /// them.
+    pub fn dash(mut self, dash: &[f64], offset: f64) -> RenderOptions {
+        self.dash = dash.to_vec();
+        self.dash_offset = offset;
+
+        self
+    }
+
+    /// Sets the ratio of the length of a mitered join to the line width beyond
+    /// which the join is beveled instead; cairo's default is 10.
+    /// It only applies to `LineJoin::Miter` joins.
+    pub fn miter_limit(mut self, miter_limit: f64) -> RenderOptions {
+        self.miter_limit = miter_limit;
+
+        self
+    }
+
+    /// Sets the style of polygon corners.
+    pub fn corner(mut self, corner: Corner) -> RenderOptions {
+        self.corner = corner;
+
+        self
+    }
+
+    /// Sets which parts of each polygon are painted.
+    pub fn paint(mut self, paint: PaintMode) -> RenderOptions {
+        self.paint = paint;
+
+        self
+    }
+
+    /// Sets the outline drawn beneath every polygon's stroke, unless a shape
+    /// sets its own.
+    pub fn outline(mut self, outline: Outline) -> RenderOptions {
+        self.outline = Some(outline);
+
+        self
+    }
+
+    /// Sets whether shapes and their edges are labeled with their indexes.
+    pub fn labels(mut self, show_labels: bool) -> RenderOptions {
+        self.show_labels = show_labels;
+
+        self
+    }
+
+    /// Sets whether every shape's edges are drawn for debugging: an arrow
+    /// along each edge showing its direction, edge 0 highlighted, and a faint
+    /// line from the shape's center to its first vertex marking its
+    /// orientation. Together with labels, this shows which edges to pass to
+    /// `Model::add_multi`.
+    pub fn debug_edges(mut self, debug_edges: bool) -> RenderOptions {
+        self.debug_edges = debug_edges;
+
+        self
+    }
+
+    /// Sets what each vertex of every shape is labeled with, or leaves
+    /// vertices unlabeled if it is `None`.
+    /// Vertex labels are drawn whether or not shape and edge labels are.
+    pub fn vertex_labels(mut self, vertex_labels: impl Into<Option<VertexLabel>>) -> RenderOptions {
+        self.vertex_labels = vertex_labels.into();
+
+        self
+    }
+
+    /// Sets the height (in pixels) of label text, 18 by default.
+    /// Text is never taller than a quarter of an edge, so it shrinks with
+    /// small scales.
+    pub fn label_size(mut self, label_size: f64) -> RenderOptions {
+        self.label_size = label_size;
+
+        self
+    }
+
+    /// Sets the color of the box drawn behind each label so it stays readable
+    /// over strokes and dark fills, or draws no box if it is `None` (the
+    /// default).
+    pub fn label_background(mut self, background: impl Into<Option<Color>>) -> RenderOptions {
+        self.label_background = background.into();
+
+        self
+    }
+
+    /// Sets the font of label and annotation text, or uses cairo's default
+    /// sans-serif font if it is `None` (the default).
+    #[cfg(feature = "cairo")]
+    pub fn label_font(mut self, font: impl Into<Option<Font>>) -> RenderOptions {
+        self.label_font = font.into();
+
+        self
+    }
+
+    /// Sets the color of label text, or draws each label in black or white,
+    /// whichever contrasts more with the color beneath it, if it is `None`
+    /// (the default).
+    pub fn label_color(mut self, color: impl Into<Option<Color>>) -> RenderOptions {
+        self.label_color = color.into();
+
+        self
+    }
+
+    /// Sets how the edges of polygons are antialiased; backends other than
+    /// cairo antialias unless it is `Antialias::None`.
+    pub fn antialias(mut self, antialias: impl Into<Antialias>) -> RenderOptions {
+        self.antialias = antialias.into();
+
+        self
+    }
+
+    /// Sets the maximum error (in pixels) allowed when cairo approximates
+    /// curves, such as rounded corners and circles, with line segments.
+    /// Cairo's default is 0.1; larger values render faster but coarser curves.
+    pub fn tolerance(mut self, tolerance: f64) -> RenderOptions {
+        self.tolerance = Some(tolerance);
+
+        self
+    }
+
+    /// Sets the number of samples taken along each axis of every pixel.
+    /// Values greater than 1 render onto a proportionally larger canvas that is
+    /// downscaled afterwards, which smooths thin strokes at small scales at the
+    /// cost of time and memory that grow with the square of factor.
+    /// A factor of 0 is treated as 1.
+    pub fn supersample(mut self, factor: u32) -> RenderOptions {
+        self.supersample = factor.max(1);
+
+        self
+    }
+
+    /// Sets whether the lines shapes, tiles, and cells of the dual are
+    /// stroked through are moved to the centers of the pixels their corners
+    /// fall in once the tiling is scaled, so that strokes one pixel wide
+    /// along rows and columns of pixels cover exactly one of them rather than
+    /// being blurred across two by antialiasing.
+    /// Only strokes are snapped: fills, overlays, motifs, shape hooks, and
+    /// annotations keep their places. Snapping moves corners by up to half a
+    /// pixel, which can bend the outlines of small shapes noticeably; it
+    /// suits pixel-exact assets such as icons and interface backgrounds.
+    /// Pixels are those of the finished render, so a supersampled render
+    /// snaps to the larger pixels it is scaled down to. `Model::render`
+    /// and `Model::to_svg` snap strokes to the pixels of their canvases too.
+    pub fn pixel_snap(mut self, snap: bool) -> RenderOptions {
+        self.pixel_snap = snap;
+
+        self
+    }
+
+    /// Returns the size (in device units) of the pixels that strokes are
+    /// snapped to, or none if they are not snapped.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn snap_pixel(&self) -> Option<f64> {
+        self.pixel_snap.then_some(self.supersample as f64)
+    }
+
+    /// Varies the fill of every shape a little, for a hand-made look: each
+    /// fill's lightness is raised or lowered by up to amplitude (out of 1) and
+    /// its hue turned by up to amplitude times 180 degrees.
+    /// The variation of each shape is chosen at random by seed and the
+    /// shape's center, so the same seed always varies a tiling the same way.
+    pub fn color_jitter(mut self, amplitude: f64, seed: u64) -> RenderOptions {
+        self.jitter = Some((amplitude, seed));
+
+        self
+    }
+
+    /// Sets whether every shape is filled before any edge is stroked, and
+    /// each distinct edge is then stroked once, rather than every shape
+    /// stroking all of its edges.
+    /// An edge shared by two shapes is otherwise stroked twice, which makes it
+    /// look darker and thicker than the tiling's other lines when strokes are
+    /// thin or antialiased. A shared edge takes the stroke (and edge style) of
+    /// the first shape drawn with it, and edges are stroked straight, so only
+    /// fills follow corner styles.
+    pub fn dedup_edges(mut self, dedup: bool) -> RenderOptions {
+        self.dedup_edges = dedup;
+
+        self
+    }
+
+    /// Sets the name of the model's style set (see `Model::add_style_set`)
+    /// the shapes are drawn in, or draws them in their own colors if it is
+    /// `None`.
+    /// Cells colored individually keep their fills and take the set's
+    /// strokes.
+    pub fn style_set<S: Into<String>>(mut self, name: impl Into<Option<S>>) -> RenderOptions {
+        self.style_set = name.into().map(Into::into);
+
+        self
+    }
+
+    /// Adds motif to be drawn inside every visible shape after the shapes are
+    /// drawn.
+    /// Motifs are drawn in the order they were added, so several motifs may be
+    /// layered.
+    #[cfg(feature = "cairo")]
+    pub fn tile_motif(mut self, motif: Motif) -> RenderOptions {
+        self.motifs.push(motif);
+
+        self
+    }
+
+    /// Adds annotation to be drawn over the tiling, after its shapes and
+    /// labels, so figures need no editing in another tool.
+    pub fn annotate(mut self, annotation: Annotation) -> RenderOptions {
+        self.annotations.push(annotation);
+
+        self
+    }
+
+    /// Sets whether the render records the metadata of the model (see
+    /// `Model::metadata`) for `Render::write_with_metadata`.
+    /// Finding the model's notation and geometry hash takes a fraction of the
+    /// time rendering does, so it is off by default.
+    pub fn metadata(mut self, metadata: bool) -> RenderOptions {
+        self.metadata = metadata;
+
+        self
+    }
+
+    /// Adds a hook that is called for every shape after all shapes are drawn,
+    /// allowing extra decorations to be drawn on top of each shape.
+    /// The hook is given the placed shape and the index of the shape it was
+    /// placed from, as `Model::shape` takes it.
+    /// The context's state is saved before and restored after each call.
+    #[cfg(feature = "cairo")]
+    pub fn on_shape<F>(mut self, hook: F) -> RenderOptions
+    where
+        F: Fn(&cairo::Context, &Shape, usize) -> Result<()> + Send + Sync + 'static,
+    {
+        self.shape_hooks.push(Arc::new(hook));
+
+        self
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions::new()
+    }
+}
+
+/// A problem encountered while rendering that did not prevent the render.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Warning {
+    /// The dual cell at index was skipped because the margin exceeds its
+    /// inradius, so insetting it would turn it inside out.
+    CollapsedCell { index: usize, margin: f64 },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::CollapsedCell { index, margin } => write!(
+                f,
+                "dual cell {} collapses under margin {} and was skipped",
+                index, margin
+            ),
+        }
+    }
+}
+
+/// The parameters of the model a render was made from, which
+/// `Render::write_with_metadata` records in its PNG file so the image can be
+/// reproduced.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metadata {
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) scale: f64,
+    pub(crate) dpi: Option<f64>,
+    pub(crate) shapes: usize,
+    pub(crate) notation: Option<String>,
+    pub(crate) seed: Option<u64>,
+    pub(crate) hash: u64,
+    pub(crate) steps: Vec<String>,
+}
+
+impl Metadata {
+    /// Returns the width (in pixels) of the model's canvas.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Returns the height (in pixels) of the model's canvas.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns the model's scale (in pixels per edge length).
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Returns the model's resolution in dots per inch, or none if it is
+    /// unspecified.
+    pub fn dpi(&self) -> Option<f64> {
+        self.dpi
+    }
+
+    /// Returns the number of shapes placed in the model, counting repeats.
+    pub fn shapes(&self) -> usize {
+        self.shapes
+    }
+
+    /// Returns the notation of the model's tiling (see `Model::notation`), or
+    /// none if it has gaps or overlaps.
+    pub fn notation(&self) -> Option<&str> {
+        self.notation.as_deref()
+    }
+
+    /// Returns the seed the model was last colored with by
+    /// `Model::color_with` (or `Model::color_with_palette`) or
+    /// `Model::shuffle_fills`, or none.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Returns the model's geometry hash (see `Model::geometry_hash`).
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Returns the operations that built the model (see `Model::history`),
+    /// oldest first, each written as the call that applied it.
+    pub fn steps(&self) -> &[String] {
+        &self.steps
+    }
+
+    /// Returns the version of this crate that made the render.
+    pub fn version(&self) -> &'static str {
+        env!("CARGO_PKG_VERSION")
+    }
+
+    /// Returns the metadata as a JSON object. The geometry hash is written
+    /// as a hex string, since JSON numbers cannot hold every 64-bit integer.
+    pub fn to_json(&self) -> String {
+        let option = |value: Option<String>| value.unwrap_or_else(|| String::from("null"));
+        let steps = self
+            .steps
+            .iter()
+            .map(|s| json_string(s))
+            .collect::<Vec<String>>();
+
+        format!(
+            r#"{{"width":{},"height":{},"scale":{},"dpi":{},"shapes":{},"notation":{},"seed":{},"geometry_hash":"{:016x}","steps":[{}],"version":{}}}"#,
+            self.width,
+            self.height,
+            self.scale,
+            option(self.dpi.map(|dpi| dpi.to_string())),
+            self.shapes,
+            option(self.notation.as_deref().map(json_string)),
+            option(self.seed.map(|seed| seed.to_string())),
+            self.hash,
+            steps.join(","),
+            json_string(self.version()),
+        )
+    }
+}
+
+/// Returns text as a JSON string, quoted, with quotes, backslashes, and
+/// control characters escaped.
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    json
+}
+
+/// Represents a rendered model.
+#[cfg(feature = "cairo")]
+pub struct Render {
+    pub(crate) surface: cairo::ImageSurface,
+    warnings: Vec<Warning>,
+    dpi: Option<f64>,
+    metadata: Option<Metadata>,
+}
+
+#[cfg(feature = "cairo")]
+impl Render {
+    /// Returns a render of surface without warnings.
+    pub(crate) fn new(surface: cairo::ImageSurface) -> Render {
+        Render {
+            surface,
+            warnings: Vec::new(),
+            dpi: None,
+            metadata: None,
+        }
+    }
+
+    /// Returns the render with its resolution set to dpi dots per inch, or
+    /// left unspecified if it is `None`.
+    /// The resolution is written to PNG files, so they print at their
+    /// intended physical size.
+    pub fn with_dpi(mut self, dpi: impl Into<Option<f64>>) -> Render {
+        self.dpi = dpi.into();
+
+        self
+    }
+
+    /// Returns the render's resolution in dots per inch, or none if it is
+    /// unspecified.
+    pub fn dpi(&self) -> Option<f64> {
+        self.dpi
+    }
+
+    /// Returns the render with the metadata of the model it was made from
+    /// attached.
+    pub(crate) fn with_metadata(mut self, metadata: Metadata) -> Render {
+        self.metadata = Some(metadata);
+
+        self
+    }
+
+    /// Returns the metadata of the model the render was made from, or none
+    /// if it was not recorded (see `RenderOptions::metadata`) or the render
+    /// was not made from a model, such as a render read from a file.
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Returns the render with warnings attached.
+    pub(crate) fn with_warnings(mut self, warnings: Vec<Warning>) -> Render {
+        self.warnings = warnings;
+
+        self
+    }
+
+    /// Reads a render from a PNG file at path.
+    pub fn from_png<P: AsRef<Path>>(path: P) -> Result<Render> {
+        let mut file = File::open(path)?;
+
+        Ok(Render::new(cairo::ImageSurface::create_from_png(
+            &mut file,
+        )?))
+    }
+
+    /// Returns the problems encountered while rendering.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Returns the render's width in pixels.
+    pub fn width(&self) -> i32 {
+        self.surface.width()
+    }
+
+    /// Returns the render's height in pixels.
+    pub fn height(&self) -> i32 {
+        self.surface.height()
+    }
+
+    /// Returns the surface the render was drawn on.
+    pub fn surface(&self) -> &cairo::ImageSurface {
+        &self.surface
+    }
+
+    /// Returns the surface the render was drawn on, consuming the render.
+    pub fn into_surface(self) -> cairo::ImageSurface {
+        self.surface
+    }
+
+    /// Returns a context for drawing over the render in pixels, with the
+    /// origin at the top left, so watermarks or extra geometry can be added
+    /// before the render is written. `Model::context_for` returns one that
+    /// draws in model units instead.
+    pub fn context(&self) -> Result<cairo::Context> {
+        Ok(cairo::Context::new(&self.surface)?)
+    }
+
+    /// Returns whether the render has an alpha channel, which it does when its
+    /// background was left transparent.
+    pub fn is_transparent(&self) -> bool {
+        self.surface.format() == cairo::Format::ARgb32
+    }
+
+    /// Returns the red, green, and blue components of every pixel, row by row.
+    pub(crate) fn rgb(&self) -> Result<Vec<[u8; 3]>> {
+        let (width, height, stride) = (self.width(), self.height(), self.surface.stride());
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        self.surface.with_data(|data| {
+            for y in 0..height {
+                for x in 0..width {
+                    let i = (y * stride + x * 4) as usize;
+                    let p = u32::from_ne_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+                    pixels.push([(p >> 16) as u8, (p >> 8) as u8, p as u8]);
+                }
+            }
+        })?;
+
+        Ok(pixels)
+    }
+
+    /// Returns the render's pixels as 8-bit RGBA, row by row from the top.
+    /// Rows are tightly packed, `rgba8_stride` bytes apart, and each pixel is
+    /// four bytes of red, green, blue, and alpha in sRGB. The color is not
+    /// premultiplied, and alpha is always 255 unless the render's background
+    /// was left transparent.
+    /// This matches wgpu's `Rgba8UnormSrgb` texture format (and bevy's
+    /// `TextureFormat::Rgba8UnormSrgb`), so the buffer can be uploaded as a
+    /// texture as is.
+    pub fn as_rgba8(&self) -> Result<Vec<u8>> {
+        let pixels = self.rgb()?;
+        if !self.is_transparent() {
+            return Ok(pixels
+                .iter()
+                .flat_map(|[r, g, b]| [*r, *g, *b, u8::MAX])
+                .collect());
+        }
+
+        let (width, height, stride) = (self.width(), self.height(), self.surface.stride());
+        let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+        self.surface.with_data(|data| {
+            for y in 0..height {
+                for x in 0..width {
+                    let i = (y * stride + x * 4) as usize;
+                    let p = u32::from_ne_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+                    let a = (p >> 24) as u8;
+                    let unpremultiply = |c: u32| match a {
+                        0 => 0,
+                        _ => ((c & 0xff) * 255 + a as u32 / 2) / a as u32,
+                    } as u8;
+                    rgba.extend([
+                        unpremultiply(p >> 16),
+                        unpremultiply(p >> 8),
+                        unpremultiply(p),
+                        a,
+                    ]);
+                }
+            }
+        })?;
+
+        Ok(rgba)
+    }
+
+    /// Returns the number of bytes between the starts of consecutive rows in
+    /// the buffer returned by `as_rgba8`.
+    pub fn rgba8_stride(&self) -> usize {
+        self.width() as usize * 4
+    }
+
+    /// Returns a hash of the render's size and pixels, which is the same on
+    /// every platform and with every version of Rust.
+    /// Snapshot tests can compare it to a stored value to detect unintended
+    /// changes to a render without storing a golden image, though renders of
+    /// the same model may still differ between versions of cairo.
+    pub fn content_hash(&self) -> Result<u64> {
+        let mut hash = Fnv::new();
+        hash.write_i64(self.width() as i64);
+        hash.write_i64(self.height() as i64);
+        hash.write(&self.as_rgba8()?);
+
+        Ok(hash.finish())
+    }
+
+    /// Returns the render scaled down to columns by rows characters of ASCII
+    /// art, one line per row, with darker characters for darker colors, so a
+    /// tiling can be checked in a terminal without viewing an image.
+    /// Transparent pixels count as white.
+    pub fn to_ascii(&self, columns: usize, rows: usize) -> Result<String> {
+        let cells = self.cells(columns, rows)?;
+        let mut ascii = String::with_capacity((columns + 1) * rows);
+        for row in cells.chunks(columns.max(1)) {
+            for [r, g, b] in row {
+                let luminance = Color::rgb(*r as i32, *g as i32, *b as i32).luminance();
+                // Gamma encoding spreads the ramp evenly to the eye.
+                let darkness = 1.0 - luminance.powf(1.0 / 2.2);
+                let i = (darkness * (ASCII_RAMP.len() - 1) as f64).round() as usize;
+                ascii.push(ASCII_RAMP[i.min(ASCII_RAMP.len() - 1)] as char);
+            }
+            ascii.push('\n');
+        }
+
+        Ok(ascii)
+    }
+
+    /// Returns the render scaled down to columns by rows characters of
+    /// Unicode half blocks in 24-bit ANSI color, one line per row, for
+    /// terminals that support true color.
+    /// Each character shows two pixels of the preview, one above the other,
+    /// so rows may be about half the number of columns for a square render.
+    /// Transparent pixels count as white.
+    pub fn to_ansi(&self, columns: usize, rows: usize) -> Result<String> {
+        let cells = self.cells(columns, rows * 2)?;
+        let mut ansi = String::new();
+        for pair in cells.chunks(columns.max(1) * 2) {
+            let (top, bottom) = pair.split_at(columns.min(pair.len()));
+            for ([r0, g0, b0], [r1, g1, b1]) in top.iter().zip(bottom) {
+                ansi.push_str(&format!(
+                    "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                    r0, g0, b0, r1, g1, b1
+                ));
+            }
+            ansi.push_str("\x1b[0m\n");
+        }
+
+        Ok(ansi)
+    }
+
+    /// Returns the mean color of each of columns by rows cells the render is
+    /// divided into, row by row, with transparent pixels counted as white.
+    fn cells(&self, columns: usize, rows: usize) -> Result<Vec<[u8; 3]>> {
+        let rgba = self.as_rgba8()?;
+        let (width, height) = (self.width().max(0) as usize, self.height().max(0) as usize);
+        let mut cells = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            let y0 = row * height / rows;
+            let y1 = ((row + 1) * height / rows).max(y0 + 1);
+            for column in 0..columns {
+                let x0 = column * width / columns;
+                let x1 = ((column + 1) * width / columns).max(x0 + 1);
+                let mut sum = [0.0; 3];
+                let mut count = 0.0;
+                for y in y0..y1.min(height) {
+                    for x in x0..x1.min(width) {
+                        let p = &rgba[(y * width + x) * 4..][..4];
+                        let alpha = p[3] as f64 / 255.0;
+                        for (s, c) in sum.iter_mut().zip(p) {
+                            *s += *c as f64 * alpha + 255.0 * (1.0 - alpha);
+                        }
+                        count += 1.0;
+                    }
+                }
+                let mean = |s: f64| match count > 0.0 {
+                    true => (s / count).round() as u8,
+                    false => u8::MAX,
+                };
+                cells.push([mean(sum[0]), mean(sum[1]), mean(sum[2])]);
+            }
+        }
+
+        Ok(cells)
+    }
+
+    /// Writes a rendered model to a PNG file at path, recording its
+    /// resolution if it has one.
+    pub fn write_to_png<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if self.dpi.is_none() {
+            self.surface.write_to_png(&mut File::create(path)?)?;
+
+            return Ok(());
+        }
+
+        self.write_png(path, &[])
+    }
+
+    /// Writes a rendered model to a PNG file at path like `write_to_png`,
+    /// recording the metadata of the model it was made from in the file's
+    /// text chunks: the crate's name and version as the standard `Software`
+    /// keyword and, if the render recorded it, the metadata as JSON (see
+    /// `Metadata::to_json`) under the `tiling` keyword, so the image can be
+    /// traced back to its model.
+    pub fn write_with_metadata<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut text = vec![("Software", format!("tiling {}", env!("CARGO_PKG_VERSION")))];
+        if let Some(metadata) = self.metadata.as_ref() {
+            text.push((METADATA_KEYWORD, metadata.to_json()));
+        }
+
+        self.write_png(path, &text)
+    }
+
+    /// Writes the render to a PNG file at path with the png crate, recording
+    /// its resolution and a text chunk for each keyword and text in text.
+    fn write_png<P: AsRef<Path>>(&self, path: P, text: &[(&str, String)]) -> Result<()> {
+        let file = File::create(path)?;
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(file),
+            self.width() as u32,
+            self.height() as u32,
+        );
+        encoder.set_depth(png::BitDepth::Eight);
+        let data = if self.is_transparent() {
+            encoder.set_color(png::ColorType::RGBA);
+            self.as_rgba8()?
+        } else {
+            encoder.set_color(png::ColorType::RGB);
+            self.rgb()?.concat()
+        };
+        let mut writer = encoder.write_header()?;
+        write_dpi(&mut writer, self.dpi)?;
+        for (keyword, text) in text {
+            let mut data = keyword.as_bytes().to_vec();
+            data.push(0);
+            data.extend_from_slice(text.as_bytes());
+            writer.write_chunk(TEXT, &data)?;
+        }
+        writer.write_image_data(&data)?;
+
+        Ok(())
+    }
+
+    /// Writes a rendered model to a PNG file at path like `write_to_png`, but
+    /// filters and compresses the image on every core, which is several times
+    /// faster for very large renders at the cost of a slightly larger file.
+    #[cfg(feature = "fast-png")]
+    pub fn write_to_png_fast<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let (channels, pixels) = if self.is_transparent() {
+            (4, self.as_rgba8()?)
+        } else {
+            (3, self.rgb()?.concat())
+        };
+        let file = File::create(path)?;
+
+        crate::fastpng::write(
+            BufWriter::new(file),
+            self.width() as usize,
+            self.height() as usize,
+            channels,
+            &pixels,
+            self.dpi,
+        )
+    }
+
+    /// Writes a rendered model to a PNG file at path whose pixels index a
+    /// palette of at most max_colors colors (between 1 and 256), which is
+    /// much smaller than a full color file for a tiling's few colors.
+    /// Renders with more colors than that, such as the antialiased edges of
+    /// shapes, are quantized to the palette.
+    pub fn write_to_indexed_png<P: AsRef<Path>>(&self, path: P, max_colors: usize) -> Result<()> {
+        let pixels = self
+            .as_rgba8()?
+            .chunks_exact(4)
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .collect::<Vec<[u8; 4]>>();
+        let mut counts: HashMap<[u8; 4], usize> = HashMap::new();
+        for p in pixels.iter() {
+            *counts.entry(*p).or_insert(0) += 1;
+        }
+        let palette = quantize::palette(&counts, max_colors.clamp(1, 256));
+        let indexes = counts
+            .keys()
+            .map(|c| (*c, quantize::nearest(&palette, *c) as u8))
+            .collect::<HashMap<[u8; 4], u8>>();
+
+        // Pack as many pixels into each byte as the palette allows.
+        let (bits, depth) = match palette.len() {
+            0..=2 => (1, png::BitDepth::One),
+            3..=4 => (2, png::BitDepth::Two),
+            5..=16 => (4, png::BitDepth::Four),
+            _ => (8, png::BitDepth::Eight),
+        };
+        let width = self.width() as usize;
+        let row = (width * bits + 7) / 8;
+        let mut data = vec![0; row * self.height() as usize];
+        for (i, p) in pixels.iter().enumerate() {
+            let (y, x) = (i / width, i % width);
+            let shift = 8 - bits - (x * bits) % 8;
+            data[y * row + x * bits / 8] |= indexes[p] << shift;
+        }
+
+        let file = File::create(path)?;
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(file),
+            self.width() as u32,
+            self.height() as u32,
+        );
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(depth);
+        encoder.set_palette(palette.iter().flat_map(|c| [c[0], c[1], c[2]]).collect());
+        if self.is_transparent() {
+            encoder.set_trns(palette.iter().map(|c| c[3]).collect());
+        }
+        let mut writer = encoder.write_header()?;
+        write_dpi(&mut writer, self.dpi)?;
+        writer.write_image_data(&data)?;
+
+        Ok(())
+    }
+}
+
+/// A color scheme applied to an already built model by `Model::render_variants`.
+#[cfg(feature = "cairo")]
+#[derive(Clone, Debug)]
+pub struct RenderStyle {
+    pub(crate) background: Color,
+    pub(crate) line_width: f64,
+    pub(crate) stroke: Option<Color>,
+    pub(crate) fills: HashMap<i32, Color>,
+    pub(crate) paint: PaintMode,
+}
+
+#[cfg(feature = "cairo")]
+impl RenderStyle {
+    /// Returns a style with background that keeps each shape's own colors.
+    pub fn new(background: Color) -> RenderStyle {
+        RenderStyle {
+            background,
+            line_width: 0.1,
+            stroke: None,
+            fills: HashMap::new(),
+            paint: PaintMode::default(),
+        }
+    }
+
+    /// Sets the width of polygon strokes.
+    pub fn line_width(mut self, line_width: f64) -> RenderStyle {
+        self.line_width = line_width;
+
+        self
+    }
+
+    /// Sets the stroke of every shape.
+    pub fn stroke(mut self, stroke: Color) -> RenderStyle {
+        self.stroke = Some(stroke);
+
+        self
+    }
+
+    /// Sets the fill of every shape with sides sides.
+    pub fn fill(mut self, sides: i32, fill: Color) -> RenderStyle {
+        self.fills.insert(sides, fill);
+
+        self
+    }
+
+    /// Sets the fill of every shape with sides in sides to a color from
+    /// palette, assigning the palette's colors in order of increasing sides.
+    pub fn palette(mut self, palette: &Palette, sides: &[i32]) -> RenderStyle {
+        let mut sides = sides.to_vec();
+        sides.sort_unstable();
+        sides.dedup();
+        for (i, s) in sides.into_iter().enumerate() {
+            self.fills.insert(s, palette.color(i));
+        }
+
+        self
+    }
+
+    /// Sets which parts of each shape are painted, unless the shape overrides
+    /// it.
+    pub fn paint(mut self, paint: PaintMode) -> RenderStyle {
+        self.paint = paint;
+
+        self
+    }
+}
+
+/// Prepares a cairo surface and context for rendering a width by height canvas
+/// whose origin is at its center.
+/// When supersampling, the surface is larger than the canvas; pass it to
+/// `finish` once drawing is done.
+#[cfg(feature = "cairo")]
+pub(crate) fn canvas(
+    width: i32,
+    height: i32,
+    scale: f64,
+    options: &RenderOptions,
+) -> Result<(cairo::ImageSurface, cairo::Context)> {
+    let factor = options.supersample as i32;
+    let (w, h) = (width as i64 * factor as i64, height as i64 * factor as i64);
+    if w > MAX_SURFACE_SIZE as i64 || h > MAX_SURFACE_SIZE as i64 {
+        return Err(CanvasTooLarge {
+            width: w,
+            height: h,
+        });
+    }
+    let format = match options.background {
+        Some(_) => cairo::Format::Rgb24,
+        None => cairo::Format::ARgb32,
+    };
+    let surface = cairo::ImageSurface::create(format, width * factor, height * factor)?;
+    let context = cairo::Context::new(&surface)?;
+    prepare(&context, width, height, scale, factor, options)?;
+
+    Ok((surface, context))
+}
+
+/// Prepares context, whose surface is width by height pixels magnified by
+/// factor, for rendering a canvas whose origin is at its center with scale
+/// pixels per edge length as configured by options, and paints its
+/// background.
+#[cfg(feature = "cairo")]
+pub(crate) fn prepare(
+    context: &cairo::Context,
+    width: i32,
+    height: i32,
+    scale: f64,
+    factor: i32,
+    options: &RenderOptions,
+) -> Result<()> {
+    context.set_antialias(options.antialias.into());
+    if let Some(tolerance) = options.tolerance {
+        context.set_tolerance(tolerance * factor as f64);
+    }
+    context.set_line_cap(options.line_cap.into());
+    context.set_line_join(options.line_join.into());
+    context.set_dash(&options.dash, options.dash_offset);
+    context.set_miter_limit(options.miter_limit);
+    context.set_line_width(options.line_width);
+    match &options.label_font {
+        Some(Font::Family(family, weight)) => {
+            context.select_font_face(family, cairo::FontSlant::Normal, *weight)
+        }
+        #[cfg(feature = "freetype")]
+        Some(Font::File(path)) => context.set_font_face(&load_font(path)?),
+        None => {}
+    }
+    context.set_font_size((options.label_size / scale).min(MAX_LABEL_SIZE));
+    context.translate(
+        (width * factor) as f64 / 2.0,
+        (height * factor) as f64 / 2.0,
+    );
+    context.scale(scale * factor as f64, scale * factor as f64);
+    if let Some(background) = options.background {
+        let (red, green, blue) = background.rgb_unit_int();
+        context.set_source_rgb(red, green, blue);
+        context.paint()?;
+    }
+
+    Ok(())
+}
+
+/// Writes a width by height image to a PNG file at path a band of rows at a
+/// time, recording its resolution of dpi dots per inch if any.
+/// band is called with the top row and height of each band, rows_per_band
+/// rows or fewer, in turn and returns its pixels row by row with channels
+/// bytes each (3 for RGB or 4 for RGBA), so an image too large to hold in
+/// memory is never held whole.
+#[cfg(feature = "cairo")]
+pub(crate) fn write_png_bands<P, F>(
+    path: P,
+    (width, height): (i32, i32),
+    channels: usize,
+    dpi: Option<f64>,
+    rows_per_band: i32,
+    mut band: F,
+) -> Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(i32, i32) -> Result<Vec<u8>>,
+{
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_color(match channels {
+        3 => png::ColorType::RGB,
+        _ => png::ColorType::RGBA,
+    });
+    let mut writer = encoder.write_header()?;
+    write_dpi(&mut writer, dpi)?;
+    let mut stream = writer.stream_writer();
+    for y in (0..height).step_by(rows_per_band.max(1) as usize) {
+        stream.write_all(&band(y, rows_per_band.min(height - y))?)?;
+    }
+    stream.finish()?;
+
+    Ok(())
+}
+
+/// Writes a pHYs chunk recording a resolution of dpi dots per inch, if any,
+/// to writer before its image data.
+#[cfg(feature = "cairo")]
+pub(crate) fn write_dpi<W: Write>(writer: &mut png::Writer<W>, dpi: Option<f64>) -> Result<()> {
+    if let Some(dpi) = dpi {
+        // The chunk records pixels per meter along each axis.
+        let ppm = ((dpi * 1000.0 / MM_PER_INCH).round() as u32).to_be_bytes();
+        let mut data = Vec::with_capacity(9);
+        data.extend_from_slice(&ppm);
+        data.extend_from_slice(&ppm);
+        data.push(1);
+        writer.write_chunk(png::chunk::pHYs, &data)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the render of a surface prepared by `canvas`, downscaling it to
+/// the canvas' size if it was supersampled.
+#[cfg(feature = "cairo")]
+pub(crate) fn finish(surface: cairo::ImageSurface, options: &RenderOptions) -> Result<Render> {
+    if options.supersample == 1 {
+        return Ok(Render::new(surface));
+    }
+
+    let factor = options.supersample as i32;
+    let render = cairo::ImageSurface::create(
+        surface.format(),
+        surface.width() / factor,
+        surface.height() / factor,
+    )?;
+    let context = cairo::Context::new(&render)?;
+    context.scale(1.0 / factor as f64, 1.0 / factor as f64);
+    context.set_source_surface(&surface, 0.0, 0.0)?;
+    context.source().set_filter(cairo::Filter::Good);
+    context.paint()?;
+    drop(context);
+
+    Ok(Render::new(render))
+}
+
+/// Returns margin, the margin named field, ensuring it is finite and not
+/// negative.
+pub(crate) fn check_margin(field: &str, margin: f64) -> Result<f64> {
+    if !margin.is_finite() || margin < 0.0 {
+        return Err(InvalidMargin {
+            field: field.to_string(),
+            margin,
+            reason: String::from("is negative or not finite"),
+        });
+    }
+
+    Ok(margin)
+}
+
+/// Returns fill varied by up to amplitude as configured by
+/// `RenderOptions::color_jitter`, chosen at random by seed and point.
+pub(crate) fn jitter(fill: Color, point: Point, amplitude: f64, seed: u64) -> Color {
+    let mut rng = hash::rng_at(seed, point);
+    let (hue, lightness) = (rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0));
+
+    fill.shifted(hue * amplitude * JITTER_HUE, lightness * amplitude)
+}
+
+/// Draws a band width wide of color around the outside of each of the
+/// polygons through rings' points onto context, leaving their insides
+/// uncovered.
+#[cfg(feature = "cairo")]
+pub(crate) fn border(
+    context: &cairo::Context,
+    rings: &[Vec<Point>],
+    color: Color,
+    width: f64,
+) -> Result<()> {
+    let path = |context: &cairo::Context| {
+        for ring in rings.iter().filter(|r| !r.is_empty()) {
+            context.move_to(ring[0].x, ring[0].y);
+            for p in ring.iter().skip(1) {
+                context.line_to(p.x, p.y);
+            }
+            context.close_path();
+        }
+    };
+
+    // A stroke twice as wide as the band, clipped to the outside of the
+    // rings, leaves the band without covering them.
+    context.save()?;
+    let (x1, y1, x2, y2) = context.clip_extents()?;
+    context.rectangle(x1, y1, x2 - x1, y2 - y1);
+    path(context);
+    context.set_fill_rule(cairo::FillRule::EvenOdd);
+    context.clip();
+    path(context);
+    let (red, green, blue) = color.rgb_unit_int();
+    context.set_source_rgb(red, green, blue);
+    context.set_line_width(2.0 * width);
+    context.set_line_join(cairo::LineJoin::Round);
+    context.set_dash(&[], 0.0);
+    context.stroke()?;
+    context.restore()?;
+
+    Ok(())
+}
+
+/// Returns the width and height of the box label draws around text.
+#[cfg(feature = "cairo")]
+pub(crate) fn label_size(context: &cairo::Context, text: &str) -> Result<(f64, f64)> {
+    let te = context.text_extents(text)?;
+    let padding = context.font_extents()?.ascent * LABEL_PADDING;
+
+    Ok((te.width + padding * 2.0, te.height + padding * 2.0))
+}
+
+/// Draws text centered on center, on a box of background if any, in color
+/// if any or else in black or white, whichever contrasts more with the box
+/// or, without one, with fill, the color beneath the text.
+#[cfg(feature = "cairo")]
+pub(crate) fn label(
+    context: &cairo::Context,
+    text: &str,
+    center: Point,
+    background: Option<Color>,
+    color: Option<Color>,
+    fill: Color,
+) -> Result<()> {
+    let te = context.text_extents(text)?;
+    if let Some(background) = background {
+        let (width, height) = label_size(context, text)?;
+        let (red, green, blue) = background.rgb_unit_int();
+        context.set_source_rgb(red, green, blue);
+        context.rectangle(
+            center.x - width / 2.0,
+            center.y - height / 2.0,
+            width,
+            height,
+        );
+        context.fill()?;
+    }
+
+    // The text's extents are measured from where it starts on its baseline.
+    let color = color.unwrap_or(
+        if background.unwrap_or(fill).luminance() < LABEL_LUMINANCE {
+            Color::WHITE
+        } else {
+            Color::BLACK
+        },
+    );
+    let (red, green, blue) = color.rgb_unit_int();
+    context.set_source_rgb(red, green, blue);
+    context.move_to(
+        center.x - te.width / 2.0 - te.x_bearing,
+        center.y - te.height / 2.0 - te.y_bearing,
+    );
+    context.show_text(text)?;
+
+    Ok(())
+}
+
+/// Returns the font face loaded from the font file at path.
+/// Faces are loaded once per thread and kept for its life, since cairo
+/// needs their FreeType faces for as long as any text is drawn with them.
+#[cfg(feature = "freetype")]
+fn load_font(path: &Path) -> Result<cairo::FontFace> {
+    use std::{cell::RefCell, ffi::CString, ptr};
+
+    use freetype_crate::freetype::{FT_Face, FT_Init_FreeType, FT_Library, FT_New_Face};
+
+    use crate::Error::InvalidFont;
+
+    thread_local! {
+        static FACES: RefCell<(FT_Library, HashMap<PathBuf, cairo::FontFace>)> =
+            RefCell::new((ptr::null_mut(), HashMap::new()));
+    }
+
+    FACES.with(|faces| {
+        let (library, faces) = &mut *faces.borrow_mut();
+        if let Some(face) = faces.get(path) {
+            return Ok(face.clone());
+        }
+
+        let invalid = || InvalidFont {
+            path: path.to_path_buf(),
+        };
+        let name = CString::new(path.to_string_lossy().as_bytes()).map_err(|_| invalid())?;
+        let mut face: FT_Face = ptr::null_mut();
+        // Safety: the library and face are never freed, so they outlive the
+        // font face that cairo creates for them.
+        let face = unsafe {
+            if library.is_null() && FT_Init_FreeType(library) != 0 {
+                return Err(invalid());
+            }
+            if FT_New_Face(*library, name.as_ptr(), 0, &mut face) != 0 {
+                return Err(invalid());
+            }
+            cairo::FontFace::create_from_ft(face)?
+        };
+        faces.insert(path.to_path_buf(), face.clone());
+
+        Ok(face)
+    })
+}
+
+/// Returns the color of text drawn over background: black unless the
+/// background is dark.
+#[cfg(feature = "cairo")]
+fn ink(background: Option<Color>) -> Color {
+    let dark = |c: Color| {
+        let (red, green, blue) = c.rgb_unit_int();
+        0.2126 * red + 0.7152 * green + 0.0722 * blue < 0.5
+    };
+
+    match background {
+        Some(background) if dark(background) => Color::WHITE,
+        _ => Color::BLACK,
+    }
+}
+
+/// Draws the annotations of options over context, which is prepared by
+/// `canvas` to draw in edge lengths.
+#[cfg(feature = "cairo")]
+pub(crate) fn annotate(context: &cairo::Context, options: &RenderOptions) -> Result<()> {
+    if options.annotations.is_empty() {
+        return Ok(());
+    }
+
+    // Annotations are drawn in pixels, so the model's origin and edge length
+    // are found before the context's transformation is dropped.
+    let factor = options.supersample as f64;
+    let (ox, oy) = context.user_to_device(0.0, 0.0);
+    let (ux, uy) = context.user_to_device(1.0, 1.0);
+    let (origin, unit) = (
+        Point {
+            x: ox / factor,
+            y: oy / factor,
+        },
+        ((ux - ox) / factor, (uy - oy) / factor),
+    );
+    context.save()?;
+    context.identity_matrix();
+    context.scale(factor, factor);
+    let (x0, y0, x1, y1) = context.clip_extents()?;
+
+    let (red, green, blue) = ink(options.background).rgb_unit_int();
+    context.set_source_rgb(red, green, blue);
+    context.set_line_width(ANNOTATION_LINE);
+    context.set_line_cap(cairo::LineCap::Butt);
+    context.set_dash(&[], 0.0);
+    let captioned = options
+        .annotations
+        .iter()
+        .any(|a| matches!(a, Annotation::Caption(_)));
+    for annotation in options.annotations.iter() {
+        match annotation {
+            Annotation::Title(text) => {
+                context.set_font_size(TITLE_SIZE);
+                let te = context.text_extents(text)?;
+                context.move_to(
+                    (x0 + x1 - te.width) / 2.0 - te.x_bearing,
+                    y0 + ANNOTATION_PADDING - te.y_bearing,
+                );
+                context.show_text(text)?;
+            }
+            Annotation::Caption(text) => {
+                context.set_font_size(CAPTION_SIZE);
+                let te = context.text_extents(text)?;
+                context.move_to(
+                    (x0 + x1 - te.width) / 2.0 - te.x_bearing,
+                    y1 - ANNOTATION_PADDING - te.height - te.y_bearing,
+                );
+                context.show_text(text)?;
+            }
+            Annotation::ScaleBar { length, label } => {
+                // The bar sits above the caption, if there is one.
+                let y = match captioned {
+                    true => y1 - 2.0 * ANNOTATION_PADDING - CAPTION_SIZE,
+                    false => y1 - ANNOTATION_PADDING,
+                };
+                let (left, right) = (
+                    x0 + ANNOTATION_PADDING,
+                    x0 + ANNOTATION_PADDING + length * unit.0,
+                );
+                context.move_to(left, y - TICK_SIZE);
+                context.line_to(left, y);
+                context.line_to(right, y);
+                context.line_to(right, y - TICK_SIZE);
+                context.stroke()?;
+
+                context.set_font_size(CAPTION_SIZE);
+                let te = context.text_extents(label)?;
+                context.move_to(
+                    (left + right - te.width) / 2.0 - te.x_bearing,
+                    y - TICK_SIZE - te.height - te.y_bearing,
+                );
+                context.show_text(label)?;
+            }
+            Annotation::Axes => {
+                context.move_to(x0, origin.y);
+                context.line_to(x1, origin.y);
+                context.move_to(origin.x, y0);
+                context.line_to(origin.x, y1);
+
+                // Ticks are left out when they would crowd each other.
+                let (dx, dy) = (unit.0.abs(), unit.1.abs());
+                if dx >= 2.0 * TICK_SIZE {
+                    let first = ((x0 - origin.x) / dx).ceil() as i64;
+                    let last = ((x1 - origin.x) / dx).floor() as i64;
+                    for i in first..=last {
+                        let x = origin.x + i as f64 * dx;
+                        context.move_to(x, origin.y - TICK_SIZE / 2.0);
+                        context.line_to(x, origin.y + TICK_SIZE / 2.0);
+                    }
+                }
+                if dy >= 2.0 * TICK_SIZE {
+                    let first = ((y0 - origin.y) / dy).ceil() as i64;
+                    let last = ((y1 - origin.y) / dy).floor() as i64;
+                    for i in first..=last {
+                        let y = origin.y + i as f64 * dy;
+                        context.move_to(origin.x - TICK_SIZE / 2.0, y);
+                        context.line_to(origin.x + TICK_SIZE / 2.0, y);
+                    }
+                }
+                context.stroke()?;
+
+                context.set_font_size(CAPTION_SIZE);
+                let te = context.text_extents("x")?;
+                context.move_to(
+                    x1 - ANNOTATION_PADDING - te.width - te.x_bearing,
+                    origin.y - TICK_SIZE,
+                );
+                context.show_text("x")?;
+                let te = context.text_extents("y")?;
+                context.move_to(origin.x + TICK_SIZE, y0 + ANNOTATION_PADDING - te.y_bearing);
+                context.show_text("y")?;
+            }
+        }
+    }
+    context.restore()?;
+
+    Ok(())
+}
+
+/// Returns render with a legend strip appended below it, listing a swatch of
+/// each shape in entries, its name, and its count on a row of its own.
+/// Swatches are stroked line_width pixels wide.
+#[cfg(feature = "cairo")]
+pub(crate) fn legend(
+    render: &Render,
+    entries: &[(Shape, usize)],
+    line_width: f64,
+    options: &RenderOptions,
+) -> Result<Render> {
+    let strip = (2.0 * LEGEND_PADDING + LEGEND_ROW * entries.len() as f64).ceil() as i32;
+    let (width, height) = (render.width(), render.height());
+    let surface = cairo::ImageSurface::create(render.surface.format(), width, height + strip)?;
+    let context = cairo::Context::new(&surface)?;
+    context.set_antialias(options.antialias.into());
+    if let Some(background) = options.background {
+        let (red, green, blue) = background.rgb_unit_int();
+        context.set_source_rgb(red, green, blue);
+        context.paint()?;
+    }
+    context.set_source_surface(&render.surface, 0.0, 0.0)?;
+    context.paint()?;
+
+    let text = ink(options.background);
+    context.set_font_size(LEGEND_ROW * 0.6);
+    for (i, (shape, count)) in entries.iter().enumerate() {
+        let y = height as f64 + LEGEND_PADDING + LEGEND_ROW * (i as f64 + 0.5);
+
+        // Scale each swatch so its circumcircle fills most of the row.
+        let circumradius = 0.5 / (PI / shape.sides() as f64).sin();
+        let scale = LEGEND_ROW * 0.4 / circumradius;
+        context.save()?;
+        context.translate(LEGEND_PADDING + LEGEND_ROW / 2.0, y);
+        context.scale(scale, scale);
+        context.set_line_width(line_width / scale);
+        context.set_line_join(options.line_join.into());
+        shape
+            .clone_at(Point::origin())
+            .with_corner(shape.corner().unwrap_or(options.corner))
+            .with_paint(PaintMode::FillAndStroke)
+            .render(&context, 0.0)?;
+        context.restore()?;
+
+        let (singular, plural) = svg::name(shape.sides());
+        let label = format!("{} {}", count, if *count == 1 { singular } else { plural });
+        let te = context.text_extents(&label)?;
+        let (red, green, blue) = text.rgb_unit_int();
+        context.set_source_rgb(red, green, blue);
+        context.move_to(
+            LEGEND_PADDING * 2.0 + LEGEND_ROW,
+            y - te.y_bearing - te.height / 2.0,
+        );
+        context.show_text(&label)?;
+    }
+    drop(context);
+
+    Ok(Render::new(surface))
+}
@@ -0,0 +1,268 @@
+use std::f64::consts::PI;
+
+use crate::{
+    math, shape::corners, Axis, Color, Corner, Error, Error::*, PaintMode, Point, Polygon, Result,
+    Tile,
+};
+
+#[cfg(feature = "cairo")]
+use crate::shape;
+
+/// A representation of a rhombus (all sides are equal, but angles alternate).
+/// Rhombi are not regular polygons, so rather than being attached to a
+/// `Model`'s shapes they are built edge to edge with `adjacent`, which attaches
+/// a rhombus the same way `Shape::adjacent` attaches a regular polygon, and
+/// added to a model with `Model::add_tile`.
+#[derive(Clone, Copy, Debug)]
+pub struct Rhombus {
+    angle: f64,
+    vertex: Point,
+    rotation: f64,
+    fill: Color,
+    stroke: Color,
+    corner: Corner,
+    paint: PaintMode,
+}
+
+impl Rhombus {
+    /// Returns a new rhombus whose vertices with index 1 and 3 have the interior
+    /// angle angle (in radians), ensuring angle is strictly between 0 and π.
+    pub fn new(angle: f64, fill: Color, stroke: Color) -> Result<Rhombus> {
+        if !(angle > 0.0 && angle < PI) {
+            return Err(InvalidShape {
+                reason: format!("rhombus angle {} is not between 0 and π", angle),
+            });
+        }
+
+        let rhombus = Rhombus {
+            angle,
+            vertex: Point::origin(),
+            rotation: 0.0,
+            fill,
+            stroke,
+            corner: Corner::default(),
+            paint: PaintMode::default(),
+        };
+
+        Ok(rhombus.clone_at(Point::origin()))
+    }
+
+    /// Returns the rhombus with its corners rendered in the corner style.
+    pub fn with_corner(mut self, corner: Corner) -> Rhombus {
+        self.corner = corner;
+
+        self
+    }
+
+    /// Returns the rhombus with the parts painted by paint mode.
+    pub fn with_paint(mut self, paint: PaintMode) -> Rhombus {
+        self.paint = paint;
+
+        self
+    }
+
+    /// Returns the rhombus' interior angle (in radians) at its vertices with
+    /// index 1 and 3.
+    pub fn angle(&self) -> f64 {
+        self.angle
+    }
+
+    /// Returns the rhombus' center.
+    pub fn point(&self) -> Point {
+        let (p0, _, p2, _) = self.vertices();
+
+        Point {
+            x: (p0.x + p2.x) / 2.0,
+            y: (p0.y + p2.y) / 2.0,
+        }
+    }
+
+    /// Returns the direction (in radians) of the rhombus' edge with index 0.
+    pub fn rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    /// Returns the rhombus' fill.
+    pub fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the rhombus' stroke.
+    pub fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns the rhombus' area.
+    pub fn area(&self) -> f64 {
+        math::sin(self.angle)
+    }
+
+    /// Returns a copy of the rhombus centered at point.
+    pub fn clone_at(&self, point: Point) -> Rhombus {
+        let center = self.point();
+        let mut r = *self;
+        r.vertex = Point {
+            x: self.vertex.x + point.x - center.x,
+            y: self.vertex.y + point.y - center.y,
+        };
+
+        r
+    }
+
+    /// Returns the rhombus with interior angle angle adjacent to the edge with
+    /// index edge.
+    /// The adjacent rhombus' edge with index 0 is the edge it shares with the
+    /// rhombus.
+    pub fn adjacent(&self, edge: usize, angle: f64, fill: Color, stroke: Color) -> Result<Rhombus> {
+        Rhombus::on_edge(self, edge, angle, fill, stroke)
+    }
+
+    /// Returns the rhombus with interior angle angle adjacent to the edge with
+    /// index edge of polygon, which may be a regular `Shape`.
+    /// The rhombus' edge with index 0 is the edge it shares with polygon.
+    pub fn on_edge<P: Polygon>(
+        polygon: &P,
+        edge: usize,
+        angle: f64,
+        fill: Color,
+        stroke: Color,
+    ) -> Result<Rhombus> {
+        let points = polygon.points(0.0)?;
+        let (p0, p1) = points
+            .get(edge)
+            .zip(points.get(edge + 1))
+            .ok_or(OutOfBounds {
+                index: edge,
+                length: points.len().saturating_sub(1),
+                name: String::from("polygon edges"),
+            })?;
+        let mut rhombus = Rhombus::new(angle, fill, stroke)?;
+        rhombus.vertex = *p1;
+        rhombus.rotation = math::atan2(p0.y - p1.y, p0.x - p1.x);
+
+        Ok(rhombus)
+    }
+
+    /// Returns the rhombus' four vertices in edge order.
+    fn vertices(&self) -> (Point, Point, Point, Point) {
+        let step = |p: Point, a: f64| Point {
+            x: p.x + math::cos(a),
+            y: p.y + math::sin(a),
+        };
+        let p0 = self.vertex;
+        let p1 = step(p0, self.rotation);
+        let p2 = step(p1, self.rotation + PI - self.angle);
+        let p3 = step(p2, self.rotation + PI);
+
+        (p0, p1, p2, p3)
+    }
+}
+
+impl Polygon for Rhombus {
+    /// Returns the polygon's points.
+    /// A rhombus has an inscribed circle, so insetting it by margin scales it
+    /// about its center; a margin that reaches the inradius is an error.
+    fn points(&self, margin: f64) -> Result<Vec<Point>> {
+        let (p0, p1, p2, p3) = self.vertices();
+        let center = self.point();
+        let inradius = math::sin(self.angle) / 2.0;
+        if margin.is_nan() || margin >= inradius {
+            return Err(Error::margin_reaches("margin", margin, inradius, 4));
+        }
+        let scale = 1.0 - margin / inradius;
+
+        let points = [p0, p1, p2, p3, p0]
+            .iter()
+            .map(|p| Point {
+                x: center.x + (p.x - center.x) * scale,
+                y: center.y + (p.y - center.y) * scale,
+            })
+            .collect();
+
+        Ok(points)
+    }
+
+    /// Returns the rhombus' points inset by margin with its corners cut in
+    /// its corner style.
+    fn outline_points(&self, margin: f64) -> Result<Vec<Point>> {
+        Ok(corners(&self.points(margin)?, self.corner))
+    }
+
+    /// Renders the polygon.
+    #[cfg(feature = "cairo")]
+    fn render(&self, context: &cairo::Context, margin: f64) -> Result<()> {
+        self.render_snapped(context, margin, None)
+    }
+
+    /// Renders the polygon as `render` does, with its stroke snapped to the
+    /// centers of pixels pixel device units wide if pixel is some.
+    #[cfg(feature = "cairo")]
+    fn render_snapped(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        let points = self.outline_points(margin)?;
+
+        shape::render_snapped(context, &points, self.fill, self.stroke, self.paint, pixel)
+    }
+}
+
+impl Tile for Rhombus {
+    /// Returns the tile's center.
+    fn point(&self) -> Point {
+        Rhombus::point(self)
+    }
+
+    /// Returns a copy of the tile moved by vector.
+    fn translated(&self, vector: Point) -> Box<dyn Tile> {
+        Box::new(self.clone_at(Rhombus::point(self) + vector))
+    }
+
+    /// Returns a copy of the tile rotated by angle (in radians) about center.
+    fn rotated(&self, angle: f64, center: Point) -> Box<dyn Tile> {
+        let mut r = *self;
+        r.vertex = self.vertex.rotate(angle, center);
+        r.rotation = self.rotation + angle;
+
+        Box::new(r)
+    }
+
+    /// Returns a copy of the tile mirrored across axis.
+    fn reflected(&self, axis: &Axis) -> Box<dyn Tile> {
+        // Mirroring reverses the order of the vertices, so the reflection
+        // starts at the image of vertex 0 and heads toward the image of
+        // vertex 3, keeping the angles at vertices 1 and 3.
+        let (p0, _, _, p3) = self.vertices();
+        let (q0, q3) = (axis.reflect(p0), axis.reflect(p3));
+        let mut r = *self;
+        r.vertex = q0;
+        r.rotation = math::atan2(q3.y - q0.y, q3.x - q0.x);
+
+        Box::new(r)
+    }
+
+    /// Returns a copy of the tile filled with fill.
+    fn filled(&self, fill: Color) -> Box<dyn Tile> {
+        let mut r = *self;
+        r.fill = fill;
+
+        Box::new(r)
+    }
+
+    /// Returns the color the tile is filled with.
+    fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the color the tile is stroked with.
+    fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns whether the tile is filled, stroked, or both.
+    fn paint(&self) -> PaintMode {
+        self.paint
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt::Write;
+
+use crate::{Color, Point, Polygon, Result, Shape};
+
+/// A tiling as a graph of nodes, one per shape, whose children are the shape's
+/// edges and vertices, with every coordinate in canvas space: pixels from the
+/// canvas' top left corner.
+/// A scene is meant for renderers and front-ends outside of Rust, such as a
+/// JavaScript canvas that hit-tests shapes, and is written by `to_json`.
+#[derive(Clone, Debug)]
+pub struct Scene {
+    width: i32,
+    height: i32,
+    nodes: Vec<Node>,
+}
+
+/// A shape in a scene.
+#[derive(Clone, Debug)]
+pub struct Node {
+    id: usize,
+    index: usize,
+    sides: i32,
+    fill: Color,
+    stroke: Color,
+    center: Point,
+    vertices: Vec<Point>,
+    edges: Vec<(Point, Point)>,
+}
+
+impl Scene {
+    /// Returns an empty scene on a width by height canvas.
+    pub(crate) fn new(width: i32, height: i32) -> Scene {
+        Scene {
+            width,
+            height,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Adds a node for shape, placed from the model shape with index index, to
+    /// the scene, mapping model coordinates to canvas coordinates with
+    /// transform.
+    pub(crate) fn push<F>(&mut self, index: usize, shape: &Shape, transform: F) -> Result<()>
+    where
+        F: Fn(Point) -> Point,
+    {
+        let points = shape.points(0.0)?;
+        let vertices = points[0..points.len() - 1]
+            .iter()
+            .map(|p| transform(*p))
+            .collect::<Vec<Point>>();
+        let edges = points
+            .windows(2)
+            .map(|e| (transform(e[0]), transform(e[1])))
+            .collect();
+
+        self.nodes.push(Node {
+            id: self.nodes.len(),
+            index,
+            sides: shape.sides(),
+            fill: shape.fill(),
+            stroke: shape.stroke(),
+            center: transform(shape.point()),
+            vertices,
+            edges,
+        });
+
+        Ok(())
+    }
+
+    /// Returns the canvas' width in pixels.
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// Returns the canvas' height in pixels.
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// Returns the scene's nodes.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the node whose shape contains the canvas point (x, y), if any.
+    pub fn node_at(&self, x: f64, y: f64) -> Option<&Node> {
+        self.nodes.iter().find(|n| n.contains(Point { x, y }))
+    }
+
+    /// Returns the scene as a JSON object with the canvas' "width" and
+    /// "height" and a list of "nodes".
+    /// Each node holds its "id", the "index" of the model shape it was placed
+    /// from, its number of "sides", its "fill" and "stroke" colors, its
+    /// "center", and its children: the "vertices" and "edges" of its shape.
+    /// Points are written as [x, y] and each edge as an object with its
+    /// "index" and the "start" and "end" of its line.
+    pub fn to_json(&self) -> String {
+        let point = |p: &Point| format!("[{},{}]", p.x, p.y);
+
+        // Writing to a String cannot fail.
+        let mut json = String::new();
+        let _ = write!(
+            json,
+            r#"{{"width":{},"height":{},"nodes":["#,
+            self.width, self.height
+        );
+        for (i, n) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            let vertices = n.vertices.iter().map(point).collect::<Vec<String>>();
+            let edges = n
+                .edges
+                .iter()
+                .enumerate()
+                .map(|(i, (p0, p1))| {
+                    format!(
+                        r#"{{"index":{},"start":{},"end":{}}}"#,
+                        i,
+                        point(p0),
+                        point(p1)
+                    )
+                })
+                .collect::<Vec<String>>();
+            let _ = write!(
+                json,
+                r#"{{"id":{},"index":{},"sides":{},"fill":"{}","stroke":"{}","center":{},"vertices":[{}],"edges":[{}]}}"#,
+                n.id,
+                n.index,
+                n.sides,
+                n.fill.to_hex(),
+                n.stroke.to_hex(),
+                point(&n.center),
+                vertices.join(","),
+                edges.join(","),
+            );
+        }
+        json.push_str("]}");
+
+        json
+    }
+}
+
+impl Node {
+    /// Returns the node's id, its position in the scene's nodes.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the index of the model shape that the node's shape was placed
+    /// from (either the shape itself or the shape it repeats).
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the number of sides of the node's shape.
+    pub fn sides(&self) -> i32 {
+        self.sides
+    }
+
+    /// Returns the shape's fill.
+    pub fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the shape's stroke.
+    pub fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns the shape's center.
+    pub fn center(&self) -> Point {
+        self.center
+    }
+
+    /// Returns the shape's vertices in edge order.
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Returns the shape's edges as the start and end of each line, indexed as
+    /// the shape's edges are.
+    pub fn edges(&self) -> &[(Point, Point)] {
+        &self.edges
+    }
+
+    /// Returns whether point lies within the node's shape.
+    pub fn contains(&self, point: Point) -> bool {
+        // Shapes are convex, so point is inside when it is on the same side of
+        // every edge.
+        let sides = self
+            .edges
+            .iter()
+            .map(|(p0, p1)| (p1.x - p0.x) * (point.y - p0.y) - (p1.y - p0.y) * (point.x - p0.x))
+            .collect::<Vec<f64>>();
+
+        sides.iter().all(|s| *s >= 0.0) || sides.iter().all(|s| *s <= 0.0)
+    }
+}
@@ -0,0 +1,88 @@
+#[cfg(feature = "cairo")]
+use crate::{Render, Result};
+
+/// A signed distance field of a tiling's edges: the distance from the center
+/// of every pixel to the nearest edge of the shape containing it.
+/// Distances are in model units, positive inside shapes and negative outside
+/// of the tiling, so a shader can draw edges of any width, at any zoom, by
+/// thresholding the field.
+#[derive(Clone, Debug)]
+pub struct DistanceField {
+    width: usize,
+    height: usize,
+    resolution: f64,
+    values: Vec<f32>,
+}
+
+impl DistanceField {
+    /// Returns a width by height field of values, row by row from the top,
+    /// sampled resolution times per model unit.
+    pub(crate) fn new(
+        width: usize,
+        height: usize,
+        resolution: f64,
+        values: Vec<f32>,
+    ) -> DistanceField {
+        DistanceField {
+            width,
+            height,
+            resolution,
+            values,
+        }
+    }
+
+    /// Returns the field's width in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the field's height in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of pixels per model unit.
+    pub fn resolution(&self) -> f64 {
+        self.resolution
+    }
+
+    /// Returns the distance of every pixel, row by row from the top.
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Returns the distance at pixel (x, y), if the pixel is in the field.
+    pub fn get(&self, x: usize, y: usize) -> Option<f32> {
+        if x >= self.width {
+            return None;
+        }
+
+        self.values.get(y * self.width + x).copied()
+    }
+
+    /// Returns the field as a grayscale image in which edges are mid gray,
+    /// distances of spread (in model units) or more inside shapes are white,
+    /// and distances of spread or more outside of the tiling are black.
+    #[cfg(feature = "cairo")]
+    pub fn to_render(&self, spread: f64) -> Result<Render> {
+        let mut surface = cairo::ImageSurface::create(
+            cairo::Format::Rgb24,
+            self.width as i32,
+            self.height as i32,
+        )?;
+        let stride = surface.stride() as usize;
+        {
+            let mut data = surface.data()?;
+            for (i, d) in self.values.iter().enumerate() {
+                let (x, y) = (i % self.width, i / self.width);
+                let v = ((0.5 + *d as f64 / (2.0 * spread)).clamp(0.0, 1.0) * 255.0).round() as u32;
+                let p = (v << 16 | v << 8 | v).to_ne_bytes();
+                let j = y * stride + x * 4;
+                data[j..j + 4].copy_from_slice(&p);
+            }
+        }
+        surface.mark_dirty();
+
+        Ok(Render::new(surface))
+    }
+}
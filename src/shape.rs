@@ -1,56 +1,484 @@
 use std::{
+    cell::RefCell,
+    collections::HashMap,
     f64::consts::PI,
+    fmt,
     hash::{Hash, Hasher},
     ops,
+    rc::Rc,
 };
 
 use itertools::multizip;
 
-use crate::{Color, Error::*, Result};
+use crate::{
+    hash::Fnv, irregular, math, Blend, Color, Corner, Error::*, IrregularShape, Outline, PaintMode,
+    Result, Style, Theme,
+};
+
+#[cfg(feature = "cairo")]
+use crate::{render, EdgeStyle, VertexLabel};
+
+/// The default number of decimal places to use when comparing points.
+pub const PRECISION: i32 = 6;
+
+/// The fewest sides a shape may have.
+pub const MIN_SIDES: i32 = 3;
+
+/// The most sides a shape may have.
+pub const MAX_SIDES: i32 = 4096;
+
+/// The number of sides above which a shape is rendered as a circle.
+/// Beyond this point the polygon is visually indistinguishable from a circle
+/// and drawing each side is slow and numerically noisy.
+pub const CIRCLE_SIDES: i32 = 360;
+
+/// The most sides and rotation pairs whose vertex directions each thread
+/// caches; past it the cache starts over, so models rotated to many angles
+/// do not grow it without bound.
+const DIRECTIONS_CACHED: usize = 1024;
+
+/// The directions of a shape's vertices from its center, as cosines and sines.
+type Directions = Rc<[(f64, f64)]>;
+
+thread_local! {
+    /// The directions of shapes' vertices from their centers, by their sides
+    /// and the bits of their rotations.
+    static DIRECTIONS: RefCell<HashMap<(i32, u64), Directions>> =
+        RefCell::new(HashMap::new());
+}
+
+/// The number of segments used to approximate a rounded corner.
+const CORNER_SEGMENTS: usize = 8;
 
-/// The number of decimal places to use when comparing points.
-const PRECISION: i32 = 6;
+/// The color edge 0 is highlighted with when debugging edges.
+#[cfg(feature = "cairo")]
+const DEBUG_FIRST_EDGE: Color = Color::rgb(230, 30, 160);
+
+/// The opacity of the band shading edge 0 and of orientation markers when
+/// debugging edges.
+#[cfg(feature = "cairo")]
+const DEBUG_ALPHA: f64 = 0.35;
+
+/// The length of debugging arrows, as a fraction of their edges' lengths.
+#[cfg(feature = "cairo")]
+const DEBUG_ARROW: f64 = 0.4;
+
+/// The tolerance to use when testing geometric predicates.
+pub(crate) const EPSILON: f64 = 1e-6;
 
 /// A generic interface of a polygon.
 pub trait Polygon {
     /// Returns the polygon's points.
     fn points(&self, margin: f64) -> Result<Vec<Point>>;
 
+    /// Returns the polygon's points inset by margin with its corners cut in
+    /// its corner style, the outline it is rendered through. A polygon
+    /// without a corner style is outlined by its points.
+    fn outline_points(&self, margin: f64) -> Result<Vec<Point>> {
+        self.points(margin)
+    }
+
     /// Renders the polygon.
+    #[cfg(feature = "cairo")]
     fn render(&self, context: &cairo::Context, margin: f64) -> Result<()>;
+
+    /// Renders the polygon as `render` does, with the lines it strokes
+    /// snapped to the centers of pixels pixel device units wide if pixel is
+    /// some, as `RenderOptions::pixel_snap` configures. Fills are never
+    /// snapped. A polygon that cannot snap its strokes, such as one traced
+    /// through arcs, renders as `render` does.
+    #[cfg(feature = "cairo")]
+    fn render_snapped(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        _pixel: Option<f64>,
+    ) -> Result<()> {
+        self.render(context, margin)
+    }
+}
+
+/// A polygon that a model can store, repeat, and render alongside its regular
+/// shapes, such as a `Dual`, a `Rhombus`, or an `IrregularShape`.
+/// Since a model moves its tiles along with its shapes, a tile must be able to
+/// return copies of itself moved the same ways a `Shape` can be; the copies
+/// need not be of the same type.
+pub trait Tile: Polygon + fmt::Debug + Send + Sync {
+    /// Returns the tile's center, which is repeated like a shape's center.
+    fn point(&self) -> Point;
+
+    /// Returns a copy of the tile moved by vector.
+    fn translated(&self, vector: Point) -> Box<dyn Tile>;
+
+    /// Returns a copy of the tile rotated by angle (in radians) about center.
+    fn rotated(&self, angle: f64, center: Point) -> Box<dyn Tile>;
+
+    /// Returns a copy of the tile mirrored across axis.
+    fn reflected(&self, axis: &Axis) -> Box<dyn Tile>;
+
+    /// Returns a copy of the tile filled with fill, as a model fills single
+    /// cells of its tiling.
+    fn filled(&self, fill: Color) -> Box<dyn Tile>;
+
+    /// Returns the color the tile is filled with.
+    fn fill(&self) -> Color;
+
+    /// Returns the color the tile is stroked with.
+    fn stroke(&self) -> Color;
+
+    /// Returns whether the tile is filled, stroked, or both.
+    fn paint(&self) -> PaintMode;
+
+    /// Returns the closed rings outlining the tile inset by margin, the
+    /// largest first and any holes after it. A tile without holes is outlined
+    /// by its points.
+    fn rings(&self, margin: f64) -> Result<Vec<Vec<Point>>> {
+        Ok(vec![self.points(margin)?])
+    }
+}
+
+/// The order in which a polygon's points go around it, as seen on the canvas,
+/// whose y axis points down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Winding {
+    /// Clockwise (the default).
+    #[default]
+    Clockwise,
+    /// Counterclockwise.
+    CounterClockwise,
+}
+
+/// The side of an edge that a shape is attached on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Side {
+    /// Outside of the shape the edge belongs to (the default), so the shapes
+    /// sit side by side.
+    #[default]
+    Outward,
+    /// Inside of the shape the edge belongs to, so the attached shape fills a
+    /// notch or the inside of a ring of shapes.
+    Inward,
+}
+
+/// The kind of a regular polygon: one of the polygons that tile the plane on
+/// their own or in the uniform tilings, or any other number of sides.
+/// A shape made from a named kind always has a valid number of sides; only
+/// `NGon`'s sides are checked when the shape is made.
+/// Shapes are made from kinds with `Shape::of_kind`. Numbers of sides
+/// convert to kinds, and kinds compare by their sides, so `NGon(4)` equals
+/// `Square`.
+#[derive(Clone, Copy, Debug)]
+pub enum PolygonKind {
+    /// A three-sided polygon.
+    Triangle,
+    /// A four-sided polygon.
+    Square,
+    /// A six-sided polygon.
+    Hexagon,
+    /// A twelve-sided polygon.
+    Dodecagon,
+    /// A polygon with any other number of sides.
+    NGon(i32),
+}
+
+impl PolygonKind {
+    /// Returns the kind's number of sides.
+    pub fn sides(&self) -> i32 {
+        match self {
+            PolygonKind::Triangle => 3,
+            PolygonKind::Square => 4,
+            PolygonKind::Hexagon => 6,
+            PolygonKind::Dodecagon => 12,
+            PolygonKind::NGon(sides) => *sides,
+        }
+    }
+}
+
+impl From<i32> for PolygonKind {
+    /// Returns the kind with sides sides, named if it has a name.
+    fn from(sides: i32) -> PolygonKind {
+        match sides {
+            3 => PolygonKind::Triangle,
+            4 => PolygonKind::Square,
+            6 => PolygonKind::Hexagon,
+            12 => PolygonKind::Dodecagon,
+            _ => PolygonKind::NGon(sides),
+        }
+    }
+}
+
+impl PartialEq for PolygonKind {
+    fn eq(&self, other: &PolygonKind) -> bool {
+        self.sides() == other.sides()
+    }
+}
+
+impl Eq for PolygonKind {}
+
+impl Hash for PolygonKind {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sides().hash(state);
+    }
+}
+
+impl fmt::Display for PolygonKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolygonKind::Triangle => write!(f, "triangle"),
+            PolygonKind::Square => write!(f, "square"),
+            PolygonKind::Hexagon => write!(f, "hexagon"),
+            PolygonKind::Dodecagon => write!(f, "dodecagon"),
+            PolygonKind::NGon(sides) => write!(f, "{}-gon", sides),
+        }
+    }
+}
+
+impl Winding {
+    /// Returns the order in which points go around the polygon they bound,
+    /// which may repeat its first point at the end, as `Polygon::points`
+    /// does.
+    pub fn of(points: &[Point]) -> Winding {
+        if irregular::area(points) < 0.0 {
+            Winding::CounterClockwise
+        } else {
+            Winding::Clockwise
+        }
+    }
 }
 
 /// A representation of a regular polygon (all angles and sides are equal).
+/// A shape's fill and stroke are held as a `Style`, apart from its geometry:
+/// it is the style the shape is drawn in by default, which the style sets a
+/// model is rendered with (see `StyleSet`) replace.
 #[derive(Clone, Copy, Debug)]
 pub struct Shape {
     sides: i32,
     point: Point,
     rotation: f64,
-    fill: Color,
-    stroke: Color,
+    style: Style,
+    margin: Option<f64>,
+    corner: Option<Corner>,
+    paint: Option<PaintMode>,
+    outline: Option<Outline>,
+    opacity: Option<f64>,
+    blend: Option<Blend>,
+    transform: Option<Affine>,
+    winding: Winding,
+}
+
+/// Returns an error if a shape may not have sides sides: fewer than
+/// `MIN_SIDES` or more than `MAX_SIDES`.
+pub(crate) fn check_sides(sides: i32) -> Result<()> {
+    if sides < MIN_SIDES {
+        return Err(InvalidShape {
+            reason: format!("{} sides is fewer than the minimum of {}", sides, MIN_SIDES),
+        });
+    }
+    if sides > MAX_SIDES {
+        return Err(InvalidShape {
+            reason: format!("{} sides is more than the maximum of {}", sides, MAX_SIDES),
+        });
+    }
+
+    Ok(())
+}
+
+/// The fill, stroke, and rendering overrides of a shape, which a model
+/// stores once for all of its shapes styled alike.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Styling {
+    style: Style,
+    margin: Option<f64>,
+    corner: Option<Corner>,
+    paint: Option<PaintMode>,
+    outline: Option<Outline>,
+    opacity: Option<f64>,
+    blend: Option<Blend>,
+    transform: Option<Affine>,
+    winding: Winding,
+}
+
+impl Styling {
+    /// Returns a hash of the styling that stylings equal to it share, so
+    /// stylings can be looked up by it rather than compared one by one.
+    pub(crate) fn hash(&self) -> u64 {
+        // Adds f to hash, with -0 hashed as 0, which it equals.
+        fn float(hash: &mut Fnv, f: Option<f64>) {
+            match f {
+                Some(f) => hash.write_i64((f + 0.0).to_bits() as i64),
+                None => hash.write(&[0]),
+            }
+        }
+
+        let mut hash = Fnv::new();
+        for color in [self.style.fill(), self.style.stroke()] {
+            hash.write_i64(color.to_u32() as i64);
+        }
+        float(&mut hash, self.margin);
+        match self.corner {
+            Some(Corner::Miter) => hash.write(&[1]),
+            Some(Corner::Round(r)) => {
+                hash.write(&[2]);
+                float(&mut hash, Some(r));
+            }
+            Some(Corner::Bevel(d)) => {
+                hash.write(&[3]);
+                float(&mut hash, Some(d));
+            }
+            None => hash.write(&[0]),
+        }
+        hash.write_i64(self.paint.map_or(-1, |p| p as i64));
+        match self.outline {
+            Some(o) => {
+                float(&mut hash, Some(o.width()));
+                hash.write_i64(o.color().to_u32() as i64);
+            }
+            None => hash.write(&[0]),
+        }
+        float(&mut hash, self.opacity);
+        hash.write_i64(self.blend.map_or(-1, |b| b as i64));
+        match self.transform {
+            Some(t) => {
+                for f in [t.xx, t.xy, t.yx, t.yy] {
+                    float(&mut hash, Some(f));
+                }
+            }
+            None => hash.write(&[0]),
+        }
+        hash.write_i64(self.winding as i64);
+
+        hash.finish()
+    }
 }
 
 impl Shape {
-    /// Returns a new shape, ensuring the number of sides is at least three.
+    /// Returns a new shape, ensuring the number of sides is at least `MIN_SIDES`
+    /// and at most `MAX_SIDES`.
     pub fn new(sides: i32, fill: Color, stroke: Color) -> Result<Shape> {
-        if sides < 3 {
-            return Err(InvalidShape);
-        }
+        check_sides(sides)?;
 
         Ok(Shape {
             sides,
             point: Point::origin(),
             rotation: 0.0,
-            fill,
-            stroke,
+            style: Style::new(fill, stroke),
+            margin: None,
+            corner: None,
+            paint: None,
+            outline: None,
+            opacity: None,
+            blend: None,
+            transform: None,
+            winding: Winding::default(),
         })
     }
 
+    /// Returns a new shape of kind, ensuring an `NGon`'s number of sides is
+    /// valid as `new` does.
+    pub fn of_kind(kind: PolygonKind, fill: Color, stroke: Color) -> Result<Shape> {
+        Shape::new(kind.sides(), fill, stroke)
+    }
+
+    /// Returns a new shape with sides sides colored by theme, with the
+    /// theme's stroke and its fill for sides.
+    pub fn themed(sides: i32, theme: &Theme) -> Result<Shape> {
+        Shape::new(sides, theme.fill(sides), theme.stroke())
+    }
+
+    /// Returns the shape with a margin that overrides the margin used to render
+    /// the rest of the model.
+    pub fn with_margin(mut self, margin: f64) -> Shape {
+        self.margin = Some(margin);
+
+        self
+    }
+
+    /// Returns the shape with a corner style that overrides the corner style
+    /// used to render the rest of the model.
+    pub fn with_corner(mut self, corner: Corner) -> Shape {
+        self.corner = Some(corner);
+
+        self
+    }
+
+    /// Returns the shape with a paint mode that overrides the paint mode used
+    /// to render the rest of the model.
+    pub fn with_paint(mut self, paint: PaintMode) -> Shape {
+        self.paint = Some(paint);
+
+        self
+    }
+
+    /// Returns the shape with an outline that overrides the outline used to
+    /// render the rest of the model.
+    pub fn with_outline(mut self, outline: Outline) -> Shape {
+        self.outline = Some(outline);
+
+        self
+    }
+
+    /// Returns the shape painted at opacity, from 0 (transparent) to 1
+    /// (opaque), so what was drawn beneath it shows through.
+    /// Opacities outside of that range are clamped to it.
+    pub fn with_opacity(mut self, opacity: f64) -> Shape {
+        self.opacity = Some(opacity.clamp(0.0, 1.0));
+
+        self
+    }
+
+    /// Returns the shape composited with what was drawn beneath it by blend
+    /// mode.
+    pub fn with_blend(mut self, blend: Blend) -> Shape {
+        self.blend = Some(blend);
+
+        self
+    }
+
+    /// Returns the shape with transform applied about its center when it is
+    /// rendered, such as to shrink or turn single tiles of a regular tiling.
+    /// The shape's geometry is unchanged, so shapes are still attached,
+    /// repeated, and hit-tested as if it were not transformed.
+    pub fn with_transform(mut self, transform: Affine) -> Shape {
+        self.transform = Some(transform);
+
+        self
+    }
+
+    /// Returns the shape with its points listed in winding order, which
+    /// matters to exports and fill rules that depend on orientation.
+    /// The shape's edges keep their indexes, which always run clockwise from
+    /// the vertex at its rotation, so the shape is attached and styled the
+    /// same way in either winding.
+    pub fn with_winding(mut self, winding: Winding) -> Shape {
+        self.winding = winding;
+
+        self
+    }
+
+    /// Returns the shape with fill and stroke in place of its colors.
+    pub fn with_colors(mut self, fill: Color, stroke: Color) -> Shape {
+        self.style = Style::new(fill, stroke);
+
+        self
+    }
+
+    /// Returns the shape drawn in style in place of its colors.
+    pub fn with_style(mut self, style: Style) -> Shape {
+        self.style = style;
+
+        self
+    }
+
     /// Returns the shape's sides.
     pub fn sides(&self) -> i32 {
         self.sides
     }
 
+    /// Returns the shape's kind.
+    pub fn kind(&self) -> PolygonKind {
+        PolygonKind::from(self.sides)
+    }
+
     /// Returns the shape's point.
     pub fn point(&self) -> Point {
         self.point
@@ -61,31 +489,291 @@ impl Shape {
         self.rotation
     }
 
-    /// Returns the shape's fill.
+    /// Returns the shape's fill, its style's fill.
     pub fn fill(&self) -> Color {
-        self.fill
+        self.style.fill()
     }
 
-    /// Returns the shape's stroke.
+    /// Returns the shape's stroke, its style's stroke.
     pub fn stroke(&self) -> Color {
-        self.stroke
+        self.style.stroke()
+    }
+
+    /// Returns the style the shape is drawn in unless a style set replaces
+    /// it.
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// Returns the shape's margin, if it overrides the model's.
+    pub fn margin(&self) -> Option<f64> {
+        self.margin
+    }
+
+    /// Returns the shape's corner style, if it overrides the model's.
+    pub fn corner(&self) -> Option<Corner> {
+        self.corner
+    }
+
+    /// Returns the shape's paint mode, if it overrides the model's.
+    pub fn paint(&self) -> Option<PaintMode> {
+        self.paint
+    }
+
+    /// Returns the shape's outline, if it overrides the model's.
+    pub fn outline(&self) -> Option<Outline> {
+        self.outline
+    }
+
+    /// Returns the shape's opacity, if it is not opaque.
+    pub fn opacity(&self) -> Option<f64> {
+        self.opacity
+    }
+
+    /// Returns the shape's blend mode, if it is not the normal one.
+    pub fn blend(&self) -> Option<Blend> {
+        self.blend
+    }
+
+    /// Returns the transform applied to the shape when it is rendered, if any.
+    pub fn transform(&self) -> Option<Affine> {
+        self.transform
+    }
+
+    /// Returns the order in which the shape's points go around it.
+    pub fn winding(&self) -> Winding {
+        self.winding
+    }
+
+    /// Returns the shape with the rendering overrides and winding of
+    /// prototype.
+    pub(crate) fn inherit(mut self, prototype: &Shape) -> Shape {
+        self.margin = prototype.margin;
+        self.corner = prototype.corner;
+        self.paint = prototype.paint;
+        self.outline = prototype.outline;
+        self.opacity = prototype.opacity;
+        self.blend = prototype.blend;
+        self.transform = prototype.transform;
+        self.winding = prototype.winding;
+
+        self
+    }
+
+    /// Returns the shape's styling: everything about it but its sides and
+    /// position.
+    pub(crate) fn styling(&self) -> Styling {
+        Styling {
+            style: self.style,
+            margin: self.margin,
+            corner: self.corner,
+            paint: self.paint,
+            outline: self.outline,
+            opacity: self.opacity,
+            blend: self.blend,
+            transform: self.transform,
+            winding: self.winding,
+        }
+    }
+
+    /// Returns a sides-sided shape centered at point, turned to rotation, and
+    /// styled with styling, without checking its sides as `new` does.
+    pub(crate) fn styled(sides: i32, point: Point, rotation: f64, styling: &Styling) -> Shape {
+        Shape {
+            sides,
+            point,
+            rotation,
+            style: styling.style,
+            margin: styling.margin,
+            corner: styling.corner,
+            paint: styling.paint,
+            outline: styling.outline,
+            opacity: styling.opacity,
+            blend: styling.blend,
+            transform: styling.transform,
+            winding: styling.winding,
+        }
+    }
+
+    /// Returns true if the shape and other share a center, a number of sides,
+    /// and a rotation (modulo the shape's rotational symmetry).
+    pub fn coincides(&self, other: &Shape) -> bool {
+        self.coincides_within(other, PRECISION)
+    }
+
+    /// Returns true if the shape and other coincide when compared to precision
+    /// decimal places.
+    pub(crate) fn coincides_within(&self, other: &Shape, precision: i32) -> bool {
+        if self.sides != other.sides || self.point.key(precision) != other.point.key(precision) {
+            return false;
+        }
+
+        let period = 2.0 * PI / self.sides as f64;
+        let delta = snap(
+            (self.rotation - other.rotation).rem_euclid(period),
+            precision,
+        );
+
+        delta == 0 || delta == snap(period, precision)
+    }
+
+    /// Returns the shape's area.
+    pub fn area(&self) -> f64 {
+        let n = self.sides as f64;
+
+        n / (4.0 * math::tan(PI / n))
+    }
+
+    /// Returns the length of the shape's boundary, one per side.
+    pub fn perimeter(&self) -> f64 {
+        self.sides as f64
+    }
+
+    /// Returns true if point lies inside the shape or on its boundary.
+    pub fn contains(&self, point: Point) -> bool {
+        let (dx, dy) = (point.x - self.point.x, point.y - self.point.y);
+        let r = (dx * dx + dy * dy).sqrt();
+        if self.sides > CIRCLE_SIDES {
+            return r <= self.circumradius() + EPSILON;
+        }
+
+        // Measure the point's angle from the normal of the nearest edge.
+        let angle = 2.0 * PI / self.sides as f64;
+        let normal = self.rotation - PI / 2.0 + angle / 2.0;
+        let theta = (math::atan2(dy, dx) - normal + angle / 2.0).rem_euclid(angle) - angle / 2.0;
+
+        r * math::cos(theta) <= 0.5 / math::tan(angle / 2.0) + EPSILON
+    }
+
+    /// Returns the distance from point to the shape's outline, which is
+    /// positive when point lies inside the shape and negative outside of it.
+    pub(crate) fn signed_distance(&self, point: Point) -> Result<f64> {
+        let (dx, dy) = (point.x - self.point.x, point.y - self.point.y);
+        let r = (dx * dx + dy * dy).sqrt();
+        if self.sides > CIRCLE_SIDES {
+            return Ok(self.circumradius() - r);
+        }
+
+        // Inside, the nearest edge is the one whose normal is nearest point.
+        let angle = 2.0 * PI / self.sides as f64;
+        let normal = self.rotation - PI / 2.0 + angle / 2.0;
+        let theta = (math::atan2(dy, dx) - normal + angle / 2.0).rem_euclid(angle) - angle / 2.0;
+        let inside = 0.5 / math::tan(angle / 2.0) - r * math::cos(theta);
+        if inside >= 0.0 {
+            return Ok(inside);
+        }
+
+        let outside = self
+            .edges(0.0)?
+            .iter()
+            .map(|(p0, p1)| {
+                let (ex, ey) = (p1.x - p0.x, p1.y - p0.y);
+                let t = (((point.x - p0.x) * ex + (point.y - p0.y) * ey) / (ex * ex + ey * ey))
+                    .clamp(0.0, 1.0);
+                distance(
+                    point,
+                    Point {
+                        x: p0.x + ex * t,
+                        y: p0.y + ey * t,
+                    },
+                )
+            })
+            .fold(f64::INFINITY, f64::min);
+
+        Ok(-outside)
+    }
+
+    /// Returns the interior angle (in radians) at each of the shape's
+    /// vertices.
+    pub fn vertex_angle(&self) -> f64 {
+        PI * (self.sides - 2) as f64 / self.sides as f64
+    }
+
+    /// Returns the distance (in edge lengths) from the shape's center to each
+    /// of its edges, the radius of the largest circle inside it.
+    pub fn inradius(&self) -> f64 {
+        0.5 / math::tan(PI / self.sides as f64)
+    }
+
+    /// Returns the distance (in edge lengths) from the shape's center to each
+    /// of its vertices, the radius of the smallest circle around it.
+    pub fn circumradius(&self) -> f64 {
+        0.5 / math::sin(PI / self.sides as f64)
+    }
+
+    /// Returns the shape's centroid: the mean of the points of its area.
+    /// A regular polygon balances on its center, and the shape's transform
+    /// (see `with_transform`) keeps it there, so this is the shape's point.
+    pub fn centroid(&self) -> Point {
+        self.point
+    }
+
+    /// Returns true if the interiors of the shape and other intersect.
+    /// Shapes that only touch along an edge or at a vertex do not overlap.
+    pub fn overlaps(&self, other: &Shape) -> Result<bool> {
+        let (dx, dy) = (self.point.x - other.point.x, self.point.y - other.point.y);
+        if (dx * dx + dy * dy).sqrt() >= self.circumradius() + other.circumradius() {
+            return Ok(false);
+        }
+
+        let (a, b) = (self.vertices(0.0)?, other.vertices(0.0)?);
+        let separated = |ps: &[Point]| {
+            ps.windows(2).any(|w| {
+                let (nx, ny) = (w[1].y - w[0].y, w[0].x - w[1].x);
+                let project = |qs: &[Point]| {
+                    qs.iter().fold((f64::MAX, f64::MIN), |(min, max), q| {
+                        let d = q.x * nx + q.y * ny;
+                        (min.min(d), max.max(d))
+                    })
+                };
+                let ((min_a, max_a), (min_b, max_b)) = (project(&a), project(&b));
+
+                max_a.min(max_b) - min_a.max(min_b) <= EPSILON
+            })
+        };
+
+        Ok(!separated(&a) && !separated(&b))
+    }
+
+    /// Returns true if a vertex of either the shape or other lies within (but not
+    /// at the end of) an edge of the other, meaning the two shapes do not meet
+    /// edge-to-edge.
+    pub fn meets_partially(&self, other: &Shape) -> Result<bool> {
+        let (a, b) = (self.vertices(0.0)?, other.vertices(0.0)?);
+        let partial = |vs: &[Point], ps: &[Point]| {
+            vs.iter().any(|v| {
+                ps.windows(2).any(|w| {
+                    let (p0, p1) = (w[0], w[1]);
+                    if *v == p0 || *v == p1 {
+                        return false;
+                    }
+                    let (ex, ey) = (p1.x - p0.x, p1.y - p0.y);
+                    let t = ((v.x - p0.x) * ex + (v.y - p0.y) * ey) / (ex * ex + ey * ey);
+                    let (cx, cy) = (p0.x + t * ex - v.x, p0.y + t * ey - v.y);
+
+                    t > 0.0 && t < 1.0 && (cx * cx + cy * cy).sqrt() < EPSILON
+                })
+            })
+        };
+
+        Ok(partial(&a, &b) || partial(&b, &a))
     }
 
     /// Returns the the edge indexed by index.
-    fn edge(&self, index: usize, margin: f64) -> Result<Edge> {
+    pub(crate) fn edge(&self, index: usize, margin: f64) -> Result<Edge> {
         let es = self.edges(margin)?;
         es.get(index)
             .ok_or(OutOfBounds {
-                index: index,
+                index,
                 length: es.len(),
                 name: String::from("shape edges"),
             })
-            .map(|(p0, p1)| (p0.clone(), p1.clone()))
+            .map(|(p0, p1)| (*p0, *p1))
     }
 
     /// Returns the shape's edges.
     fn edges(&self, margin: f64) -> Result<Vec<Edge>> {
-        let ps = self.points(margin)?;
+        let ps = self.vertices(margin)?;
 
         let mut es = Vec::new();
         for i in 0..self.sides {
@@ -101,101 +789,731 @@ impl Shape {
                 name: String::from("shape vertices"),
             })?;
 
-            es.push((p0.clone(), p1.clone()));
+            es.push((*p0, *p1));
         }
 
         Ok(es)
     }
 
     /// Returns the sides-sided shape adjacent to the edge with index edge.
+    /// The adjacent shape's edge with index 0 is the edge it shares with the
+    /// shape.
     pub fn adjacent(&self, sides: i32, edge: usize, fill: Color, stroke: Color) -> Result<Shape> {
+        self.adjacent_at(sides, edge, 0.0, fill, stroke)
+    }
+
+    /// Returns the sides-sided shape adjacent to the edge with index edge, slid
+    /// t edge lengths along the edge in the direction of its vertices.
+    /// The adjacent shape's edge with index 0 lies on the edge but, unless t is
+    /// 0, shares only part of it (1 - |t| edge lengths), as in tilings that are
+    /// not edge-to-edge. t must lie strictly between -1 and 1 so the shapes
+    /// still touch along an edge.
+    pub fn adjacent_at(
+        &self,
+        sides: i32,
+        edge: usize,
+        t: f64,
+        fill: Color,
+        stroke: Color,
+    ) -> Result<Shape> {
+        self.adjacent_on(sides, edge, t, Side::Outward, fill, stroke)
+    }
+
+    /// Returns the sides-sided shape on side of the edge with index edge,
+    /// slid t edge lengths along the edge as `adjacent_at` does.
+    /// A shape attached inward overlaps the shape unless the edge borders a
+    /// notch or a hole, such as the inside of a ring of shapes.
+    pub fn adjacent_on(
+        &self,
+        sides: i32,
+        edge: usize,
+        t: f64,
+        side: Side,
+        fill: Color,
+        stroke: Color,
+    ) -> Result<Shape> {
+        if t.is_nan() || t.abs() >= 1.0 {
+            return Err(InvalidShape {
+                reason: format!("offset {} does not lie strictly between -1 and 1", t),
+            });
+        }
+
         let (p0, p1) = self.edge(edge, 0.0)?;
         let angle = 2.0 * PI / sides as f64;
-        let a = (p1.y - p0.y).atan2(p1.x - p0.x);
+        // Attaching inward attaches outward to the edge walked backward.
+        let (p0, p1, t) = match side {
+            Side::Outward => (p0, p1, t),
+            Side::Inward => (p1, p0, -t),
+        };
+        let a = math::atan2(p1.y - p0.y, p1.x - p0.x);
         let b = a - PI / 2.0;
-        let d = 0.5 / (angle / 2.0).tan();
+        let d = 0.5 / math::tan(angle / 2.0);
         let p = Point {
-            x: p0.x + (p1.x - p0.x) / 2.0 + b.cos() * d,
-            y: p0.y + (p1.y - p0.y) / 2.0 + b.sin() * d,
-        };
+            x: p0.x + (p1.x - p0.x) / 2.0 + math::cos(b) * d,
+            y: p0.y + (p1.y - p0.y) / 2.0 + math::sin(b) * d,
+        } + (p1 - p0) * t;
         let r = a + angle * ((sides - 1) as f64 / 2.0);
 
-        Ok(Shape {
-            sides: sides,
-            point: p,
-            rotation: r,
-            fill: fill,
-            stroke: stroke,
-        })
+        Ok(Shape {
+            sides,
+            point: p,
+            rotation: r,
+            style: Style::new(fill, stroke),
+            margin: None,
+            corner: None,
+            paint: None,
+            outline: None,
+            opacity: None,
+            blend: None,
+            transform: None,
+            winding: Winding::default(),
+        })
+    }
+
+    /// Renders the index of each edge as an edge label, inside the shape and
+    /// clear of the edge's stroke, in black or white, whichever contrasts
+    /// more with the shape's fill.
+    #[cfg(feature = "cairo")]
+    pub fn render_edge_labels(&self, context: &cairo::Context, margin: f64) -> Result<()> {
+        self.label_edges(context, margin, None, None)
+    }
+
+    /// Renders text as the shape's label, centered on the shape, in black or
+    /// white, whichever contrasts more with the shape's fill.
+    #[cfg(feature = "cairo")]
+    pub fn render_label(&self, context: &cairo::Context, text: &str) -> Result<()> {
+        render::label(context, text, self.point, None, None, self.style.fill())
+    }
+
+    /// Renders the index of each of the shape's edges (inset by margin) in
+    /// color, if any, on a box of background, if any, just inside the edge's
+    /// stroke.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn label_edges(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        background: Option<Color>,
+        color: Option<Color>,
+    ) -> Result<()> {
+        let points = self.rendered_points(margin)?;
+        let clearance = context.line_width() / 2.0;
+        for (i, (p0, p1)) in points.iter().zip(points.iter().skip(1)).enumerate() {
+            let text = i.to_string();
+            let (width, height) = render::label_size(context, &text)?;
+            // The label is moved from the edge's midpoint along the edge's
+            // inward normal until its box clears the stroke.
+            let length = p0.distance(*p1);
+            let normal = Point {
+                x: (p0.y - p1.y) / length,
+                y: (p1.x - p0.x) / length,
+            };
+            let normal = if (self.point - *p0).x * normal.x + (self.point - *p0).y * normal.y < 0.0
+            {
+                normal * -1.0
+            } else {
+                normal
+            };
+            let extent = (normal.x.abs() * width + normal.y.abs() * height) / 2.0;
+            let center = p0.midpoint(*p1) + normal * (clearance + extent);
+            render::label(context, &text, center, background, color, self.style.fill())?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the shape's edges (inset by margin) for debugging: an arrow
+    /// inside each edge pointing from its first vertex to its second, a band
+    /// shading the inside of edge 0, and a faint line from the shape's center
+    /// to its first vertex marking its orientation.
+    /// Marks are sized by the shape's edges so they stay legible whatever the
+    /// line width.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn debug_edges(&self, context: &cairo::Context, margin: f64) -> Result<()> {
+        let points = self.rendered_points(margin)?;
+        let length = points[0].distance(points[1]);
+        let ink = match self.style.fill().luminance() < render::LABEL_LUMINANCE {
+            true => Color::WHITE,
+            false => Color::BLACK,
+        };
+        let unit = |p: Point| p * (1.0 / p.distance(Point::origin()).max(EPSILON));
+        // Arrows sit just inside their edges' strokes, so the arrows of
+        // neighbors sharing an edge don't cover each other.
+        let clearance = context.line_width() / 2.0 + length / 16.0;
+
+        context.save()?;
+        context.set_dash(&[], 0.0);
+        let (red, green, blue) = ink.rgb_unit_int();
+        context.set_source_rgba(red, green, blue, DEBUG_ALPHA);
+        context.set_line_width(length / 60.0);
+        context.move_to(self.point.x, self.point.y);
+        context.line_to(points[0].x, points[0].y);
+        context.stroke()?;
+
+        // The band is drawn inside edge 0 so it doesn't spill onto the shape
+        // across it.
+        let band = length / 8.0;
+        let inward = unit(self.point - points[0].midpoint(points[1])) * (band / 2.0);
+        let (red, green, blue) = DEBUG_FIRST_EDGE.rgb_unit_int();
+        context.set_source_rgba(red, green, blue, DEBUG_ALPHA);
+        context.set_line_width(band);
+        context.move_to(points[0].x + inward.x, points[0].y + inward.y);
+        context.line_to(points[1].x + inward.x, points[1].y + inward.y);
+        context.stroke()?;
+
+        context.set_line_width(length / 40.0);
+        for (i, (p0, p1)) in points.iter().zip(points.iter().skip(1)).enumerate() {
+            let along = unit(*p1 - *p0);
+            let normal = Point {
+                x: -along.y,
+                y: along.x,
+            };
+            let center = p0.midpoint(*p1) + unit(self.point - p0.midpoint(*p1)) * clearance;
+            let half = along * (DEBUG_ARROW * length / 2.0);
+            let (tail, head) = (center - half, center + half);
+            let barb = length / 10.0;
+            context.move_to(tail.x, tail.y);
+            context.line_to(head.x, head.y);
+            for side in [1.0, -1.0] {
+                let b = head - along * barb + normal * (side * barb / 2.0);
+                context.move_to(head.x, head.y);
+                context.line_to(b.x, b.y);
+            }
+            let color = match i {
+                0 => DEBUG_FIRST_EDGE,
+                _ => ink,
+            };
+            let (red, green, blue) = color.rgb_unit_int();
+            context.set_source_rgb(red, green, blue);
+            context.stroke()?;
+        }
+        context.restore()?;
+
+        Ok(())
+    }
+
+    /// Renders each of the shape's vertices (inset by margin) labeled with
+    /// label in color, if any, on a box of background, if any, just inside
+    /// the vertex's corner.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn label_vertices(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        label: VertexLabel,
+        background: Option<Color>,
+        color: Option<Color>,
+    ) -> Result<()> {
+        let points = self.rendered_points(margin)?;
+        let n = self.sides as usize;
+        let clearance = context.line_width() / 2.0;
+        for i in 0..n {
+            let text = match label {
+                VertexLabel::Index => i.to_string(),
+                VertexLabel::Coordinates => format!("{:.2},{:.2}", points[i].x, points[i].y),
+            };
+            let (width, height) = render::label_size(context, &text)?;
+            // The label is moved from the vertex along the bisector of its
+            // corner until a circle around its box clears both edges' strokes.
+            let unit = |p: Point| {
+                let d = p - points[i];
+                d * (1.0 / d.distance(Point::origin()))
+            };
+            let (a, b) = (unit(points[(i + n - 1) % n]), unit(points[i + 1]));
+            let bisector = unit(points[i] + a + b);
+            let sine = (a.x * bisector.y - a.y * bisector.x).abs();
+            let radius = (width * width + height * height).sqrt() / 2.0;
+            let center = points[i] + bisector * ((clearance + radius) / sine.max(EPSILON));
+            render::label(context, &text, center, background, color, self.style.fill())?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the shape's points inset by margin, clockwise from the vertex
+    /// at its rotation and back to it, whatever its winding, so that vertex
+    /// and edge indexes do not depend on the winding.
+    pub(crate) fn vertices(&self, margin: f64) -> Result<Vec<Point>> {
+        let angle = 2.0 * PI / self.sides as f64;
+        let d = {
+            let a = angle / 2.0;
+            0.5 / math::sin(a) - margin / math::cos(a)
+        };
+
+        let points = directions(self.sides, self.rotation)
+            .iter()
+            .map(|(cos, sin)| Point {
+                x: self.point.x + cos * d,
+                y: self.point.y + sin * d,
+            })
+            .collect();
+
+        Ok(points)
+    }
+
+    /// Returns the shape's points inset by margin and then transformed by the
+    /// shape's transform about its center.
+    pub(crate) fn rendered_points(&self, margin: f64) -> Result<Vec<Point>> {
+        let points = self.vertices(margin)?;
+        let transform = match self.transform {
+            Some(transform) => transform,
+            None => return Ok(points),
+        };
+
+        Ok(points
+            .iter()
+            .map(|p| {
+                let d = transform.apply(Point {
+                    x: p.x - self.point.x,
+                    y: p.y - self.point.y,
+                });
+                self.point + d
+            })
+            .collect())
+    }
+
+    /// Renders the shape, stroking each edge in styles with its style and every
+    /// other edge with the shape's stroke.
+    /// Styled edges are stroked straight to the shape's vertices, so only the
+    /// fill follows the shape's corner style.
+    #[cfg(feature = "cairo")]
+    pub fn render_with_edges(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        styles: &HashMap<usize, EdgeStyle>,
+    ) -> Result<()> {
+        self.render_with_paths(context, margin, styles, &HashMap::new())
+    }
+
+    /// Renders the shape as `render_with_edges` does, with each edge in paths
+    /// drawn through its path's points (in the frame of the edge, which runs
+    /// from (0, 0) to (1, 0) with y pointing out of the shape) rather than
+    /// straight.
+    /// The shape's corner style applies only if no edge has a path.
+    #[cfg(feature = "cairo")]
+    pub fn render_with_paths(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        styles: &HashMap<usize, EdgeStyle>,
+        paths: &HashMap<usize, Vec<Point>>,
+    ) -> Result<()> {
+        self.draw(context, margin, styles, paths, None)
+    }
+
+    /// Renders the shape as `render_with_paths` does, with the lines it
+    /// strokes snapped to the centers of pixels pixel device units wide if
+    /// pixel is some.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn draw(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        styles: &HashMap<usize, EdgeStyle>,
+        paths: &HashMap<usize, Vec<Point>>,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        if styles.is_empty() && paths.is_empty() {
+            return self.render_snapped(context, margin, pixel);
+        }
+
+        self.composite(context, || {
+            self.stroke_edges(context, margin, styles, paths, pixel)
+        })
+    }
+
+    /// Renders the shape as `draw` does, without compositing it.
+    #[cfg(feature = "cairo")]
+    fn stroke_edges(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        styles: &HashMap<usize, EdgeStyle>,
+        paths: &HashMap<usize, Vec<Point>>,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        let (outline, edges) = self.traced(margin, paths)?;
+
+        self.paint_traced(context, &outline, &edges, styles, pixel)
+    }
+
+    /// Returns the outline the shape is filled through, inset by margin, and
+    /// each of its edges as `traced_edges` returns them, as
+    /// `render_with_paths` draws them.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn traced(
+        &self,
+        margin: f64,
+        paths: &HashMap<usize, Vec<Point>>,
+    ) -> Result<(Vec<Point>, Vec<Vec<Point>>)> {
+        let edges = self.traced_edges(margin, paths)?;
+        let outline = match paths.is_empty() {
+            true => corners(
+                &self.rendered_points(margin)?,
+                self.corner.unwrap_or_default(),
+            ),
+            false => edges.concat(),
+        };
+
+        Ok((outline, edges))
+    }
+
+    /// Renders the shape as `render_with_paths` does through outline and
+    /// edges, as `traced` returns them, so they can be traced once and drawn
+    /// many times.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn render_traced(
+        &self,
+        context: &cairo::Context,
+        outline: &[Point],
+        edges: &[Vec<Point>],
+        styles: &HashMap<usize, EdgeStyle>,
+    ) -> Result<()> {
+        self.composite(context, || {
+            self.paint_traced(context, outline, edges, styles, None)
+        })
+    }
+
+    /// Renders the shape as `render_traced` does, without compositing it,
+    /// with the lines it strokes snapped to the centers of pixels pixel
+    /// device units wide if pixel is some.
+    #[cfg(feature = "cairo")]
+    fn paint_traced(
+        &self,
+        context: &cairo::Context,
+        outline: &[Point],
+        edges: &[Vec<Point>],
+        styles: &HashMap<usize, EdgeStyle>,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        let mode = self.paint.unwrap_or_default();
+        if mode.fills() {
+            trace(context, outline);
+            let (r, g, b) = self.style.fill().rgb_unit_int();
+            context.set_source_rgb(r, g, b);
+            context.fill()?;
+        }
+        if !mode.strokes() {
+            return Ok(());
+        }
+        // Unstyled outlines are stroked whole, so their corners are joined.
+        if styles.is_empty() {
+            trace_snapped(context, outline, pixel);
+            context.close_path();
+            let (r, g, b) = self.style.stroke().rgb_unit_int();
+            context.set_source_rgb(r, g, b);
+            context.stroke()?;
+
+            return Ok(());
+        }
+
+        for (i, line) in edges.iter().enumerate() {
+            let style = styles.get(&i).copied().unwrap_or_default();
+            if style.is_hidden() {
+                continue;
+            }
+
+            context.save()?;
+            if let Some((on, off)) = style.dashes() {
+                context.set_dash(&[on, off], 0.0);
+            }
+            let (r, g, b) = style.stroke().unwrap_or(self.style.stroke()).rgb_unit_int();
+            context.set_source_rgb(r, g, b);
+            trace_snapped(context, line, pixel);
+            context.stroke()?;
+            context.restore()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns each of the shape's edges, inset by margin, as the line from its
+    /// start to its end through the points of its path in paths, if it has
+    /// one, in the frame of the edge, which runs from (0, 0) to (1, 0) with y
+    /// pointing out of the shape.
+    pub(crate) fn traced_edges(
+        &self,
+        margin: f64,
+        paths: &HashMap<usize, Vec<Point>>,
+    ) -> Result<Vec<Vec<Point>>> {
+        let points = self.rendered_points(margin)?;
+        // Edges run clockwise on the canvas, with the shape to their right,
+        // unless a transform has mirrored them.
+        let area = points
+            .windows(2)
+            .map(|w| w[0].x * w[1].y - w[1].x * w[0].y)
+            .sum::<f64>();
+        let outward = match area > 0.0 {
+            true => -1.0,
+            false => 1.0,
+        };
+        let edges = points
+            .windows(2)
+            .enumerate()
+            .map(|(i, e)| {
+                let mut line = vec![e[0]];
+                if let Some(path) = paths.get(&i) {
+                    line.extend(path.iter().map(|q| {
+                        let q = Point {
+                            x: q.x,
+                            y: q.y * outward,
+                        };
+                        frame(e[0], e[1], q)
+                    }));
+                }
+                line.push(e[1]);
+
+                line
+            })
+            .collect();
+
+        Ok(edges)
+    }
+
+    /// Renders the shape as `Polygon::render_snapped` does, without
+    /// compositing it. Circles are traced as arcs, which are not snapped.
+    #[cfg(feature = "cairo")]
+    fn trace_and_paint(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        if self.sides <= CIRCLE_SIDES {
+            let points = corners(
+                &self.rendered_points(margin)?,
+                self.corner.unwrap_or_default(),
+            );
+            return render_snapped(
+                context,
+                &points,
+                self.style.fill(),
+                self.style.stroke(),
+                self.paint.unwrap_or_default(),
+                pixel,
+            );
+        }
+
+        let a = PI / self.sides as f64;
+        let d = 0.5 / math::sin(a) - margin / math::cos(a);
+        context.new_path();
+        // The path keeps the transform once the matrix is restored, but the
+        // stroke does not.
+        context.save()?;
+        if let Some(t) = self.transform {
+            let matrix = cairo::Matrix::new(t.xx, t.yx, t.xy, t.yy, 0.0, 0.0);
+            context.translate(self.point.x, self.point.y);
+            context.transform(matrix);
+            context.translate(-self.point.x, -self.point.y);
+        }
+        context.arc(self.point.x, self.point.y, d, 0.0, 2.0 * PI);
+        context.restore()?;
+
+        paint(
+            context,
+            self.style.fill(),
+            self.style.stroke(),
+            self.paint.unwrap_or_default(),
+        )
+    }
+
+    /// Paints what draw paints onto context with the shape's opacity and
+    /// blend mode, drawing it into a group of its own first unless the shape
+    /// is opaque and blended normally.
+    #[cfg(feature = "cairo")]
+    fn composite<F>(&self, context: &cairo::Context, draw: F) -> Result<()>
+    where
+        F: FnOnce() -> Result<()>,
+    {
+        let opacity = self.opacity.unwrap_or(1.0);
+        let blend = self.blend.unwrap_or_default();
+        if opacity >= 1.0 && blend == Blend::Normal {
+            return draw();
+        }
+
+        context.push_group();
+        // The group is popped even if drawing fails, so the context is left
+        // as it was.
+        let drawn = draw();
+        context.pop_group_to_source()?;
+        drawn?;
+        context.save()?;
+        context.set_operator(blend.operator());
+        context.paint_with_alpha(opacity)?;
+        context.restore()?;
+
+        Ok(())
+    }
+
+    /// Returns the shape rotated by a multiple of its rotational symmetry so
+    /// that the outward normal of its edge with index 0 is the first at or
+    /// clockwise (on the canvas) from angle.
+    /// The shape's geometry is unchanged; only its edges are renumbered.
+    pub(crate) fn renumbered(mut self, angle: f64) -> Shape {
+        let exterior = 2.0 * PI / self.sides as f64;
+        let normal = self.rotation - PI / 2.0 + exterior / 2.0;
+        let offset = (normal - angle + EPSILON).rem_euclid(exterior) - EPSILON;
+        self.rotation = (angle + offset + PI / 2.0 - exterior / 2.0).rem_euclid(2.0 * PI);
+
+        self
+    }
+
+    /// Returns the shape mirrored across axis.
+    /// Mirroring reverses the order of the shape's edges, so edge i of the
+    /// shape becomes edge sides - 1 - i of the mirrored shape.
+    pub fn reflected(&self, axis: &Axis) -> Shape {
+        let mut s = *self;
+        s.point = axis.reflect(self.point);
+        s.rotation = (2.0 * axis.angle - self.rotation + PI).rem_euclid(2.0 * PI);
+
+        s
+    }
+
+    /// Returns the shape rotated by angle (in radians) about center.
+    pub fn rotated(&self, angle: f64, center: Point) -> Shape {
+        let (sin, cos) = math::sin_cos(angle);
+        let (dx, dy) = (self.point.x - center.x, self.point.y - center.y);
+
+        let mut s = *self;
+        s.point = Point {
+            x: center.x + dx * cos - dy * sin,
+            y: center.y + dx * sin + dy * cos,
+        };
+        s.rotation = (self.rotation + angle).rem_euclid(2.0 * PI);
+
+        s
+    }
+
+    /// Returns the shape moved by vector.
+    pub fn translated(&self, vector: Point) -> Shape {
+        self.clone_at(self.point + vector)
+    }
+
+    /// Returns a copy of the shape centered at point and turned to rotation.
+    pub(crate) fn placed_at(&self, point: Point, rotation: f64) -> Shape {
+        let mut s = *self;
+        s.point = point;
+        s.rotation = rotation;
+
+        s
+    }
+
+    /// Returns a copy of the shape centered at point.
+    pub fn clone_at(&self, point: Point) -> Shape {
+        let mut s = *self;
+        s.point = point;
+
+        s
+    }
+
+    /// Returns the region covered by the shape or other as an irregular shape
+    /// with the shape's colors.
+    pub fn union(&self, other: &Shape) -> Result<IrregularShape> {
+        self.irregular()?.union(&other.irregular()?)
+    }
+
+    /// Returns the region covered by both the shape and other as an irregular
+    /// shape with the shape's colors, ensuring they overlap.
+    pub fn intersection(&self, other: &Shape) -> Result<IrregularShape> {
+        self.irregular()?.intersection(&other.irregular()?)
+    }
+
+    /// Returns the region covered by the shape but not other as an irregular
+    /// shape with the shape's colors, ensuring other does not cover the shape.
+    pub fn difference(&self, other: &Shape) -> Result<IrregularShape> {
+        self.irregular()?.difference(&other.irregular()?)
+    }
+
+    /// Returns the shape as an irregular shape.
+    fn irregular(&self) -> Result<IrregularShape> {
+        Ok(IrregularShape::new(self, self.fill(), self.stroke())?
+            .with_corner(self.corner.unwrap_or_default())
+            .with_paint(self.paint.unwrap_or_default()))
+    }
+}
+
+impl Polygon for Shape {
+    /// Returns the polygon's points in the shape's winding order, starting
+    /// and ending at the vertex at its rotation.
+    fn points(&self, margin: f64) -> Result<Vec<Point>> {
+        let mut points = self.vertices(margin)?;
+        if self.winding == Winding::CounterClockwise {
+            points.reverse();
+        }
+
+        Ok(points)
     }
 
-    /// Renders the index of each edge as an edge label.
-    pub fn render_edge_labels(&self, context: &cairo::Context, margin: f64) -> Result<()> {
-        let es = self.edges(margin)?;
-        for (i, e) in es.iter().enumerate() {
-            let text = i.to_string();
-            let (p0, p1) = e;
-            let te = context.text_extents(&text)?;
-            let x = p0.x + (p1.x - p0.x) / 2.0 - te.width / 2.0;
-            let y = p0.y + (p1.y - p0.y) / 2.0 - te.height / 2.0;
-
-            context.set_source_rgb(0.0, 0.0, 0.0);
-            context.move_to(x, y);
-            context.show_text(&text)?;
-        }
+    /// Returns the polygon's points inset by margin with its corners cut in
+    /// the shape's corner style, if it has one.
+    fn outline_points(&self, margin: f64) -> Result<Vec<Point>> {
+        Ok(corners(
+            &self.points(margin)?,
+            self.corner.unwrap_or_default(),
+        ))
+    }
 
-        Ok(())
+    /// Renders the polygon, composited with its opacity and blend mode.
+    /// Shapes with more than `CIRCLE_SIDES` sides are rendered as circles.
+    #[cfg(feature = "cairo")]
+    fn render(&self, context: &cairo::Context, margin: f64) -> Result<()> {
+        self.render_snapped(context, margin, None)
     }
 
-    /// Renders text as the shape's label.
-    pub fn render_label(&self, context: &cairo::Context, text: &str) -> Result<()> {
-        let te = context.text_extents(text)?;
-        let x = self.point.x - te.width / 2.0;
-        let y = self.point.y - te.height / 2.0;
+    /// Renders the polygon as `render` does, with its stroke snapped to the
+    /// centers of pixels pixel device units wide if pixel is some.
+    #[cfg(feature = "cairo")]
+    fn render_snapped(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        self.composite(context, || self.trace_and_paint(context, margin, pixel))
+    }
+}
 
-        context.set_source_rgb(0.0, 0.0, 0.0);
-        context.move_to(x, y);
-        context.show_text(text)?;
+impl Tile for Shape {
+    /// Returns the tile's center.
+    fn point(&self) -> Point {
+        self.point
+    }
 
-        Ok(())
+    /// Returns a copy of the tile moved by vector.
+    fn translated(&self, vector: Point) -> Box<dyn Tile> {
+        Box::new(Shape::translated(self, vector))
     }
 
-    /// Returns a copy of the shape centered at point.
-    pub fn clone_at(&self, point: Point) -> Shape {
-        let mut s = self.clone();
-        s.point = point;
+    /// Returns a copy of the tile rotated by angle (in radians) about center.
+    fn rotated(&self, angle: f64, center: Point) -> Box<dyn Tile> {
+        Box::new(Shape::rotated(self, angle, center))
+    }
 
-        s
+    /// Returns a copy of the tile mirrored across axis.
+    fn reflected(&self, axis: &Axis) -> Box<dyn Tile> {
+        Box::new(Shape::reflected(self, axis))
     }
-}
 
-impl Polygon for Shape {
-    /// Returns the polygon's points.
-    fn points(&self, margin: f64) -> Result<Vec<Point>> {
-        let angle = 2.0 * PI / self.sides as f64;
-        let rotation = self.rotation - PI / 2.0;
-        let angles = (0..=self.sides)
-            .map(|i| (i % self.sides) as f64 * angle + rotation)
-            .collect::<Vec<f64>>();
-        let d = {
-            let a = angle / 2.0;
-            0.5 / a.sin() - margin / a.cos()
-        };
+    /// Returns a copy of the tile filled with fill.
+    fn filled(&self, fill: Color) -> Box<dyn Tile> {
+        Box::new(self.with_colors(fill, self.stroke()))
+    }
 
-        let points = angles
-            .iter()
-            .map(|a| Point {
-                x: self.point.x + a.cos() * d,
-                y: self.point.y + a.sin() * d,
-            })
-            .collect();
+    /// Returns the color the tile is filled with.
+    fn fill(&self) -> Color {
+        self.style.fill()
+    }
 
-        Ok(points)
+    /// Returns the color the tile is stroked with.
+    fn stroke(&self) -> Color {
+        self.style.stroke()
     }
 
-    /// Renders the polygon.
-    fn render(&self, context: &cairo::Context, margin: f64) -> Result<()> {
-        render(context, self.points(margin)?, self.fill, self.stroke)
+    /// Returns whether the tile is filled, stroked, or both, which is the
+    /// shape's paint mode if it has one.
+    fn paint(&self) -> PaintMode {
+        self.paint.unwrap_or_default()
     }
 }
 
@@ -205,6 +1523,8 @@ pub struct Dual {
     points: Vec<Point>,
     fill: Color,
     stroke: Color,
+    corner: Corner,
+    paint: PaintMode,
 }
 
 impl Dual {
@@ -214,11 +1534,45 @@ impl Dual {
             points,
             fill,
             stroke,
+            corner: Corner::default(),
+            paint: PaintMode::default(),
         }
     }
 
+    /// Returns the dual with its corners rendered in the corner style.
+    pub fn with_corner(mut self, corner: Corner) -> Dual {
+        self.corner = corner;
+
+        self
+    }
+
+    /// Returns the dual with the parts painted by paint mode.
+    pub fn with_paint(mut self, paint: PaintMode) -> Dual {
+        self.paint = paint;
+
+        self
+    }
+
+    /// Returns whether insetting the dual by margin turns it inside out, which
+    /// happens once margin exceeds the dual's inradius.
+    pub fn collapses(&self, margin: f64) -> Result<bool> {
+        Ok(margin != 0.0 && self.inset(margin)?.is_none())
+    }
+
+    /// Returns the dual's points inset by margin, or None if an inset edge
+    /// reverses direction.
+    fn inset(&self, margin: f64) -> Result<Option<Vec<Point>>> {
+        let inset = Dual::inset_polygon(self.points.clone(), margin)?;
+        let reversed = self.points.windows(2).zip(inset.windows(2)).any(|(p, q)| {
+            let dot = (p[1].x - p[0].x) * (q[1].x - q[0].x) + (p[1].y - p[0].y) * (q[1].y - q[0].y);
+            !dot.is_finite() || dot <= 0.0
+        });
+
+        Ok(if reversed { None } else { Some(inset) })
+    }
+
     /// Computes the inset polygon for a polygon with vertices points.
-    fn inset_polygon(points: Vec<Point>, margin: f64) -> Result<Vec<Point>> {
+    pub(crate) fn inset_polygon(points: Vec<Point>, margin: f64) -> Result<Vec<Point>> {
         let p = points.get(points.len() - 2).ok_or(OutOfBounds {
             index: points.len() - 2,
             length: points.len(),
@@ -228,7 +1582,7 @@ impl Dual {
         points.insert(0, *p);
 
         let mut rs = multizip((&points, &points[1..], &points[2..]))
-            .map(|(p0, p1, p2)| Dual::inset_corner((p0.clone(), p1.clone(), p2.clone()), margin))
+            .map(|(p0, p1, p2)| Dual::inset_corner((*p0, *p1, *p2), margin))
             .collect::<Vec<Point>>();
         rs.push(rs[0]);
 
@@ -238,12 +1592,12 @@ impl Dual {
     /// Computes the inset corner for a tuple of three points.
     fn inset_corner(plane: Plane, margin: f64) -> Point {
         let (p0, p1, p2) = plane;
-        let a0 = (p1.y - p0.y).atan2(p1.x - p0.x) - PI / 2.0;
-        let a1 = (p2.y - p1.y).atan2(p2.x - p1.x) - PI / 2.0;
-        let (ax0, ay0) = (p0.x + a0.cos() * margin, p0.y + a0.sin() * margin);
-        let (ax1, ay1) = (p1.x + a0.cos() * margin, p1.y + a0.sin() * margin);
-        let (bx0, by0) = (p1.x + a1.cos() * margin, p1.y + a1.sin() * margin);
-        let (bx1, by1) = (p2.x + a1.cos() * margin, p2.y + a1.sin() * margin);
+        let a0 = math::atan2(p1.y - p0.y, p1.x - p0.x) - PI / 2.0;
+        let a1 = math::atan2(p2.y - p1.y, p2.x - p1.x) - PI / 2.0;
+        let (ax0, ay0) = (p0.x + math::cos(a0) * margin, p0.y + math::sin(a0) * margin);
+        let (ax1, ay1) = (p1.x + math::cos(a0) * margin, p1.y + math::sin(a0) * margin);
+        let (bx0, by0) = (p1.x + math::cos(a1) * margin, p1.y + math::sin(a1) * margin);
+        let (bx1, by1) = (p2.x + math::cos(a1) * margin, p2.y + math::sin(a1) * margin);
         let (ady, adx) = (ay1 - ay0, ax0 - ax1);
         let (bdy, bdx) = (by1 - by0, bx0 - bx1);
         let c0 = ady * ax0 + adx * ay0;
@@ -261,15 +1615,294 @@ impl Polygon for Dual {
     /// Returns the polygon's points.
     fn points(&self, margin: f64) -> Result<Vec<Point>> {
         if margin == 0.0 {
-            Ok(self.points.clone())
-        } else {
-            Dual::inset_polygon(self.points.clone(), margin)
+            return Ok(self.points.clone());
+        }
+
+        match self.inset(margin)? {
+            Some(points) => Ok(points),
+            None => {
+                let vertices = &self.points[0..self.points.len() - 1];
+                let n = vertices.len() as f64;
+                let center = Point {
+                    x: vertices.iter().map(|p| p.x).sum::<f64>() / n,
+                    y: vertices.iter().map(|p| p.y).sum::<f64>() / n,
+                };
+
+                Ok(vec![center; self.points.len()])
+            }
         }
     }
 
-    /// Renders the polygon.
+    /// Renders the polygon, skipping it if margin collapses it.
+    #[cfg(feature = "cairo")]
     fn render(&self, context: &cairo::Context, margin: f64) -> Result<()> {
-        render(context, self.points(margin)?, self.fill, self.stroke)
+        self.render_snapped(context, margin, None)
+    }
+
+    /// Renders the polygon as `render` does, with its stroke snapped to the
+    /// centers of pixels pixel device units wide if pixel is some.
+    #[cfg(feature = "cairo")]
+    fn render_snapped(
+        &self,
+        context: &cairo::Context,
+        margin: f64,
+        pixel: Option<f64>,
+    ) -> Result<()> {
+        if self.collapses(margin)? {
+            return Ok(());
+        }
+
+        render_snapped(
+            context,
+            &self.outline_points(margin)?,
+            self.fill,
+            self.stroke,
+            self.paint,
+            pixel,
+        )
+    }
+
+    /// Returns the dual's points inset by margin with its corners cut in its
+    /// corner style.
+    fn outline_points(&self, margin: f64) -> Result<Vec<Point>> {
+        Ok(corners(&self.points(margin)?, self.corner))
+    }
+}
+
+impl Tile for Dual {
+    /// Returns the average of the dual's vertices.
+    fn point(&self) -> Point {
+        let vertices = &self.points[0..self.points.len() - 1];
+        let n = vertices.len() as f64;
+
+        Point {
+            x: vertices.iter().map(|p| p.x).sum::<f64>() / n,
+            y: vertices.iter().map(|p| p.y).sum::<f64>() / n,
+        }
+    }
+
+    /// Returns a copy of the tile moved by vector.
+    fn translated(&self, vector: Point) -> Box<dyn Tile> {
+        let mut d = self.clone();
+        d.points = self.points.iter().map(|p| *p + vector).collect();
+
+        Box::new(d)
+    }
+
+    /// Returns a copy of the tile rotated by angle (in radians) about center.
+    fn rotated(&self, angle: f64, center: Point) -> Box<dyn Tile> {
+        let mut d = self.clone();
+        d.points = self
+            .points
+            .iter()
+            .map(|p| p.rotate(angle, center))
+            .collect();
+
+        Box::new(d)
+    }
+
+    /// Returns a copy of the dual mirrored across axis, with its points
+    /// reversed so they keep winding the same way.
+    fn reflected(&self, axis: &Axis) -> Box<dyn Tile> {
+        let mut d = self.clone();
+        d.points = self.points.iter().rev().map(|p| axis.reflect(*p)).collect();
+
+        Box::new(d)
+    }
+
+    /// Returns a copy of the tile filled with fill.
+    fn filled(&self, fill: Color) -> Box<dyn Tile> {
+        let mut d = self.clone();
+        d.fill = fill;
+
+        Box::new(d)
+    }
+
+    /// Returns the color the tile is filled with.
+    fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the color the tile is stroked with.
+    fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns whether the tile is filled, stroked, or both.
+    fn paint(&self) -> PaintMode {
+        self.paint
+    }
+}
+
+/// A linear transform of the plane, such as a scale, skew, or rotation, which
+/// `Shape::with_transform` applies to a shape about its center.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    xx: f64,
+    xy: f64,
+    yx: f64,
+    yy: f64,
+}
+
+impl Affine {
+    /// Returns the transform that maps (x, y) to (xx * x + xy * y, yx * x +
+    /// yy * y).
+    pub fn new(xx: f64, xy: f64, yx: f64, yy: f64) -> Affine {
+        Affine { xx, xy, yx, yy }
+    }
+
+    /// Returns the transform that leaves every point in place.
+    pub fn identity() -> Affine {
+        Affine::new(1.0, 0.0, 0.0, 1.0)
+    }
+
+    /// Returns the transform that scales x coordinates by x and y coordinates
+    /// by y.
+    pub fn scale(x: f64, y: f64) -> Affine {
+        Affine::new(x, 0.0, 0.0, y)
+    }
+
+    /// Returns the transform that rotates points by angle (in radians).
+    pub fn rotate(angle: f64) -> Affine {
+        let (sin, cos) = math::sin_cos(angle);
+
+        Affine::new(cos, -sin, sin, cos)
+    }
+
+    /// Returns the transform that skews points by angle x (in radians) along
+    /// the x axis and by angle y along the y axis.
+    pub fn skew(x: f64, y: f64) -> Affine {
+        Affine::new(1.0, math::tan(x), math::tan(y), 1.0)
+    }
+
+    /// Returns the transform that applies the transform and then other.
+    pub fn then(&self, other: Affine) -> Affine {
+        Affine::new(
+            other.xx * self.xx + other.xy * self.yx,
+            other.xx * self.xy + other.xy * self.yy,
+            other.yx * self.xx + other.yy * self.yx,
+            other.yx * self.xy + other.yy * self.yy,
+        )
+    }
+
+    /// Returns the transform's components in the order `new` takes them.
+    pub fn components(&self) -> (f64, f64, f64, f64) {
+        (self.xx, self.xy, self.yx, self.yy)
+    }
+
+    /// Returns point transformed.
+    pub fn apply(&self, point: Point) -> Point {
+        Point {
+            x: self.xx * point.x + self.xy * point.y,
+            y: self.yx * point.x + self.yy * point.y,
+        }
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Affine {
+        Affine::identity()
+    }
+}
+
+/// A line that shapes may be mirrored across.
+#[derive(Clone, Copy, Debug)]
+pub struct Axis {
+    point: Point,
+    angle: f64,
+}
+
+impl Axis {
+    /// Returns the line through point at angle (in radians) to the x axis.
+    pub fn new(point: Point, angle: f64) -> Axis {
+        Axis { point, angle }
+    }
+
+    /// Returns the x axis.
+    pub fn horizontal() -> Axis {
+        Axis::new(Point::origin(), 0.0)
+    }
+
+    /// Returns the y axis.
+    pub fn vertical() -> Axis {
+        Axis::new(Point::origin(), PI / 2.0)
+    }
+
+    /// Returns a point on the axis.
+    pub fn point(&self) -> Point {
+        self.point
+    }
+
+    /// Returns the axis' angle (in radians) to the x axis.
+    pub fn angle(&self) -> f64 {
+        self.angle
+    }
+
+    /// Returns point mirrored across the axis.
+    pub(crate) fn reflect(&self, point: Point) -> Point {
+        let (sin, cos) = math::sin_cos(2.0 * self.angle);
+        let (dx, dy) = (point.x - self.point.x, point.y - self.point.y);
+
+        Point {
+            x: self.point.x + dx * cos + dy * sin,
+            y: self.point.y + dx * sin - dy * cos,
+        }
+    }
+}
+
+/// An axis-aligned rectangle in model units.
+#[derive(Clone, Copy, Debug)]
+pub struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Rect {
+    /// Returns the rectangle with its top left corner at x, y that is width
+    /// wide and height tall.
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Returns the rectangle's top left corner.
+    pub fn origin(&self) -> Point {
+        Point {
+            x: self.x,
+            y: self.y,
+        }
+    }
+
+    /// Returns the rectangle's center.
+    pub fn center(&self) -> Point {
+        Point {
+            x: self.x + self.width / 2.0,
+            y: self.y + self.height / 2.0,
+        }
+    }
+
+    /// Returns the rectangle's width.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// Returns the rectangle's height.
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    /// Returns true if the rectangle and other overlap.
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
     }
 }
 
@@ -280,16 +1913,58 @@ pub struct Point {
     pub y: f64,
 }
 
+/// Represents a displacement in two-dimensional space, such as the vectors
+/// passed to `Model::repeat_by`.
+/// Vectors are points measured from the origin, so they share points'
+/// arithmetic: the difference of two points is the vector between them, and
+/// a point plus a vector is the point moved along it.
+pub type Vector = Point;
+
 impl Point {
     /// Returns a point at the origin.
     pub fn origin() -> Point {
         Point { x: 0.0, y: 0.0 }
     }
 
+    /// Returns the point scaled by factor about origin.
+    pub fn scale(&self, factor: f64, origin: Point) -> Point {
+        origin + (*self - origin) * factor
+    }
+
+    /// Returns the point rotated by angle (in radians) about origin.
+    pub fn rotate(&self, angle: f64, origin: Point) -> Point {
+        let (sin, cos) = math::sin_cos(angle);
+        let (dx, dy) = (self.x - origin.x, self.y - origin.y);
+
+        Point {
+            x: origin.x + dx * cos - dy * sin,
+            y: origin.y + dx * sin + dy * cos,
+        }
+    }
+
+    /// Returns the distance between the point and other.
+    pub fn distance(&self, other: Point) -> f64 {
+        math::hypot(other.x - self.x, other.y - self.y)
+    }
+
+    /// Returns the point halfway between the point and other.
+    pub fn midpoint(&self, other: Point) -> Point {
+        Point {
+            x: (self.x + other.x) / 2.0,
+            y: (self.y + other.y) / 2.0,
+        }
+    }
+
+    /// Returns the point snapped to precision decimal digits, which identifies
+    /// every point that equals it at that precision.
+    pub(crate) fn key(&self, precision: i32) -> Key {
+        Key(snap(self.x, precision), snap(self.y, precision))
+    }
+
     /// Returns a normalized point component.
     /// This is used to simplify equality tests and hashing.
-    fn normalize(n: f64) -> i32 {
-        (n * 10_f64.powi(PRECISION)).round() as i32
+    fn normalize(n: f64) -> i64 {
+        snap(n, PRECISION)
     }
 }
 
@@ -320,29 +1995,301 @@ impl ops::Add<Point> for Point {
     }
 }
 
+impl ops::Sub<Point> for Point {
+    type Output = Point;
+
+    fn sub(self, _rhs: Point) -> Point {
+        Point {
+            x: self.x - _rhs.x,
+            y: self.y - _rhs.y,
+        }
+    }
+}
+
+impl ops::Mul<f64> for Point {
+    type Output = Point;
+
+    fn mul(self, _rhs: f64) -> Point {
+        Point {
+            x: self.x * _rhs,
+            y: self.y * _rhs,
+        }
+    }
+}
+
+/// A point snapped to a fixed number of decimal digits, used to compare and
+/// hash points at a precision other than `PRECISION`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Key(pub(crate) i64, pub(crate) i64);
+
+/// Returns n snapped to precision decimal digits.
+pub(crate) fn snap(n: f64, precision: i32) -> i64 {
+    (n * math::powi(10.0, precision)).round() as i64
+}
+
 /// A representation of an edge in two-dimensional space.
 type Edge = (Point, Point);
 
 /// A representation of a plane in two-dimensional space.
 type Plane = (Point, Point, Point);
 
-/// Renders the polygon defined by points.
-fn render(context: &cairo::Context, points: Vec<Point>, fill: Color, stroke: Color) -> Result<()> {
-    for i in 0..points.len() {
-        let p = points[i];
+/// Returns the closed polygon points with its corners replaced according to
+/// the corner style.
+/// A corner is never cut back past the midpoint of either of its edges.
+pub(crate) fn corners(points: &[Point], corner: Corner) -> Vec<Point> {
+    let size = match corner {
+        Corner::Miter => return points.to_vec(),
+        Corner::Round(size) | Corner::Bevel(size) => size,
+    };
+    if size <= 0.0 || points.len() < 4 {
+        return points.to_vec();
+    }
+
+    let n = points.len() - 1;
+    let mut rs = Vec::new();
+    for i in 0..n {
+        let (p, v, q) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+        let (l0, l1) = (distance(v, p), distance(v, q));
+        let (u0, u1) = (
+            ((p.x - v.x) / l0, (p.y - v.y) / l0),
+            ((q.x - v.x) / l1, (q.y - v.y) / l1),
+        );
+        let half = math::acos((u0.0 * u1.0 + u0.1 * u1.1).clamp(-1.0, 1.0)) / 2.0;
+
+        let t = match corner {
+            Corner::Round(r) => r / math::tan(half),
+            _ => size,
+        }
+        .min(l0 / 2.0)
+        .min(l1 / 2.0);
+        let a = Point {
+            x: v.x + u0.0 * t,
+            y: v.y + u0.1 * t,
+        };
+        let b = Point {
+            x: v.x + u1.0 * t,
+            y: v.y + u1.1 * t,
+        };
+
+        if let Corner::Round(_) = corner {
+            let (bx, by) = (u0.0 + u1.0, u0.1 + u1.1);
+            let bl = (bx * bx + by * by).sqrt();
+            let h = t / math::cos(half);
+            let c = Point {
+                x: v.x + bx / bl * h,
+                y: v.y + by / bl * h,
+            };
+            let r = t * math::tan(half);
+            let start = math::atan2(a.y - c.y, a.x - c.x);
+            let mut sweep = math::atan2(b.y - c.y, b.x - c.x) - start;
+            if sweep > PI {
+                sweep -= 2.0 * PI;
+            } else if sweep < -PI {
+                sweep += 2.0 * PI;
+            }
+            for k in 0..=CORNER_SEGMENTS {
+                let angle = start + sweep * k as f64 / CORNER_SEGMENTS as f64;
+                rs.push(Point {
+                    x: c.x + math::cos(angle) * r,
+                    y: c.y + math::sin(angle) * r,
+                });
+            }
+        } else {
+            rs.push(a);
+            rs.push(b);
+        }
+    }
+    rs.push(rs[0]);
+
+    rs
+}
+
+/// Returns the cosine and sine of the direction from the center of a shape
+/// with sides sides and rotation to each of its vertices, clockwise from the
+/// vertex at its rotation and back to it.
+/// Every placement of a shape in a repeated model shares its rotation, so the
+/// directions are computed once per thread and reused.
+fn directions(sides: i32, rotation: f64) -> Directions {
+    let key = (sides, rotation.to_bits());
+    DIRECTIONS.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some(directions) = cache.get(&key) {
+            return directions.clone();
+        }
+
+        let angle = 2.0 * PI / sides as f64;
+        let rotation = rotation - PI / 2.0;
+        let directions = (0..=sides)
+            .map(|i| {
+                let a = (i % sides) as f64 * angle + rotation;
+                (math::cos(a), math::sin(a))
+            })
+            .collect::<Directions>();
+        if cache.len() >= DIRECTIONS_CACHED {
+            cache.clear();
+        }
+        cache.insert(key, directions.clone());
+
+        directions
+    })
+}
+
+/// Returns the distance between p0 and p1.
+fn distance(p0: Point, p1: Point) -> f64 {
+    ((p1.x - p0.x).powi(2) + (p1.y - p0.y).powi(2)).sqrt()
+}
+
+/// Returns point q of the frame of the edge from p0 to p1, in which the edge
+/// runs from (0, 0) to (1, 0) and y points to the right of the edge on the
+/// canvas.
+pub(crate) fn frame(p0: Point, p1: Point, q: Point) -> Point {
+    let (dx, dy) = (p1.x - p0.x, p1.y - p0.y);
+
+    Point {
+        x: p0.x + q.x * dx - q.y * dy,
+        y: p0.y + q.x * dy + q.y * dx,
+    }
+}
+
+/// Returns v, a coordinate in device units, moved to the center of the
+/// pixel pixel device units wide that contains it.
+pub(crate) fn pixel_center(v: f64, pixel: f64) -> f64 {
+    ((v / pixel).floor() + 0.5) * pixel
+}
+
+/// Returns point moved to the center of the pixel pixel device units wide
+/// that contains it.
+#[cfg(feature = "cairo")]
+fn snap_to_pixel(context: &cairo::Context, point: Point, pixel: f64) -> Point {
+    let (x, y) = context.user_to_device(point.x, point.y);
+
+    context
+        .device_to_user(pixel_center(x, pixel), pixel_center(y, pixel))
+        .map(|(x, y)| Point { x, y })
+        .unwrap_or(point)
+}
+
+/// Adds the path through points to the context.
+#[cfg(feature = "cairo")]
+pub(crate) fn trace(context: &cairo::Context, points: &[Point]) {
+    trace_snapped(context, points, None);
+}
+
+/// Adds the path through points to the context, snapped to the centers of
+/// pixels pixel device units wide if pixel is some.
+#[cfg(feature = "cairo")]
+pub(crate) fn trace_snapped(context: &cairo::Context, points: &[Point], pixel: Option<f64>) {
+    for (i, p) in points.iter().enumerate() {
+        let p = match pixel {
+            Some(pixel) => snap_to_pixel(context, *p, pixel),
+            None => *p,
+        };
         match i {
             0 => context.move_to(p.x, p.y),
             _ => context.line_to(p.x, p.y),
         }
     }
+}
+
+/// Renders the polygon defined by points.
+#[cfg(feature = "cairo")]
+pub(crate) fn render(
+    context: &cairo::Context,
+    points: &[Point],
+    fill: Color,
+    stroke: Color,
+    mode: PaintMode,
+) -> Result<()> {
+    render_snapped(context, points, fill, stroke, mode, None)
+}
+
+/// Renders the polygon defined by points as `render` does, with its stroke
+/// snapped to the centers of pixels pixel device units wide if pixel is
+/// some.
+#[cfg(feature = "cairo")]
+pub(crate) fn render_snapped(
+    context: &cairo::Context,
+    points: &[Point],
+    fill: Color,
+    stroke: Color,
+    mode: PaintMode,
+    pixel: Option<f64>,
+) -> Result<()> {
+    paint_path(context, fill, stroke, mode, pixel, |pixel| {
+        trace_snapped(context, points, pixel)
+    })
+}
+
+/// Fills and/or strokes the path that trace adds to the context according
+/// to mode, as `paint` does. The fill is traced with no pixel and the stroke
+/// with pixel, so that only the stroke is snapped.
+#[cfg(feature = "cairo")]
+pub(crate) fn paint_path<F>(
+    context: &cairo::Context,
+    fill: Color,
+    stroke: Color,
+    mode: PaintMode,
+    pixel: Option<f64>,
+    trace: F,
+) -> Result<()>
+where
+    F: Fn(Option<f64>),
+{
+    if pixel.is_none() || !mode.strokes() {
+        trace(None);
+        return paint(context, fill, stroke, mode);
+    }
+
+    if mode.fills() {
+        trace(None);
+        paint(context, fill, stroke, PaintMode::Fill)?;
+    }
+    trace(pixel);
+
+    paint(context, fill, stroke, PaintMode::Stroke)
+}
 
-    let (r, g, b) = fill.rgb_unit_int();
-    context.set_source_rgb(r, g, b);
-    context.fill_preserve()?;
+/// Fills and/or strokes the context's current path according to mode.
+#[cfg(feature = "cairo")]
+pub(crate) fn paint(
+    context: &cairo::Context,
+    fill: Color,
+    stroke: Color,
+    mode: PaintMode,
+) -> Result<()> {
+    if mode.fills() {
+        let (r, g, b) = fill.rgb_unit_int();
+        context.set_source_rgb(r, g, b);
+        context.fill_preserve()?;
+    }
 
-    let (r, g, b) = stroke.rgb_unit_int();
-    context.set_source_rgb(r, g, b);
-    context.stroke()?;
+    if mode.strokes() {
+        let (r, g, b) = stroke.rgb_unit_int();
+        context.set_source_rgb(r, g, b);
+        context.stroke()?;
+    } else {
+        context.new_path();
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_path_bulges_outward() -> Result<()> {
+        let color = Color::new(0, 0, 0)?;
+        let square = Shape::new(4, color, color)?;
+        let paths = HashMap::from([(0, vec![Point { x: 0.5, y: 0.25 }])]);
+
+        let edges = square.traced_edges(0.0, &paths)?;
+        let bulge = edges[0][1];
+        let middle = edges[0][0].midpoint(edges[0][2]);
+        assert!(bulge.distance(square.point()) > middle.distance(square.point()));
+        assert!((bulge.distance(middle) - 0.25).abs() < 1e-9);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use tiny_skia::{
+    FillRule, FilterQuality, LineCap, LineJoin, Mask, Paint, PathBuilder, Pixmap, PixmapPaint,
+    Stroke, StrokeDash, Transform,
+};
+
+use crate::{Antialias, Color, Error, Error::*, Point, RenderBackend, RenderOptions, Result};
+
+/// A backend that renders with tiny-skia, in pure Rust, so PNGs can be made
+/// where cairo is painful to build.
+/// It honors the line caps and joins, miter limit, antialiasing (on or off),
+/// and supersampling of its options.
+pub struct SkiaBackend {
+    options: RenderOptions,
+    pixmap: Option<Pixmap>,
+}
+
+impl SkiaBackend {
+    /// Returns a backend that renders as configured by options.
+    pub fn new(options: &RenderOptions) -> SkiaBackend {
+        SkiaBackend {
+            options: options.clone(),
+            pixmap: None,
+        }
+    }
+
+    /// Returns the pixmap to draw on, or an error if the render has not
+    /// begun.
+    fn pixmap(&mut self) -> Result<&mut Pixmap> {
+        self.pixmap.as_mut().ok_or_else(not_begun)
+    }
+
+    /// Returns a paint of color.
+    fn paint(&self, color: Color) -> Paint<'static> {
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(
+            color.red() as u8,
+            color.green() as u8,
+            color.blue() as u8,
+            255,
+        );
+        paint.anti_alias = self.options.antialias != Antialias::None;
+
+        paint
+    }
+
+    /// Returns the transform from pixels to the pixmap, which is larger than
+    /// the render when it is supersampled.
+    fn transform(&self) -> Transform {
+        let factor = self.options.supersample as f32;
+
+        Transform::from_scale(factor, factor)
+    }
+}
+
+/// Returns the path through points, or an error if it has fewer than 2 points.
+fn path(points: &[Point]) -> Result<tiny_skia::Path> {
+    let mut builder = PathBuilder::new();
+    for (i, p) in points.iter().enumerate() {
+        match i {
+            0 => builder.move_to(p.x as f32, p.y as f32),
+            _ => builder.line_to(p.x as f32, p.y as f32),
+        }
+    }
+
+    builder.finish().ok_or_else(|| Backend {
+        reason: format!("cannot draw a path through {} points", points.len()),
+    })
+}
+
+/// Returns the closed path through the points of each of rings, or none if
+/// they have no points.
+fn rings(rings: &[Vec<Point>]) -> Option<tiny_skia::Path> {
+    let mut builder = PathBuilder::new();
+    for ring in rings.iter().filter(|r| !r.is_empty()) {
+        builder.move_to(ring[0].x as f32, ring[0].y as f32);
+        for p in ring.iter().skip(1) {
+            builder.line_to(p.x as f32, p.y as f32);
+        }
+        builder.close();
+    }
+
+    builder.finish()
+}
+
+/// Returns the error of allocating a width by height pixmap.
+fn unallocated(width: u32, height: u32) -> Error {
+    Backend {
+        reason: format!("cannot allocate a {} by {} pixmap", width, height),
+    }
+}
+
+/// Returns the error of drawing before a render has begun.
+fn not_begun() -> Error {
+    Backend {
+        reason: String::from("render has not begun"),
+    }
+}
+
+impl RenderBackend for SkiaBackend {
+    type Output = SkiaRender;
+
+    fn options(&self) -> &RenderOptions {
+        &self.options
+    }
+
+    fn begin(&mut self, width: i32, height: i32, background: Option<Color>) -> Result<()> {
+        let (width, height) = (width as u32, height as u32);
+        let factor = self.options.supersample;
+        let mut pixmap = Pixmap::new(width * factor, height * factor)
+            .ok_or_else(|| unallocated(width, height))?;
+        if let Some(c) = background {
+            let (r, g, b) = (c.red() as u8, c.green() as u8, c.blue() as u8);
+            pixmap.fill(tiny_skia::Color::from_rgba8(r, g, b, 255));
+        }
+        self.pixmap = Some(pixmap);
+
+        Ok(())
+    }
+
+    fn fill(&mut self, points: &[Point], color: Color) -> Result<()> {
+        let (path, paint, transform) = (path(points)?, self.paint(color), self.transform());
+        self.pixmap()?
+            .fill_path(&path, &paint, FillRule::Winding, transform, None);
+
+        Ok(())
+    }
+
+    fn stroke(&mut self, points: &[Point], color: Color, width: f64, dash: &[f64]) -> Result<()> {
+        let (path, paint, transform) = (path(points)?, self.paint(color), self.transform());
+        // Like cairo, an odd number of dash lengths repeats to alternate.
+        let mut dash = dash.iter().map(|d| *d as f32).collect::<Vec<f32>>();
+        if dash.len() % 2 == 1 {
+            dash.extend(dash.clone());
+        }
+        let stroke = Stroke {
+            width: width as f32,
+            miter_limit: self.options.miter_limit as f32,
+            line_cap: match self.options.line_cap {
+                crate::LineCap::Butt => LineCap::Butt,
+                crate::LineCap::Round => LineCap::Round,
+                crate::LineCap::Square => LineCap::Square,
+            },
+            line_join: match self.options.line_join {
+                crate::LineJoin::Miter => LineJoin::Miter,
+                crate::LineJoin::Round => LineJoin::Round,
+                crate::LineJoin::Bevel => LineJoin::Bevel,
+            },
+            dash: StrokeDash::new(dash, self.options.dash_offset as f32),
+        };
+        self.pixmap()?
+            .stroke_path(&path, &paint, &stroke, transform, None);
+
+        Ok(())
+    }
+
+    fn border(&mut self, rings: &[Vec<Point>], color: Color, width: f64) -> Result<()> {
+        let path = match self::rings(rings) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let (paint, transform) = (self.paint(color), self.transform());
+        let pixmap = self.pixmap()?;
+        let (w, h) = (pixmap.width(), pixmap.height());
+        // A stroke twice as wide as the band, masked to the outside of the
+        // rings, leaves the band without covering them.
+        let mut mask = Mask::new(w, h).ok_or_else(|| unallocated(w, h))?;
+        mask.fill_path(&path, FillRule::EvenOdd, paint.anti_alias, transform);
+        mask.invert();
+        let stroke = Stroke {
+            width: 2.0 * width as f32,
+            line_join: LineJoin::Round,
+            ..Stroke::default()
+        };
+        pixmap.stroke_path(&path, &paint, &stroke, transform, Some(&mask));
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<SkiaRender> {
+        let transparent = self.options.background.is_none();
+        let factor = self.options.supersample;
+        let pixmap = self.pixmap.ok_or_else(not_begun)?;
+        if factor == 1 {
+            return Ok(SkiaRender {
+                pixmap,
+                transparent,
+            });
+        }
+
+        let (width, height) = (pixmap.width() / factor, pixmap.height() / factor);
+        let mut render =
+            Pixmap::new(width.max(1), height.max(1)).ok_or_else(|| unallocated(width, height))?;
+        let paint = PixmapPaint {
+            quality: FilterQuality::Bicubic,
+            ..PixmapPaint::default()
+        };
+        let scale = 1.0 / factor as f32;
+        render.draw_pixmap(
+            0,
+            0,
+            pixmap.as_ref(),
+            &paint,
+            Transform::from_scale(scale, scale),
+            None,
+        );
+
+        Ok(SkiaRender {
+            pixmap: render,
+            transparent,
+        })
+    }
+}
+
+/// Represents a model rendered by a `SkiaBackend`.
+pub struct SkiaRender {
+    pixmap: Pixmap,
+    transparent: bool,
+}
+
+impl SkiaRender {
+    /// Returns the render's width in pixels.
+    pub fn width(&self) -> i32 {
+        self.pixmap.width() as i32
+    }
+
+    /// Returns the render's height in pixels.
+    pub fn height(&self) -> i32 {
+        self.pixmap.height() as i32
+    }
+
+    /// Returns the render's pixels as 8-bit RGBA, row by row from the top, as
+    /// `Render::as_rgba8` does.
+    pub fn as_rgba8(&self) -> Vec<u8> {
+        self.pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect()
+    }
+
+    /// Writes the render as a PNG file to path, with an alpha channel only if
+    /// its background was left transparent.
+    pub fn write_to_png<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.pixmap.width(), self.pixmap.height());
+        encoder.set_depth(png::BitDepth::Eight);
+        let rgba = self.as_rgba8();
+        let data = if self.transparent {
+            encoder.set_color(png::ColorType::RGBA);
+            rgba
+        } else {
+            encoder.set_color(png::ColorType::RGB);
+            rgba.chunks(4).flat_map(|p| [p[0], p[1], p[2]]).collect()
+        };
+        encoder.write_header()?.write_image_data(&data)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{Point, Shape};
+
+#[cfg(feature = "cairo")]
+use crate::{render, Color, Dual, Polygon, Render, RenderOptions, Result, Warning};
+
+/// An immutable snapshot of a model's tiling produced by `Model::freeze`.
+/// A tiling holds the placed shapes and the geometry of its dual, so it may be
+/// rendered repeatedly (at different sizes and in different colors) without
+/// repeating the model.
+/// Only the region the model was repeated over is covered, so rendering at a
+/// lower scale than the model's may reveal the tiling's edges.
+/// A tiling is `Send` and `Sync`, so it may be shared between threads and
+/// rendered in parallel.
+#[derive(Clone, Debug)]
+pub struct Tiling {
+    shapes: Vec<Shape>,
+    duals: Vec<Vec<Point>>,
+}
+
+impl Tiling {
+    /// Returns a new tiling.
+    pub(crate) fn new(shapes: Vec<Shape>, duals: Vec<Vec<Point>>) -> Tiling {
+        Tiling { shapes, duals }
+    }
+
+    /// Returns the tiling's shapes.
+    pub fn shapes(&self) -> &[Shape] {
+        &self.shapes
+    }
+
+    /// Returns the points of each polygon in the tiling's dual.
+    pub fn duals(&self) -> &[Vec<Point>] {
+        &self.duals
+    }
+
+    /// Renders the tiling onto a width by height canvas, leaving the background
+    /// transparent if it is `None`.
+    #[cfg(feature = "cairo")]
+    pub fn render(
+        &self,
+        width: i32,
+        height: i32,
+        scale: f64,
+        background: impl Into<Option<Color>>,
+        margin: f64,
+        line_width: f64,
+    ) -> Result<Render> {
+        let options = RenderOptions::new()
+            .background(background)
+            .line_width(line_width);
+        let (surface, context) = render::canvas(width, height, scale, &options)?;
+
+        for s in self.shapes.iter() {
+            s.render(&context, s.margin().unwrap_or(margin))?;
+        }
+
+        Ok(Render::new(surface))
+    }
+
+    /// Renders the tiling's dual onto a width by height canvas, leaving the
+    /// background transparent if it is `None`.
+    #[cfg(feature = "cairo")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_dual(
+        &self,
+        width: i32,
+        height: i32,
+        scale: f64,
+        background: impl Into<Option<Color>>,
+        fill: Color,
+        stroke: Color,
+        margin: f64,
+        line_width: f64,
+    ) -> Result<Render> {
+        let options = RenderOptions::new()
+            .background(background)
+            .line_width(line_width);
+        let (surface, context) = render::canvas(width, height, scale, &options)?;
+
+        let mut warnings = Vec::new();
+        for (index, points) in self.duals.iter().enumerate() {
+            let dual = Dual::new(points.clone(), fill, stroke);
+            if dual.collapses(margin)? {
+                warnings.push(Warning::CollapsedCell { index, margin });
+                continue;
+            }
+
+            dual.render(&context, margin)?;
+        }
+
+        Ok(Render::new(surface).with_warnings(warnings))
+    }
+}
@@ -0,0 +1,399 @@
+use std::cmp::Ordering::Less;
+
+use crate::{Color, Error::*, Result};
+
+#[cfg(feature = "cairo")]
+use crate::{render, shape, Point, Render, RenderOptions};
+
+/// The golden ratio, which places the vertices of the icosahedron and
+/// dodecahedron.
+const PHI: f64 = 1.618_033_988_749_895;
+
+/// The number of great circle segments used to draw each edge.
+#[cfg(feature = "cairo")]
+const ARC_SEGMENTS: usize = 16;
+
+/// The tolerance used when matching vertices to faces.
+const EPSILON: f64 = 1e-9;
+
+/// A point on (or direction from the center of) the unit sphere.
+type Vector = [f64; 3];
+
+/// How a spherical tiling is projected onto the canvas.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Projection {
+    /// Views the sphere from far along the z axis, drawing only the parts of
+    /// faces on the near hemisphere.
+    #[default]
+    Orthographic,
+    /// Projects the sphere onto the plane of its equator from the center of
+    /// the face farthest from the viewer, which preserves angles; that face
+    /// is left as background.
+    /// The sphere is first turned (as little as it takes) to bring the
+    /// face's center to the far pole, so no vertex lies at the point
+    /// projected from, which would be sent to infinity.
+    Stereographic,
+}
+
+/// A tiling of the sphere by regular polygons: the spherical form of a
+/// Platonic solid.
+#[derive(Clone, Debug)]
+pub struct Polyhedron {
+    p: i32,
+    q: i32,
+    faces: Vec<Vec<Vector>>,
+    #[cfg_attr(not(feature = "cairo"), allow(dead_code))]
+    fill: Color,
+    #[cfg_attr(not(feature = "cairo"), allow(dead_code))]
+    stroke: Color,
+}
+
+impl Polyhedron {
+    /// Returns the tiling with Schläfli symbol {p, q}: p-sided faces, q of which
+    /// meet at every vertex.
+    /// The tilings are {3, 3} (tetrahedron), {3, 4} (octahedron), {4, 3}
+    /// (cube), {3, 5} (icosahedron), and {5, 3} (dodecahedron).
+    pub fn new(p: i32, q: i32, fill: Color, stroke: Color) -> Result<Polyhedron> {
+        let normals = match (p, q) {
+            (3, 3) => vertices(3, 3).iter().map(|v| scale(*v, -1.0)).collect(),
+            _ => vertices(q, p),
+        };
+        let vertices = vertices(p, q);
+        if vertices.is_empty() {
+            return Err(InvalidShape {
+                reason: format!("{{{}, {}}} is not a tiling of the sphere", p, q),
+            });
+        }
+
+        let faces = normals
+            .iter()
+            .map(|n| {
+                let nearest = vertices
+                    .iter()
+                    .map(|v| dot(*v, *n))
+                    .fold(f64::MIN, f64::max);
+                let mut face = vertices
+                    .iter()
+                    .filter(|v| dot(**v, *n) > nearest - EPSILON)
+                    .copied()
+                    .collect::<Vec<Vector>>();
+                let (u, w) = basis(*n);
+                let angle = |v: &Vector| dot(*v, w).atan2(dot(*v, u));
+                face.sort_by(|a, b| angle(a).partial_cmp(&angle(b)).unwrap_or(Less));
+
+                face
+            })
+            .collect();
+
+        Ok(Polyhedron {
+            p,
+            q,
+            faces,
+            fill,
+            stroke,
+        })
+    }
+
+    /// Returns the tiling's Schläfli symbol {p, q}.
+    pub fn schlafli(&self) -> (i32, i32) {
+        (self.p, self.q)
+    }
+
+    /// Returns the vertices of each of the tiling's faces, which lie on the
+    /// unit sphere.
+    pub fn faces(&self) -> &[Vec<[f64; 3]>] {
+        &self.faces
+    }
+
+    /// Returns the tiling turned by yaw (in radians) about the vertical axis,
+    /// and then by pitch (in radians) about the horizontal axis.
+    pub fn rotated(&self, yaw: f64, pitch: f64) -> Polyhedron {
+        let (sy, cy) = yaw.sin_cos();
+        let (sp, cp) = pitch.sin_cos();
+        let rotate = |[x, y, z]: Vector| {
+            let (x, z) = (x * cy + z * sy, z * cy - x * sy);
+            let (y, z) = (y * cp - z * sp, y * sp + z * cp);
+
+            [x, y, z]
+        };
+
+        let mut polyhedron = self.clone();
+        for face in polyhedron.faces.iter_mut() {
+            for v in face.iter_mut() {
+                *v = rotate(*v);
+            }
+        }
+
+        polyhedron
+    }
+
+    /// Renders the tiling onto a width by height canvas, with scale pixels per
+    /// unit of the sphere's radius, as configured by options.
+    /// The background, line width, and paint mode of options are applied.
+    #[cfg(feature = "cairo")]
+    pub fn render(
+        &self,
+        width: i32,
+        height: i32,
+        scale: f64,
+        projection: Projection,
+        options: &RenderOptions,
+    ) -> Result<Render> {
+        let (surface, context) = render::canvas(width, height, scale, options)?;
+        for points in self.projected(projection) {
+            shape::render(&context, &points, self.fill, self.stroke, options.paint)?;
+        }
+
+        render::finish(surface, options)
+    }
+
+    /// Returns the closed outline of each face drawn by projection, farthest
+    /// first.
+    #[cfg(feature = "cairo")]
+    fn projected(&self, projection: Projection) -> Vec<Vec<Point>> {
+        let mut faces = self
+            .faces
+            .iter()
+            .map(|face| (face, dot(centroid(face), [0.0, 0.0, 1.0])))
+            .collect::<Vec<(&Vec<Vector>, f64)>>();
+        faces.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Less));
+
+        match projection {
+            Projection::Orthographic => faces
+                .iter()
+                .map(|(face, _)| hemisphere(&arcs(face)))
+                .filter(|near| !near.is_empty())
+                .map(|near| near.iter().map(|v| orthographic(*v)).collect())
+                .collect(),
+            Projection::Stereographic => {
+                let far = match faces.first() {
+                    Some((face, _)) => centroid(face),
+                    None => return Vec::new(),
+                };
+                faces
+                    .iter()
+                    .skip(1)
+                    .map(|(face, _)| {
+                        arcs(face)
+                            .iter()
+                            .map(|v| stereographic(turn(*v, far, [0.0, 0.0, -1.0])))
+                            .collect()
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Returns the vertices on the unit sphere of the Platonic solid {p, q}, or
+/// none if there is no such solid.
+fn vertices(p: i32, q: i32) -> Vec<Vector> {
+    let signs = |v: Vector| {
+        let mut vs = vec![v];
+        for axis in 0..3 {
+            if v[axis] != 0.0 {
+                let flipped = vs
+                    .iter()
+                    .map(|u| {
+                        let mut u = *u;
+                        u[axis] = -u[axis];
+                        u
+                    })
+                    .collect::<Vec<Vector>>();
+                vs.extend(flipped);
+            }
+        }
+
+        vs
+    };
+    let cycles = |[x, y, z]: Vector| {
+        signs([x, y, z])
+            .into_iter()
+            .chain(signs([y, z, x]))
+            .chain(signs([z, x, y]))
+            .collect::<Vec<Vector>>()
+    };
+
+    let vs = match (p, q) {
+        (3, 3) => vec![
+            [1.0, 1.0, 1.0],
+            [1.0, -1.0, -1.0],
+            [-1.0, 1.0, -1.0],
+            [-1.0, -1.0, 1.0],
+        ],
+        (3, 4) => cycles([1.0, 0.0, 0.0]),
+        (4, 3) => signs([1.0, 1.0, 1.0]),
+        (3, 5) => cycles([0.0, 1.0, PHI]),
+        (5, 3) => signs([1.0, 1.0, 1.0])
+            .into_iter()
+            .chain(cycles([0.0, PHI, 1.0 / PHI]))
+            .collect(),
+        _ => vec![],
+    };
+
+    vs.into_iter().map(normalize).collect()
+}
+
+/// Returns the closed outline of face, with each edge divided into
+/// `ARC_SEGMENTS` segments along its great circle.
+#[cfg(feature = "cairo")]
+fn arcs(face: &[Vector]) -> Vec<Vector> {
+    let mut vs = Vec::new();
+    for (i, a) in face.iter().enumerate() {
+        let b = face[(i + 1) % face.len()];
+        let theta = dot(*a, b).clamp(-1.0, 1.0).acos();
+        for k in 0..ARC_SEGMENTS {
+            let t = k as f64 / ARC_SEGMENTS as f64;
+            let (wa, wb) = (((1.0 - t) * theta).sin(), (t * theta).sin());
+            vs.push(normalize(add(scale(*a, wa), scale(b, wb))));
+        }
+    }
+    vs.push(vs[0]);
+
+    vs
+}
+
+/// Returns the part of the closed outline vs on the near (z ≥ 0) hemisphere,
+/// joining the points where it crosses the equator along the equator.
+#[cfg(feature = "cairo")]
+fn hemisphere(vs: &[Vector]) -> Vec<Vector> {
+    let n = vs.len() - 1;
+    let start = match vs[..n].iter().position(|v| v[2] >= 0.0) {
+        Some(start) => start,
+        None => return Vec::new(),
+    };
+    let crossing = |a: Vector, b: Vector| {
+        let t = a[2] / (a[2] - b[2]);
+
+        normalize(add(scale(a, 1.0 - t), scale(b, t)))
+    };
+
+    let mut near = Vec::new();
+    let mut exit = [0.0; 3];
+    for i in start..start + n {
+        let (a, b) = (vs[i % n], vs[(i + 1) % n]);
+        if a[2] >= 0.0 {
+            near.push(a);
+            if b[2] < 0.0 {
+                exit = crossing(a, b);
+                near.push(exit);
+            }
+        } else if b[2] >= 0.0 {
+            let entry = crossing(a, b);
+            for k in 1..ARC_SEGMENTS {
+                let t = k as f64 / ARC_SEGMENTS as f64;
+                near.push(normalize(add(scale(exit, 1.0 - t), scale(entry, t))));
+            }
+            near.push(entry);
+        }
+    }
+    near.push(near[0]);
+
+    near
+}
+
+/// Returns v projected orthographically onto the plane of the equator.
+#[cfg(feature = "cairo")]
+fn orthographic([x, y, _]: Vector) -> Point {
+    Point { x, y }
+}
+
+/// Returns v projected from the far pole onto the plane of the equator.
+#[cfg(feature = "cairo")]
+fn stereographic([x, y, z]: Vector) -> Point {
+    Point {
+        x: x / (1.0 + z),
+        y: y / (1.0 + z),
+    }
+}
+
+/// Returns v turned about the axis perpendicular to the unit vectors from and
+/// to by the angle that turns from onto to.
+#[cfg(feature = "cairo")]
+fn turn(v: Vector, from: Vector, to: Vector) -> Vector {
+    let axis = cross(from, to);
+    let sin = dot(axis, axis).sqrt();
+    if sin < EPSILON {
+        return v;
+    }
+
+    let (k, cos) = (scale(axis, 1.0 / sin), dot(from, to));
+    add(
+        add(scale(v, cos), scale(cross(k, v), sin)),
+        scale(k, dot(k, v) * (1.0 - cos)),
+    )
+}
+
+/// Returns two unit vectors perpendicular to n and each other.
+fn basis(n: Vector) -> (Vector, Vector) {
+    let a = if n[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    let u = normalize(cross(n, a));
+
+    (u, cross(n, u))
+}
+
+/// Returns the direction of the mean of face's vertices.
+#[cfg(feature = "cairo")]
+fn centroid(face: &[Vector]) -> Vector {
+    normalize(face.iter().fold([0.0; 3], |sum, v| add(sum, *v)))
+}
+
+/// Returns the sum of a and b.
+#[cfg(feature = "cairo")]
+fn add(a: Vector, b: Vector) -> Vector {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+/// Returns v scaled by s.
+fn scale(v: Vector, s: f64) -> Vector {
+    [v[0] * s, v[1] * s, v[2] * s]
+}
+
+/// Returns the dot product of a and b.
+fn dot(a: Vector, b: Vector) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Returns the cross product of a and b.
+fn cross(a: Vector, b: Vector) -> Vector {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Returns v scaled to unit length.
+fn normalize(v: Vector) -> Vector {
+    scale(v, 1.0 / dot(v, v).sqrt())
+}
+
+#[cfg(all(test, feature = "cairo"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stereographic_octahedron_is_finite() -> Result<()> {
+        // A vertex of the octahedron lies at the far pole.
+        let color = Color::new(0, 0, 0)?;
+        let octahedron = Polyhedron::new(3, 4, color, color)?;
+        assert!(octahedron
+            .faces()
+            .iter()
+            .flatten()
+            .any(|v| (v[2] + 1.0).abs() < EPSILON));
+
+        let faces = octahedron.projected(Projection::Stereographic);
+        assert_eq!(faces.len(), 7);
+        assert!(faces
+            .iter()
+            .flatten()
+            .all(|p| p.x.is_finite() && p.y.is_finite()));
+
+        Ok(())
+    }
+}
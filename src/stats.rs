@@ -0,0 +1,155 @@
+use std::{collections::BTreeMap, f64::consts::PI, iter};
+
+use crate::{Point, Shape};
+
+/// The tolerance to use when testing whether a vertex's angles close.
+const ANGLE_EPSILON: f64 = 1e-6;
+
+/// Summary statistics of a tiling.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    shapes: BTreeMap<i32, usize>,
+    vertex_configurations: BTreeMap<String, usize>,
+    area: f64,
+}
+
+impl Stats {
+    /// Returns new statistics.
+    pub(crate) fn new(
+        shapes: BTreeMap<i32, usize>,
+        vertex_configurations: BTreeMap<String, usize>,
+        area: f64,
+    ) -> Stats {
+        Stats {
+            shapes,
+            vertex_configurations,
+            area,
+        }
+    }
+
+    /// Returns the number of shapes keyed by their number of sides.
+    pub fn shapes(&self) -> &BTreeMap<i32, usize> {
+        &self.shapes
+    }
+
+    /// Returns the number of complete vertices keyed by their vertex
+    /// configuration (e.g. `"3.4.6.4"`).
+    /// Vertices on the boundary of the tiling, which are not fully surrounded by
+    /// shapes, are not counted.
+    pub fn vertex_configurations(&self) -> &BTreeMap<String, usize> {
+        &self.vertex_configurations
+    }
+
+    /// Returns the number of distinct vertex configurations.
+    pub fn vertex_types(&self) -> usize {
+        self.vertex_configurations.len()
+    }
+
+    /// Returns the total area covered by shapes.
+    pub fn area(&self) -> f64 {
+        self.area
+    }
+}
+
+/// A vertex of a tiling and the shapes that meet at it.
+#[derive(Clone, Debug)]
+pub struct VertexFigure {
+    point: Point,
+    configuration: String,
+    angle: f64,
+}
+
+impl VertexFigure {
+    /// Returns the figure of the vertex at point surrounded by shapes (sorted
+    /// around the vertex) with a corner there, and by crossing shapes with an
+    /// edge that passes through it, each of which takes up a straight angle.
+    pub(crate) fn new(point: Point, shapes: &[Shape], crossing: usize) -> VertexFigure {
+        VertexFigure {
+            point,
+            configuration: canonical(shapes),
+            angle: shapes.iter().map(|s| s.vertex_angle()).sum::<f64>() + crossing as f64 * PI,
+        }
+    }
+
+    /// Returns the vertex's position.
+    pub fn point(&self) -> Point {
+        self.point
+    }
+
+    /// Returns the vertex's configuration (e.g. `"3.4.6.4"`), written in its
+    /// canonical form.
+    pub fn configuration(&self) -> &str {
+        &self.configuration
+    }
+
+    /// Returns the sum of the interior angles (in radians) of the shapes that
+    /// meet at the vertex, counting a straight angle for each shape whose edge
+    /// passes through it.
+    pub fn angle(&self) -> f64 {
+        self.angle
+    }
+
+    /// Returns true if the shapes that meet at the vertex close it, with their
+    /// angles summing to 360°.
+    /// A vertex that does not close has a gap (or an overlap) between shapes.
+    pub fn closes(&self) -> bool {
+        (self.angle - 2.0 * PI).abs() <= ANGLE_EPSILON
+    }
+}
+
+/// Returns the vertex configuration of a vertex surrounded by shapes (sorted
+/// around the vertex), or `None` if the shapes do not close the vertex.
+pub(crate) fn vertex_configuration(shapes: &[Shape]) -> Option<String> {
+    let total = shapes.iter().map(|s| s.vertex_angle()).sum::<f64>();
+    if (total - 2.0 * PI).abs() > ANGLE_EPSILON {
+        return None;
+    }
+
+    Some(canonical(shapes))
+}
+
+/// Returns the configuration of shapes (sorted around a vertex) in its
+/// canonical form.
+fn canonical(shapes: &[Shape]) -> String {
+    canonical_sides(&shapes.iter().map(|s| s.sides()).collect::<Vec<i32>>())
+}
+
+/// Returns the configuration written as configuration (e.g. `"3.4.6.4"`, or
+/// `"3^2.4.3.4"` with repeated sides as exponents) in its canonical form, or
+/// `None` if it is not a list of polygons' sides.
+pub(crate) fn parse_configuration(configuration: &str) -> Option<String> {
+    let mut sides = Vec::new();
+    for term in configuration.trim().split('.') {
+        let (n, count) = match term.split_once('^') {
+            Some((n, count)) => (n, count.trim().parse::<usize>().ok()?),
+            None => (term, 1),
+        };
+        let n = n.trim().parse::<i32>().ok()?;
+        if n < 3 || count == 0 {
+            return None;
+        }
+        sides.extend(iter::repeat(n).take(count));
+    }
+
+    Some(canonical_sides(&sides))
+}
+
+/// Returns the sequence of sides of the polygons around a vertex in its
+/// canonical form: the lexicographically smallest rotation or reflection of
+/// the sequence, joined by dots.
+fn canonical_sides(sides: &[i32]) -> String {
+    let reversed = sides.iter().rev().copied().collect::<Vec<i32>>();
+    let canonical = (0..sides.len())
+        .flat_map(|i| {
+            let rotate = |v: &[i32]| [&v[i..], &v[..i]].concat();
+            vec![rotate(sides), rotate(&reversed)]
+        })
+        .min()
+        .unwrap_or_default();
+
+    canonical
+        .iter()
+        .map(|s| s.to_string())
+        .collect::<Vec<String>>()
+        .join(".")
+}
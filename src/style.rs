@@ -0,0 +1,639 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+#[cfg(feature = "cairo")]
+use std::{f64::consts::PI, fmt, sync::Arc};
+
+use crate::{Color, Shape};
+
+#[cfg(feature = "cairo")]
+use crate::{Point, Rect, Result};
+
+/// A function that draws a custom motif, called with the render context in
+/// the frame of a shape (see `Motif`) and the shape.
+#[cfg(feature = "cairo")]
+pub type MotifFn = Arc<dyn Fn(&cairo::Context, &Shape) -> Result<()> + Send + Sync>;
+
+/// The style of a single polygon edge.
+/// The default style draws the edge as a solid line in the polygon's stroke.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EdgeStyle {
+    hidden: bool,
+    color: Option<Color>,
+    dashes: Option<(f64, f64)>,
+}
+
+impl EdgeStyle {
+    /// Returns a style that draws the edge as a solid line.
+    pub fn solid() -> EdgeStyle {
+        EdgeStyle::default()
+    }
+
+    /// Returns a style that does not draw the edge.
+    pub fn hidden() -> EdgeStyle {
+        EdgeStyle {
+            hidden: true,
+            ..EdgeStyle::default()
+        }
+    }
+
+    /// Returns a style that draws the edge as a dashed line with dashes of length
+    /// on separated by gaps of length off.
+    /// Lengths are in model units, where a polygon's side has length 1.
+    pub fn dashed(on: f64, off: f64) -> EdgeStyle {
+        EdgeStyle {
+            dashes: Some((on, off)),
+            ..EdgeStyle::default()
+        }
+    }
+
+    /// Returns the style with the edge drawn in color instead of the polygon's
+    /// stroke.
+    pub fn color(mut self, color: Color) -> EdgeStyle {
+        self.color = Some(color);
+
+        self
+    }
+
+    /// Returns true if the edge is not drawn.
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    /// Returns the edge's color, if it differs from the polygon's stroke.
+    pub fn stroke(&self) -> Option<Color> {
+        self.color
+    }
+
+    /// Returns the edge's dash and gap lengths, if the edge is dashed.
+    pub fn dashes(&self) -> Option<(f64, f64)> {
+        self.dashes
+    }
+}
+
+/// A second, wider stroke drawn beneath a polygon's stroke, so that the
+/// stroke becomes a thin inner line within an outline of another color, as in
+/// the interlaced bands of Islamic geometric patterns.
+/// Outlines are drawn after every polygon is filled and before any polygon is
+/// stroked, so neighboring polygons cover neither.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Outline {
+    width: f64,
+    color: Color,
+}
+
+impl Outline {
+    /// Returns an outline width wide (in model units, like a render's line
+    /// width) drawn in color.
+    pub fn new(width: f64, color: Color) -> Outline {
+        Outline { width, color }
+    }
+
+    /// Returns the outline's width.
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    /// Returns the outline's color.
+    pub fn color(&self) -> Color {
+        self.color
+    }
+}
+
+/// The style of a rendered polygon's corners.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Corner {
+    /// Sharp corners (the default).
+    #[default]
+    Miter,
+    /// Corners rounded with the given radius.
+    Round(f64),
+    /// Corners cut off the given distance from the vertex along each edge.
+    Bevel(f64),
+}
+
+/// Which parts of a polygon are painted when it is rendered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PaintMode {
+    /// Fill the polygon and stroke its outline (the default).
+    #[default]
+    FillAndStroke,
+    /// Fill the polygon without stroking its outline.
+    Fill,
+    /// Stroke the polygon's outline without filling it, leaving the background
+    /// visible inside the outline.
+    Stroke,
+}
+
+impl PaintMode {
+    /// Returns true if the polygon is filled.
+    pub fn fills(&self) -> bool {
+        *self != PaintMode::Stroke
+    }
+
+    /// Returns true if the polygon's outline is stroked.
+    pub fn strokes(&self) -> bool {
+        *self != PaintMode::Fill
+    }
+}
+
+/// How a polygon's paint is composited with what was drawn beneath it, so
+/// overlapping shapes of layered models mix instead of the last drawn
+/// covering the rest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Blend {
+    /// Paint over what is beneath (the default).
+    #[default]
+    Normal,
+    /// Multiply the colors, which darkens where shapes overlap.
+    Multiply,
+    /// Invert, multiply, and invert the colors, which lightens where shapes
+    /// overlap.
+    Screen,
+    /// Multiply dark colors beneath and screen light ones, which keeps the
+    /// contrast of what is beneath.
+    Overlay,
+    /// Keep the darker of the colors.
+    Darken,
+    /// Keep the lighter of the colors.
+    Lighten,
+}
+
+impl Blend {
+    /// Returns the cairo operator that composites as the blend mode does.
+    #[cfg(feature = "cairo")]
+    pub(crate) fn operator(self) -> cairo::Operator {
+        match self {
+            Blend::Normal => cairo::Operator::Over,
+            Blend::Multiply => cairo::Operator::Multiply,
+            Blend::Screen => cairo::Operator::Screen,
+            Blend::Overlay => cairo::Operator::Overlay,
+            Blend::Darken => cairo::Operator::Darken,
+            Blend::Lighten => cairo::Operator::Lighten,
+        }
+    }
+
+    /// Returns the CSS `mix-blend-mode` that composites as the blend mode
+    /// does.
+    pub(crate) fn css(self) -> &'static str {
+        match self {
+            Blend::Normal => "normal",
+            Blend::Multiply => "multiply",
+            Blend::Screen => "screen",
+            Blend::Overlay => "overlay",
+            Blend::Darken => "darken",
+            Blend::Lighten => "lighten",
+        }
+    }
+}
+
+/// How the ends of open strokes, such as dashes, are drawn.
+/// Cairo's `LineCap` converts into it, and it into cairo's.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// End the stroke exactly at its end point.
+    Butt,
+    /// End the stroke with a half circle around its end point (the default).
+    #[default]
+    Round,
+    /// End the stroke with a half square around its end point.
+    Square,
+}
+
+#[cfg(feature = "cairo")]
+impl From<cairo::LineCap> for LineCap {
+    fn from(cap: cairo::LineCap) -> LineCap {
+        match cap {
+            cairo::LineCap::Butt => LineCap::Butt,
+            cairo::LineCap::Square => LineCap::Square,
+            _ => LineCap::Round,
+        }
+    }
+}
+
+#[cfg(feature = "cairo")]
+impl From<LineCap> for cairo::LineCap {
+    fn from(cap: LineCap) -> cairo::LineCap {
+        match cap {
+            LineCap::Butt => cairo::LineCap::Butt,
+            LineCap::Round => cairo::LineCap::Round,
+            LineCap::Square => cairo::LineCap::Square,
+        }
+    }
+}
+
+/// How strokes are joined at the corners of polygons.
+/// Cairo's `LineJoin` converts into it, and it into cairo's.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Sharp corners, beveled past the miter limit.
+    Miter,
+    /// Rounded corners (the default).
+    #[default]
+    Round,
+    /// Corners cut off at half the line width from the vertex.
+    Bevel,
+}
+
+#[cfg(feature = "cairo")]
+impl From<cairo::LineJoin> for LineJoin {
+    fn from(join: cairo::LineJoin) -> LineJoin {
+        match join {
+            cairo::LineJoin::Miter => LineJoin::Miter,
+            cairo::LineJoin::Bevel => LineJoin::Bevel,
+            _ => LineJoin::Round,
+        }
+    }
+}
+
+#[cfg(feature = "cairo")]
+impl From<LineJoin> for cairo::LineJoin {
+    fn from(join: LineJoin) -> cairo::LineJoin {
+        match join {
+            LineJoin::Miter => cairo::LineJoin::Miter,
+            LineJoin::Round => cairo::LineJoin::Round,
+            LineJoin::Bevel => cairo::LineJoin::Bevel,
+        }
+    }
+}
+
+/// How the edges of polygons are antialiased when they are rasterized.
+/// Cairo's `Antialias` converts into it, and it into cairo's; backends
+/// other than cairo antialias unless it is `None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Antialias {
+    /// The backend's default antialiasing (the default).
+    #[default]
+    Default,
+    /// No antialiasing, for hard pixel edges.
+    None,
+    /// Single-color antialiasing.
+    Gray,
+    /// Antialiasing that uses the order of a display's subpixels.
+    Subpixel,
+    /// Some antialiasing, preferring speed over quality.
+    Fast,
+    /// Antialiasing that balances speed and quality.
+    Good,
+    /// The best antialiasing, preferring quality over speed.
+    Best,
+}
+
+#[cfg(feature = "cairo")]
+impl From<cairo::Antialias> for Antialias {
+    fn from(antialias: cairo::Antialias) -> Antialias {
+        match antialias {
+            cairo::Antialias::None => Antialias::None,
+            cairo::Antialias::Gray => Antialias::Gray,
+            cairo::Antialias::Subpixel => Antialias::Subpixel,
+            cairo::Antialias::Fast => Antialias::Fast,
+            cairo::Antialias::Good => Antialias::Good,
+            cairo::Antialias::Best => Antialias::Best,
+            _ => Antialias::Default,
+        }
+    }
+}
+
+#[cfg(feature = "cairo")]
+impl From<Antialias> for cairo::Antialias {
+    fn from(antialias: Antialias) -> cairo::Antialias {
+        match antialias {
+            Antialias::Default => cairo::Antialias::Default,
+            Antialias::None => cairo::Antialias::None,
+            Antialias::Gray => cairo::Antialias::Gray,
+            Antialias::Subpixel => cairo::Antialias::Subpixel,
+            Antialias::Fast => cairo::Antialias::Fast,
+            Antialias::Good => cairo::Antialias::Good,
+            Antialias::Best => cairo::Antialias::Best,
+        }
+    }
+}
+
+/// What the interior of a shape is painted with.
+#[derive(Clone, Debug)]
+pub enum Fill {
+    /// A solid color.
+    Color(Color),
+    /// The PNG image at a path, fitted to the shape by a fit and clipped to
+    /// it, over the shape's fill.
+    Image(PathBuf, Fit),
+    /// A texture drawn in an ink color over the shape's fill, which prints
+    /// legibly in black and white.
+    Texture(Texture, Color),
+}
+
+/// A procedural texture that fills shapes, drawn with a line half the
+/// render's line width.
+/// Textures are anchored at the origin, so they run on unbroken from shape to
+/// shape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Texture {
+    /// Parallel lines spacing apart (in model units) at angle (in radians)
+    /// from the x axis.
+    Hatch { angle: f64, spacing: f64 },
+    /// Hatching crossed by a second set of lines at right angles to it.
+    CrossHatch { angle: f64, spacing: f64 },
+    /// Dots of radius (in model units) on a square grid spacing apart.
+    Dots { spacing: f64, radius: f64 },
+}
+
+#[cfg(feature = "cairo")]
+impl Texture {
+    /// Paints the texture in ink within context's clip.
+    /// The texture's lines and dots are drawn as paths, which keeps them
+    /// vector in PDF and SVG output.
+    pub(crate) fn paint(&self, context: &cairo::Context, ink: Color) -> Result<()> {
+        let (spacing, angle) = match *self {
+            Texture::Hatch { angle, spacing } | Texture::CrossHatch { angle, spacing } => {
+                (spacing, angle)
+            }
+            Texture::Dots { spacing, .. } => (spacing, 0.0),
+        };
+        if spacing <= 0.0 || spacing.is_nan() {
+            return Ok(());
+        }
+
+        context.save()?;
+        context.rotate(angle);
+        // The clip's extents in the rotated space bound the rows and columns
+        // that can show.
+        let (x0, y0, x1, y1) = context.clip_extents()?;
+        let rows = (y0 / spacing).floor() as i64..=(y1 / spacing).ceil() as i64;
+        let columns = (x0 / spacing).floor() as i64..=(x1 / spacing).ceil() as i64;
+        let (r, g, b) = ink.rgb_unit_int();
+        context.set_source_rgb(r, g, b);
+        context.set_line_width(context.line_width() / 2.0);
+        match *self {
+            Texture::Hatch { .. } => {
+                for row in rows {
+                    context.move_to(x0, row as f64 * spacing);
+                    context.line_to(x1, row as f64 * spacing);
+                }
+                context.stroke()?;
+            }
+            Texture::CrossHatch { .. } => {
+                for row in rows {
+                    context.move_to(x0, row as f64 * spacing);
+                    context.line_to(x1, row as f64 * spacing);
+                }
+                for column in columns {
+                    context.move_to(column as f64 * spacing, y0);
+                    context.line_to(column as f64 * spacing, y1);
+                }
+                context.stroke()?;
+            }
+            Texture::Dots { radius, .. } => {
+                for row in rows {
+                    for column in columns.clone() {
+                        context.new_sub_path();
+                        let (x, y) = (column as f64 * spacing, row as f64 * spacing);
+                        context.arc(x, y, radius, 0.0, 2.0 * PI);
+                    }
+                }
+                context.fill()?;
+            }
+        }
+        context.restore()?;
+
+        Ok(())
+    }
+}
+
+/// How an image that fills shapes is scaled and placed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fit {
+    /// Each shape shows the image scaled to cover the square around the
+    /// shape's circumcircle, centered on the shape, with the parts of a
+    /// non-square image that overflow the square cropped (the default).
+    #[default]
+    Cover,
+    /// Each shape shows the whole image scaled to fit within the square
+    /// around the shape's circumcircle, centered on the shape.
+    Contain,
+    /// A single copy of the image covers the canvas, and each shape shows the
+    /// part of it beneath the shape, as in a photo mosaic.
+    Canvas,
+}
+
+#[cfg(feature = "cairo")]
+impl Fit {
+    /// Paints image within context's clip, scaled to bounds (in model units)
+    /// as the fit requires, centered on bounds, and moved by offset.
+    pub(crate) fn paint(
+        &self,
+        context: &cairo::Context,
+        image: &cairo::ImageSurface,
+        bounds: Rect,
+        offset: Point,
+    ) -> Result<()> {
+        let (width, height) = (image.width() as f64, image.height() as f64);
+        if width == 0.0 || height == 0.0 {
+            return Ok(());
+        }
+
+        let (sx, sy) = (bounds.width() / width, bounds.height() / height);
+        let scale = match self {
+            Fit::Cover | Fit::Canvas => sx.max(sy),
+            Fit::Contain => sx.min(sy),
+        };
+        context.save()?;
+        context.translate(
+            bounds.origin().x + bounds.width() / 2.0 + offset.x,
+            bounds.origin().y + bounds.height() / 2.0 + offset.y,
+        );
+        context.scale(scale, scale);
+        context.set_source_surface(image, -width / 2.0, -height / 2.0)?;
+        context.paint()?;
+        context.restore()?;
+
+        Ok(())
+    }
+}
+
+/// A decoration drawn inside every shape of a render, such as the arcs of a
+/// Truchet tiling.
+/// Motifs are drawn in each shape's own frame: centered on the shape, rotated
+/// and transformed with it, and scaled so that the unit circle is the shape's
+/// incircle (inset by the render's margin), so one motif fits shapes of every
+/// size and orientation.
+/// Motifs draw with cairo, so they need the `cairo` feature.
+#[cfg(feature = "cairo")]
+#[derive(Clone)]
+pub enum Motif {
+    /// The shape's incircle, filled with color.
+    InscribedCircle { color: Color },
+    /// A regular polygon with as many sides as the shape, aligned with it and
+    /// scale times its size, filled with color.
+    InscribedPolygon { scale: f64, color: Color },
+    /// A motif drawn by a function, which paints whatever it draws itself.
+    /// Line widths are in the shape's frame, so a width of 0.1 is a tenth of
+    /// the shape's inradius.
+    Custom(MotifFn),
+}
+
+#[cfg(feature = "cairo")]
+impl Motif {
+    /// Draws the motif inside shape, which is inset by margin.
+    pub(crate) fn render(
+        &self,
+        context: &cairo::Context,
+        shape: &Shape,
+        margin: f64,
+    ) -> Result<()> {
+        let radius = shape.inradius() - margin;
+        if radius <= 0.0 {
+            return Ok(());
+        }
+
+        context.save()?;
+        context.translate(shape.point().x, shape.point().y);
+        if let Some(t) = shape.transform() {
+            let (xx, xy, yx, yy) = t.components();
+            context.transform(cairo::Matrix::new(xx, yx, xy, yy, 0.0, 0.0));
+        }
+        context.rotate(shape.rotation());
+        context.scale(radius, radius);
+        context.new_path();
+        match self {
+            Motif::InscribedCircle { color } => {
+                let (r, g, b) = color.rgb_unit_int();
+                context.set_source_rgb(r, g, b);
+                context.arc(0.0, 0.0, 1.0, 0.0, 2.0 * PI);
+                context.fill()?;
+            }
+            Motif::InscribedPolygon { scale, color } => {
+                // The shape's vertices lie on its circumcircle, whose radius
+                // is 1 / cos(PI / sides) in the shape's frame.
+                let sides = shape.sides();
+                let d = scale / (PI / sides as f64).cos();
+                for i in 0..sides {
+                    let a = i as f64 * 2.0 * PI / sides as f64 - PI / 2.0;
+                    context.line_to(a.cos() * d, a.sin() * d);
+                }
+                context.close_path();
+                let (r, g, b) = color.rgb_unit_int();
+                context.set_source_rgb(r, g, b);
+                context.fill()?;
+            }
+            Motif::Custom(f) => f(context, shape)?,
+        }
+        context.restore()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "cairo")]
+impl fmt::Debug for Motif {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Motif::InscribedCircle { color } => f
+                .debug_struct("InscribedCircle")
+                .field("color", color)
+                .finish(),
+            Motif::InscribedPolygon { scale, color } => f
+                .debug_struct("InscribedPolygon")
+                .field("scale", scale)
+                .field("color", color)
+                .finish(),
+            Motif::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// The fill and stroke a shape is drawn with, kept apart from its geometry
+/// so that one model can be drawn in several looks (see `StyleSet`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Style {
+    fill: Color,
+    stroke: Color,
+}
+
+impl Style {
+    /// Returns a style that fills shapes with fill and strokes them with
+    /// stroke.
+    pub fn new(fill: Color, stroke: Color) -> Style {
+        Style { fill, stroke }
+    }
+
+    /// Returns the style's fill.
+    pub fn fill(&self) -> Color {
+        self.fill
+    }
+
+    /// Returns the style's stroke.
+    pub fn stroke(&self) -> Color {
+        self.stroke
+    }
+}
+
+/// A named look for a model, such as one for print and one for a dark web
+/// page, mapping its shapes to the styles they are drawn with.
+/// Sets are added with `Model::add_style_set` and chosen when rendering with
+/// `RenderOptions::style_set`.
+/// A shape takes the style given for its index, else the style given for
+/// its number of sides, else the set's fallback, else its own fill and
+/// stroke; repeats take the style of the shape they repeat.
+#[derive(Clone, Debug, Default)]
+pub struct StyleSet {
+    shapes: HashMap<usize, Style>,
+    sides: BTreeMap<i32, Style>,
+    fallback: Option<Style>,
+}
+
+impl StyleSet {
+    /// Returns an empty set, which draws every shape in its own fill and
+    /// stroke.
+    pub fn new() -> StyleSet {
+        StyleSet::default()
+    }
+
+    /// Sets the style of the shape with index (and its repeats).
+    pub fn shape(mut self, index: usize, style: Style) -> StyleSet {
+        self.shapes.insert(index, style);
+
+        self
+    }
+
+    /// Sets the style of the shapes with sides sides.
+    pub fn sides(mut self, sides: i32, style: Style) -> StyleSet {
+        self.sides.insert(sides, style);
+
+        self
+    }
+
+    /// Sets the style of the shapes given no other style by the set.
+    pub fn fallback(mut self, style: Style) -> StyleSet {
+        self.fallback = Some(style);
+
+        self
+    }
+
+    /// Returns the styles the set gives shapes by index.
+    pub(crate) fn shape_styles(&self) -> &HashMap<usize, Style> {
+        &self.shapes
+    }
+
+    /// Returns the styles the set gives shapes by number of sides.
+    pub(crate) fn side_styles(&self) -> &BTreeMap<i32, Style> {
+        &self.sides
+    }
+
+    /// Returns the style the set gives shapes it gives no other style, if
+    /// any.
+    pub(crate) fn fallback_style(&self) -> Option<Style> {
+        self.fallback
+    }
+
+    /// Returns the style the set gives shape, the model shape with index or
+    /// one of its repeats, if any.
+    pub fn get(&self, index: usize, shape: &Shape) -> Option<Style> {
+        self.shapes
+            .get(&index)
+            .or_else(|| self.sides.get(&shape.sides()))
+            .or(self.fallback.as_ref())
+            .copied()
+    }
+}
@@ -0,0 +1,334 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{Blend, Color, LineCap, LineJoin, Point};
+
+/// A polygon to be written as an SVG element, with the colors it is filled
+/// and stroked with (if it is filled or stroked).
+pub(crate) struct Element {
+    pub(crate) id: usize,
+    pub(crate) sides: i32,
+    pub(crate) points: Vec<Point>,
+    /// The closed rings of the holes cut out of the polygon, if any.
+    pub(crate) holes: Vec<Vec<Point>>,
+    /// The polygon's outline and holes with their points snapped to pixels,
+    /// which it is stroked through instead, if strokes are snapped.
+    pub(crate) snapped: Option<Vec<Vec<Point>>>,
+    pub(crate) fill: Option<Color>,
+    pub(crate) stroke: Option<Color>,
+    /// The opacity and blend mode the element is composited with, if it is
+    /// not opaque or not blended normally.
+    pub(crate) opacity: Option<f64>,
+    pub(crate) blend: Option<Blend>,
+    /// A data attribute's name and value, which links the element to elements
+    /// of the other tiling.
+    pub(crate) link: Option<(&'static str, String)>,
+}
+
+/// The size of an SVG document, where the model's origin lies on it, and the
+/// scale from model to document units.
+pub(crate) struct Canvas {
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) origin: (f64, f64),
+    pub(crate) scale: (f64, f64),
+    pub(crate) background: Option<Color>,
+    pub(crate) line_width: f64,
+    pub(crate) line_cap: LineCap,
+    pub(crate) line_join: LineJoin,
+    pub(crate) dash: Vec<f64>,
+    pub(crate) dash_offset: f64,
+    pub(crate) miter_limit: f64,
+    /// The loops a border frames (in model units), and the border's width and
+    /// color, if the document has a border.
+    pub(crate) border: Option<(Vec<Vec<Point>>, f64, Color)>,
+}
+
+/// Returns an SVG document of shapes, then tiles, then dual, grouping shapes
+/// with the same number of sides into a layer and tiles and dual into layers
+/// of their own.
+/// Each shape layer has the id "layer-{sides}", a class and Inkscape label
+/// naming the shape (e.g. "hexagons"), and each shape has the id
+/// "shape-{id}" and classes "shape" and the shape's name (e.g. "hexagon").
+/// The tile layer has the id "tiles", and each of its tiles has the id
+/// "tile-{id}" and the class "tile". The dual layer has the id "dual", and
+/// each of its cells has the id "dual-{id}" and the class "cell".
+pub(crate) fn document(
+    canvas: &Canvas,
+    shapes: &[Element],
+    tiles: &[Element],
+    dual: &[Element],
+) -> String {
+    let mut layers: BTreeMap<i32, Vec<&Element>> = BTreeMap::new();
+    for e in shapes {
+        layers.entry(e.sides).or_default().push(e);
+    }
+
+    // Writing to a String cannot fail.
+    let mut svg = String::new();
+    let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = canvas.width,
+        h = canvas.height,
+    );
+    if let Some(background) = canvas.background {
+        let _ = writeln!(
+            svg,
+            r#"<rect id="background" width="100%" height="100%" fill="{}"/>"#,
+            background.to_hex()
+        );
+    }
+
+    for (sides, elements) in layers {
+        let (singular, plural) = name(sides);
+        let _ = writeln!(
+            svg,
+            r#"<g id="layer-{}" class="{}" inkscape:groupmode="layer" inkscape:label="{}" {}>"#,
+            sides,
+            plural,
+            plural,
+            attributes(canvas),
+        );
+        for e in elements {
+            polygon(
+                &mut svg,
+                &format!("shape-{}", e.id),
+                &format!("shape {}", singular),
+                e,
+            );
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+
+    if !tiles.is_empty() {
+        let _ = writeln!(
+            svg,
+            r#"<g id="tiles" class="tiles" inkscape:groupmode="layer" inkscape:label="tiles" {}>"#,
+            attributes(canvas),
+        );
+        for e in tiles {
+            polygon(&mut svg, &format!("tile-{}", e.id), "tile", e);
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+
+    if let Some((rings, width, color)) = canvas.border.as_ref() {
+        let _ = writeln!(
+            svg,
+            r#"<g id="border" class="border" transform="translate({} {}) scale({} {})">"#,
+            canvas.origin.0, canvas.origin.1, canvas.scale.0, canvas.scale.1,
+        );
+        border(&mut svg, rings, *color, *width);
+        let _ = writeln!(svg, "</g>");
+    }
+
+    if !dual.is_empty() {
+        let _ = writeln!(
+            svg,
+            r#"<g id="dual" class="dual" inkscape:groupmode="layer" inkscape:label="dual" {}>"#,
+            attributes(canvas),
+        );
+        for e in dual {
+            polygon(&mut svg, &format!("dual-{}", e.id), "cell", e);
+        }
+        let _ = writeln!(svg, "</g>");
+    }
+    let _ = writeln!(svg, "</svg>");
+
+    svg
+}
+
+/// Writes a band width wide of color around the outside of each of the
+/// polygons through rings' points to svg, as a stroke twice as wide clipped
+/// to the outside of the polygons.
+pub(crate) fn border(svg: &mut String, rings: &[Vec<Point>], color: Color, width: f64) {
+    let points = rings.iter().flatten();
+    let (mut x1, mut y1, mut x2, mut y2) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+    for p in points {
+        x1 = x1.min(p.x - 2.0 * width);
+        y1 = y1.min(p.y - 2.0 * width);
+        x2 = x2.max(p.x + 2.0 * width);
+        y2 = y2.max(p.y + 2.0 * width);
+    }
+    if x1 > x2 {
+        return;
+    }
+    let path = rings
+        .iter()
+        .filter(|r| !r.is_empty())
+        .map(|r| {
+            let points = r
+                .iter()
+                .map(|p| format!("{},{}", p.x, p.y))
+                .collect::<Vec<String>>()
+                .join(" L");
+            format!("M{} Z", points)
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+    let _ = writeln!(
+        svg,
+        r#"<clipPath id="border-clip"><path clip-rule="evenodd" d="M{x1},{y1} H{x2} V{y2} H{x1} Z {path}"/></clipPath>"#,
+        x1 = x1,
+        y1 = y1,
+        x2 = x2,
+        y2 = y2,
+        path = path,
+    );
+    let _ = writeln!(
+        svg,
+        r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round" clip-path="url(#border-clip)"/>"#,
+        path,
+        color.to_hex(),
+        2.0 * width,
+    );
+}
+
+/// Returns the attributes of a layer, which place and stroke its elements.
+fn attributes(canvas: &Canvas) -> String {
+    let cap = match canvas.line_cap {
+        LineCap::Butt => "butt",
+        LineCap::Square => "square",
+        LineCap::Round => "round",
+    };
+    let join = match canvas.line_join {
+        LineJoin::Miter => "miter",
+        LineJoin::Bevel => "bevel",
+        LineJoin::Round => "round",
+    };
+    let mut attributes = format!(
+        r#"transform="translate({} {}) scale({} {})" stroke-width="{}" stroke-linecap="{}" stroke-linejoin="{}""#,
+        canvas.origin.0,
+        canvas.origin.1,
+        canvas.scale.0,
+        canvas.scale.1,
+        canvas.line_width,
+        cap,
+        join,
+    );
+    if canvas.line_join == LineJoin::Miter {
+        let _ = write!(attributes, r#" stroke-miterlimit="{}""#, canvas.miter_limit);
+    }
+    if !canvas.dash.is_empty() {
+        let dash = canvas
+            .dash
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        let _ = write!(
+            attributes,
+            r#" stroke-dasharray="{}" stroke-dashoffset="{}""#,
+            dash, canvas.dash_offset
+        );
+    }
+
+    attributes
+}
+
+/// Writes element e to svg as a polygon with id and class, or as a path
+/// filled even-odd if it has holes.
+/// An element stroked through snapped rings is written filled only, followed
+/// by a path with id "{id}-stroke" that strokes the snapped rings.
+fn polygon(svg: &mut String, id: &str, class: &str, e: &Element) {
+    let mut attributes = e.link.as_ref().map_or(String::new(), |(name, value)| {
+        format!(r#" {}="{}""#, name, value)
+    });
+    if let Some(opacity) = e.opacity.filter(|o| *o < 1.0) {
+        let _ = write!(attributes, r#" opacity="{}""#, opacity);
+    }
+    if let Some(blend) = e.blend.filter(|b| *b != Blend::Normal) {
+        let _ = write!(attributes, r#" style="mix-blend-mode:{}""#, blend.css());
+    }
+    let (stroke, snapped) = match (e.stroke, &e.snapped) {
+        (Some(stroke), Some(rings)) => (None, Some((stroke, rings))),
+        (stroke, _) => (stroke, None),
+    };
+    write_polygon(svg, id, class, e, stroke, &attributes);
+    if let Some((stroke, rings)) = snapped {
+        let path = rings
+            .iter()
+            .map(|ring| format!("M{} Z", points(ring).join(" L")))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let _ = writeln!(
+            svg,
+            r#"<path id="{}-stroke" class="{}" d="{}" fill="none" stroke="{}"{}/>"#,
+            id,
+            class,
+            path,
+            stroke.to_hex(),
+            attributes,
+        );
+    }
+}
+
+/// Writes element e to svg as `polygon` does, stroked with stroke, if any,
+/// and with attributes.
+fn write_polygon(
+    svg: &mut String,
+    id: &str,
+    class: &str,
+    e: &Element,
+    stroke: Option<Color>,
+    attributes: &str,
+) {
+    let paint = |c: Option<Color>| c.map_or(String::from("none"), |c| c.to_hex());
+    if !e.holes.is_empty() {
+        let path = std::iter::once(&e.points)
+            .chain(e.holes.iter())
+            .map(|ring| format!("M{} Z", points(ring).join(" L")))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let _ = writeln!(
+            svg,
+            r#"<path id="{}" class="{}" d="{}" fill-rule="evenodd" fill="{}" stroke="{}"{}/>"#,
+            id,
+            class,
+            path,
+            paint(e.fill),
+            paint(stroke),
+            attributes,
+        );
+        return;
+    }
+    let _ = writeln!(
+        svg,
+        r#"<polygon id="{}" class="{}" points="{}" fill="{}" stroke="{}"{}/>"#,
+        id,
+        class,
+        points(&e.points).join(" "),
+        paint(e.fill),
+        paint(stroke),
+        attributes,
+    );
+}
+
+/// Returns the points of ring, which ends where it starts, as SVG
+/// coordinates, without the repeated last point.
+fn points(ring: &[Point]) -> Vec<String> {
+    ring[0..ring.len() - 1]
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect()
+}
+
+/// Returns the singular and plural names of a polygon with sides sides.
+pub(crate) fn name(sides: i32) -> (String, String) {
+    let singular = match sides {
+        3 => String::from("triangle"),
+        4 => String::from("square"),
+        5 => String::from("pentagon"),
+        6 => String::from("hexagon"),
+        7 => String::from("heptagon"),
+        8 => String::from("octagon"),
+        9 => String::from("nonagon"),
+        10 => String::from("decagon"),
+        12 => String::from("dodecagon"),
+        _ => format!("{}-gon", sides),
+    };
+    let plural = format!("{}s", singular);
+
+    (singular, plural)
+}
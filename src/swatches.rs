@@ -0,0 +1,156 @@
+use crate::Color;
+
+/// A named qualitative palette of colors that are easy to tell apart.
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    name: &'static str,
+    colors: &'static [Color],
+    colorblind_safe: bool,
+}
+
+/// The palette of Okabe and Ito, designed to be distinguishable with every
+/// common form of color blindness.
+pub const OKABE_ITO: Palette = Palette {
+    name: "okabe-ito",
+    colors: &[
+        Color::rgb(230, 159, 0),
+        Color::rgb(86, 180, 233),
+        Color::rgb(0, 158, 115),
+        Color::rgb(240, 228, 66),
+        Color::rgb(0, 114, 178),
+        Color::rgb(213, 94, 0),
+        Color::rgb(204, 121, 167),
+        Color::rgb(0, 0, 0),
+    ],
+    colorblind_safe: true,
+};
+
+/// ColorBrewer's Set1 palette of saturated colors.
+pub const SET1: Palette = Palette {
+    name: "set1",
+    colors: &[
+        Color::rgb(228, 26, 28),
+        Color::rgb(55, 126, 184),
+        Color::rgb(77, 175, 74),
+        Color::rgb(152, 78, 163),
+        Color::rgb(255, 127, 0),
+        Color::rgb(255, 255, 51),
+        Color::rgb(166, 86, 40),
+        Color::rgb(247, 129, 191),
+        Color::rgb(153, 153, 153),
+    ],
+    colorblind_safe: false,
+};
+
+/// ColorBrewer's Set2 palette of muted colors.
+pub const SET2: Palette = Palette {
+    name: "set2",
+    colors: &[
+        Color::rgb(102, 194, 165),
+        Color::rgb(252, 141, 98),
+        Color::rgb(141, 160, 203),
+        Color::rgb(231, 138, 195),
+        Color::rgb(166, 216, 84),
+        Color::rgb(255, 217, 47),
+        Color::rgb(229, 196, 148),
+        Color::rgb(179, 179, 179),
+    ],
+    colorblind_safe: false,
+};
+
+/// ColorBrewer's Dark2 palette of dark colors.
+pub const DARK2: Palette = Palette {
+    name: "dark2",
+    colors: &[
+        Color::rgb(27, 158, 119),
+        Color::rgb(217, 95, 2),
+        Color::rgb(117, 112, 179),
+        Color::rgb(231, 41, 138),
+        Color::rgb(102, 166, 30),
+        Color::rgb(230, 171, 2),
+        Color::rgb(166, 118, 29),
+        Color::rgb(102, 102, 102),
+    ],
+    colorblind_safe: false,
+};
+
+/// ColorBrewer's Paired palette of light and dark pairs of hues.
+pub const PAIRED: Palette = Palette {
+    name: "paired",
+    colors: &[
+        Color::rgb(166, 206, 227),
+        Color::rgb(31, 120, 180),
+        Color::rgb(178, 223, 138),
+        Color::rgb(51, 160, 44),
+        Color::rgb(251, 154, 153),
+        Color::rgb(227, 26, 28),
+        Color::rgb(253, 191, 111),
+        Color::rgb(255, 127, 0),
+        Color::rgb(202, 178, 214),
+        Color::rgb(106, 61, 154),
+        Color::rgb(255, 255, 153),
+        Color::rgb(177, 89, 40),
+    ],
+    colorblind_safe: false,
+};
+
+/// ColorBrewer's Pastel1 palette of light colors.
+pub const PASTEL1: Palette = Palette {
+    name: "pastel1",
+    colors: &[
+        Color::rgb(251, 180, 174),
+        Color::rgb(179, 205, 227),
+        Color::rgb(204, 235, 197),
+        Color::rgb(222, 203, 228),
+        Color::rgb(254, 217, 166),
+        Color::rgb(255, 255, 204),
+        Color::rgb(229, 216, 189),
+        Color::rgb(253, 218, 236),
+        Color::rgb(242, 242, 242),
+    ],
+    colorblind_safe: false,
+};
+
+/// Every built-in palette.
+const PALETTES: [Palette; 6] = [OKABE_ITO, SET1, SET2, DARK2, PAIRED, PASTEL1];
+
+impl Palette {
+    /// Returns the built-in palette named name (ignoring case), e.g.
+    /// "okabe-ito" or "set2".
+    pub fn by_name(name: &str) -> Option<Palette> {
+        PALETTES
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Returns every built-in palette.
+    pub fn all() -> &'static [Palette] {
+        &PALETTES
+    }
+
+    /// Returns the palette's name.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the palette's colors.
+    pub fn colors(&self) -> &'static [Color] {
+        self.colors
+    }
+
+    /// Returns the color with index index, cycling through the palette's colors
+    /// if index exceeds their number.
+    pub fn color(&self, index: usize) -> Color {
+        self.colors[index % self.colors.len()]
+    }
+
+    /// Returns true if the palette's colors remain distinguishable with common
+    /// forms of color blindness.
+    /// Only palettes published as safe in full are, such as Okabe and Ito's;
+    /// ColorBrewer rates its qualitative palettes safe for at most a few of
+    /// their colors, so none of them are.
+    pub fn is_colorblind_safe(&self) -> bool {
+        self.colorblind_safe
+    }
+}
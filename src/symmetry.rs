@@ -0,0 +1,409 @@
+use std::f64::consts::PI;
+
+use crate::{grid::Clusters, math, Affine, Point, Polygon, Result, Shape};
+
+/// The tolerance (in radians) to use when comparing the angles of isometries.
+const ANGLE_EPSILON: f64 = 1e-6;
+
+/// The fraction of a lattice cell beyond its sides that is indexed, so points
+/// wrapped into the cell that round across a side are still found.
+const CELL_MARGIN: f64 = 0.05;
+
+/// The translations of the lattice combined with each symmetry found to
+/// reach the other rotation centers and mirror and glide axes of its kind,
+/// along each of the lattice's two vectors.
+const LATTICE_STEPS: i32 = 2;
+
+/// An isometry of the plane: a rotation about the origin, or a reflection
+/// across a line through the origin, followed by a translation.
+#[derive(Clone, Copy, Debug)]
+struct Isometry {
+    linear: Affine,
+    translation: Point,
+}
+
+impl Isometry {
+    /// Returns the isometry that rotates points by angle (or, if reflect,
+    /// reflects them across the line at half the angle) and maps from to to.
+    fn mapping(angle: f64, reflect: bool, from: Point, to: Point) -> Isometry {
+        let (sin, cos) = math::sin_cos(angle);
+        let linear = if reflect {
+            Affine::new(cos, sin, sin, -cos)
+        } else {
+            Affine::new(cos, -sin, sin, cos)
+        };
+
+        Isometry {
+            linear,
+            translation: to - linear.apply(from),
+        }
+    }
+
+    /// Returns point moved by the isometry.
+    fn apply(&self, point: Point) -> Point {
+        self.linear.apply(point) + self.translation
+    }
+
+    /// Returns the isometry followed by the translation by offset.
+    fn shifted(&self, offset: Point) -> Isometry {
+        Isometry {
+            linear: self.linear,
+            translation: self.translation + offset,
+        }
+    }
+
+    /// Returns true if the isometry reverses orientation.
+    fn reflects(&self) -> bool {
+        let (xx, xy, yx, yy) = self.linear.components();
+
+        xx * yy - xy * yx < 0.0
+    }
+
+    /// Returns the angle (in radians, from -π to π) the isometry rotates by,
+    /// or twice the angle of the line it reflects across.
+    fn angle(&self) -> f64 {
+        let (xx, _, yx, _) = self.linear.components();
+
+        math::atan2(yx, xx)
+    }
+
+    /// Returns the order of a rotation: the number of times it turns to make
+    /// a full turn, or 1 if it only translates.
+    fn order(&self) -> i32 {
+        let angle = self.angle().abs();
+        if angle < ANGLE_EPSILON {
+            return 1;
+        }
+
+        (2.0 * PI / angle).round() as i32
+    }
+
+    /// Returns the point a rotation turns about.
+    fn center(&self) -> Point {
+        let (sin, cos) = math::sin_cos(self.angle());
+        let (a, b) = (1.0 - cos, -sin);
+        let (t, d) = (self.translation, a * a + b * b);
+
+        Point {
+            x: (t.x * a + t.y * b) / d,
+            y: (t.y * a - t.x * b) / d,
+        }
+    }
+
+    /// Returns the reflection a glide reflection makes and the distance it
+    /// then translates along the reflection's line.
+    fn glide(&self) -> (Isometry, f64) {
+        let (sin, cos) = math::sin_cos(self.angle() / 2.0);
+        let t = self.translation;
+        let glide = t.x * cos + t.y * sin;
+        let mirror = Isometry {
+            linear: self.linear,
+            translation: Point {
+                x: t.x - glide * cos,
+                y: t.y - glide * sin,
+            },
+        };
+
+        (mirror, glide)
+    }
+}
+
+/// One cell of the lattice of translations a tiling repeats along, with the
+/// centers and corners of the tiling's shapes that fall in it indexed, so
+/// any point of the tiling can be wrapped into the cell and looked up.
+struct Cell {
+    origin: Point,
+    vectors: (Point, Point),
+    shapes: Vec<(Point, Vec<Point>)>,
+    centers: Clusters,
+    sides: Vec<usize>,
+    corners: Clusters,
+    tolerance: f64,
+}
+
+impl Cell {
+    /// Returns the cell spanned by vectors and centered at center, indexing
+    /// shapes, or none if the shapes do not cover the cell.
+    /// The shapes are known to cover bounds, the box reaching the width and
+    /// height in it from center.
+    fn new(
+        shapes: &[Shape],
+        center: Point,
+        vectors: (Point, Point),
+        bounds: (f64, f64),
+        tolerance: f64,
+    ) -> Result<Option<Cell>> {
+        let origin = center - (vectors.0 + vectors.1) * 0.5;
+        let mut cell = Cell {
+            origin,
+            vectors,
+            shapes: Vec::new(),
+            centers: Clusters::new(tolerance),
+            sides: Vec::new(),
+            corners: Clusters::new(tolerance),
+            tolerance,
+        };
+        let (low, high) = (-CELL_MARGIN, 1.0 + CELL_MARGIN);
+        for (s, t) in [(low, low), (low, high), (high, low), (high, high)] {
+            let p = cell.at(s, t);
+            if (p.x - center.x).abs() > bounds.0 || (p.y - center.y).abs() > bounds.1 {
+                return Ok(None);
+            }
+        }
+
+        let inside = |(s, t): (f64, f64)| s >= low && s <= high && t >= low && t <= high;
+        for shape in shapes.iter() {
+            let (point, mut points) = (shape.point(), shape.points(0.0)?);
+            points.pop();
+            if inside(cell.coordinates(point)) && cell.centers.insert(point) == cell.sides.len() {
+                cell.sides.push(points.len());
+            }
+            for p in points.iter() {
+                if inside(cell.coordinates(*p)) {
+                    cell.corners.insert(*p);
+                }
+            }
+            let (s, t) = cell.coordinates(point);
+            if (0.0..1.0).contains(&s) && (0.0..1.0).contains(&t) {
+                cell.shapes.push((point, points));
+            }
+        }
+
+        Ok(Some(cell))
+    }
+
+    /// Returns the point at coordinates (s, t) along the cell's vectors.
+    fn at(&self, s: f64, t: f64) -> Point {
+        self.origin + self.vectors.0 * s + self.vectors.1 * t
+    }
+
+    /// Returns the coordinates of point along the cell's vectors.
+    fn coordinates(&self, point: Point) -> (f64, f64) {
+        let (u, v, p) = (self.vectors.0, self.vectors.1, point - self.origin);
+        let det = cross(u, v);
+
+        (cross(p, v) / det, cross(u, p) / det)
+    }
+
+    /// Returns point translated along the lattice into the cell.
+    fn wrap(&self, point: Point) -> Point {
+        let (s, t) = self.coordinates(point);
+
+        self.at(s - s.floor(), t - t.floor())
+    }
+
+    /// Returns true if vector is a translation of the lattice.
+    fn in_lattice(&self, vector: Point) -> bool {
+        let (u, v) = self.vectors;
+        let det = cross(u, v);
+        let (s, t) = (cross(vector, v) / det, cross(u, vector) / det);
+
+        (vector - u * s.round() - v * t.round()).distance(Point::origin()) <= self.tolerance
+    }
+
+    /// Returns true if isometry maps every shape of the tiling onto a shape
+    /// with as many sides.
+    /// The isometry must map the lattice onto itself for the shapes of the
+    /// cell to stand for their copies in every other.
+    fn is_symmetry(&self, isometry: &Isometry) -> bool {
+        let (u, v) = self.vectors;
+        if !self.in_lattice(isometry.linear.apply(u)) || !self.in_lattice(isometry.linear.apply(v))
+        {
+            return false;
+        }
+
+        self.shapes.iter().all(|(center, corners)| {
+            let mapped = self.centers.find(self.wrap(isometry.apply(*center)));
+            mapped.map(|i| self.sides[i]) == Some(corners.len())
+                && corners.iter().all(|p| {
+                    let p = self.wrap(isometry.apply(*p));
+                    self.corners.find(p).is_some()
+                })
+        })
+    }
+
+    /// Returns the symmetries of the tiling that map a shape with the rarest
+    /// number of sides in the cell onto each shape with as many sides in it,
+    /// which, with the lattice's translations, make up all of them.
+    fn symmetries(&self) -> Vec<Isometry> {
+        let count = |n: usize| self.shapes.iter().filter(|(_, c)| c.len() == n).count();
+        let (center, corners) = match self.shapes.iter().min_by_key(|(_, c)| count(c.len())) {
+            Some(shape) => shape,
+            None => return Vec::new(),
+        };
+        let angle = |c: Point, p: Point| math::atan2(p.y - c.y, p.x - c.x);
+        let first = angle(*center, corners[0]);
+
+        let mut symmetries = Vec::new();
+        for (c, points) in self.shapes.iter().filter(|(_, c)| c.len() == corners.len()) {
+            for p in points.iter() {
+                let to = angle(*c, *p);
+                for (angle, reflect) in [(to - first, false), (to + first, true)] {
+                    let isometry = Isometry::mapping(angle, reflect, *center, *c);
+                    if self.is_symmetry(&isometry) {
+                        symmetries.push(isometry);
+                    }
+                }
+            }
+        }
+
+        symmetries
+    }
+
+    /// Returns the translations of the lattice within `LATTICE_STEPS` steps
+    /// along each of its vectors.
+    fn steps(&self) -> Vec<Point> {
+        let steps = -LATTICE_STEPS..=LATTICE_STEPS;
+        steps
+            .clone()
+            .flat_map(|i| steps.clone().map(move |j| (i, j)))
+            .map(|(i, j)| self.vectors.0 * i as f64 + self.vectors.1 * j as f64)
+            .collect()
+    }
+}
+
+/// Returns the orbifold notation (e.g. `"*632"`) of the symmetry group of the
+/// tiling by shapes that repeats along the translations generators, found by
+/// testing which isometries map the tiling onto itself.
+/// The shapes are known to cover the box reaching bounds from center.
+/// Returns none if the generators do not span the plane or the shapes do not
+/// cover a cell of their lattice, since the symmetries cannot then be told.
+pub(crate) fn orbifold(
+    shapes: &[Shape],
+    generators: &[Point],
+    center: Point,
+    bounds: (f64, f64),
+    tolerance: f64,
+) -> Result<Option<&'static str>> {
+    let vectors = match lattice(generators, tolerance) {
+        Some(vectors) => vectors,
+        None => return Ok(None),
+    };
+    let cell = match Cell::new(shapes, center, vectors, bounds, tolerance)? {
+        Some(cell) => cell,
+        None => return Ok(None),
+    };
+    // The generators may only span some of the tiling's translations, which
+    // are found first, so the lattice is that of all of them.
+    let translations = cell
+        .symmetries()
+        .into_iter()
+        .filter(|s| !s.reflects() && s.order() == 1)
+        .flat_map(|s| cell.steps().into_iter().map(move |t| s.translation + t))
+        .collect::<Vec<Point>>();
+    let vectors = match lattice(&translations, tolerance) {
+        Some(vectors) => vectors,
+        None => return Ok(None),
+    };
+    let cell = match Cell::new(shapes, center, vectors, bounds, tolerance)? {
+        Some(cell) => cell,
+        None => return Ok(None),
+    };
+    let symmetries = cell.symmetries();
+    if symmetries.is_empty() {
+        return Ok(None);
+    }
+
+    // Each symmetry found stands for those it makes with the lattice's
+    // translations, whose rotation centers and axes lie elsewhere.
+    let steps = cell.steps();
+    let all = symmetries
+        .iter()
+        .flat_map(|s| steps.iter().map(move |t| s.shifted(*t)))
+        .collect::<Vec<Isometry>>();
+    let order = all
+        .iter()
+        .filter(|s| !s.reflects())
+        .map(|s| s.order())
+        .max();
+    let order = order.unwrap_or(1);
+    let (mirrors, glides): (Vec<&Isometry>, Vec<&Isometry>) = all
+        .iter()
+        .filter(|s| s.reflects())
+        .partition(|s| s.glide().1.abs() <= tolerance);
+
+    let mut directions: Vec<f64> = Vec::new();
+    for m in mirrors.iter() {
+        let angle = m.angle() / 2.0;
+        let turned = |d: &f64| {
+            let difference = (angle - d).rem_euclid(PI);
+            difference.min(PI - difference) < ANGLE_EPSILON
+        };
+        if !directions.iter().any(turned) {
+            directions.push(angle);
+        }
+    }
+    // Every rotation center of the highest order lies on a mirror.
+    let centered = all
+        .iter()
+        .filter(|s| !s.reflects() && s.order() == order)
+        .all(|s| {
+            let c = s.center();
+            directions
+                .iter()
+                .any(|d| cell.is_symmetry(&Isometry::mapping(2.0 * d, true, c, c)))
+        });
+    // Some glide reflection's line is not a mirror.
+    let offset = glides.iter().any(|g| !cell.is_symmetry(&g.glide().0));
+
+    let notation = match (order, !mirrors.is_empty()) {
+        (6, true) => "*632",
+        (6, false) => "632",
+        (4, true) if centered => "*442",
+        (4, true) => "4*2",
+        (4, false) => "442",
+        (3, true) if centered => "*333",
+        (3, true) => "3*3",
+        (3, false) => "333",
+        (2, true) if directions.len() > 1 && centered => "*2222",
+        (2, true) if directions.len() > 1 => "2*22",
+        (2, true) => "22*",
+        (2, false) if !glides.is_empty() => "22×",
+        (2, false) => "2222",
+        (_, true) if offset => "*×",
+        (_, true) => "**",
+        (_, false) if !glides.is_empty() => "××",
+        _ => "o",
+    };
+
+    Ok(Some(notation))
+}
+
+/// Returns a reduced basis of the lattice of translations generators span: two
+/// shortest vectors of it that are nearly perpendicular, or none if the
+/// generators do not span the plane.
+fn lattice(generators: &[Point], tolerance: f64) -> Option<(Point, Point)> {
+    let length = |p: &Point| p.distance(Point::origin());
+    let mut sorted = generators
+        .iter()
+        .copied()
+        .filter(|g| length(g) > tolerance)
+        .collect::<Vec<Point>>();
+    sorted.sort_by(|a, b| length(a).total_cmp(&length(b)));
+    let u = *sorted.first()?;
+    let v = sorted
+        .iter()
+        .copied()
+        .find(|g| cross(u, *g).abs() > tolerance * length(&u) * length(g))?;
+
+    // Lagrange's reduction: shorten the longer vector by the shorter until
+    // it is no longer shorter.
+    let (mut u, mut v) = (u, v);
+    loop {
+        v = v - u * (dot(u, v) / dot(u, u)).round();
+        if length(&v) >= length(&u) - tolerance {
+            return Some((u, v));
+        }
+        std::mem::swap(&mut u, &mut v);
+    }
+}
+
+/// Returns the dot product of a and b.
+fn dot(a: Point, b: Point) -> f64 {
+    a.x * b.x + a.y * b.y
+}
+
+/// Returns the cross product of a and b.
+fn cross(a: Point, b: Point) -> f64 {
+    a.x * b.y - a.y * b.x
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use crate::{shape::Styling, Point, Shape};
+
+/// A shape added to a model, stored as its geometry and the index of its
+/// styling in the table's stylings.
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    sides: i32,
+    point: Point,
+    rotation: f64,
+    styling: usize,
+}
+
+/// The shapes added to a model, stored compactly: each styling is interned
+/// once, and shapes refer to theirs by index, so a model of many shapes
+/// styled a few ways stores a few stylings.
+/// Shapes are built from their entries as they are read.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ShapeTable {
+    entries: Vec<Entry>,
+    stylings: Vec<Styling>,
+    index: HashMap<u64, Vec<usize>>,
+}
+
+impl ShapeTable {
+    /// Returns the index of styling, interning it if the table has no
+    /// styling equal to it.
+    pub(crate) fn intern(&mut self, styling: Styling) -> usize {
+        let hash = styling.hash();
+        let stylings = &self.stylings;
+        let candidates = self.index.entry(hash).or_default();
+        if let Some(i) = candidates.iter().find(|i| stylings[**i] == styling) {
+            return *i;
+        }
+        candidates.push(self.stylings.len());
+        self.stylings.push(styling);
+
+        self.stylings.len() - 1
+    }
+
+    /// Returns the styling with index index, if there is one.
+    pub(crate) fn styling(&self, index: usize) -> Option<&Styling> {
+        self.stylings.get(index)
+    }
+
+    /// Returns the interned stylings, in the order of their indexes.
+    pub(crate) fn stylings(&self) -> &[Styling] {
+        &self.stylings
+    }
+
+    /// Returns the number of shapes in the table.
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the table has no shapes.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the shape with index index, if there is one.
+    pub(crate) fn get(&self, index: usize) -> Option<Shape> {
+        self.entries.get(index).map(|e| self.build(e))
+    }
+
+    /// Returns the shape with index index, panicking if there is none.
+    pub(crate) fn at(&self, index: usize) -> Shape {
+        self.build(&self.entries[index])
+    }
+
+    /// Returns the first shape, if there is one.
+    pub(crate) fn first(&self) -> Option<Shape> {
+        self.get(0)
+    }
+
+    /// Returns the shapes in the order of their indexes.
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = Shape> + ExactSizeIterator + '_ {
+        self.entries.iter().map(move |e| self.build(e))
+    }
+
+    /// Returns the number of sides of the shape with index index.
+    pub(crate) fn sides(&self, index: usize) -> i32 {
+        self.entries[index].sides
+    }
+
+    /// Returns the center of the shape with index index.
+    pub(crate) fn point(&self, index: usize) -> Point {
+        self.entries[index].point
+    }
+
+    /// Returns the shape with index index centered at point and turned to
+    /// rotation.
+    pub(crate) fn placed_at(&self, index: usize, point: Point, rotation: f64) -> Shape {
+        let e = &self.entries[index];
+
+        Shape::styled(e.sides, point, rotation, &self.stylings[e.styling])
+    }
+
+    /// Adds shape to the end of the table.
+    pub(crate) fn push(&mut self, shape: &Shape) {
+        let entry = self.entry(shape);
+        self.entries.push(entry);
+    }
+
+    /// Replaces the shape with index index by shape, returning the shape it
+    /// replaced.
+    pub(crate) fn set(&mut self, index: usize, shape: &Shape) -> Shape {
+        let entry = self.entry(shape);
+        let replaced = std::mem::replace(&mut self.entries[index], entry);
+
+        self.build(&replaced)
+    }
+
+    /// Replaces each shape by what f returns for it.
+    pub(crate) fn update<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Shape) -> Shape,
+    {
+        for i in 0..self.entries.len() {
+            let shape = f(self.at(i));
+            self.set(i, &shape);
+        }
+    }
+
+    /// Removes the shapes with indexes of len or more, keeping their
+    /// stylings, which other shapes and `StyleId`s may still refer to.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.entries.truncate(len);
+    }
+
+    /// Returns the entry that stores shape, interning its styling.
+    fn entry(&mut self, shape: &Shape) -> Entry {
+        Entry {
+            sides: shape.sides(),
+            point: shape.point(),
+            rotation: shape.rotation(),
+            styling: self.intern(shape.styling()),
+        }
+    }
+
+    /// Returns the shape entry stores.
+    fn build(&self, entry: &Entry) -> Shape {
+        Shape::styled(
+            entry.sides,
+            entry.point,
+            entry.rotation,
+            &self.stylings[entry.styling],
+        )
+    }
+}
@@ -0,0 +1,288 @@
+//! Utilities for writing regression tests of tilings.
+//!
+//! A `Fixture` captures the geometry of a model so it can be stored alongside
+//! tests and compared (with a tolerance) against the geometry a model produces
+//! later. `pixel_diff` compares two renders pixel by pixel.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+use crate::{Error::*, Model, Point, Result};
+
+#[cfg(feature = "cairo")]
+use crate::Render;
+
+/// The stored geometry of a tiling: the vertices of each shape.
+#[derive(Clone, Debug, Default)]
+pub struct Fixture {
+    shapes: Vec<Vec<Point>>,
+}
+
+impl Fixture {
+    /// Returns the geometry of model's (repeated) tiling.
+    pub fn from_model(model: &Model) -> Result<Fixture> {
+        let mesh = model.to_mesh()?;
+        let shapes = mesh
+            .faces()
+            .iter()
+            .map(|f| f.iter().map(|v| mesh.vertices()[*v]).collect())
+            .collect();
+
+        Ok(Fixture { shapes })
+    }
+
+    /// Reads a fixture from a file at path.
+    /// Each line holds the vertices of one shape as space separated `x,y` pairs.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Fixture> {
+        let mut shapes = Vec::new();
+        for (i, line) in BufReader::new(File::open(path)?).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let points = line
+                .split_whitespace()
+                .map(|p| {
+                    let mut xy = p.split(',').map(|n| n.parse::<f64>().ok());
+                    match (xy.next().flatten(), xy.next().flatten(), xy.next()) {
+                        (Some(x), Some(y), None) => Some(Point { x, y }),
+                        _ => None,
+                    }
+                })
+                .collect::<Option<Vec<Point>>>()
+                .ok_or(InvalidFixture { line: i + 1 })?;
+
+            shapes.push(points);
+        }
+
+        Ok(Fixture { shapes })
+    }
+
+    /// Writes the fixture to a file at path.
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut file = File::create(path)?;
+        for points in self.shapes.iter() {
+            let line = points
+                .iter()
+                .map(|p| format!("{},{}", p.x, p.y))
+                .collect::<Vec<String>>()
+                .join(" ");
+            writeln!(file, "{}", line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the vertices of each shape.
+    pub fn shapes(&self) -> &[Vec<Point>] {
+        &self.shapes
+    }
+
+    /// Succeeds if every vertex of the fixture is within tolerance of the
+    /// corresponding vertex of expected, and fails with `Error::Mismatch`
+    /// describing the first difference otherwise.
+    pub fn compare(&self, expected: &Fixture, tolerance: f64) -> Result<()> {
+        if self.shapes.len() != expected.shapes.len() {
+            return Err(Mismatch {
+                reason: format!(
+                    "found {} shapes, expected {}",
+                    self.shapes.len(),
+                    expected.shapes.len()
+                ),
+            });
+        }
+
+        for (i, (a, e)) in self.shapes.iter().zip(expected.shapes.iter()).enumerate() {
+            if a.len() != e.len() {
+                return Err(Mismatch {
+                    reason: format!("shape {} has {} vertices, expected {}", i, a.len(), e.len()),
+                });
+            }
+
+            for (j, (p, q)) in a.iter().zip(e.iter()).enumerate() {
+                if (p.x - q.x).abs() > tolerance || (p.y - q.y).abs() > tolerance {
+                    return Err(Mismatch {
+                        reason: format!(
+                            "vertex {} of shape {} is ({}, {}), expected ({}, {})",
+                            j, i, p.x, p.y, q.x, q.y
+                        ),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The result of comparing two renders pixel by pixel.
+#[derive(Clone, Copy, Debug)]
+pub struct PixelDiff {
+    differing: usize,
+    total: usize,
+    max_delta: u8,
+}
+
+impl PixelDiff {
+    /// Returns the number of pixels that differ.
+    pub fn differing(&self) -> usize {
+        self.differing
+    }
+
+    /// Returns the total number of pixels compared.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the largest difference of any color component of any pixel.
+    pub fn max_delta(&self) -> u8 {
+        self.max_delta
+    }
+
+    /// Returns true if no pixels differ.
+    pub fn is_identical(&self) -> bool {
+        self.differing == 0
+    }
+}
+
+/// Compares the color of each pixel of actual and expected, counting pixels
+/// that differ by more than tolerance in any color component.
+/// Fails with `Error::Mismatch` if the renders differ in size.
+#[cfg(feature = "cairo")]
+pub fn pixel_diff(actual: &Render, expected: &Render, tolerance: u8) -> Result<PixelDiff> {
+    if actual.width() != expected.width() || actual.height() != expected.height() {
+        return Err(Mismatch {
+            reason: format!(
+                "render is {}x{}, expected {}x{}",
+                actual.width(),
+                actual.height(),
+                expected.width(),
+                expected.height()
+            ),
+        });
+    }
+
+    let mut diff = PixelDiff {
+        differing: 0,
+        total: 0,
+        max_delta: 0,
+    };
+    for (a, e) in actual.rgb()?.iter().zip(expected.rgb()?.iter()) {
+        let delta = (0..3)
+            .map(|c| a[c].max(e[c]) - a[c].min(e[c]))
+            .max()
+            .unwrap_or(0);
+        diff.total += 1;
+        diff.max_delta = diff.max_delta.max(delta);
+        if delta > tolerance {
+            diff.differing += 1;
+        }
+    }
+
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+    use crate::{Color, Shape};
+
+    #[cfg(feature = "cairo")]
+    use crate::RenderOptions;
+
+    /// Returns a model of a hexagon ringed by squares.
+    fn model() -> Result<Model> {
+        let (fill, stroke) = (Color::new(23, 216, 146)?, Color::new(242, 60, 60)?);
+        let mut model = Model::new(128, 128, 16.0);
+        model.add(Shape::new(6, fill, stroke)?)?;
+        model.add_multi(0..1, 0..6, Shape::new(4, fill, stroke)?)?;
+
+        Ok(model)
+    }
+
+    /// Returns a path in the temporary directory unique to this process.
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        env::temp_dir().join(format!("tiling-{}-{}", process::id(), name))
+    }
+
+    #[test]
+    fn fixture_round_trips_through_a_file() -> Result<()> {
+        let fixture = Fixture::from_model(&model()?)?;
+        assert_eq!(fixture.shapes().len(), 7);
+
+        let path = temp_path("fixture.txt");
+        fixture.write(&path)?;
+        let read = Fixture::read(&path);
+        fs::remove_file(&path)?;
+
+        read?.compare(&fixture, 0.0)
+    }
+
+    #[test]
+    fn compare_reports_differences_beyond_tolerance() -> Result<()> {
+        let fixture = Fixture::from_model(&model()?)?;
+        let mut moved = fixture.clone();
+        moved.shapes[3][1].x += 0.01;
+
+        moved.compare(&fixture, 0.1)?;
+        assert!(matches!(
+            moved.compare(&fixture, 0.001),
+            Err(Mismatch { reason }) if reason.starts_with("vertex 1 of shape 3")
+        ));
+
+        let mut fewer = fixture.clone();
+        fewer.shapes.pop();
+        assert!(matches!(fewer.compare(&fixture, 0.1), Err(Mismatch { .. })));
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_rejects_malformed_lines() -> Result<()> {
+        let path = temp_path("malformed.txt");
+        fs::write(&path, "0,0 1,0 1,1\n\n0,0 1,x\n")?;
+        let read = Fixture::read(&path);
+        fs::remove_file(&path)?;
+
+        assert!(matches!(read, Err(InvalidFixture { line: 3 })));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "cairo")]
+    #[test]
+    fn pixel_diff_counts_differing_pixels() -> Result<()> {
+        let model = model()?;
+        let white = Color::new(255, 255, 255)?;
+        let black = Color::new(0, 0, 0)?;
+        let options = |background| {
+            RenderOptions::new()
+                .background(background)
+                .margin(0.1)
+                .line_width(0.1)
+        };
+        let a = model.render_with(&options(white))?;
+        let b = model.render_with(&options(white))?;
+        let c = model.render_with(&options(black))?;
+
+        let same = pixel_diff(&a, &b, 0)?;
+        assert!(same.is_identical());
+        assert_eq!(same.total(), 128 * 128);
+
+        let diff = pixel_diff(&a, &c, 0)?;
+        assert!(diff.differing() > 0 && diff.differing() < diff.total());
+        assert_eq!(diff.max_delta(), 255);
+        assert_eq!(pixel_diff(&a, &c, 255)?.differing(), 0);
+
+        let small = Model::new(64, 64, 16.0).render_with(&options(white))?;
+        assert!(matches!(pixel_diff(&a, &small, 0), Err(Mismatch { .. })));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+use std::borrow::Cow;
+
+use crate::{Color, Error::*, Palette, Result};
+
+/// The look of a tiling: a stroke shared by every shape, a background, and
+/// fills that shapes are colored with by their number of sides.
+/// Build shapes with `Shape::themed` and restyle a whole model with
+/// `Model::apply_theme`, so switching the look of a tiling takes one line.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    stroke: Color,
+    background: Option<Color>,
+    fills: Cow<'static, [Color]>,
+}
+
+impl Theme {
+    /// Returns a theme of stroke, background (or a transparent background if
+    /// it is `None`), and fills, or an error if fills is empty.
+    pub fn new(
+        stroke: Color,
+        background: impl Into<Option<Color>>,
+        fills: &[Color],
+    ) -> Result<Theme> {
+        if fills.is_empty() {
+            return Err(InvalidColor);
+        }
+
+        Ok(Theme {
+            stroke,
+            background: background.into(),
+            fills: Cow::Owned(fills.to_vec()),
+        })
+    }
+
+    /// Returns a theme of stroke and background filled from palette, whose
+    /// colors it borrows rather than copies.
+    pub fn from_palette(
+        palette: &Palette,
+        stroke: Color,
+        background: impl Into<Option<Color>>,
+    ) -> Theme {
+        Theme {
+            stroke,
+            background: background.into(),
+            fills: Cow::Borrowed(palette.colors()),
+        }
+    }
+
+    /// Returns the stroke of every shape.
+    pub fn stroke(&self) -> Color {
+        self.stroke
+    }
+
+    /// Returns the background, or none if it is transparent.
+    pub fn background(&self) -> Option<Color> {
+        self.background
+    }
+
+    /// Returns the fills, in order.
+    pub fn fills(&self) -> &[Color] {
+        &self.fills
+    }
+
+    /// Returns the fill of shapes with sides sides: the fill with index sides,
+    /// cycling through the fills if sides exceeds their number, as
+    /// `ModelBuilder` fills shapes from its palette.
+    pub fn fill(&self, sides: i32) -> Color {
+        self.fills[sides.max(0) as usize % self.fills.len()]
+    }
+}
+
+/// The default theme colors shapes as `ModelBuilder` does, with black strokes
+/// and fills from `swatches::SET2`, on a white background.
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::from_palette(
+            &crate::swatches::SET2,
+            Color::rgb(0, 0, 0),
+            Color::rgb(255, 255, 255),
+        )
+    }
+}
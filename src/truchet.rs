@@ -0,0 +1,208 @@
+#[cfg(feature = "cairo")]
+use std::{f64::consts::PI, sync::Arc};
+
+use rand::Rng;
+
+use crate::{hash, Color, Model, Result, Shape, SquareGrid, TriangleGrid};
+
+#[cfg(feature = "cairo")]
+use crate::{Motif, Point, Render, RenderOptions};
+
+/// The default width of a Truchet tiling's lines, in edge lengths.
+const LINE_WIDTH: f64 = 0.1;
+
+/// The lattice of tiles a Truchet tiling fills.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TruchetLattice {
+    /// Squares, each in one of 2 orientations.
+    Square,
+    /// Triangles, each in one of 3 orientations.
+    Triangle,
+}
+
+/// The motif drawn on each tile of a Truchet tiling.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TruchetStyle {
+    /// Arcs around corners joining the midpoints of the corners' edges (the
+    /// default): around opposite corners of a square, or around one corner of
+    /// a triangle.
+    #[default]
+    Arcs,
+    /// Straight lines: a diagonal of a square, or the line from a corner of a
+    /// triangle to the midpoint of the opposite edge.
+    Diagonals,
+}
+
+/// A Truchet tiling: a lattice of tiles decorated with a motif, each tile
+/// turned to one of the motif's orientations at random.
+/// The same seed always orients the same tiles the same way, however large
+/// the canvas.
+#[derive(Clone, Debug)]
+pub struct Truchet {
+    model: Model,
+    style: TruchetStyle,
+    seed: u64,
+    #[cfg_attr(not(feature = "cairo"), allow(dead_code))]
+    color: Color,
+    line_width: f64,
+}
+
+impl Truchet {
+    /// Returns a Truchet tiling of lattice covering a width by height canvas
+    /// with scale pixels per edge, its tiles filled with fill and its lines
+    /// drawn in color, oriented at random by seed.
+    pub fn new(
+        lattice: TruchetLattice,
+        width: i32,
+        height: i32,
+        scale: f64,
+        seed: u64,
+        fill: Color,
+        color: Color,
+    ) -> Result<Truchet> {
+        // The tiles are stroked in their fill so the lattice is not drawn.
+        let model = match lattice {
+            TruchetLattice::Square => SquareGrid::new(width, height, scale, fill, fill)?
+                .model()
+                .clone(),
+            TruchetLattice::Triangle => TriangleGrid::new(width, height, scale, fill, fill)?
+                .model()
+                .clone(),
+        };
+
+        Ok(Truchet {
+            model,
+            style: TruchetStyle::default(),
+            seed,
+            color,
+            line_width: LINE_WIDTH,
+        })
+    }
+
+    /// Returns the tiling with its tiles decorated in style.
+    pub fn with_style(mut self, style: TruchetStyle) -> Truchet {
+        self.style = style;
+
+        self
+    }
+
+    /// Returns the tiling with lines line_width wide (in edge lengths).
+    pub fn with_line_width(mut self, line_width: f64) -> Truchet {
+        self.line_width = line_width;
+
+        self
+    }
+
+    /// Returns the orientation of the tile shape, between 0 and the number of
+    /// orientations of its lattice.
+    pub fn orientation(&self, shape: &Shape) -> usize {
+        orientation(self.seed, shape)
+    }
+
+    /// Returns the tiling's model.
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Returns the tiling's model, which may be rendered or further styled.
+    pub fn model_mut(&mut self) -> &mut Model {
+        &mut self.model
+    }
+
+    /// Renders the tiling with options, drawing each tile's motif on top of
+    /// any motifs the options already draw.
+    #[cfg(feature = "cairo")]
+    pub fn render(&self, options: &RenderOptions) -> Result<Render> {
+        let (style, seed, color) = (self.style, self.seed, self.color);
+        let (line_width, margin) = (self.line_width, options.margin);
+        let motif = Motif::Custom(Arc::new(move |context: &cairo::Context, s: &Shape| {
+            // Motifs are drawn in units of the tile's inradius.
+            let unit = s.inradius() - s.margin().unwrap_or(margin);
+            let (r, g, b) = color.rgb_unit_int();
+            context.set_source_rgb(r, g, b);
+            context.set_line_width(line_width / unit);
+            draw(context, s.sides(), orientation(seed, s), style)
+        }));
+
+        self.model.render_with(&options.clone().tile_motif(motif))
+    }
+}
+
+/// Returns the orientation chosen by seed for the tile shape.
+fn orientation(seed: u64, shape: &Shape) -> usize {
+    let mut rng = hash::rng_at(seed, shape.point());
+
+    rng.gen_range(0..orientations(shape.sides()))
+}
+
+/// Returns the number of orientations of the motif on a tile with sides sides:
+/// one per pair of opposite corners if sides is even and one per corner if it
+/// is odd.
+fn orientations(sides: i32) -> usize {
+    if sides % 2 == 0 {
+        sides as usize / 2
+    } else {
+        sides as usize
+    }
+}
+
+/// Draws the motif in style on a tile with sides sides in orientation, in the
+/// tile's frame (see `Motif`).
+#[cfg(feature = "cairo")]
+fn draw(
+    context: &cairo::Context,
+    sides: i32,
+    orientation: usize,
+    style: TruchetStyle,
+) -> Result<()> {
+    let n = sides as usize;
+    let vertex = |i: usize| {
+        let a = (i % n) as f64 * 2.0 * PI / n as f64 - PI / 2.0;
+        let d = 1.0 / (PI / n as f64).cos();
+        Point {
+            x: a.cos() * d,
+            y: a.sin() * d,
+        }
+    };
+    // Arcs on tiles with an even number of sides go around opposite corners,
+    // so that every edge's midpoint is joined to another.
+    let corners = if style == TruchetStyle::Arcs && n % 2 == 0 {
+        vec![orientation, orientation + n / 2]
+    } else {
+        vec![orientation]
+    };
+
+    context.new_path();
+    for c in corners {
+        let v = vertex(c);
+        match style {
+            TruchetStyle::Arcs => {
+                // A quarter (or sixth) of a circle around the corner, whose
+                // radius is half an edge.
+                let (prev, next) = (vertex(c + n - 1), vertex(c + 1));
+                let from = (next.y - v.y).atan2(next.x - v.x);
+                let to = (prev.y - v.y).atan2(prev.x - v.x);
+                let (from, to) = if (to - from).rem_euclid(2.0 * PI) <= PI {
+                    (from, to)
+                } else {
+                    (to, from)
+                };
+                context.new_sub_path();
+                context.arc(v.x, v.y, (PI / n as f64).tan(), from, to);
+            }
+            TruchetStyle::Diagonals if n % 2 == 0 => {
+                let w = vertex(c + n / 2);
+                context.move_to(v.x, v.y);
+                context.line_to(w.x, w.y);
+            }
+            TruchetStyle::Diagonals => {
+                let w = vertex(c + n / 2).midpoint(vertex(c + n / 2 + 1));
+                context.move_to(v.x, v.y);
+                context.line_to(w.x, w.y);
+            }
+        }
+    }
+    context.stroke()?;
+
+    Ok(())
+}
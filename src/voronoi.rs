@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use crate::{Point, Rect};
+
+/// The side (in model units) of the square buckets sites are sorted into, so
+/// a cell is only clipped by the sites near it.
+const BUCKET_SIZE: f64 = 1.0;
+
+/// Returns the closed polygon of each site's Voronoi cell, in the order of
+/// sites: the points of bounds nearer to the site than to any other site.
+pub(crate) fn cells(sites: &[Point], bounds: Rect) -> Vec<Vec<Point>> {
+    let bucket = |p: Point| {
+        (
+            (p.x / BUCKET_SIZE).floor() as i64,
+            (p.y / BUCKET_SIZE).floor() as i64,
+        )
+    };
+    let mut buckets: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    for (i, p) in sites.iter().enumerate() {
+        buckets.entry(bucket(*p)).or_default().push(i);
+    }
+
+    let (x, y) = (bounds.origin().x, bounds.origin().y);
+    let (w, h) = (bounds.width(), bounds.height());
+    let corners = vec![
+        Point { x, y },
+        Point { x: x + w, y },
+        Point { x: x + w, y: y + h },
+        Point { x, y: y + h },
+    ];
+    // Buckets are searched ring by ring around the site's own; every site
+    // outside the rings searched is at least as far as the rings reach, and
+    // sites more than twice as far as the cell's farthest vertex cannot clip
+    // it.
+    let rings = (w.hypot(h) / BUCKET_SIZE).ceil() as i64 + 1;
+
+    sites
+        .iter()
+        .enumerate()
+        .map(|(i, site)| {
+            let (bx, by) = bucket(*site);
+            let mut cell = corners.clone();
+            for ring in 0..=rings {
+                let reach = cell.iter().map(|p| p.distance(*site)).fold(0.0, f64::max);
+                if (ring - 1) as f64 * BUCKET_SIZE > 2.0 * reach {
+                    break;
+                }
+
+                for dx in -ring..=ring {
+                    for dy in -ring..=ring {
+                        if dx.abs() != ring && dy.abs() != ring {
+                            continue;
+                        }
+                        for j in buckets.get(&(bx + dx, by + dy)).into_iter().flatten() {
+                            if *j != i {
+                                cell = clip(&cell, *site, sites[*j]);
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(first) = cell.first().copied() {
+                cell.push(first);
+            }
+
+            cell
+        })
+        .collect()
+}
+
+/// Returns the convex polygon with vertices polygon clipped to the points
+/// nearer to site than to other.
+fn clip(polygon: &[Point], site: Point, other: Point) -> Vec<Point> {
+    let middle = site.midpoint(other);
+    let (nx, ny) = (other.x - site.x, other.y - site.y);
+    let side = |p: Point| (p.x - middle.x) * nx + (p.y - middle.y) * ny;
+
+    let mut clipped = Vec::new();
+    for (k, p) in polygon.iter().enumerate() {
+        let q = polygon[(k + 1) % polygon.len()];
+        let (sp, sq) = (side(*p), side(q));
+        if sp <= 0.0 {
+            clipped.push(*p);
+        }
+        if (sp < 0.0 && sq > 0.0) || (sp > 0.0 && sq < 0.0) {
+            let t = sp / (sp - sq);
+            clipped.push(*p + (q - *p) * t);
+        }
+    }
+
+    clipped
+}